@@ -1,3 +1,19 @@
 fn main() {
+  // Expose the git revision and cargo profile to `get_app_info`
+  let git_hash = std::process::Command::new("git")
+    .args(["rev-parse", "--short", "HEAD"])
+    .output()
+    .ok()
+    .filter(|output| output.status.success())
+    .and_then(|output| String::from_utf8(output.stdout).ok())
+    .map(|hash| hash.trim().to_string())
+    .unwrap_or_else(|| "unknown".to_string());
+  println!("cargo:rustc-env=GIT_HASH={}", git_hash);
+  println!(
+    "cargo:rustc-env=BUILD_PROFILE={}",
+    std::env::var("PROFILE").unwrap_or_else(|_| "unknown".to_string())
+  );
+  println!("cargo:rerun-if-changed=../.git/HEAD");
+
   tauri_build::build()
 }
@@ -0,0 +1,315 @@
+// ============================================================================
+// API ERRORS
+// ============================================================================
+// Failure responses from commands carry, next to the human-readable message,
+// an `error: { code, message }` whose code the frontend can branch on
+// ============================================================================
+
+#[cfg(feature = "aws-sdk")]
+use crate::aws::AwsError;
+use crate::azure::AzureError;
+use crate::database::{DbError, InstanceRequestError};
+use crate::gcp::GcpError;
+
+/// Category of a failed command. The `code` of each variant is part of the
+/// frontend contract and must not change.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    InvalidInput(String),
+    /// Missing, invalid or expired cloud credentials
+    #[error("{0}")]
+    AwsAuth(String),
+    /// Valid credentials without the IAM permission an operation needs
+    #[error("{0}")]
+    AwsPermission(String),
+    /// The account hasn't enabled an AWS service the operation relies on
+    #[error("{0}")]
+    OptInRequired(String),
+    /// The provider couldn't be reached, timed out or throttled the request
+    #[error("{0}")]
+    Network(String),
+    #[error("{0}")]
+    Database(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+/// Message fragments, lowercased, that identify a category when all that's
+/// left of an error is its text. Checked in order, so permission failures
+/// (whose messages often mention credentials) come before auth ones.
+const MESSAGE_PATTERNS: &[(&[&str], fn(String) -> ApiError)] = &[
+    (&["accessdenied", "access denied", "unauthorizedoperation", "not authorized", "permission denied", "missing permissions"], ApiError::AwsPermission),
+    (&["credential", "invalidclienttokenid", "signaturedoesnotmatch", "expiredtoken", "authfailure", "unrecognizedclient", "authentication error"], ApiError::AwsAuth),
+    (&["not found", "does not exist"], ApiError::NotFound),
+    (&["dispatch failure", "timed out", "timeout", "connection", "dns error", "throttl", "rate limit"], ApiError::Network),
+    (&["database", "sqlx", "no rows returned"], ApiError::Database),
+    (&["invalid", "must ", "required", "unsupported", "read-only", "not allowed"], ApiError::InvalidInput),
+];
+
+impl ApiError {
+    /// Stable machine-readable code
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "not_found",
+            ApiError::InvalidInput(_) => "invalid_input",
+            ApiError::AwsAuth(_) => "aws_auth",
+            ApiError::AwsPermission(_) => "aws_permission",
+            ApiError::OptInRequired(_) => "opt_in_required",
+            ApiError::Network(_) => "network",
+            ApiError::Database(_) => "database",
+            ApiError::Internal(_) => "internal",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::NotFound(message)
+            | ApiError::InvalidInput(message)
+            | ApiError::AwsAuth(message)
+            | ApiError::AwsPermission(message)
+            | ApiError::OptInRequired(message)
+            | ApiError::Network(message)
+            | ApiError::Database(message)
+            | ApiError::Internal(message) => message,
+        }
+    }
+
+    /// The same category with a different message
+    pub fn with_message(&self, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match self {
+            ApiError::NotFound(_) => ApiError::NotFound(message),
+            ApiError::InvalidInput(_) => ApiError::InvalidInput(message),
+            ApiError::AwsAuth(_) => ApiError::AwsAuth(message),
+            ApiError::AwsPermission(_) => ApiError::AwsPermission(message),
+            ApiError::OptInRequired(_) => ApiError::OptInRequired(message),
+            ApiError::Network(_) => ApiError::Network(message),
+            ApiError::Database(_) => ApiError::Database(message),
+            ApiError::Internal(_) => ApiError::Internal(message),
+        }
+    }
+
+    /// Categorize an error known only by its message, falling back to Internal.
+    /// A last resort for errors that reach a command as text; errors whose
+    /// source is known build their variant directly.
+    pub fn classify(message: impl Into<String>) -> Self {
+        Self::classify_or(message, ApiError::Internal)
+    }
+
+    /// A failed database operation
+    pub fn database(message: impl Into<String>) -> Self {
+        ApiError::Database(message.into())
+    }
+
+    /// Categorize a failed database call, prefixing its message with `context`
+    pub fn from_db(context: &str, error: &anyhow::Error) -> Self {
+        Self::from_db_with_message(error, format!("{}: {}", context, error))
+    }
+
+    /// Categorize a failed database call described by `message`. Missing rows
+    /// and rejected input keep their own categories; anything else is a
+    /// database failure.
+    pub fn from_db_with_message(error: &anyhow::Error, message: impl Into<String>) -> Self {
+        let message = message.into();
+        if let Some(rejected) = error.downcast_ref::<InstanceRequestError>() {
+            return Self::instance_request(rejected, message);
+        }
+        match error.downcast_ref::<DbError>() {
+            Some(DbError::NotFound(_)) => ApiError::NotFound(message),
+            Some(DbError::InvalidInput(_)) => ApiError::InvalidInput(message),
+            None => ApiError::Database(message),
+        }
+    }
+
+    /// Categorize an instance request its project rejected, described by `message`
+    pub fn instance_request(error: &InstanceRequestError, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match error {
+            InstanceRequestError::ProjectNotFound { .. } => ApiError::NotFound(message),
+            InstanceRequestError::PlatformMismatch { .. }
+            | InstanceRequestError::RegionMismatch { .. } => ApiError::InvalidInput(message),
+            InstanceRequestError::Lookup { .. } => ApiError::Database(message),
+        }
+    }
+
+    fn classify_or(message: impl Into<String>, fallback: fn(String) -> ApiError) -> Self {
+        let message = message.into();
+        let lowered = message.to_lowercase();
+        let category = MESSAGE_PATTERNS
+            .iter()
+            .find(|(fragments, _)| fragments.iter().any(|fragment| lowered.contains(fragment)))
+            .map_or(fallback, |(_, category)| *category);
+        category(message)
+    }
+
+    /// Categorize an AWS failure, prefixing its message with `context`
+    #[cfg(feature = "aws-sdk")]
+    pub fn aws(context: &str, error: &AwsError) -> Self {
+        Self::aws_with_message(error, format!("{}: {}", context, error))
+    }
+
+    /// Categorize an AWS failure described by `message`
+    #[cfg(feature = "aws-sdk")]
+    pub fn aws_with_message(error: &AwsError, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match error {
+            AwsError::AuthError(_) => ApiError::AwsAuth(message),
+            AwsError::PermissionError(_) => ApiError::AwsPermission(message),
+            AwsError::OptInRequired(_) => ApiError::OptInRequired(message),
+            AwsError::NetworkError(_)
+            | AwsError::TimeoutError(_)
+            | AwsError::RateLimitError(_)
+            | AwsError::RetriesExhausted { .. } => ApiError::Network(message),
+            AwsError::ConfigError(_)
+            | AwsError::RegionError(_)
+            | AwsError::WrongPlatform { .. }
+            | AwsError::InstanceStateError(_)
+            | AwsError::CostLimitError(_) => Self::classify_or(message, ApiError::InvalidInput),
+            _ => Self::classify(message),
+        }
+    }
+
+    /// Categorize an Azure failure, prefixing its message with `context`
+    #[cfg(feature = "azure-sdk")]
+    pub fn azure(context: &str, error: &AzureError) -> Self {
+        Self::azure_with_message(error, format!("{}: {}", context, error))
+    }
+
+    /// Categorize an Azure failure described by `message`
+    pub fn azure_with_message(error: &AzureError, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match error {
+            AzureError::ConfigError(_) => ApiError::InvalidInput(message),
+            AzureError::AuthError(_) => ApiError::AwsAuth(message),
+            AzureError::ApiError { status, .. } => Self::from_http_status(*status, message),
+            #[cfg(feature = "azure-sdk")]
+            AzureError::NetworkError(_) => ApiError::Network(message),
+        }
+    }
+
+    /// Categorize a GCP failure, prefixing its message with `context`
+    #[cfg(feature = "gcp")]
+    pub fn gcp(context: &str, error: &GcpError) -> Self {
+        Self::gcp_with_message(error, format!("{}: {}", context, error))
+    }
+
+    /// Categorize a GCP failure described by `message`
+    pub fn gcp_with_message(error: &GcpError, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match error {
+            GcpError::KeyError(_) => ApiError::InvalidInput(message),
+            GcpError::AuthError(_) => ApiError::AwsAuth(message),
+            GcpError::ApiError { status, .. } => Self::from_http_status(*status, message),
+            #[cfg(feature = "gcp")]
+            GcpError::NetworkError(_) => ApiError::Network(message),
+        }
+    }
+
+    /// Category for an error response from a provider's REST API
+    fn from_http_status(status: u16, message: String) -> Self {
+        match status {
+            401 => ApiError::AwsAuth(message),
+            403 => ApiError::AwsPermission(message),
+            404 => ApiError::NotFound(message),
+            408 | 429 | 500..=599 => ApiError::Network(message),
+            400..=499 => Self::classify_or(message, ApiError::InvalidInput),
+            _ => Self::classify(message),
+        }
+    }
+
+    /// The `{success: false, message, error: {code, message}, data}` response
+    pub fn into_response(self, data: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "success": false,
+            "message": self.message(),
+            "error": { "code": self.code(), "message": self.message() },
+            "data": data
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failures_map_to_stable_codes() {
+        let response = ApiError::AwsAuth("Missing AWS credentials".to_string()).into_response(serde_json::Value::Null);
+        assert_eq!(response["success"], false);
+        assert_eq!(response["message"], "Missing AWS credentials");
+        assert_eq!(response["error"]["code"], "aws_auth");
+        assert_eq!(response["error"]["message"], "Missing AWS credentials");
+
+        let code = |message: &str| ApiError::classify(message).code();
+        assert_eq!(code("Failed to collect EC2 instances: UnauthorizedOperation: You are not authorized to perform this operation"), "aws_permission");
+        assert_eq!(code("Failed to stop instance: AuthFailure: AWS was not able to validate the provided access credentials"), "aws_auth");
+        assert_eq!(code("Instance not found"), "not_found");
+        assert_eq!(code("Failed to describe volumes: dispatch failure: io error: connection refused"), "network");
+        assert_eq!(code("Failed to get accounts: error returned from database: disk I/O error"), "database");
+        assert_eq!(code("Invalid request format: missing field `name`"), "invalid_input");
+        assert_eq!(code("Failed to upload object"), "internal");
+    }
+
+    #[test]
+    fn test_database_errors_map_by_type() {
+        assert_eq!(ApiError::database("Failed to save credential failure threshold: database is locked").code(), "database");
+
+        let sqlite = anyhow::anyhow!("error returned from database: disk I/O error").context("Failed to fetch accounts");
+        assert_eq!(ApiError::from_db("Failed to get accounts", &sqlite).code(), "database");
+
+        let invalid = anyhow::Error::new(DbError::InvalidInput("Blueprint export has no schema_version".to_string()));
+        let error = ApiError::from_db("Failed to import blueprint", &invalid);
+        assert_eq!(error.code(), "invalid_input");
+        assert_eq!(error.message(), "Failed to import blueprint: Blueprint export has no schema_version");
+
+        let missing = anyhow::Error::new(DbError::NotFound("Account not found".to_string())).context("Failed to load credentials");
+        assert_eq!(ApiError::from_db("Failed to get credentials", &missing).code(), "not_found");
+
+        let mismatch = anyhow::Error::new(InstanceRequestError::PlatformMismatch {
+            project_id: 1,
+            project_platform: "aws".to_string(),
+            platform: "gcp".to_string(),
+        });
+        assert_eq!(ApiError::from_db("Failed to deploy blueprint", &mismatch).code(), "invalid_input");
+        assert_eq!(ApiError::instance_request(&InstanceRequestError::ProjectNotFound { project_id: 9 }, "Project 9 does not exist").code(), "not_found");
+    }
+
+    #[cfg(feature = "aws-sdk")]
+    #[test]
+    fn test_aws_errors_map_by_variant() {
+        let auth = ApiError::aws("Failed to create AWS client", &AwsError::AuthError("Missing AWS credentials".to_string()));
+        assert_eq!(auth.code(), "aws_auth");
+        assert_eq!(auth.message(), "Failed to create AWS client: Authentication error: Missing AWS credentials");
+
+        let missing = ApiError::aws("Failed to create AWS client", &AwsError::ConfigError("Account not found".to_string()));
+        assert_eq!(missing.code(), "not_found");
+        let config = ApiError::aws("Failed to create AWS client", &AwsError::ConfigError("Unknown region xx-1".to_string()));
+        assert_eq!(config.code(), "invalid_input");
+        let wrong_platform = ApiError::aws("Failed to create AWS client", &AwsError::WrongPlatform { account_id: 1, platform: "gcp".to_string() });
+        assert_eq!(wrong_platform.code(), "invalid_input");
+
+        assert_eq!(ApiError::aws("Failed", &AwsError::PermissionError("ec2:StopInstances".to_string())).code(), "aws_permission");
+        assert_eq!(ApiError::aws("Failed", &AwsError::RateLimitError("slow down".to_string())).code(), "network");
+        assert_eq!(ApiError::aws("Failed", &AwsError::OptInRequired("Compute Optimizer".to_string())).code(), "opt_in_required");
+        assert_eq!(ApiError::aws("Failed", &AwsError::CacheError("poisoned".to_string())).code(), "internal");
+    }
+
+    #[test]
+    fn test_provider_api_errors_map_by_status() {
+        let api_error = |status| AzureError::ApiError { status, message: "request failed".to_string() };
+        assert_eq!(ApiError::azure_with_message(&api_error(401), "Failed").code(), "aws_auth");
+        assert_eq!(ApiError::azure_with_message(&api_error(403), "Failed").code(), "aws_permission");
+        assert_eq!(ApiError::azure_with_message(&api_error(404), "Failed").code(), "not_found");
+        assert_eq!(ApiError::azure_with_message(&api_error(429), "Failed").code(), "network");
+        assert_eq!(ApiError::azure_with_message(&api_error(503), "Failed").code(), "network");
+        assert_eq!(ApiError::azure_with_message(&api_error(400), "Failed").code(), "invalid_input");
+        assert_eq!(ApiError::azure_with_message(&AzureError::ConfigError("tenant_id is required".to_string()), "Failed").code(), "invalid_input");
+
+        let key_error = GcpError::KeyError("missing field `private_key`".to_string());
+        assert_eq!(ApiError::gcp_with_message(&key_error, key_error.to_string()).code(), "invalid_input");
+        assert_eq!(ApiError::gcp_with_message(&GcpError::AuthError("invalid_grant".to_string()), "Failed").code(), "aws_auth");
+    }
+}
@@ -0,0 +1,63 @@
+// ============================================================================
+// APP INFO
+// ============================================================================
+// Build and version details reported to support for issue triage
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppInfo {
+    pub version: String,
+    pub git_hash: String,
+    pub build_profile: String,
+    pub features: Vec<String>,
+    pub schema_version: i64,
+}
+
+impl AppInfo {
+    pub fn current(schema_version: i64) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_hash: env!("GIT_HASH").to_string(),
+            build_profile: env!("BUILD_PROFILE").to_string(),
+            features: enabled_features(),
+            schema_version,
+        }
+    }
+}
+
+/// Cargo features this binary was compiled with
+pub fn enabled_features() -> Vec<String> {
+    [
+        ("aws-sdk", cfg!(feature = "aws-sdk")),
+        ("azure-sdk", cfg!(feature = "azure-sdk")),
+        ("gcp", cfg!(feature = "gcp")),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(feature, _)| feature.to_string())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_info_has_version() {
+        let info = AppInfo::current(1);
+        assert!(!info.version.is_empty());
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_hash.is_empty());
+        assert_eq!(info.schema_version, 1);
+    }
+
+    #[test]
+    fn test_app_info_reflects_compiled_features() {
+        let info = AppInfo::current(1);
+        assert_eq!(info.features.contains(&"aws-sdk".to_string()), cfg!(feature = "aws-sdk"));
+        assert_eq!(info.features.contains(&"azure-sdk".to_string()), cfg!(feature = "azure-sdk"));
+        assert_eq!(info.features.contains(&"gcp".to_string()), cfg!(feature = "gcp"));
+    }
+}
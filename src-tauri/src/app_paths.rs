@@ -0,0 +1,131 @@
+// ============================================================================
+// APP DATA DIRECTORY
+// ============================================================================
+// Per-user directory for the secrets the app keeps on disk (the fallback
+// credentials file, private keys handed to SSH). Resolved the way Tauri
+// resolves app_data_dir for the bundle identifier, so it's available before
+// there is an AppHandle and doesn't depend on where the app was launched from.
+// ============================================================================
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+/// Bundle identifier from tauri.conf.json
+const APP_IDENTIFIER: &str = "com.pocketarchitect.app";
+
+/// Platform data directory, from the environment variables the platform
+/// conventions are defined in
+fn base_data_dir(var: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let absolute = |value: String| Some(PathBuf::from(value)).filter(|path| path.is_absolute());
+
+    if cfg!(windows) {
+        var("APPDATA").and_then(absolute)
+    } else if cfg!(target_os = "macos") {
+        var("HOME").and_then(absolute).map(|home| home.join("Library").join("Application Support"))
+    } else {
+        var("XDG_DATA_HOME")
+            .and_then(absolute)
+            .or_else(|| var("HOME").and_then(absolute).map(|home| home.join(".local").join("share")))
+    }
+}
+
+fn resolve_app_data_dir(var: impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    base_data_dir(var).map(|base| base.join(APP_IDENTIFIER))
+}
+
+#[cfg(not(test))]
+fn data_dir() -> Result<PathBuf> {
+    resolve_app_data_dir(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        .ok_or_else(|| anyhow::anyhow!("Can't determine the app data directory: no home directory is set"))
+}
+
+/// Tests keep their files out of the user's real data directory, each test
+/// thread in a directory of its own that's removed when the thread ends
+#[cfg(test)]
+fn data_dir() -> Result<PathBuf> {
+    Ok(TEST_DATA_DIR.with(|dir| dir.0.clone()))
+}
+
+#[cfg(test)]
+struct TestDataDir(PathBuf);
+
+#[cfg(test)]
+impl Drop for TestDataDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_DATA_DIR: TestDataDir = {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let index = NEXT.fetch_add(1, Ordering::Relaxed);
+        TestDataDir(std::env::temp_dir().join(format!("pocket-architect-test-{}-{}", std::process::id(), index)))
+    };
+}
+
+/// The app's data directory, created (private to the user on Unix) if missing
+pub fn app_data_dir() -> Result<PathBuf> {
+    let dir = data_dir()?;
+
+    if !dir.exists() {
+        std::fs::create_dir_all(&dir).context(format!("Failed to create app data directory: {}", dir.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))
+                .context(format!("Failed to restrict app data directory: {}", dir.display()))?;
+        }
+    }
+
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_app_data_dir_ignores_relative_locations() {
+        let resolve = |vars: &[(&str, &str)]| {
+            let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+            resolve_app_data_dir(|name| vars.get(name).cloned())
+        };
+
+        assert_eq!(resolve(&[]), None);
+        assert_eq!(resolve(&[("HOME", "relative/home"), ("APPDATA", "relative")]), None);
+
+        if cfg!(all(unix, not(target_os = "macos"))) {
+            assert_eq!(resolve(&[("HOME", "/home/ops")]), Some(PathBuf::from("/home/ops/.local/share/com.pocketarchitect.app")));
+            assert_eq!(
+                resolve(&[("HOME", "/home/ops"), ("XDG_DATA_HOME", "/data")]),
+                Some(PathBuf::from("/data/com.pocketarchitect.app"))
+            );
+            // A relative XDG_DATA_HOME is invalid per the spec and falls back to HOME
+            assert_eq!(
+                resolve(&[("HOME", "/home/ops"), ("XDG_DATA_HOME", "data")]),
+                Some(PathBuf::from("/home/ops/.local/share/com.pocketarchitect.app"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_each_test_thread_gets_a_removed_data_dir() {
+        let dir = app_data_dir().unwrap();
+        assert!(dir.starts_with(std::env::temp_dir()));
+
+        let (other, existed) = std::thread::spawn(|| {
+            let dir = app_data_dir().unwrap();
+            std::fs::write(dir.join("secret"), "value").unwrap();
+            (dir.clone(), dir.exists())
+        })
+        .join()
+        .unwrap();
+        assert!(existed);
+        assert_ne!(other, dir);
+        assert!(!other.exists());
+    }
+}
@@ -26,9 +26,15 @@ pub struct Instance {
     pub region: String,
     pub public_ip: Option<String>,
     pub private_ip: String,
+    #[serde(default)]
+    pub ipv6_address: Option<String>,
+    #[serde(default)]
+    pub network_interface_count: usize,
     pub created: String,
     pub uptime: String,
     pub monthly_cost: f64,
+    /// 'on_demand' | 'reserved' | 'spot'
+    pub coverage: InstanceCoverage,
     pub storage: i64,
     pub security_config: String,
     pub ssh_key: String,
@@ -60,9 +66,12 @@ pub fn aws_instance_to_frontend(aws_instance: AwsInstance, project_id: i64, proj
         region: aws_instance.region,
         public_ip: aws_instance.public_ip,
         private_ip: aws_instance.private_ip.unwrap_or_else(|| "unknown".to_string()),
+        ipv6_address: aws_instance.ipv6_address,
+        network_interface_count: aws_instance.network_interfaces.len(),
         created,
         uptime,
         monthly_cost,
+        coverage: aws_instance.coverage,
         storage,
         security_config,
         ssh_key,
@@ -107,6 +116,15 @@ fn map_aws_state_to_frontend_status(aws_state: &str) -> String {
     }
 }
 
+/// Frontend status for an instance whose status checks are known. Failing
+/// checks mark it degraded whatever its state would map to.
+pub fn map_instance_health_status(aws_state: &str, status_checks_failing: bool) -> String {
+    if status_checks_failing {
+        return "degraded".to_string();
+    }
+    map_aws_state_to_frontend_status(aws_state)
+}
+
 /// Calculate uptime from launch time
 fn calculate_uptime(launch_time: &str) -> String {
     if let Ok(launch_dt) = DateTime::parse_from_rfc3339(launch_time) {
@@ -126,10 +144,23 @@ fn calculate_uptime(launch_time: &str) -> String {
 }
 
 /// Estimate monthly cost for an instance (simplified)
-fn estimate_monthly_cost(instance: &AwsInstance) -> f64 {
+pub fn estimate_monthly_cost(instance: &AwsInstance) -> f64 {
+    estimate_monthly_cost_for_type(&instance.instance_type)
+}
+
+/// Estimated on-demand monthly cost of running an instance type
+pub fn estimate_monthly_cost_for_type(instance_type: &str) -> f64 {
+    on_demand_hourly_rate(instance_type) * HOURS_PER_MONTH
+}
+
+/// Assume 730 hours per month (24 * 30.4)
+pub const HOURS_PER_MONTH: f64 = 730.0;
+
+/// Approximate on-demand hourly price of an instance type
+pub fn on_demand_hourly_rate(instance_type: &str) -> f64 {
     // Basic cost estimation based on instance type
     // In a real implementation, this would use AWS pricing API
-    let base_cost_per_hour = match instance.instance_type.as_str() {
+    match instance_type {
         "t2.micro" => 0.0116,
         "t2.small" => 0.023,
         "t2.medium" => 0.0464,
@@ -140,10 +171,7 @@ fn estimate_monthly_cost(instance: &AwsInstance) -> f64 {
         "m5.xlarge" => 0.192,
         "c5.large" => 0.085,
         _ => 0.05, // Default fallback
-    };
-
-    // Assume 730 hours per month (24 * 30.4)
-    base_cost_per_hour * 730.0
+    }
 }
 
 /// Format security groups for display
@@ -0,0 +1,221 @@
+// ============================================================================
+// AMI CATALOG
+// ============================================================================
+// Current images for common distros, so launches can name "ubuntu-22.04/arm64"
+// instead of a region-specific AMI id
+// ============================================================================
+
+use crate::aws::{AwsAmi, AmiListOptions, AwsClient, AwsError, AwsResult};
+use aws_sdk_ec2::types::{Filter, Image};
+
+/// A distro the catalog knows how to find
+#[derive(Debug, Clone, Copy)]
+pub struct AmiDistro {
+    pub key: &'static str,
+    pub label: &'static str,
+    /// Account that publishes the images
+    pub owner: &'static str,
+    /// DescribeImages name filter
+    pub name_pattern: &'static str,
+}
+
+pub const AMI_DISTROS: &[AmiDistro] = &[
+    AmiDistro {
+        key: "amazon-linux-2023",
+        label: "Amazon Linux 2023",
+        owner: "amazon",
+        // Excludes the al2023-ami-minimal images
+        name_pattern: "al2023-ami-2023.*",
+    },
+    AmiDistro {
+        key: "ubuntu-22.04",
+        label: "Ubuntu 22.04",
+        owner: "099720109477",
+        name_pattern: "ubuntu/images/hvm-ssd/ubuntu-jammy-22.04-*-server-*",
+    },
+    AmiDistro {
+        key: "ubuntu-24.04",
+        label: "Ubuntu 24.04",
+        owner: "099720109477",
+        name_pattern: "ubuntu/images/hvm-ssd-gp3/ubuntu-noble-24.04-*-server-*",
+    },
+    AmiDistro {
+        key: "debian-12",
+        label: "Debian 12",
+        owner: "136693071363",
+        // debian-12-amd64-* and debian-12-arm64-*, but not debian-12-backports-*
+        name_pattern: "debian-12-a*",
+    },
+];
+
+/// Architectures the catalog lists images for
+pub const AMI_ARCHITECTURES: &[&str] = &["x86_64", "arm64"];
+
+/// Newest images kept per distro and architecture; older builds are rarely wanted
+const AMI_VERSIONS_PER_DISTRO: usize = 5;
+
+pub fn find_distro(key: &str) -> AwsResult<&'static AmiDistro> {
+    AMI_DISTROS.iter().find(|d| d.key == key).ok_or_else(|| {
+        let known: Vec<&str> = AMI_DISTROS.iter().map(|d| d.key).collect();
+        AwsError::ConfigError(format!("Unknown distro '{}'; expected one of {}", key, known.join(", ")))
+    })
+}
+
+/// Normalize an architecture name; "amd64" and "aarch64" are accepted as aliases
+pub fn normalize_architecture(arch: &str) -> AwsResult<&'static str> {
+    match arch {
+        "x86_64" | "amd64" => Ok("x86_64"),
+        "arm64" | "aarch64" => Ok("arm64"),
+        other => Err(AwsError::ConfigError(format!("Unsupported architecture '{}'; expected x86_64 or arm64", other))),
+    }
+}
+
+/// Split an image alias such as "ubuntu-22.04/arm64" into distro and
+/// architecture. The architecture defaults to x86_64.
+pub fn parse_ami_alias(alias: &str) -> AwsResult<(&'static AmiDistro, &'static str)> {
+    let (distro, arch) = alias.split_once('/').unwrap_or((alias, "x86_64"));
+    Ok((find_distro(distro)?, normalize_architecture(arch)?))
+}
+
+/// An image reference is either an AMI id or a catalog alias
+pub fn is_ami_id(image: &str) -> bool {
+    image.starts_with("ami-")
+}
+
+/// Map a DescribeImages result into a catalog entry
+pub fn map_ami(image: &Image, distro: Option<&str>) -> Option<AwsAmi> {
+    Some(AwsAmi {
+        image_id: image.image_id()?.to_string(),
+        name: image.name().unwrap_or_default().to_string(),
+        description: image.description().map(|d| d.to_string()),
+        architecture: image.architecture().map(|a| a.as_str().to_string()).unwrap_or_else(|| "unknown".to_string()),
+        creation_date: image.creation_date().unwrap_or_default().to_string(),
+        owner_id: image.owner_id().unwrap_or_default().to_string(),
+        distro: distro.map(|d| d.to_string()),
+    })
+}
+
+/// Catalog entries for one distro: the newest few per architecture,
+/// newest first
+pub fn catalog_from_images(distro: &str, images: &[Image]) -> Vec<AwsAmi> {
+    let mut amis: Vec<AwsAmi> = images.iter().filter_map(|image| map_ami(image, Some(distro))).collect();
+    sort_newest_first(&mut amis);
+
+    let mut kept = Vec::new();
+    for arch in AMI_ARCHITECTURES {
+        kept.extend(amis.iter().filter(|ami| ami.architecture == *arch).take(AMI_VERSIONS_PER_DISTRO).cloned());
+    }
+    sort_newest_first(&mut kept);
+    kept
+}
+
+/// Creation dates are ISO 8601, so they sort as strings
+fn sort_newest_first(amis: &mut [AwsAmi]) {
+    amis.sort_by(|a, b| b.creation_date.cmp(&a.creation_date));
+}
+
+/// The newest catalog image for a distro and architecture
+pub fn latest_ami<'a>(catalog: &'a [AwsAmi], distro: &str, architecture: &str) -> Option<&'a AwsAmi> {
+    catalog
+        .iter()
+        .filter(|ami| ami.distro.as_deref() == Some(distro) && ami.architecture == architecture)
+        .max_by(|a, b| a.creation_date.cmp(&b.creation_date))
+}
+
+/// Available images matching `filters` from `owner`
+async fn describe_images(client: &AwsClient, owner: &str, filters: Vec<Filter>) -> AwsResult<Vec<Image>> {
+    client.ec2_client
+        .describe_images()
+        .owners(owner)
+        .set_filters(Some(filters))
+        .into_paginator()
+        .items()
+        .send()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to describe images owned by {}: {:?}", owner, e);
+            AwsError::SdkError(e.into())
+        })
+}
+
+/// The distro catalog for the client's region, from the cache when fresh
+async fn distro_catalog(client: &AwsClient) -> AwsResult<Vec<AwsAmi>> {
+    let region = client.primary_region();
+    if let Some(catalog) = client.cache.get_amis(region).await {
+        return Ok(catalog);
+    }
+
+    tracing::info!("Building AMI catalog for region {}", region);
+    let mut catalog = Vec::new();
+    for distro in AMI_DISTROS {
+        let filters = vec![
+            Filter::builder().name("name").values(distro.name_pattern).build(),
+            Filter::builder().name("state").values("available").build(),
+            Filter::builder().name("architecture").set_values(Some(AMI_ARCHITECTURES.iter().map(|a| a.to_string()).collect())).build(),
+        ];
+        let images = describe_images(client, distro.owner, filters).await?;
+        catalog.extend(catalog_from_images(distro.key, &images));
+    }
+    sort_newest_first(&mut catalog);
+
+    client.cache.put_amis(region.to_string(), catalog.clone()).await;
+    Ok(catalog)
+}
+
+/// Catalog images for the requested distros (all known ones when none are
+/// given) and architecture, plus the account's own images when asked,
+/// newest first
+pub async fn list_amis(client: &AwsClient, options: &AmiListOptions) -> AwsResult<Vec<AwsAmi>> {
+    for distro in &options.distros {
+        find_distro(distro)?;
+    }
+    let architecture = options.architecture.as_deref().map(normalize_architecture).transpose()?;
+
+    let mut amis: Vec<AwsAmi> = distro_catalog(client)
+        .await?
+        .into_iter()
+        .filter(|ami| options.distros.is_empty() || ami.distro.as_ref().is_some_and(|d| options.distros.contains(d)))
+        .filter(|ami| architecture.map_or(true, |arch| ami.architecture == arch))
+        .collect();
+
+    if options.include_own {
+        let mut filters = vec![Filter::builder().name("state").values("available").build()];
+        if let Some(arch) = architecture {
+            filters.push(Filter::builder().name("architecture").values(arch).build());
+        }
+        let images = describe_images(client, "self", filters).await?;
+        amis.extend(images.iter().filter_map(|image| map_ami(image, None)));
+    }
+
+    sort_newest_first(&mut amis);
+    Ok(amis)
+}
+
+/// The newest image for a distro and architecture in the client's region
+pub async fn resolve_latest_ami(client: &AwsClient, distro: &str, architecture: &str) -> AwsResult<AwsAmi> {
+    let distro = find_distro(distro)?;
+    let architecture = normalize_architecture(architecture)?;
+
+    let catalog = distro_catalog(client).await?;
+    latest_ami(&catalog, distro.key, architecture).cloned().ok_or_else(|| {
+        AwsError::OperationError(format!(
+            "No {} image for {} found in {}",
+            distro.label,
+            architecture,
+            client.primary_region()
+        ))
+    })
+}
+
+/// An AMI id as is, or the newest image for a catalog alias such as
+/// "ubuntu-22.04/arm64"
+pub async fn resolve_image(client: &AwsClient, image: &str) -> AwsResult<String> {
+    if is_ami_id(image) {
+        return Ok(image.to_string());
+    }
+    let (distro, architecture) = parse_ami_alias(image)?;
+    let ami = resolve_latest_ami(client, distro.key, architecture).await?;
+    tracing::info!("Resolved image {} to {} ({})", image, ami.image_id, ami.name);
+    Ok(ami.image_id)
+}
@@ -0,0 +1,286 @@
+// ============================================================================
+// INSTANCE COST ATTRIBUTION
+// ============================================================================
+// Total monthly cost of an instance: its own compute and EBS storage plus a
+// share of the network resources it depends on in its VPC.
+//
+// Allocation method: each NAT gateway and load balancer in the VPC is split
+// evenly between the instances in that VPC that were not terminated. Data
+// processing and transfer billed to a NAT gateway or load balancer is part of
+// that resource's cost and is split with it. Costs come from Cost Explorer's
+// resource-level data, which AWS only keeps for the last 14 days and which
+// must be enabled in the billing console.
+// ============================================================================
+
+use crate::aws::{AwsClient, AwsError, AwsResult};
+use aws_sdk_costexplorer::types::{
+    DateInterval, Dimension, DimensionValues, Expression, Granularity, GroupDefinition, GroupDefinitionType,
+};
+use aws_sdk_ec2::error::DisplayErrorContext;
+use aws_sdk_ec2::types::{Filter, NetworkInterface, NetworkInterfaceType};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// Cost Explorer services holding instance, volume, NAT gateway and load balancer charges
+const ATTRIBUTED_SERVICES: &[&str] = &[
+    "Amazon Elastic Compute Cloud - Compute",
+    "EC2 - Other",
+    "Elastic Load Balancing",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SharedResourceKind {
+    NatGateway,
+    LoadBalancer,
+}
+
+/// A network resource shared by every instance in a VPC
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SharedResource {
+    /// `nat-…` for NAT gateways, `app/<name>/<id>` style for load balancers
+    pub id: String,
+    pub kind: SharedResourceKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedCostShare {
+    pub resource_id: String,
+    pub kind: SharedResourceKind,
+    pub resource_cost_usd: f64,
+    pub instances_sharing: usize,
+    pub share_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceCostBreakdown {
+    pub instance_id: String,
+    pub month: String,
+    pub vpc_id: Option<String>,
+    pub compute_usd: f64,
+    pub storage_usd: f64,
+    pub shared: Vec<SharedCostShare>,
+    pub shared_usd: f64,
+    pub total_usd: f64,
+    pub allocation_method: String,
+}
+
+/// Everything the allocation needs, gathered from EC2 and Cost Explorer
+#[derive(Debug, Clone, Default)]
+pub struct AttributionInputs {
+    pub vpc_id: Option<String>,
+    pub volume_ids: Vec<String>,
+    /// Instances in the VPC, including this one
+    pub vpc_instance_count: usize,
+    pub shared_resources: Vec<SharedResource>,
+    /// Cost Explorer RESOURCE_ID -> unblended cost for the month
+    pub resource_costs: HashMap<String, f64>,
+}
+
+/// Cost Explorer reports NAT gateways and load balancers by ARN, instances and
+/// volumes by id
+fn resource_cost(resource_costs: &HashMap<String, f64>, id: &str) -> f64 {
+    resource_costs
+        .iter()
+        .filter(|(resource_id, _)| resource_id.as_str() == id || resource_id.ends_with(&format!("/{}", id)))
+        .map(|(_, cost)| cost)
+        .sum()
+}
+
+/// Combine an instance's own costs with an even split of its VPC's shared resources
+pub fn attribute_instance_cost(instance_id: &str, month: &str, inputs: &AttributionInputs) -> InstanceCostBreakdown {
+    let compute_usd = resource_cost(&inputs.resource_costs, instance_id);
+    let storage_usd = inputs.volume_ids.iter().map(|id| resource_cost(&inputs.resource_costs, id)).sum();
+    let instances_sharing = inputs.vpc_instance_count.max(1);
+
+    let shared: Vec<SharedCostShare> = inputs.shared_resources
+        .iter()
+        .map(|resource| {
+            let resource_cost_usd = resource_cost(&inputs.resource_costs, &resource.id);
+            SharedCostShare {
+                resource_id: resource.id.clone(),
+                kind: resource.kind,
+                resource_cost_usd,
+                instances_sharing,
+                share_usd: resource_cost_usd / instances_sharing as f64,
+            }
+        })
+        .collect();
+    let shared_usd: f64 = shared.iter().map(|s| s.share_usd).sum();
+
+    InstanceCostBreakdown {
+        instance_id: instance_id.to_string(),
+        month: month.to_string(),
+        vpc_id: inputs.vpc_id.clone(),
+        compute_usd,
+        storage_usd,
+        shared,
+        shared_usd,
+        total_usd: compute_usd + storage_usd + shared_usd,
+        allocation_method: "instance_count".to_string(),
+    }
+}
+
+/// NAT gateways and load balancers behind a VPC's network interfaces
+pub fn shared_resources_from_interfaces(interfaces: &[NetworkInterface]) -> Vec<SharedResource> {
+    let resources: BTreeSet<SharedResource> = interfaces
+        .iter()
+        .filter_map(|interface| {
+            let description = interface.description().unwrap_or_default();
+            if interface.interface_type() == Some(&NetworkInterfaceType::NatGateway) {
+                // "Interface for NAT Gateway nat-0123456789abcdef0"
+                let id = description.split_whitespace().find(|word| word.starts_with("nat-"))?;
+                Some(SharedResource { id: id.to_string(), kind: SharedResourceKind::NatGateway })
+            } else {
+                // "ELB app/web/50dc6c495c0c9188", or "ELB web" for classic load balancers
+                let name = description.strip_prefix("ELB ")?;
+                Some(SharedResource { id: name.to_string(), kind: SharedResourceKind::LoadBalancer })
+            }
+        })
+        .collect();
+
+    resources.into_iter().collect()
+}
+
+/// First day of a `YYYY-MM` month and of the month after it
+pub fn month_bounds(month: &str) -> AwsResult<(NaiveDate, NaiveDate)> {
+    let start = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| AwsError::ConfigError(format!("Invalid month '{}'. Use YYYY-MM", month)))?;
+    let end = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    }
+    .ok_or_else(|| AwsError::ConfigError(format!("Invalid month '{}'", month)))?;
+
+    Ok((start, end))
+}
+
+/// Cost breakdown for an instance in `month` (YYYY-MM)
+pub async fn instance_total_cost(client: &AwsClient, instance_id: &str, month: &str) -> AwsResult<InstanceCostBreakdown> {
+    tracing::info!("Attributing {} costs for instance {}", month, instance_id);
+
+    let inputs = gather_inputs(client, instance_id, month).await?;
+    Ok(attribute_instance_cost(instance_id, month, &inputs))
+}
+
+async fn gather_inputs(client: &AwsClient, instance_id: &str, month: &str) -> AwsResult<AttributionInputs> {
+    let (start, next_month) = month_bounds(month)?;
+    // Cost Explorer rejects periods ending after tomorrow
+    let end = next_month.min(chrono::Utc::now().date_naive() + chrono::Duration::days(1));
+    if start >= end {
+        return Err(AwsError::ConfigError(format!("Month {} has not started yet", month)));
+    }
+
+    let ec2 = &client.ec2_client;
+    let instance = ec2.describe_instances()
+        .instance_ids(instance_id)
+        .send()
+        .await
+        .map_err(|e| AwsError::SdkError(e.into()))?
+        .reservations()
+        .iter()
+        .flat_map(|r| r.instances())
+        .next()
+        .cloned()
+        .ok_or_else(|| AwsError::OperationError(format!("Instance {} not found", instance_id)))?;
+
+    let volume_ids: Vec<String> = instance.block_device_mappings()
+        .iter()
+        .filter_map(|mapping| mapping.ebs()?.volume_id().map(str::to_string))
+        .collect();
+
+    let mut inputs = AttributionInputs {
+        vpc_id: instance.vpc_id().map(str::to_string),
+        volume_ids,
+        vpc_instance_count: 1,
+        ..Default::default()
+    };
+
+    if let Some(vpc_id) = &inputs.vpc_id {
+        let vpc_filter = Filter::builder().name("vpc-id").values(vpc_id).build();
+
+        let vpc_instances = ec2.describe_instances()
+            .filters(vpc_filter.clone())
+            .filters(
+                Filter::builder()
+                    .name("instance-state-name")
+                    .set_values(Some(vec!["pending".into(), "running".into(), "stopping".into(), "stopped".into()]))
+                    .build(),
+            )
+            .into_paginator()
+            .items()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .map_err(|e| AwsError::SdkError(e.into()))?;
+        inputs.vpc_instance_count = vpc_instances.iter().map(|r| r.instances().len()).sum::<usize>().max(1);
+
+        let interfaces = ec2.describe_network_interfaces()
+            .filters(vpc_filter)
+            .into_paginator()
+            .items()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .map_err(|e| AwsError::SdkError(e.into()))?;
+        inputs.shared_resources = shared_resources_from_interfaces(&interfaces);
+    }
+
+    inputs.resource_costs = resource_costs(client, start, end).await?;
+    Ok(inputs)
+}
+
+/// Unblended cost per resource id over [start, end)
+async fn resource_costs(client: &AwsClient, start: NaiveDate, end: NaiveDate) -> AwsResult<HashMap<String, f64>> {
+    let period = DateInterval::builder()
+        .start(start.format("%Y-%m-%d").to_string())
+        .end(end.format("%Y-%m-%d").to_string())
+        .build()
+        .map_err(|e| AwsError::OperationError(e.to_string()))?;
+    let filter = Expression::builder()
+        .dimensions(
+            DimensionValues::builder()
+                .key(Dimension::Service)
+                .set_values(Some(ATTRIBUTED_SERVICES.iter().map(|s| s.to_string()).collect()))
+                .build(),
+        )
+        .build();
+
+    let mut costs = HashMap::new();
+    let mut next_page_token: Option<String> = None;
+    loop {
+        let response = client.ce_client
+            .get_cost_and_usage_with_resources()
+            .time_period(period.clone())
+            .granularity(Granularity::Monthly)
+            .filter(filter.clone())
+            .metrics("UnblendedCost")
+            .group_by(GroupDefinition::builder().r#type(GroupDefinitionType::Dimension).key("RESOURCE_ID").build())
+            .set_next_page_token(next_page_token.take())
+            .send()
+            .await
+            .map_err(|e| AwsError::OperationError(format!(
+                "Failed to get resource-level costs (is hourly and resource level data enabled in Cost Explorer?): {}",
+                DisplayErrorContext(&e)
+            )))?;
+
+        for group in response.results_by_time().iter().flat_map(|r| r.groups()) {
+            let Some(resource_id) = group.keys().first() else { continue };
+            let amount = group.metrics()
+                .and_then(|m| m.get("UnblendedCost"))
+                .and_then(|m| m.amount())
+                .and_then(|a| a.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            *costs.entry(resource_id.clone()).or_insert(0.0) += amount;
+        }
+
+        next_page_token = response.next_page_token().map(str::to_string);
+        if next_page_token.is_none() {
+            break;
+        }
+    }
+
+    Ok(costs)
+}
@@ -0,0 +1,124 @@
+// ============================================================================
+// AWS HEALTH DASHBOARD
+// ============================================================================
+// Open AWS service events affecting the account, from the AWS Health API.
+// The API is only available on Business, Enterprise On-Ramp and Enterprise
+// support plans; other accounts get an empty, flagged result.
+// ============================================================================
+
+use crate::aws::{AwsClient, AwsError, AwsResult};
+use aws_sdk_health::error::{DisplayErrorContext, ProvideErrorMetadata};
+use aws_sdk_health::types::{Event, EventFilter, EventStatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Error code returned when the account lacks a qualifying support plan
+const SUBSCRIPTION_REQUIRED_CODE: &str = "SubscriptionRequiredException";
+
+/// DescribeEventDetails accepts at most 10 event ARNs per call
+const EVENT_DETAILS_BATCH_SIZE: usize = 10;
+
+/// How long fetched events are reused before asking AWS again
+pub const SERVICE_EVENTS_TTL_SECONDS: i64 = 300;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceEvent {
+    pub arn: String,
+    pub service: String,
+    pub region: String,
+    pub status: String,
+    pub start_time: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceEventsReport {
+    pub events: Vec<ServiceEvent>,
+    /// The account's support plan does not include the AWS Health API
+    pub business_support_required: bool,
+}
+
+/// Flatten a Health event and its latest description
+pub fn map_event(event: &Event, description: Option<String>) -> ServiceEvent {
+    ServiceEvent {
+        arn: event.arn().unwrap_or_default().to_string(),
+        service: event.service().unwrap_or("unknown").to_string(),
+        region: event.region().unwrap_or("global").to_string(),
+        status: event.status_code().map(|s| s.as_str().to_ascii_lowercase()).unwrap_or_else(|| "unknown".to_string()),
+        start_time: event.start_time().map(|t| t.to_string()),
+        description,
+    }
+}
+
+/// Open events for the account, with their descriptions
+pub async fn fetch_service_events(client: &AwsClient) -> AwsResult<ServiceEventsReport> {
+    tracing::info!("Fetching AWS Health events");
+
+    let filter = EventFilter::builder()
+        .event_status_codes(EventStatusCode::Open)
+        .build();
+
+    let events = match client.health_client
+        .describe_events()
+        .filter(filter)
+        .into_paginator()
+        .items()
+        .send()
+        .collect::<Result<Vec<Event>, _>>()
+        .await
+    {
+        Ok(events) => events,
+        Err(e) if e.code() == Some(SUBSCRIPTION_REQUIRED_CODE) => {
+            tracing::info!("AWS Health API requires a Business support plan; skipping service events");
+            return Ok(ServiceEventsReport { events: Vec::new(), business_support_required: true });
+        }
+        Err(e) => {
+            return Err(AwsError::OperationError(format!("Failed to describe AWS Health events: {}", DisplayErrorContext(&e))));
+        }
+    };
+
+    let arns: Vec<String> = events.iter().filter_map(|e| e.arn().map(str::to_string)).collect();
+    let descriptions = event_descriptions(client, &arns).await?;
+
+    let events = events
+        .iter()
+        .map(|event| {
+            let description = event.arn().and_then(|arn| descriptions.get(arn).cloned());
+            map_event(event, description)
+        })
+        .collect::<Vec<_>>();
+
+    tracing::info!("Found {} open AWS Health events", events.len());
+    Ok(ServiceEventsReport { events, business_support_required: false })
+}
+
+/// Latest description of each event, keyed by event ARN
+async fn event_descriptions(client: &AwsClient, arns: &[String]) -> AwsResult<HashMap<String, String>> {
+    let mut descriptions = HashMap::new();
+
+    for batch in arns.chunks(EVENT_DETAILS_BATCH_SIZE) {
+        let response = client.health_client
+            .describe_event_details()
+            .set_event_arns(Some(batch.to_vec()))
+            .send()
+            .await
+            .map_err(|e| AwsError::OperationError(format!("Failed to describe AWS Health event details: {}", DisplayErrorContext(&e))))?;
+
+        for detail in response.successful_set() {
+            let arn = detail.event().and_then(|e| e.arn());
+            let description = detail.event_description().and_then(|d| d.latest_description());
+            if let (Some(arn), Some(description)) = (arn, description) {
+                descriptions.insert(arn.to_string(), description.to_string());
+            }
+        }
+        for failure in response.failed_set() {
+            tracing::warn!(
+                "No details for AWS Health event {}: {}",
+                failure.event_arn().unwrap_or("unknown"),
+                failure.error_message().unwrap_or("unknown error")
+            );
+        }
+    }
+
+    Ok(descriptions)
+}
@@ -5,12 +5,26 @@
 // ============================================================================
 
 use crate::aws::AwsResult;
+use crate::aws::cloudwatch::{InstanceMetrics, InstancesHealthSummary, MetricWindow};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{RwLock, Mutex};
 use chrono::{DateTime, Utc, Duration};
 use serde::{Serialize, Deserialize};
 
+/// Default lifetime of cached resource lists
+pub const DEFAULT_CACHE_TTL_SECONDS: i64 = 180;
+
+/// Instance type hardware only changes when AWS revises a type, so it is kept for a week
+pub const INSTANCE_TYPE_TTL_SECONDS: i64 = 7 * 24 * 3600;
+
+/// Distros publish new images every few days at most
+pub const AMI_CATALOG_TTL_SECONDS: i64 = 6 * 3600;
+
+/// CloudWatch publishes EC2 metrics every minute at best; refetching sooner
+/// returns the same data points
+pub const METRICS_TTL_SECONDS: i64 = 60;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheEntry<T> {
     pub data: T,
@@ -45,6 +59,13 @@ pub struct AwsCache {
     s3_buckets: Arc<RwLock<HashMap<String, CacheEntry<Vec<crate::aws::AwsBucket>>>>>,
     iam_users: Arc<RwLock<Option<CacheEntry<Vec<crate::aws::AwsIamUser>>>>>,
     iam_roles: Arc<RwLock<Option<CacheEntry<Vec<String>>>>>,
+    instance_types: Arc<RwLock<HashMap<String, CacheEntry<crate::aws::InstanceTypeSpec>>>>,
+    amis: Arc<RwLock<HashMap<String, CacheEntry<Vec<crate::aws::AwsAmi>>>>>,
+    volumes: Arc<RwLock<HashMap<String, CacheEntry<Vec<crate::aws::AwsEbsVolume>>>>>,
+    vpcs: Arc<RwLock<HashMap<String, CacheEntry<Vec<crate::aws::AwsVpc>>>>>,
+    subnets: Arc<RwLock<HashMap<String, CacheEntry<Vec<crate::aws::AwsSubnet>>>>>,
+    instance_metrics: Arc<RwLock<HashMap<(String, MetricWindow), CacheEntry<InstanceMetrics>>>>,
+    instance_health: Arc<RwLock<HashMap<String, CacheEntry<InstancesHealthSummary>>>>,
     default_ttl_seconds: i64,
 }
 
@@ -55,6 +76,13 @@ impl AwsCache {
             s3_buckets: Arc::new(RwLock::new(HashMap::new())),
             iam_users: Arc::new(RwLock::new(None)),
             iam_roles: Arc::new(RwLock::new(None)),
+            instance_types: Arc::new(RwLock::new(HashMap::new())),
+            amis: Arc::new(RwLock::new(HashMap::new())),
+            volumes: Arc::new(RwLock::new(HashMap::new())),
+            vpcs: Arc::new(RwLock::new(HashMap::new())),
+            subnets: Arc::new(RwLock::new(HashMap::new())),
+            instance_metrics: Arc::new(RwLock::new(HashMap::new())),
+            instance_health: Arc::new(RwLock::new(HashMap::new())),
             default_ttl_seconds,
         }
     }
@@ -81,6 +109,25 @@ impl AwsCache {
         tracing::debug!("Cached EC2 instances for region {}", region);
     }
 
+    /// Apply the states a start, stop or terminate call reported to the
+    /// cached instances of a region, leaving the entry's age alone. Returns
+    /// the number of cached instances updated.
+    pub async fn update_instance_states(&self, region: &str, transitions: &[crate::aws::InstanceStateTransition]) -> usize {
+        let mut cache = self.ec2_instances.write().await;
+        let Some(entry) = cache.get_mut(region) else {
+            return 0;
+        };
+
+        let mut updated = 0;
+        for instance in entry.data.iter_mut() {
+            if let Some(transition) = transitions.iter().find(|t| t.instance_id == instance.instance_id) {
+                instance.state = transition.current_state.clone();
+                updated += 1;
+            }
+        }
+        updated
+    }
+
     /// Get cached S3 buckets for a region, or None if expired/missing
     pub async fn get_s3_buckets(&self, region: &str) -> Option<Vec<crate::aws::AwsBucket>> {
         let cache = self.s3_buckets.read().await;
@@ -147,6 +194,141 @@ impl AwsCache {
         tracing::debug!("Cached IAM roles");
     }
 
+    /// Cached specs for whichever of `instance_types` are cached and not expired
+    pub async fn get_instance_types(&self, instance_types: &[String]) -> HashMap<String, crate::aws::InstanceTypeSpec> {
+        let cache = self.instance_types.read().await;
+        instance_types
+            .iter()
+            .filter_map(|instance_type| cache.get(instance_type))
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| (entry.data.instance_type.clone(), entry.data.clone()))
+            .collect()
+    }
+
+    /// Cache instance type specs
+    pub async fn put_instance_types(&self, specs: Vec<crate::aws::InstanceTypeSpec>) {
+        let mut cache = self.instance_types.write().await;
+        for spec in specs {
+            cache.insert(spec.instance_type.clone(), CacheEntry::new(spec, INSTANCE_TYPE_TTL_SECONDS));
+        }
+        tracing::debug!("Cached specs for {} instance types", cache.len());
+    }
+
+    /// Get the cached AMI catalog for a region, or None if expired/missing
+    pub async fn get_amis(&self, region: &str) -> Option<Vec<crate::aws::AwsAmi>> {
+        let cache = self.amis.read().await;
+        if let Some(entry) = cache.get(region) {
+            if !entry.is_expired() {
+                tracing::debug!("Cache hit for AMI catalog in region {} (age: {}s)", region, entry.age_seconds());
+                return Some(entry.data.clone());
+            } else {
+                tracing::debug!("Cache expired for AMI catalog in region {} (age: {}s > {}s)", region, entry.age_seconds(), entry.ttl_seconds);
+            }
+        }
+        None
+    }
+
+    /// Cache the AMI catalog for a region
+    pub async fn put_amis(&self, region: String, amis: Vec<crate::aws::AwsAmi>) {
+        let mut cache = self.amis.write().await;
+        cache.insert(region.clone(), CacheEntry::new(amis, AMI_CATALOG_TTL_SECONDS));
+        tracing::debug!("Cached AMI catalog for region {}", region);
+    }
+
+    /// Get cached EBS volumes for a region, or None if expired/missing
+    pub async fn get_volumes(&self, region: &str) -> Option<Vec<crate::aws::AwsEbsVolume>> {
+        let cache = self.volumes.read().await;
+        if let Some(entry) = cache.get(region) {
+            if !entry.is_expired() {
+                tracing::debug!("Cache hit for EBS volumes in region {} (age: {}s)", region, entry.age_seconds());
+                return Some(entry.data.clone());
+            } else {
+                tracing::debug!("Cache expired for EBS volumes in region {} (age: {}s > {}s)", region, entry.age_seconds(), entry.ttl_seconds);
+            }
+        }
+        None
+    }
+
+    /// Cache EBS volumes for a region
+    pub async fn put_volumes(&self, region: String, volumes: Vec<crate::aws::AwsEbsVolume>) {
+        let mut cache = self.volumes.write().await;
+        cache.insert(region.clone(), CacheEntry::new(volumes, self.default_ttl_seconds));
+        tracing::debug!("Cached EBS volumes for region {}", region);
+    }
+
+    /// Drop cached EBS volumes for a region after one is created or changed
+    pub async fn invalidate_volumes(&self, region: &str) {
+        self.volumes.write().await.remove(region);
+    }
+
+    /// Get cached VPCs for a region, or None if expired/missing
+    pub async fn get_vpcs(&self, region: &str) -> Option<Vec<crate::aws::AwsVpc>> {
+        let cache = self.vpcs.read().await;
+        let entry = cache.get(region)?;
+        if entry.is_expired() {
+            return None;
+        }
+        tracing::debug!("Cache hit for VPCs in region {} (age: {}s)", region, entry.age_seconds());
+        Some(entry.data.clone())
+    }
+
+    /// Cache VPCs for a region
+    pub async fn put_vpcs(&self, region: String, vpcs: Vec<crate::aws::AwsVpc>) {
+        let mut cache = self.vpcs.write().await;
+        cache.insert(region, CacheEntry::new(vpcs, self.default_ttl_seconds));
+    }
+
+    /// Get cached subnets (of every VPC) for a region, or None if expired/missing
+    pub async fn get_subnets(&self, region: &str) -> Option<Vec<crate::aws::AwsSubnet>> {
+        let cache = self.subnets.read().await;
+        let entry = cache.get(region)?;
+        if entry.is_expired() {
+            return None;
+        }
+        tracing::debug!("Cache hit for subnets in region {} (age: {}s)", region, entry.age_seconds());
+        Some(entry.data.clone())
+    }
+
+    /// Cache subnets for a region
+    pub async fn put_subnets(&self, region: String, subnets: Vec<crate::aws::AwsSubnet>) {
+        let mut cache = self.subnets.write().await;
+        cache.insert(region, CacheEntry::new(subnets, self.default_ttl_seconds));
+    }
+
+    /// Get an instance's cached metrics for a window, or None if expired/missing
+    pub async fn get_instance_metrics(&self, instance_id: &str, window: MetricWindow) -> Option<InstanceMetrics> {
+        let cache = self.instance_metrics.read().await;
+        let entry = cache.get(&(instance_id.to_string(), window))?;
+        if entry.is_expired() {
+            return None;
+        }
+        tracing::debug!("Cache hit for metrics of {} (age: {}s)", instance_id, entry.age_seconds());
+        Some(entry.data.clone())
+    }
+
+    /// Cache an instance's metrics for a window
+    pub async fn put_instance_metrics(&self, metrics: InstanceMetrics) {
+        let mut cache = self.instance_metrics.write().await;
+        cache.insert((metrics.instance_id.clone(), metrics.window), CacheEntry::new(metrics, METRICS_TTL_SECONDS));
+    }
+
+    /// Get the cached status check summary for a region, or None if expired/missing
+    pub async fn get_instances_health(&self, region: &str) -> Option<InstancesHealthSummary> {
+        let cache = self.instance_health.read().await;
+        let entry = cache.get(region)?;
+        if entry.is_expired() {
+            return None;
+        }
+        tracing::debug!("Cache hit for instance health in region {} (age: {}s)", region, entry.age_seconds());
+        Some(entry.data.clone())
+    }
+
+    /// Cache the status check summary for a region
+    pub async fn put_instances_health(&self, region: String, summary: InstancesHealthSummary) {
+        let mut cache = self.instance_health.write().await;
+        cache.insert(region, CacheEntry::new(summary, METRICS_TTL_SECONDS));
+    }
+
     /// Invalidate all cached data
     pub async fn invalidate_all(&self) {
         let mut ec2_cache = self.ec2_instances.write().await;
@@ -161,6 +343,21 @@ impl AwsCache {
         let mut iam_roles_cache = self.iam_roles.write().await;
         *iam_roles_cache = None;
 
+        let mut instance_types_cache = self.instance_types.write().await;
+        instance_types_cache.clear();
+
+        let mut amis_cache = self.amis.write().await;
+        amis_cache.clear();
+
+        let mut volumes_cache = self.volumes.write().await;
+        volumes_cache.clear();
+
+        self.vpcs.write().await.clear();
+        self.subnets.write().await.clear();
+
+        self.instance_metrics.write().await.clear();
+        self.instance_health.write().await.clear();
+
         tracing::info!("Invalidated all AWS cache entries");
     }
 
@@ -172,6 +369,17 @@ impl AwsCache {
         let mut s3_cache = self.s3_buckets.write().await;
         s3_cache.remove(region);
 
+        let mut amis_cache = self.amis.write().await;
+        amis_cache.remove(region);
+
+        let mut volumes_cache = self.volumes.write().await;
+        volumes_cache.remove(region);
+
+        self.vpcs.write().await.remove(region);
+        self.subnets.write().await.remove(region);
+
+        self.instance_health.write().await.remove(region);
+
         tracing::info!("Invalidated cache for region {}", region);
     }
 
@@ -198,6 +406,31 @@ impl AwsCache {
                 *cache = None;
                 tracing::info!("Invalidated IAM roles cache");
             }
+            CacheType::InstanceTypes => {
+                let mut cache = self.instance_types.write().await;
+                cache.clear();
+                tracing::info!("Invalidated instance types cache");
+            }
+            CacheType::Amis => {
+                let mut cache = self.amis.write().await;
+                cache.clear();
+                tracing::info!("Invalidated AMI catalog cache");
+            }
+            CacheType::Volumes => {
+                let mut cache = self.volumes.write().await;
+                cache.clear();
+                tracing::info!("Invalidated EBS volumes cache");
+            }
+            CacheType::Networks => {
+                self.vpcs.write().await.clear();
+                self.subnets.write().await.clear();
+                tracing::info!("Invalidated VPC and subnet cache");
+            }
+            CacheType::Metrics => {
+                self.instance_metrics.write().await.clear();
+                self.instance_health.write().await.clear();
+                tracing::info!("Invalidated CloudWatch metrics cache");
+            }
         }
     }
 
@@ -207,12 +440,22 @@ impl AwsCache {
         let s3_entries = self.s3_buckets.read().await.len();
         let iam_users_cached = self.iam_users.read().await.is_some();
         let iam_roles_cached = self.iam_roles.read().await.is_some();
+        let instance_types_cached = self.instance_types.read().await.len();
+        let ami_regions_cached = self.amis.read().await.len();
+        let volume_regions_cached = self.volumes.read().await.len();
+        let network_regions_cached = self.vpcs.read().await.len();
+        let instance_metrics_cached = self.instance_metrics.read().await.len();
 
         CacheStats {
             ec2_regions_cached: ec2_entries,
             s3_regions_cached: s3_entries,
             iam_users_cached,
             iam_roles_cached,
+            instance_types_cached,
+            ami_regions_cached,
+            volume_regions_cached,
+            network_regions_cached,
+            instance_metrics_cached,
             default_ttl_seconds: self.default_ttl_seconds,
         }
     }
@@ -274,6 +517,56 @@ impl AwsCache {
             }
         }
 
+        // Clean instance type cache
+        {
+            let mut instance_types_cache = self.instance_types.write().await;
+            let before = instance_types_cache.len();
+            instance_types_cache.retain(|_, entry| !entry.is_expired());
+            cleaned_count += before - instance_types_cache.len();
+        }
+
+        // Clean AMI catalog cache
+        {
+            let mut amis_cache = self.amis.write().await;
+            let before = amis_cache.len();
+            amis_cache.retain(|_, entry| !entry.is_expired());
+            cleaned_count += before - amis_cache.len();
+        }
+
+        // Clean EBS volume cache
+        {
+            let mut volumes_cache = self.volumes.write().await;
+            let before = volumes_cache.len();
+            volumes_cache.retain(|_, entry| !entry.is_expired());
+            cleaned_count += before - volumes_cache.len();
+        }
+
+        // Clean VPC and subnet cache
+        {
+            let mut vpcs_cache = self.vpcs.write().await;
+            let before = vpcs_cache.len();
+            vpcs_cache.retain(|_, entry| !entry.is_expired());
+            cleaned_count += before - vpcs_cache.len();
+
+            let mut subnets_cache = self.subnets.write().await;
+            let before = subnets_cache.len();
+            subnets_cache.retain(|_, entry| !entry.is_expired());
+            cleaned_count += before - subnets_cache.len();
+        }
+
+        // Clean CloudWatch metrics cache
+        {
+            let mut metrics_cache = self.instance_metrics.write().await;
+            let before = metrics_cache.len();
+            metrics_cache.retain(|_, entry| !entry.is_expired());
+            cleaned_count += before - metrics_cache.len();
+
+            let mut health_cache = self.instance_health.write().await;
+            let before = health_cache.len();
+            health_cache.retain(|_, entry| !entry.is_expired());
+            cleaned_count += before - health_cache.len();
+        }
+
         if cleaned_count > 0 {
             tracing::info!("Cleaned {} expired cache entries", cleaned_count);
         }
@@ -286,6 +579,11 @@ pub enum CacheType {
     S3Buckets,
     IamUsers,
     IamRoles,
+    InstanceTypes,
+    Amis,
+    Volumes,
+    Networks,
+    Metrics,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -294,6 +592,11 @@ pub struct CacheStats {
     pub s3_regions_cached: usize,
     pub iam_users_cached: bool,
     pub iam_roles_cached: bool,
+    pub instance_types_cached: usize,
+    pub ami_regions_cached: usize,
+    pub volume_regions_cached: usize,
+    pub network_regions_cached: usize,
+    pub instance_metrics_cached: usize,
     pub default_ttl_seconds: i64,
 }
 
@@ -305,7 +608,7 @@ pub struct CacheRefresher {
     iam_service: crate::aws::iam::IamService,
     event_emitter: std::sync::Arc<crate::aws::events::AwsEventEmitter>,
     debounce_instances: Mutex<crate::aws::events::DebouncedEmitter>,
-    debounce_costs: Mutex<crate::aws::events::DebouncedEmitter>,
+    account_gate: Option<crate::aws::manager::AccountGate>,
 }
 
 impl CacheRefresher {
@@ -323,17 +626,29 @@ impl CacheRefresher {
             iam_service,
             event_emitter,
             debounce_instances: Mutex::new(crate::aws::events::DebouncedEmitter::new(std::time::Duration::from_secs(30))),
-            debounce_costs: Mutex::new(crate::aws::events::DebouncedEmitter::new(std::time::Duration::from_secs(30))),
+            account_gate: None,
         }
     }
 
+    /// Skip background refreshes while the account is disabled
+    pub fn with_account_gate(mut self, gate: crate::aws::manager::AccountGate) -> Self {
+        self.account_gate = Some(gate);
+        self
+    }
+
     /// Refresh all cached data with debounced event emission
     pub async fn refresh_all(&self) -> AwsResult<()> {
         tracing::info!("Starting cache refresh for all AWS resources");
 
         // Refresh EC2 instances
         match self.ec2_service.collect_instances().await {
-            Ok(instances) => {
+            Ok(mut instances) => {
+                let reservations = self.ec2_service.collect_reserved_instances().await.unwrap_or_else(|e| {
+                    tracing::warn!("Failed to collect reserved instances, showing instances as on-demand: {:?}", e);
+                    Vec::new()
+                });
+                crate::aws::reservations::annotate_coverage(&mut instances, &reservations);
+
                 // Group by region and cache
                 let mut region_instances: HashMap<String, Vec<crate::aws::AwsInstance>> = HashMap::new();
                 for instance in instances {
@@ -405,6 +720,13 @@ impl CacheRefresher {
             loop {
                 tokio::time::sleep(interval).await;
 
+                if let Some(gate) = &self.account_gate {
+                    if !gate.is_active().await {
+                        tracing::debug!("Skipping cache refresh for inactive account");
+                        continue;
+                    }
+                }
+
                 if let Err(e) = self.refresh_all().await {
                     tracing::error!("Background cache refresh failed: {:?}", e);
                 }
@@ -4,15 +4,51 @@
 // AWS SDK client initialization and configuration
 // ============================================================================
 
+use crate::aws::cache::{AwsCache, DEFAULT_CACHE_TTL_SECONDS};
+use crate::aws::permissions::{missing_permissions, policy_source_arn, PermissionCache};
+use crate::aws::config::{AssumeRoleConfig, Timeouts};
 use crate::aws::{AwsConfig, AwsError, AwsResult};
+use aws_config::retry::RetryConfig;
+use aws_config::timeout::TimeoutConfig;
 use aws_config::{BehaviorVersion, Region};
+use aws_credential_types::provider::{error::CredentialsError, future, ProvideCredentials};
 use aws_credential_types::Credentials;
 use aws_sdk_ec2::Client as Ec2Client;
 use aws_sdk_s3::Client as S3Client;
 use aws_sdk_iam::Client as IamClient;
 use aws_sdk_rds::Client as RdsClient;
 use aws_sdk_lambda::Client as LambdaClient;
+use aws_sdk_costexplorer::Client as CostExplorerClient;
+use aws_sdk_health::Client as HealthClient;
+use aws_sdk_cloudwatch::Client as CloudWatchClient;
+use aws_sdk_computeoptimizer::Client as ComputeOptimizerClient;
+use aws_sdk_sts::operation::assume_role::AssumeRoleOutput;
+use aws_sdk_sts::operation::get_caller_identity::GetCallerIdentityOutput;
+use aws_sdk_sts::Client as StsClient;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
 
+/// Session name shown in CloudTrail for calls made through an assumed role
+const ASSUME_ROLE_SESSION_NAME: &str = "pocket-architect";
+
+/// Who a set of credentials belongs to, from sts:GetCallerIdentity
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CallerIdentity {
+    pub account_id: String,
+    pub arn: String,
+    pub user_id: String,
+}
+
+/// Per-request timeouts for the SDK clients. A request that takes longer
+/// fails its attempt, and the SDK's own retries or `with_retry` take it from
+/// there.
+pub fn sdk_timeouts(timeouts: &Timeouts) -> TimeoutConfig {
+    TimeoutConfig::builder()
+        .connect_timeout(Duration::from_secs(timeouts.connect_seconds))
+        .operation_attempt_timeout(Duration::from_secs(timeouts.request_seconds))
+        .build()
+}
 
 #[derive(Clone)]
 pub struct AwsClient {
@@ -22,12 +58,37 @@ pub struct AwsClient {
     pub iam_client: IamClient,
     pub rds_client: RdsClient,
     pub lambda_client: LambdaClient,
+    pub sts_client: StsClient,
+    /// Cost Explorer is only served from us-east-1, whatever the client's region
+    pub ce_client: CostExplorerClient,
+    /// The AWS Health API is global and served from us-east-1
+    pub health_client: HealthClient,
+    pub cloudwatch_client: CloudWatchClient,
+    pub compute_optimizer_client: ComputeOptimizerClient,
+    /// Identity verified when the client was built with `new`
+    pub identity: Option<CallerIdentity>,
+    /// Shared by clones so cached clients keep their simulated decisions
+    pub permission_cache: Arc<PermissionCache>,
+    /// Resource data cached for this client; clones share it
+    pub cache: AwsCache,
 }
 
 impl AwsClient {
     pub async fn new(config: AwsConfig) -> AwsResult<Self> {
         tracing::info!("Initializing AWS client for region: {}", config.region);
 
+        let mut client = Self::from_config(config).await;
+
+        // Test the connection and learn which AWS account the credentials belong to
+        let identity = client.caller_identity().await?;
+        tracing::info!("AWS client initialized successfully for account {}", identity.account_id);
+        client.identity = Some(identity);
+
+        Ok(client)
+    }
+
+    /// Build the SDK clients for a config without testing the connection
+    pub async fn from_config(config: AwsConfig) -> Self {
         let region = Region::new(config.region.clone());
 
         let credentials = Credentials::new(
@@ -38,37 +99,90 @@ impl AwsClient {
             "pocket-architect",
         );
 
-        let aws_config = aws_config::defaults(BehaviorVersion::v2025_08_07())
-            .region(region)
+        let mut aws_config = aws_config::defaults(BehaviorVersion::v2025_08_07())
+            .region(region.clone())
             .credentials_provider(credentials)
+            .timeout_config(sdk_timeouts(&config.timeouts))
+            // Calls `with_retry` repeats opt out of these per call (see
+            // `retry::single_attempt_ec2`), so the two layers don't multiply
+            .retry_config(RetryConfig::standard())
             .load()
             .await;
 
-        let ec2_client = Ec2Client::new(&aws_config);
-        let s3_client = S3Client::new(&aws_config);
-        let iam_client = IamClient::new(&aws_config);
-        let rds_client = RdsClient::new(&aws_config);
-        let lambda_client = LambdaClient::new(&aws_config);
-
-        // Test the connection
-        Self::test_connection(&ec2_client).await?;
+        // Every service client uses the role's temporary credentials; the SDK's
+        // identity cache re-assumes the role shortly before they expire
+        if let Some(role) = &config.role {
+            tracing::info!("Assuming role {} for AWS access", role.role_arn);
+            let provider = AssumedRoleProvider {
+                sts_client: StsClient::new(&aws_config),
+                role: role.clone(),
+            };
+            aws_config = aws_config::defaults(BehaviorVersion::v2025_08_07())
+                .region(region)
+                .credentials_provider(provider)
+                .timeout_config(sdk_timeouts(&config.timeouts))
+                .retry_config(RetryConfig::standard())
+                .load()
+                .await;
+        }
 
-        tracing::info!("AWS client initialized successfully");
-        Ok(Self {
+        Self {
+            ec2_client: Ec2Client::new(&aws_config),
+            s3_client: S3Client::new(&aws_config),
+            iam_client: IamClient::new(&aws_config),
+            rds_client: RdsClient::new(&aws_config),
+            lambda_client: LambdaClient::new(&aws_config),
+            sts_client: StsClient::new(&aws_config),
+            ce_client: CostExplorerClient::from_conf(
+                aws_sdk_costexplorer::config::Builder::from(&aws_config)
+                    .region(Region::new("us-east-1"))
+                    .build(),
+            ),
+            health_client: HealthClient::from_conf(
+                aws_sdk_health::config::Builder::from(&aws_config)
+                    .region(Region::new("us-east-1"))
+                    .build(),
+            ),
+            cloudwatch_client: CloudWatchClient::new(&aws_config),
+            compute_optimizer_client: ComputeOptimizerClient::new(&aws_config),
+            identity: None,
+            permission_cache: Arc::new(PermissionCache::default()),
+            cache: AwsCache::new(DEFAULT_CACHE_TTL_SECONDS),
             config,
-            ec2_client,
-            s3_client,
-            iam_client,
-            rds_client,
-            lambda_client,
-        })
+        }
+    }
+
+    /// Client for another region using this client's credentials and settings,
+    /// used for cross-region fallback
+    pub async fn for_region(&self, region: &str) -> AwsResult<Self> {
+        tracing::debug!("Creating cross-region AWS client for region: {}", region);
+        let region = Region::new(region.to_string());
+
+        // The regional SDK clients are copies of this client's with another region,
+        // so they keep its credentials provider, HTTP client, timeouts and retries.
+        // IAM is global, and Cost Explorer and Health stay in us-east-1. The identity
+        // verified for this client still holds, and the cache is keyed by region
+        // where it matters, so both are shared with the new client.
+        let mut client = self.clone();
+        client.config = self.config.for_region(region.as_ref());
+        client.ec2_client = Ec2Client::from_conf(self.ec2_client.config().to_builder().region(region.clone()).build());
+        client.s3_client = S3Client::from_conf(self.s3_client.config().to_builder().region(region.clone()).build());
+        client.rds_client = RdsClient::from_conf(self.rds_client.config().to_builder().region(region.clone()).build());
+        client.lambda_client = LambdaClient::from_conf(self.lambda_client.config().to_builder().region(region.clone()).build());
+        client.sts_client = StsClient::from_conf(self.sts_client.config().to_builder().region(region.clone()).build());
+        client.cloudwatch_client = CloudWatchClient::from_conf(self.cloudwatch_client.config().to_builder().region(region.clone()).build());
+        client.compute_optimizer_client = ComputeOptimizerClient::from_conf(
+            self.compute_optimizer_client.config().to_builder().region(region).build(),
+        );
+        Ok(client)
     }
 
-    async fn test_connection(ec2_client: &Ec2Client) -> AwsResult<()> {
-        tracing::debug!("Testing AWS connection with describe-regions");
+    /// Resolve the AWS account and principal behind this client's credentials
+    pub async fn caller_identity(&self) -> AwsResult<CallerIdentity> {
+        tracing::debug!("Testing AWS connection with sts:GetCallerIdentity");
 
-        ec2_client
-            .describe_regions()
+        let response = self.sts_client
+            .get_caller_identity()
             .send()
             .await
             .map_err(|e| {
@@ -76,9 +190,49 @@ impl AwsClient {
                 AwsError::AuthError(format!("Failed to connect to AWS: {}", e))
             })?;
 
-        tracing::debug!("AWS connection test successful");
-    Ok(())
-}
+        caller_identity_from_response(&response)
+    }
+
+    /// Actions from `actions` the caller's IAM policies do not allow, using
+    /// iam:SimulatePrincipalPolicy. Decisions are cached for a few minutes.
+    pub async fn check_permissions(&self, actions: &[&str]) -> AwsResult<Vec<String>> {
+        let identity = match &self.identity {
+            Some(identity) => identity.clone(),
+            None => self.caller_identity().await?,
+        };
+        let Some(source_arn) = policy_source_arn(&identity.arn) else {
+            tracing::debug!("Skipping permission simulation for {}", identity.arn);
+            return Ok(Vec::new());
+        };
+
+        missing_permissions(&self.permission_cache, actions, |uncached| async move {
+            tracing::debug!("Simulating {} IAM actions for {}", uncached.len(), source_arn);
+
+            let mut results = Vec::new();
+            let mut marker: Option<String> = None;
+            loop {
+                let response = self.iam_client
+                    .simulate_principal_policy()
+                    .policy_source_arn(&source_arn)
+                    .set_action_names(Some(uncached.clone()))
+                    .set_marker(marker.take())
+                    .send()
+                    .await
+                    .map_err(|e| AwsError::PermissionError(format!(
+                        "Failed to simulate IAM policies: {}",
+                        aws_sdk_iam::error::DisplayErrorContext(&e)
+                    )))?;
+
+                results.extend(response.evaluation_results().iter().cloned());
+                marker = response.marker().map(str::to_string);
+                if !response.is_truncated() || marker.is_none() {
+                    break;
+                }
+            }
+            Ok(results)
+        })
+        .await
+    }
 
     pub fn primary_region(&self) -> &str {
         self.config.primary_region()
@@ -94,12 +248,42 @@ impl AwsClient {
         ec2_service.collect_instances().await
     }
 
+    /// Collect the EC2 instances matching `filters`
+    pub async fn collect_matching_instances(&self, filters: &crate::aws::InstanceFilters) -> AwsResult<Vec<crate::aws::AwsInstance>> {
+        let ec2_service = crate::aws::ec2::Ec2Service::new(self.clone());
+        ec2_service.collect_matching_instances(filters).await
+    }
+
+    /// Collect the region's active reserved instances
+    pub async fn collect_reserved_instances(&self) -> AwsResult<Vec<crate::aws::AwsReservedInstance>> {
+        let ec2_service = crate::aws::ec2::Ec2Service::new(self.clone());
+        ec2_service.collect_reserved_instances().await
+    }
+
     /// Collect S3 buckets using the S3 service
     pub async fn collect_buckets(&self) -> AwsResult<Vec<crate::aws::AwsBucket>> {
         let s3_service = crate::aws::s3::S3Service::new(self.clone());
         s3_service.collect_buckets().await
     }
 
+    /// Collect EC2 instances from each of the given regions
+    pub async fn collect_instances_in_regions(&self, regions: &[String]) -> crate::aws::RegionalCollection<crate::aws::AwsInstance> {
+        let ec2_service = crate::aws::ec2::Ec2Service::new(self.clone());
+        ec2_service.collect_instances_in_regions(regions).await
+    }
+
+    /// Collect Elastic IPs from each of the given regions
+    pub async fn collect_elastic_ips_in_regions(&self, regions: &[String]) -> crate::aws::RegionalCollection<crate::aws::AwsElasticIp> {
+        let ec2_service = crate::aws::ec2::Ec2Service::new(self.clone());
+        ec2_service.collect_elastic_ips_in_regions(regions).await
+    }
+
+    /// Collect S3 buckets located in each of the given regions
+    pub async fn collect_buckets_in_regions(&self, regions: &[String]) -> crate::aws::RegionalCollection<crate::aws::AwsBucket> {
+        let s3_service = crate::aws::s3::S3Service::new(self.clone());
+        s3_service.collect_buckets_in_regions(regions).await
+    }
+
     /// Collect Lambda functions using the Lambda service
     pub async fn collect_lambda_functions(&self) -> AwsResult<Vec<crate::aws::AwsLambdaFunction>> {
         let lambda_service = crate::aws::lambda::LambdaService::new(self.clone());
@@ -108,11 +292,11 @@ impl AwsClient {
 }
 
 // Public test connection function that takes credentials
-pub async fn test_connection(access_key: &str, secret_key: &str) -> AwsResult<()> {
+pub async fn test_connection(access_key: &str, secret_key: &str) -> AwsResult<CallerIdentity> {
     tracing::debug!("Testing AWS connection with provided credentials");
 
-    // Create a temporary client just for testing
-    let region = Region::new("us-east-1"); // Use us-east-1 for basic connectivity test
+    // Create a temporary client just for testing; STS is global so any region works
+    let region = Region::new("us-east-1");
     let credentials = Credentials::new(access_key, secret_key, None, None, "test");
 
     let config = aws_config::defaults(BehaviorVersion::latest())
@@ -121,11 +305,8 @@ pub async fn test_connection(access_key: &str, secret_key: &str) -> AwsResult<()
         .load()
         .await;
 
-    let ec2_client = Ec2Client::new(&config);
-
-    // Test connection by describing regions
-    ec2_client
-        .describe_regions()
+    let response = StsClient::new(&config)
+        .get_caller_identity()
         .send()
         .await
         .map_err(|e| {
@@ -133,6 +314,74 @@ pub async fn test_connection(access_key: &str, secret_key: &str) -> AwsResult<()
             AwsError::AuthError(format!("Failed to connect to AWS: {}", e))
         })?;
 
-    tracing::debug!("AWS connection test successful");
-    Ok(())
-}
\ No newline at end of file
+    let identity = caller_identity_from_response(&response)?;
+    tracing::debug!("AWS connection test successful for account {}", identity.account_id);
+    Ok(identity)
+}
+
+/// Credentials provider that assumes a role using the base credentials' STS client
+#[derive(Debug)]
+struct AssumedRoleProvider {
+    sts_client: StsClient,
+    role: AssumeRoleConfig,
+}
+
+impl ProvideCredentials for AssumedRoleProvider {
+    fn provide_credentials<'a>(&'a self) -> future::ProvideCredentials<'a>
+    where
+        Self: 'a,
+    {
+        future::ProvideCredentials::new(async move {
+            assume_role_credentials(&self.role, |role| {
+                self.sts_client
+                    .assume_role()
+                    .role_arn(&role.role_arn)
+                    .set_external_id(role.external_id.clone())
+                    .role_session_name(ASSUME_ROLE_SESSION_NAME)
+                    .send()
+            })
+            .await
+            .map_err(CredentialsError::provider_error)
+        })
+    }
+}
+
+/// Temporary credentials for `role` from the response of an sts:AssumeRole
+/// call made by `assume`
+pub async fn assume_role_credentials<F, Fut, E>(role: &AssumeRoleConfig, assume: F) -> AwsResult<Credentials>
+where
+    F: FnOnce(&AssumeRoleConfig) -> Fut,
+    Fut: Future<Output = Result<AssumeRoleOutput, E>>,
+    E: std::fmt::Display,
+{
+    tracing::debug!("Requesting temporary credentials for role {}", role.role_arn);
+
+    let response = assume(role)
+        .await
+        .map_err(|e| AwsError::AuthError(format!("Failed to assume role {}: {}", role.role_arn, e)))?;
+    let credentials = response.credentials()
+        .ok_or_else(|| AwsError::AuthError(format!("AssumeRole for {} returned no credentials", role.role_arn)))?;
+    let expiry = std::time::SystemTime::try_from(*credentials.expiration())
+        .map_err(|e| AwsError::AuthError(format!("Invalid credential expiry: {}", e)))?;
+
+    Ok(Credentials::new(
+        credentials.access_key_id(),
+        credentials.secret_access_key(),
+        Some(credentials.session_token().to_string()),
+        Some(expiry),
+        "pocket-architect-assume-role",
+    ))
+}
+
+/// Account, ARN and user id from a GetCallerIdentity response
+pub fn caller_identity_from_response(response: &GetCallerIdentityOutput) -> AwsResult<CallerIdentity> {
+    let account_id = response.account()
+        .filter(|account| account.len() == 12 && account.chars().all(|c| c.is_ascii_digit()))
+        .ok_or_else(|| AwsError::OperationError("GetCallerIdentity returned no valid account id".to_string()))?;
+
+    Ok(CallerIdentity {
+        account_id: account_id.to_string(),
+        arn: response.arn().unwrap_or_default().to_string(),
+        user_id: response.user_id().unwrap_or_default().to_string(),
+    })
+}
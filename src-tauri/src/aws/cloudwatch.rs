@@ -0,0 +1,445 @@
+// ============================================================================
+// CLOUDWATCH METRICS
+// ============================================================================
+// Instance utilization and status check time series from GetMetricData
+// ============================================================================
+
+use crate::aws::{AwsClient, AwsError, AwsResult};
+use aws_sdk_cloudwatch::error::DisplayErrorContext;
+use aws_sdk_cloudwatch::primitives::DateTime as AwsDateTime;
+use aws_sdk_cloudwatch::types::{Dimension, Metric, MetricDataQuery, MetricDataResult, MetricStat, ScanBy};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const EC2_NAMESPACE: &str = "AWS/EC2";
+
+/// Query id, CloudWatch metric name and statistic for each collected series
+const INSTANCE_METRICS: [(&str, &str, &str); 4] = [
+    ("cpu_utilization", "CPUUtilization", "Average"),
+    ("network_in", "NetworkIn", "Sum"),
+    ("network_out", "NetworkOut", "Sum"),
+    ("status_check_failed", "StatusCheckFailed", "Maximum"),
+];
+
+const STATUS_CHECK_QUERY_ID: &str = "status_check_failed";
+
+/// Namespace the CloudWatch agent publishes to, and the query id of its root
+/// filesystem usage
+const AGENT_NAMESPACE: &str = "CWAgent";
+const DISK_USED_QUERY_ID: &str = "disk_used_percent";
+
+/// Most queries GetMetricData accepts in one request
+pub const MAX_METRIC_QUERIES: usize = 500;
+
+/// How far back metrics are fetched, and at what resolution
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MetricWindow {
+    #[default]
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "6h")]
+    SixHours,
+    #[serde(rename = "24h")]
+    OneDay,
+    #[serde(rename = "7d")]
+    SevenDays,
+}
+
+impl MetricWindow {
+    /// Parse "1h", "6h", "24h" or "7d"
+    pub fn parse(value: &str) -> AwsResult<Self> {
+        match value {
+            "1h" => Ok(Self::OneHour),
+            "6h" => Ok(Self::SixHours),
+            "24h" => Ok(Self::OneDay),
+            "7d" => Ok(Self::SevenDays),
+            other => Err(AwsError::ConfigError(format!(
+                "Unsupported metric period '{}'; expected 1h, 6h, 24h or 7d",
+                other
+            ))),
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            Self::OneHour => Duration::hours(1),
+            Self::SixHours => Duration::hours(6),
+            Self::OneDay => Duration::hours(24),
+            Self::SevenDays => Duration::days(7),
+        }
+    }
+
+    /// Seconds per data point. Basic monitoring publishes every five minutes,
+    /// so shorter windows can't go finer than that.
+    pub fn resolution_seconds(&self) -> i32 {
+        match self {
+            Self::OneHour | Self::SixHours => 300,
+            Self::OneDay => 900,
+            Self::SevenDays => 3600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricPoint {
+    pub timestamp: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceMetrics {
+    pub instance_id: String,
+    pub window: MetricWindow,
+    pub resolution_seconds: i32,
+    /// Average CPU utilization, in percent
+    pub cpu_utilization: Vec<MetricPoint>,
+    /// Bytes received per data point
+    pub network_in: Vec<MetricPoint>,
+    /// Bytes sent per data point
+    pub network_out: Vec<MetricPoint>,
+    /// 1 when either status check failed during the data point
+    pub status_check_failed: Vec<MetricPoint>,
+    /// The most recent status check data point reports a failure
+    pub status_checks_failing: bool,
+}
+
+/// Status checks of one instance in a health summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceStatusChecks {
+    pub instance_id: String,
+    /// 1 when either status check failed during the data point
+    pub status_check_failed: Vec<MetricPoint>,
+    /// The most recent data point reports a failure
+    pub status_checks_failing: bool,
+}
+
+/// Status checks of every running instance in a region
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstancesHealthSummary {
+    pub region: String,
+    pub window: MetricWindow,
+    pub resolution_seconds: i32,
+    pub instances: Vec<InstanceStatusChecks>,
+    /// Instances whose status checks are currently failing
+    pub failing: usize,
+}
+
+/// Where a storage utilization figure comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageUtilizationSource {
+    /// The CloudWatch agent's disk_used_percent metric
+    CloudwatchAgent,
+    /// The agent isn't publishing disk metrics for the instance
+    Unavailable,
+}
+
+/// Provisioned EBS storage of an instance and how much of its root
+/// filesystem is in use
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageUtilization {
+    pub instance_id: String,
+    pub provisioned_gb: i64,
+    /// Latest disk_used_percent of the root filesystem; None without the agent
+    pub used_percent: Option<f64>,
+    pub source: StorageUtilizationSource,
+}
+
+/// Query for one EC2 metric of an instance at the window's resolution
+fn metric_query(query_id: &str, instance_id: &str, metric_name: &str, stat: &str, window: MetricWindow) -> MetricDataQuery {
+    let metric = Metric::builder()
+        .namespace(EC2_NAMESPACE)
+        .metric_name(metric_name)
+        .dimensions(Dimension::builder().name("InstanceId").value(instance_id).build())
+        .build();
+
+    MetricDataQuery::builder()
+        .id(query_id)
+        .metric_stat(
+            MetricStat::builder()
+                .metric(metric)
+                .period(window.resolution_seconds())
+                .stat(stat)
+                .build(),
+        )
+        .return_data(true)
+        .build()
+}
+
+/// GetMetricData queries for an instance's metrics at the window's resolution
+pub fn metric_queries(instance_id: &str, window: MetricWindow) -> Vec<MetricDataQuery> {
+    INSTANCE_METRICS
+        .iter()
+        .map(|(id, metric_name, stat)| metric_query(id, instance_id, metric_name, stat, window))
+        .collect()
+}
+
+/// Query for the root filesystem's disk_used_percent as published by the
+/// CloudWatch agent. The agent adds dimensions (device, fstype, and sometimes
+/// image id or instance type) that vary by config, so the series is found by
+/// search rather than by an exact dimension set.
+pub fn disk_used_query(instance_id: &str, window: MetricWindow) -> MetricDataQuery {
+    let search = format!(
+        "MAX(SEARCH('Namespace=\"{}\" MetricName=\"disk_used_percent\" InstanceId=\"{}\" path=\"/\"', 'Average', {}))",
+        AGENT_NAMESPACE,
+        instance_id,
+        window.resolution_seconds()
+    );
+
+    MetricDataQuery::builder()
+        .id(DISK_USED_QUERY_ID)
+        .expression(search)
+        .return_data(true)
+        .build()
+}
+
+/// StatusCheckFailed queries for several instances, split into requests of at
+/// most `MAX_METRIC_QUERIES`. Query ids are `status_<index into instance_ids>`.
+pub fn status_check_queries(instance_ids: &[String], window: MetricWindow) -> Vec<Vec<MetricDataQuery>> {
+    let queries: Vec<MetricDataQuery> = instance_ids
+        .iter()
+        .enumerate()
+        .map(|(index, instance_id)| {
+            metric_query(&format!("status_{}", index), instance_id, "StatusCheckFailed", "Maximum", window)
+        })
+        .collect();
+
+    queries.chunks(MAX_METRIC_QUERIES).map(<[MetricDataQuery]>::to_vec).collect()
+}
+
+/// Average CPUUtilization of each instance over the last `window_hours`, as
+/// one data point per instance, split into requests of at most
+/// `MAX_METRIC_QUERIES`. Query ids are `cpu_<index into instance_ids>`.
+pub fn average_cpu_queries(instance_ids: &[String], window_hours: i64) -> Vec<Vec<MetricDataQuery>> {
+    let period = (window_hours * 3600) as i32;
+    let queries: Vec<MetricDataQuery> = instance_ids
+        .iter()
+        .enumerate()
+        .map(|(index, instance_id)| {
+            let metric = Metric::builder()
+                .namespace(EC2_NAMESPACE)
+                .metric_name("CPUUtilization")
+                .dimensions(Dimension::builder().name("InstanceId").value(instance_id).build())
+                .build();
+            MetricDataQuery::builder()
+                .id(format!("cpu_{}", index))
+                .metric_stat(MetricStat::builder().metric(metric).period(period).stat("Average").build())
+                .return_data(true)
+                .build()
+        })
+        .collect();
+
+    queries.chunks(MAX_METRIC_QUERIES).map(<[MetricDataQuery]>::to_vec).collect()
+}
+
+/// Average CPU per instance from the results of `average_cpu_queries`. When
+/// the window straddles a period boundary the data points are averaged.
+/// Instances without data points are left out.
+pub fn average_cpu_from_results(instance_ids: &[String], results: &[MetricDataResult]) -> HashMap<String, f64> {
+    let mut series = series_by_query(results);
+
+    instance_ids
+        .iter()
+        .enumerate()
+        .filter_map(|(index, instance_id)| {
+            let points = series.remove(&format!("cpu_{}", index))?;
+            if points.is_empty() {
+                return None;
+            }
+            let average = points.iter().map(|point| point.value).sum::<f64>() / points.len() as f64;
+            Some((instance_id.clone(), average))
+        })
+        .collect()
+}
+
+/// Points of each query id, oldest first. Results for one query may be split
+/// across pages.
+fn series_by_query(results: &[MetricDataResult]) -> HashMap<String, Vec<MetricPoint>> {
+    let mut series: HashMap<String, Vec<(i64, MetricPoint)>> = HashMap::new();
+
+    for result in results {
+        let Some(id) = result.id() else { continue };
+        let points = series.entry(id.to_string()).or_default();
+        for (timestamp, value) in result.timestamps().iter().zip(result.values()) {
+            let Some(time) = DateTime::<Utc>::from_timestamp(timestamp.secs(), timestamp.subsec_nanos()) else {
+                continue;
+            };
+            points.push((timestamp.secs(), MetricPoint { timestamp: time.to_rfc3339(), value: *value }));
+        }
+    }
+
+    series
+        .into_iter()
+        .map(|(id, mut points)| {
+            points.sort_by_key(|(secs, _)| *secs);
+            (id, points.into_iter().map(|(_, point)| point).collect())
+        })
+        .collect()
+}
+
+fn latest_failing(points: &[MetricPoint]) -> bool {
+    points.last().is_some_and(|point| point.value > 0.0)
+}
+
+/// Group GetMetricData results into per-metric series, oldest point first
+pub fn metrics_from_results(instance_id: &str, window: MetricWindow, results: &[MetricDataResult]) -> InstanceMetrics {
+    let mut series = series_by_query(results);
+    let mut take = |id: &str| series.remove(id).unwrap_or_default();
+
+    let cpu_utilization = take("cpu_utilization");
+    let network_in = take("network_in");
+    let network_out = take("network_out");
+    let status_check_failed = take(STATUS_CHECK_QUERY_ID);
+    let status_checks_failing = latest_failing(&status_check_failed);
+
+    InstanceMetrics {
+        instance_id: instance_id.to_string(),
+        window,
+        resolution_seconds: window.resolution_seconds(),
+        cpu_utilization,
+        network_in,
+        network_out,
+        status_check_failed,
+        status_checks_failing,
+    }
+}
+
+/// Storage utilization from the results of `disk_used_query`, marked
+/// unavailable when the agent published no data points
+pub fn storage_utilization_from_results(instance_id: &str, provisioned_gb: i64, results: &[MetricDataResult]) -> StorageUtilization {
+    let used_percent = series_by_query(results)
+        .remove(DISK_USED_QUERY_ID)
+        .and_then(|points| points.last().map(|point| point.value));
+
+    StorageUtilization {
+        instance_id: instance_id.to_string(),
+        provisioned_gb,
+        used_percent,
+        source: match used_percent {
+            Some(_) => StorageUtilizationSource::CloudwatchAgent,
+            None => StorageUtilizationSource::Unavailable,
+        },
+    }
+}
+
+/// Build a health summary from the results of `status_check_queries`.
+/// Instances without data points are listed as passing.
+pub fn health_summary_from_results(region: &str, instance_ids: &[String], window: MetricWindow, results: &[MetricDataResult]) -> InstancesHealthSummary {
+    let mut series = series_by_query(results);
+
+    let instances: Vec<InstanceStatusChecks> = instance_ids
+        .iter()
+        .enumerate()
+        .map(|(index, instance_id)| {
+            let status_check_failed = series.remove(&format!("status_{}", index)).unwrap_or_default();
+            InstanceStatusChecks {
+                instance_id: instance_id.clone(),
+                status_checks_failing: latest_failing(&status_check_failed),
+                status_check_failed,
+            }
+        })
+        .collect();
+
+    InstancesHealthSummary {
+        region: region.to_string(),
+        window,
+        resolution_seconds: window.resolution_seconds(),
+        failing: instances.iter().filter(|i| i.status_checks_failing).count(),
+        instances,
+    }
+}
+
+/// Run GetMetricData for the window ending now and return every page's results
+async fn get_metric_data(client: &AwsClient, queries: Vec<MetricDataQuery>, window: MetricWindow, subject: &str) -> AwsResult<Vec<MetricDataResult>> {
+    get_metric_data_since(client, queries, window.duration(), subject).await
+}
+
+/// Run GetMetricData for the `lookback` ending now and return every page's results
+async fn get_metric_data_since(client: &AwsClient, queries: Vec<MetricDataQuery>, lookback: Duration, subject: &str) -> AwsResult<Vec<MetricDataResult>> {
+    let end = Utc::now();
+    let start = end - lookback;
+
+    let pages = client.cloudwatch_client
+        .get_metric_data()
+        .set_metric_data_queries(Some(queries))
+        .start_time(AwsDateTime::from_secs(start.timestamp()))
+        .end_time(AwsDateTime::from_secs(end.timestamp()))
+        .scan_by(ScanBy::TimestampAscending)
+        .into_paginator()
+        .send()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .map_err(|e| AwsError::OperationError(format!("Failed to get CloudWatch metrics for {}: {}", subject, DisplayErrorContext(&e))))?;
+
+    Ok(pages
+        .iter()
+        .flat_map(|page| page.metric_data_results().iter().cloned())
+        .collect())
+}
+
+/// Fetch an instance's utilization and status check metrics for the window
+/// ending now
+pub async fn fetch_instance_metrics(client: &AwsClient, instance_id: &str, window: MetricWindow) -> AwsResult<InstanceMetrics> {
+    tracing::info!("Fetching CloudWatch metrics for {} over {:?}", instance_id, window);
+
+    let results = get_metric_data(client, metric_queries(instance_id, window), window, instance_id).await?;
+    Ok(metrics_from_results(instance_id, window, &results))
+}
+
+/// Root filesystem usage over the last hour next to `provisioned_gb`
+pub async fn fetch_storage_utilization(client: &AwsClient, instance_id: &str, provisioned_gb: i64) -> AwsResult<StorageUtilization> {
+    let window = MetricWindow::OneHour;
+    let results = get_metric_data(client, vec![disk_used_query(instance_id, window)], window, instance_id).await?;
+    Ok(storage_utilization_from_results(instance_id, provisioned_gb, &results))
+}
+
+/// Average CPU of each instance over the last `window_hours`
+pub async fn fetch_average_cpu(client: &AwsClient, instance_ids: &[String], window_hours: i64) -> AwsResult<HashMap<String, f64>> {
+    let region = client.primary_region().to_string();
+    let mut results = Vec::new();
+    for queries in average_cpu_queries(instance_ids, window_hours) {
+        results.extend(get_metric_data_since(client, queries, Duration::hours(window_hours), &region).await?);
+    }
+    Ok(average_cpu_from_results(instance_ids, &results))
+}
+
+/// `fetch_instance_metrics`, served from the client's cache for up to a minute
+pub async fn cached_instance_metrics(client: &AwsClient, instance_id: &str, window: MetricWindow) -> AwsResult<InstanceMetrics> {
+    if let Some(metrics) = client.cache.get_instance_metrics(instance_id, window).await {
+        return Ok(metrics);
+    }
+
+    let metrics = fetch_instance_metrics(client, instance_id, window).await?;
+    client.cache.put_instance_metrics(metrics.clone()).await;
+    Ok(metrics)
+}
+
+/// Status checks over the last hour for every running instance in the client's
+/// region, batched into as few GetMetricData calls as possible and cached for
+/// up to a minute
+pub async fn instances_health_summary(client: &AwsClient) -> AwsResult<InstancesHealthSummary> {
+    let region = client.primary_region().to_string();
+    if let Some(summary) = client.cache.get_instances_health(&region).await {
+        return Ok(summary);
+    }
+
+    let instances = crate::aws::global::cached_instances(client.clone()).await?;
+    let instance_ids: Vec<String> = instances
+        .into_iter()
+        .filter(|instance| instance.state == "running")
+        .map(|instance| instance.instance_id)
+        .collect();
+
+    let window = MetricWindow::OneHour;
+    tracing::info!("Fetching status checks for {} running instances in {}", instance_ids.len(), region);
+
+    let mut results = Vec::new();
+    for queries in status_check_queries(&instance_ids, window) {
+        results.extend(get_metric_data(client, queries, window, &region).await?);
+    }
+
+    let summary = health_summary_from_results(&region, &instance_ids, window, &results);
+    client.cache.put_instances_health(region, summary.clone()).await;
+    Ok(summary)
+}
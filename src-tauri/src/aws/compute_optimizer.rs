@@ -0,0 +1,108 @@
+// ============================================================================
+// COMPUTE OPTIMIZER
+// ============================================================================
+// Rightsizing recommendations for EC2 instances from AWS Compute Optimizer.
+// Accounts have to opt in before it analyses anything; until then every call
+// fails with OptInRequiredException.
+// ============================================================================
+
+use crate::aws::diagnostics;
+use crate::aws::{AwsClient, AwsError, AwsResult};
+use aws_sdk_computeoptimizer::error::{DisplayErrorContext, ProvideErrorMetadata};
+use aws_sdk_computeoptimizer::types::InstanceRecommendation as SdkInstanceRecommendation;
+use serde::{Deserialize, Serialize};
+
+/// Error code returned to accounts that haven't opted in
+pub const OPT_IN_REQUIRED_CODE: &str = "OptInRequiredException";
+
+const OPT_IN_REQUIRED_MESSAGE: &str = "Compute Optimizer isn't enabled for this account. \
+    Opt in from the Compute Optimizer console; recommendations appear once it has analysed about 30 hours of metrics.";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstanceRecommendation {
+    pub instance_id: String,
+    pub instance_name: Option<String>,
+    pub current_type: String,
+    /// "overprovisioned", "underprovisioned", "optimized" or "not_optimized"
+    pub finding: String,
+    /// Recommended instance types, best first
+    pub recommended_types: Vec<String>,
+    /// Estimated monthly savings in USD of switching to the first recommended
+    /// type; zero when it wouldn't save anything
+    pub projected_savings: f64,
+}
+
+/// "NotOptimized" to "not_optimized"
+fn snake_case(value: &str) -> String {
+    let mut snake = String::with_capacity(value.len() + 4);
+    for (i, c) in value.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+/// Flatten a recommendation. Options are ordered by their rank, and the
+/// savings are those of the top-ranked one.
+pub fn map_instance_recommendation(recommendation: &SdkInstanceRecommendation) -> Option<InstanceRecommendation> {
+    let instance_id = recommendation.instance_arn()?.rsplit('/').next()?.to_string();
+
+    let mut options: Vec<_> = recommendation.recommendation_options().iter().collect();
+    options.sort_by_key(|option| option.rank());
+
+    let projected_savings = options
+        .first()
+        .and_then(|option| option.savings_opportunity())
+        .and_then(|savings| savings.estimated_monthly_savings())
+        .map_or(0.0, |savings| savings.value());
+
+    Some(InstanceRecommendation {
+        instance_id,
+        instance_name: recommendation.instance_name().map(str::to_string),
+        current_type: recommendation.current_instance_type().unwrap_or_default().to_string(),
+        finding: recommendation.finding().map_or_else(|| "unknown".to_string(), |finding| snake_case(finding.as_str())),
+        recommended_types: options.iter().filter_map(|option| option.instance_type()).map(str::to_string).collect(),
+        projected_savings,
+    })
+}
+
+/// Recommendations for the instances in the client's region, biggest
+/// projected savings first
+pub async fn fetch_instance_recommendations(client: &AwsClient) -> AwsResult<Vec<InstanceRecommendation>> {
+    tracing::info!("Fetching Compute Optimizer recommendations in {}", client.primary_region());
+
+    let mut recommendations = Vec::new();
+    let mut next_token: Option<String> = None;
+
+    loop {
+        let response = client.compute_optimizer_client
+            .get_ec2_instance_recommendations()
+            .set_next_token(next_token.take())
+            .send()
+            .await
+            .map_err(|e| {
+                if e.code() == Some(OPT_IN_REQUIRED_CODE) {
+                    tracing::info!("Compute Optimizer isn't enabled for this account");
+                    return AwsError::OptInRequired(OPT_IN_REQUIRED_MESSAGE.to_string());
+                }
+                tracing::error!("Failed to get Compute Optimizer recommendations: {}", DisplayErrorContext(&e));
+                diagnostics::record(
+                    "compute-optimizer",
+                    "GetEC2InstanceRecommendations",
+                    AwsError::OperationError(DisplayErrorContext(&e).to_string()),
+                )
+            })?;
+
+        recommendations.extend(response.instance_recommendations().iter().filter_map(map_instance_recommendation));
+
+        match response.next_token() {
+            Some(token) if !token.is_empty() => next_token = Some(token.to_string()),
+            _ => break,
+        }
+    }
+
+    recommendations.sort_by(|a, b| b.projected_savings.total_cmp(&a.projected_savings));
+    Ok(recommendations)
+}
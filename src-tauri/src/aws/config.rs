@@ -4,8 +4,10 @@
 // Load AWS credentials and settings from aws-config.toml
 // ============================================================================
 
+use crate::regions::default_fallback_region;
 use serde::Deserialize;
 use std::fs;
+use std::time::Duration;
 use anyhow::{Result, Context};
 
 #[derive(Debug, Clone, Deserialize)]
@@ -14,17 +16,73 @@ pub struct AwsCredentials {
     pub secret_access_key: String,
 }
 
+/// Role assumed with the base credentials for cross-account access
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssumeRoleConfig {
+    pub role_arn: String,
+    pub external_id: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct CostLimits {
     pub monthly_api_limit: u64,
     pub warning_threshold_percent: f64,
 }
 
+/// How long AWS calls may take before they fail with a network error rather
+/// than leaving a command waiting
 #[derive(Debug, Clone, Deserialize)]
 pub struct Timeouts {
+    /// Whole instance collections, all pages and retries included
     pub instance_operations_seconds: u64,
     pub bucket_operations_seconds: u64,
     pub iam_operations_seconds: u64,
+    /// Each single HTTP request the SDK clients send
+    #[serde(default = "default_request_seconds")]
+    pub request_seconds: u64,
+    #[serde(default = "default_connect_seconds")]
+    pub connect_seconds: u64,
+}
+
+fn default_request_seconds() -> u64 {
+    60
+}
+
+fn default_connect_seconds() -> u64 {
+    10
+}
+
+impl Timeouts {
+    pub fn instance_operations(&self) -> Duration {
+        Duration::from_secs(self.instance_operations_seconds)
+    }
+
+    pub fn bucket_operations(&self) -> Duration {
+        Duration::from_secs(self.bucket_operations_seconds)
+    }
+
+    pub fn iam_operations(&self) -> Duration {
+        Duration::from_secs(self.iam_operations_seconds)
+    }
+}
+
+/// Retrying of throttled or briefly unavailable AWS calls
+#[derive(Debug, Clone, Deserialize)]
+pub struct Retries {
+    /// Attempts in total, including the first; 1 disables retrying
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for Retries {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay_ms: 250,
+            max_delay_ms: 8_000,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -38,6 +96,8 @@ pub struct AwsConfigData {
     pub aws: AwsSection,
     pub cost_limits: CostLimits,
     pub timeouts: Timeouts,
+    #[serde(default)]
+    pub retries: Retries,
     pub regions: Regions,
 }
 
@@ -54,12 +114,14 @@ pub struct AwsSection {
 #[derive(Debug, Clone)]
 pub struct AwsConfig {
     pub credentials: AwsCredentials,
+    pub role: Option<AssumeRoleConfig>,
     pub region: String,
     pub refresh_interval_seconds: u64,
     pub enable_cost_tracking: bool,
     pub debug_logging: bool,
     pub cost_limits: CostLimits,
     pub timeouts: Timeouts,
+    pub retries: Retries,
     pub regions: Regions,
 }
 
@@ -69,7 +131,12 @@ impl AwsConfig {
         let config_content = fs::read_to_string(config_path)
             .context(format!("Failed to read config file: {}", config_path))?;
 
-        let config_data: AwsConfigData = toml::from_str(&config_content)
+        Self::from_toml(&config_content)
+    }
+
+    /// Parse the contents of an aws-config.toml file
+    pub fn from_toml(config_content: &str) -> Result<Self> {
+        let config_data: AwsConfigData = toml::from_str(config_content)
             .context("Failed to parse config file")?;
 
         Ok(Self {
@@ -77,16 +144,81 @@ impl AwsConfig {
                 access_key_id: config_data.aws.access_key_id,
                 secret_access_key: config_data.aws.secret_access_key,
             },
+            role: None,
             region: config_data.aws.region,
             refresh_interval_seconds: config_data.aws.refresh_interval_seconds,
             enable_cost_tracking: config_data.aws.enable_cost_tracking,
             debug_logging: config_data.aws.debug_logging,
             cost_limits: config_data.cost_limits,
             timeouts: config_data.timeouts,
+            retries: config_data.retries,
             regions: config_data.regions,
         })
     }
 
+    /// Build a config from account credentials stored in the database, using
+    /// the account region as primary and default limits, timeouts and retries
+    pub fn from_credentials(access_key_id: String, secret_access_key: String, region: String) -> Self {
+        let fallback = default_fallback_region(&region).to_string();
+
+        Self {
+            credentials: AwsCredentials {
+                access_key_id,
+                secret_access_key,
+            },
+            role: None,
+            region: region.clone(),
+            refresh_interval_seconds: 180,
+            enable_cost_tracking: true,
+            debug_logging: false,
+            cost_limits: CostLimits {
+                monthly_api_limit: 10_000,
+                warning_threshold_percent: 80.0,
+            },
+            timeouts: Timeouts {
+                instance_operations_seconds: 300,
+                bucket_operations_seconds: 60,
+                iam_operations_seconds: 60,
+                request_seconds: default_request_seconds(),
+                connect_seconds: default_connect_seconds(),
+            },
+            retries: Retries::default(),
+            regions: Regions {
+                primary: region,
+                fallback,
+            },
+        }
+    }
+
+    /// Assume `role_arn` with these credentials instead of using them directly
+    pub fn with_role(mut self, role_arn: String, external_id: Option<String>) -> Self {
+        self.role = Some(AssumeRoleConfig { role_arn, external_id });
+        self
+    }
+
+    /// Use an account's region as primary, overriding any config-file regions,
+    /// with its fallback region or a same-partition default
+    pub fn with_account_regions(mut self, region: &str, fallback_region: Option<&str>) -> Self {
+        let fallback = fallback_region
+            .filter(|fallback| !fallback.is_empty() && *fallback != region)
+            .unwrap_or_else(|| default_fallback_region(region));
+
+        self.region = region.to_string();
+        self.regions = Regions {
+            primary: region.to_string(),
+            fallback: fallback.to_string(),
+        };
+        self
+    }
+
+    /// Same credentials and settings, targeting another region
+    pub fn for_region(&self, region: &str) -> Self {
+        let mut config = self.clone();
+        config.region = region.to_string();
+        config.regions.primary = region.to_string();
+        config
+    }
+
     pub fn primary_region(&self) -> &str {
         &self.regions.primary
     }
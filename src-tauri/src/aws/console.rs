@@ -0,0 +1,168 @@
+// ============================================================================
+// INSTANCE CONSOLE
+// ============================================================================
+// Serial console output and screenshots for instances that can't be reached
+// over SSH
+// ============================================================================
+
+use crate::aws::{diagnostics, AwsClient, AwsError, AwsResult};
+use aws_sdk_ec2::error::ProvideErrorMetadata;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Console output kept by default, from the end of the log
+pub const DEFAULT_CONSOLE_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Minimum time between console calls for the same instance; EC2 throttles
+/// these APIs aggressively
+pub const CONSOLE_CALL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Error code EC2 returns for a stopped or still-launching instance
+const INCORRECT_INSTANCE_STATE_CODE: &str = "IncorrectInstanceState";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsoleOutput {
+    pub instance_id: String,
+    /// When EC2 last updated the captured output
+    pub timestamp: Option<String>,
+    pub output: String,
+    /// Earlier output was cut to keep the last `max_bytes`
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsoleScreenshot {
+    pub instance_id: String,
+    /// `data:image/jpeg;base64,...`, ready for an `<img>` tag
+    pub data_uri: String,
+    pub captured_at: String,
+}
+
+/// Allows one call per key per interval
+pub struct CallLimiter {
+    interval: Duration,
+    last_calls: Mutex<HashMap<String, Instant>>,
+}
+
+impl CallLimiter {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, last_calls: Mutex::new(HashMap::new()) }
+    }
+
+    /// Record a call for `key` at `now`, or return how long until one is allowed
+    pub fn try_acquire(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut last_calls = self.last_calls.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(last) = last_calls.get(key) {
+            let elapsed = now.saturating_duration_since(*last);
+            if elapsed < self.interval {
+                return Err(self.interval - elapsed);
+            }
+        }
+        last_calls.retain(|_, last| now.saturating_duration_since(*last) < self.interval);
+        last_calls.insert(key.to_string(), now);
+        Ok(())
+    }
+}
+
+fn limiter() -> &'static CallLimiter {
+    static LIMITER: OnceLock<CallLimiter> = OnceLock::new();
+    LIMITER.get_or_init(|| CallLimiter::new(CONSOLE_CALL_INTERVAL))
+}
+
+fn acquire(action: &str, instance_id: &str) -> AwsResult<()> {
+    limiter().try_acquire(&format!("{}:{}", action, instance_id), Instant::now()).map_err(|wait| {
+        AwsError::RateLimitError(format!(
+            "{} was called for {} less than {}s ago; try again in {}s",
+            action, instance_id, CONSOLE_CALL_INTERVAL.as_secs(), wait.as_secs().max(1)
+        ))
+    })
+}
+
+/// Decode base64 console output and keep at most its last `max_bytes`,
+/// starting on a line boundary when one is close. Returns the text and
+/// whether anything was cut.
+pub fn decode_console_output(encoded: &str, max_bytes: usize) -> AwsResult<(String, bool)> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| AwsError::OperationError(format!("Console output is not valid base64: {}", e)))?;
+    let text = String::from_utf8_lossy(&bytes);
+
+    if text.len() <= max_bytes {
+        return Ok((text.into_owned(), false));
+    }
+
+    let mut start = text.len() - max_bytes;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    // Drop the partial first line unless that would discard most of the tail
+    if let Some(newline) = text[start..].find('\n') {
+        if newline < max_bytes / 4 {
+            start += newline + 1;
+        }
+    }
+    Ok((text[start..].to_string(), true))
+}
+
+fn console_error<E, R>(action: &str, instance_id: &str, error: aws_sdk_ec2::error::SdkError<E, R>) -> AwsError
+where
+    E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+    R: std::fmt::Debug + Send + Sync + 'static,
+    aws_sdk_ec2::Error: From<aws_sdk_ec2::error::SdkError<E, R>>,
+{
+    if error.code() == Some(INCORRECT_INSTANCE_STATE_CODE) {
+        let message = error.message().unwrap_or("the instance must be running").to_string();
+        return AwsError::InstanceStateError(format!("{}: {}", instance_id, message));
+    }
+    tracing::error!("{} failed for {}: {:?}", action, instance_id, error);
+    diagnostics::record("ec2", action, AwsError::SdkError(error.into()))
+}
+
+/// The instance's serial console output, keeping the last `max_bytes`
+pub async fn get_console_output(client: &AwsClient, instance_id: &str, max_bytes: usize) -> AwsResult<ConsoleOutput> {
+    acquire("GetConsoleOutput", instance_id)?;
+
+    let response = client.ec2_client
+        .get_console_output()
+        .instance_id(instance_id)
+        .send()
+        .await
+        .map_err(|e| console_error("GetConsoleOutput", instance_id, e))?;
+
+    let (output, truncated) = match response.output() {
+        Some(encoded) => decode_console_output(encoded, max_bytes)?,
+        None => (String::new(), false),
+    };
+
+    Ok(ConsoleOutput {
+        instance_id: instance_id.to_string(),
+        timestamp: response.timestamp().map(|t| t.to_string()),
+        output,
+        truncated,
+    })
+}
+
+/// A screenshot of the instance's console. EC2 returns it as a JPEG.
+pub async fn get_console_screenshot(client: &AwsClient, instance_id: &str) -> AwsResult<ConsoleScreenshot> {
+    acquire("GetConsoleScreenshot", instance_id)?;
+
+    let response = client.ec2_client
+        .get_console_screenshot()
+        .instance_id(instance_id)
+        .wake_up(true)
+        .send()
+        .await
+        .map_err(|e| console_error("GetConsoleScreenshot", instance_id, e))?;
+
+    let image = response.image_data()
+        .ok_or_else(|| AwsError::OperationError(format!("EC2 returned no screenshot for {}", instance_id)))?;
+
+    Ok(ConsoleScreenshot {
+        instance_id: instance_id.to_string(),
+        data_uri: format!("data:image/jpeg;base64,{}", image.trim()),
+        captured_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
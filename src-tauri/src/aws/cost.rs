@@ -5,11 +5,39 @@
 // ============================================================================
 
 use crate::aws::{AwsClient, CostAlert, AwsResult, AwsError};
+use crate::aws::events::{AwsEventEmitter, DebouncedEmitter};
+use crate::database::DbPool;
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::{Mutex, RwLock};
 use chrono::{DateTime, Utc};
 
+/// Recorded calls kept for spend deltas; older calls are dropped
+const MAX_COST_SAMPLES: usize = 10_000;
+
+/// Minimum time between two emitted cost alert events
+pub const COST_EVENT_DEBOUNCE: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Spend of one recorded API call
+#[derive(Debug, Clone, Copy)]
+struct CostSample {
+    timestamp: DateTime<Utc>,
+    cost_micros: u64,
+}
+
+/// Spend recorded in a time window
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CostDelta {
+    pub since: String,
+    pub until: String,
+    pub api_calls: u64,
+    pub spend_usd: f64,
+    pub total_spend_usd: f64,
+    /// Set when `since` predates the retained samples, so older calls are missing
+    pub truncated: bool,
+}
+
 #[derive(Clone)]
 pub struct CostTracker {
     client: AwsClient,
@@ -17,7 +45,10 @@ pub struct CostTracker {
     estimated_cost_usd: Arc<AtomicU64>, // Stored as micro-dollars (millionths of a dollar)
     cost_limit_usd: f64,
     alert_threshold_percent: f64,
+    // Percentages of the cost limit that raise an alert when spend crosses them
+    alert_thresholds_percent: Vec<f64>,
     start_time: DateTime<Utc>,
+    samples: Arc<StdMutex<VecDeque<CostSample>>>,
 }
 
 impl CostTracker {
@@ -28,17 +59,29 @@ impl CostTracker {
             estimated_cost_usd: Arc::new(AtomicU64::new(0)),
             cost_limit_usd,
             alert_threshold_percent,
+            alert_thresholds_percent: vec![alert_threshold_percent, 100.0],
             start_time: Utc::now(),
+            samples: Arc::new(StdMutex::new(VecDeque::new())),
         }
     }
 
-    /// Record an API call and update cost estimates
-    pub async fn record_api_call(&self, service: &str, operation: &str) -> AwsResult<()> {
+    /// Record an API call and update cost estimates. Returns an alert when this
+    /// call pushed the estimate across one of the alert thresholds.
+    pub async fn record_api_call(&self, service: &str, operation: &str) -> AwsResult<Option<CostAlert>> {
         let call_count = self.api_call_count.fetch_add(1, Ordering::Relaxed) + 1;
 
         // Estimate cost based on service and operation
         let cost_micros = self.estimate_operation_cost(service, operation);
-        let total_cost_micros = self.estimated_cost_usd.fetch_add(cost_micros, Ordering::Relaxed) + cost_micros;
+        let previous_cost_micros = self.estimated_cost_usd.fetch_add(cost_micros, Ordering::Relaxed);
+        let total_cost_micros = previous_cost_micros + cost_micros;
+
+        {
+            let mut samples = self.samples.lock().unwrap();
+            samples.push_back(CostSample { timestamp: Utc::now(), cost_micros });
+            while samples.len() > MAX_COST_SAMPLES {
+                samples.pop_front();
+            }
+        }
 
         let total_cost_usd = total_cost_micros as f64 / 1_000_000.0;
 
@@ -47,15 +90,95 @@ impl CostTracker {
             service, operation, call_count, total_cost_usd
         );
 
-        // Check if we've exceeded the alert threshold
-        let threshold_usd = self.cost_limit_usd * (self.alert_threshold_percent / 100.0);
-        if total_cost_usd > threshold_usd {
-            tracing::warn!(
-                "Cost alert triggered: ${:.6} > ${:.6} ({}% of limit ${:.2})",
-                total_cost_usd, threshold_usd, self.alert_threshold_percent, self.cost_limit_usd
-            );
+        // Alert only on the call that crosses a threshold, not on every call above it
+        let previous_cost_usd = previous_cost_micros as f64 / 1_000_000.0;
+        let crossed = self.alert_thresholds_percent
+            .iter()
+            .copied()
+            .filter(|percent| {
+                let threshold_usd = self.cost_limit_usd * (percent / 100.0);
+                previous_cost_usd < threshold_usd && total_cost_usd >= threshold_usd
+            })
+            .fold(None, |highest: Option<f64>, percent| Some(highest.map_or(percent, |h| h.max(percent))));
+
+        let Some(percent) = crossed else {
+            return Ok(None);
+        };
+
+        let threshold_usd = self.cost_limit_usd * (percent / 100.0);
+        tracing::warn!(
+            "Cost alert triggered: ${:.6} >= ${:.6} ({}% of limit ${:.2})",
+            total_cost_usd, threshold_usd, percent, self.cost_limit_usd
+        );
+
+        Ok(Some(CostAlert {
+            alert_type: "cost_threshold_crossed".to_string(),
+            message: format!(
+                "Estimated cost ${:.6} crossed {}% of the ${:.2} limit",
+                total_cost_usd, percent, self.cost_limit_usd
+            ),
+            severity: if percent >= 100.0 { "critical" } else { "warning" }.to_string(),
+        }))
+    }
+
+    /// Spend and API calls recorded after `since`
+    pub fn spend_since(&self, since: DateTime<Utc>) -> CostDelta {
+        let samples = self.samples.lock().unwrap();
+        let recent = samples.iter().filter(|sample| sample.timestamp > since);
+
+        let (api_calls, spend_micros) = recent.fold((0u64, 0u64), |(calls, micros), sample| {
+            (calls + 1, micros + sample.cost_micros)
+        });
+        let retained_calls = samples.len() as u64;
+        let truncated = self.api_call_count.load(Ordering::Relaxed) > retained_calls
+            && samples.front().is_some_and(|oldest| oldest.timestamp > since);
+
+        CostDelta {
+            since: since.to_rfc3339(),
+            until: Utc::now().to_rfc3339(),
+            api_calls,
+            spend_usd: spend_micros as f64 / 1_000_000.0,
+            total_spend_usd: self.estimated_cost_usd.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+            truncated,
+        }
+    }
+
+    /// Set the warning and hard limit in USD. The warning becomes the alert
+    /// threshold alongside the limit itself.
+    pub fn set_limits(&mut self, warn_usd: f64, limit_usd: f64) -> AwsResult<()> {
+        if !(limit_usd.is_finite() && limit_usd > 0.0) || !(warn_usd.is_finite() && warn_usd > 0.0 && warn_usd <= limit_usd) {
+            return Err(AwsError::OperationError(format!(
+                "Invalid cost thresholds: warn ${:.2} must be positive and no greater than limit ${:.2}",
+                warn_usd, limit_usd
+            )));
         }
 
+        self.cost_limit_usd = limit_usd;
+        self.alert_threshold_percent = warn_usd / limit_usd * 100.0;
+        self.alert_thresholds_percent = vec![self.alert_threshold_percent, 100.0];
+        Ok(())
+    }
+
+    /// Fail with `CostLimitError` when the operation would take estimated spend past the limit
+    pub async fn ensure_within_limit(&self, service: &str, operation: &str) -> AwsResult<()> {
+        if self.check_cost_limit(service, operation).await? {
+            return Err(AwsError::CostLimitError(format!(
+                "{}::{} would exceed the ${:.2} cost limit (estimated spend so far ${:.6})",
+                service,
+                operation,
+                self.cost_limit_usd,
+                self.estimated_cost_usd.load(Ordering::Relaxed) as f64 / 1_000_000.0
+            )));
+        }
+        Ok(())
+    }
+
+    /// Replace the alert thresholds (percentages of the cost limit)
+    pub fn set_alert_thresholds(&mut self, thresholds_percent: Vec<f64>) -> AwsResult<()> {
+        if thresholds_percent.iter().any(|p| !p.is_finite() || *p <= 0.0) {
+            return Err(AwsError::OperationError("Cost alert thresholds must be positive percentages".to_string()));
+        }
+        self.alert_thresholds_percent = thresholds_percent;
         Ok(())
     }
 
@@ -147,6 +270,7 @@ impl CostTracker {
     pub async fn reset_tracking(&self) {
         self.api_call_count.store(0, Ordering::Relaxed);
         self.estimated_cost_usd.store(0, Ordering::Relaxed);
+        self.samples.lock().unwrap().clear();
         tracing::info!("Cost tracking reset");
     }
 
@@ -159,6 +283,11 @@ impl CostTracker {
     pub fn alert_threshold(&self) -> f64 {
         self.alert_threshold_percent
     }
+
+    /// Percentages of the cost limit that raise alerts
+    pub fn alert_thresholds(&self) -> &[f64] {
+        &self.alert_thresholds_percent
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -175,18 +304,62 @@ pub struct CostStatus {
 #[derive(Clone)]
 pub struct SharedCostTracker {
     inner: Arc<RwLock<CostTracker>>,
+    event_emitter: Option<Arc<AwsEventEmitter>>,
+    /// Database holding the SMTP settings alert emails are sent with
+    email_alerts: Option<DbPool>,
+    debounce_costs: Arc<Mutex<DebouncedEmitter>>,
 }
 
 impl SharedCostTracker {
     pub fn new(client: AwsClient, cost_limit_usd: f64, alert_threshold_percent: f64) -> Self {
         Self {
             inner: Arc::new(RwLock::new(CostTracker::new(client, cost_limit_usd, alert_threshold_percent))),
+            event_emitter: None,
+            email_alerts: None,
+            debounce_costs: Arc::new(Mutex::new(DebouncedEmitter::new(COST_EVENT_DEBOUNCE))),
         }
     }
 
-    pub async fn record_api_call(&self, service: &str, operation: &str) -> AwsResult<()> {
-        let tracker = self.inner.read().await;
-        tracker.record_api_call(service, operation).await
+    /// Emit a `cost_alert` event when spend crosses a threshold, at most once per `min_interval`
+    pub fn with_event_emitter(mut self, event_emitter: Arc<AwsEventEmitter>, min_interval: std::time::Duration) -> Self {
+        self.event_emitter = Some(event_emitter);
+        self.debounce_costs = Arc::new(Mutex::new(DebouncedEmitter::new(min_interval)));
+        self
+    }
+
+    /// Also email threshold crossings to the recipients in the SMTP settings
+    /// stored in `pool`, sharing the event debounce
+    pub fn with_email_alerts(mut self, pool: DbPool) -> Self {
+        self.email_alerts = Some(pool);
+        self
+    }
+
+    pub async fn record_api_call(&self, service: &str, operation: &str) -> AwsResult<Option<CostAlert>> {
+        let alert = {
+            let tracker = self.inner.read().await;
+            tracker.record_api_call(service, operation).await?
+        };
+
+        let notified = self.event_emitter.is_some() || self.email_alerts.is_some();
+        if let Some(alert) = alert.as_ref().filter(|_| notified) {
+            if self.debounce_costs.lock().await.should_emit() {
+                if let Some(emitter) = &self.event_emitter {
+                    emitter.emit_cost_alert(alert.clone()).await;
+                }
+                if let Some(pool) = &self.email_alerts {
+                    // Sent in the background so the call being recorded doesn't wait on SMTP
+                    let (pool, alert) = (pool.clone(), alert.clone());
+                    tokio::spawn(async move {
+                        let subject = format!("Pocket Architect {} cost alert", alert.severity);
+                        crate::notifier::notify_alert(&pool, &subject, &alert.message).await;
+                    });
+                }
+            } else {
+                tracing::debug!("Suppressed cost alert event within debounce interval");
+            }
+        }
+
+        Ok(alert)
     }
 
     pub async fn get_cost_status(&self) -> AwsResult<CostStatus> {
@@ -199,8 +372,29 @@ impl SharedCostTracker {
         tracker.check_cost_limit(service, operation).await
     }
 
+    pub async fn ensure_within_limit(&self, service: &str, operation: &str) -> AwsResult<()> {
+        let tracker = self.inner.read().await;
+        tracker.ensure_within_limit(service, operation).await
+    }
+
     pub async fn reset_tracking(&self) {
         let tracker = self.inner.read().await;
         tracker.reset_tracking().await
     }
-}
\ No newline at end of file
+
+    pub async fn set_limits(&self, warn_usd: f64, limit_usd: f64) -> AwsResult<()> {
+        self.inner.write().await.set_limits(warn_usd, limit_usd)
+    }
+
+    pub async fn spend_since(&self, since: DateTime<Utc>) -> CostDelta {
+        self.inner.read().await.spend_since(since)
+    }
+
+    pub async fn set_alert_thresholds(&self, thresholds_percent: Vec<f64>) -> AwsResult<()> {
+        self.inner.write().await.set_alert_thresholds(thresholds_percent)
+    }
+
+    pub async fn alert_thresholds(&self) -> Vec<f64> {
+        self.inner.read().await.alert_thresholds().to_vec()
+    }
+}
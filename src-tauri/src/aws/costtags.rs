@@ -0,0 +1,117 @@
+// ============================================================================
+// COST ALLOCATION TAGS
+// ============================================================================
+// Cost Explorer only groups spend by tags activated as cost allocation tags;
+// a project tag that isn't active silently reports no cost.
+// ============================================================================
+
+use crate::aws::{AwsClient, AwsError, AwsResult};
+use aws_sdk_costexplorer::error::DisplayErrorContext;
+use aws_sdk_costexplorer::types::{CostAllocationTag as CeCostAllocationTag, CostAllocationTagStatus, CostAllocationTagStatusEntry};
+use serde::{Deserialize, Serialize};
+
+/// UpdateCostAllocationTagsStatus accepts at most 20 tags per request
+const STATUS_UPDATE_BATCH_SIZE: usize = 20;
+
+/// Shown whenever tags are activated
+pub const ACTIVATION_DATA_LAG_WARNING: &str =
+    "Cost allocation tags can take up to 24 hours after activation to appear in cost data";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostAllocationTag {
+    pub key: String,
+    /// "active" or "inactive"
+    pub status: String,
+    /// "UserDefined" or "AWSGenerated"
+    pub tag_type: String,
+    pub last_updated: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostTagActivationFailure {
+    pub key: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostTagActivationReport {
+    pub activated: Vec<String>,
+    pub failed: Vec<CostTagActivationFailure>,
+    pub warning: String,
+}
+
+/// Flatten a Cost Explorer cost allocation tag
+pub fn map_cost_allocation_tag(tag: &CeCostAllocationTag) -> CostAllocationTag {
+    CostAllocationTag {
+        key: tag.tag_key().to_string(),
+        status: tag.status().as_str().to_ascii_lowercase(),
+        tag_type: tag.r#type().as_str().to_string(),
+        last_updated: tag.last_updated_date().map(|d| d.to_string()),
+    }
+}
+
+/// Status entries that activate each of `keys`
+pub fn activation_entries(keys: &[String]) -> AwsResult<Vec<CostAllocationTagStatusEntry>> {
+    keys.iter()
+        .map(|key| {
+            CostAllocationTagStatusEntry::builder()
+                .tag_key(key)
+                .status(CostAllocationTagStatus::Active)
+                .build()
+                .map_err(|e| AwsError::ConfigError(format!("Invalid cost allocation tag {}: {}", key, e)))
+        })
+        .collect()
+}
+
+/// Every user-defined and AWS-generated cost allocation tag with its status
+pub async fn list_cost_allocation_tags(client: &AwsClient) -> AwsResult<Vec<CostAllocationTag>> {
+    tracing::info!("Listing cost allocation tags");
+
+    let pages = client.ce_client
+        .list_cost_allocation_tags()
+        .into_paginator()
+        .send()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .map_err(|e| AwsError::OperationError(format!("Failed to list cost allocation tags: {}", DisplayErrorContext(&e))))?;
+
+    Ok(pages
+        .iter()
+        .flat_map(|page| page.cost_allocation_tags())
+        .map(map_cost_allocation_tag)
+        .collect())
+}
+
+/// Activate `keys` as cost allocation tags. Keys Cost Explorer rejects (for
+/// example, tags it has never seen on a resource) are reported per key.
+pub async fn activate_cost_allocation_tags(client: &AwsClient, keys: &[String]) -> AwsResult<CostTagActivationReport> {
+    tracing::info!("Activating {} cost allocation tags", keys.len());
+
+    let mut failed = Vec::new();
+
+    for batch in keys.chunks(STATUS_UPDATE_BATCH_SIZE) {
+        let response = client.ce_client
+            .update_cost_allocation_tags_status()
+            .set_cost_allocation_tags_status(Some(activation_entries(batch)?))
+            .send()
+            .await
+            .map_err(|e| AwsError::OperationError(format!("Failed to activate cost allocation tags: {}", DisplayErrorContext(&e))))?;
+
+        failed.extend(response.errors().iter().map(|error| CostTagActivationFailure {
+            key: error.tag_key().unwrap_or("unknown").to_string(),
+            error: error.message().or(error.code()).unwrap_or("unknown error").to_string(),
+        }));
+    }
+
+    let activated = keys
+        .iter()
+        .filter(|key| !failed.iter().any(|f| &f.key == *key))
+        .cloned()
+        .collect();
+
+    Ok(CostTagActivationReport {
+        activated,
+        failed,
+        warning: ACTIVATION_DATA_LAG_WARNING.to_string(),
+    })
+}
@@ -0,0 +1,103 @@
+// ============================================================================
+// AWS ERROR DIAGNOSTICS
+// ============================================================================
+// Recent AWS API errors kept in memory so users can see why an operation
+// failed without reading the logs
+// ============================================================================
+
+use crate::aws::AwsError;
+use aws_sdk_ec2::error::ProvideErrorMetadata;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+/// Errors kept; the oldest are dropped first
+pub const RECENT_ERROR_CAPACITY: usize = 200;
+
+/// Longest message kept per error; SDK errors can carry whole response bodies
+const MAX_MESSAGE_CHARS: usize = 500;
+
+static RECENT_ERRORS: Mutex<VecDeque<RecordedAwsError>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedAwsError {
+    pub timestamp: DateTime<Utc>,
+    /// Service the call went to, such as "ec2"
+    pub service: String,
+    /// API action, such as "RunInstances"
+    pub action: String,
+    /// AWS error code when the service returned one, such as "UnauthorizedOperation"
+    pub code: Option<String>,
+    pub message: String,
+}
+
+/// Record `error` from a call to `service`'s `action` and hand it back, so
+/// service methods can wrap the error they map
+pub fn record(service: &str, action: &str, error: AwsError) -> AwsError {
+    let (code, message) = error_summary(&error);
+    let recorded = RecordedAwsError {
+        timestamp: Utc::now(),
+        service: service.to_string(),
+        action: action.to_string(),
+        code,
+        message: truncate(&redact_credentials(&message), MAX_MESSAGE_CHARS),
+    };
+
+    let mut errors = RECENT_ERRORS.lock().unwrap_or_else(|e| e.into_inner());
+    errors.push_back(recorded);
+    while errors.len() > RECENT_ERROR_CAPACITY {
+        errors.pop_front();
+    }
+
+    error
+}
+
+/// Up to `limit` recorded errors, newest first
+pub fn recent_errors(limit: usize) -> Vec<RecordedAwsError> {
+    let errors = RECENT_ERRORS.lock().unwrap_or_else(|e| e.into_inner());
+    errors.iter().rev().take(limit).cloned().collect()
+}
+
+/// The service's error code and message when it sent one, otherwise the
+/// error's own description
+fn error_summary(error: &AwsError) -> (Option<String>, String) {
+    let (code, message) = match error {
+        AwsError::SdkError(e) => (e.code(), e.message()),
+        AwsError::S3SdkError(e) => (e.code(), e.message()),
+        AwsError::IamSdkError(e) => (e.code(), e.message()),
+        _ => (None, None),
+    };
+
+    let message = match (code, message) {
+        (_, Some(message)) => message.to_string(),
+        _ => error.to_string(),
+    };
+    (code.map(str::to_string), message)
+}
+
+fn truncate(message: &str, max_chars: usize) -> String {
+    match message.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &message[..end]),
+        None => message.to_string(),
+    }
+}
+
+/// Mask access key ids, secret keys, session tokens and request signatures
+pub fn redact_credentials(message: &str) -> String {
+    static ACCESS_KEY_ID: OnceLock<Regex> = OnceLock::new();
+    static SECRET_PAIR: OnceLock<Regex> = OnceLock::new();
+    static SECRET_KEY: OnceLock<Regex> = OnceLock::new();
+
+    let access_key_id = ACCESS_KEY_ID.get_or_init(|| Regex::new(r"\b(AKIA|ASIA)[A-Z0-9]{16}\b").unwrap());
+    let secret_pair = SECRET_PAIR.get_or_init(|| {
+        Regex::new(r#"(?i)(secret_?access_?key|session_?token|security-token|signature|credential|password)(["']?\s*[:=]\s*["']?)[^\s"'&,;]+"#).unwrap()
+    });
+    // Secret access keys are 40 characters of base64
+    let secret_key = SECRET_KEY.get_or_init(|| Regex::new(r"\b[A-Za-z0-9/+]{40}\b").unwrap());
+
+    let redacted = access_key_id.replace_all(message, "${1}[REDACTED]");
+    let redacted = secret_pair.replace_all(&redacted, "${1}${2}[REDACTED]");
+    secret_key.replace_all(&redacted, "[REDACTED]").into_owned()
+}
@@ -0,0 +1,448 @@
+// ============================================================================
+// EBS SERVICE IMPLEMENTATION
+// ============================================================================
+// EBS volume listing, creation, attachment and resizing, and snapshots
+// ============================================================================
+
+use crate::aws::config::Retries;
+use crate::aws::diagnostics;
+use crate::aws::ec2::validate_root_volume;
+use crate::aws::retry::{single_attempt_ec2, with_retry};
+use crate::aws::{AwsClient, AwsEbsSnapshot, AwsEbsVolume, CreateVolumeRequest, EbsAttachment, RootVolumeSpec, AwsResult, AwsError};
+use aws_sdk_ec2::types::{ResourceType, Snapshot, SnapshotState, Tag, TagSpecification, Volume, VolumeModificationState, VolumeType};
+use std::time::Duration;
+
+/// Largest EBS volume AWS allows, in GiB
+pub const MAX_VOLUME_SIZE_GB: i32 = 16_384;
+
+/// How often, and how many times, a resize polls the volume modification
+const MODIFY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const MODIFY_POLL_ATTEMPTS: u32 = 60;
+
+pub struct EbsService {
+    client: AwsClient,
+}
+
+impl EbsService {
+    pub fn new(client: AwsClient) -> Self {
+        Self { client }
+    }
+
+    /// Collect EBS volumes in the primary region, from the cache when fresh,
+    /// falling back to the fallback region if the primary fails
+    pub async fn collect_volumes(&self) -> AwsResult<Vec<AwsEbsVolume>> {
+        let region = self.client.primary_region();
+        if let Some(volumes) = self.client.cache.get_volumes(region).await {
+            return Ok(volumes);
+        }
+
+        tracing::info!("Collecting EBS volumes in region {}", region);
+        match collect_volumes_in_region(&self.client.ec2_client, &self.client.config.retries, region).await {
+            Ok(volumes) => {
+                self.client.cache.put_volumes(region.to_string(), volumes.clone()).await;
+                Ok(volumes)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to collect EBS volumes from primary region {}: {:?}", region, e);
+
+                let fallback_region = self.client.fallback_region();
+                let fallback_client = self.client.for_region(fallback_region).await.map_err(|e2| {
+                    AwsError::RegionError(format!("Failed to collect volumes from both regions: primary={}, fallback={}", e, e2))
+                })?;
+                let volumes = collect_volumes_in_region(&fallback_client.ec2_client, &fallback_client.config.retries, fallback_region).await.map_err(|e2| {
+                    tracing::error!("Failed to collect EBS volumes from both primary and fallback regions: primary={}, fallback={}", e, e2);
+                    AwsError::RegionError(format!("Failed to collect volumes from both regions: primary={}, fallback={}", e, e2))
+                })?;
+
+                tracing::info!("Collected {} EBS volumes from fallback region {}", volumes.len(), fallback_region);
+                self.client.cache.put_volumes(fallback_region.to_string(), volumes.clone()).await;
+                Ok(volumes)
+            }
+        }
+    }
+
+    /// One volume by id, straight from EC2
+    pub async fn get_volume(&self, volume_id: &str) -> AwsResult<AwsEbsVolume> {
+        let region = self.client.primary_region();
+        let response = self.client.ec2_client
+            .describe_volumes()
+            .volume_ids(volume_id)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to describe volume {}: {:?}", volume_id, e);
+                diagnostics::record("ec2", "DescribeVolumes", AwsError::SdkError(e.into()))
+            })?;
+
+        response.volumes()
+            .first()
+            .and_then(|volume| map_ebs_volume(volume, region))
+            .ok_or_else(|| AwsError::OperationError(format!("Volume {} not found", volume_id)))
+    }
+
+    /// Create a volume in the client's region
+    pub async fn create_volume(&self, request: &CreateVolumeRequest) -> AwsResult<AwsEbsVolume> {
+        validate_root_volume(&RootVolumeSpec {
+            size_gb: request.size_gb,
+            volume_type: request.volume_type.clone(),
+            encrypted: request.encrypted,
+            iops: request.iops,
+        }, None)?;
+
+        let region = self.client.primary_region();
+        tracing::info!("Creating {} GB {} volume in {}", request.size_gb, request.volume_type, request.availability_zone);
+
+        let mut tags = TagSpecification::builder()
+            .resource_type(ResourceType::Volume)
+            .tags(Tag::builder().key("CreatedBy").value("PocketArchitect").build());
+        if let Some(name) = request.name.as_deref().filter(|n| !n.is_empty()) {
+            tags = tags.tags(Tag::builder().key("Name").value(name).build());
+        }
+
+        let response = self.client.ec2_client
+            .create_volume()
+            .availability_zone(&request.availability_zone)
+            .size(request.size_gb)
+            .volume_type(VolumeType::from(request.volume_type.as_str()))
+            .encrypted(request.encrypted)
+            .set_iops(request.iops)
+            .tag_specifications(tags.build())
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to create volume in {}: {:?}", request.availability_zone, e);
+                diagnostics::record("ec2", "CreateVolume", AwsError::SdkError(e.into()))
+            })?;
+
+        let volume_id = response.volume_id()
+            .ok_or_else(|| AwsError::OperationError("CreateVolume returned no volume id".to_string()))?;
+        self.client.cache.invalidate_volumes(region).await;
+
+        tracing::info!("Created EBS volume {}", volume_id);
+        Ok(AwsEbsVolume {
+            volume_id: volume_id.to_string(),
+            region: region.to_string(),
+            availability_zone: response.availability_zone().unwrap_or(&request.availability_zone).to_string(),
+            size_gb: response.size().unwrap_or(request.size_gb),
+            volume_type: response.volume_type().map(|t| t.as_str().to_string()).unwrap_or_else(|| request.volume_type.clone()),
+            state: response.state().map(|s| s.as_str().to_string()).unwrap_or_else(|| "creating".to_string()),
+            encrypted: response.encrypted().unwrap_or(request.encrypted),
+            iops: response.iops(),
+            attachments: Vec::new(),
+            tags: response.tags().iter()
+                .filter_map(|tag| Some((tag.key()?.to_string(), tag.value()?.to_string())))
+                .collect(),
+        })
+    }
+
+    /// Attach a volume to an instance as `device` (e.g. /dev/sdf)
+    pub async fn attach_volume(&self, volume_id: &str, instance_id: &str, device: &str) -> AwsResult<EbsAttachment> {
+        tracing::info!("Attaching volume {} to instance {} as {}", volume_id, instance_id, device);
+
+        let response = self.client.ec2_client
+            .attach_volume()
+            .volume_id(volume_id)
+            .instance_id(instance_id)
+            .device(device)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to attach volume {} to {}: {:?}", volume_id, instance_id, e);
+                diagnostics::record("ec2", "AttachVolume", AwsError::SdkError(e.into()))
+            })?;
+        self.client.cache.invalidate_volumes(self.client.primary_region()).await;
+
+        Ok(EbsAttachment {
+            instance_id: response.instance_id().unwrap_or(instance_id).to_string(),
+            device: response.device().unwrap_or(device).to_string(),
+            state: response.state().map(|s| s.as_str().to_string()).unwrap_or_else(|| "attaching".to_string()),
+        })
+    }
+
+    /// Detach a volume from whichever instance it's attached to. `force` skips
+    /// the clean unmount and can lose unflushed writes.
+    pub async fn detach_volume(&self, volume_id: &str, force: bool) -> AwsResult<EbsAttachment> {
+        tracing::info!("Detaching volume {} (force: {})", volume_id, force);
+
+        let response = self.client.ec2_client
+            .detach_volume()
+            .volume_id(volume_id)
+            .force(force)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to detach volume {}: {:?}", volume_id, e);
+                diagnostics::record("ec2", "DetachVolume", AwsError::SdkError(e.into()))
+            })?;
+        self.client.cache.invalidate_volumes(self.client.primary_region()).await;
+
+        Ok(EbsAttachment {
+            instance_id: response.instance_id().unwrap_or_default().to_string(),
+            device: response.device().unwrap_or_default().to_string(),
+            state: response.state().map(|s| s.as_str().to_string()).unwrap_or_else(|| "detaching".to_string()),
+        })
+    }
+
+    /// Grow a volume to `size_gb` and wait until the new size is usable.
+    /// Shrinking is refused; EBS can't do it.
+    pub async fn modify_volume_size(&self, volume_id: &str, size_gb: i32) -> AwsResult<AwsEbsVolume> {
+        let volume = self.get_volume(volume_id).await?;
+        validate_volume_resize(volume_id, volume.size_gb, size_gb)?;
+
+        tracing::info!("Resizing volume {} from {} GB to {} GB", volume_id, volume.size_gb, size_gb);
+        self.client.ec2_client
+            .modify_volume()
+            .volume_id(volume_id)
+            .size(size_gb)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to modify volume {}: {:?}", volume_id, e);
+                diagnostics::record("ec2", "ModifyVolume", AwsError::SdkError(e.into()))
+            })?;
+        self.client.cache.invalidate_volumes(self.client.primary_region()).await;
+
+        self.wait_for_modification(volume_id).await?;
+        self.get_volume(volume_id).await
+    }
+
+    /// Poll until the volume's modification reaches optimizing (the new size
+    /// can be used) or completed
+    async fn wait_for_modification(&self, volume_id: &str) -> AwsResult<()> {
+        for attempt in 0..MODIFY_POLL_ATTEMPTS {
+            let response = self.client.ec2_client
+                .describe_volumes_modifications()
+                .volume_ids(volume_id)
+                .send()
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to describe modifications of volume {}: {:?}", volume_id, e);
+                    diagnostics::record("ec2", "DescribeVolumesModifications", AwsError::SdkError(e.into()))
+                })?;
+
+            let modification = response.volumes_modifications().first();
+            match modification.and_then(|m| m.modification_state()) {
+                Some(VolumeModificationState::Optimizing | VolumeModificationState::Completed) => {
+                    tracing::info!("Volume {} resized after {} checks", volume_id, attempt + 1);
+                    return Ok(());
+                }
+                Some(VolumeModificationState::Failed) => {
+                    let reason = modification.and_then(|m| m.status_message()).unwrap_or("no reason given");
+                    return Err(AwsError::OperationError(format!("Resizing volume {} failed: {}", volume_id, reason)));
+                }
+                _ => tokio::time::sleep(MODIFY_POLL_INTERVAL).await,
+            }
+        }
+
+        Err(AwsError::OperationError(format!(
+            "Volume {} was still being modified after {} seconds",
+            volume_id,
+            MODIFY_POLL_INTERVAL.as_secs() * MODIFY_POLL_ATTEMPTS as u64
+        )))
+    }
+
+    /// Snapshots owned by the account in the primary region
+    pub async fn collect_snapshots(&self) -> AwsResult<Vec<AwsEbsSnapshot>> {
+        let region = self.client.primary_region();
+        tracing::info!("Collecting EBS snapshots in region {}", region);
+
+        let snapshots = with_retry(&self.client.config.retries, "DescribeSnapshots", || async move {
+            single_attempt_ec2(&self.client.ec2_client)
+                .describe_snapshots()
+                .owner_ids("self")
+                .into_paginator()
+                .items()
+                .send()
+                .collect::<Result<Vec<_>, _>>()
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to describe snapshots in region {}: {:?}", region, e);
+                    diagnostics::record("ec2", "DescribeSnapshots", AwsError::SdkError(e.into()))
+                })
+        })
+        .await?;
+
+        Ok(snapshots.iter().filter_map(|snapshot| map_ebs_snapshot(snapshot, region)).collect())
+    }
+
+    /// Start a snapshot of a volume. It is `pending` until EBS has copied the
+    /// volume's blocks; see `progress_percent`.
+    pub async fn create_snapshot(&self, volume_id: &str, description: Option<&str>) -> AwsResult<AwsEbsSnapshot> {
+        self.create_snapshot_tagged(volume_id, description, &[]).await
+    }
+
+    /// `create_snapshot` with extra tags on the snapshot
+    pub async fn create_snapshot_tagged(&self, volume_id: &str, description: Option<&str>, extra_tags: &[(&str, &str)]) -> AwsResult<AwsEbsSnapshot> {
+        let region = self.client.primary_region();
+        tracing::info!("Creating snapshot of volume {}", volume_id);
+
+        let tags = extra_tags.iter().fold(
+            TagSpecification::builder()
+                .resource_type(ResourceType::Snapshot)
+                .tags(Tag::builder().key("CreatedBy").value("PocketArchitect").build()),
+            |tags, (key, value)| tags.tags(Tag::builder().key(*key).value(*value).build()),
+        )
+        .build();
+
+        let response = self.client.ec2_client
+            .create_snapshot()
+            .volume_id(volume_id)
+            .set_description(description.filter(|d| !d.is_empty()).map(str::to_string))
+            .tag_specifications(tags)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to create snapshot of volume {}: {:?}", volume_id, e);
+                diagnostics::record("ec2", "CreateSnapshot", AwsError::SdkError(e.into()))
+            })?;
+
+        let snapshot_id = response.snapshot_id()
+            .ok_or_else(|| AwsError::OperationError("CreateSnapshot returned no snapshot id".to_string()))?;
+
+        tracing::info!("Started snapshot {} of volume {}", snapshot_id, volume_id);
+        Ok(AwsEbsSnapshot {
+            snapshot_id: snapshot_id.to_string(),
+            volume_id: Some(response.volume_id().unwrap_or(volume_id).to_string()),
+            region: region.to_string(),
+            state: response.state().map(|s| s.as_str().to_string()).unwrap_or_else(|| "pending".to_string()),
+            progress_percent: snapshot_progress(response.state(), response.progress()),
+            volume_size_gb: response.volume_size().unwrap_or(0),
+            description: response.description().map(str::to_string),
+            start_time: response.start_time().map(|dt| dt.to_string()),
+            encrypted: response.encrypted().unwrap_or(false),
+            tags: response.tags().iter()
+                .filter_map(|tag| Some((tag.key()?.to_string(), tag.value()?.to_string())))
+                .collect(),
+        })
+    }
+
+    /// Delete a volume. AWS refuses while it is attached to an instance.
+    pub async fn delete_volume(&self, volume_id: &str) -> AwsResult<()> {
+        tracing::info!("Deleting volume {}", volume_id);
+
+        self.client.ec2_client
+            .delete_volume()
+            .volume_id(volume_id)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to delete volume {}: {:?}", volume_id, e);
+                diagnostics::record("ec2", "DeleteVolume", AwsError::SdkError(e.into()))
+            })?;
+
+        self.client.cache.invalidate_volumes(self.client.primary_region()).await;
+        tracing::info!("Deleted volume {}", volume_id);
+        Ok(())
+    }
+
+    /// Delete a snapshot. AWS refuses while a registered AMI still uses it.
+    pub async fn delete_snapshot(&self, snapshot_id: &str) -> AwsResult<()> {
+        tracing::info!("Deleting snapshot {}", snapshot_id);
+
+        self.client.ec2_client
+            .delete_snapshot()
+            .snapshot_id(snapshot_id)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to delete snapshot {}: {:?}", snapshot_id, e);
+                diagnostics::record("ec2", "DeleteSnapshot", AwsError::SdkError(e.into()))
+            })?;
+
+        tracing::info!("Deleted snapshot {}", snapshot_id);
+        Ok(())
+    }
+}
+
+/// Every volume in a region
+async fn collect_volumes_in_region(ec2_client: &aws_sdk_ec2::Client, retries: &Retries, region: &str) -> AwsResult<Vec<AwsEbsVolume>> {
+    let volumes = with_retry(retries, "DescribeVolumes", || async move {
+        single_attempt_ec2(ec2_client)
+            .describe_volumes()
+            .into_paginator()
+            .items()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to describe volumes in region {}: {:?}", region, e);
+                diagnostics::record("ec2", "DescribeVolumes", AwsError::SdkError(e.into()))
+            })
+    })
+    .await?;
+
+    Ok(volumes.iter().filter_map(|volume| map_ebs_volume(volume, region)).collect())
+}
+
+/// Map an SDK volume to our AwsEbsVolume type
+pub fn map_ebs_volume(volume: &Volume, region: &str) -> Option<AwsEbsVolume> {
+    Some(AwsEbsVolume {
+        volume_id: volume.volume_id()?.to_string(),
+        region: region.to_string(),
+        availability_zone: volume.availability_zone().unwrap_or_default().to_string(),
+        size_gb: volume.size().unwrap_or(0),
+        volume_type: volume.volume_type().map(|t| t.as_str().to_string()).unwrap_or_else(|| "unknown".to_string()),
+        state: volume.state().map(|s| s.as_str().to_string()).unwrap_or_else(|| "unknown".to_string()),
+        encrypted: volume.encrypted().unwrap_or(false),
+        iops: volume.iops(),
+        attachments: volume.attachments()
+            .iter()
+            .filter_map(|attachment| Some(EbsAttachment {
+                instance_id: attachment.instance_id()?.to_string(),
+                device: attachment.device().unwrap_or_default().to_string(),
+                state: attachment.state().map(|s| s.as_str().to_string()).unwrap_or_else(|| "unknown".to_string()),
+            }))
+            .collect(),
+        tags: volume.tags()
+            .iter()
+            .filter_map(|tag| Some((tag.key()?.to_string(), tag.value()?.to_string())))
+            .collect(),
+    })
+}
+
+/// A resize must grow the volume and stay within the EBS size limit
+pub fn validate_volume_resize(volume_id: &str, current_gb: i32, requested_gb: i32) -> AwsResult<()> {
+    if requested_gb < current_gb {
+        return Err(AwsError::ConfigError(format!(
+            "Volume {} is {} GB and EBS volumes can't shrink; create a smaller volume and copy the data instead of resizing to {} GB",
+            volume_id, current_gb, requested_gb
+        )));
+    }
+    if requested_gb == current_gb {
+        return Err(AwsError::ConfigError(format!("Volume {} is already {} GB", volume_id, current_gb)));
+    }
+    if requested_gb > MAX_VOLUME_SIZE_GB {
+        return Err(AwsError::ConfigError(format!(
+            "EBS volumes can be at most {} GB, got {}",
+            MAX_VOLUME_SIZE_GB, requested_gb
+        )));
+    }
+    Ok(())
+}
+
+/// Map an SDK snapshot to our AwsEbsSnapshot type
+pub fn map_ebs_snapshot(snapshot: &Snapshot, region: &str) -> Option<AwsEbsSnapshot> {
+    Some(AwsEbsSnapshot {
+        snapshot_id: snapshot.snapshot_id()?.to_string(),
+        volume_id: snapshot.volume_id().map(str::to_string),
+        region: region.to_string(),
+        state: snapshot.state().map(|s| s.as_str().to_string()).unwrap_or_else(|| "unknown".to_string()),
+        progress_percent: snapshot_progress(snapshot.state(), snapshot.progress()),
+        volume_size_gb: snapshot.volume_size().unwrap_or(0),
+        description: snapshot.description().filter(|d| !d.is_empty()).map(str::to_string),
+        start_time: snapshot.start_time().map(|dt| dt.to_string()),
+        encrypted: snapshot.encrypted().unwrap_or(false),
+        tags: snapshot.tags()
+            .iter()
+            .filter_map(|tag| Some((tag.key()?.to_string(), tag.value()?.to_string())))
+            .collect(),
+    })
+}
+
+/// Progress of a pending snapshot; EBS reports it as a string such as "42%"
+pub fn snapshot_progress(state: Option<&SnapshotState>, progress: Option<&str>) -> Option<u8> {
+    if state != Some(&SnapshotState::Pending) {
+        return None;
+    }
+    progress?.trim().trim_end_matches('%').parse::<u8>().ok().map(|percent| percent.min(100))
+}
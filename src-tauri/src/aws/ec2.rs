@@ -4,14 +4,71 @@
 // EC2 instance management with real AWS API integration
 // ============================================================================
 
-use crate::aws::{AwsClient, AwsInstance, AwsSecurityGroup, AwsResult, AwsError};
-use aws_config::{BehaviorVersion, Region};
-use aws_credential_types::Credentials;
-use aws_sdk_ec2::types::{Instance as AwsSdkInstance, InstanceStateName, InstanceType};
+use crate::api_error::ApiError;
+use crate::aws::{AwsClient, AwsEbsSnapshot, BulkInstanceOperation, BulkInstanceOperationReport, BulkOperationFailure, InstanceStateTransition, AwsInstance, AwsNetworkInterface, AwsReservedInstance, AwsSpotPrice, AwsSubnet, AwsVolume, AwsVpc, InstanceCoverage, InstanceFilters, InstanceResize, InstanceStateWait, InstanceScheduledEvent, InstanceTypeSpec, LaunchOptions, PurchaseOption, RootVolumeSpec, AwsSecurityGroup, AwsSecurityGroupInfo, AwsElasticIp, EipReclaimReport, EipReleaseFailure, GeneratedKeyPair, KeyPairLaunch, UnstoredPrivateKey, RegionalCollection, AwsResult, AwsError};
+use crate::aws::diagnostics;
+use crate::aws::ebs::EbsService;
+use crate::aws::config::Retries;
+use crate::aws::regional::{collect_across_regions, MAX_CONCURRENT_REGIONS};
+use crate::aws::retry::{single_attempt_ec2, with_retry, with_timeout};
+use aws_sdk_ec2::error::{ProvideErrorMetadata, SdkError};
+use aws_sdk_ec2::operation::create_image::CreateImageOutput;
+use aws_sdk_ec2::operation::describe_instances::DescribeInstancesError;
+use aws_sdk_ec2::operation::run_instances::builders::RunInstancesFluentBuilder;
+use aws_sdk_ec2::primitives::DateTime as AwsDateTime;
+use aws_sdk_ec2::types::{Address, AttributeValue, BlockDeviceMapping, DomainType, EbsBlockDevice, EventCode, Filter, Instance as AwsSdkInstance, InstanceInterruptionBehavior, InstanceLifecycleType, InstanceMarketOptionsRequest, InstanceState, InstanceStateChange, InstanceStateName, InstanceStatus, InstanceType, InstanceTypeInfo, InstanceTypeOffering, LocationType, IpPermission, MarketType, SpotInstanceType, SpotMarketOptions, IpRange, Ipv6Range, ReservedInstanceState, ReservedInstances, Scope, SecurityGroup, SpotPrice, Subnet, UserIdGroupPair, Volume, VolumeType, Vpc};
+use crate::database::SecurityRule;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
 use chrono::Utc;
 use uuid::Uuid;
 
+/// Tag key that protects an Elastic IP from automatic reclaim
+pub const EIP_PROTECTED_TAG_KEY: &str = "PocketArchitect:Protected";
+
+/// How far back `get_spot_price_history` looks
+pub const SPOT_PRICE_HISTORY_HOURS: i64 = 24;
+
+/// Spot prices are quoted per operating system; launches here are Linux
+const SPOT_PRODUCT_DESCRIPTION: &str = "Linux/UNIX";
+
+/// Tags on a snapshot taken before its instance was terminated
+pub const FINAL_SNAPSHOT_INSTANCE_TAG_KEY: &str = "PocketArchitect:InstanceId";
+pub const FINAL_SNAPSHOT_TIME_TAG_KEY: &str = "PocketArchitect:SnapshotTime";
+
+/// Approximate monthly charge for an idle public IPv4 address ($0.005/hour)
+pub const UNASSOCIATED_EIP_MONTHLY_COST_USD: f64 = 0.005 * 730.0;
+
+/// Directory (next to the database) where stored private keys are written for SSH
+pub const SSH_KEY_DIR: &str = "ssh-keys";
+
+/// Tag linking a security group to the SecurityConfig it was built from
+pub const SECURITY_CONFIG_TAG_KEY: &str = "PocketArchitect:SecurityConfigId";
+
+/// EC2 limits user data to 16 KB before base64 encoding
+pub const MAX_USER_DATA_BYTES: usize = 16 * 1024;
+
+/// Error code EC2 returns for a dry-run request that would have succeeded
+const DRY_RUN_OPERATION_CODE: &str = "DryRunOperation";
+
+/// DescribeInstanceTypes accepts at most 100 instance types per request
+const DESCRIBE_INSTANCE_TYPES_BATCH_SIZE: usize = 100;
+
+/// Start, stop and terminate calls take at most 100 instance ids each
+pub const MAX_INSTANCES_PER_STATE_CALL: usize = 100;
+
+/// How often, and how many times, state transitions (a resize's stop, or a
+/// start/stop the caller waits on) poll the instance
+const INSTANCE_STATE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const INSTANCE_STATE_POLL_ATTEMPTS: u32 = 60;
+
+/// How often, and how many times, image creation polls for the AMI to finish
+const IMAGE_POLL_INTERVAL: Duration = Duration::from_secs(15);
+const IMAGE_POLL_ATTEMPTS: u32 = 40;
+
 pub struct Ec2Service {
     client: AwsClient,
 }
@@ -23,12 +80,19 @@ impl Ec2Service {
 
     /// Collect all EC2 instances across all regions with cross-region fallback
     pub async fn collect_instances(&self) -> AwsResult<Vec<AwsInstance>> {
-        tracing::info!("Starting EC2 instance collection across all regions");
+        self.collect_matching_instances(&InstanceFilters::default()).await
+    }
+
+    /// Collect the EC2 instances matching `filters`, with cross-region fallback.
+    /// Filtering happens in DescribeInstances; results are never read from or
+    /// written to the per-region instance cache.
+    pub async fn collect_matching_instances(&self, filters: &InstanceFilters) -> AwsResult<Vec<AwsInstance>> {
+        tracing::info!("Starting EC2 instance collection across all regions (filtered: {})", !filters.is_empty());
 
         let mut all_instances = Vec::new();
 
         // Try primary region first
-        match self.collect_instances_in_region(self.client.primary_region()).await {
+        match self.collect_instances_in_region(self.client.primary_region(), filters).await {
             Ok(mut instances) => {
                 all_instances.append(&mut instances);
                 tracing::debug!("Collected {} instances from primary region {}", all_instances.len(), self.client.primary_region());
@@ -37,7 +101,7 @@ impl Ec2Service {
                 tracing::warn!("Failed to collect instances from primary region {}: {:?}", self.client.primary_region(), e);
 
                 // Try fallback region with cross-region client
-                match self.collect_instances_cross_region(self.client.fallback_region()).await {
+                match self.collect_instances_cross_region(self.client.fallback_region(), filters).await {
                     Ok(mut instances) => {
                         all_instances.append(&mut instances);
                         tracing::info!("Successfully collected {} instances from fallback region {} using cross-region fallback", all_instances.len(), self.client.fallback_region());
@@ -54,105 +118,149 @@ impl Ec2Service {
         Ok(all_instances)
     }
 
+    /// Collect EC2 instances from each of the given regions concurrently
+    pub async fn collect_instances_in_regions(&self, regions: &[String]) -> RegionalCollection<AwsInstance> {
+        tracing::info!("Collecting EC2 instances from {} regions", regions.len());
+
+        let client = self.client.clone();
+        collect_across_regions(regions, MAX_CONCURRENT_REGIONS, move |region| {
+            let client = client.clone();
+            async move {
+                let regional_client = if region == client.config.region {
+                    client
+                } else {
+                    client.for_region(&region).await?
+                };
+                Ec2Service::new(regional_client).collect_instances_in_region(&region, &InstanceFilters::default()).await
+            }
+        })
+        .await
+    }
+
     /// Collect EC2 instances using cross-region fallback client
-    async fn collect_instances_cross_region(&self, region: &str) -> AwsResult<Vec<AwsInstance>> {
+    async fn collect_instances_cross_region(&self, region: &str, filters: &InstanceFilters) -> AwsResult<Vec<AwsInstance>> {
         tracing::debug!("Attempting cross-region collection for EC2 instances in region: {}", region);
 
-        // Create a new client for the fallback region
-        use crate::aws::AwsConfig;
-        use aws_config::{BehaviorVersion, Region};
-        use aws_sdk_ec2::Client as Ec2Client;
-
-        let config = AwsConfig::load_from_file().map_err(|e| {
-            tracing::error!("Failed to load AWS config for cross-region fallback: {:?}", e);
-            AwsError::ConfigError(format!("Config load failed: {}", e))
+        // Create a new client for the fallback region with this client's credentials
+        let fallback_client = self.client.for_region(region).await.map_err(|e| {
+            tracing::error!("Cross-region fallback connection test failed for region {}: {:?}", region, e);
+            AwsError::NetworkError(format!("Cross-region connection failed: {}", e))
         })?;
-
-        let credentials = Credentials::new(
-            config.credentials.access_key_id.clone(),
-            config.credentials.secret_access_key.clone(),
-            None, // session token
-            None, // expiry
-            "pocket-architect",
-        );
-
-        let aws_config = aws_config::defaults(BehaviorVersion::v2025_08_07())
-            .region(Region::new(region.to_string()))
-            .credentials_provider(credentials)
-            .load()
-            .await;
-
-        let ec2_client = Ec2Client::new(&aws_config);
-
-        // Test the fallback connection
-        ec2_client
-            .describe_regions()
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Cross-region fallback connection test failed for region {}: {:?}", region, e);
-                AwsError::NetworkError(format!("Cross-region connection failed: {}", e))
-            })?;
+        let ec2_client = &fallback_client.ec2_client;
 
         // Now collect instances with the fallback client
-        let response = ec2_client
-            .describe_instances()
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to describe instances in fallback region {}: {:?}", region, e);
-                AwsError::SdkError(e.into())
-            })?;
-
-        let mut instances = Vec::new();
-
-        for reservation in response.reservations().iter() {
-            for instance in reservation.instances().iter() {
-                if let Some(mapped_instance) = self.map_aws_instance(instance, region) {
-                    instances.push(mapped_instance);
-                }
-            }
-        }
+        let describe = with_retry(&self.client.config.retries, "DescribeInstances", || async move {
+            describe_all_instances(&single_attempt_ec2(ec2_client), region, describe_filters(filters))
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to describe instances in fallback region {}: {:?}", region, e);
+                    diagnostics::record("ec2", "DescribeInstances", AwsError::SdkError(e.into()))
+                })
+        });
+        let (sdk_instances, pages) = with_timeout(self.client.config.timeouts.instance_operations(), "DescribeInstances", describe).await?;
+        tracing::debug!("Read {} DescribeInstances pages from fallback region {}", pages, region);
+
+        let specs = self.instance_type_specs(ec2_client, &sdk_instances).await;
+        let volumes = attached_volumes(ec2_client, &self.client.config.retries, &sdk_instances).await;
+        let groups = referenced_security_groups(ec2_client, &self.client.config.retries, &sdk_instances, region).await;
+        let instances: Vec<AwsInstance> = sdk_instances
+            .iter()
+            .filter_map(|instance| self.map_aws_instance(instance, region, &specs, &volumes, &groups))
+            .collect();
 
         tracing::info!("Successfully collected {} instances using cross-region fallback in {}", instances.len(), region);
         Ok(instances)
     }
 
     /// Collect EC2 instances in a specific region
-    async fn collect_instances_in_region(&self, region: &str) -> AwsResult<Vec<AwsInstance>> {
+    async fn collect_instances_in_region(&self, region: &str, filters: &InstanceFilters) -> AwsResult<Vec<AwsInstance>> {
         tracing::debug!("Collecting EC2 instances in region: {}", region);
 
-        let ec2_client = &self.client.ec2_client;
+        let describe = with_retry(&self.client.config.retries, "DescribeInstances", || async move {
+            describe_all_instances(&single_attempt_ec2(&self.client.ec2_client), region, describe_filters(filters))
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to describe instances in region {}: {:?}", region, e);
+                    diagnostics::record("ec2", "DescribeInstances", AwsError::SdkError(e.into()))
+                })
+        });
+        let (sdk_instances, pages) = with_timeout(self.client.config.timeouts.instance_operations(), "DescribeInstances", describe).await?;
+
+        let specs = self.instance_type_specs(&self.client.ec2_client, &sdk_instances).await;
+        let volumes = attached_volumes(&self.client.ec2_client, &self.client.config.retries, &sdk_instances).await;
+        let groups = referenced_security_groups(&self.client.ec2_client, &self.client.config.retries, &sdk_instances, region).await;
+        let instances: Vec<AwsInstance> = sdk_instances
+            .iter()
+            .filter_map(|instance| self.map_aws_instance(instance, region, &specs, &volumes, &groups))
+            .collect();
 
-        let response = ec2_client
-            .describe_instances()
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to describe instances in region {}: {:?}", region, e);
-                AwsError::SdkError(e.into())
-            })?;
+        tracing::debug!("Collected {} instances from {} pages in region {}", instances.len(), pages, region);
+        Ok(instances)
+    }
+
+    /// Hardware specs for the types of `instances`, from the cache or
+    /// DescribeInstanceTypes. Types the API can't describe are left out so
+    /// mapping falls back to the static table.
+    async fn instance_type_specs(&self, ec2_client: &aws_sdk_ec2::Client, instances: &[AwsSdkInstance]) -> HashMap<String, InstanceTypeSpec> {
+        let mut types: Vec<String> = instances
+            .iter()
+            .filter_map(|i| i.instance_type().map(|t| t.as_str().to_string()))
+            .collect();
+        types.sort();
+        types.dedup();
 
-        let mut instances = Vec::new();
+        let mut specs = self.client.cache.get_instance_types(&types).await;
+        let missing: Vec<InstanceType> = types
+            .iter()
+            .filter(|t| !specs.contains_key(*t))
+            .map(|t| InstanceType::from(t.as_str()))
+            .collect();
+        if missing.is_empty() {
+            return specs;
+        }
+
+        let mut fetched = Vec::new();
+        for batch in missing.chunks(DESCRIBE_INSTANCE_TYPES_BATCH_SIZE) {
+            let result = with_retry(&self.client.config.retries, "DescribeInstanceTypes", || async move {
+                single_attempt_ec2(ec2_client)
+                    .describe_instance_types()
+                    .set_instance_types(Some(batch.to_vec()))
+                    .into_paginator()
+                    .items()
+                    .send()
+                    .collect::<Result<Vec<_>, _>>()
+                    .await
+                    .map_err(|e| AwsError::SdkError(e.into()))
+            })
+            .await;
 
-        for reservation in response.reservations().iter() {
-            for instance in reservation.instances().iter() {
-                if let Some(mapped_instance) = self.map_aws_instance(instance, region) {
-                    instances.push(mapped_instance);
+            match result {
+                Ok(infos) => fetched.extend(infos.iter().filter_map(instance_type_spec)),
+                Err(e) => {
+                    tracing::warn!("Failed to describe instance types, using built-in specs: {}", e);
+                    break;
                 }
             }
         }
 
-        tracing::debug!("Collected {} instances from region {}", instances.len(), region);
-        Ok(instances)
+        self.client.cache.put_instance_types(fetched.clone()).await;
+        specs.extend(fetched.into_iter().map(|spec| (spec.instance_type.clone(), spec)));
+        specs
     }
 
     /// Map AWS SDK instance to our custom AwsInstance type
-    fn map_aws_instance(&self, instance: &AwsSdkInstance, region: &str) -> Option<AwsInstance> {
+    fn map_aws_instance(
+        &self,
+        instance: &AwsSdkInstance,
+        region: &str,
+        specs: &HashMap<String, InstanceTypeSpec>,
+        volumes: &HashMap<String, AwsVolume>,
+        groups: &HashMap<String, AwsSecurityGroupInfo>,
+    ) -> Option<AwsInstance> {
         let instance_id = instance.instance_id().unwrap_or("unknown").to_string();
 
         let instance_type = match instance.instance_type() {
-            Some(it) => format!("{:?}", it),
+            Some(it) => it.as_str().to_string(),
             None => "unknown".to_string(),
         };
 
@@ -173,31 +281,18 @@ impl Ec2Service {
             .unwrap_or("unknown")
             .to_string();
 
-        let cpu_count = instance.cpu_options()
-            .and_then(|cpu| cpu.core_count())
-            .unwrap_or(1) as i32;
+        let spec = instance.instance_type().and_then(|it| specs.get(it.as_str()));
+        let (cpu_count, memory_gb, network_performance) = instance_resources(instance, spec);
 
-        let memory_gb = instance.instance_type()
-            .map(|it| self.instance_type_memory_gb(it.clone()))
-            .unwrap_or(1.0);
-
-        let storage_gb = 0.0; // TODO: Calculate actual EBS storage size
-
-        let network_performance = instance.instance_type()
-            .map(|it| self.instance_type_network_performance(it.clone()))
-            .unwrap_or("unknown".to_string());
+        let volumes = instance_volumes(instance, volumes);
+        let storage_gb = volumes.iter().map(|v| v.size_gb as f64).sum();
 
         let public_ip = instance.public_ip_address().map(|s| s.to_string());
         let private_ip = instance.private_ip_address().map(|s| s.to_string());
+        let network_interfaces = instance_network_interfaces(instance);
+        let ipv6_address = primary_ipv6_address(instance, &network_interfaces);
 
-        let security_groups = instance.security_groups()
-            .iter()
-            .map(|sg| AwsSecurityGroup {
-                group_id: sg.group_id().unwrap_or("unknown").to_string(),
-                group_name: sg.group_name().unwrap_or("unknown").to_string(),
-                description: None,
-            })
-            .collect();
+        let security_groups = instance_security_groups(instance, groups);
 
         let key_pairs = instance.key_name()
             .map(|kn| vec![kn.to_string()])
@@ -241,9 +336,12 @@ impl Ec2Service {
             cpu_count,
             memory_gb,
             storage_gb,
+            volumes,
             network_performance,
             public_ip,
             private_ip,
+            ipv6_address,
+            network_interfaces,
             security_groups,
             key_pairs,
             tags,
@@ -252,71 +350,12 @@ impl Ec2Service {
             ebs_optimized,
             virtualization_type,
             architecture,
+            lifecycle: instance_lifecycle(instance).to_string(),
+            platform_details: instance.platform_details().map(str::to_string),
+            coverage: InstanceCoverage::OnDemand,
         })
     }
 
-    /// Get memory in GB for instance type (simplified mapping)
-    fn instance_type_memory_gb(&self, instance_type: InstanceType) -> f64 {
-        match instance_type {
-            InstanceType::T2Micro => 1.0,
-            InstanceType::T2Small => 2.0,
-            InstanceType::T2Medium => 4.0,
-            InstanceType::T2Large => 8.0,
-            InstanceType::T2Xlarge => 16.0,
-            InstanceType::T22xlarge => 32.0,
-            InstanceType::T3Micro => 1.0,
-            InstanceType::T3Small => 2.0,
-            InstanceType::T3Medium => 4.0,
-            InstanceType::T3Large => 8.0,
-            InstanceType::T3Xlarge => 16.0,
-            InstanceType::T32xlarge => 32.0,
-            InstanceType::M5Large => 8.0,
-            InstanceType::M5Xlarge => 16.0,
-            InstanceType::M52xlarge => 32.0,
-            InstanceType::M54xlarge => 64.0,
-            InstanceType::M58xlarge => 128.0,
-            InstanceType::M5a12xlarge => 192.0,
-            InstanceType::M5a16xlarge => 256.0,
-            InstanceType::M5a24xlarge => 384.0,
-            InstanceType::M5aLarge => 8.0,
-            InstanceType::M5aXlarge => 16.0,
-            InstanceType::M5a2xlarge => 32.0,
-            InstanceType::M5a4xlarge => 64.0,
-            InstanceType::M5a8xlarge => 128.0,
-            InstanceType::M5a12xlarge => 192.0,
-            InstanceType::M5a16xlarge => 256.0,
-            InstanceType::M5a24xlarge => 384.0,
-            InstanceType::C5Large => 4.0,
-            InstanceType::C5Xlarge => 8.0,
-            InstanceType::C52xlarge => 16.0,
-            InstanceType::C54xlarge => 32.0,
-            InstanceType::C59xlarge => 72.0,
-            InstanceType::C512xlarge => 96.0,
-            InstanceType::C518xlarge => 144.0,
-            InstanceType::C524xlarge => 192.0,
-            _ => 8.0, // Default fallback
-        }
-    }
-
-    /// Get network performance for instance type
-    fn instance_type_network_performance(&self, instance_type: InstanceType) -> String {
-        match instance_type {
-            InstanceType::T2Micro | InstanceType::T2Small | InstanceType::T2Medium => "Low to Moderate".to_string(),
-            InstanceType::T2Large | InstanceType::T2Xlarge | InstanceType::T22xlarge => "Moderate".to_string(),
-            InstanceType::T3Micro | InstanceType::T3Small | InstanceType::T3Medium => "Low to Moderate".to_string(),
-            InstanceType::T3Large | InstanceType::T3Xlarge | InstanceType::T32xlarge => "Moderate".to_string(),
-            InstanceType::M5Large | InstanceType::M5Xlarge => "Up to 10 Gigabit".to_string(),
-            InstanceType::M52xlarge | InstanceType::M54xlarge => "Up to 10 Gigabit".to_string(),
-            InstanceType::M58xlarge | InstanceType::M5a12xlarge => "10 Gigabit".to_string(),
-            InstanceType::M5a16xlarge | InstanceType::M5a24xlarge => "20 Gigabit".to_string(),
-            InstanceType::C5Large | InstanceType::C5Xlarge => "Up to 10 Gigabit".to_string(),
-            InstanceType::C52xlarge | InstanceType::C54xlarge => "Up to 10 Gigabit".to_string(),
-            InstanceType::C59xlarge | InstanceType::C512xlarge => "10 Gigabit".to_string(),
-            InstanceType::C518xlarge | InstanceType::C524xlarge => "20 Gigabit".to_string(),
-            _ => "Moderate".to_string(),
-        }
-    }
-
     /// Create a new EC2 instance with timestamp naming
     pub async fn create_instance(
         &self,
@@ -325,14 +364,120 @@ impl Ec2Service {
         key_name: Option<&str>,
         security_group_ids: Vec<String>,
         region: Option<&str>,
+        options: &LaunchOptions,
     ) -> AwsResult<String> {
         let region = region.unwrap_or(self.client.primary_region());
         tracing::info!("Creating EC2 instance in region {}: type={}, ami={}", region, instance_type, ami_id);
 
-        let timestamp = Utc::now().format("%Y%m%d-%H%M%S");
-        let unique_id = Uuid::new_v4().simple().to_string()[..8].to_string();
-        let instance_name = format!("pocket-architect-{}-{}", timestamp, unique_id);
+        let user_data = options.user_data.as_deref().map(encode_user_data).transpose()?;
+        self.resolve_launch_vpc(options).await?;
+        let instance_name = generate_resource_name();
+        let mut request = self.run_instances_request(instance_type, ami_id, &instance_name, key_name, security_group_ids, user_data);
+
+        if let Some(subnet_id) = &options.subnet_id {
+            request = request.subnet_id(subnet_id);
+        }
+        if let Some(market_options) = market_options(options) {
+            request = request.instance_market_options(market_options);
+        }
+
+        if let Some(root_volume) = &options.root_volume {
+            let (device_name, snapshot_size_gb) = self.root_device(ami_id).await?;
+            validate_root_volume(root_volume, snapshot_size_gb)?;
+            request = request.block_device_mappings(root_block_device_mapping(&device_name, root_volume));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to create EC2 instance: {:?}", e);
+                diagnostics::record("ec2", "RunInstances", AwsError::SdkError(e.into()))
+            })?;
+
+        let instance_id = response.instances()
+            .first()
+            .and_then(|i| i.instance_id())
+            .ok_or_else(|| {
+                tracing::error!("No instance ID returned from create operation");
+                AwsError::OperationError("Failed to get instance ID after creation".to_string())
+            })?;
+
+        tracing::info!("Successfully created {} EC2 instance: {} ({})", options.purchase_option.lifecycle(), instance_id, instance_name);
+        Ok(instance_id.to_string())
+    }
+
+    /// Root device name of an AMI and the size of its root snapshot, which a
+    /// custom root volume must not be smaller than
+    async fn root_device(&self, ami_id: &str) -> AwsResult<(String, Option<i32>)> {
+        let response = self.client.ec2_client
+            .describe_images()
+            .image_ids(ami_id)
+            .send()
+            .await
+            .map_err(|e| diagnostics::record("ec2", "DescribeImages", AwsError::SdkError(e.into())))?;
+
+        let image = response
+            .images()
+            .first()
+            .ok_or_else(|| AwsError::OperationError(format!("AMI {} not found", ami_id)))?;
+        let device_name = image.root_device_name().unwrap_or("/dev/xvda").to_string();
+        let snapshot_size_gb = image
+            .block_device_mappings()
+            .iter()
+            .find(|mapping| mapping.device_name() == Some(device_name.as_str()))
+            .and_then(|mapping| mapping.ebs())
+            .and_then(|ebs| ebs.volume_size());
+
+        Ok((device_name, snapshot_size_gb))
+    }
+
+    /// Generate a new key pair, launch the instance with it attached and store the
+    /// private key keyed by the new instance id. The key pair is only deleted
+    /// again when the launch fails; once the instance runs it keeps its key.
+    pub async fn create_instance_with_key_pair(
+        &self,
+        instance_type: &str,
+        ami_id: &str,
+        security_group_ids: Vec<String>,
+        region: Option<&str>,
+        options: &LaunchOptions,
+    ) -> AwsResult<KeyPairLaunch> {
+        let key_pair = self.create_key_pair(&generate_resource_name()).await?;
+        let key_name = key_pair.key_name.clone();
+
+        let result = launch_with_key_pair(
+            key_pair,
+            |key_name| async move {
+                self.create_instance(instance_type, ami_id, Some(&key_name), security_group_ids, region, options).await
+            },
+            |instance_id, key_pair| {
+                crate::credential_store::store_ssh_private_key(instance_id, &key_pair.private_key)
+                    .map(|_| ())
+                    .map_err(|e| AwsError::OperationError(format!("Failed to store SSH private key: {}", e)))
+            },
+        ).await;
+
+        if result.is_err() {
+            // Don't leave an orphaned key pair behind when the launch fails
+            if let Err(delete_err) = self.delete_key_pair(&key_name).await {
+                tracing::warn!("Failed to clean up key pair {}: {}", key_name, delete_err);
+            }
+        }
+        result
+    }
 
+    /// Build the run_instances request for a launch, tagging the instance with its name.
+    /// `user_data` must already be base64-encoded.
+    pub fn run_instances_request(
+        &self,
+        instance_type: &str,
+        ami_id: &str,
+        instance_name: &str,
+        key_name: Option<&str>,
+        security_group_ids: Vec<String>,
+        user_data: Option<String>,
+    ) -> RunInstancesFluentBuilder {
         let ec2_client = &self.client.ec2_client;
 
         let mut request = ec2_client
@@ -347,7 +492,7 @@ impl Ec2Service {
                     .tags(
                         aws_sdk_ec2::types::Tag::builder()
                             .key("Name")
-                            .value(&instance_name)
+                            .value(instance_name)
                             .build()
                     )
                     .tags(
@@ -373,42 +518,99 @@ impl Ec2Service {
             request = request.security_group_ids(sg_id);
         }
 
-        let response = request
+        if let Some(user_data) = user_data {
+            request = request.user_data(user_data);
+        }
+
+        request
+    }
+
+    /// Create a new EC2 key pair, returning the private key material
+    pub async fn create_key_pair(&self, key_name: &str) -> AwsResult<GeneratedKeyPair> {
+        tracing::info!("Creating EC2 key pair: {}", key_name);
+
+        let ec2_client = &self.client.ec2_client;
+
+        let response = ec2_client
+            .create_key_pair()
+            .key_name(key_name)
+            .key_type(aws_sdk_ec2::types::KeyType::Rsa)
+            .key_format(aws_sdk_ec2::types::KeyFormat::Pem)
+            .tag_specifications(
+                aws_sdk_ec2::types::TagSpecification::builder()
+                    .resource_type(aws_sdk_ec2::types::ResourceType::KeyPair)
+                    .tags(
+                        aws_sdk_ec2::types::Tag::builder()
+                            .key("CreatedBy")
+                            .value("PocketArchitect")
+                            .build()
+                    )
+                    .build()
+            )
             .send()
             .await
             .map_err(|e| {
-                tracing::error!("Failed to create EC2 instance: {:?}", e);
-                AwsError::SdkError(e.into())
+                tracing::error!("Failed to create key pair {}: {:?}", key_name, e);
+                diagnostics::record("ec2", "CreateKeyPair", AwsError::SdkError(e.into()))
             })?;
 
-        let instance_id = response.instances()
-            .first()
-            .and_then(|i| i.instance_id())
-            .ok_or_else(|| {
-                tracing::error!("No instance ID returned from create operation");
-                AwsError::OperationError("Failed to get instance ID after creation".to_string())
-            })?;
+        let private_key = response.key_material()
+            .ok_or_else(|| AwsError::OperationError(format!("No key material returned for key pair {}", key_name)))?;
 
-        tracing::info!("Successfully created EC2 instance: {} ({})", instance_id, instance_name);
-        Ok(instance_id.to_string())
+        tracing::info!("Successfully created EC2 key pair: {}", key_name);
+        Ok(GeneratedKeyPair {
+            key_name: response.key_name().unwrap_or(key_name).to_string(),
+            key_fingerprint: response.key_fingerprint().unwrap_or("").to_string(),
+            private_key: private_key.to_string(),
+        })
     }
 
-    /// Delete (terminate) an EC2 instance
-    pub async fn delete_instance(&self, instance_id: &str) -> AwsResult<()> {
-        tracing::info!("Terminating EC2 instance: {}", instance_id);
+    /// Delete an EC2 key pair by name
+    pub async fn delete_key_pair(&self, key_name: &str) -> AwsResult<()> {
+        tracing::info!("Deleting EC2 key pair: {}", key_name);
 
         let ec2_client = &self.client.ec2_client;
 
         ec2_client
-            .terminate_instances()
-            .instance_ids(instance_id)
+            .delete_key_pair()
+            .key_name(key_name)
             .send()
             .await
             .map_err(|e| {
-                tracing::error!("Failed to terminate EC2 instance {}: {:?}", instance_id, e);
-                AwsError::SdkError(e.into())
+                tracing::error!("Failed to delete key pair {}: {:?}", key_name, e);
+                diagnostics::record("ec2", "DeleteKeyPair", AwsError::SdkError(e.into()))
             })?;
 
+        Ok(())
+    }
+
+    /// Delete (terminate) an EC2 instance. With `dry_run` set, EC2 checks the
+    /// instance and the caller's permissions without terminating anything.
+    pub async fn delete_instance(&self, instance_id: &str, dry_run: bool) -> AwsResult<()> {
+        tracing::info!("Terminating EC2 instance: {} (dry run: {})", instance_id, dry_run);
+
+        let ec2_client = &self.client.ec2_client;
+
+        let result = ec2_client
+            .terminate_instances()
+            .instance_ids(instance_id)
+            .dry_run(dry_run)
+            .send()
+            .await;
+
+        match result {
+            Ok(_) => {}
+            // EC2 answers a dry run that would have succeeded with this error
+            Err(e) if dry_run && e.code() == Some(DRY_RUN_OPERATION_CODE) => {
+                tracing::info!("Dry run: EC2 instance {} would be terminated", instance_id);
+                return Ok(());
+            }
+            Err(e) => {
+                tracing::error!("Failed to terminate EC2 instance {}: {:?}", instance_id, e);
+                return Err(diagnostics::record("ec2", "TerminateInstances", AwsError::SdkError(e.into())));
+            }
+        }
+
         tracing::info!("Successfully initiated termination of EC2 instance: {}", instance_id);
         Ok(())
     }
@@ -426,16 +628,20 @@ impl Ec2Service {
             .await
             .map_err(|e| {
                 tracing::error!("Failed to get instance details for {}: {:?}", instance_id, e);
-                AwsError::SdkError(e.into())
+                diagnostics::record("ec2", "DescribeInstances", AwsError::SdkError(e.into()))
             })?;
 
-        for reservation in response.reservations().iter() {
-            for instance in reservation.instances().iter() {
-                if instance.instance_id() == Some(instance_id) {
-                    let region = self.client.primary_region();
-                    return Ok(self.map_aws_instance(instance, region));
-                }
-            }
+        let found = response.reservations()
+            .iter()
+            .flat_map(|r| r.instances())
+            .find(|instance| instance.instance_id() == Some(instance_id));
+        if let Some(instance) = found {
+            let instances = std::slice::from_ref(instance);
+            let specs = self.instance_type_specs(ec2_client, instances).await;
+            let volumes = attached_volumes(ec2_client, instances).await;
+            let region = self.client.primary_region();
+            let groups = referenced_security_groups(ec2_client, instances, region).await;
+            return Ok(self.map_aws_instance(instance, region, &specs, &volumes, &groups));
         }
 
         tracing::debug!("Instance {} not found", instance_id);
@@ -455,7 +661,7 @@ impl Ec2Service {
             .await
             .map_err(|e| {
                 tracing::error!("Failed to start EC2 instance {}: {:?}", instance_id, e);
-                AwsError::SdkError(e.into())
+                diagnostics::record("ec2", "StartInstances", AwsError::SdkError(e.into()))
             })?;
 
         tracing::info!("Successfully initiated start of EC2 instance: {}", instance_id);
@@ -475,7 +681,7 @@ impl Ec2Service {
             .await
             .map_err(|e| {
                 tracing::error!("Failed to stop EC2 instance {}: {:?}", instance_id, e);
-                AwsError::SdkError(e.into())
+                diagnostics::record("ec2", "StopInstances", AwsError::SdkError(e.into()))
             })?;
 
         tracing::info!("Successfully initiated stop of EC2 instance: {}", instance_id);
@@ -495,34 +701,2309 @@ impl Ec2Service {
             .await
             .map_err(|e| {
                 tracing::error!("Failed to restart EC2 instance {}: {:?}", instance_id, e);
-                AwsError::SdkError(e.into())
+                diagnostics::record("ec2", "RebootInstances", AwsError::SdkError(e.into()))
             })?;
 
         tracing::info!("Successfully initiated restart of EC2 instance: {}", instance_id);
         Ok(())
     }
 
-    /// Get SSH configuration for an instance
-    pub async fn get_ssh_config(&self, instance_id: &str) -> AwsResult<serde_json::Value> {
-        tracing::debug!("Getting SSH config for EC2 instance: {}", instance_id);
+    /// Start, stop or terminate `instance_ids`, with one call per 100
+    /// instances. A failed call only fails its own instances; the report has
+    /// the state changes EC2 returned for the rest.
+    pub async fn bulk_instance_operation(&self, operation: BulkInstanceOperation, instance_ids: &[String]) -> BulkInstanceOperationReport {
+        let mut report = BulkInstanceOperationReport {
+            operation,
+            transitions: Vec::new(),
+            failed: Vec::new(),
+        };
 
-        let instance_details = self.get_instance_details(instance_id).await?;
+        for batch in instance_ids.chunks(MAX_INSTANCES_PER_STATE_CALL) {
+            tracing::info!("Requesting {} of {} EC2 instances", operation.as_str(), batch.len());
+            match self.change_instance_states(operation, batch).await {
+                Ok(changes) => report.transitions.extend(changes.iter().filter_map(map_state_change)),
+                Err(e) => report.failed.push(BulkOperationFailure {
+                    instance_ids: batch.to_vec(),
+                    error: e.to_string(),
+                    api_error: Some(ApiError::aws_with_message(&e, e.to_string())),
+                }),
+            }
+        }
 
-        match instance_details {
-            Some(instance) => {
-                let ssh_config = serde_json::json!({
-                    "host": instance.public_ip.or_else(|| instance.private_ip).unwrap_or_else(|| "unknown".to_string()),
-                    "user": "ec2-user", // Default for Amazon Linux 2
-                    "keyPath": instance.key_pairs.first().map(|k| format!("~/.ssh/{}.pem", k)).unwrap_or_else(|| "~/.ssh/default.pem".to_string()),
-                    "port": 22,
-                    "instanceId": instance.instance_id,
-                    "region": instance.region,
-                    "availabilityZone": instance.availability_zone
-                });
+        report
+    }
 
-                Ok(ssh_config)
-            }
-            None => Err(AwsError::OperationError(format!("Instance {} not found", instance_id)))
+    async fn change_instance_states(&self, operation: BulkInstanceOperation, instance_ids: &[String]) -> AwsResult<Vec<InstanceStateChange>> {
+        let ec2_client = &self.client.ec2_client;
+        let ids = Some(instance_ids.to_vec());
+
+        let (action, result) = match operation {
+            BulkInstanceOperation::Start => (
+                "StartInstances",
+                ec2_client.start_instances().set_instance_ids(ids).send().await
+                    .map(|output| output.starting_instances().to_vec())
+                    .map_err(|e| AwsError::SdkError(e.into())),
+            ),
+            BulkInstanceOperation::Stop => (
+                "StopInstances",
+                ec2_client.stop_instances().set_instance_ids(ids).send().await
+                    .map(|output| output.stopping_instances().to_vec())
+                    .map_err(|e| AwsError::SdkError(e.into())),
+            ),
+            BulkInstanceOperation::Terminate => (
+                "TerminateInstances",
+                ec2_client.terminate_instances().set_instance_ids(ids).send().await
+                    .map(|output| output.terminating_instances().to_vec())
+                    .map_err(|e| AwsError::SdkError(e.into())),
+            ),
+        };
+
+        result.map_err(|e| {
+            tracing::error!("{} failed for {} instances: {:?}", action, instance_ids.len(), e);
+            diagnostics::record("ec2", action, e)
+        })
+    }
+
+    /// Overwrite the `Name` tag of an EC2 instance
+    pub async fn set_name_tag(&self, instance_id: &str, name: &str) -> AwsResult<()> {
+        tracing::info!("Renaming EC2 instance {} to {}", instance_id, name);
+
+        self.client.ec2_client
+            .create_tags()
+            .resources(instance_id)
+            .tags(aws_sdk_ec2::types::Tag::builder().key("Name").value(name).build())
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to rename EC2 instance {}: {:?}", instance_id, e);
+                diagnostics::record("ec2", "CreateTags", AwsError::SdkError(e.into()))
+            })?;
+
+        Ok(())
+    }
+
+    /// Change the type of an instance. A running instance is only stopped when
+    /// `allow_stop` is set and, with `restart`, started again once the new type is
+    /// applied. Nothing is changed when the type isn't offered in the instance's
+    /// availability zone.
+    pub async fn resize_instance(&self, instance_id: &str, new_type: &str, allow_stop: bool, restart: bool) -> AwsResult<InstanceResize> {
+        let instance = self.get_instance_details(instance_id).await?
+            .ok_or_else(|| AwsError::OperationError(format!("Instance {} not found", instance_id)))?;
+        let plan = resize_plan(&InstanceStateName::from(instance.state.as_str()), restart)?;
+
+        if plan.contains(&ResizeStep::Stop) && !allow_stop {
+            return Ok(InstanceResize::StopRequired { state: instance.state });
+        }
+
+        if !self.instance_type_offered_in_zone(new_type, &instance.availability_zone).await? {
+            return Ok(InstanceResize::TypeUnavailable {
+                instance_type: new_type.to_string(),
+                availability_zone: instance.availability_zone,
+            });
         }
+
+        tracing::info!("Resizing EC2 instance {} from {} to {}", instance_id, instance.instance_type, new_type);
+
+        let instance_type = InstanceType::from(new_type);
+        run_resize(&plan, |step| {
+            let instance_type = instance_type.clone();
+            async move {
+                match step {
+                    ResizeStep::Stop => self.stop_instance(instance_id).await,
+                    ResizeStep::WaitStopped => match self.wait_for_instance_state(instance_id, InstanceStateName::Stopped).await? {
+                        InstanceStateWait::Reached(_) => Ok(()),
+                        InstanceStateWait::TimedOut { last_state } => Err(AwsError::OperationError(format!(
+                            "Timed out waiting for instance {} to stop (last state: {})",
+                            instance_id, last_state
+                        ))),
+                    },
+                    ResizeStep::Modify => self.modify_instance_type(instance_id, instance_type).await,
+                    ResizeStep::Start => self.start_instance(instance_id).await,
+                }
+            }
+        }).await?;
+
+        tracing::info!("Successfully resized EC2 instance {} to {}", instance_id, new_type);
+        let resized = self.get_instance_details(instance_id).await?
+            .ok_or_else(|| AwsError::OperationError(format!("Instance {} not found", instance_id)))?;
+        Ok(InstanceResize::Resized(resized))
+    }
+
+    /// Whether `instance_type` can be launched in `availability_zone`
+    pub async fn instance_type_offered_in_zone(&self, instance_type: &str, availability_zone: &str) -> AwsResult<bool> {
+        let response = self.client.ec2_client
+            .describe_instance_type_offerings()
+            .location_type(LocationType::AvailabilityZone)
+            .filters(Filter::builder().name("location").values(availability_zone).build())
+            .filters(Filter::builder().name("instance-type").values(instance_type).build())
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to describe instance type offerings in {}: {:?}", availability_zone, e);
+                diagnostics::record("ec2", "DescribeInstanceTypeOfferings", AwsError::SdkError(e.into()))
+            })?;
+
+        Ok(instance_type_offered(response.instance_type_offerings(), instance_type))
+    }
+
+    /// Linux spot prices for `instance_type` over the last
+    /// `SPOT_PRICE_HISTORY_HOURS`, newest first, in one availability zone or
+    /// every zone of the region
+    pub async fn get_spot_price_history(&self, instance_type: &str, availability_zone: Option<&str>) -> AwsResult<Vec<AwsSpotPrice>> {
+        let start = Utc::now() - chrono::Duration::hours(SPOT_PRICE_HISTORY_HOURS);
+        let history = self.client.ec2_client
+            .describe_spot_price_history()
+            .instance_types(InstanceType::from(instance_type))
+            .product_descriptions(SPOT_PRODUCT_DESCRIPTION)
+            .set_availability_zone(availability_zone.map(|az| az.to_string()))
+            .start_time(AwsDateTime::from_secs(start.timestamp()))
+            .into_paginator()
+            .items()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to describe spot price history for {}: {:?}", instance_type, e);
+                diagnostics::record("ec2", "DescribeSpotPriceHistory", AwsError::SdkError(e.into()))
+            })?;
+
+        let mut prices: Vec<AwsSpotPrice> = history.iter().filter_map(map_spot_price).collect();
+        prices.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(prices)
+    }
+
+    /// Active reserved instances in the client's region
+    pub async fn collect_reserved_instances(&self) -> AwsResult<Vec<AwsReservedInstance>> {
+        let response = with_retry(&self.client.config.retries, "DescribeReservedInstances", || async move {
+            single_attempt_ec2(&self.client.ec2_client)
+                .describe_reserved_instances()
+                .filters(Filter::builder().name("state").values(ReservedInstanceState::Active.as_str()).build())
+                .send()
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to describe reserved instances: {:?}", e);
+                    diagnostics::record("ec2", "DescribeReservedInstances", AwsError::SdkError(e.into()))
+                })
+        })
+        .await?;
+
+        let region = self.client.config.region.clone();
+        Ok(response.reserved_instances()
+            .iter()
+            .filter_map(|reservation| map_reserved_instance(reservation, &region))
+            .collect())
+    }
+
+    /// Create an AMI from an instance and return its image id. The image starts
+    /// out `pending`; see `wait_for_image`. With `no_reboot` the instance keeps
+    /// running, at the cost of file system consistency in the image.
+    pub async fn create_image(&self, instance_id: &str, name: &str, description: Option<&str>, no_reboot: bool) -> AwsResult<String> {
+        tracing::info!("Creating AMI {} from EC2 instance {} (no reboot: {})", name, instance_id, no_reboot);
+
+        let response = self.client.ec2_client
+            .create_image()
+            .instance_id(instance_id)
+            .name(name)
+            .set_description(description.map(str::to_string))
+            .no_reboot(no_reboot)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to create AMI from EC2 instance {}: {:?}", instance_id, e);
+                diagnostics::record("ec2", "CreateImage", AwsError::SdkError(e.into()))
+            })?;
+
+        let image_id = ami_id_from_response(&response)?;
+        tracing::info!("Successfully initiated AMI {} from EC2 instance {}", image_id, instance_id);
+        Ok(image_id)
+    }
+
+    /// Current state of an AMI (`pending`, `available`, `failed`, ...)
+    pub async fn get_image_state(&self, image_id: &str) -> AwsResult<String> {
+        let response = self.client.ec2_client
+            .describe_images()
+            .image_ids(image_id)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to describe AMI {}: {:?}", image_id, e);
+                diagnostics::record("ec2", "DescribeImages", AwsError::SdkError(e.into()))
+            })?;
+
+        response.images()
+            .first()
+            .and_then(|image| image.state())
+            .map(|state| state.as_str().to_string())
+            .ok_or_else(|| AwsError::OperationError(format!("AMI {} not found", image_id)))
+    }
+
+    /// Ids of the EBS snapshots backing an AMI's block devices
+    pub async fn get_image_snapshot_ids(&self, image_id: &str) -> AwsResult<Vec<String>> {
+        let response = self.client.ec2_client
+            .describe_images()
+            .image_ids(image_id)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to describe AMI {}: {:?}", image_id, e);
+                diagnostics::record("ec2", "DescribeImages", AwsError::SdkError(e.into()))
+            })?;
+
+        let image = response.images()
+            .first()
+            .ok_or_else(|| AwsError::OperationError(format!("AMI {} not found", image_id)))?;
+        Ok(image_snapshot_ids(image))
     }
-}
\ No newline at end of file
+
+    /// Deregister an AMI. Its snapshots are left in place.
+    pub async fn deregister_image(&self, image_id: &str) -> AwsResult<()> {
+        tracing::info!("Deregistering AMI {}", image_id);
+
+        self.client.ec2_client
+            .deregister_image()
+            .image_id(image_id)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to deregister AMI {}: {:?}", image_id, e);
+                diagnostics::record("ec2", "DeregisterImage", AwsError::SdkError(e.into()))
+            })?;
+
+        Ok(())
+    }
+
+    /// Poll an AMI until it leaves the `pending` state and return the final
+    /// state. Gives up after ten minutes, returning `pending`.
+    pub async fn wait_for_image(&self, image_id: &str) -> AwsResult<String> {
+        for _ in 0..IMAGE_POLL_ATTEMPTS {
+            let state = self.get_image_state(image_id).await?;
+            if state != "pending" {
+                return Ok(state);
+            }
+            tokio::time::sleep(IMAGE_POLL_INTERVAL).await;
+        }
+
+        tracing::warn!("AMI {} is still pending after polling", image_id);
+        Ok("pending".to_string())
+    }
+
+    /// Set the instance type attribute of a stopped instance
+    async fn modify_instance_type(&self, instance_id: &str, instance_type: InstanceType) -> AwsResult<()> {
+        self.client.ec2_client
+            .modify_instance_attribute()
+            .instance_id(instance_id)
+            .instance_type(AttributeValue::builder().value(instance_type.as_str()).build())
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to change type of EC2 instance {}: {:?}", instance_id, e);
+                diagnostics::record("ec2", "ModifyInstanceAttribute", AwsError::SdkError(e.into()))
+            })?;
+        Ok(())
+    }
+
+    /// Poll until the instance reaches `target`, giving up after five minutes.
+    /// Fails early if the instance is terminating, since it will never get there.
+    pub async fn wait_for_instance_state(&self, instance_id: &str, target: InstanceStateName) -> AwsResult<InstanceStateWait> {
+        self.poll_instance_state(instance_id, target, INSTANCE_STATE_POLL_INTERVAL, INSTANCE_STATE_POLL_ATTEMPTS).await
+    }
+
+    pub(crate) async fn poll_instance_state(
+        &self,
+        instance_id: &str,
+        target: InstanceStateName,
+        interval: Duration,
+        attempts: u32,
+    ) -> AwsResult<InstanceStateWait> {
+        let mut last_state = "unknown".to_string();
+
+        for attempt in 0..attempts {
+            if attempt > 0 {
+                tokio::time::sleep(interval).await;
+            }
+
+            let Some(instance) = self.get_instance_details(instance_id).await? else {
+                continue;
+            };
+            if InstanceStateName::from(instance.state.as_str()) == target {
+                return Ok(InstanceStateWait::Reached(instance));
+            }
+            if instance.state == "terminated" {
+                return Err(AwsError::OperationError(format!(
+                    "Instance {} was terminated and won't become {}",
+                    instance_id,
+                    target.as_str()
+                )));
+            }
+            last_state = instance.state;
+        }
+
+        tracing::warn!("Instance {} is still {} after waiting for {}", instance_id, last_state, target.as_str());
+        Ok(InstanceStateWait::TimedOut { last_state })
+    }
+
+    /// Total size in GiB of the EBS volumes attached to each of the given instances
+    pub async fn get_attached_volume_totals(&self, instance_ids: &[String]) -> AwsResult<HashMap<String, i64>> {
+        if instance_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        tracing::debug!("Getting attached volume sizes for {} instances", instance_ids.len());
+
+        let volumes = self.describe_attached_volumes(instance_ids).await?;
+        Ok(volume_totals_by_instance(&volumes))
+    }
+
+    /// Ids of the EBS volumes attached to each of the given instances
+    pub async fn get_attached_volume_ids(&self, instance_ids: &[String]) -> AwsResult<HashMap<String, Vec<String>>> {
+        if instance_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let volumes = self.describe_attached_volumes(instance_ids).await?;
+        let mut ids: HashMap<String, Vec<String>> = HashMap::new();
+        for volume in &volumes {
+            let Some(volume_id) = volume.volume_id() else { continue };
+            for instance_id in volume.attachments().iter().filter_map(|a| a.instance_id()) {
+                ids.entry(instance_id.to_string()).or_default().push(volume_id.to_string());
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Issue one step of a resource purge. Resources EC2 reports as already
+    /// gone count as done, so an interrupted purge can be run again.
+    pub async fn execute_purge_step(&self, step: &PurgeStep) -> AwsResult<()> {
+        let result = match step.action {
+            PurgeAction::SnapshotVolume => self.final_snapshot(step).await.map(|_| ()),
+            PurgeAction::Terminate => self.delete_instance(&step.resource_id, false).await,
+            PurgeAction::WaitTerminated => self.wait_until_terminated(&step.resource_id).await,
+            PurgeAction::ReleaseEip => self.release_eip(&step.resource_id).await,
+            PurgeAction::DeleteVolume => EbsService::new(self.client.clone()).delete_volume(&step.resource_id).await,
+        };
+
+        match result {
+            Err(e) if is_not_found_error(&e) => {
+                tracing::info!("{} was already gone", step.resource_id);
+                Ok(())
+            }
+            other => other,
+        }
+    }
+
+    /// Snapshot a volume before its instance is terminated, tagged with the
+    /// instance id and the time of the snapshot
+    pub async fn final_snapshot(&self, step: &PurgeStep) -> AwsResult<AwsEbsSnapshot> {
+        let taken_at = Utc::now().to_rfc3339();
+        let description = format!("Final snapshot of {} from {}", step.resource_id, step.instance_id);
+        EbsService::new(self.client.clone())
+            .create_snapshot_tagged(
+                &step.resource_id,
+                Some(&description),
+                &[(FINAL_SNAPSHOT_INSTANCE_TAG_KEY, &step.instance_id), (FINAL_SNAPSHOT_TIME_TAG_KEY, &taken_at)],
+            )
+            .await
+    }
+
+    async fn wait_until_terminated(&self, instance_id: &str) -> AwsResult<()> {
+        match self.get_instance_details(instance_id).await? {
+            None => return Ok(()),
+            Some(instance) if instance.state == "terminated" => return Ok(()),
+            Some(_) => {}
+        }
+
+        match self.wait_for_instance_state(instance_id, InstanceStateName::Terminated).await? {
+            InstanceStateWait::Reached(_) => Ok(()),
+            InstanceStateWait::TimedOut { last_state } => Err(AwsError::TimeoutError(format!(
+                "Instance {} is still {} after waiting for it to terminate",
+                instance_id, last_state
+            ))),
+        }
+    }
+
+    async fn describe_attached_volumes(&self, instance_ids: &[String]) -> AwsResult<Vec<Volume>> {
+        let ec2_client = &self.client.ec2_client;
+
+        ec2_client
+            .describe_volumes()
+            .filters(
+                aws_sdk_ec2::types::Filter::builder()
+                    .name("attachment.instance-id")
+                    .set_values(Some(instance_ids.to_vec()))
+                    .build()
+            )
+            .into_paginator()
+            .items()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to describe volumes: {:?}", e);
+                diagnostics::record("ec2", "DescribeVolumes", AwsError::SdkError(e.into()))
+            })
+    }
+
+    /// List Elastic IPs that are not associated with any instance or network interface.
+    /// Addresses tagged with `EIP_PROTECTED_TAG_KEY` are excluded.
+    pub async fn list_unassociated_eips(&self) -> AwsResult<Vec<AwsElasticIp>> {
+        let region = self.client.primary_region();
+        tracing::info!("Collecting unassociated Elastic IPs in region {}", region);
+
+        let ec2_client = &self.client.ec2_client;
+
+        let response = ec2_client
+            .describe_addresses()
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to describe addresses in region {}: {:?}", region, e);
+                diagnostics::record("ec2", "DescribeAddresses", AwsError::SdkError(e.into()))
+            })?;
+
+        let eips = unassociated_eips(response.addresses(), region);
+
+        tracing::info!("Found {} unassociated Elastic IPs in region {}", eips.len(), region);
+        Ok(eips)
+    }
+
+    /// Release an Elastic IP by allocation id
+    pub async fn release_eip(&self, allocation_id: &str) -> AwsResult<()> {
+        tracing::info!("Releasing Elastic IP: {}", allocation_id);
+
+        let ec2_client = &self.client.ec2_client;
+
+        ec2_client
+            .release_address()
+            .allocation_id(allocation_id)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to release Elastic IP {}: {:?}", allocation_id, e);
+                diagnostics::record("ec2", "ReleaseAddress", AwsError::SdkError(e.into()))
+            })?;
+
+        tracing::info!("Successfully released Elastic IP: {}", allocation_id);
+        Ok(())
+    }
+
+    /// Release every unassociated, unprotected Elastic IP. With `dry_run` set, only reports candidates.
+    pub async fn release_unused_eips(&self, dry_run: bool) -> AwsResult<EipReclaimReport> {
+        let eips = self.list_unassociated_eips().await?;
+        Ok(reclaim_eips(eips, dry_run, |allocation_id| async move {
+            self.release_eip(&allocation_id).await
+        }).await)
+    }
+
+    /// Every Elastic IP in the client's region, associated or not
+    pub async fn collect_elastic_ips(&self) -> AwsResult<Vec<AwsElasticIp>> {
+        let region = self.client.primary_region();
+        tracing::info!("Collecting Elastic IPs in region {}", region);
+
+        let response = with_retry(&self.client.config.retries, "DescribeAddresses", || async move {
+            single_attempt_ec2(&self.client.ec2_client)
+                .describe_addresses()
+                .send()
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to describe addresses in region {}: {:?}", region, e);
+                    diagnostics::record("ec2", "DescribeAddresses", AwsError::SdkError(e.into()))
+                })
+        })
+        .await?;
+
+        Ok(response.addresses().iter().filter_map(|address| map_aws_address(address, region)).collect())
+    }
+
+    /// Collect Elastic IPs from each of the given regions concurrently
+    pub async fn collect_elastic_ips_in_regions(&self, regions: &[String]) -> RegionalCollection<AwsElasticIp> {
+        let client = self.client.clone();
+        collect_across_regions(regions, MAX_CONCURRENT_REGIONS, move |region| {
+            let client = client.clone();
+            async move {
+                let regional_client = if region == client.config.region {
+                    client
+                } else {
+                    client.for_region(&region).await?
+                };
+                Ec2Service::new(regional_client).collect_elastic_ips().await
+            }
+        })
+        .await
+    }
+
+    /// Allocate a new VPC Elastic IP in the client's region
+    pub async fn allocate_elastic_ip(&self) -> AwsResult<AwsElasticIp> {
+        let region = self.client.primary_region();
+        tracing::info!("Allocating Elastic IP in region {}", region);
+
+        let response = self.client.ec2_client
+            .allocate_address()
+            .domain(DomainType::Vpc)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to allocate Elastic IP in region {}: {:?}", region, e);
+                diagnostics::record("ec2", "AllocateAddress", AwsError::SdkError(e.into()))
+            })?;
+
+        let allocation_id = response.allocation_id()
+            .ok_or_else(|| AwsError::OperationError("AllocateAddress returned no allocation id".to_string()))?;
+
+        tracing::info!("Allocated Elastic IP {} ({})", allocation_id, response.public_ip().unwrap_or("unknown"));
+        Ok(AwsElasticIp {
+            allocation_id: allocation_id.to_string(),
+            public_ip: response.public_ip().unwrap_or("unknown").to_string(),
+            region: region.to_string(),
+            domain: response.domain().map(|d| d.as_str().to_string()).unwrap_or_else(|| "vpc".to_string()),
+            association_id: None,
+            instance_id: None,
+            tags: HashMap::new(),
+        })
+    }
+
+    /// Associate an Elastic IP with an instance, returning the association id.
+    /// An address already associated elsewhere is moved.
+    pub async fn associate_elastic_ip(&self, allocation_id: &str, instance_id: &str) -> AwsResult<String> {
+        tracing::info!("Associating Elastic IP {} with instance {}", allocation_id, instance_id);
+
+        let response = self.client.ec2_client
+            .associate_address()
+            .allocation_id(allocation_id)
+            .instance_id(instance_id)
+            .allow_reassociation(true)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to associate Elastic IP {} with {}: {:?}", allocation_id, instance_id, e);
+                diagnostics::record("ec2", "AssociateAddress", AwsError::SdkError(e.into()))
+            })?;
+
+        let association_id = response.association_id()
+            .ok_or_else(|| AwsError::OperationError("AssociateAddress returned no association id".to_string()))?;
+
+        tracing::info!("Associated Elastic IP {} with instance {} ({})", allocation_id, instance_id, association_id);
+        Ok(association_id.to_string())
+    }
+
+    /// Security groups in the client's region, with the config each was built from
+    pub async fn collect_security_groups(&self) -> AwsResult<Vec<AwsSecurityGroupInfo>> {
+        let region = self.client.primary_region();
+        tracing::info!("Collecting security groups in region {}", region);
+
+        let groups: Vec<SecurityGroup> = with_retry(&self.client.config.retries, "DescribeSecurityGroups", || async move {
+            single_attempt_ec2(&self.client.ec2_client)
+                .describe_security_groups()
+                .into_paginator()
+                .items()
+                .send()
+                .collect::<Result<Vec<_>, _>>()
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to describe security groups in region {}: {:?}", region, e);
+                    diagnostics::record("ec2", "DescribeSecurityGroups", AwsError::SdkError(e.into()))
+                })
+        })
+        .await?;
+
+        let groups: Vec<AwsSecurityGroupInfo> = groups.iter().map(|g| map_security_group(g, region)).collect();
+        tracing::info!("Found {} security groups in region {}", groups.len(), region);
+        Ok(groups)
+    }
+
+    /// VPCs in the primary region, from the cache when fresh
+    pub async fn collect_vpcs(&self) -> AwsResult<Vec<AwsVpc>> {
+        let region = self.client.primary_region();
+        if let Some(vpcs) = self.client.cache.get_vpcs(region).await {
+            return Ok(vpcs);
+        }
+
+        let vpcs: Vec<Vpc> = with_retry(&self.client.config.retries, "DescribeVpcs", || async move {
+            single_attempt_ec2(&self.client.ec2_client)
+                .describe_vpcs()
+                .into_paginator()
+                .items()
+                .send()
+                .collect::<Result<Vec<_>, _>>()
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to describe VPCs in region {}: {:?}", region, e);
+                    diagnostics::record("ec2", "DescribeVpcs", AwsError::SdkError(e.into()))
+                })
+        })
+        .await?;
+
+        let vpcs: Vec<AwsVpc> = vpcs.iter().map(|vpc| map_vpc(vpc, region)).collect();
+        self.client.cache.put_vpcs(region.to_string(), vpcs.clone()).await;
+        Ok(vpcs)
+    }
+
+    /// Subnets in the primary region, only those of `vpc_id` when given, from
+    /// the cache when fresh
+    pub async fn collect_subnets(&self, vpc_id: Option<&str>) -> AwsResult<Vec<AwsSubnet>> {
+        let region = self.client.primary_region();
+        let subnets = match self.client.cache.get_subnets(region).await {
+            Some(subnets) => subnets,
+            None => {
+                let subnets: Vec<Subnet> = with_retry(&self.client.config.retries, "DescribeSubnets", || async move {
+                    single_attempt_ec2(&self.client.ec2_client)
+                        .describe_subnets()
+                        .into_paginator()
+                        .items()
+                        .send()
+                        .collect::<Result<Vec<_>, _>>()
+                        .await
+                        .map_err(|e| {
+                            tracing::error!("Failed to describe subnets in region {}: {:?}", region, e);
+                            diagnostics::record("ec2", "DescribeSubnets", AwsError::SdkError(e.into()))
+                        })
+                })
+                .await?;
+
+                let subnets: Vec<AwsSubnet> = subnets.iter().map(|subnet| map_subnet(subnet, region)).collect();
+                self.client.cache.put_subnets(region.to_string(), subnets.clone()).await;
+                subnets
+            }
+        };
+
+        Ok(subnets.into_iter().filter(|subnet| vpc_id.map_or(true, |vpc_id| subnet.vpc_id == vpc_id)).collect())
+    }
+
+    /// One subnet by id, straight from EC2, or None if it doesn't exist in the region
+    pub async fn get_subnet(&self, subnet_id: &str) -> AwsResult<Option<AwsSubnet>> {
+        let result = self.client.ec2_client
+            .describe_subnets()
+            .subnet_ids(subnet_id)
+            .send()
+            .await;
+
+        match result {
+            Ok(response) => Ok(response.subnets().first().map(|subnet| map_subnet(subnet, self.client.primary_region()))),
+            Err(e) if e.code() == Some("InvalidSubnetID.NotFound") => Ok(None),
+            Err(e) => {
+                tracing::error!("Failed to describe subnet {}: {:?}", subnet_id, e);
+                Err(diagnostics::record("ec2", "DescribeSubnets", AwsError::SdkError(e.into())))
+            }
+        }
+    }
+
+    /// VPC a launch with `options` lands in, checking the subnet exists and
+    /// belongs to the requested VPC. None means the default VPC.
+    pub async fn resolve_launch_vpc(&self, options: &LaunchOptions) -> AwsResult<Option<String>> {
+        let subnet = match &options.subnet_id {
+            Some(subnet_id) => Some(
+                self.get_subnet(subnet_id).await?
+                    .ok_or_else(|| AwsError::OperationError(format!("Subnet {} not found", subnet_id)))?
+            ),
+            None => None,
+        };
+        validate_launch_network(options.vpc_id.as_deref(), subnet.as_ref())
+    }
+
+    /// One security group by id, or None if it doesn't exist in the region
+    pub async fn get_security_group(&self, group_id: &str) -> AwsResult<Option<SecurityGroup>> {
+        let result = self.client.ec2_client
+            .describe_security_groups()
+            .group_ids(group_id)
+            .send()
+            .await;
+
+        match result {
+            Ok(response) => Ok(response.security_groups().first().cloned()),
+            Err(e) if e.code() == Some("InvalidGroup.NotFound") => Ok(None),
+            Err(e) => {
+                tracing::error!("Failed to describe security group {}: {:?}", group_id, e);
+                Err(diagnostics::record("ec2", "DescribeSecurityGroups", AwsError::SdkError(e.into())))
+            }
+        }
+    }
+
+    /// Scheduled reboot, maintenance and retirement events for instances in
+    /// the primary region, retirements first
+    pub async fn get_scheduled_events(&self) -> AwsResult<Vec<InstanceScheduledEvent>> {
+        let region = self.client.primary_region();
+        tracing::info!("Collecting scheduled instance events in region {}", region);
+
+        let statuses: Vec<InstanceStatus> = self.client.ec2_client
+            .describe_instance_status()
+            .include_all_instances(true)
+            .into_paginator()
+            .items()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to describe instance status in region {}: {:?}", region, e);
+                diagnostics::record("ec2", "DescribeInstanceStatus", AwsError::SdkError(e.into()))
+            })?;
+
+        let events = scheduled_events(&statuses, region);
+        tracing::info!("Found {} scheduled instance events in region {}", events.len(), region);
+        Ok(events)
+    }
+
+    /// Create or update the security group for a security config's rules and return its
+    /// group ID. The group is found by the config id it's tagged with. Re-applying only
+    /// authorizes rules the group lacks and revokes rules the config no longer has,
+    /// in that order, so a failed call never leaves the group without the old rules
+    /// before the new ones are in place.
+    /// Egress is left alone (AWS default: allow all) when the config has no egress rules.
+    pub async fn apply_security_config(
+        &self,
+        config_id: i64,
+        name: &str,
+        description: Option<&str>,
+        rules: &[SecurityRule],
+        region: Option<&str>,
+        vpc_id: Option<&str>,
+    ) -> AwsResult<String> {
+        let permissions = rules_to_ip_permissions(rules)?;
+
+        let region = region.unwrap_or(self.client.primary_region());
+        tracing::info!("Applying security config {} ({}) in region {}", config_id, name, region);
+
+        let regional_client = if region == self.client.config.region {
+            self.client.clone()
+        } else {
+            self.client.for_region(region).await?
+        };
+        let ec2_client = &regional_client.ec2_client;
+
+        let mut filters = vec![
+            Filter::builder()
+                .name(format!("tag:{}", SECURITY_CONFIG_TAG_KEY))
+                .values(config_id.to_string())
+                .build()
+        ];
+        if let Some(vpc_id) = vpc_id {
+            filters.push(Filter::builder().name("vpc-id").values(vpc_id).build());
+        }
+
+        let existing = ec2_client
+            .describe_security_groups()
+            .set_filters(Some(filters))
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to describe security groups for config {}: {:?}", config_id, e);
+                diagnostics::record("ec2", "DescribeSecurityGroups", AwsError::SdkError(e.into()))
+            })?
+            .security_groups()
+            .first()
+            .cloned();
+
+        let (group_id, current_ingress, current_egress) = match existing {
+            Some(group) => (
+                group.group_id().unwrap_or_default().to_string(),
+                group.ip_permissions().to_vec(),
+                group.ip_permissions_egress().to_vec(),
+            ),
+            None => {
+                let group_name = format!("pocket-architect-{}-{}", config_id, sanitize_group_name(name));
+                let description = description
+                    .filter(|d| !d.is_empty())
+                    .map_or_else(|| format!("Pocket Architect security config {}", name), str::to_string);
+
+                let response = ec2_client
+                    .create_security_group()
+                    .group_name(&group_name)
+                    .description(description)
+                    .set_vpc_id(vpc_id.map(str::to_string))
+                    .tag_specifications(
+                        aws_sdk_ec2::types::TagSpecification::builder()
+                            .resource_type(aws_sdk_ec2::types::ResourceType::SecurityGroup)
+                            .tags(
+                                aws_sdk_ec2::types::Tag::builder()
+                                    .key(SECURITY_CONFIG_TAG_KEY)
+                                    .value(config_id.to_string())
+                                    .build()
+                            )
+                            .tags(
+                                aws_sdk_ec2::types::Tag::builder()
+                                    .key("CreatedBy")
+                                    .value("PocketArchitect")
+                                    .build()
+                            )
+                            .build()
+                    )
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        tracing::error!("Failed to create security group {}: {:?}", group_name, e);
+                        diagnostics::record("ec2", "CreateSecurityGroup", AwsError::SdkError(e.into()))
+                    })?;
+
+                let group_id = response.group_id()
+                    .ok_or_else(|| AwsError::OperationError("No group ID returned from create_security_group".to_string()))?
+                    .to_string();
+                tracing::info!("Created security group {} ({}) for config {}", group_id, group_name, config_id);
+
+                // New groups come with an allow-all egress rule
+                let default_egress = IpPermission::builder()
+                    .ip_protocol("-1")
+                    .ip_ranges(IpRange::builder().cidr_ip("0.0.0.0/0").build())
+                    .build();
+                (group_id, Vec::new(), vec![default_egress])
+            }
+        };
+
+        let ingress = diff_ip_permissions(&current_ingress, &permissions.ingress);
+        if !ingress.to_authorize.is_empty() {
+            ec2_client
+                .authorize_security_group_ingress()
+                .group_id(&group_id)
+                .set_ip_permissions(Some(ingress.to_authorize))
+                .send()
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to authorize ingress rules on {}: {:?}", group_id, e);
+                    diagnostics::record("ec2", "AuthorizeSecurityGroupIngress", AwsError::SdkError(e.into()))
+                })?;
+        }
+        if !ingress.to_revoke.is_empty() {
+            ec2_client
+                .revoke_security_group_ingress()
+                .group_id(&group_id)
+                .set_ip_permissions(Some(ingress.to_revoke))
+                .send()
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to revoke ingress rules on {}: {:?}", group_id, e);
+                    diagnostics::record("ec2", "RevokeSecurityGroupIngress", AwsError::SdkError(e.into()))
+                })?;
+        }
+
+        if !permissions.egress.is_empty() {
+            let egress = diff_ip_permissions(&current_egress, &permissions.egress);
+            if !egress.to_authorize.is_empty() {
+                ec2_client
+                    .authorize_security_group_egress()
+                    .group_id(&group_id)
+                    .set_ip_permissions(Some(egress.to_authorize))
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        tracing::error!("Failed to authorize egress rules on {}: {:?}", group_id, e);
+                        diagnostics::record("ec2", "AuthorizeSecurityGroupEgress", AwsError::SdkError(e.into()))
+                    })?;
+            }
+            if !egress.to_revoke.is_empty() {
+                ec2_client
+                    .revoke_security_group_egress()
+                    .group_id(&group_id)
+                    .set_ip_permissions(Some(egress.to_revoke))
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        tracing::error!("Failed to revoke egress rules on {}: {:?}", group_id, e);
+                        diagnostics::record("ec2", "RevokeSecurityGroupEgress", AwsError::SdkError(e.into()))
+                    })?;
+            }
+        }
+
+        tracing::info!("Applied security config {} to security group {}", config_id, group_id);
+        Ok(group_id)
+    }
+
+    /// Get SSH configuration for an instance. `user_override` takes precedence over
+    /// the login user detected from the instance's AMI, and a recorded `elastic_ip` over
+    /// the instance's own addresses. `key_paths` maps key pair names to the private
+    /// keys users keep outside ~/.ssh.
+    pub async fn get_ssh_config(&self, instance_id: &str, user_override: Option<&str>, elastic_ip: Option<&str>, key_paths: &HashMap<String, String>) -> AwsResult<serde_json::Value> {
+        tracing::debug!("Getting SSH config for EC2 instance: {}", instance_id);
+
+        let instance_details = self.get_instance_details(instance_id).await?;
+
+        match instance_details {
+            Some(instance) => {
+                let key_path = match instance.key_pairs.first() {
+                    Some(key_name) => ssh_key_path(&instance.instance_id, key_name, key_paths)?,
+                    None => local_ssh_key_path("default", key_paths),
+                };
+
+                let user = match user_override.filter(|u| !u.is_empty()) {
+                    Some(user) => user.to_string(),
+                    None => self.detect_ssh_user(instance_id).await?,
+                };
+
+                let (host, address_family) = ssh_host(
+                    elastic_ip,
+                    instance.public_ip.as_deref(),
+                    instance.ipv6_address.as_deref(),
+                    instance.private_ip.as_deref(),
+                );
+
+                let ssh_config = serde_json::json!({
+                    "host": host,
+                    "addressFamily": address_family,
+                    "elasticIp": elastic_ip,
+                    "user": user,
+                    "userOverridden": user_override.is_some_and(|u| !u.is_empty()),
+                    "keyPath": key_path,
+                    "port": 22,
+                    "instanceId": instance.instance_id,
+                    "region": instance.region,
+                    "availabilityZone": instance.availability_zone
+                });
+
+                Ok(ssh_config)
+            }
+            None => Err(AwsError::OperationError(format!("Instance {} not found", instance_id)))
+        }
+    }
+
+    /// Detect the default login user from the instance's platform details and AMI
+    async fn detect_ssh_user(&self, instance_id: &str) -> AwsResult<String> {
+        let ec2_client = &self.client.ec2_client;
+
+        let response = ec2_client
+            .describe_instances()
+            .instance_ids(instance_id)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to describe instance {}: {:?}", instance_id, e);
+                diagnostics::record("ec2", "DescribeInstances", AwsError::SdkError(e.into()))
+            })?;
+
+        let instance = response.reservations()
+            .iter()
+            .flat_map(|r| r.instances())
+            .find(|i| i.instance_id() == Some(instance_id));
+
+        let platform_details = instance.and_then(|i| i.platform_details()).unwrap_or("");
+        let Some(image_id) = instance.and_then(|i| i.image_id()) else {
+            return Ok(default_ssh_user(platform_details, "", "").to_string());
+        };
+
+        // The AMI may have been deregistered or be private to another account
+        let image = match ec2_client.describe_images().image_ids(image_id).send().await {
+            Ok(images) => images.images().first().cloned(),
+            Err(e) => {
+                tracing::warn!("Failed to describe image {} for SSH user detection: {:?}", image_id, e);
+                None
+            }
+        };
+
+        let image_name = image.as_ref().and_then(|i| i.name()).unwrap_or("");
+        let image_description = image.as_ref().and_then(|i| i.description()).unwrap_or("");
+
+        let user = default_ssh_user(platform_details, image_name, image_description);
+        tracing::debug!("Detected SSH user {} for instance {} (image {})", user, instance_id, image_id);
+        Ok(user.to_string())
+    }
+}
+
+/// Check launch options without knowing the AMI, so bad input fails before
+/// anything is sent to AWS
+pub fn validate_launch_options(options: &LaunchOptions) -> AwsResult<()> {
+    if let Some(user_data) = &options.user_data {
+        encode_user_data(user_data)?;
+    }
+    if let Some(root_volume) = &options.root_volume {
+        validate_root_volume(root_volume, None)?;
+    }
+    if let Some(max_price) = &options.spot_max_price {
+        if options.purchase_option != PurchaseOption::Spot {
+            return Err(AwsError::ConfigError("A spot max price only applies to spot launches".to_string()));
+        }
+        if !max_price.parse::<f64>().is_ok_and(|price| price > 0.0) {
+            return Err(AwsError::ConfigError(format!("Invalid spot max price '{}'; expected a positive hourly price in USD", max_price)));
+        }
+    }
+    Ok(())
+}
+
+/// Market options asking RunInstances for a one-time spot instance, which
+/// AWS terminates on interruption. None for on-demand launches.
+pub fn market_options(options: &LaunchOptions) -> Option<InstanceMarketOptionsRequest> {
+    if options.purchase_option != PurchaseOption::Spot {
+        return None;
+    }
+
+    let spot_options = SpotMarketOptions::builder()
+        .spot_instance_type(SpotInstanceType::OneTime)
+        .instance_interruption_behavior(InstanceInterruptionBehavior::Terminate)
+        .set_max_price(options.spot_max_price.clone())
+        .build();
+    Some(
+        InstanceMarketOptionsRequest::builder()
+            .market_type(MarketType::Spot)
+            .spot_options(spot_options)
+            .build(),
+    )
+}
+
+fn map_spot_price(price: &SpotPrice) -> Option<AwsSpotPrice> {
+    Some(AwsSpotPrice {
+        instance_type: price.instance_type()?.as_str().to_string(),
+        availability_zone: price.availability_zone()?.to_string(),
+        price: price.spot_price()?.parse().ok()?,
+        timestamp: price.timestamp().and_then(|t| chrono::DateTime::<Utc>::from_timestamp(t.secs(), 0)).map(|t| t.to_rfc3339()),
+    })
+}
+
+/// Previous and current state of one instance in a start, stop or terminate
+/// response
+pub fn map_state_change(change: &InstanceStateChange) -> Option<InstanceStateTransition> {
+    let state_name = |state: Option<&InstanceState>| {
+        state.and_then(|state| state.name()).map_or("unknown", |name| name.as_str()).to_string()
+    };
+
+    Some(InstanceStateTransition {
+        instance_id: change.instance_id()?.to_string(),
+        previous_state: state_name(change.previous_state()),
+        current_state: state_name(change.current_state()),
+    })
+}
+
+fn map_reserved_instance(reservation: &ReservedInstances, region: &str) -> Option<AwsReservedInstance> {
+    Some(AwsReservedInstance {
+        reserved_instances_id: reservation.reserved_instances_id()?.to_string(),
+        instance_type: reservation.instance_type()?.as_str().to_string(),
+        region: region.to_string(),
+        availability_zone: match reservation.scope() {
+            Some(Scope::Region) => None,
+            _ => reservation.availability_zone().map(str::to_string),
+        },
+        instance_count: reservation.instance_count().unwrap_or(0),
+        product_description: reservation.product_description().map(|d| d.as_str().to_string()).unwrap_or_default(),
+        offering_type: reservation.offering_type().map(|o| o.as_str().to_string()).unwrap_or_default(),
+        end: reservation.end().and_then(|t| chrono::DateTime::<Utc>::from_timestamp(t.secs(), 0)).map(|t| t.to_rfc3339()),
+    })
+}
+
+/// The newest price in each availability zone of a newest-first history,
+/// cheapest zone first
+pub fn current_spot_prices(history: &[AwsSpotPrice]) -> Vec<AwsSpotPrice> {
+    let mut current: Vec<AwsSpotPrice> = Vec::new();
+    for price in history {
+        if !current.iter().any(|p| p.availability_zone == price.availability_zone) {
+            current.push(price.clone());
+        }
+    }
+    current.sort_by(|a, b| a.price.total_cmp(&b.price));
+    current
+}
+
+/// "spot" for spot instances, otherwise "on-demand"
+pub fn instance_lifecycle(instance: &AwsSdkInstance) -> &'static str {
+    match instance.instance_lifecycle() {
+        Some(InstanceLifecycleType::Spot) => PurchaseOption::Spot.lifecycle(),
+        _ => PurchaseOption::OnDemand.lifecycle(),
+    }
+}
+
+/// Check a root volume against the limits of its volume type and the size of
+/// the AMI's root snapshot, if known
+pub fn validate_root_volume(spec: &RootVolumeSpec, snapshot_size_gb: Option<i32>) -> AwsResult<()> {
+    // (minimum size, maximum size, IOPS range, maximum IOPS per GiB)
+    let (min_size, max_size, iops_range, iops_per_gb) = match spec.volume_type.as_str() {
+        "gp2" => (1, 16_384, None, 0),
+        "gp3" => (1, 16_384, Some(3_000..=16_000), 500),
+        "io2" => (4, 16_384, Some(100..=256_000), 1_000),
+        other => {
+            return Err(AwsError::ConfigError(format!(
+                "Unsupported root volume type '{}'; expected gp3, gp2 or io2",
+                other
+            )))
+        }
+    };
+
+    if !(min_size..=max_size).contains(&spec.size_gb) {
+        return Err(AwsError::ConfigError(format!(
+            "{} root volumes must be between {} and {} GB, got {}",
+            spec.volume_type, min_size, max_size, spec.size_gb
+        )));
+    }
+    if let Some(snapshot_size) = snapshot_size_gb.filter(|size| spec.size_gb < *size) {
+        return Err(AwsError::ConfigError(format!(
+            "Root volume of {} GB is smaller than the image's {} GB snapshot",
+            spec.size_gb, snapshot_size
+        )));
+    }
+
+    match (spec.iops, iops_range) {
+        (None, _) => Ok(()),
+        (Some(_), None) => Err(AwsError::ConfigError(format!("{} volumes don't take provisioned IOPS", spec.volume_type))),
+        (Some(iops), Some(range)) => {
+            if !range.contains(&iops) {
+                return Err(AwsError::ConfigError(format!(
+                    "{} IOPS must be between {} and {}, got {}",
+                    spec.volume_type, range.start(), range.end(), iops
+                )));
+            }
+            if iops > spec.size_gb.saturating_mul(iops_per_gb) {
+                return Err(AwsError::ConfigError(format!(
+                    "{} allows at most {} IOPS per GB; {} GB supports up to {} IOPS",
+                    spec.volume_type, iops_per_gb, spec.size_gb, spec.size_gb.saturating_mul(iops_per_gb)
+                )));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Block device mapping that replaces the AMI's root volume. io2 volumes
+/// need provisioned IOPS, so they get 3000 (or as many as their size
+/// allows) when none are given.
+pub fn root_block_device_mapping(device_name: &str, spec: &RootVolumeSpec) -> BlockDeviceMapping {
+    let iops = match (spec.iops, spec.volume_type.as_str()) {
+        (Some(iops), _) => Some(iops),
+        (None, "io2") => Some(3_000.min(spec.size_gb.saturating_mul(1_000)).max(100)),
+        (None, _) => None,
+    };
+
+    BlockDeviceMapping::builder()
+        .device_name(device_name)
+        .ebs(
+            EbsBlockDevice::builder()
+                .volume_size(spec.size_gb)
+                .volume_type(VolumeType::from(spec.volume_type.as_str()))
+                .encrypted(spec.encrypted)
+                .delete_on_termination(true)
+                .set_iops(iops)
+                .build(),
+        )
+        .build()
+}
+
+/// Upcoming events from DescribeInstanceStatus, retirements first and then by
+/// window start. Events AWS marks "[Completed]" or "[Canceled]" are skipped.
+pub fn scheduled_events(statuses: &[InstanceStatus], region: &str) -> Vec<InstanceScheduledEvent> {
+    let mut events: Vec<InstanceScheduledEvent> = statuses
+        .iter()
+        .flat_map(|status| {
+            let instance_id = status.instance_id().unwrap_or("unknown");
+            status.events().iter().map(move |event| (instance_id, event))
+        })
+        .filter(|(_, event)| {
+            let description = event.description().unwrap_or_default();
+            !description.starts_with("[Completed]") && !description.starts_with("[Canceled]")
+        })
+        .map(|(instance_id, event)| InstanceScheduledEvent {
+            instance_id: instance_id.to_string(),
+            region: region.to_string(),
+            event_id: event.instance_event_id().map(|id| id.to_string()),
+            code: event.code().map(|c| c.as_str().to_string()).unwrap_or_else(|| "unknown".to_string()),
+            description: event.description().unwrap_or_default().to_string(),
+            not_before: event.not_before().map(|d| d.to_string()),
+            not_after: event.not_after().map(|d| d.to_string()),
+            not_before_deadline: event.not_before_deadline().map(|d| d.to_string()),
+            // EBS-backed instances on retiring hardware get an instance-stop event
+            is_retirement: matches!(event.code(), Some(EventCode::InstanceRetirement | EventCode::InstanceStop)),
+        })
+        .collect();
+
+    events.sort_by(|a, b| b.is_retirement.cmp(&a.is_retirement).then_with(|| a.not_before.cmp(&b.not_before)));
+    events
+}
+
+/// Base64-encode a user data script for RunInstances, rejecting scripts over
+/// the 16 KB limit
+pub fn encode_user_data(user_data: &str) -> AwsResult<String> {
+    if user_data.len() > MAX_USER_DATA_BYTES {
+        return Err(AwsError::ConfigError(format!(
+            "User data is {} bytes; EC2 allows at most {} bytes (16 KB)",
+            user_data.len(),
+            MAX_USER_DATA_BYTES
+        )));
+    }
+    Ok(base64::engine::general_purpose::STANDARD.encode(user_data))
+}
+
+/// DescribeInstances filters for the requested states, tags and instance
+/// types. AWS ANDs separate filters, so each tag becomes its own `tag:Key`
+/// filter; they're emitted in key order to keep requests stable.
+pub fn describe_filters(filters: &InstanceFilters) -> Vec<Filter> {
+    let mut describe = Vec::new();
+
+    if !filters.states.is_empty() {
+        describe.push(Filter::builder().name("instance-state-name").set_values(Some(filters.states.clone())).build());
+    }
+    if let Some(tag) = &filters.tag {
+        describe.push(match &tag.value {
+            Some(value) => Filter::builder().name(format!("tag:{}", tag.key)).values(value).build(),
+            None => Filter::builder().name("tag-key").values(&tag.key).build(),
+        });
+    }
+    let mut tags: Vec<(&String, &String)> = filters.tags.iter().collect();
+    tags.sort();
+    for (key, value) in tags {
+        describe.push(Filter::builder().name(format!("tag:{}", key)).values(value).build());
+    }
+    if !filters.instance_types.is_empty() {
+        describe.push(Filter::builder().name("instance-type").set_values(Some(filters.instance_types.clone())).build());
+    }
+
+    describe
+}
+
+/// Every instance DescribeInstances returns, following `NextToken` through the
+/// SDK paginator. Also returns the number of pages read.
+pub async fn describe_all_instances(
+    ec2_client: &aws_sdk_ec2::Client,
+    region: &str,
+    filters: Vec<Filter>,
+) -> Result<(Vec<AwsSdkInstance>, usize), SdkError<DescribeInstancesError>> {
+    let filters = if filters.is_empty() { None } else { Some(filters) };
+    let mut pages = ec2_client.describe_instances().set_filters(filters).into_paginator().send();
+    let mut instances = Vec::new();
+    let mut page_count = 0;
+
+    while let Some(page) = pages.next().await {
+        let page = page?;
+        page_count += 1;
+        instances.extend(page.reservations().iter().flat_map(|r| r.instances()).cloned());
+        tracing::debug!("DescribeInstances page {} in {}: {} instances so far", page_count, region, instances.len());
+    }
+
+    Ok((instances, page_count))
+}
+
+/// Default login user for an AMI, based on its platform details, name and description.
+/// Falls back to `ec2-user` when the distribution is not recognised.
+pub fn default_ssh_user(platform_details: &str, image_name: &str, image_description: &str) -> &'static str {
+    if platform_details.to_lowercase().contains("windows") {
+        return "Administrator";
+    }
+
+    let text = format!("{} {} {}", platform_details, image_name, image_description).to_lowercase();
+
+    // Checked in order so e.g. "Bitnami ... on Debian" resolves to bitnami
+    const USERS: &[(&str, &str)] = &[
+        ("bitnami", "bitnami"),
+        ("ubuntu", "ubuntu"),
+        ("debian", "admin"),
+        ("centos", "centos"),
+        ("fedora", "fedora"),
+        ("rocky", "rocky"),
+        ("almalinux", "ec2-user"),
+        ("red hat", "ec2-user"),
+        ("rhel", "ec2-user"),
+        ("suse", "ec2-user"),
+        ("amzn", "ec2-user"),
+        ("amazon linux", "ec2-user"),
+    ];
+
+    USERS
+        .iter()
+        .find(|(pattern, _)| text.contains(pattern))
+        .map(|(_, user)| *user)
+        .unwrap_or("ec2-user")
+}
+
+/// EBS volumes attached to `instances`, keyed by volume id, from a single
+/// DescribeVolumes call. Storage is reported as zero if the call fails.
+async fn attached_volumes(ec2_client: &aws_sdk_ec2::Client, retries: &Retries, instances: &[AwsSdkInstance]) -> HashMap<String, AwsVolume> {
+    let volume_ids: Vec<String> = instances
+        .iter()
+        .flat_map(|i| i.block_device_mappings())
+        .filter_map(|mapping| mapping.ebs().and_then(|ebs| ebs.volume_id()))
+        .map(str::to_string)
+        .collect();
+    if volume_ids.is_empty() {
+        return HashMap::new();
+    }
+
+    let result = with_retry(retries, "DescribeVolumes", || {
+        let volume_ids = volume_ids.clone();
+        async move {
+            single_attempt_ec2(ec2_client)
+                .describe_volumes()
+                .set_volume_ids(Some(volume_ids))
+                .send()
+                .await
+                .map_err(|e| AwsError::SdkError(e.into()))
+        }
+    })
+    .await;
+
+    match result {
+        Ok(response) => response.volumes().iter().filter_map(map_aws_volume).map(|v| (v.volume_id.clone(), v)).collect(),
+        Err(e) => {
+            tracing::warn!("Failed to describe attached volumes, storage will show as zero: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// Describe every security group referenced by `instances` in one call, keyed
+/// by group id
+async fn referenced_security_groups(ec2_client: &aws_sdk_ec2::Client, retries: &Retries, instances: &[AwsSdkInstance], region: &str) -> HashMap<String, AwsSecurityGroupInfo> {
+    let mut group_ids: Vec<String> = instances
+        .iter()
+        .flat_map(|i| i.security_groups())
+        .filter_map(|group| group.group_id())
+        .map(str::to_string)
+        .collect();
+    group_ids.sort();
+    group_ids.dedup();
+    if group_ids.is_empty() {
+        return HashMap::new();
+    }
+
+    let result = with_retry(retries, "DescribeSecurityGroups", || {
+        let group_ids = group_ids.clone();
+        async move {
+            single_attempt_ec2(ec2_client)
+                .describe_security_groups()
+                .set_group_ids(Some(group_ids))
+                .into_paginator()
+                .items()
+                .send()
+                .collect::<Result<Vec<_>, _>>()
+                .await
+                .map_err(|e| AwsError::SdkError(e.into()))
+        }
+    })
+    .await;
+
+    match result {
+        Ok(groups) => groups.iter()
+            .map(|group| map_security_group(group, region))
+            .map(|group| (group.group_id.clone(), group))
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Failed to describe instance security groups, descriptions will be missing: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// An instance's security groups, with the description and rule counts of
+/// those that were described
+pub fn instance_security_groups(instance: &AwsSdkInstance, groups: &HashMap<String, AwsSecurityGroupInfo>) -> Vec<AwsSecurityGroup> {
+    instance.security_groups()
+        .iter()
+        .map(|sg| {
+            let group_id = sg.group_id().unwrap_or("unknown");
+            let described = groups.get(group_id);
+            AwsSecurityGroup {
+                group_id: group_id.to_string(),
+                group_name: sg.group_name().unwrap_or("unknown").to_string(),
+                description: described.and_then(|g| g.description.clone()),
+                ingress_rules: described.map(|g| g.ingress_rules),
+                egress_rules: described.map(|g| g.egress_rules),
+            }
+        })
+        .collect()
+}
+
+/// An instance's network interfaces in device index order, each with its
+/// primary private and IPv6 addresses first
+pub fn instance_network_interfaces(instance: &AwsSdkInstance) -> Vec<AwsNetworkInterface> {
+    let mut interfaces: Vec<_> = instance.network_interfaces().iter().collect();
+    interfaces.sort_by_key(|interface| interface.attachment().and_then(|a| a.device_index()).unwrap_or(i32::MAX));
+
+    interfaces
+        .into_iter()
+        .filter_map(|interface| {
+            let mut private_ips: Vec<_> = interface.private_ip_addresses().iter().collect();
+            private_ips.sort_by_key(|address| !address.primary().unwrap_or(false));
+            let mut private_ips: Vec<String> = private_ips.iter().filter_map(|a| a.private_ip_address()).map(str::to_string).collect();
+            if private_ips.is_empty() {
+                private_ips.extend(interface.private_ip_address().map(str::to_string));
+            }
+
+            let mut ipv6_addresses: Vec<_> = interface.ipv6_addresses().iter().collect();
+            ipv6_addresses.sort_by_key(|address| !address.is_primary_ipv6().unwrap_or(false));
+
+            Some(AwsNetworkInterface {
+                eni_id: interface.network_interface_id()?.to_string(),
+                private_ips,
+                ipv6_addresses: ipv6_addresses.iter().filter_map(|a| a.ipv6_address()).map(str::to_string).collect(),
+                subnet_id: interface.subnet_id().map(str::to_string),
+                security_groups: interface.groups().iter().filter_map(|g| g.group_id()).map(str::to_string).collect(),
+            })
+        })
+        .collect()
+}
+
+/// The instance's primary IPv6 address, or else the first one on its primary
+/// network interface
+pub fn primary_ipv6_address(instance: &AwsSdkInstance, interfaces: &[AwsNetworkInterface]) -> Option<String> {
+    instance
+        .ipv6_address()
+        .map(str::to_string)
+        .or_else(|| interfaces.first().and_then(|interface| interface.ipv6_addresses.first().cloned()))
+}
+
+/// Map an SDK volume to our custom AwsVolume type
+pub fn map_aws_volume(volume: &Volume) -> Option<AwsVolume> {
+    Some(AwsVolume {
+        volume_id: volume.volume_id()?.to_string(),
+        size_gb: volume.size().unwrap_or(0),
+        volume_type: volume.volume_type().map(|t| t.as_str().to_string()).unwrap_or_else(|| "unknown".to_string()),
+        encrypted: volume.encrypted().unwrap_or(false),
+    })
+}
+
+/// The described volumes among an instance's block device mappings
+pub fn instance_volumes(instance: &AwsSdkInstance, volumes: &HashMap<String, AwsVolume>) -> Vec<AwsVolume> {
+    instance.block_device_mappings()
+        .iter()
+        .filter_map(|mapping| mapping.ebs().and_then(|ebs| ebs.volume_id()))
+        .filter_map(|volume_id| volumes.get(volume_id).cloned())
+        .collect()
+}
+
+/// Sum attached volume sizes (GiB) per instance. A volume attached to several
+/// instances (multi-attach) counts towards each of them.
+pub fn volume_totals_by_instance(volumes: &[Volume]) -> HashMap<String, i64> {
+    let mut totals = HashMap::new();
+
+    for volume in volumes {
+        let size = volume.size().unwrap_or(0) as i64;
+        for attachment in volume.attachments() {
+            if let Some(instance_id) = attachment.instance_id() {
+                *totals.entry(instance_id.to_string()).or_insert(0) += size;
+            }
+        }
+    }
+
+    totals
+}
+
+/// Map SDK addresses to `AwsElasticIp`, keeping only unassociated and unprotected ones
+pub fn unassociated_eips(addresses: &[Address], region: &str) -> Vec<AwsElasticIp> {
+    addresses
+        .iter()
+        .filter(|address| address.association_id().is_none() && address.instance_id().is_none())
+        .filter(|address| address.network_interface_id().is_none())
+        .filter_map(|address| map_aws_address(address, region))
+        .filter(|eip| !is_protected_eip(eip))
+        .collect()
+}
+
+/// Allocation id and public IP of each associated Elastic IP, keyed by the
+/// instance it's attached to
+pub fn elastic_ips_by_instance(eips: &[AwsElasticIp]) -> HashMap<String, (String, String)> {
+    eips.iter()
+        .filter_map(|eip| {
+            let instance_id = eip.instance_id.as_ref()?;
+            Some((instance_id.clone(), (eip.allocation_id.clone(), eip.public_ip.clone())))
+        })
+        .collect()
+}
+
+/// Host to SSH to and its address family ("ipv4" or "ipv6"): the Elastic IP
+/// when one is associated, otherwise the public IPv4 address, then the IPv6
+/// address, then the private address. The family is None when the instance
+/// has no address at all.
+pub fn ssh_host(elastic_ip: Option<&str>, public_ip: Option<&str>, ipv6_address: Option<&str>, private_ip: Option<&str>) -> (String, Option<&'static str>) {
+    match (elastic_ip.or(public_ip), ipv6_address, private_ip) {
+        (Some(public), _, _) => (public.to_string(), Some("ipv4")),
+        (None, Some(ipv6), _) => (ipv6.to_string(), Some("ipv6")),
+        (None, None, Some(private)) => (private.to_string(), Some("ipv4")),
+        (None, None, None) => ("unknown".to_string(), None),
+    }
+}
+
+/// Map an SDK address to our custom AwsElasticIp type
+fn map_aws_address(address: &Address, region: &str) -> Option<AwsElasticIp> {
+    let allocation_id = address.allocation_id()?.to_string();
+
+    let tags = address.tags()
+        .iter()
+        .map(|tag| (
+            tag.key().unwrap_or("unknown").to_string(),
+            tag.value().unwrap_or("").to_string(),
+        ))
+        .collect::<HashMap<String, String>>();
+
+    Some(AwsElasticIp {
+        allocation_id,
+        public_ip: address.public_ip().unwrap_or("unknown").to_string(),
+        region: region.to_string(),
+        domain: address.domain().map(|d| d.as_str().to_string()).unwrap_or_else(|| "vpc".to_string()),
+        association_id: address.association_id().map(|s| s.to_string()),
+        instance_id: address.instance_id().map(|s| s.to_string()),
+        tags,
+    })
+}
+
+/// An EIP is protected when it carries the protective tag with any value other than "false"
+pub fn is_protected_eip(eip: &AwsElasticIp) -> bool {
+    eip.tags
+        .get(EIP_PROTECTED_TAG_KEY)
+        .map(|value| !value.eq_ignore_ascii_case("false"))
+        .unwrap_or(false)
+}
+
+/// Release the given EIPs through `release`, or only report them when `dry_run` is set
+pub async fn reclaim_eips<F, Fut>(eips: Vec<AwsElasticIp>, dry_run: bool, mut release: F) -> EipReclaimReport
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = AwsResult<()>>,
+{
+    let mut released = Vec::new();
+    let mut failed = Vec::new();
+
+    if !dry_run {
+        for eip in &eips {
+            match release(eip.allocation_id.clone()).await {
+                Ok(()) => released.push(eip.allocation_id.clone()),
+                Err(e) => failed.push(EipReleaseFailure {
+                    allocation_id: eip.allocation_id.clone(),
+                    error: e.to_string(),
+                }),
+            }
+        }
+    }
+
+    let reclaimable = if dry_run { eips.len() } else { released.len() };
+
+    EipReclaimReport {
+        dry_run,
+        candidates: eips,
+        released,
+        failed,
+        estimated_monthly_savings_usd: reclaimable as f64 * UNASSOCIATED_EIP_MONTHLY_COST_USD,
+    }
+}
+
+/// One AWS call issued while resizing an instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeStep {
+    Stop,
+    WaitStopped,
+    Modify,
+    Start,
+}
+
+/// Calls needed to change the type of an instance currently in `state`. The
+/// type can only be modified while stopped; `restart` starts the instance again
+/// if it was running before the resize.
+pub fn resize_plan(state: &InstanceStateName, restart: bool) -> AwsResult<Vec<ResizeStep>> {
+    let was_running = match state {
+        InstanceStateName::Running => true,
+        InstanceStateName::Stopping | InstanceStateName::Stopped => false,
+        InstanceStateName::Terminated | InstanceStateName::ShuttingDown => {
+            return Err(AwsError::OperationError("Cannot resize a terminated instance".to_string()));
+        }
+        other => {
+            return Err(AwsError::OperationError(format!(
+                "Cannot resize an instance in state '{}'; wait until it is running or stopped",
+                other.as_str()
+            )));
+        }
+    };
+
+    let mut steps = Vec::new();
+    if was_running {
+        steps.push(ResizeStep::Stop);
+    }
+    if *state != InstanceStateName::Stopped {
+        steps.push(ResizeStep::WaitStopped);
+    }
+    steps.push(ResizeStep::Modify);
+    if was_running && restart {
+        steps.push(ResizeStep::Start);
+    }
+    Ok(steps)
+}
+
+/// Whether `offerings` include `instance_type`
+pub fn instance_type_offered(offerings: &[InstanceTypeOffering], instance_type: &str) -> bool {
+    offerings.iter().any(|offering| offering.instance_type().map(|t| t.as_str()) == Some(instance_type))
+}
+
+/// Issue the steps of a resize plan in order through `execute`, stopping at the
+/// first failure
+pub async fn run_resize<F, Fut>(plan: &[ResizeStep], mut execute: F) -> AwsResult<()>
+where
+    F: FnMut(ResizeStep) -> Fut,
+    Fut: Future<Output = AwsResult<()>>,
+{
+    for step in plan {
+        execute(*step).await?;
+    }
+    Ok(())
+}
+
+/// What one step of a resource purge does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurgeAction {
+    SnapshotVolume,
+    Terminate,
+    WaitTerminated,
+    ReleaseEip,
+    DeleteVolume,
+}
+
+/// One AWS call issued while purging a project's resources
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PurgeStep {
+    pub action: PurgeAction,
+    pub region: String,
+    /// Instance, allocation or volume id the step acts on
+    pub resource_id: String,
+    /// Instance the resource belongs to
+    pub instance_id: String,
+}
+
+/// An instance to purge and the resources that go with it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PurgeTarget {
+    pub region: String,
+    pub instance_id: String,
+    pub eip_allocation_id: Option<String>,
+    /// Attached volumes; only deleted when the purge includes volumes
+    pub volume_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PurgeReport {
+    pub completed: Vec<PurgeStep>,
+    /// The step that stopped the purge, with its error
+    pub failed: Option<(PurgeStep, String)>,
+}
+
+/// What became of one step of a plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurgeStepStatus {
+    Completed,
+    Failed,
+    /// Not attempted because an earlier step failed
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PurgeStepResult {
+    #[serde(flatten)]
+    pub step: PurgeStep,
+    pub status: PurgeStepStatus,
+    pub error: Option<String>,
+}
+
+impl PurgeReport {
+    pub fn succeeded(&self) -> bool {
+        self.failed.is_none()
+    }
+
+    /// Every step of `plan` with what became of it
+    pub fn step_results(&self, plan: &[PurgeStep]) -> Vec<PurgeStepResult> {
+        plan.iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let (status, error) = if index < self.completed.len() {
+                    (PurgeStepStatus::Completed, None)
+                } else if index == self.completed.len() && self.failed.is_some() {
+                    (PurgeStepStatus::Failed, self.failed.as_ref().map(|(_, error)| error.clone()))
+                } else {
+                    (PurgeStepStatus::Skipped, None)
+                };
+                PurgeStepResult { step: step.clone(), status, error }
+            })
+            .collect()
+    }
+
+    /// Resource ids of the completed steps of one kind
+    pub fn completed_ids(&self, action: PurgeAction) -> Vec<String> {
+        self.completed.iter()
+            .filter(|step| step.action == action)
+            .map(|step| step.resource_id.clone())
+            .collect()
+    }
+}
+
+/// Calls needed to destroy `targets`: every instance is terminated first, since
+/// addresses and volumes can only be released once their instance is gone
+pub fn purge_plan(targets: &[PurgeTarget], delete_volumes: bool) -> Vec<PurgeStep> {
+    let mut steps: Vec<PurgeStep> = targets.iter()
+        .map(|t| purge_step(PurgeAction::Terminate, t, &t.instance_id))
+        .collect();
+    steps.extend(targets.iter().map(|t| purge_step(PurgeAction::WaitTerminated, t, &t.instance_id)));
+    steps.extend(targets.iter().filter_map(|t| {
+        t.eip_allocation_id.as_deref().map(|allocation_id| purge_step(PurgeAction::ReleaseEip, t, allocation_id))
+    }));
+    if delete_volumes {
+        steps.extend(targets.iter().flat_map(|t| {
+            t.volume_ids.iter().map(|volume_id| purge_step(PurgeAction::DeleteVolume, t, volume_id))
+        }));
+    }
+    steps
+}
+
+fn purge_step(action: PurgeAction, target: &PurgeTarget, resource_id: &str) -> PurgeStep {
+    PurgeStep {
+        action,
+        region: target.region.clone(),
+        resource_id: resource_id.to_string(),
+        instance_id: target.instance_id.clone(),
+    }
+}
+
+/// Safety steps around terminating a single instance
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeleteInstanceOptions {
+    /// Snapshot every attached volume before terminating
+    #[serde(default)]
+    pub final_snapshot: bool,
+    /// Release the instance's Elastic IP once it has terminated
+    #[serde(default)]
+    pub release_eip: bool,
+}
+
+/// Calls needed to delete one instance: final snapshots first, so nothing is
+/// terminated unless they were all started, then the termination, then the
+/// Elastic IP, which can only be released once the instance is gone
+pub fn delete_instance_plan(target: &PurgeTarget, options: &DeleteInstanceOptions) -> Vec<PurgeStep> {
+    let mut steps = Vec::new();
+    if options.final_snapshot {
+        steps.extend(target.volume_ids.iter().map(|volume_id| purge_step(PurgeAction::SnapshotVolume, target, volume_id)));
+    }
+    steps.push(purge_step(PurgeAction::Terminate, target, &target.instance_id));
+    if let (true, Some(allocation_id)) = (options.release_eip, target.eip_allocation_id.as_deref()) {
+        steps.push(purge_step(PurgeAction::WaitTerminated, target, &target.instance_id));
+        steps.push(purge_step(PurgeAction::ReleaseEip, target, allocation_id));
+    }
+    steps
+}
+
+/// Issue the steps of a purge plan in order through `execute`, stopping at the
+/// first failure so nothing is released while its instance may still be running
+pub async fn run_purge<F, Fut>(plan: &[PurgeStep], mut execute: F) -> PurgeReport
+where
+    F: FnMut(PurgeStep) -> Fut,
+    Fut: Future<Output = AwsResult<()>>,
+{
+    let mut report = PurgeReport::default();
+    for step in plan {
+        match execute(step.clone()).await {
+            Ok(()) => report.completed.push(step.clone()),
+            Err(e) => {
+                tracing::error!("Purge stopped at {:?} of {}: {}", step.action, step.resource_id, e);
+                report.failed = Some((step.clone(), e.to_string()));
+                break;
+            }
+        }
+    }
+    report
+}
+
+/// Whether EC2 reported the resource as already gone, such as
+/// InvalidVolume.NotFound or InvalidAllocationID.NotFound
+pub fn is_not_found_error(error: &AwsError) -> bool {
+    match error {
+        AwsError::SdkError(e) => e.code().is_some_and(|code| code.ends_with(".NotFound")),
+        _ => false,
+    }
+}
+
+/// Snapshot ids of an image's EBS block devices, in device order
+pub fn image_snapshot_ids(image: &aws_sdk_ec2::types::Image) -> Vec<String> {
+    image.block_device_mappings()
+        .iter()
+        .filter_map(|mapping| mapping.ebs()?.snapshot_id())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Image id of a CreateImage response
+pub fn ami_id_from_response(response: &CreateImageOutput) -> AwsResult<String> {
+    match response.image_id() {
+        Some(image_id) if image_id.starts_with("ami-") => Ok(image_id.to_string()),
+        Some(other) => Err(AwsError::OperationError(format!("Unexpected image id in CreateImage response: {}", other))),
+        None => Err(AwsError::OperationError("CreateImage response did not include an image id".to_string())),
+    }
+}
+
+/// Memory in GB for the instance types Pocket Architect knows about
+fn known_instance_type_memory_gb(instance_type: &InstanceType) -> Option<f64> {
+    match instance_type {
+        InstanceType::T2Micro => Some(1.0),
+        InstanceType::T2Small => Some(2.0),
+        InstanceType::T2Medium => Some(4.0),
+        InstanceType::T2Large => Some(8.0),
+        InstanceType::T2Xlarge => Some(16.0),
+        InstanceType::T22xlarge => Some(32.0),
+        InstanceType::T3Micro => Some(1.0),
+        InstanceType::T3Small => Some(2.0),
+        InstanceType::T3Medium => Some(4.0),
+        InstanceType::T3Large => Some(8.0),
+        InstanceType::T3Xlarge => Some(16.0),
+        InstanceType::T32xlarge => Some(32.0),
+        InstanceType::M5Large => Some(8.0),
+        InstanceType::M5Xlarge => Some(16.0),
+        InstanceType::M52xlarge => Some(32.0),
+        InstanceType::M54xlarge => Some(64.0),
+        InstanceType::M58xlarge => Some(128.0),
+        InstanceType::M512xlarge => Some(192.0),
+        InstanceType::M516xlarge => Some(256.0),
+        InstanceType::M524xlarge => Some(384.0),
+        InstanceType::M5aLarge => Some(8.0),
+        InstanceType::M5aXlarge => Some(16.0),
+        InstanceType::M5a2xlarge => Some(32.0),
+        InstanceType::M5a4xlarge => Some(64.0),
+        InstanceType::M5a8xlarge => Some(128.0),
+        InstanceType::M5a12xlarge => Some(192.0),
+        InstanceType::M5a16xlarge => Some(256.0),
+        InstanceType::M5a24xlarge => Some(384.0),
+        InstanceType::C5Large => Some(4.0),
+        InstanceType::C5Xlarge => Some(8.0),
+        InstanceType::C52xlarge => Some(16.0),
+        InstanceType::C54xlarge => Some(32.0),
+        InstanceType::C59xlarge => Some(72.0),
+        InstanceType::C512xlarge => Some(96.0),
+        InstanceType::C518xlarge => Some(144.0),
+        InstanceType::C524xlarge => Some(192.0),
+        _ => None,
+    }
+}
+
+/// Network performance for the instance types Pocket Architect knows about
+fn known_instance_type_network_performance(instance_type: &InstanceType) -> Option<&'static str> {
+    match instance_type {
+        InstanceType::T2Micro | InstanceType::T2Small | InstanceType::T2Medium => Some("Low to Moderate"),
+        InstanceType::T2Large | InstanceType::T2Xlarge | InstanceType::T22xlarge => Some("Moderate"),
+        InstanceType::T3Micro | InstanceType::T3Small | InstanceType::T3Medium => Some("Low to Moderate"),
+        InstanceType::T3Large | InstanceType::T3Xlarge | InstanceType::T32xlarge => Some("Moderate"),
+        InstanceType::M5Large | InstanceType::M5Xlarge => Some("Up to 10 Gigabit"),
+        InstanceType::M52xlarge | InstanceType::M54xlarge => Some("Up to 10 Gigabit"),
+        InstanceType::M58xlarge | InstanceType::M512xlarge => Some("10 Gigabit"),
+        InstanceType::M516xlarge | InstanceType::M524xlarge => Some("20 Gigabit"),
+        InstanceType::C5Large | InstanceType::C5Xlarge => Some("Up to 10 Gigabit"),
+        InstanceType::C52xlarge | InstanceType::C54xlarge => Some("Up to 10 Gigabit"),
+        InstanceType::C59xlarge | InstanceType::C512xlarge => Some("10 Gigabit"),
+        InstanceType::C518xlarge | InstanceType::C524xlarge => Some("20 Gigabit"),
+        _ => None,
+    }
+}
+
+/// Spec for a DescribeInstanceTypes entry
+pub fn instance_type_spec(info: &InstanceTypeInfo) -> Option<InstanceTypeSpec> {
+    Some(InstanceTypeSpec {
+        instance_type: info.instance_type()?.as_str().to_string(),
+        vcpus: info.v_cpu_info().and_then(|v| v.default_v_cpus())?,
+        memory_gb: info.memory_info().and_then(|m| m.size_in_mib())? as f64 / 1024.0,
+        network_performance: info.network_info()
+            .and_then(|n| n.network_performance())
+            .unwrap_or("unknown")
+            .to_string(),
+    })
+}
+
+/// vCPUs, memory in GB and network performance of an instance. Without a
+/// spec from DescribeInstanceTypes, the built-in tables and the instance's
+/// CPU options are used.
+pub fn instance_resources(instance: &AwsSdkInstance, spec: Option<&InstanceTypeSpec>) -> (i32, f64, String) {
+    if let Some(spec) = spec {
+        return (spec.vcpus, spec.memory_gb, spec.network_performance.clone());
+    }
+
+    let vcpus = instance.cpu_options()
+        .and_then(|cpu| Some(cpu.core_count()? * cpu.threads_per_core().unwrap_or(1)))
+        .unwrap_or(1);
+    let memory_gb = instance.instance_type()
+        .map(|it| known_instance_type_memory_gb(it).unwrap_or(8.0))
+        .unwrap_or(1.0);
+    let network_performance = instance.instance_type()
+        .map(|it| known_instance_type_network_performance(it).unwrap_or("Moderate"))
+        .unwrap_or("unknown")
+        .to_string();
+
+    (vcpus, memory_gb, network_performance)
+}
+
+/// Unique `pocket-architect-<timestamp>-<id>` name for instances and key pairs
+fn generate_resource_name() -> String {
+    let timestamp = Utc::now().format("%Y%m%d-%H%M%S");
+    let unique_id = Uuid::new_v4().simple().to_string()[..8].to_string();
+    format!("pocket-architect-{}-{}", timestamp, unique_id)
+}
+
+/// Launch through `launch` with the key pair name attached, then hand the private
+/// key to `persist` keyed by the new instance id. Only a failed launch is an
+/// error: if persisting fails the instance is already running, so the key is
+/// returned with the launch instead of being lost.
+pub async fn launch_with_key_pair<L, Fut, P>(key_pair: GeneratedKeyPair, launch: L, persist: P) -> AwsResult<KeyPairLaunch>
+where
+    L: FnOnce(String) -> Fut,
+    Fut: Future<Output = AwsResult<String>>,
+    P: FnOnce(&str, &GeneratedKeyPair) -> AwsResult<()>,
+{
+    let instance_id = launch(key_pair.key_name.clone()).await?;
+
+    let unstored_private_key = match persist(&instance_id, &key_pair) {
+        Ok(()) => {
+            tracing::info!("Stored private key for key pair {} (instance {})", key_pair.key_name, instance_id);
+            None
+        }
+        Err(e) => {
+            tracing::error!("Instance {} launched but its private key couldn't be stored: {}", instance_id, e);
+            Some(UnstoredPrivateKey {
+                private_key: key_pair.private_key,
+                error: e.to_string(),
+            })
+        }
+    };
+
+    Ok(KeyPairLaunch {
+        instance_id,
+        key_name: key_pair.key_name,
+        unstored_private_key,
+    })
+}
+
+/// Directory generated private keys are written to for SSH
+pub fn ssh_key_dir() -> AwsResult<std::path::PathBuf> {
+    let dir = crate::app_paths::app_data_dir()
+        .map_err(|e| AwsError::OperationError(e.to_string()))?
+        .join(SSH_KEY_DIR);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| AwsError::OperationError(format!("Failed to create SSH key directory: {}", e)))?;
+    Ok(dir)
+}
+
+/// Write a generated private key into `dir` for SSH, unless it's already
+/// there. The file is created owner-only, so the key is never readable by
+/// anyone else, even briefly.
+pub fn write_ssh_key_file(dir: &std::path::Path, key_name: &str, private_key: &str) -> AwsResult<std::path::PathBuf> {
+    let path = dir.join(format!("{}.pem", key_name));
+    if !path.exists() {
+        crate::credential_store::write_private_file(&path, private_key.as_bytes())
+            .map_err(|e| AwsError::OperationError(format!("Failed to write SSH key file: {}", e)))?;
+    }
+    Ok(path)
+}
+
+/// Path of the key file for an instance. A path configured for the key pair wins;
+/// keys generated by Pocket Architect are written once from the credential
+/// store into the app data directory; other keys are expected in ~/.ssh.
+fn ssh_key_path(instance_id: &str, key_name: &str, key_paths: &HashMap<String, String>) -> AwsResult<String> {
+    if key_paths.contains_key(key_name) {
+        return Ok(local_ssh_key_path(key_name, key_paths));
+    }
+
+    let dir = ssh_key_dir()?;
+    let existing = dir.join(format!("{}.pem", key_name));
+    if existing.exists() {
+        return Ok(existing.display().to_string());
+    }
+
+    let private_key = crate::credential_store::retrieve_ssh_private_key(instance_id)
+        .map_err(|e| AwsError::OperationError(format!("Failed to read SSH private key: {}", e)))?;
+
+    let Some(private_key) = private_key else {
+        return Ok(local_ssh_key_path(key_name, key_paths));
+    };
+
+    let path = write_ssh_key_file(&dir, key_name, &private_key)?;
+    Ok(path.display().to_string())
+}
+
+/// Private key kept by the user for `key_name`: the configured path, otherwise
+/// ~/.ssh/<key_name>.pem
+pub fn local_ssh_key_path(key_name: &str, key_paths: &HashMap<String, String>) -> String {
+    match key_paths.get(key_name).filter(|path| !path.is_empty()) {
+        Some(path) => path.clone(),
+        None => format!("~/.ssh/{}.pem", key_name),
+    }
+}
+
+/// Map an SDK security group, including the SecurityConfig it was built from
+pub fn map_security_group(group: &SecurityGroup, region: &str) -> AwsSecurityGroupInfo {
+    let tag = |key: &str| group.tags().iter().find(|t| t.key() == Some(key)).and_then(|t| t.value());
+
+    AwsSecurityGroupInfo {
+        group_id: group.group_id().unwrap_or("unknown").to_string(),
+        group_name: group.group_name().unwrap_or("unknown").to_string(),
+        description: group.description().map(|s| s.to_string()),
+        vpc_id: group.vpc_id().map(|s| s.to_string()),
+        region: region.to_string(),
+        ingress_rules: split_ip_permissions(group.ip_permissions()).len(),
+        egress_rules: split_ip_permissions(group.ip_permissions_egress()).len(),
+        security_config_id: tag(SECURITY_CONFIG_TAG_KEY).and_then(|v| v.parse().ok()),
+    }
+}
+
+fn name_tag(tags: &[aws_sdk_ec2::types::Tag]) -> Option<String> {
+    tags.iter().find(|t| t.key() == Some("Name")).and_then(|t| t.value()).map(str::to_string)
+}
+
+pub fn map_vpc(vpc: &Vpc, region: &str) -> AwsVpc {
+    let mut cidr_blocks: Vec<String> = vpc.cidr_block().map(str::to_string).into_iter().collect();
+    for association in vpc.cidr_block_association_set() {
+        if let Some(cidr) = association.cidr_block() {
+            if !cidr_blocks.iter().any(|c| c == cidr) {
+                cidr_blocks.push(cidr.to_string());
+            }
+        }
+    }
+
+    AwsVpc {
+        vpc_id: vpc.vpc_id().unwrap_or("unknown").to_string(),
+        name: name_tag(vpc.tags()),
+        cidr_blocks,
+        is_default: vpc.is_default().unwrap_or(false),
+        state: vpc.state().map(|s| s.as_str().to_string()).unwrap_or_else(|| "unknown".to_string()),
+        region: region.to_string(),
+    }
+}
+
+pub fn map_subnet(subnet: &Subnet, region: &str) -> AwsSubnet {
+    AwsSubnet {
+        subnet_id: subnet.subnet_id().unwrap_or("unknown").to_string(),
+        vpc_id: subnet.vpc_id().unwrap_or("unknown").to_string(),
+        name: name_tag(subnet.tags()),
+        cidr_block: subnet.cidr_block().map(str::to_string),
+        availability_zone: subnet.availability_zone().unwrap_or("unknown").to_string(),
+        available_ip_addresses: subnet.available_ip_address_count().unwrap_or(0),
+        map_public_ip_on_launch: subnet.map_public_ip_on_launch().unwrap_or(false),
+        region: region.to_string(),
+    }
+}
+
+/// VPC a launch lands in given the requested VPC and the chosen subnet. A
+/// subnet decides the VPC, so one in another VPC is rejected; a VPC without a
+/// subnet is rejected too, since EC2 would otherwise use the default VPC.
+pub fn validate_launch_network(vpc_id: Option<&str>, subnet: Option<&AwsSubnet>) -> AwsResult<Option<String>> {
+    match (vpc_id, subnet) {
+        (Some(vpc_id), Some(subnet)) if subnet.vpc_id != vpc_id => Err(AwsError::OperationError(format!(
+            "Subnet {} belongs to {}, not {}",
+            subnet.subnet_id, subnet.vpc_id, vpc_id
+        ))),
+        (_, Some(subnet)) => Ok(Some(subnet.vpc_id.clone())),
+        (Some(vpc_id), None) => Err(AwsError::OperationError(format!(
+            "Choose a subnet in {} to launch into it",
+            vpc_id
+        ))),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Rules read from a security group, and the permissions that couldn't be
+/// expressed as `SecurityRule`s
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportedRules {
+    pub rules: Vec<SecurityRule>,
+    pub skipped: Vec<String>,
+}
+
+/// Map permissions to `SecurityRule`s of `rule_type`. A rule has one source,
+/// so a permission listing several CIDRs or groups becomes one rule per source,
+/// and sources repeated across permissions are only kept once. Port ranges
+/// other than "all ports" have no rule equivalent and are skipped.
+pub fn ip_permissions_to_rules(permissions: &[IpPermission], rule_type: &str) -> ImportedRules {
+    let mut imported = ImportedRules::default();
+    let mut seen = std::collections::HashSet::new();
+
+    for (key, permission) in split_ip_permissions(permissions) {
+        let (source, description) = match &key.source {
+            PermissionSource::Ipv4(cidr) => (cidr.clone(), permission.ip_ranges().first().and_then(|r| r.description())),
+            PermissionSource::Ipv6(cidr) => (cidr.clone(), permission.ipv6_ranges().first().and_then(|r| r.description())),
+            PermissionSource::Group(group_id) => (group_id.clone(), permission.user_id_group_pairs().first().and_then(|p| p.description())),
+        };
+
+        let port = match (key.protocol.as_str(), key.from_port, key.to_port) {
+            ("-1", _, _) => None,
+            ("icmp", Some(icmp_type), _) if icmp_type >= 0 => Some(icmp_type),
+            ("icmp", _, _) => None,
+            (_, Some(from), Some(to)) if from == to => Some(from),
+            (protocol, from, to) => {
+                imported.skipped.push(format!(
+                    "{} ports {}-{} from {}",
+                    protocol,
+                    from.map_or("?".to_string(), |p| p.to_string()),
+                    to.map_or("?".to_string(), |p| p.to_string()),
+                    source
+                ));
+                continue;
+            }
+        };
+
+        if !seen.insert((key.protocol.clone(), port, source.clone())) {
+            continue;
+        }
+        imported.rules.push(SecurityRule {
+            rule_type: rule_type.to_string(),
+            port,
+            protocol: Some(key.protocol),
+            source,
+            description: description.map(str::to_string),
+        });
+    }
+
+    imported
+}
+
+/// Where traffic for a single-source permission comes from or goes to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum PermissionSource {
+    Ipv4(String),
+    Ipv6(String),
+    Group(String),
+}
+
+/// Identity of a single-source permission; descriptions don't count
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PermissionKey {
+    protocol: String,
+    from_port: Option<i32>,
+    to_port: Option<i32>,
+    source: PermissionSource,
+}
+
+/// Split permissions into one permission per source, keyed for comparison
+fn split_ip_permissions(permissions: &[IpPermission]) -> Vec<(PermissionKey, IpPermission)> {
+    let mut split = Vec::new();
+
+    for permission in permissions {
+        let base = || {
+            IpPermission::builder()
+                .set_ip_protocol(permission.ip_protocol().map(str::to_string))
+                .set_from_port(permission.from_port())
+                .set_to_port(permission.to_port())
+        };
+        let key = |source| PermissionKey {
+            protocol: permission.ip_protocol().unwrap_or("-1").to_lowercase(),
+            from_port: permission.from_port(),
+            to_port: permission.to_port(),
+            source,
+        };
+
+        for range in permission.ip_ranges() {
+            let cidr = range.cidr_ip().unwrap_or_default().to_string();
+            split.push((key(PermissionSource::Ipv4(cidr)), base().ip_ranges(range.clone()).build()));
+        }
+        for range in permission.ipv6_ranges() {
+            let cidr = range.cidr_ipv6().unwrap_or_default().to_string();
+            split.push((key(PermissionSource::Ipv6(cidr)), base().ipv6_ranges(range.clone()).build()));
+        }
+        for pair in permission.user_id_group_pairs() {
+            let group_id = pair.group_id().unwrap_or_default().to_string();
+            split.push((key(PermissionSource::Group(group_id)), base().user_id_group_pairs(pair.clone()).build()));
+        }
+    }
+
+    split
+}
+
+/// Rules to add and remove to bring a security group in line with its config
+#[derive(Debug, Clone, Default)]
+pub struct PermissionDiff {
+    pub to_authorize: Vec<IpPermission>,
+    pub to_revoke: Vec<IpPermission>,
+}
+
+/// Compare a group's current permissions with the desired ones, source by source
+pub fn diff_ip_permissions(current: &[IpPermission], desired: &[IpPermission]) -> PermissionDiff {
+    let current = split_ip_permissions(current);
+    let desired = split_ip_permissions(desired);
+
+    let mut diff = PermissionDiff::default();
+    let mut authorized = Vec::new();
+    for (key, permission) in &desired {
+        if !current.iter().any(|(k, _)| k == key) && !authorized.contains(key) {
+            authorized.push(key.clone());
+            diff.to_authorize.push(permission.clone());
+        }
+    }
+    for (key, permission) in current {
+        if !desired.iter().any(|(k, _)| k == &key) {
+            diff.to_revoke.push(permission);
+        }
+    }
+
+    diff
+}
+
+/// Ingress and egress permissions built from a SecurityConfig's rules
+#[derive(Debug, Clone, Default)]
+pub struct SecurityGroupPermissions {
+    pub ingress: Vec<IpPermission>,
+    pub egress: Vec<IpPermission>,
+}
+
+/// Convert every rule, failing on the first malformed one so nothing is applied partially
+pub fn rules_to_ip_permissions(rules: &[SecurityRule]) -> AwsResult<SecurityGroupPermissions> {
+    let mut permissions = SecurityGroupPermissions::default();
+
+    for rule in rules {
+        let permission = rule_to_ip_permission(rule)?;
+        match rule.rule_type.to_lowercase().as_str() {
+            "ingress" | "inbound" => permissions.ingress.push(permission),
+            _ => permissions.egress.push(permission),
+        }
+    }
+
+    Ok(permissions)
+}
+
+/// Convert a stored SecurityRule to an EC2 IpPermission. Ports must be 0-65535,
+/// protocols tcp/udp/icmp/-1, and the source a CIDR block or security group ID.
+pub fn rule_to_ip_permission(rule: &SecurityRule) -> AwsResult<IpPermission> {
+    match rule.rule_type.to_lowercase().as_str() {
+        "ingress" | "inbound" | "egress" | "outbound" => {}
+        other => return Err(AwsError::OperationError(format!("Invalid rule type '{}': expected ingress or egress", other))),
+    }
+
+    let protocol = rule.protocol.as_deref().unwrap_or("tcp").to_lowercase();
+    if !matches!(protocol.as_str(), "tcp" | "udp" | "icmp" | "-1") {
+        return Err(AwsError::OperationError(format!("Invalid protocol '{}': expected tcp, udp, icmp or -1", protocol)));
+    }
+
+    if let Some(port) = rule.port {
+        if !(0..=65535).contains(&port) {
+            return Err(AwsError::OperationError(format!("Invalid port {}: must be between 0 and 65535", port)));
+        }
+    }
+
+    let mut permission = IpPermission::builder().ip_protocol(&protocol);
+
+    permission = match (protocol.as_str(), rule.port) {
+        ("tcp" | "udp", Some(port)) => permission.from_port(port).to_port(port),
+        ("tcp" | "udp", None) => {
+            return Err(AwsError::OperationError(format!("A port is required for {} rules", protocol)));
+        }
+        // For ICMP the port field carries the ICMP type; no port means all types
+        ("icmp", port) => permission.from_port(port.unwrap_or(-1)).to_port(-1),
+        _ => permission,
+    };
+
+    let source = rule.source.trim();
+    let description = rule.description.clone().filter(|d| !d.is_empty());
+
+    permission = if source.starts_with("sg-") {
+        permission.user_id_group_pairs(
+            UserIdGroupPair::builder()
+                .group_id(source)
+                .set_description(description)
+                .build()
+        )
+    } else {
+        match parse_cidr(source) {
+            Some(std::net::IpAddr::V4(_)) => permission.ip_ranges(
+                IpRange::builder().cidr_ip(source).set_description(description).build()
+            ),
+            Some(std::net::IpAddr::V6(_)) => permission.ipv6_ranges(
+                Ipv6Range::builder().cidr_ipv6(source).set_description(description).build()
+            ),
+            None => {
+                return Err(AwsError::OperationError(format!("Invalid source '{}': expected a CIDR block or security group ID", source)));
+            }
+        }
+    };
+
+    Ok(permission.build())
+}
+
+/// Parse `address/prefix`, returning the address when the prefix fits its family
+fn parse_cidr(cidr: &str) -> Option<std::net::IpAddr> {
+    let (address, prefix) = cidr.split_once('/')?;
+    let address: std::net::IpAddr = address.parse().ok()?;
+    let prefix: u8 = prefix.parse().ok()?;
+
+    let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+    (prefix <= max_prefix).then_some(address)
+}
+
+/// Security group names allow a limited character set; replace anything else
+fn sanitize_group_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect()
+}
@@ -23,6 +23,9 @@ pub enum AwsError {
     #[error("Authentication error: {0}")]
     AuthError(String),
 
+    #[error("Account {account_id} is a {platform} account; AWS clients can only be built for AWS accounts")]
+    WrongPlatform { account_id: i64, platform: String },
+
     #[error("Region error: {0}")]
     RegionError(String),
 
@@ -32,9 +35,22 @@ pub enum AwsError {
     #[error("Rate limit exceeded: {0}")]
     RateLimitError(String),
 
+    #[error("{action} still failing after {attempts} attempts, giving up: {source}")]
+    RetriesExhausted {
+        action: String,
+        attempts: u32,
+        source: Box<AwsError>,
+    },
+
     #[error("Permission denied: {0}")]
     PermissionError(String),
 
+    #[error("Opt-in required: {0}")]
+    OptInRequired(String),
+
+    #[error("Instance is not in a valid state: {0}")]
+    InstanceStateError(String),
+
     #[error("Network error: {0}")]
     NetworkError(String),
 
@@ -44,44 +44,126 @@ pub enum AwsEventType {
 // EVENT STORE FOR PERSISTENCE
 // ============================================================================
 
+/// Events kept for replay by default. At roughly 1 KB per event this bounds
+/// the store to about a megabyte.
+pub const DEFAULT_EVENT_BUFFER_SIZE: usize = 1000;
+
+/// An event with the sequence id the store assigned to it. This is the shape
+/// emitted to the frontend, so it can replay from the last id it saw.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredEvent {
+    pub id: u64,
+    #[serde(flatten)]
+    pub event: AwsEventPayload,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventReplay {
+    pub events: Vec<StoredEvent>,
+    /// Id of the newest stored event, to resume from on the next replay
+    pub last_id: u64,
+    /// Events after the requested id were evicted before they could be replayed
+    pub truncated: bool,
+}
+
+struct EventBuffer {
+    next_id: u64,
+    events: VecDeque<StoredEvent>,
+}
+
+/// Ring buffer of recent events. Ids increase by one per stored event and
+/// start at 1; once `max_events` are held, storing an event evicts the oldest.
 pub struct EventStore {
     max_events: usize,
-    events: Arc<Mutex<VecDeque<AwsEventPayload>>>,
+    buffer: Arc<Mutex<EventBuffer>>,
 }
 
 impl EventStore {
     pub fn new(max_events: usize) -> Self {
         Self {
             max_events,
-            events: Arc::new(Mutex::new(VecDeque::new())),
+            buffer: Arc::new(Mutex::new(EventBuffer {
+                next_id: 1,
+                events: VecDeque::with_capacity(max_events),
+            })),
         }
     }
 
-    pub async fn store_event(&self, event: AwsEventPayload) {
-        let mut events = self.events.lock().await;
-        events.push_back(event);
+    /// Store an event, evicting the oldest when full, and return it with its id
+    pub async fn store_event(&self, event: AwsEventPayload) -> StoredEvent {
+        let mut buffer = self.buffer.lock().await;
+        let stored = StoredEvent { id: buffer.next_id, event };
+        buffer.next_id += 1;
+        buffer.events.push_back(stored.clone());
 
         // Keep only recent events
-        while events.len() > self.max_events {
-            events.pop_front();
+        while buffer.events.len() > self.max_events {
+            buffer.events.pop_front();
         }
+        stored
     }
 
     pub async fn get_recent_events(&self, since: Option<DateTime<Utc>>) -> Vec<AwsEventPayload> {
-        let events = self.events.lock().await;
-        if let Some(since_time) = since {
-            events.iter()
-                .filter(|e| e.timestamp > since_time)
+        let buffer = self.buffer.lock().await;
+        buffer.events.iter()
+            .filter(|e| since.map_or(true, |since_time| e.event.timestamp > since_time))
+            .map(|e| e.event.clone())
+            .collect()
+    }
+
+    /// Stored events with an id above `after_id`, optionally limited to the
+    /// given event types
+    pub async fn replay(&self, after_id: u64, types: &[String]) -> EventReplay {
+        let buffer = self.buffer.lock().await;
+        let oldest_id = buffer.events.front().map_or(buffer.next_id, |e| e.id);
+
+        EventReplay {
+            events: buffer.events.iter()
+                .filter(|e| e.id > after_id)
+                .filter(|e| types.is_empty() || types.contains(&e.event.event_type))
                 .cloned()
-                .collect()
-        } else {
-            events.iter().cloned().collect()
+                .collect(),
+            last_id: buffer.next_id - 1,
+            truncated: oldest_id > after_id + 1,
         }
     }
 
+    /// Rewrite `project_name` on the stored instance events of a renamed
+    /// project, so replays show the new name without a resync. Returns the
+    /// number of instance entries updated.
+    pub async fn rename_project(&self, project_id: i64, project_name: &str) -> usize {
+        let mut buffer = self.buffer.lock().await;
+        let mut renamed = 0;
+
+        for stored in buffer.events.iter_mut() {
+            let instances = match stored.event.event_type.as_str() {
+                "instance_created" | "instance_updated" => std::slice::from_mut(&mut stored.event.data),
+                "instances_updated" => match stored.event.data.as_array_mut() {
+                    Some(instances) => instances.as_mut_slice(),
+                    None => continue,
+                },
+                _ => continue,
+            };
+
+            for instance in instances.iter_mut() {
+                if instance.get("project_id").and_then(|id| id.as_i64()) == Some(project_id) {
+                    instance["project_name"] = serde_json::Value::String(project_name.to_string());
+                    renamed += 1;
+                }
+            }
+        }
+        renamed
+    }
+
     pub async fn get_event_count(&self) -> usize {
-        let events = self.events.lock().await;
-        events.len()
+        let buffer = self.buffer.lock().await;
+        buffer.events.len()
+    }
+}
+
+impl Default for EventStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_BUFFER_SIZE)
     }
 }
 
@@ -141,6 +223,17 @@ impl AwsEventEmitter {
         self.emit_and_store(payload).await;
     }
 
+    /// One event for a start, stop or terminate applied to many instances
+    pub async fn emit_instances_bulk_operation(&self, report: &crate::aws::BulkInstanceOperationReport) {
+        let payload = AwsEventPayload {
+            event_type: "instances_bulk_operation".to_string(),
+            timestamp: Utc::now(),
+            data: serde_json::to_value(report).unwrap(),
+            request_id: None,
+        };
+        self.emit_and_store(payload).await;
+    }
+
     pub async fn emit_instance_status_change(&self, instance_id: &str, new_status: &str) {
         let payload = AwsEventPayload {
             event_type: "instance_status_changed".to_string(),
@@ -233,6 +326,16 @@ impl AwsEventEmitter {
         self.emit_and_store(payload).await;
     }
 
+    pub async fn emit_project_renamed(&self, project_id: i64, project_name: &str) {
+        let payload = AwsEventPayload {
+            event_type: "project_renamed".to_string(),
+            timestamp: Utc::now(),
+            data: serde_json::json!({ "project_id": project_id, "project_name": project_name }),
+            request_id: None,
+        };
+        self.emit_and_store(payload).await;
+    }
+
     pub async fn emit_account_connected(&self, account: serde_json::Value) {
         let payload = AwsEventPayload {
             event_type: "account_connected".to_string(),
@@ -271,12 +374,12 @@ impl AwsEventEmitter {
     async fn emit_and_store(&self, payload: AwsEventPayload) {
         tracing::debug!("Emitting AWS event: {} at {}", payload.event_type, payload.timestamp);
 
-        // Emit to frontend
+        // Store first so the emitted event carries its replay id
         let event_name = format!("aws:{}", payload.event_type);
-        let _ = self.app_handle.emit(&event_name, &payload);
+        let stored = self.event_store.store_event(payload).await;
 
-        // Store for persistence
-        self.event_store.store_event(payload).await;
+        // Emit to frontend
+        let _ = self.app_handle.emit(&event_name, &stored);
     }
 }
 
@@ -302,9 +405,12 @@ pub fn create_optimistic_instance(instance_type: &str, ami_id: &str, region: Opt
         region: region.unwrap_or("us-east-1").to_string(),
         public_ip: None,
         private_ip: "creating...".to_string(),
+        ipv6_address: None,
+        network_interface_count: 0,
         created: chrono::Utc::now().to_rfc3339(),
         uptime: "0s".to_string(),
         monthly_cost: 0.0, // Will be calculated after creation
+        coverage: crate::aws::InstanceCoverage::OnDemand,
         storage: 8, // Default
         security_config: "default".to_string(),
         ssh_key: "default".to_string(),
@@ -42,9 +42,12 @@ mod integration_tests {
                 cpu_count: 1,
                 memory_gb: 1.0,
                 storage_gb: 8.0,
+                volumes: Vec::new(),
                 network_performance: "Low to Moderate".to_string(),
                 public_ip: Some("1.2.3.4".to_string()),
                 private_ip: Some("10.0.0.1".to_string()),
+                ipv6_address: None,
+                network_interfaces: Vec::new(),
                 security_groups: vec![],
                 key_pairs: vec!["my-key".to_string()],
                 tags: [("Name".to_string(), "real-instance".to_string())].into(),
@@ -53,6 +56,9 @@ mod integration_tests {
                 ebs_optimized: false,
                 virtualization_type: "hvm".to_string(),
                 architecture: "x86_64".to_string(),
+                lifecycle: "on-demand".to_string(),
+                platform_details: None,
+                coverage: InstanceCoverage::OnDemand,
             };
 
             let real_frontend_instance = aws_instance_to_frontend(
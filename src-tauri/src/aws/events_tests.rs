@@ -62,6 +62,77 @@ mod event_system_tests {
         });
     }
 
+    #[test]
+    fn test_replay_skips_evicted_events() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let store = EventStore::new(3);
+
+            for i in 0..5 {
+                let event = AwsEventPayload {
+                    event_type: if i % 2 == 0 { "cost_alert" } else { "instance_updated" }.to_string(),
+                    timestamp: Utc::now(),
+                    data: serde_json::json!({ "n": i }),
+                    request_id: None,
+                };
+                let stored = store.store_event(event).await;
+                assert_eq!(stored.id, i + 1);
+            }
+
+            // Events 1 and 2 were evicted; the frontend last saw event 1
+            let replay = store.replay(1, &[]).await;
+            assert_eq!(replay.events.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3, 4, 5]);
+            assert_eq!(replay.last_id, 5);
+            assert!(replay.truncated);
+
+            // Nothing was missed when resuming from the oldest buffered id
+            let replay = store.replay(2, &["cost_alert".to_string()]).await;
+            assert_eq!(replay.events.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3, 5]);
+            assert!(!replay.truncated);
+
+            assert!(store.replay(5, &[]).await.events.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_rename_project_updates_stored_instances() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let store = EventStore::new(10);
+            let instance = |id: i64, project_id: i64, project_name: &str| {
+                let mut instance = create_optimistic_instance("t3.micro", "ami-12345678", None);
+                instance.id = id;
+                instance.project_id = project_id;
+                instance.project_name = project_name.to_string();
+                instance
+            };
+
+            store.store_event(AwsEventPayload {
+                event_type: "instances_updated".to_string(),
+                timestamp: Utc::now(),
+                data: serde_json::to_value(vec![instance(1, 7, "Staging"), instance(2, 8, "Prod")]).unwrap(),
+                request_id: None,
+            }).await;
+            store.store_event(AwsEventPayload {
+                event_type: "instance_updated".to_string(),
+                timestamp: Utc::now(),
+                data: serde_json::to_value(instance(1, 7, "Staging")).unwrap(),
+                request_id: None,
+            }).await;
+
+            assert_eq!(store.rename_project(7, "QA").await, 2);
+
+            let replay = store.replay(0, &[]).await;
+            let listed: Vec<crate::aws::adapters::Instance> = serde_json::from_value(replay.events[0].event.data.clone()).unwrap();
+            assert_eq!(listed[0].project_name, "QA");
+            assert_eq!(listed[1].project_name, "Prod");
+            let updated: crate::aws::adapters::Instance = serde_json::from_value(replay.events[1].event.data.clone()).unwrap();
+            assert_eq!(updated.project_name, "QA");
+
+            assert_eq!(store.rename_project(42, "Nobody").await, 0);
+        });
+    }
+
     #[test]
     fn test_debounced_emitter() {
         let mut emitter = DebouncedEmitter::new(std::time::Duration::from_millis(100));
@@ -108,13 +179,18 @@ mod event_system_tests {
             cpu_count: 1,
             memory_gb: 1.0,
             storage_gb: 8.0,
+            volumes: Vec::new(),
             network_performance: "Low to Moderate".to_string(),
             public_ip: Some("1.2.3.4".to_string()),
             private_ip: Some("10.0.0.1".to_string()),
+            ipv6_address: None,
+            network_interfaces: Vec::new(),
             security_groups: vec![AwsSecurityGroup {
                 group_id: "sg-12345".to_string(),
                 group_name: "default".to_string(),
                 description: Some("Default security group".to_string()),
+                ingress_rules: Some(1),
+                egress_rules: Some(1),
             }],
             key_pairs: vec!["my-key".to_string()],
             tags: [("Name".to_string(), "test-instance".to_string()), ("Environment".to_string(), "dev".to_string())].into(),
@@ -123,6 +199,9 @@ mod event_system_tests {
             ebs_optimized: false,
             virtualization_type: "hvm".to_string(),
             architecture: "x86_64".to_string(),
+            lifecycle: "on-demand".to_string(),
+            platform_details: None,
+            coverage: InstanceCoverage::OnDemand,
         };
 
         let frontend_instance = aws_instance_to_frontend(
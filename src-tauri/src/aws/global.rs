@@ -0,0 +1,114 @@
+// ============================================================================
+// GLOBAL INSTANCE VIEW
+// ============================================================================
+// One instance list across every configured account, collected concurrently
+// and bounded by a semaphore
+// ============================================================================
+
+use crate::aws::{AwsClient, AwsInstance, AwsResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Maximum number of accounts queried at the same time
+pub const MAX_CONCURRENT_ACCOUNTS: usize = 4;
+
+/// The configured account an instance was collected through
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstanceAccount {
+    pub account_id: i64,
+    pub account_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalInstance {
+    pub account_id: i64,
+    pub account_name: String,
+    #[serde(flatten)]
+    pub instance: AwsInstance,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GlobalInstanceView {
+    pub instances: Vec<GlobalInstance>,
+    /// Error per account id for accounts that couldn't be collected
+    pub errors: BTreeMap<i64, String>,
+    /// Instances seen through more than one account (two accounts holding
+    /// credentials for the same AWS account), listed only once
+    pub duplicates_removed: usize,
+}
+
+/// Call `collect` for every account, at most `max_concurrent` at a time, and
+/// merge the results. A failing account is recorded in `errors` without
+/// affecting the others. When the same instance comes back from several
+/// accounts it is kept under the first one in `accounts`.
+pub async fn collect_across_accounts<C, F, Fut>(accounts: Vec<(InstanceAccount, C)>, max_concurrent: usize, collect: F) -> GlobalInstanceView
+where
+    F: Fn(C) -> Fut,
+    Fut: Future<Output = AwsResult<Vec<AwsInstance>>> + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+    let mut tasks = JoinSet::new();
+
+    for (index, (account, target)) in accounts.into_iter().enumerate() {
+        let semaphore = semaphore.clone();
+        let future = collect(target);
+
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.expect("account semaphore closed");
+            (index, account, future.await)
+        });
+    }
+
+    let mut outcomes = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(e) => tracing::error!("Account collection task failed: {}", e),
+        }
+    }
+
+    // Merge in the order the accounts were given so deduplication is stable
+    outcomes.sort_by_key(|(index, _, _)| *index);
+
+    let mut view = GlobalInstanceView::default();
+    let mut seen = HashSet::new();
+    for (_, account, result) in outcomes {
+        match result {
+            Ok(instances) => {
+                for instance in instances {
+                    if !seen.insert((instance.region.clone(), instance.instance_id.clone())) {
+                        view.duplicates_removed += 1;
+                        continue;
+                    }
+                    view.instances.push(GlobalInstance {
+                        account_id: account.account_id,
+                        account_name: account.account_name.clone(),
+                        instance,
+                    });
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Instance collection failed for account {}: {}", account.account_id, e);
+                view.errors.insert(account.account_id, e.to_string());
+            }
+        }
+    }
+
+    view
+}
+
+/// The account's instances in its primary region, from the cache when fresh
+pub async fn cached_instances(client: AwsClient) -> AwsResult<Vec<AwsInstance>> {
+    let region = client.primary_region().to_string();
+    if let Some(instances) = client.cache.get_ec2_instances(&region).await {
+        return Ok(instances);
+    }
+
+    let instances = client.collect_instances().await?;
+    client.cache.put_ec2_instances(region, instances.clone()).await;
+    Ok(instances)
+}
@@ -5,19 +5,52 @@
 // ============================================================================
 
 use crate::aws::{AwsClient, AwsResult, AwsError, AwsHealthReport};
+use crate::database::DbPool;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use chrono::{DateTime, Utc, Duration};
 use serde::{Serialize, Deserialize};
+use aws_sdk_ec2::error::SdkError;
+use aws_sdk_ec2::operation::describe_instances::{DescribeInstancesError, DescribeInstancesOutput};
+use aws_sdk_iam::operation::get_account_summary::{GetAccountSummaryError, GetAccountSummaryOutput};
+use aws_sdk_s3::operation::list_buckets::{ListBucketsError, ListBucketsOutput};
+use aws_sdk_iam::operation::list_users::{ListUsersError, ListUsersOutput};
+use aws_sdk_costexplorer::operation::get_cost_and_usage::{GetCostAndUsageError, GetCostAndUsageOutput};
+use aws_sdk_costexplorer::types::{DateInterval, Granularity};
+use aws_sdk_lambda::operation::list_functions::{ListFunctionsError, ListFunctionsOutput};
+use aws_sdk_costexplorer::error::{DisplayErrorContext, ProvideErrorMetadata};
+use crate::aws::permissions::{permission_status_for_error, PermissionStatus};
+
+/// Service name of the Cost Explorer entry in health reports
+pub const COST_EXPLORER_SERVICE: &str = "cost_explorer";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AwsHealthStatus {
     pub overall_status: String, // "healthy", "degraded", "unhealthy"
     pub last_check: DateTime<Utc>,
     pub services: Vec<AwsHealthReport>,
+    /// Status of each checked region and the services failing there
+    pub regions: Vec<RegionHealth>,
     pub connectivity_status: ConnectivityStatus,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionHealth {
+    pub region: String,
+    pub status: String, // "healthy", "degraded", "unhealthy"
+    pub failed_services: Vec<String>,
+}
+
+/// Outcome of one service probe. Regional services (EC2, S3) carry the
+/// region they were checked in.
+pub struct HealthCheck {
+    pub service: &'static str,
+    pub label: &'static str,
+    pub region: Option<String>,
+    pub result: AwsResult<AwsHealthReport>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectivityStatus {
     pub can_connect: bool,
@@ -29,9 +62,13 @@ pub struct ConnectivityStatus {
 #[derive(Clone)]
 pub struct AwsHealthMonitor {
     client: AwsClient,
+    regions: Vec<String>,
     status: Arc<RwLock<AwsHealthStatus>>,
     check_interval_seconds: i64,
     event_emitter: Arc<crate::aws::events::AwsEventEmitter>,
+    account_gate: Option<crate::aws::manager::AccountGate>,
+    webhook: Option<HealthWebhook>,
+    email_alerts: Option<HealthEmailAlerts>,
 }
 
 impl AwsHealthMonitor {
@@ -40,6 +77,7 @@ impl AwsHealthMonitor {
             overall_status: "unknown".to_string(),
             last_check: Utc::now(),
             services: Vec::new(),
+            regions: Vec::new(),
             connectivity_status: ConnectivityStatus {
                 can_connect: false,
                 last_successful_connection: None,
@@ -49,107 +87,71 @@ impl AwsHealthMonitor {
         };
 
         Self {
+            regions: vec![client.config.region.clone()],
             client,
             status: Arc::new(RwLock::new(initial_status)),
             check_interval_seconds,
             event_emitter,
+            account_gate: None,
+            webhook: None,
+            email_alerts: None,
         }
     }
 
-    /// Perform a comprehensive health check
-    pub async fn perform_health_check(&self) -> AwsResult<AwsHealthStatus> {
-        tracing::debug!("Performing AWS health check");
+    /// Skip background checks while the account is disabled
+    pub fn with_account_gate(mut self, gate: crate::aws::manager::AccountGate) -> Self {
+        self.account_gate = Some(gate);
+        self
+    }
 
-        let mut services = Vec::new();
-        let mut overall_status = "healthy".to_string();
-        let mut connectivity_status = ConnectivityStatus {
-            can_connect: false,
-            last_successful_connection: None,
-            consecutive_failures: 0,
-            error_message: None,
-        };
+    /// Post status transitions to a webhook
+    pub fn with_webhook(mut self, webhook: HealthWebhook) -> Self {
+        self.webhook = Some(webhook);
+        self
+    }
 
-        // Check basic connectivity
-        match self.check_connectivity().await {
-            Ok(last_success) => {
-                connectivity_status.can_connect = true;
-                connectivity_status.last_successful_connection = Some(last_success);
-                connectivity_status.consecutive_failures = 0;
-                services.push(AwsHealthReport {
-                    service: "connectivity".to_string(),
-                    status: "healthy".to_string(),
-                    details: vec!["AWS API connectivity successful".to_string()],
-                    last_check: Utc::now().to_rfc3339(),
-                });
-            }
-            Err(e) => {
-                connectivity_status.can_connect = false;
-                connectivity_status.consecutive_failures = self.get_current_failures().await + 1;
-                connectivity_status.error_message = Some(format!("Connectivity check failed: {}", e));
-                overall_status = "unhealthy".to_string();
-                services.push(AwsHealthReport {
-                    service: "connectivity".to_string(),
-                    status: "unhealthy".to_string(),
-                    details: vec![format!("AWS API connectivity failed: {}", e)],
-                    last_check: Utc::now().to_rfc3339(),
-                });
-            }
+    /// Email status transitions when SMTP is configured
+    pub fn with_email_alerts(mut self, email_alerts: HealthEmailAlerts) -> Self {
+        self.email_alerts = Some(email_alerts);
+        self
+    }
+
+    /// Run the EC2 and S3 checks in each of `regions` instead of only the
+    /// client's region
+    pub fn with_regions(mut self, regions: Vec<String>) -> Self {
+        if !regions.is_empty() {
+            self.regions = regions;
         }
+        self
+    }
 
-        // Only check other services if we can connect
-        if connectivity_status.can_connect {
-            // Check EC2 service
-            match self.check_ec2_health().await {
-                Ok(report) => services.push(report),
-                Err(e) => {
-                    tracing::warn!("EC2 health check failed: {:?}", e);
-                    overall_status = "degraded".to_string();
-                    services.push(AwsHealthReport {
-                        service: "ec2".to_string(),
-                        status: "unhealthy".to_string(),
-                        details: vec![format!("EC2 health check failed: {}", e)],
-                        last_check: Utc::now().to_rfc3339(),
-                    });
-                }
-            }
+    /// Perform a comprehensive health check
+    pub async fn perform_health_check(&self) -> AwsResult<AwsHealthStatus> {
+        tracing::debug!("Performing AWS health check");
 
-            // Check S3 service
-            match self.check_s3_health().await {
-                Ok(report) => services.push(report),
-                Err(e) => {
-                    tracing::warn!("S3 health check failed: {:?}", e);
-                    overall_status = "degraded".to_string();
-                    services.push(AwsHealthReport {
-                        service: "s3".to_string(),
-                        status: "unhealthy".to_string(),
-                        details: vec![format!("S3 health check failed: {}", e)],
-                        last_check: Utc::now().to_rfc3339(),
-                    });
-                }
-            }
+        let connectivity = self.check_connectivity().await;
 
-            // Check IAM service
-            match self.check_iam_health().await {
-                Ok(report) => services.push(report),
-                Err(e) => {
-                    tracing::warn!("IAM health check failed: {:?}", e);
-                    overall_status = "degraded".to_string();
-                    services.push(AwsHealthReport {
-                        service: "iam".to_string(),
-                        status: "unhealthy".to_string(),
-                        details: vec![format!("IAM health check failed: {}", e)],
-                        last_check: Utc::now().to_rfc3339(),
-                    });
-                }
+        // Only check other services if we can connect
+        let mut checks = Vec::new();
+        if connectivity.is_ok() {
+            for region in &self.regions {
+                checks.extend(self.check_region(region).await);
             }
+            checks.push(HealthCheck {
+                service: "iam",
+                label: "IAM",
+                region: None,
+                result: self.check_iam_health().await,
+            });
+            checks.push(HealthCheck {
+                service: COST_EXPLORER_SERVICE,
+                label: "Cost Explorer",
+                region: None,
+                result: self.check_cost_explorer_health().await,
+            });
         }
 
-        let new_status = AwsHealthStatus {
-            overall_status,
-            last_check: Utc::now(),
-            services,
-            connectivity_status,
-        };
+        let new_status = health_status_from_checks(connectivity, self.get_current_failures().await, checks);
 
         // Update stored status
         {
@@ -157,6 +159,13 @@ impl AwsHealthMonitor {
             *status = new_status.clone();
         }
 
+        if let Some(webhook) = &self.webhook {
+            webhook.notify(&new_status).await;
+        }
+        if let Some(email_alerts) = &self.email_alerts {
+            email_alerts.notify(&new_status).await;
+        }
+
         tracing::debug!("Health check completed: {}", new_status.overall_status);
         Ok(new_status)
     }
@@ -179,15 +188,34 @@ impl AwsHealthMonitor {
         Ok(start_time)
     }
 
-    /// Check EC2 service health
-    async fn check_ec2_health(&self) -> AwsResult<AwsHealthReport> {
-        let ec2_client = &self.client.ec2_client;
+    /// Check EC2 and S3 in one region
+    async fn check_region(&self, region: &str) -> Vec<HealthCheck> {
+        let regional_client = if region == self.client.config.region {
+            Ok(self.client.clone())
+        } else {
+            self.client.for_region(region).await
+        };
 
-        // Try to describe instances (limited to avoid costs)
-        let response = ec2_client
-            .describe_instances()
-            .max_results(5)
-            .send()
+        let (ec2, s3) = match regional_client {
+            Ok(client) => (
+                Self::check_ec2_health(&client, region).await,
+                Self::check_s3_health(&client, region).await,
+            ),
+            Err(e) => {
+                let message = format!("Failed to create client for {}: {}", region, e);
+                (Err(AwsError::RegionError(message.clone())), Err(AwsError::RegionError(message)))
+            }
+        };
+
+        vec![
+            HealthCheck { service: "ec2", label: "EC2", region: Some(region.to_string()), result: ec2 },
+            HealthCheck { service: "s3", label: "S3", region: Some(region.to_string()), result: s3 },
+        ]
+    }
+
+    /// Check EC2 service health
+    async fn check_ec2_health(client: &AwsClient, region: &str) -> AwsResult<AwsHealthReport> {
+        let response = probe_describe_instances(client)
             .await
             .map_err(|e| AwsError::from(aws_sdk_ec2::Error::from(e)))?;
 
@@ -198,6 +226,7 @@ impl AwsHealthMonitor {
 
         Ok(AwsHealthReport {
             service: "ec2".to_string(),
+            region: Some(region.to_string()),
             status: "healthy".to_string(),
             details: vec![
                 format!("Successfully queried EC2 instances"),
@@ -208,13 +237,8 @@ impl AwsHealthMonitor {
     }
 
     /// Check S3 service health
-    async fn check_s3_health(&self) -> AwsResult<AwsHealthReport> {
-        let s3_client = &self.client.s3_client;
-
-        // Try to list buckets
-        let response = s3_client
-            .list_buckets()
-            .send()
+    async fn check_s3_health(client: &AwsClient, region: &str) -> AwsResult<AwsHealthReport> {
+        let response = probe_list_buckets(client)
             .await
             .map_err(|e| AwsError::from(aws_sdk_s3::Error::from(e)))?;
 
@@ -222,6 +246,7 @@ impl AwsHealthMonitor {
 
         Ok(AwsHealthReport {
             service: "s3".to_string(),
+            region: Some(region.to_string()),
             status: "healthy".to_string(),
             details: vec![
                 format!("Successfully queried S3 buckets"),
@@ -233,12 +258,7 @@ impl AwsHealthMonitor {
 
     /// Check IAM service health
     async fn check_iam_health(&self) -> AwsResult<AwsHealthReport> {
-        let iam_client = &self.client.iam_client;
-
-        // Try to get account summary (safe, read-only operation)
-        let response = iam_client
-            .get_account_summary()
-            .send()
+        let response = probe_get_account_summary(&self.client)
             .await
             .map_err(|e| AwsError::from(aws_sdk_iam::Error::from(e)))?;
 
@@ -248,6 +268,7 @@ impl AwsHealthMonitor {
 
         Ok(AwsHealthReport {
             service: "iam".to_string(),
+            region: None,
             status: "healthy".to_string(),
             details: vec![
                 format!("Successfully queried IAM account summary"),
@@ -257,6 +278,27 @@ impl AwsHealthMonitor {
         })
     }
 
+    /// Check Cost Explorer access with a one-day cost query
+    async fn check_cost_explorer_health(&self) -> AwsResult<AwsHealthReport> {
+        let response = probe_get_cost_and_usage(&self.client)
+            .await
+            .map_err(|e| match permission_status_for_error(e.code()) {
+                PermissionStatus::Denied => AwsError::PermissionError(DisplayErrorContext(&e).to_string()),
+                _ => AwsError::OperationError(DisplayErrorContext(&e).to_string()),
+            })?;
+
+        Ok(AwsHealthReport {
+            service: COST_EXPLORER_SERVICE.to_string(),
+            region: None,
+            status: "healthy".to_string(),
+            details: vec![
+                format!("Successfully queried Cost Explorer"),
+                format!("Received {} daily cost results", response.results_by_time().len()),
+            ],
+            last_check: Utc::now().to_rfc3339(),
+        })
+    }
+
     /// Get current health status
     pub async fn get_health_status(&self) -> AwsHealthStatus {
         let status = self.status.read().await;
@@ -274,7 +316,8 @@ impl AwsHealthMonitor {
         let status = self.status.read().await;
         let now = Utc::now();
         let time_since_last_check = now - status.last_check;
-        time_since_last_check > Duration::seconds(self.check_interval_seconds)
+        // Until the first check there is only the placeholder status
+        status.overall_status == "unknown" || time_since_last_check > Duration::seconds(self.check_interval_seconds)
     }
 
     /// Start background health monitoring
@@ -285,6 +328,13 @@ impl AwsHealthMonitor {
             loop {
                 tokio::time::sleep(interval).await;
 
+                if let Some(gate) = &self.account_gate {
+                    if !gate.is_active().await {
+                        tracing::debug!("Skipping health check for inactive account");
+                        continue;
+                    }
+                }
+
                 if let Err(e) = self.perform_health_check().await {
                     tracing::error!("Background health check failed: {:?}", e);
                 }
@@ -315,6 +365,350 @@ impl AwsHealthMonitor {
     }
 }
 
+/// Rank of a status string; higher is worse
+fn status_severity(status: &str) -> u8 {
+    match status {
+        "healthy" => 0,
+        "degraded" => 1,
+        _ => 2,
+    }
+}
+
+/// Combine the connectivity result and per-service checks into an overall
+/// status. Connectivity failures make the system unhealthy and a failing
+/// global service degrades it. A region is degraded when some of its checks
+/// fail and unhealthy when all do; the overall status is the worst of these.
+pub fn health_status_from_checks(
+    connectivity: AwsResult<DateTime<Utc>>,
+    previous_failures: u32,
+    checks: Vec<HealthCheck>,
+) -> AwsHealthStatus {
+    let mut services = Vec::new();
+    let mut overall_status = "healthy".to_string();
+
+    let connectivity_status = match connectivity {
+        Ok(last_success) => {
+            services.push(AwsHealthReport {
+                service: "connectivity".to_string(),
+                region: None,
+                status: "healthy".to_string(),
+                details: vec!["AWS API connectivity successful".to_string()],
+                last_check: Utc::now().to_rfc3339(),
+            });
+            ConnectivityStatus {
+                can_connect: true,
+                last_successful_connection: Some(last_success),
+                consecutive_failures: 0,
+                error_message: None,
+            }
+        }
+        Err(e) => {
+            overall_status = "unhealthy".to_string();
+            services.push(AwsHealthReport {
+                service: "connectivity".to_string(),
+                region: None,
+                status: "unhealthy".to_string(),
+                details: vec![format!("AWS API connectivity failed: {}", e)],
+                last_check: Utc::now().to_rfc3339(),
+            });
+            ConnectivityStatus {
+                can_connect: false,
+                last_successful_connection: None,
+                consecutive_failures: previous_failures + 1,
+                error_message: Some(format!("Connectivity check failed: {}", e)),
+            }
+        }
+    };
+
+    // Region -> (checks run, failed services)
+    let mut regional: BTreeMap<String, (usize, Vec<String>)> = BTreeMap::new();
+
+    for check in checks {
+        if let Some(region) = &check.region {
+            let entry = regional.entry(region.clone()).or_default();
+            entry.0 += 1;
+            if check.result.is_err() {
+                entry.1.push(check.service.to_string());
+            }
+        }
+
+        match check.result {
+            Ok(report) => services.push(report),
+            Err(e) => {
+                let label = match &check.region {
+                    Some(region) => format!("{} ({})", check.label, region),
+                    None => check.label.to_string(),
+                };
+                tracing::warn!("{} health check failed: {:?}", label, e);
+                if check.region.is_none() && overall_status == "healthy" {
+                    overall_status = "degraded".to_string();
+                }
+
+                let mut details = vec![format!("{} health check failed: {}", label, e)];
+                if check.service == COST_EXPLORER_SERVICE {
+                    details.push(
+                        "Cost data is unavailable. Attach ce:* permissions (at least ce:GetCostAndUsage) to this account's IAM user or role".to_string(),
+                    );
+                }
+
+                services.push(AwsHealthReport {
+                    service: check.service.to_string(),
+                    region: check.region,
+                    status: "unhealthy".to_string(),
+                    details,
+                    last_check: Utc::now().to_rfc3339(),
+                });
+            }
+        }
+    }
+
+    let regions: Vec<RegionHealth> = regional
+        .into_iter()
+        .map(|(region, (total, failed_services))| {
+            let status = if failed_services.is_empty() {
+                "healthy"
+            } else if failed_services.len() == total {
+                "unhealthy"
+            } else {
+                "degraded"
+            };
+            RegionHealth {
+                region,
+                status: status.to_string(),
+                failed_services,
+            }
+        })
+        .collect();
+
+    if let Some(worst) = regions.iter().max_by_key(|r| status_severity(&r.status)) {
+        if status_severity(&worst.status) > status_severity(&overall_status) {
+            overall_status = worst.status.clone();
+        }
+    }
+
+    AwsHealthStatus {
+        overall_status,
+        last_check: Utc::now(),
+        services,
+        regions,
+        connectivity_status,
+    }
+}
+
+// ============================================================================
+// TRANSITION NOTIFICATIONS
+// ============================================================================
+// Post to a webhook (Slack incoming webhooks included) when the overall status
+// changes, rather than on every check
+
+/// Minimum time between webhook posts, so a flapping status doesn't flood the channel
+pub const DEFAULT_WEBHOOK_MIN_INTERVAL_SECONDS: i64 = 300;
+
+/// Payload posted for a status change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthTransition {
+    pub previous_status: String,
+    pub status: String,
+    pub failing_services: Vec<String>,
+    /// One-line summary; Slack shows this field
+    pub text: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Services that are not healthy, as `service` or `service (region)`
+pub fn failing_services(status: &AwsHealthStatus) -> Vec<String> {
+    status.services
+        .iter()
+        .filter(|s| s.status != "healthy")
+        .map(|s| match &s.region {
+            Some(region) => format!("{} ({})", s.service, region),
+            None => s.service.clone(),
+        })
+        .collect()
+}
+
+/// Decides which checks are reported. The first check only sets the baseline.
+/// A change within `min_interval` of the last post is held back and reported
+/// by the next check after the interval if the status has not settled back.
+#[derive(Debug)]
+pub struct TransitionTracker {
+    reported_status: Option<String>,
+    last_sent: Option<DateTime<Utc>>,
+    min_interval: Duration,
+}
+
+impl TransitionTracker {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            reported_status: None,
+            last_sent: None,
+            min_interval,
+        }
+    }
+
+    pub fn observe(&mut self, status: &AwsHealthStatus, now: DateTime<Utc>) -> Option<HealthTransition> {
+        let Some(previous_status) = self.reported_status.clone() else {
+            self.reported_status = Some(status.overall_status.clone());
+            return None;
+        };
+
+        if previous_status == status.overall_status {
+            return None;
+        }
+
+        if let Some(last_sent) = self.last_sent {
+            if now - last_sent < self.min_interval {
+                tracing::debug!("Holding back health transition to {} (debounced)", status.overall_status);
+                return None;
+            }
+        }
+
+        self.reported_status = Some(status.overall_status.clone());
+        self.last_sent = Some(now);
+
+        let failing_services = failing_services(status);
+        let mut text = format!("AWS health changed from {} to {}", previous_status, status.overall_status);
+        if !failing_services.is_empty() {
+            text.push_str(&format!(". Failing: {}", failing_services.join(", ")));
+        }
+
+        Some(HealthTransition {
+            previous_status,
+            status: status.overall_status.clone(),
+            failing_services,
+            text,
+            timestamp: now,
+        })
+    }
+}
+
+/// Hand the transition for a completed check, if any, to `post`
+pub async fn report_transition<F, Fut>(tracker: &mut TransitionTracker, status: &AwsHealthStatus, now: DateTime<Utc>, post: F) -> AwsResult<bool>
+where
+    F: FnOnce(HealthTransition) -> Fut,
+    Fut: std::future::Future<Output = AwsResult<()>>,
+{
+    match tracker.observe(status, now) {
+        Some(transition) => {
+            post(transition).await?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[derive(Clone)]
+pub struct HealthWebhook {
+    url: String,
+    http: reqwest::Client,
+    tracker: Arc<tokio::sync::Mutex<TransitionTracker>>,
+}
+
+impl HealthWebhook {
+    pub fn new(url: String, min_interval: Duration) -> Self {
+        Self {
+            url,
+            http: reqwest::Client::new(),
+            tracker: Arc::new(tokio::sync::Mutex::new(TransitionTracker::new(min_interval))),
+        }
+    }
+
+    /// Post the status if it is a transition. Failures are logged, never
+    /// surfaced to the health check.
+    pub async fn notify(&self, status: &AwsHealthStatus) {
+        let mut tracker = self.tracker.lock().await;
+        let result = report_transition(&mut tracker, status, Utc::now(), |transition| async move {
+            tracing::info!("{}", transition.text);
+            self.http
+                .post(&self.url)
+                .json(&transition)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| AwsError::NetworkError(format!("Health webhook failed: {}", e)))?;
+            Ok(())
+        })
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("{}", e);
+        }
+    }
+}
+
+/// Emails transitions to the recipients in the SMTP settings stored in the database
+#[derive(Clone)]
+pub struct HealthEmailAlerts {
+    pool: DbPool,
+    tracker: Arc<tokio::sync::Mutex<TransitionTracker>>,
+}
+
+impl HealthEmailAlerts {
+    pub fn new(pool: DbPool, min_interval: Duration) -> Self {
+        Self {
+            pool,
+            tracker: Arc::new(tokio::sync::Mutex::new(TransitionTracker::new(min_interval))),
+        }
+    }
+
+    /// Email the status if it is a transition; `notify_alert` skips it when
+    /// SMTP isn't configured and only logs failures
+    pub async fn notify(&self, status: &AwsHealthStatus) {
+        let mut tracker = self.tracker.lock().await;
+        let _ = report_transition(&mut tracker, status, Utc::now(), |transition| async move {
+            let subject = format!("Pocket Architect: AWS health is {}", transition.status);
+            crate::notifier::notify_alert(&self.pool, &subject, &transition.text).await;
+            Ok(())
+        })
+        .await;
+    }
+}
+
+// ============================================================================
+// PROBES
+// ============================================================================
+// Cheap read-only calls shared by the health checks and the permission preflight
+
+/// Describe a handful of instances (limited to avoid costs)
+pub async fn probe_describe_instances(client: &AwsClient) -> Result<DescribeInstancesOutput, SdkError<DescribeInstancesError>> {
+    client.ec2_client.describe_instances().max_results(5).send().await
+}
+
+pub async fn probe_list_buckets(client: &AwsClient) -> Result<ListBucketsOutput, SdkError<ListBucketsError>> {
+    client.s3_client.list_buckets().send().await
+}
+
+/// Account summary (safe, read-only operation)
+pub async fn probe_get_account_summary(client: &AwsClient) -> Result<GetAccountSummaryOutput, SdkError<GetAccountSummaryError>> {
+    client.iam_client.get_account_summary().send().await
+}
+
+pub async fn probe_list_users(client: &AwsClient) -> Result<ListUsersOutput, SdkError<ListUsersError>> {
+    client.iam_client.list_users().max_items(1).send().await
+}
+
+/// Yesterday's unblended cost (Cost Explorer bills $0.01 per request)
+pub async fn probe_get_cost_and_usage(client: &AwsClient) -> Result<GetCostAndUsageOutput, SdkError<GetCostAndUsageError>> {
+    let today = Utc::now().date_naive();
+    let period = DateInterval::builder()
+        .start((today - Duration::days(1)).format("%Y-%m-%d").to_string())
+        .end(today.format("%Y-%m-%d").to_string())
+        .build()
+        .expect("start and end are set");
+
+    client.ce_client
+        .get_cost_and_usage()
+        .time_period(period)
+        .granularity(Granularity::Daily)
+        .metrics("UnblendedCost")
+        .send()
+        .await
+}
+
+pub async fn probe_list_functions(client: &AwsClient) -> Result<ListFunctionsOutput, SdkError<ListFunctionsError>> {
+    client.lambda_client.list_functions().max_items(1).send().await
+}
+
 /// Integration point for main health system (to be implemented)
 /// For now, provides AWS health status directly
 pub struct HealthIntegration {
@@ -341,7 +735,10 @@ impl HealthIntegration {
 
         report.push_str("\nService Details:\n");
         for service in &aws_status.services {
-            report.push_str(&format!("- {}: {}\n", service.service, service.status));
+            match &service.region {
+                Some(region) => report.push_str(&format!("- {} ({}): {}\n", service.service, region, service.status)),
+                None => report.push_str(&format!("- {}: {}\n", service.service, service.status)),
+            }
             for detail in &service.details {
                 report.push_str(&format!("  {}\n", detail));
             }
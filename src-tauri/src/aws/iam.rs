@@ -4,8 +4,10 @@
 // IAM user and role management with read-only operations for safety
 // ============================================================================
 
-use crate::aws::{AwsClient, AwsIamUser, AwsAccessKey, AwsPolicy, AwsResult, AwsError};
-use aws_sdk_iam::types::{User as AwsSdkUser, AccessKeyMetadata};
+use crate::aws::{AwsClient, AwsIamUser, AwsIamRole, AwsAccessKey, AwsPolicy, NewAccessKey, AwsResult, AwsError};
+use crate::aws::diagnostics;
+use crate::aws::retry::{single_attempt_iam, with_retry, with_timeout};
+use aws_sdk_iam::types::{User as AwsSdkUser, AccessKeyMetadata, AttachedPolicy, Role, StatusType};
 use chrono::Utc;
 
 pub struct IamService {
@@ -23,14 +25,17 @@ impl IamService {
 
         let iam_client = &self.client.iam_client;
 
-        let response = iam_client
-            .list_users()
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to list IAM users: {:?}", e);
-                AwsError::from(aws_sdk_iam::Error::from(e))
-            })?;
+        let listing = with_retry(&self.client.config.retries, "ListUsers", || async move {
+            single_attempt_iam(iam_client)
+                .list_users()
+                .send()
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to list IAM users: {:?}", e);
+                    diagnostics::record("iam", "ListUsers", AwsError::from(aws_sdk_iam::Error::from(e)))
+                })
+        });
+        let response = with_timeout(self.client.config.timeouts.iam_operations(), "ListUsers", listing).await?;
 
         let mut users = Vec::new();
 
@@ -56,10 +61,11 @@ impl IamService {
             .map(|dt| dt.to_string());
 
         // Get access keys for this user
-        let access_keys = self.get_user_access_keys(&user_name).await.unwrap_or_default();
+        let access_keys = self.list_access_keys(&user_name).await.unwrap_or_default();
 
-        // Get attached policies for this user
+        // Get attached and inline policies for this user
         let attached_policies = self.get_user_policies(&user_name).await.unwrap_or_default();
+        let inline_policies = self.get_user_inline_policies(&user_name).await.unwrap_or_default();
 
         // Get groups for this user
         let groups = self.get_user_groups(&user_name).await.unwrap_or_default();
@@ -72,12 +78,14 @@ impl IamService {
             password_last_used,
             access_keys,
             attached_policies,
+            inline_policies,
             groups,
         })
     }
 
-    /// Get access keys for a specific user
-    async fn get_user_access_keys(&self, user_name: &str) -> AwsResult<Vec<AwsAccessKey>> {
+    /// A user's access keys with their status and creation date. Secrets are
+    /// never returned after creation.
+    pub async fn list_access_keys(&self, user_name: &str) -> AwsResult<Vec<AwsAccessKey>> {
         let iam_client = &self.client.iam_client;
 
         let response = iam_client
@@ -87,81 +95,129 @@ impl IamService {
             .await
             .map_err(|e| {
                 tracing::warn!("Failed to get access keys for user {}: {:?}", user_name, e);
-                AwsError::from(aws_sdk_iam::Error::from(e))
+                diagnostics::record("iam", "ListAccessKeys", AwsError::from(aws_sdk_iam::Error::from(e)))
             })?;
 
-        let mut access_keys = Vec::new();
+        Ok(response.access_key_metadata().iter().map(map_access_key_metadata).collect())
+    }
 
-        for key_metadata in response.access_key_metadata() {
-            if let Some(access_key) = self.map_access_key_metadata(key_metadata) {
-                access_keys.push(access_key);
-            }
-        }
+    /// Create an access key for a user. The secret is only ever available in
+    /// this response.
+    pub async fn create_access_key(&self, user_name: &str) -> AwsResult<NewAccessKey> {
+        tracing::info!("Creating access key for IAM user: {}", user_name);
 
-        Ok(access_keys)
-    }
+        let response = self.client.iam_client
+            .create_access_key()
+            .user_name(user_name)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to create access key for user {}: {:?}", user_name, e);
+                diagnostics::record("iam", "CreateAccessKey", AwsError::from(aws_sdk_iam::Error::from(e)))
+            })?;
 
-    /// Map access key metadata to our custom type
-    fn map_access_key_metadata(&self, metadata: &AccessKeyMetadata) -> Option<AwsAccessKey> {
-        let access_key_id = metadata.access_key_id().unwrap_or("unknown").to_string();
-        let status = match metadata.status() {
-            Some(status) => format!("{:?}", status).to_lowercase(),
-            None => "unknown".to_string(),
-        };
+        let key = response
+            .access_key()
+            .ok_or_else(|| AwsError::OperationError(format!("No access key returned for user {}", user_name)))?;
+
+        Ok(NewAccessKey {
+            access_key: AwsAccessKey {
+                access_key_id: key.access_key_id().to_string(),
+                status: key.status().as_str().to_lowercase(),
+                create_date: key.create_date().map(|d| d.to_string()).unwrap_or_else(|| Utc::now().to_rfc3339()),
+                last_used: None,
+            },
+            secret_access_key: key.secret_access_key().to_string(),
+        })
+    }
 
-        let create_date = metadata.create_date().unwrap().to_string();
+    /// Delete one of a user's access keys, refusing to remove the last active
+    /// key since that would lock the user out of programmatic access
+    pub async fn delete_access_key(&self, user_name: &str, access_key_id: &str) -> AwsResult<()> {
+        let keys = self.list_access_keys(user_name).await?;
+        check_access_key_deletion(&keys, access_key_id)?;
 
-        // Last used information is not available in ListAccessKeys
-        // Would need to call GetAccessKeyLastUsed for each key (expensive)
-        let last_used = None;
+        tracing::info!("Deleting access key {} of IAM user {}", access_key_id, user_name);
+        self.client.iam_client
+            .delete_access_key()
+            .user_name(user_name)
+            .access_key_id(access_key_id)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to delete access key {} of user {}: {:?}", access_key_id, user_name, e);
+                diagnostics::record("iam", "DeleteAccessKey", AwsError::from(aws_sdk_iam::Error::from(e)))
+            })?;
 
-        Some(AwsAccessKey {
-            access_key_id,
-            status,
-            create_date,
-            last_used,
-        })
+        Ok(())
     }
 
-    /// Get attached policies for a specific user
+    /// Get attached managed policies for a specific user, across all pages
     async fn get_user_policies(&self, user_name: &str) -> AwsResult<Vec<AwsPolicy>> {
-        let iam_client = &self.client.iam_client;
-
-        let response = iam_client
+        let pages = self.client.iam_client
             .list_attached_user_policies()
             .user_name(user_name)
+            .into_paginator()
             .send()
+            .collect::<Result<Vec<_>, _>>()
             .await
             .map_err(|e| {
                 tracing::warn!("Failed to get attached policies for user {}: {:?}", user_name, e);
-                AwsError::from(aws_sdk_iam::Error::from(e))
+                diagnostics::record("iam", "ListAttachedUserPolicies", AwsError::from(aws_sdk_iam::Error::from(e)))
             })?;
 
-        let mut policies = Vec::new();
+        Ok(pages.iter().flat_map(|page| page.attached_policies()).filter_map(map_attached_policy).collect())
+    }
 
-        for attached_policy in response.attached_policies() {
-            if let Some(policy) = self.map_attached_policy(attached_policy) {
-                policies.push(policy);
-            }
-        }
+    /// Names of a user's inline policies, across all pages
+    async fn get_user_inline_policies(&self, user_name: &str) -> AwsResult<Vec<String>> {
+        let pages = self.client.iam_client
+            .list_user_policies()
+            .user_name(user_name)
+            .into_paginator()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .map_err(|e| {
+                tracing::warn!("Failed to get inline policies for user {}: {:?}", user_name, e);
+                diagnostics::record("iam", "ListUserPolicies", AwsError::from(aws_sdk_iam::Error::from(e)))
+            })?;
 
-        Ok(policies)
+        Ok(pages.iter().flat_map(|page| page.policy_names()).cloned().collect())
     }
 
-    /// Map attached policy to our custom type
-    fn map_attached_policy(&self, attached_policy: &aws_sdk_iam::types::AttachedPolicy) -> Option<AwsPolicy> {
-        let policy_name = attached_policy.policy_name()?.to_string();
-        let policy_arn = attached_policy.policy_arn()?.to_string();
+    /// Get attached managed policies for a specific role, across all pages
+    async fn get_role_policies(&self, role_name: &str) -> AwsResult<Vec<AwsPolicy>> {
+        let pages = self.client.iam_client
+            .list_attached_role_policies()
+            .role_name(role_name)
+            .into_paginator()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .map_err(|e| {
+                tracing::warn!("Failed to get attached policies for role {}: {:?}", role_name, e);
+                diagnostics::record("iam", "ListAttachedRolePolicies", AwsError::from(aws_sdk_iam::Error::from(e)))
+            })?;
+
+        Ok(pages.iter().flat_map(|page| page.attached_policies()).filter_map(map_attached_policy).collect())
+    }
 
-        // For attached policies, we don't get the type directly
-        // Most attached policies are managed policies
-        let policy_type = "Managed".to_string();
+    /// Names of a role's inline policies, across all pages
+    async fn get_role_inline_policies(&self, role_name: &str) -> AwsResult<Vec<String>> {
+        let pages = self.client.iam_client
+            .list_role_policies()
+            .role_name(role_name)
+            .into_paginator()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .map_err(|e| {
+                tracing::warn!("Failed to get inline policies for role {}: {:?}", role_name, e);
+                diagnostics::record("iam", "ListRolePolicies", AwsError::from(aws_sdk_iam::Error::from(e)))
+            })?;
 
-        Some(AwsPolicy {
-            policy_name,
-            policy_arn,
-            policy_type,
-        })
+        Ok(pages.iter().flat_map(|page| page.policy_names()).cloned().collect())
     }
 
     /// Get groups for a specific user
@@ -175,7 +231,7 @@ impl IamService {
             .await
             .map_err(|e| {
                 tracing::warn!("Failed to get groups for user {}: {:?}", user_name, e);
-                AwsError::from(aws_sdk_iam::Error::from(e))
+                diagnostics::record("iam", "ListGroupsForUser", AwsError::from(aws_sdk_iam::Error::from(e)))
             })?;
 
         let groups = response.groups()
@@ -192,15 +248,18 @@ impl IamService {
 
         let iam_client = &self.client.iam_client;
 
-        let response = iam_client
-            .list_roles()
-            .max_items(50) // Limited for safety and performance
-            .send()
-            .await
-            .map_err(|e| {
-                tracing::error!("Failed to list IAM roles: {:?}", e);
-                AwsError::from(aws_sdk_iam::Error::from(e))
-            })?;
+        let listing = with_retry(&self.client.config.retries, "ListRoles", || async move {
+            single_attempt_iam(iam_client)
+                .list_roles()
+                .max_items(50) // Limited for safety and performance
+                .send()
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to list IAM roles: {:?}", e);
+                    diagnostics::record("iam", "ListRoles", AwsError::from(aws_sdk_iam::Error::from(e)))
+                })
+        });
+        let response = with_timeout(self.client.config.timeouts.iam_operations(), "ListRoles", listing).await?;
 
         let roles: Vec<String> = response.roles()
             .iter()
@@ -238,6 +297,25 @@ impl IamService {
         }
     }
 
+    /// Get a role with its attached and inline policies, or `None` if it doesn't exist
+    pub async fn get_role_details(&self, role_name: &str) -> AwsResult<Option<AwsIamRole>> {
+        tracing::debug!("Getting details for IAM role: {}", role_name);
+
+        let response = match self.client.iam_client.get_role().role_name(role_name).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::debug!("Role {} not found: {:?}", role_name, e);
+                return Ok(None);
+            }
+        };
+        let Some(role) = response.role() else { return Ok(None) };
+
+        let attached_policies = self.get_role_policies(role_name).await?;
+        let inline_policies = self.get_role_inline_policies(role_name).await?;
+
+        Ok(Some(map_role_details(role, attached_policies, inline_policies)))
+    }
+
     /// Check if current user has specific permissions (basic check)
     pub async fn check_permissions(&self) -> AwsResult<Vec<String>> {
         tracing::info!("Checking IAM permissions");
@@ -296,7 +374,7 @@ impl IamService {
             .await
             .map_err(|e| {
                 tracing::error!("Failed to get account summary: {:?}", e);
-                AwsError::from(aws_sdk_iam::Error::from(e))
+                diagnostics::record("iam", "GetAccountSummary", AwsError::from(aws_sdk_iam::Error::from(e)))
             })?;
 
         let mut summary = std::collections::HashMap::new();
@@ -310,4 +388,81 @@ impl IamService {
         tracing::info!("Successfully retrieved IAM account summary with {} metrics", summary.len());
         Ok(summary)
     }
-}
\ No newline at end of file
+}
+
+/// Map access key metadata to our custom type
+pub fn map_access_key_metadata(metadata: &AccessKeyMetadata) -> AwsAccessKey {
+    let access_key_id = metadata.access_key_id().unwrap_or("unknown").to_string();
+    let status = match metadata.status() {
+        Some(status) => status.as_str().to_lowercase(),
+        None => "unknown".to_string(),
+    };
+
+    let create_date = metadata
+        .create_date()
+        .map(|d| d.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    // Last used information is not available in ListAccessKeys
+    // Would need to call GetAccessKeyLastUsed for each key (expensive)
+    let last_used = None;
+
+    AwsAccessKey {
+        access_key_id,
+        status,
+        create_date,
+        last_used,
+    }
+}
+
+/// Check that `access_key_id` belongs to the user and isn't their only active key
+pub fn check_access_key_deletion(keys: &[AwsAccessKey], access_key_id: &str) -> AwsResult<()> {
+    let active = StatusType::Active.as_str().to_lowercase();
+
+    let key = keys
+        .iter()
+        .find(|key| key.access_key_id == access_key_id)
+        .ok_or_else(|| AwsError::OperationError(format!("Access key {} not found for this user", access_key_id)))?;
+
+    let other_active = keys
+        .iter()
+        .any(|other| other.access_key_id != access_key_id && other.status == active);
+
+    if key.status == active && !other_active {
+        return Err(AwsError::OperationError(format!(
+            "Refusing to delete {}: it is the user's last active access key. Create a new key first.",
+            access_key_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Map attached policy to our custom type
+pub fn map_attached_policy(attached_policy: &AttachedPolicy) -> Option<AwsPolicy> {
+    let policy_name = attached_policy.policy_name()?.to_string();
+    let policy_arn = attached_policy.policy_arn()?.to_string();
+
+    // Attached policies are always managed (AWS- or customer-managed)
+    let policy_type = "Managed".to_string();
+
+    Some(AwsPolicy {
+        policy_name,
+        policy_arn,
+        policy_type,
+    })
+}
+
+/// Combine a role with its policy listings
+pub fn map_role_details(role: &Role, attached_policies: Vec<AwsPolicy>, inline_policies: Vec<String>) -> AwsIamRole {
+    AwsIamRole {
+        role_name: role.role_name().to_string(),
+        role_id: role.role_id().to_string(),
+        arn: role.arn().to_string(),
+        create_date: role.create_date().to_string(),
+        description: role.description().map(|d| d.to_string()),
+        attached_policies,
+        inline_policies,
+    }
+}
+
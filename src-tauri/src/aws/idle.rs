@@ -0,0 +1,114 @@
+// ============================================================================
+// IDLE INSTANCES
+// ============================================================================
+// Running instances whose average CPU stayed under a threshold, as
+// candidates for stopping, with what stopping them would save
+// ============================================================================
+
+use crate::aws::adapters::estimate_monthly_cost;
+use crate::aws::cloudwatch::fetch_average_cpu;
+use crate::aws::{AwsClient, AwsError, AwsInstance, AwsResult, InstanceFilters};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Tag key that keeps an instance out of idle stopping
+pub const ALWAYS_ON_TAG_KEY: &str = "always-on";
+
+pub const DEFAULT_CPU_THRESHOLD: f64 = 5.0;
+pub const DEFAULT_WINDOW_HOURS: i64 = 24;
+
+/// Longest lookback; beyond two weeks CloudWatch only keeps hourly data
+pub const MAX_WINDOW_HOURS: i64 = 14 * 24;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdleInstance {
+    pub instance_id: String,
+    pub name: Option<String>,
+    pub instance_type: String,
+    pub average_cpu: f64,
+    /// Estimated on-demand compute cost saved per month by stopping it
+    pub monthly_savings: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IdleReport {
+    /// Idle instances, most expensive first
+    pub idle: Vec<IdleInstance>,
+    /// Idle instances left alone because of their always-on tag
+    pub always_on: Vec<String>,
+    /// Running instances with no CPU data for the window
+    pub no_data: Vec<String>,
+    pub estimated_monthly_savings: f64,
+}
+
+/// Check a threshold and window before querying anything
+pub fn validate_idle_criteria(cpu_threshold: f64, window_hours: i64) -> AwsResult<()> {
+    if !(cpu_threshold > 0.0 && cpu_threshold <= 100.0) {
+        return Err(AwsError::ConfigError(format!(
+            "CPU threshold must be above 0 and at most 100 percent, got {}",
+            cpu_threshold
+        )));
+    }
+    if !(1..=MAX_WINDOW_HOURS).contains(&window_hours) {
+        return Err(AwsError::ConfigError(format!(
+            "Window must be between 1 and {} hours, got {}",
+            MAX_WINDOW_HOURS, window_hours
+        )));
+    }
+    Ok(())
+}
+
+/// Whether an instance is tagged to stay on; any value but "false" counts
+pub fn is_always_on(instance: &AwsInstance) -> bool {
+    instance
+        .tags
+        .iter()
+        .any(|(key, value)| key.eq_ignore_ascii_case(ALWAYS_ON_TAG_KEY) && !value.eq_ignore_ascii_case("false"))
+}
+
+/// Running instances whose average CPU is below `cpu_threshold`
+pub fn select_idle_instances(instances: &[AwsInstance], average_cpu: &HashMap<String, f64>, cpu_threshold: f64) -> IdleReport {
+    let mut report = IdleReport::default();
+
+    for instance in instances.iter().filter(|instance| instance.state == "running") {
+        let Some(&cpu) = average_cpu.get(&instance.instance_id) else {
+            report.no_data.push(instance.instance_id.clone());
+            continue;
+        };
+        if cpu >= cpu_threshold {
+            continue;
+        }
+        if is_always_on(instance) {
+            report.always_on.push(instance.instance_id.clone());
+            continue;
+        }
+
+        report.idle.push(IdleInstance {
+            instance_id: instance.instance_id.clone(),
+            name: instance.tags.get("Name").cloned(),
+            instance_type: instance.instance_type.clone(),
+            average_cpu: cpu,
+            monthly_savings: estimate_monthly_cost(instance),
+        });
+    }
+
+    report.idle.sort_by(|a, b| b.monthly_savings.total_cmp(&a.monthly_savings));
+    report.estimated_monthly_savings = report.idle.iter().map(|i| i.monthly_savings).sum();
+    report
+}
+
+/// Find the idle running instances in the client's region
+pub async fn find_idle_instances(client: &AwsClient, cpu_threshold: f64, window_hours: i64) -> AwsResult<IdleReport> {
+    validate_idle_criteria(cpu_threshold, window_hours)?;
+
+    let filters = InstanceFilters {
+        states: vec!["running".to_string()],
+        ..Default::default()
+    };
+    let instances = client.collect_matching_instances(&filters).await?;
+    let instance_ids: Vec<String> = instances.iter().map(|i| i.instance_id.clone()).collect();
+
+    tracing::info!("Checking {} running instances for average CPU under {}% over {}h", instance_ids.len(), cpu_threshold, window_hours);
+    let average_cpu = fetch_average_cpu(client, &instance_ids, window_hours).await?;
+    Ok(select_idle_instances(&instances, &average_cpu, cpu_threshold))
+}
@@ -0,0 +1,301 @@
+// ============================================================================
+// AWS CLIENT MANAGER
+// ============================================================================
+// Lazily builds and caches one AwsClient per (account, region) so commands
+// don't rebuild SDK clients and re-validate credentials on every call
+// ============================================================================
+
+use crate::aws::awshealth::{ServiceEventsReport, SERVICE_EVENTS_TTL_SECONDS};
+use crate::aws::cache::CacheEntry;
+use crate::aws::cost::{SharedCostTracker, COST_EVENT_DEBOUNCE};
+use crate::aws::health::{AwsHealthMonitor, HealthEmailAlerts, HealthWebhook};
+use crate::aws::{AwsClient, AwsConfig, AwsError, AwsEventEmitter, AwsResult, EventStore};
+use crate::database::{self, DbPool};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// How old an account's health status may get before reading it runs a new check
+pub const HEALTH_CHECK_INTERVAL_SECONDS: i64 = 300;
+
+/// Least time between two alerts about an account's health transitions
+pub const HEALTH_ALERT_INTERVAL: chrono::Duration = chrono::Duration::minutes(5);
+
+/// Lets background tasks for an account pause while it is disabled
+#[derive(Clone)]
+pub struct AccountGate {
+    db: Arc<tokio::sync::Mutex<DbPool>>,
+    account_id: i64,
+}
+
+impl AccountGate {
+    pub fn new(db: Arc<tokio::sync::Mutex<DbPool>>, account_id: i64) -> Self {
+        Self { db, account_id }
+    }
+
+    /// Whether the account is active. Database errors count as active so a
+    /// transient failure doesn't silently stop background work.
+    pub async fn is_active(&self) -> bool {
+        match database::is_account_active(&*self.db.lock().await, self.account_id).await {
+            Ok(active) => active,
+            Err(e) => {
+                tracing::warn!("Failed to check status of account {}: {}", self.account_id, e);
+                true
+            }
+        }
+    }
+}
+
+struct CachedClient {
+    client: AwsClient,
+    credentials_fingerprint: u64,
+}
+
+#[derive(Default)]
+pub struct AwsClientManager {
+    clients: RwLock<HashMap<(i64, String), CachedClient>>,
+    cost_trackers: RwLock<HashMap<i64, SharedCostTracker>>,
+    health_monitors: RwLock<HashMap<i64, AwsHealthMonitor>>,
+    service_events: RwLock<HashMap<i64, CacheEntry<ServiceEventsReport>>>,
+    event_store: Arc<EventStore>,
+}
+
+/// Refuse to build an AWS client from another platform's account, which
+/// would otherwise fail later with a confusing credentials error
+pub fn ensure_aws_account(account: &database::Account) -> AwsResult<()> {
+    if account.platform.eq_ignore_ascii_case("aws") {
+        return Ok(());
+    }
+    Err(AwsError::WrongPlatform {
+        account_id: account.id,
+        platform: account.platform.clone(),
+    })
+}
+
+impl AwsClientManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the client for an account in its configured region, building it on first use
+    pub async fn get_client(&self, pool: &DbPool, account_id: i64) -> AwsResult<AwsClient> {
+        let account = database::get_account(pool, account_id)
+            .await
+            .map_err(|e| AwsError::ConfigError(format!("Failed to get account: {}", e)))?
+            .ok_or_else(|| AwsError::ConfigError("Account not found".to_string()))?;
+
+        let region = account.region.clone().unwrap_or_else(|| "us-east-1".to_string());
+        self.get_client_in_region(pool, account_id, &region).await
+    }
+
+    /// Get the client for an account in a specific region, building it on first use
+    pub async fn get_client_in_region(&self, pool: &DbPool, account_id: i64, region: &str) -> AwsResult<AwsClient> {
+        let account = database::get_account(pool, account_id)
+            .await
+            .map_err(|e| AwsError::ConfigError(format!("Failed to get account: {}", e)))?
+            .ok_or_else(|| AwsError::ConfigError("Account not found".to_string()))?;
+        ensure_aws_account(&account)?;
+
+        let credentials = database::get_account_credentials(pool, account_id)
+            .await
+            .map_err(|e| AwsError::AuthError(format!("Failed to get credentials: {}", e)))?;
+
+        let access_key = credentials.access_key.unwrap_or_default();
+        let secret_key = credentials.secret_key.unwrap_or_default();
+
+        if access_key.is_empty() || secret_key.is_empty() {
+            return Err(AwsError::AuthError("Missing AWS credentials".to_string()));
+        }
+
+        let fingerprint = credentials_fingerprint(&access_key, &secret_key, account.role_arn.as_deref(), account.external_id.as_deref());
+        let mut config = AwsConfig::from_credentials(access_key, secret_key, region.to_string())
+            .with_account_regions(region, account.fallback_region.as_deref());
+        if let Some(role_arn) = account.role_arn {
+            config = config.with_role(role_arn, account.external_id);
+        }
+
+        self.get_or_build(account_id, region, fingerprint, || async move {
+            let client = AwsClient::new(config).await?;
+            if let Some(identity) = &client.identity {
+                record_identity(pool, account_id, identity).await;
+            }
+            Ok(client)
+        })
+        .await
+    }
+
+    /// Return the cached client for (account, region) if its credentials are unchanged,
+    /// otherwise build a new one with `build` and cache it
+    pub async fn get_or_build<F, Fut>(&self, account_id: i64, region: &str, credentials_fingerprint: u64, build: F) -> AwsResult<AwsClient>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = AwsResult<AwsClient>>,
+    {
+        let key = (account_id, region.to_string());
+
+        {
+            let clients = self.clients.read().await;
+            if let Some(cached) = clients.get(&key) {
+                if cached.credentials_fingerprint == credentials_fingerprint {
+                    tracing::debug!("Reusing cached AWS client for account {} in {}", account_id, region);
+                    return Ok(cached.client.clone());
+                }
+                tracing::info!("Credentials rotated for account {}, rebuilding AWS client", account_id);
+            }
+        }
+
+        let client = build().await?;
+
+        let mut clients = self.clients.write().await;
+        clients.insert(key, CachedClient {
+            client: client.clone(),
+            credentials_fingerprint,
+        });
+        tracing::debug!("Cached AWS client for account {} in {}", account_id, region);
+
+        Ok(client)
+    }
+
+    /// Drop every cached client for an account (after an update or deletion)
+    pub async fn invalidate_account(&self, account_id: i64) {
+        let mut clients = self.clients.write().await;
+        clients.retain(|(id, _), _| *id != account_id);
+        self.cost_trackers.write().await.remove(&account_id);
+        self.health_monitors.write().await.remove(&account_id);
+        self.service_events.write().await.remove(&account_id);
+        tracing::debug!("Invalidated cached AWS clients for account {}", account_id);
+    }
+
+    /// Drop every cached client, returning how many were removed
+    pub async fn clear(&self) -> usize {
+        let mut clients = self.clients.write().await;
+        let count = clients.len();
+        clients.clear();
+        tracing::info!("Cleared {} cached AWS clients", count);
+        count
+    }
+
+    /// Get the account's API cost tracker, creating it on first use. Threshold
+    /// crossings are emitted to the frontend as debounced `cost_alert` events
+    /// and emailed when SMTP is configured.
+    pub async fn cost_tracker(&self, pool: &DbPool, account_id: i64, app_handle: tauri::AppHandle) -> AwsResult<SharedCostTracker> {
+        if let Some(tracker) = self.cost_trackers.read().await.get(&account_id) {
+            return Ok(tracker.clone());
+        }
+
+        let client = self.get_client(pool, account_id).await?;
+        let thresholds = database::get_cost_thresholds(pool)
+            .await
+            .map_err(|e| AwsError::ConfigError(format!("Failed to load cost thresholds: {}", e)))?;
+        let emitter = Arc::new(AwsEventEmitter::new(app_handle, self.event_store.clone()));
+
+        let mut trackers = self.cost_trackers.write().await;
+        let tracker = trackers
+            .entry(account_id)
+            .or_insert_with(|| {
+                let warn_percent = thresholds.warn_usd / thresholds.limit_usd * 100.0;
+                SharedCostTracker::new(client, thresholds.limit_usd, warn_percent)
+                    .with_event_emitter(emitter, COST_EVENT_DEBOUNCE)
+                    .with_email_alerts(pool.clone())
+            })
+            .clone();
+        Ok(tracker)
+    }
+
+    /// Get the account's health monitor, creating it on first use. Status
+    /// transitions are posted to the health webhook and emailed when those
+    /// are configured.
+    pub async fn health_monitor(&self, pool: &DbPool, account_id: i64, app_handle: tauri::AppHandle) -> AwsResult<AwsHealthMonitor> {
+        if let Some(monitor) = self.health_monitors.read().await.get(&account_id) {
+            return Ok(monitor.clone());
+        }
+
+        let client = self.get_client(pool, account_id).await?;
+        let webhook_url = database::get_health_webhook_url(pool)
+            .await
+            .map_err(|e| AwsError::ConfigError(format!("Failed to load health webhook URL: {}", e)))?;
+        let emitter = Arc::new(AwsEventEmitter::new(app_handle, self.event_store.clone()));
+
+        let mut monitors = self.health_monitors.write().await;
+        let monitor = monitors
+            .entry(account_id)
+            .or_insert_with(|| {
+                let monitor = AwsHealthMonitor::new(client, HEALTH_CHECK_INTERVAL_SECONDS, emitter)
+                    .with_email_alerts(HealthEmailAlerts::new(pool.clone(), HEALTH_ALERT_INTERVAL));
+                match webhook_url {
+                    Some(url) => monitor.with_webhook(HealthWebhook::new(url, HEALTH_ALERT_INTERVAL)),
+                    None => monitor,
+                }
+            })
+            .clone();
+        Ok(monitor)
+    }
+
+    /// Drop every health monitor so the next read picks up a changed webhook
+    pub async fn reset_health_monitors(&self) {
+        self.health_monitors.write().await.clear();
+    }
+
+    /// Apply new cost thresholds to every live tracker
+    pub async fn apply_cost_thresholds(&self, thresholds: database::CostThresholds) -> AwsResult<()> {
+        for tracker in self.cost_trackers.read().await.values() {
+            tracker.set_limits(thresholds.warn_usd, thresholds.limit_usd).await?;
+        }
+        Ok(())
+    }
+
+    /// AWS Health events fetched for an account within the last few minutes
+    pub async fn cached_service_events(&self, account_id: i64) -> Option<ServiceEventsReport> {
+        self.service_events
+            .read()
+            .await
+            .get(&account_id)
+            .filter(|entry| !entry.is_expired())
+            .map(|entry| entry.data.clone())
+    }
+
+    pub async fn put_service_events(&self, account_id: i64, report: ServiceEventsReport) {
+        self.service_events
+            .write()
+            .await
+            .insert(account_id, CacheEntry::new(report, SERVICE_EVENTS_TTL_SECONDS));
+    }
+
+    /// Events emitted for every account, kept for replay after a frontend reload
+    pub fn event_store(&self) -> Arc<EventStore> {
+        self.event_store.clone()
+    }
+
+    pub async fn len(&self) -> usize {
+        self.clients.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.clients.read().await.is_empty()
+    }
+}
+
+/// Store the identity a freshly built client resolved to, warning when the
+/// credentials now point at a different AWS account than before
+async fn record_identity(pool: &DbPool, account_id: i64, identity: &crate::aws::CallerIdentity) {
+    match database::record_account_identity(pool, account_id, &identity.account_id, &identity.arn).await {
+        Ok(database::AccountIdentityCheck::Mismatch { stored_account_id }) => tracing::warn!(
+            "Credentials for account {} now belong to AWS account {} (previously {})",
+            account_id, identity.account_id, stored_account_id
+        ),
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to record AWS identity for account {}: {}", account_id, e),
+    }
+}
+
+pub(crate) fn credentials_fingerprint(access_key: &str, secret_key: &str, role_arn: Option<&str>, external_id: Option<&str>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    access_key.hash(&mut hasher);
+    secret_key.hash(&mut hasher);
+    role_arn.hash(&mut hasher);
+    external_id.hash(&mut hasher);
+    hasher.finish()
+}
@@ -1,6 +1,8 @@
 pub mod client;
 pub mod config;
 pub mod ec2;
+pub mod ebs;
+pub mod ami;
 pub mod s3;
 pub mod iam;
 pub mod rds;
@@ -10,15 +12,31 @@ pub mod cost;
 pub mod types;
 pub mod errors;
 pub mod health;
+pub mod awshealth;
+pub mod cloudwatch;
+pub mod console;
+pub mod permissions;
+pub mod attribution;
+pub mod costtags;
+pub mod posture;
+pub mod idle;
+pub mod reservations;
+pub mod compute_optimizer;
 pub mod adapters;
 pub mod events;
+pub mod diagnostics;
+pub mod manager;
+pub mod regional;
+pub mod retry;
+pub mod global;
 
 pub use events::{AwsEventEmitter, EventStore, AwsEventPayload};
 
 #[cfg(test)]
 mod tests;
 
-pub use client::{AwsClient, test_connection};
+pub use client::{AwsClient, CallerIdentity, test_connection};
 pub use config::AwsConfig;
+pub use manager::AwsClientManager;
 pub use types::*;
 pub use errors::*;
\ No newline at end of file
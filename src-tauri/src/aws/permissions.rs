@@ -0,0 +1,220 @@
+// ============================================================================
+// AWS PERMISSION PREFLIGHT
+// ============================================================================
+// Runs one cheap read-only call per service so users can see which IAM
+// permissions their keys are missing before a sync fails halfway through, and
+// simulates the caller's policies before collection and cost operations
+// ============================================================================
+
+use crate::aws::health::{
+    probe_describe_instances, probe_get_account_summary, probe_get_cost_and_usage, probe_list_buckets,
+    probe_list_functions, probe_list_users,
+};
+use crate::aws::{AwsClient, AwsResult};
+use aws_sdk_ec2::error::{DisplayErrorContext, ProvideErrorMetadata};
+use aws_sdk_iam::types::{EvaluationResult, PolicyEvaluationDecisionType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Simulation takes several seconds, so decisions are reused for a while
+pub const PERMISSION_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Actions a sync needs
+pub const COLLECTION_ACTIONS: &[&str] = &["ec2:DescribeInstances", "s3:ListAllMyBuckets", "lambda:ListFunctions"];
+
+/// Actions cost reporting needs
+pub const COST_ACTIONS: &[&str] = &["ce:GetCostAndUsage"];
+
+/// Error codes AWS services use for a missing IAM permission
+const ACCESS_DENIED_CODES: &[&str] = &[
+    "AccessDenied",
+    "AccessDeniedException",
+    "UnauthorizedOperation",
+    "AuthorizationError",
+    "UnauthorizedAccess",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionStatus {
+    Allowed,
+    Denied,
+    /// The call failed for another reason (network, throttling, service not
+    /// enabled), so the permission could not be determined
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionCheck {
+    pub service: String,
+    pub action: String,
+    pub status: PermissionStatus,
+    pub error_code: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionReport {
+    pub checks: Vec<PermissionCheck>,
+    pub allowed: usize,
+    pub denied: usize,
+    pub unknown: usize,
+}
+
+impl PermissionReport {
+    pub fn new(checks: Vec<PermissionCheck>) -> Self {
+        let count = |status| checks.iter().filter(|c| c.status == status).count();
+        Self {
+            allowed: count(PermissionStatus::Allowed),
+            denied: count(PermissionStatus::Denied),
+            unknown: count(PermissionStatus::Unknown),
+            checks,
+        }
+    }
+
+    /// Actions the keys are known to lack
+    pub fn denied_actions(&self) -> Vec<&str> {
+        self.checks
+            .iter()
+            .filter(|c| c.status == PermissionStatus::Denied)
+            .map(|c| c.action.as_str())
+            .collect()
+    }
+}
+
+/// Whether an error code means the caller lacks the permission
+pub fn permission_status_for_error(code: Option<&str>) -> PermissionStatus {
+    match code {
+        Some(code) if ACCESS_DENIED_CODES.contains(&code) => PermissionStatus::Denied,
+        _ => PermissionStatus::Unknown,
+    }
+}
+
+/// Checklist entry for the outcome of one probe call
+pub fn permission_check<T, E>(service: &str, action: &str, result: Result<T, E>) -> PermissionCheck
+where
+    E: ProvideErrorMetadata + std::error::Error,
+{
+    let (status, error_code, message) = match result {
+        Ok(_) => (PermissionStatus::Allowed, None, None),
+        Err(e) => {
+            let message = e.message().map(str::to_string).unwrap_or_else(|| DisplayErrorContext(&e).to_string());
+            (permission_status_for_error(e.code()), e.code().map(str::to_string), Some(message))
+        }
+    };
+
+    PermissionCheck {
+        service: service.to_string(),
+        action: action.to_string(),
+        status,
+        error_code,
+        message,
+    }
+}
+
+/// Run every probe concurrently and collect the checklist
+pub async fn check_account_permissions(client: &AwsClient) -> PermissionReport {
+    let (instances, buckets, users, summary, costs, functions) = tokio::join!(
+        probe_describe_instances(client),
+        probe_list_buckets(client),
+        probe_list_users(client),
+        probe_get_account_summary(client),
+        probe_get_cost_and_usage(client),
+        probe_list_functions(client),
+    );
+
+    PermissionReport::new(vec![
+        permission_check("ec2", "ec2:DescribeInstances", instances),
+        permission_check("s3", "s3:ListAllMyBuckets", buckets),
+        permission_check("iam", "iam:ListUsers", users),
+        permission_check("iam", "iam:GetAccountSummary", summary),
+        permission_check("ce", "ce:GetCostAndUsage", costs),
+        permission_check("lambda", "lambda:ListFunctions", functions),
+    ])
+}
+
+/// Recently simulated decisions, keyed by action
+#[derive(Debug)]
+pub struct PermissionCache {
+    decisions: RwLock<HashMap<String, (bool, Instant)>>,
+    ttl: Duration,
+}
+
+impl Default for PermissionCache {
+    fn default() -> Self {
+        Self::new(PERMISSION_CACHE_TTL)
+    }
+}
+
+impl PermissionCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            decisions: RwLock::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    pub async fn clear(&self) {
+        self.decisions.write().await.clear();
+    }
+}
+
+/// Actions from `actions` the caller is not allowed to perform. Only actions
+/// without a fresh cached decision are passed to `simulate`; actions the
+/// simulator says nothing about are treated as allowed.
+pub async fn missing_permissions<F, Fut>(cache: &PermissionCache, actions: &[&str], simulate: F) -> AwsResult<Vec<String>>
+where
+    F: FnOnce(Vec<String>) -> Fut,
+    Fut: Future<Output = AwsResult<Vec<EvaluationResult>>>,
+{
+    let mut allowed: HashMap<String, bool> = HashMap::new();
+    let mut uncached = Vec::new();
+    {
+        let decisions = cache.decisions.read().await;
+        for action in actions {
+            match decisions.get(*action) {
+                Some((decision, checked_at)) if checked_at.elapsed() < cache.ttl => {
+                    allowed.insert(action.to_string(), *decision);
+                }
+                _ => uncached.push(action.to_string()),
+            }
+        }
+    }
+
+    if !uncached.is_empty() {
+        let results = simulate(uncached).await?;
+        let now = Instant::now();
+        let mut decisions = cache.decisions.write().await;
+        for result in results {
+            let decision = result.eval_decision() == &PolicyEvaluationDecisionType::Allowed;
+            decisions.insert(result.eval_action_name().to_string(), (decision, now));
+            allowed.insert(result.eval_action_name().to_string(), decision);
+        }
+    }
+
+    Ok(actions
+        .iter()
+        .filter(|action| allowed.get(**action) == Some(&false))
+        .map(|action| action.to_string())
+        .collect())
+}
+
+/// ARN to simulate for a caller. Assumed-role sessions are simulated as their
+/// role; the root user cannot be simulated and has every permission anyway.
+pub fn policy_source_arn(caller_arn: &str) -> Option<String> {
+    // arn:partition:service:region:account:resource
+    let parts: Vec<&str> = caller_arn.splitn(6, ':').collect();
+    let [_, partition, service, _, account, resource] = parts.as_slice() else {
+        return None;
+    };
+
+    match (*service, resource.split('/').collect::<Vec<_>>().as_slice()) {
+        (_, ["root"]) => None,
+        ("sts", ["assumed-role", role, ..]) => Some(format!("arn:{}:iam::{}:role/{}", partition, account, role)),
+        ("iam", _) => Some(caller_arn.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,323 @@
+// ============================================================================
+// SECURITY POSTURE
+// ============================================================================
+// One 0-100 score per account from IAM hygiene, EBS encryption, S3 public
+// access blocks and world-open security groups, weighted by category
+// ============================================================================
+
+use crate::aws::ec2::map_aws_volume;
+use crate::aws::iam::IamService;
+use crate::aws::s3::S3Service;
+use crate::aws::{AwsBucket, AwsClient, AwsError, AwsIamUser, AwsResult, AwsVolume};
+use crate::database::SecurityPostureWeights;
+use aws_sdk_ec2::types::{IpPermission, SecurityGroup};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Active access keys older than this should be rotated
+pub const ACCESS_KEY_MAX_AGE_DAYS: i64 = 90;
+
+/// Findings listed in the report, worst first
+const TOP_FINDINGS: usize = 10;
+
+const ADMINISTRATOR_ACCESS_POLICY: &str = "AdministratorAccess";
+
+/// Ports that may reasonably be open to the internet
+const PUBLIC_WEB_PORTS: &[i32] = &[80, 443];
+
+/// Remote administration ports that should never be open to the internet
+const ADMIN_PORTS: &[i32] = &[22, 3389];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostureCategory {
+    Iam,
+    Encryption,
+    PublicAccess,
+    Network,
+}
+
+impl PostureCategory {
+    pub const ALL: [PostureCategory; 4] = [Self::Iam, Self::Encryption, Self::PublicAccess, Self::Network];
+
+    fn weight(&self, weights: &SecurityPostureWeights) -> f64 {
+        match self {
+            Self::Iam => weights.iam,
+            Self::Encryption => weights.encryption,
+            Self::PublicAccess => weights.public_access,
+            Self::Network => weights.network,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+impl FindingSeverity {
+    /// Points taken off the category's score
+    pub fn penalty(&self) -> f64 {
+        match self {
+            Self::Low => 5.0,
+            Self::Medium => 10.0,
+            Self::High => 25.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostureFinding {
+    pub category: PostureCategory,
+    pub severity: FindingSeverity,
+    /// User, volume, bucket or security group the finding is about
+    pub resource: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostureCategoryScore {
+    pub category: PostureCategory,
+    /// Share of the overall score, 0-1; zero when the category couldn't be scanned
+    pub weight: f64,
+    /// 0-100, or `None` when the category couldn't be scanned
+    pub score: Option<f64>,
+    /// Points this category adds to the overall score
+    pub contribution: f64,
+    pub findings: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityPostureReport {
+    /// 0-100, the sum of the category contributions
+    pub score: f64,
+    pub breakdown: Vec<PostureCategoryScore>,
+    pub top_findings: Vec<PostureFinding>,
+    pub total_findings: usize,
+    /// Categories whose scan failed, with the error
+    pub unavailable: Vec<(PostureCategory, String)>,
+}
+
+/// Users with administrator access, and active access keys that are stale or
+/// doubled up
+pub fn iam_findings(users: &[AwsIamUser], now: DateTime<Utc>) -> Vec<PostureFinding> {
+    let mut findings = Vec::new();
+
+    for user in users {
+        let finding = |severity, message: String| PostureFinding {
+            category: PostureCategory::Iam,
+            severity,
+            resource: user.user_name.clone(),
+            message,
+        };
+
+        if user.attached_policies.iter().any(|p| p.policy_name == ADMINISTRATOR_ACCESS_POLICY) {
+            findings.push(finding(FindingSeverity::High, format!("User {} has AdministratorAccess attached directly", user.user_name)));
+        }
+
+        let active_keys: Vec<_> = user.access_keys.iter().filter(|k| k.status == "active").collect();
+        for key in &active_keys {
+            let Ok(created) = DateTime::parse_from_rfc3339(&key.create_date) else {
+                continue;
+            };
+            let age_days = (now - created.with_timezone(&Utc)).num_days();
+            if age_days > ACCESS_KEY_MAX_AGE_DAYS {
+                findings.push(finding(
+                    FindingSeverity::Medium,
+                    format!("Access key {} of user {} is {} days old", key.access_key_id, user.user_name, age_days),
+                ));
+            }
+        }
+        if active_keys.len() > 1 {
+            findings.push(finding(FindingSeverity::Low, format!("User {} has {} active access keys", user.user_name, active_keys.len())));
+        }
+    }
+
+    findings
+}
+
+/// One finding per unencrypted EBS volume
+pub fn encryption_findings(volumes: &[AwsVolume]) -> Vec<PostureFinding> {
+    volumes
+        .iter()
+        .filter(|volume| !volume.encrypted)
+        .map(|volume| PostureFinding {
+            category: PostureCategory::Encryption,
+            severity: FindingSeverity::Medium,
+            resource: volume.volume_id.clone(),
+            message: format!("EBS volume {} ({} GB) is not encrypted", volume.volume_id, volume.size_gb),
+        })
+        .collect()
+}
+
+/// Buckets without all four public access block settings on
+pub fn public_access_findings(buckets: &[AwsBucket]) -> Vec<PostureFinding> {
+    buckets
+        .iter()
+        .filter(|bucket| !bucket.public_access_block_flags.all())
+        .map(|bucket| {
+            let (severity, state) = if bucket.public_access_block_flags.any() {
+                (FindingSeverity::Medium, "only partly blocks")
+            } else {
+                (FindingSeverity::High, "does not block")
+            };
+            PostureFinding {
+                category: PostureCategory::PublicAccess,
+                severity,
+                resource: bucket.name.clone(),
+                message: format!("Bucket {} {} public access", bucket.name, state),
+            }
+        })
+        .collect()
+}
+
+/// Ingress rules open to 0.0.0.0/0 or ::/0 on anything but HTTP and HTTPS
+pub fn network_findings(groups: &[SecurityGroup]) -> Vec<PostureFinding> {
+    let mut findings = Vec::new();
+
+    for group in groups {
+        let group_id = group.group_id().unwrap_or("unknown");
+        for permission in group.ip_permissions().iter().filter(|p| is_world_open(p)) {
+            let protocol = permission.ip_protocol().unwrap_or("-1");
+            let all_traffic = protocol == "-1";
+            let (from, to) = (permission.from_port().unwrap_or(0), permission.to_port().unwrap_or(65535));
+
+            if !all_traffic && from == to && PUBLIC_WEB_PORTS.contains(&from) {
+                continue;
+            }
+
+            let exposes_admin = all_traffic || ADMIN_PORTS.iter().any(|port| (from..=to).contains(port));
+            let ports = if all_traffic {
+                "all traffic".to_string()
+            } else if from == to {
+                format!("{} port {}", protocol, from)
+            } else {
+                format!("{} ports {}-{}", protocol, from, to)
+            };
+
+            findings.push(PostureFinding {
+                category: PostureCategory::Network,
+                severity: if exposes_admin { FindingSeverity::High } else { FindingSeverity::Medium },
+                resource: group_id.to_string(),
+                message: format!("Security group {} allows {} from the internet", group_id, ports),
+            });
+        }
+    }
+
+    findings
+}
+
+fn is_world_open(permission: &IpPermission) -> bool {
+    permission.ip_ranges().iter().any(|r| r.cidr_ip() == Some("0.0.0.0/0"))
+        || permission.ipv6_ranges().iter().any(|r| r.cidr_ipv6() == Some("::/0"))
+}
+
+/// Score each category from 100 down by its findings' penalties, then weight
+/// them into one 0-100 score. Unavailable categories are left out and the
+/// remaining weights rescaled.
+pub fn score_posture(
+    findings: Vec<PostureFinding>,
+    weights: &SecurityPostureWeights,
+    unavailable: Vec<(PostureCategory, String)>,
+) -> SecurityPostureReport {
+    let is_available = |category: &PostureCategory| !unavailable.iter().any(|(c, _)| c == category);
+    let total_weight: f64 = PostureCategory::ALL
+        .iter()
+        .filter(|c| is_available(c))
+        .map(|c| c.weight(weights))
+        .sum();
+
+    let breakdown: Vec<PostureCategoryScore> = PostureCategory::ALL
+        .iter()
+        .map(|category| {
+            let category_findings: Vec<&PostureFinding> = findings.iter().filter(|f| f.category == *category).collect();
+            if !is_available(category) {
+                return PostureCategoryScore { category: *category, weight: 0.0, score: None, contribution: 0.0, findings: 0 };
+            }
+
+            let penalty: f64 = category_findings.iter().map(|f| f.severity.penalty()).sum();
+            let score = (100.0 - penalty).max(0.0);
+            let weight = if total_weight > 0.0 { category.weight(weights) / total_weight } else { 0.0 };
+            PostureCategoryScore {
+                category: *category,
+                weight,
+                score: Some(score),
+                contribution: score * weight,
+                findings: category_findings.len(),
+            }
+        })
+        .collect();
+
+    let mut top_findings = findings.clone();
+    top_findings.sort_by(|a, b| b.severity.cmp(&a.severity));
+    top_findings.truncate(TOP_FINDINGS);
+
+    SecurityPostureReport {
+        score: breakdown.iter().map(|c| c.contribution).sum(),
+        breakdown,
+        top_findings,
+        total_findings: findings.len(),
+        unavailable,
+    }
+}
+
+/// EBS volumes in the client's region
+async fn collect_volumes(client: &AwsClient) -> AwsResult<Vec<AwsVolume>> {
+    let volumes = client.ec2_client
+        .describe_volumes()
+        .into_paginator()
+        .items()
+        .send()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .map_err(|e| AwsError::SdkError(e.into()))?;
+
+    Ok(volumes.iter().filter_map(map_aws_volume).collect())
+}
+
+/// Security groups in the client's region with their rules
+async fn collect_security_groups(client: &AwsClient) -> AwsResult<Vec<SecurityGroup>> {
+    client.ec2_client
+        .describe_security_groups()
+        .into_paginator()
+        .items()
+        .send()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .map_err(|e| AwsError::SdkError(e.into()))
+}
+
+/// Scan the account and score it. A category whose scan fails is reported as
+/// unavailable rather than failing the whole score.
+pub async fn security_posture(client: &AwsClient, weights: &SecurityPostureWeights) -> SecurityPostureReport {
+    tracing::info!("Scoring security posture in region {}", client.primary_region());
+
+    let iam = IamService::new(client.clone());
+    let s3 = S3Service::new(client.clone());
+    let (users, volumes, buckets, groups) = tokio::join!(
+        iam.collect_users(),
+        collect_volumes(client),
+        s3.collect_buckets(),
+        collect_security_groups(client),
+    );
+
+    let mut findings = Vec::new();
+    let mut unavailable = Vec::new();
+    let mut record = |category, result: AwsResult<Vec<PostureFinding>>| match result {
+        Ok(found) => findings.extend(found),
+        Err(e) => {
+            tracing::warn!("Security posture scan for {:?} failed: {}", category, e);
+            unavailable.push((category, e.to_string()));
+        }
+    };
+
+    record(PostureCategory::Iam, users.map(|users| iam_findings(&users, Utc::now())));
+    record(PostureCategory::Encryption, volumes.map(|volumes| encryption_findings(&volumes)));
+    record(PostureCategory::PublicAccess, buckets.map(|buckets| public_access_findings(&buckets)));
+    record(PostureCategory::Network, groups.map(|groups| network_findings(&groups)));
+
+    score_posture(findings, weights, unavailable)
+}
@@ -0,0 +1,79 @@
+// ============================================================================
+// REGIONAL COLLECTION
+// ============================================================================
+// Run a per-region collection across an account's enabled regions
+// concurrently, bounded by a semaphore
+// ============================================================================
+
+use crate::aws::{AwsResult, RegionResult, RegionalCollection};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Maximum number of regions queried at the same time
+pub const MAX_CONCURRENT_REGIONS: usize = 4;
+
+/// Call `collect` for every region, at most `max_concurrent` at a time. A failing
+/// region is reported in the results without affecting the others.
+pub async fn collect_across_regions<T, F, Fut>(regions: &[String], max_concurrent: usize, collect: F) -> RegionalCollection<T>
+where
+    T: Send + 'static,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = AwsResult<Vec<T>>> + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+    let mut tasks = JoinSet::new();
+
+    for (index, region) in regions.iter().enumerate() {
+        let semaphore = semaphore.clone();
+        let future = collect(region.clone());
+        let region = region.clone();
+
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.expect("region semaphore closed");
+            (index, region, future.await)
+        });
+    }
+
+    let mut outcomes = Vec::with_capacity(regions.len());
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(e) => tracing::error!("Regional collection task failed: {}", e),
+        }
+    }
+
+    // Report regions in the order they were requested
+    outcomes.sort_by_key(|(index, _, _)| *index);
+
+    let mut collection = RegionalCollection {
+        items: Vec::new(),
+        regions: Vec::with_capacity(outcomes.len()),
+    };
+
+    for (_, region, result) in outcomes {
+        match result {
+            Ok(mut items) => {
+                collection.regions.push(RegionResult {
+                    region,
+                    success: true,
+                    count: items.len(),
+                    error: None,
+                });
+                collection.items.append(&mut items);
+            }
+            Err(e) => {
+                tracing::warn!("Collection failed in region {}: {}", region, e);
+                collection.regions.push(RegionResult {
+                    region,
+                    success: false,
+                    count: 0,
+                    error: Some(e.to_string()),
+                });
+            }
+        }
+    }
+
+    collection
+}
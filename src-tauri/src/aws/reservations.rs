@@ -0,0 +1,121 @@
+// ============================================================================
+// RESERVATION COVERAGE
+// ============================================================================
+// Which running instances are covered by reserved instances, and how much of
+// each reservation is actually in use
+// ============================================================================
+
+use crate::aws::{AwsClient, AwsInstance, AwsReservedInstance, InstanceCoverage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Platform of instances that don't report PlatformDetails
+const DEFAULT_PLATFORM: &str = "Linux/UNIX";
+
+/// Reserved and running instance counts for one instance type
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReservationUtilization {
+    pub instance_type: String,
+    pub reserved: i32,
+    /// Running on-demand and reserved instances; spot instances don't count
+    pub running: i32,
+    pub covered: i32,
+    /// Reservations paid for with no running instance to apply to
+    pub unused_reservations: i32,
+    /// Running instances billed at the on-demand rate
+    pub uncovered_instances: i32,
+}
+
+/// Reservation product descriptions carry a " (Amazon VPC)" suffix that
+/// instance platform details don't
+fn normalize_platform(platform: &str) -> String {
+    platform.trim_end_matches(" (Amazon VPC)").trim().to_ascii_lowercase()
+}
+
+fn reservation_applies(reservation: &AwsReservedInstance, instance: &AwsInstance) -> bool {
+    reservation.instance_type == instance.instance_type
+        && reservation.region == instance.region
+        && reservation.availability_zone.as_ref().map_or(true, |az| *az == instance.availability_zone)
+        && normalize_platform(&reservation.product_description)
+            == normalize_platform(instance.platform_details.as_deref().unwrap_or(DEFAULT_PLATFORM))
+}
+
+/// Set each instance's `coverage`. Spot instances are spot; running instances
+/// take up reservations matching their type, region, platform and (for zonal
+/// reservations) zone, zonal ones first, until each reservation's count is
+/// used up. Size flexibility of regional reservations isn't modelled, so only
+/// exact instance types match.
+pub fn annotate_coverage(instances: &mut [AwsInstance], reservations: &[AwsReservedInstance]) {
+    let mut remaining: Vec<i32> = reservations.iter().map(|r| r.instance_count.max(0)).collect();
+
+    // Zonal reservations are more specific, so they go first to leave the
+    // regional ones for instances in other zones
+    let mut order: Vec<usize> = (0..reservations.len()).collect();
+    order.sort_by_key(|&i| reservations[i].availability_zone.is_none());
+
+    let mut candidates: Vec<usize> = (0..instances.len()).collect();
+    candidates.sort_by(|&a, &b| instances[a].instance_id.cmp(&instances[b].instance_id));
+
+    for index in candidates {
+        let instance = &instances[index];
+        let coverage = if instance.lifecycle == "spot" {
+            InstanceCoverage::Spot
+        } else if instance.state != "running" {
+            InstanceCoverage::OnDemand
+        } else {
+            match order.iter().find(|&&r| remaining[r] > 0 && reservation_applies(&reservations[r], instance)) {
+                Some(&r) => {
+                    remaining[r] -= 1;
+                    InstanceCoverage::Reserved
+                }
+                None => InstanceCoverage::OnDemand,
+            }
+        };
+        instances[index].coverage = coverage;
+    }
+}
+
+/// Annotate `instances` with the reservations in the client's region. A
+/// listing shouldn't fail over reservations, so if they can't be read every
+/// instance but the spot ones is left on-demand.
+pub async fn annotate_instances(client: &AwsClient, instances: &mut [AwsInstance]) {
+    let reservations = client.collect_reserved_instances().await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to collect reserved instances, showing instances as on-demand: {:?}", e);
+        Vec::new()
+    });
+    annotate_coverage(instances, &reservations);
+}
+
+fn utilization_for<'a>(by_type: &'a mut BTreeMap<String, ReservationUtilization>, instance_type: &str) -> &'a mut ReservationUtilization {
+    by_type.entry(instance_type.to_string()).or_insert_with(|| ReservationUtilization {
+        instance_type: instance_type.to_string(),
+        ..Default::default()
+    })
+}
+
+/// Reservation use per instance type, from instances already passed through
+/// `annotate_coverage`
+pub fn reservation_utilization(instances: &[AwsInstance], reservations: &[AwsReservedInstance]) -> Vec<ReservationUtilization> {
+    let mut by_type: BTreeMap<String, ReservationUtilization> = BTreeMap::new();
+
+    for reservation in reservations {
+        utilization_for(&mut by_type, &reservation.instance_type).reserved += reservation.instance_count.max(0);
+    }
+
+    for instance in instances.iter().filter(|i| i.state == "running" && i.coverage != InstanceCoverage::Spot) {
+        let utilization = utilization_for(&mut by_type, &instance.instance_type);
+        utilization.running += 1;
+        if instance.coverage == InstanceCoverage::Reserved {
+            utilization.covered += 1;
+        }
+    }
+
+    by_type
+        .into_values()
+        .map(|mut utilization| {
+            utilization.unused_reservations = utilization.reserved - utilization.covered;
+            utilization.uncovered_instances = utilization.running - utilization.covered;
+            utilization
+        })
+        .collect()
+}
@@ -0,0 +1,153 @@
+// ============================================================================
+// RETRIES AND TIMEOUTS
+// ============================================================================
+// Retry AWS calls that were throttled or hit a transient server-side failure,
+// backing off exponentially with jitter between attempts, and give up on
+// calls that take too long
+// ============================================================================
+
+use crate::aws::config::Retries;
+use crate::aws::{AwsError, AwsResult};
+use aws_config::retry::RetryConfig;
+use aws_sdk_ec2::error::ProvideErrorMetadata;
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// Error codes AWS services return when throttling a caller
+const THROTTLING_CODES: &[&str] = &[
+    "Throttling",
+    "ThrottlingException",
+    "ThrottledException",
+    "RequestLimitExceeded",
+    "RequestThrottled",
+    "RequestThrottledException",
+    "TooManyRequestsException",
+    "ProvisionedThroughputExceededException",
+    "SlowDown",
+];
+
+/// Error codes for server-side (5xx) failures that usually clear on their own
+const TRANSIENT_CODES: &[&str] = &[
+    "InternalError",
+    "InternalFailure",
+    "InternalServerError",
+    "ServiceUnavailable",
+    "ServiceUnavailableException",
+    "Unavailable",
+    "RequestTimeout",
+    "RequestTimeoutException",
+];
+
+fn error_code(error: &AwsError) -> Option<&str> {
+    match error {
+        AwsError::SdkError(e) => e.code(),
+        AwsError::S3SdkError(e) => e.code(),
+        AwsError::IamSdkError(e) => e.code(),
+        _ => None,
+    }
+}
+
+/// Whether a failed SDK call was throttled or failed server-side, so trying
+/// it again later may succeed. Everything else (bad requests, denied access,
+/// missing resources, our own rate limits) fails the same way every time.
+pub fn is_retryable(error: &AwsError) -> bool {
+    error_code(error).map_or(false, |code| THROTTLING_CODES.contains(&code) || TRANSIENT_CODES.contains(&code))
+}
+
+/// Delay before retry number `retry` (1 for the retry after the first
+/// failure): the base delay doubled per retry and capped at the max, of which
+/// `jitter` (0.0 to 1.0) picks a point in the upper half so that clients
+/// throttled together don't all come back at once
+pub fn backoff_delay(retries: &Retries, retry: u32, jitter: f64) -> Duration {
+    let exponent = retry.saturating_sub(1).min(20);
+    let ceiling = retries.base_delay_ms.saturating_mul(1 << exponent).min(retries.max_delay_ms);
+    let jittered = ceiling as f64 * (0.5 + 0.5 * jitter.clamp(0.0, 1.0));
+    Duration::from_millis(jittered.round() as u64)
+}
+
+/// Uniform value in [0, 1) from the randomly keyed std hasher
+fn random_jitter() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Copy of `client` that makes a single attempt per call. The SDK clients
+/// retry on their own; calls made inside `with_retry` go through one of these
+/// so the attempts of the two layers don't multiply.
+pub fn single_attempt_ec2(client: &aws_sdk_ec2::Client) -> aws_sdk_ec2::Client {
+    aws_sdk_ec2::Client::from_conf(client.config().to_builder().retry_config(RetryConfig::disabled()).build())
+}
+
+/// S3 counterpart of `single_attempt_ec2`
+pub fn single_attempt_s3(client: &aws_sdk_s3::Client) -> aws_sdk_s3::Client {
+    aws_sdk_s3::Client::from_conf(client.config().to_builder().retry_config(RetryConfig::disabled()).build())
+}
+
+/// IAM counterpart of `single_attempt_ec2`
+pub fn single_attempt_iam(client: &aws_sdk_iam::Client) -> aws_sdk_iam::Client {
+    aws_sdk_iam::Client::from_conf(client.config().to_builder().retry_config(RetryConfig::disabled()).build())
+}
+
+/// Run `call` until it succeeds, fails in a way retrying won't fix, or has
+/// been tried `retries.max_attempts` times
+pub async fn with_retry<T, F, Fut>(retries: &Retries, action: &str, call: F) -> AwsResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AwsResult<T>>,
+{
+    retry_with_sleep(retries, action, call, tokio::time::sleep).await
+}
+
+/// `with_retry` with the wait between attempts left to `sleep`
+pub async fn retry_with_sleep<T, F, Fut, S, SleepFut>(retries: &Retries, action: &str, mut call: F, mut sleep: S) -> AwsResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AwsResult<T>>,
+    S: FnMut(Duration) -> SleepFut,
+    SleepFut: Future<Output = ()>,
+{
+    let max_attempts = retries.max_attempts.max(1);
+    let mut attempt = 1;
+
+    loop {
+        let error = match call().await {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+
+        if !is_retryable(&error) || max_attempts == 1 {
+            return Err(error);
+        }
+        if attempt >= max_attempts {
+            tracing::error!("{} failed {} times, giving up: {}", action, attempt, error);
+            return Err(AwsError::RetriesExhausted {
+                action: action.to_string(),
+                attempts: attempt,
+                source: Box::new(error),
+            });
+        }
+
+        let delay = backoff_delay(retries, attempt, random_jitter());
+        tracing::warn!("{} failed (attempt {}/{}), retrying in {}ms: {}", action, attempt, max_attempts, delay.as_millis(), error);
+        sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Run `call`, failing with a network error if it hasn't finished within
+/// `limit`. The call is dropped at that point, so nothing it was waiting on
+/// keeps running.
+pub async fn with_timeout<T, Fut>(limit: Duration, action: &str, call: Fut) -> AwsResult<T>
+where
+    Fut: Future<Output = AwsResult<T>>,
+{
+    match tokio::time::timeout(limit, call).await {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::error!("{} timed out after {:?}", action, limit);
+            Err(AwsError::NetworkError(format!("{} timed out after {:?}", action, limit)))
+        }
+    }
+}
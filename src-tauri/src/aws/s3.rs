@@ -4,13 +4,112 @@
 // S3 bucket management with real AWS API integration
 // ============================================================================
 
-use crate::aws::{AwsClient, AwsBucket, AwsResult, AwsError};
-use aws_config::{BehaviorVersion, Region};
-use aws_credential_types::Credentials;
-use aws_sdk_s3::types::{Bucket as AwsSdkBucket, StorageClass};
+use crate::aws::{AwsClient, AwsBucket, BucketCleanupFailure, BucketEncryption, BucketEncryptionResult, BucketEncryptionStatus, EmptyBucketCleanupReport, LifecycleImpactPreview, LifecycleRule, LifecycleTransition, LifecycleTransitionImpact, PresignedUrl, PublicAccessBlockFlags, RegionalCollection, UploadedObject, AwsResult, AwsError};
+use crate::aws::diagnostics;
+use crate::aws::regional::{collect_across_regions, MAX_CONCURRENT_REGIONS};
+use crate::aws::retry::{single_attempt_s3, with_retry, with_timeout};
+use aws_sdk_s3::error::ProvideErrorMetadata;
+use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{
+    Bucket as AwsSdkBucket, BucketLifecycleConfiguration, CompletedMultipartUpload, CompletedPart, ExpirationStatus, LifecycleExpiration,
+    LifecycleRule as S3LifecycleRule, LifecycleRuleFilter, Object, PublicAccessBlockConfiguration, ServerSideEncryption,
+    ServerSideEncryptionByDefault, ServerSideEncryptionConfiguration, ServerSideEncryptionRule, StorageClass, Tag,
+    Transition, TransitionStorageClass,
+};
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
 use uuid::Uuid;
 
+/// Tag key that protects a bucket from bulk cleanup
+pub const BUCKET_PROTECTED_TAG_KEY: &str = "PocketArchitect:Protected";
+
+/// Error code GetBucketTagging returns for a bucket without tags
+const NO_SUCH_TAG_SET_CODE: &str = "NoSuchTagSet";
+
+/// Error code GetBucketLifecycleConfiguration returns for a bucket without rules
+const NO_SUCH_LIFECYCLE_CODE: &str = "NoSuchLifecycleConfiguration";
+
+/// Error code GetBucketPolicy returns for a bucket without a policy
+const NO_SUCH_BUCKET_POLICY_CODE: &str = "NoSuchBucketPolicy";
+
+/// Error code GetBucketEncryption returns for a bucket without default encryption
+const NO_SUCH_ENCRYPTION_CODE: &str = "ServerSideEncryptionConfigurationNotFoundError";
+
+/// Error code GetPublicAccessBlock returns when no settings are configured
+const NO_SUCH_PUBLIC_ACCESS_BLOCK_CODE: &str = "NoSuchPublicAccessBlockConfiguration";
+
+/// Files at least this large are uploaded in parts
+pub const MULTIPART_UPLOAD_THRESHOLD_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Size of each uploaded part; S3 requires at least 5 MiB for all but the last
+const MULTIPART_PART_SIZE_BYTES: u64 = 8 * 1024 * 1024;
+
+/// S3 rejects presigned URLs that stay valid for more than seven days
+pub const MAX_PRESIGNED_URL_EXPIRY_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+/// Objects listed at most when previewing a lifecycle rule; larger buckets
+/// get an estimate from this sample
+pub const LIFECYCLE_PREVIEW_MAX_OBJECTS: usize = 50_000;
+
+const BYTES_PER_GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// us-east-1 storage price per GB-month for a storage class, used to estimate
+/// lifecycle savings. Unknown classes are priced as STANDARD.
+pub fn storage_class_price_per_gb_month(storage_class: &str) -> f64 {
+    match storage_class {
+        "STANDARD_IA" => 0.0125,
+        "ONEZONE_IA" => 0.01,
+        "GLACIER_IR" => 0.004,
+        "GLACIER" => 0.0036,
+        "DEEP_ARCHIVE" => 0.00099,
+        "REDUCED_REDUNDANCY" => 0.024,
+        // INTELLIGENT_TIERING starts objects in its frequent access tier
+        _ => 0.023,
+    }
+}
+
+/// HTTP method a presigned URL is signed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresignMethod {
+    Get,
+    Put,
+}
+
+impl PresignMethod {
+    /// Parse "GET" or "PUT", in any case
+    pub fn parse(value: &str) -> AwsResult<Self> {
+        match value.to_ascii_uppercase().as_str() {
+            "GET" => Ok(Self::Get),
+            "PUT" => Ok(Self::Put),
+            other => Err(AwsError::ConfigError(format!("Unsupported presigned URL method '{}'; expected GET or PUT", other))),
+        }
+    }
+}
+
+/// Check a requested presigned URL lifetime against S3's limits
+pub fn validate_presign_expiry(expires_secs: i64) -> AwsResult<u64> {
+    if expires_secs <= 0 || expires_secs as u64 > MAX_PRESIGNED_URL_EXPIRY_SECONDS {
+        return Err(AwsError::ConfigError(format!(
+            "Presigned URL expiry must be between 1 and {} seconds (7 days), got {}",
+            MAX_PRESIGNED_URL_EXPIRY_SECONDS, expires_secs
+        )));
+    }
+    Ok(expires_secs as u64)
+}
+
+/// What bulk cleanup found when it looked inside a bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketCleanupStatus {
+    Protected,
+    NotEmpty,
+    Empty,
+}
+
 pub struct S3Service {
     client: AwsClient,
 }
@@ -53,36 +152,66 @@ impl S3Service {
         Ok(all_buckets)
     }
 
-    /// Collect S3 buckets using cross-region fallback client
-    async fn collect_buckets_cross_region(&self, region: &str) -> AwsResult<Vec<AwsBucket>> {
-        tracing::debug!("Attempting cross-region collection for S3 buckets in region: {}", region);
+    /// Collect S3 buckets located in each of the given regions concurrently
+    pub async fn collect_buckets_in_regions(&self, regions: &[String]) -> RegionalCollection<AwsBucket> {
+        tracing::info!("Collecting S3 buckets from {} regions", regions.len());
+
+        let client = self.client.clone();
+        collect_across_regions(regions, MAX_CONCURRENT_REGIONS, move |region| {
+            let client = client.clone();
+            async move {
+                let regional_client = if region == client.config.region {
+                    client
+                } else {
+                    client.for_region(&region).await?
+                };
+                S3Service::new(regional_client).collect_buckets_in_region(&region).await
+            }
+        })
+        .await
+    }
 
-        // Create a new client for the fallback region
-        use crate::aws::AwsConfig;
-        use aws_config::{BehaviorVersion, Region};
-        use aws_credential_types::Credentials;
-        use aws_sdk_s3::Client as S3Client;
+    /// Collect the S3 buckets located in a specific region
+    async fn collect_buckets_in_region(&self, region: &str) -> AwsResult<Vec<AwsBucket>> {
+        tracing::debug!("Collecting S3 buckets in region: {}", region);
 
-        let config = AwsConfig::load_from_file().map_err(|e| {
-            tracing::error!("Failed to load AWS config for cross-region fallback: {:?}", e);
-            AwsError::ConfigError(format!("Config load failed: {}", e))
-        })?;
+        let s3_client = &self.client.s3_client;
 
-        let credentials = Credentials::new(
-            config.credentials.access_key_id.clone(),
-            config.credentials.secret_access_key.clone(),
-            None, // session token
-            None, // expiry
-            "pocket-architect",
-        );
+        let listing = with_retry(&self.client.config.retries, "ListBuckets", || async move {
+            single_attempt_s3(s3_client)
+                .list_buckets()
+                .bucket_region(region)
+                .send()
+                .await
+                .map_err(|e| -> AwsError {
+                    tracing::error!("Failed to list buckets in region {}: {:?}", region, e);
+                    diagnostics::record("s3", "ListBuckets", AwsError::from(aws_sdk_s3::Error::from(e)))
+                })
+        });
+        let response = with_timeout(self.client.config.timeouts.bucket_operations(), "ListBuckets", listing).await?;
 
-        let aws_config = aws_config::defaults(BehaviorVersion::v2025_08_07())
-            .region(Region::new(region.to_string()))
-            .credentials_provider(credentials)
-            .load()
-            .await;
+        let mut buckets = Vec::new();
+
+        for bucket in response.buckets().iter() {
+            if let Some(mapped_bucket) = self.map_aws_bucket_fallback(bucket, region, s3_client).await {
+                buckets.push(mapped_bucket);
+            }
+        }
+
+        tracing::debug!("Collected {} buckets from region {}", buckets.len(), region);
+        Ok(buckets)
+    }
 
-        let s3_client = S3Client::new(&aws_config);
+    /// Collect S3 buckets using cross-region fallback client
+    async fn collect_buckets_cross_region(&self, region: &str) -> AwsResult<Vec<AwsBucket>> {
+        tracing::debug!("Attempting cross-region collection for S3 buckets in region: {}", region);
+
+        // Create a new client for the fallback region with this client's credentials
+        let fallback_client = self.client.for_region(region).await.map_err(|e| {
+            tracing::error!("Cross-region fallback client creation failed for region {}: {:?}", region, e);
+            AwsError::NetworkError(format!("Cross-region connection failed: {}", e))
+        })?;
+        let s3_client = &fallback_client.s3_client;
 
         // Test the fallback connection
         s3_client
@@ -95,19 +224,22 @@ impl S3Service {
             })?;
 
         // Now collect buckets with the fallback client
-        let response = s3_client
-            .list_buckets()
-            .send()
-            .await
-            .map_err(|e| -> AwsError {
-                tracing::error!("Failed to list buckets in fallback region {}: {:?}", region, e);
-                AwsError::from(aws_sdk_s3::Error::from(e))
-            })?;
+        let listing = with_retry(&self.client.config.retries, "ListBuckets", || async move {
+            single_attempt_s3(s3_client)
+                .list_buckets()
+                .send()
+                .await
+                .map_err(|e| -> AwsError {
+                    tracing::error!("Failed to list buckets in fallback region {}: {:?}", region, e);
+                    diagnostics::record("s3", "ListBuckets", AwsError::from(aws_sdk_s3::Error::from(e)))
+                })
+        });
+        let response = with_timeout(self.client.config.timeouts.bucket_operations(), "ListBuckets", listing).await?;
 
         let mut buckets = Vec::new();
 
         for bucket in response.buckets().iter() {
-            if let Some(mapped_bucket) = self.map_aws_bucket_fallback(bucket, region, &s3_client).await {
+            if let Some(mapped_bucket) = self.map_aws_bucket_fallback(bucket, region, s3_client).await {
                 buckets.push(mapped_bucket);
             }
         }
@@ -134,7 +266,7 @@ impl S3Service {
 
         let storage_class = "STANDARD".to_string();
         let versioning_enabled = self.get_bucket_versioning_fallback(&name, &bucket_region, s3_client).await.unwrap_or(false);
-        let public_access_block = self.get_public_access_block_fallback(&name, &bucket_region, s3_client).await.unwrap_or(false);
+        let public_access_block_flags = self.get_public_access_block_fallback(&name, &bucket_region, s3_client).await.unwrap_or_default();
 
         Some(AwsBucket {
             name,
@@ -147,7 +279,8 @@ impl S3Service {
             storage_class,
             versioning_enabled,
             encryption: None,
-            public_access_block,
+            public_access_block: public_access_block_flags.any(),
+            public_access_block_flags,
         })
     }
 
@@ -160,16 +293,90 @@ impl S3Service {
             .await
             .map_err(|e| -> AwsError {
                 tracing::warn!("Failed to get location for bucket {} (fallback): {:?}", bucket_name, e);
-                AwsError::from(aws_sdk_s3::Error::from(e))
+                diagnostics::record("s3", "GetBucketLocation", AwsError::from(aws_sdk_s3::Error::from(e)))
             })?;
 
-        let region = response.location_constraint()
-            .map(|rc| format!("{:?}", rc).to_lowercase())
-            .unwrap_or_else(|| "us-east-1".to_string());
+        // Buckets in us-east-1 report no location constraint, and very old
+        // eu-west-1 buckets report "EU"
+        let region = match response.location_constraint().map(|rc| rc.as_str()) {
+            None | Some("") => "us-east-1".to_string(),
+            Some("EU") => "eu-west-1".to_string(),
+            Some(region) => region.to_string(),
+        };
 
         Ok(region)
     }
 
+    /// Client for the region a bucket lives in; S3 redirects requests sent to
+    /// any other region
+    async fn bucket_client(&self, bucket_name: &str) -> AwsResult<AwsClient> {
+        let region = self.get_bucket_location(bucket_name).await?;
+        if region == self.client.config.region {
+            Ok(self.client.clone())
+        } else {
+            self.client.for_region(&region).await
+        }
+    }
+
+    /// Upload a local file as `key`, in parts when it is large. The content
+    /// type is guessed from the file extension unless given.
+    pub async fn upload_object(
+        &self,
+        bucket_name: &str,
+        key: &str,
+        src_path: &Path,
+        content_type: Option<&str>,
+    ) -> AwsResult<UploadedObject> {
+        let metadata = tokio::fs::metadata(src_path)
+            .await
+            .map_err(|e| AwsError::ConfigError(format!("Cannot read {}: {}", src_path.display(), e)))?;
+        if !metadata.is_file() {
+            return Err(AwsError::ConfigError(format!("{} is not a file", src_path.display())));
+        }
+
+        let content_type = content_type
+            .map(str::to_string)
+            .unwrap_or_else(|| content_type_for_path(src_path).to_string());
+        let size = metadata.len();
+        let multipart = size >= MULTIPART_UPLOAD_THRESHOLD_BYTES;
+        tracing::info!("Uploading {} ({} bytes) to s3://{}/{}", src_path.display(), size, bucket_name, key);
+
+        let client = self.bucket_client(bucket_name).await?;
+        let s3_client = &client.s3_client;
+
+        let etag = if multipart {
+            multipart_upload(s3_client, bucket_name, key, src_path, &content_type).await?
+        } else {
+            let body = ByteStream::from_path(src_path)
+                .await
+                .map_err(|e| AwsError::OperationError(format!("Failed to read {}: {}", src_path.display(), e)))?;
+
+            let response = s3_client
+                .put_object()
+                .bucket(bucket_name)
+                .key(key)
+                .content_type(&content_type)
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| -> AwsError {
+                    tracing::error!("Failed to upload {} to bucket {}: {:?}", key, bucket_name, e);
+                    diagnostics::record("s3", "PutObject", AwsError::from(aws_sdk_s3::Error::from(e)))
+                })?;
+            response.e_tag().unwrap_or_default().to_string()
+        };
+
+        tracing::info!("Uploaded s3://{}/{}", bucket_name, key);
+        Ok(UploadedObject {
+            bucket: bucket_name.to_string(),
+            key: key.to_string(),
+            etag: etag.trim_matches('"').to_string(),
+            size_bytes: size as i64,
+            content_type,
+            multipart,
+        })
+    }
+
     /// Get bucket analytics using fallback client
     async fn get_bucket_analytics_fallback(&self, bucket_name: &str, region: &str, s3_client: &aws_sdk_s3::Client) -> AwsResult<(i64, i64)> {
         let response = s3_client
@@ -180,7 +387,7 @@ impl S3Service {
             .await
             .map_err(|e| -> AwsError {
                 tracing::warn!("Failed to get analytics for bucket {} (fallback): {:?}", bucket_name, e);
-                AwsError::from(aws_sdk_s3::Error::from(e))
+                diagnostics::record("s3", "ListObjectsV2", AwsError::from(aws_sdk_s3::Error::from(e)))
             })?;
 
         let object_count = response.key_count().unwrap_or(0) as i64;
@@ -201,32 +408,19 @@ impl S3Service {
             .await
             .map_err(|e| -> AwsError {
                 tracing::warn!("Failed to get versioning for bucket {} (fallback): {:?}", bucket_name, e);
-                AwsError::from(aws_sdk_s3::Error::from(e))
+                diagnostics::record("s3", "GetBucketVersioning", AwsError::from(aws_sdk_s3::Error::from(e)))
             })?;
 
         Ok(response.status() == Some(&aws_sdk_s3::types::BucketVersioningStatus::Enabled))
     }
 
     /// Get public access block using fallback client
-    async fn get_public_access_block_fallback(&self, bucket_name: &str, region: &str, s3_client: &aws_sdk_s3::Client) -> AwsResult<bool> {
-        let response = s3_client
-            .get_public_access_block()
-            .bucket(bucket_name)
-            .send()
-            .await
-            .map_err(|e| -> AwsError { AwsError::from(aws_sdk_s3::Error::from(e)) })?;
-
-        let config = response.public_access_block_configuration();
-        let (block_public_acls, block_public_policy, ignore_public_acls, restrict_public_buckets) = config
-            .map(|c| (
-                c.block_public_acls().unwrap_or(false),
-                c.block_public_policy().unwrap_or(false),
-                c.ignore_public_acls().unwrap_or(false),
-                c.restrict_public_buckets().unwrap_or(false),
-            ))
-            .unwrap_or((false, false, false, false));
-
-        Ok(block_public_acls || block_public_policy || ignore_public_acls || restrict_public_buckets)
+    async fn get_public_access_block_fallback(&self, bucket_name: &str, region: &str, s3_client: &aws_sdk_s3::Client) -> AwsResult<PublicAccessBlockFlags> {
+        match s3_client.get_public_access_block().bucket(bucket_name).send().await {
+            Ok(response) => Ok(public_access_block_flags(response.public_access_block_configuration())),
+            Err(e) if e.code() == Some(NO_SUCH_PUBLIC_ACCESS_BLOCK_CODE) => Ok(PublicAccessBlockFlags::default()),
+            Err(e) => Err(diagnostics::record("s3", "GetPublicAccessBlock", AwsError::from(aws_sdk_s3::Error::from(e)))),
+        }
     }
 
     /// Get bucket versioning status
@@ -240,37 +434,178 @@ impl S3Service {
             .await
             .map_err(|e| -> AwsError {
                 tracing::warn!("Failed to get versioning for bucket {}: {:?}", bucket_name, e);
-                AwsError::from(aws_sdk_s3::Error::from(e))
+                diagnostics::record("s3", "GetBucketVersioning", AwsError::from(aws_sdk_s3::Error::from(e)))
             })?;
 
         Ok(response.status() == Some(&aws_sdk_s3::types::BucketVersioningStatus::Enabled))
     }
 
-    /// Get public access block status
-    async fn get_public_access_block(&self, bucket_name: &str, region: &str) -> AwsResult<bool> {
+    /// Public access block settings of a bucket; all off when none are configured
+    pub async fn get_public_access_block(&self, bucket_name: &str) -> AwsResult<PublicAccessBlockFlags> {
         let s3_client = &self.client.s3_client;
 
-        let response = s3_client
-            .get_public_access_block()
+        match s3_client.get_public_access_block().bucket(bucket_name).send().await {
+            Ok(response) => Ok(public_access_block_flags(response.public_access_block_configuration())),
+            Err(e) if e.code() == Some(NO_SUCH_PUBLIC_ACCESS_BLOCK_CODE) => Ok(PublicAccessBlockFlags::default()),
+            Err(e) => {
+                tracing::warn!("Failed to get public access block for bucket {}: {:?}", bucket_name, e);
+                Err(diagnostics::record("s3", "GetPublicAccessBlock", AwsError::from(aws_sdk_s3::Error::from(e))))
+            }
+        }
+    }
+
+    /// Replace a bucket's public access block settings
+    pub async fn put_public_access_block(&self, bucket_name: &str, flags: PublicAccessBlockFlags) -> AwsResult<()> {
+        tracing::info!("Setting public access block on S3 bucket {}: {:?}", bucket_name, flags);
+
+        self.client.s3_client
+            .put_public_access_block()
             .bucket(bucket_name)
+            .public_access_block_configuration(
+                PublicAccessBlockConfiguration::builder()
+                    .block_public_acls(flags.block_public_acls)
+                    .ignore_public_acls(flags.ignore_public_acls)
+                    .block_public_policy(flags.block_public_policy)
+                    .restrict_public_buckets(flags.restrict_public_buckets)
+                    .build()
+            )
             .send()
             .await
             .map_err(|e| -> AwsError {
-                tracing::warn!("Failed to get public access block for bucket {}: {:?}", bucket_name, e);
-                AwsError::from(aws_sdk_s3::Error::from(e))
+                tracing::error!("Failed to put public access block for bucket {}: {:?}", bucket_name, e);
+                diagnostics::record("s3", "PutPublicAccessBlock", AwsError::from(aws_sdk_s3::Error::from(e)))
+            })?;
+
+        Ok(())
+    }
+
+    /// A bucket's policy document, or `None` if it has no policy
+    pub async fn get_bucket_policy(&self, bucket_name: &str) -> AwsResult<Option<String>> {
+        match self.client.s3_client.get_bucket_policy().bucket(bucket_name).send().await {
+            Ok(response) => Ok(response.policy().map(|p| p.to_string())),
+            Err(e) if e.code() == Some(NO_SUCH_BUCKET_POLICY_CODE) => Ok(None),
+            Err(e) => {
+                tracing::error!("Failed to get policy for bucket {}: {:?}", bucket_name, e);
+                Err(diagnostics::record("s3", "GetBucketPolicy", AwsError::from(aws_sdk_s3::Error::from(e))))
+            }
+        }
+    }
+
+    /// Replace a bucket's policy after checking it is a well-formed policy document
+    pub async fn put_bucket_policy(&self, bucket_name: &str, policy: &str) -> AwsResult<()> {
+        validate_bucket_policy(policy)?;
+        tracing::info!("Setting policy on S3 bucket: {}", bucket_name);
+
+        self.client.s3_client
+            .put_bucket_policy()
+            .bucket(bucket_name)
+            .policy(policy)
+            .send()
+            .await
+            .map_err(|e| -> AwsError {
+                tracing::error!("Failed to put policy for bucket {}: {:?}", bucket_name, e);
+                diagnostics::record("s3", "PutBucketPolicy", AwsError::from(aws_sdk_s3::Error::from(e)))
+            })?;
+
+        Ok(())
+    }
+
+    /// A bucket's default encryption, read from the bucket's own region
+    pub async fn get_bucket_encryption(&self, bucket_name: &str) -> AwsResult<BucketEncryptionStatus> {
+        let client = self.bucket_client(bucket_name).await?;
+        let region = client.config.region.clone();
+
+        let default = match client.s3_client.get_bucket_encryption().bucket(bucket_name).send().await {
+            Ok(response) => response
+                .server_side_encryption_configuration()
+                .and_then(|config| config.rules().iter().find_map(|rule| rule.apply_server_side_encryption_by_default()))
+                .map(|default| (default.sse_algorithm().as_str().to_string(), default.kms_master_key_id().map(str::to_string))),
+            Err(e) if e.code() == Some(NO_SUCH_ENCRYPTION_CODE) => None,
+            Err(e) => {
+                tracing::warn!("Failed to get encryption for bucket {}: {:?}", bucket_name, e);
+                return Err(diagnostics::record("s3", "GetBucketEncryption", AwsError::from(aws_sdk_s3::Error::from(e))));
+            }
+        };
+
+        let (algorithm, kms_key_id) = default.map_or((None, None), |(algorithm, key)| (Some(algorithm), key));
+        Ok(BucketEncryptionStatus {
+            bucket: bucket_name.to_string(),
+            region,
+            algorithm,
+            kms_key_id,
+        })
+    }
+
+    /// Default encryption of every bucket in the account. Buckets whose
+    /// encryption can't be read are left out and logged.
+    pub async fn collect_bucket_encryption(&self) -> AwsResult<Vec<BucketEncryptionStatus>> {
+        let listing = with_retry(&self.client.config.retries, "ListBuckets", || async move {
+            single_attempt_s3(&self.client.s3_client)
+                .list_buckets()
+                .send()
+                .await
+                .map_err(|e| -> AwsError {
+                    tracing::error!("Failed to list buckets: {:?}", e);
+                    diagnostics::record("s3", "ListBuckets", AwsError::from(aws_sdk_s3::Error::from(e)))
+                })
+        });
+        let response = with_timeout(self.client.config.timeouts.bucket_operations(), "ListBuckets", listing).await?;
+
+        let mut statuses = Vec::new();
+        for name in response.buckets().iter().filter_map(|bucket| bucket.name()) {
+            match self.get_bucket_encryption(name).await {
+                Ok(status) => statuses.push(status),
+                Err(e) => tracing::warn!("Skipping bucket {} in encryption report: {}", name, e),
+            }
+        }
+        Ok(statuses)
+    }
+
+    /// Make `encryption` the default of a bucket, through a client in the
+    /// bucket's region. A KMS key must be in that region too.
+    pub async fn put_bucket_encryption(&self, bucket_name: &str, encryption: &BucketEncryption) -> AwsResult<String> {
+        let configuration = encryption_configuration(encryption)?;
+        let client = self.bucket_client(bucket_name).await?;
+        let region = client.config.region.clone();
+
+        if let BucketEncryption::SseKms { kms_key_id } = encryption {
+            let key_region = kms_key_region(kms_key_id)?;
+            if key_region != region {
+                return Err(AwsError::ConfigError(format!(
+                    "KMS key is in {} but bucket {} is in {}; use a key from the bucket's region",
+                    key_region, bucket_name, region
+                )));
+            }
+        }
+
+        tracing::info!("Setting default encryption on S3 bucket {} in {}: {:?}", bucket_name, region, encryption);
+        client.s3_client
+            .put_bucket_encryption()
+            .bucket(bucket_name)
+            .server_side_encryption_configuration(configuration)
+            .send()
+            .await
+            .map_err(|e| -> AwsError {
+                tracing::error!("Failed to put encryption for bucket {}: {:?}", bucket_name, e);
+                diagnostics::record("s3", "PutBucketEncryption", AwsError::from(aws_sdk_s3::Error::from(e)))
             })?;
 
-        let config = response.public_access_block_configuration();
-        let (block_public_acls, block_public_policy, ignore_public_acls, restrict_public_buckets) = config
-            .map(|c| (
-                c.block_public_acls().unwrap_or(false),
-                c.block_public_policy().unwrap_or(false),
-                c.ignore_public_acls().unwrap_or(false),
-                c.restrict_public_buckets().unwrap_or(false),
-            ))
-            .unwrap_or((false, false, false, false));
+        Ok(region)
+    }
 
-        Ok(block_public_acls || block_public_policy || ignore_public_acls || restrict_public_buckets)
+    /// Set default encryption on each bucket, carrying on past failures
+    pub async fn enable_default_encryption(&self, bucket_names: &[String], encryption: &BucketEncryption) -> AwsResult<Vec<BucketEncryptionResult>> {
+        encryption_configuration(encryption)?;
+
+        let mut results = Vec::with_capacity(bucket_names.len());
+        for bucket in bucket_names {
+            let result = match self.put_bucket_encryption(bucket, encryption).await {
+                Ok(region) => BucketEncryptionResult { bucket: bucket.clone(), region: Some(region), success: true, error: None },
+                Err(e) => BucketEncryptionResult { bucket: bucket.clone(), region: None, success: false, error: Some(e.to_string()) },
+            };
+            results.push(result);
+        }
+        Ok(results)
     }
 
     /// Create a new S3 bucket with timestamp naming
@@ -309,7 +644,7 @@ impl S3Service {
             .await
             .map_err(|e| -> AwsError {
                 tracing::error!("Failed to list buckets in fallback region {}: {:?}", region, e);
-                AwsError::from(aws_sdk_s3::Error::from(e))
+                diagnostics::record("s3", "CreateBucket", AwsError::from(aws_sdk_s3::Error::from(e)))
             })?;
 
         // Add tags for tracking
@@ -349,7 +684,7 @@ impl S3Service {
             .await
             .map_err(|e| -> AwsError {
                 tracing::warn!("Failed to tag bucket {}: {:?}", bucket_name, e);
-                AwsError::from(aws_sdk_s3::Error::from(e))
+                diagnostics::record("s3", "PutBucketTagging", AwsError::from(aws_sdk_s3::Error::from(e)))
             })?;
 
         Ok(())
@@ -368,13 +703,246 @@ impl S3Service {
             .await
             .map_err(|e| -> AwsError {
                 tracing::error!("Failed to delete S3 bucket {}: {:?}", bucket_name, e);
-                AwsError::from(aws_sdk_s3::Error::from(e))
+                diagnostics::record("s3", "DeleteBucket", AwsError::from(aws_sdk_s3::Error::from(e)))
             })?;
 
         tracing::info!("Successfully deleted S3 bucket: {}", bucket_name);
         Ok(())
     }
 
+    /// Check a bucket exists and is empty, the way a dry run of `delete_bucket`
+    /// would, without deleting it
+    pub async fn check_bucket_deletable(&self, bucket_name: &str) -> AwsResult<()> {
+        tracing::info!("Dry run: checking S3 bucket {} can be deleted", bucket_name);
+
+        match self.bucket_cleanup_status(bucket_name).await? {
+            BucketCleanupStatus::NotEmpty => Err(AwsError::OperationError(format!(
+                "Bucket {} is not empty; S3 only deletes empty buckets",
+                bucket_name
+            ))),
+            BucketCleanupStatus::Protected | BucketCleanupStatus::Empty => Ok(()),
+        }
+    }
+
+    /// Delete every empty, unprotected bucket in the account. With `dry_run`
+    /// set, only reports which buckets would go.
+    pub async fn delete_empty_buckets(&self, dry_run: bool) -> AwsResult<EmptyBucketCleanupReport> {
+        tracing::info!("Looking for empty S3 buckets (dry run: {})", dry_run);
+
+        let listing = with_retry(&self.client.config.retries, "ListBuckets", || async move {
+            single_attempt_s3(&self.client.s3_client)
+                .list_buckets()
+                .send()
+                .await
+                .map_err(|e| -> AwsError {
+                    tracing::error!("Failed to list buckets: {:?}", e);
+                    diagnostics::record("s3", "ListBuckets", AwsError::from(aws_sdk_s3::Error::from(e)))
+                })
+        });
+        let response = with_timeout(self.client.config.timeouts.bucket_operations(), "ListBuckets", listing).await?;
+
+        // Buckets only answer to clients in their own region
+        let mut regional_clients: HashMap<String, AwsClient> = HashMap::new();
+        let mut buckets = Vec::new();
+        for bucket in response.buckets() {
+            let Some(name) = bucket.name() else { continue };
+            let region = bucket.bucket_region().unwrap_or(self.client.primary_region()).to_string();
+            if !regional_clients.contains_key(&region) {
+                let client = if region == self.client.config.region {
+                    self.client.clone()
+                } else {
+                    self.client.for_region(&region).await?
+                };
+                regional_clients.insert(region.clone(), client);
+            }
+            buckets.push((name.to_string(), region));
+        }
+
+        let regions: HashMap<String, String> = buckets.iter().cloned().collect();
+        let names = buckets.into_iter().map(|(name, _)| name).collect();
+        let service_for = |bucket: &str| S3Service::new(regional_clients[&regions[bucket]].clone());
+
+        let report = cleanup_empty_buckets(
+            names,
+            dry_run,
+            |bucket| {
+                let service = service_for(&bucket);
+                async move { service.bucket_cleanup_status(&bucket).await }
+            },
+            |bucket| {
+                let service = service_for(&bucket);
+                async move { service.delete_bucket(&bucket).await }
+            },
+        ).await;
+
+        tracing::info!(
+            "Empty bucket cleanup: {} candidates, {} deleted, {} non-empty, {} protected",
+            report.candidates.len(), report.deleted.len(), report.skipped_non_empty.len(), report.protected.len()
+        );
+        Ok(report)
+    }
+
+    /// Whether a bucket is protected, holds objects (or object versions), or is empty
+    async fn bucket_cleanup_status(&self, bucket_name: &str) -> AwsResult<BucketCleanupStatus> {
+        let s3_client = &self.client.s3_client;
+
+        let tags = match s3_client.get_bucket_tagging().bucket(bucket_name).send().await {
+            Ok(response) => response.tag_set().to_vec(),
+            Err(e) if e.code() == Some(NO_SUCH_TAG_SET_CODE) => Vec::new(),
+            Err(e) => return Err(diagnostics::record("s3", "GetBucketTagging", AwsError::from(aws_sdk_s3::Error::from(e)))),
+        };
+        if is_protected_bucket(&tags) {
+            return Ok(BucketCleanupStatus::Protected);
+        }
+
+        let objects = s3_client
+            .list_objects_v2()
+            .bucket(bucket_name)
+            .max_keys(1)
+            .send()
+            .await
+            .map_err(|e| -> AwsError { diagnostics::record("s3", "ListObjectsV2", AwsError::from(aws_sdk_s3::Error::from(e))) })?;
+        if objects.key_count().unwrap_or(0) > 0 || !objects.contents().is_empty() {
+            return Ok(BucketCleanupStatus::NotEmpty);
+        }
+
+        // Old versions and delete markers keep a versioned bucket from being deleted
+        let versioning = s3_client
+            .get_bucket_versioning()
+            .bucket(bucket_name)
+            .send()
+            .await
+            .map_err(|e| -> AwsError { diagnostics::record("s3", "GetBucketVersioning", AwsError::from(aws_sdk_s3::Error::from(e))) })?;
+        if versioning.status().is_some() {
+            let versions = s3_client
+                .list_object_versions()
+                .bucket(bucket_name)
+                .max_keys(1)
+                .send()
+                .await
+                .map_err(|e| -> AwsError { diagnostics::record("s3", "ListObjectVersions", AwsError::from(aws_sdk_s3::Error::from(e))) })?;
+            if !versions.versions().is_empty() || !versions.delete_markers().is_empty() {
+                return Ok(BucketCleanupStatus::NotEmpty);
+            }
+        }
+
+        Ok(BucketCleanupStatus::Empty)
+    }
+
+    /// URL that lets anyone holding it GET or PUT one object until it expires
+    pub async fn generate_presigned_url(&self, bucket_name: &str, key: &str, expires_secs: u64, method: PresignMethod) -> AwsResult<PresignedUrl> {
+        tracing::info!("Presigning {:?} URL for s3://{}/{} valid for {}s", method, bucket_name, key, expires_secs);
+
+        let config = PresigningConfig::expires_in(Duration::from_secs(expires_secs))
+            .map_err(|e| AwsError::ConfigError(format!("Invalid presigned URL expiry: {}", e)))?;
+        let expires_at = Utc::now() + chrono::Duration::seconds(expires_secs as i64);
+
+        let s3_client = &self.client.s3_client;
+        let request = match method {
+            PresignMethod::Get => s3_client.get_object().bucket(bucket_name).key(key).presigned(config).await
+                .map_err(|e| AwsError::from(aws_sdk_s3::Error::from(e))),
+            PresignMethod::Put => s3_client.put_object().bucket(bucket_name).key(key).presigned(config).await
+                .map_err(|e| AwsError::from(aws_sdk_s3::Error::from(e))),
+        }
+        .inspect_err(|e| tracing::error!("Failed to presign URL for s3://{}/{}: {}", bucket_name, key, e))?;
+
+        Ok(PresignedUrl {
+            url: request.uri().to_string(),
+            method: request.method().to_string(),
+            expires_at: expires_at.to_rfc3339(),
+        })
+    }
+
+    /// Lifecycle rules configured on a bucket. A bucket without a lifecycle
+    /// configuration has no rules.
+    pub async fn get_lifecycle(&self, bucket_name: &str) -> AwsResult<Vec<LifecycleRule>> {
+        tracing::debug!("Getting lifecycle rules for S3 bucket: {}", bucket_name);
+
+        match self.client.s3_client.get_bucket_lifecycle_configuration().bucket(bucket_name).send().await {
+            Ok(response) => Ok(response.rules().iter().map(lifecycle_rule_from_sdk).collect()),
+            Err(e) if e.code() == Some(NO_SUCH_LIFECYCLE_CODE) => Ok(Vec::new()),
+            Err(e) => {
+                tracing::error!("Failed to get lifecycle rules for S3 bucket {}: {:?}", bucket_name, e);
+                Err(diagnostics::record("s3", "GetBucketLifecycleConfiguration", AwsError::from(aws_sdk_s3::Error::from(e))))
+            }
+        }
+    }
+
+    /// Replace a bucket's lifecycle rules. An empty rule set removes the
+    /// lifecycle configuration.
+    pub async fn put_lifecycle(&self, bucket_name: &str, rules: &[LifecycleRule]) -> AwsResult<()> {
+        tracing::info!("Setting {} lifecycle rules on S3 bucket: {}", rules.len(), bucket_name);
+
+        let s3_client = &self.client.s3_client;
+
+        if rules.is_empty() {
+            s3_client
+                .delete_bucket_lifecycle()
+                .bucket(bucket_name)
+                .send()
+                .await
+                .map_err(|e| -> AwsError {
+                    tracing::error!("Failed to delete lifecycle rules for S3 bucket {}: {:?}", bucket_name, e);
+                    diagnostics::record("s3", "DeleteBucketLifecycle", AwsError::from(aws_sdk_s3::Error::from(e)))
+                })?;
+            return Ok(());
+        }
+
+        let sdk_rules = rules.iter().map(lifecycle_rule_to_sdk).collect::<AwsResult<Vec<_>>>()?;
+        let configuration = BucketLifecycleConfiguration::builder()
+            .set_rules(Some(sdk_rules))
+            .build()
+            .map_err(|e| AwsError::ConfigError(format!("Invalid lifecycle configuration: {}", e)))?;
+
+        s3_client
+            .put_bucket_lifecycle_configuration()
+            .bucket(bucket_name)
+            .lifecycle_configuration(configuration)
+            .send()
+            .await
+            .map_err(|e| -> AwsError {
+                tracing::error!("Failed to put lifecycle rules for S3 bucket {}: {:?}", bucket_name, e);
+                diagnostics::record("s3", "PutBucketLifecycleConfiguration", AwsError::from(aws_sdk_s3::Error::from(e)))
+            })?;
+
+        tracing::info!("Successfully updated lifecycle rules for S3 bucket: {}", bucket_name);
+        Ok(())
+    }
+
+    /// Estimate what `rule` would transition or expire among the bucket's
+    /// current objects. Listing stops at `LIFECYCLE_PREVIEW_MAX_OBJECTS`.
+    pub async fn preview_lifecycle_impact(&self, bucket_name: &str, rule: &LifecycleRule) -> AwsResult<LifecycleImpactPreview> {
+        tracing::info!("Previewing lifecycle rule on S3 bucket {} (prefix '{}')", bucket_name, rule.prefix);
+
+        // Reject rules S3 would refuse before listing anything
+        lifecycle_rule_to_sdk(rule)?;
+
+        let mut pages = self.client.s3_client
+            .list_objects_v2()
+            .bucket(bucket_name)
+            .set_prefix(Some(rule.prefix.clone()).filter(|p| !p.is_empty()))
+            .into_paginator()
+            .send();
+
+        let mut objects = Vec::new();
+        let mut sample_capped = false;
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(|e| -> AwsError {
+                tracing::error!("Failed to list objects in S3 bucket {}: {:?}", bucket_name, e);
+                diagnostics::record("s3", "ListObjectsV2", AwsError::from(aws_sdk_s3::Error::from(e)))
+            })?;
+            objects.extend(page.contents().iter().cloned());
+
+            if objects.len() >= LIFECYCLE_PREVIEW_MAX_OBJECTS {
+                sample_capped = page.is_truncated().unwrap_or(false) || objects.len() > LIFECYCLE_PREVIEW_MAX_OBJECTS;
+                objects.truncate(LIFECYCLE_PREVIEW_MAX_OBJECTS);
+                break;
+            }
+        }
+
+        Ok(lifecycle_impact(bucket_name, rule, &objects, Utc::now(), sample_capped))
+    }
+
     /// Get bucket location
     async fn get_bucket_location(&self, bucket_name: &str) -> AwsResult<String> {
         let s3_client = &self.client.s3_client;
@@ -384,7 +952,7 @@ impl S3Service {
             .bucket(bucket_name)
             .send()
             .await
-            .map_err(|e| -> AwsError { AwsError::from(aws_sdk_s3::Error::from(e)) })?;
+            .map_err(|e| -> AwsError { diagnostics::record("s3", "GetBucketLocation", AwsError::from(aws_sdk_s3::Error::from(e))) })?;
 
         let region = response.location_constraint()
             .map(|rc| format!("{:?}", rc).to_lowercase())
@@ -424,4 +992,406 @@ impl S3Service {
 
         Ok(bucket)
     }
-}
\ No newline at end of file
+}
+
+/// A bucket is protected when it carries the protective tag with any value other than "false"
+pub fn is_protected_bucket(tags: &[Tag]) -> bool {
+    tags.iter().any(|tag| tag.key() == BUCKET_PROTECTED_TAG_KEY && !tag.value().eq_ignore_ascii_case("false"))
+}
+
+/// Delete the buckets `inspect` reports as empty through `delete`, or only
+/// report them when `dry_run` is set
+pub async fn cleanup_empty_buckets<I, IFut, D, DFut>(
+    buckets: Vec<String>,
+    dry_run: bool,
+    mut inspect: I,
+    mut delete: D,
+) -> EmptyBucketCleanupReport
+where
+    I: FnMut(String) -> IFut,
+    IFut: Future<Output = AwsResult<BucketCleanupStatus>>,
+    D: FnMut(String) -> DFut,
+    DFut: Future<Output = AwsResult<()>>,
+{
+    let mut report = EmptyBucketCleanupReport {
+        dry_run,
+        candidates: Vec::new(),
+        deleted: Vec::new(),
+        skipped_non_empty: Vec::new(),
+        protected: Vec::new(),
+        failed: Vec::new(),
+    };
+
+    for bucket in buckets {
+        match inspect(bucket.clone()).await {
+            Ok(BucketCleanupStatus::Protected) => report.protected.push(bucket),
+            Ok(BucketCleanupStatus::NotEmpty) => report.skipped_non_empty.push(bucket),
+            Ok(BucketCleanupStatus::Empty) => report.candidates.push(bucket),
+            Err(e) => report.failed.push(BucketCleanupFailure { bucket, error: e.to_string() }),
+        }
+    }
+
+    if !dry_run {
+        for bucket in &report.candidates {
+            match delete(bucket.clone()).await {
+                Ok(()) => report.deleted.push(bucket.clone()),
+                Err(e) => report.failed.push(BucketCleanupFailure { bucket: bucket.clone(), error: e.to_string() }),
+            }
+        }
+    }
+
+    report
+}
+
+/// Map an SDK lifecycle rule to our LifecycleRule type
+pub fn lifecycle_rule_from_sdk(rule: &S3LifecycleRule) -> LifecycleRule {
+    #[allow(deprecated)]
+    let prefix = rule.filter()
+        .and_then(|f| f.prefix())
+        .or_else(|| rule.prefix())
+        .unwrap_or_default()
+        .to_string();
+
+    LifecycleRule {
+        id: rule.id().map(|s| s.to_string()),
+        prefix,
+        enabled: *rule.status() == ExpirationStatus::Enabled,
+        transitions: rule.transitions()
+            .iter()
+            .filter_map(|t| Some(LifecycleTransition {
+                days: t.days()?,
+                storage_class: t.storage_class()?.as_str().to_string(),
+            }))
+            .collect(),
+        expiration_days: rule.expiration().and_then(|e| e.days()),
+    }
+}
+
+/// Build the SDK lifecycle rule for a LifecycleRule, rejecting storage
+/// classes S3 can't transition to
+pub fn lifecycle_rule_to_sdk(rule: &LifecycleRule) -> AwsResult<S3LifecycleRule> {
+    let transitions = rule.transitions
+        .iter()
+        .map(|t| {
+            if !TransitionStorageClass::values().contains(&t.storage_class.as_str()) {
+                return Err(AwsError::ConfigError(format!("Unsupported transition storage class: {}", t.storage_class)));
+            }
+            Ok(Transition::builder()
+                .days(t.days)
+                .storage_class(TransitionStorageClass::from(t.storage_class.as_str()))
+                .build())
+        })
+        .collect::<AwsResult<Vec<_>>>()?;
+
+    S3LifecycleRule::builder()
+        .set_id(rule.id.clone())
+        .filter(LifecycleRuleFilter::builder().prefix(&rule.prefix).build())
+        .status(if rule.enabled { ExpirationStatus::Enabled } else { ExpirationStatus::Disabled })
+        .set_transitions(if transitions.is_empty() { None } else { Some(transitions) })
+        .set_expiration(rule.expiration_days.map(|days| LifecycleExpiration::builder().days(days).build()))
+        .build()
+        .map_err(|e| AwsError::ConfigError(format!("Invalid lifecycle rule: {}", e)))
+}
+
+/// Apply a lifecycle rule's prefix and age criteria to listed objects. An
+/// object past the expiration age is expired; otherwise it moves to the class
+/// of the latest transition its age has reached.
+pub fn lifecycle_impact(bucket: &str, rule: &LifecycleRule, objects: &[Object], now: DateTime<Utc>, sample_capped: bool) -> LifecycleImpactPreview {
+    let mut transitions: Vec<LifecycleTransitionImpact> = Vec::new();
+    let mut objects_scanned = 0;
+    let mut bytes_scanned = 0;
+    let mut objects_expired = 0;
+    let mut bytes_expired = 0;
+    let mut monthly_cost_change = 0.0;
+
+    for object in objects.iter().filter(|o| o.key().unwrap_or_default().starts_with(&rule.prefix)) {
+        let size = object.size().unwrap_or(0);
+        objects_scanned += 1;
+        bytes_scanned += size;
+
+        let Some(age_days) = object.last_modified().map(|modified| (now.timestamp() - modified.secs()) / 86_400) else {
+            continue;
+        };
+        let current_class = object.storage_class().map(|c| c.as_str()).unwrap_or("STANDARD");
+        let size_gb = size as f64 / BYTES_PER_GB;
+
+        if rule.expiration_days.is_some_and(|days| age_days >= days as i64) {
+            objects_expired += 1;
+            bytes_expired += size;
+            monthly_cost_change -= size_gb * storage_class_price_per_gb_month(current_class);
+            continue;
+        }
+
+        let Some(transition) = rule.transitions
+            .iter()
+            .filter(|t| age_days >= t.days as i64)
+            .max_by_key(|t| t.days)
+        else {
+            continue;
+        };
+        if transition.storage_class == current_class {
+            continue;
+        }
+
+        monthly_cost_change += size_gb
+            * (storage_class_price_per_gb_month(&transition.storage_class) - storage_class_price_per_gb_month(current_class));
+        match transitions.iter_mut().find(|t| t.storage_class == transition.storage_class) {
+            Some(impact) => {
+                impact.objects += 1;
+                impact.bytes += size;
+            }
+            None => transitions.push(LifecycleTransitionImpact {
+                storage_class: transition.storage_class.clone(),
+                objects: 1,
+                bytes: size,
+            }),
+        }
+    }
+
+    let note = if sample_capped {
+        format!(
+            "Estimate from the first {} objects only; the bucket holds more. Costs use us-east-1 storage prices and ignore transition request fees.",
+            objects_scanned
+        )
+    } else {
+        "Estimate from current object ages; costs use us-east-1 storage prices and ignore transition request fees.".to_string()
+    };
+
+    LifecycleImpactPreview {
+        bucket: bucket.to_string(),
+        prefix: rule.prefix.clone(),
+        objects_scanned,
+        bytes_scanned,
+        sample_capped,
+        transitions,
+        objects_expired,
+        bytes_expired,
+        monthly_cost_change,
+        note,
+    }
+}
+
+/// The four settings of a public access block configuration; missing ones are off
+pub fn public_access_block_flags(config: Option<&PublicAccessBlockConfiguration>) -> PublicAccessBlockFlags {
+    config
+        .map(|c| PublicAccessBlockFlags {
+            block_public_acls: c.block_public_acls().unwrap_or(false),
+            ignore_public_acls: c.ignore_public_acls().unwrap_or(false),
+            block_public_policy: c.block_public_policy().unwrap_or(false),
+            restrict_public_buckets: c.restrict_public_buckets().unwrap_or(false),
+        })
+        .unwrap_or_default()
+}
+
+/// Check that a bucket policy is a JSON object with at least one statement,
+/// each carrying an Effect
+pub fn validate_bucket_policy(policy: &str) -> AwsResult<()> {
+    let document: serde_json::Value = serde_json::from_str(policy)
+        .map_err(|e| AwsError::ConfigError(format!("Bucket policy is not valid JSON: {}", e)))?;
+
+    let statements = match document.get("Statement") {
+        Some(serde_json::Value::Array(statements)) => statements.clone(),
+        Some(statement @ serde_json::Value::Object(_)) => vec![statement.clone()],
+        _ => return Err(AwsError::ConfigError("Bucket policy must contain a Statement".to_string())),
+    };
+
+    if statements.is_empty() {
+        return Err(AwsError::ConfigError("Bucket policy must contain at least one statement".to_string()));
+    }
+    for (i, statement) in statements.iter().enumerate() {
+        match statement.get("Effect").and_then(|e| e.as_str()) {
+            Some("Allow" | "Deny") => {}
+            _ => return Err(AwsError::ConfigError(format!("Statement {} needs an Effect of Allow or Deny", i + 1))),
+        }
+    }
+
+    Ok(())
+}
+
+/// The PutBucketEncryption configuration for `encryption`. KMS keys are
+/// validated and get an S3 bucket key, which cuts KMS request costs.
+pub fn encryption_configuration(encryption: &BucketEncryption) -> AwsResult<ServerSideEncryptionConfiguration> {
+    let (default, bucket_key_enabled) = match encryption {
+        BucketEncryption::SseS3 => (ServerSideEncryptionByDefault::builder().sse_algorithm(ServerSideEncryption::Aes256), false),
+        BucketEncryption::SseKms { kms_key_id } => {
+            validate_kms_key_arn(kms_key_id)?;
+            let default = ServerSideEncryptionByDefault::builder()
+                .sse_algorithm(ServerSideEncryption::AwsKms)
+                .kms_master_key_id(kms_key_id);
+            (default, true)
+        }
+    };
+    let default = default
+        .build()
+        .map_err(|e| AwsError::ConfigError(format!("Invalid encryption settings: {}", e)))?;
+
+    ServerSideEncryptionConfiguration::builder()
+        .rules(
+            ServerSideEncryptionRule::builder()
+                .apply_server_side_encryption_by_default(default)
+                .bucket_key_enabled(bucket_key_enabled)
+                .build(),
+        )
+        .build()
+        .map_err(|e| AwsError::ConfigError(format!("Invalid encryption configuration: {}", e)))
+}
+
+/// Check a KMS key ARN: `arn:<partition>:kms:<region>:<account>:key/<id>` or
+/// an `alias/<name>` in place of the key
+pub fn validate_kms_key_arn(arn: &str) -> AwsResult<()> {
+    kms_key_region(arn).map(|_| ())
+}
+
+/// Region of a KMS key ARN, validating the ARN along the way
+fn kms_key_region(arn: &str) -> AwsResult<&str> {
+    let invalid = |reason: &str| AwsError::ConfigError(format!("Invalid KMS key ARN '{}': {}", arn, reason));
+
+    let parts: Vec<&str> = arn.splitn(6, ':').collect();
+    let [prefix, partition, service, region, account, resource] = parts.as_slice() else {
+        return Err(invalid("expected arn:aws:kms:<region>:<account>:key/<id>"));
+    };
+    if *prefix != "arn" || !partition.starts_with("aws") {
+        return Err(invalid("it must start with arn:aws"));
+    }
+    if *service != "kms" {
+        return Err(invalid("it is not a KMS ARN"));
+    }
+    if region.is_empty() {
+        return Err(invalid("it has no region"));
+    }
+    if account.len() != 12 || !account.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid("the account id must be 12 digits"));
+    }
+    match resource.split_once('/') {
+        Some(("key" | "alias", id)) if !id.is_empty() => Ok(*region),
+        _ => Err(invalid("expected key/<id> or alias/<name>")),
+    }
+}
+
+/// Content type for a file, from its extension
+pub fn content_type_for_path(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "txt" | "log" => "text/plain",
+        "csv" => "text/csv",
+        "md" => "text/markdown",
+        "yaml" | "yml" => "application/yaml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" | "tgz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "mp4" => "video/mp4",
+        "mp3" => "audio/mpeg",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Upload a file in parts, aborting the upload if any step fails so S3
+/// doesn't keep (and bill for) orphaned parts. Returns the object's ETag.
+async fn multipart_upload(
+    s3_client: &aws_sdk_s3::Client,
+    bucket_name: &str,
+    key: &str,
+    src_path: &Path,
+    content_type: &str,
+) -> AwsResult<String> {
+    let upload = s3_client
+        .create_multipart_upload()
+        .bucket(bucket_name)
+        .key(key)
+        .content_type(content_type)
+        .send()
+        .await
+        .map_err(|e| -> AwsError { diagnostics::record("s3", "CreateMultipartUpload", AwsError::from(aws_sdk_s3::Error::from(e))) })?;
+    let upload_id = upload
+        .upload_id()
+        .ok_or_else(|| AwsError::OperationError(format!("No upload id returned for {}", key)))?
+        .to_string();
+
+    match upload_parts(s3_client, bucket_name, key, &upload_id, src_path).await {
+        Ok(etag) => Ok(etag),
+        Err(e) => {
+            tracing::error!("Multipart upload of {} to bucket {} failed: {}", key, bucket_name, e);
+            if let Err(abort_error) = s3_client
+                .abort_multipart_upload()
+                .bucket(bucket_name)
+                .key(key)
+                .upload_id(&upload_id)
+                .send()
+                .await
+            {
+                tracing::warn!("Failed to abort multipart upload {}: {:?}", upload_id, abort_error);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Send the file's parts for an open multipart upload, then complete it
+async fn upload_parts(
+    s3_client: &aws_sdk_s3::Client,
+    bucket_name: &str,
+    key: &str,
+    upload_id: &str,
+    src_path: &Path,
+) -> AwsResult<String> {
+    let read_error = |e: std::io::Error| AwsError::OperationError(format!("Failed to read {}: {}", src_path.display(), e));
+    let mut file = tokio::fs::File::open(src_path).await.map_err(read_error)?;
+    let mut parts = Vec::new();
+
+    for part_number in 1.. {
+        let mut buffer = Vec::new();
+        (&mut file).take(MULTIPART_PART_SIZE_BYTES).read_to_end(&mut buffer).await.map_err(read_error)?;
+        if buffer.is_empty() {
+            break;
+        }
+
+        let response = s3_client
+            .upload_part()
+            .bucket(bucket_name)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(ByteStream::from(buffer))
+            .send()
+            .await
+            .map_err(|e| -> AwsError { diagnostics::record("s3", "UploadPart", AwsError::from(aws_sdk_s3::Error::from(e))) })?;
+
+        parts.push(
+            CompletedPart::builder()
+                .part_number(part_number)
+                .set_e_tag(response.e_tag().map(str::to_string))
+                .build(),
+        );
+    }
+
+    let response = s3_client
+        .complete_multipart_upload()
+        .bucket(bucket_name)
+        .key(key)
+        .upload_id(upload_id)
+        .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+        .send()
+        .await
+        .map_err(|e| -> AwsError { diagnostics::record("s3", "CompleteMultipartUpload", AwsError::from(aws_sdk_s3::Error::from(e))) })?;
+
+    Ok(response.e_tag().unwrap_or_default().to_string())
+}
+
@@ -20,10 +20,19 @@ pub struct AwsInstance {
     pub platform: String, // Always "aws"
     pub cpu_count: i32,
     pub memory_gb: f64,
+    /// Total size of the attached EBS volumes
     pub storage_gb: f64,
+    #[serde(default)]
+    pub volumes: Vec<AwsVolume>,
     pub network_performance: String,
     pub public_ip: Option<String>,
     pub private_ip: Option<String>,
+    /// Primary IPv6 address, for instances in dual-stack or IPv6-only subnets
+    #[serde(default)]
+    pub ipv6_address: Option<String>,
+    /// Attached network interfaces, primary (device index 0) first
+    #[serde(default)]
+    pub network_interfaces: Vec<AwsNetworkInterface>,
     pub security_groups: Vec<AwsSecurityGroup>,
     pub key_pairs: Vec<String>,
     pub tags: std::collections::HashMap<String, String>,
@@ -32,13 +41,486 @@ pub struct AwsInstance {
     pub ebs_optimized: bool,
     pub virtualization_type: String,
     pub architecture: String,
+    /// "on-demand" or "spot", from the instance's InstanceLifecycle
+    #[serde(default = "default_lifecycle")]
+    pub lifecycle: String,
+    /// Billing platform, e.g. "Linux/UNIX" or "Windows", from PlatformDetails
+    #[serde(default)]
+    pub platform_details: Option<String>,
+    /// Whether a reservation covers the instance; see `reservations::annotate_coverage`
+    #[serde(default)]
+    pub coverage: InstanceCoverage,
+}
+
+fn default_lifecycle() -> String {
+    PurchaseOption::OnDemand.lifecycle().to_string()
+}
+
+/// Optional narrowing of an EC2 instance collection. Empty lists, an empty
+/// tag map and a missing tag match everything.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InstanceFilters {
+    #[serde(default)]
+    pub states: Vec<String>,
+    #[serde(default)]
+    pub tag: Option<TagFilter>,
+    /// Exact tag values an instance must all carry, e.g. `Environment=prod`
+    #[serde(default)]
+    pub tags: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    pub instance_types: Vec<String>,
+}
+
+impl InstanceFilters {
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty() && self.tag.is_none() && self.tags.is_empty() && self.instance_types.is_empty()
+    }
+}
+
+/// Tag an instance must carry; without a value any value matches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagFilter {
+    pub key: String,
+    #[serde(default)]
+    pub value: Option<String>,
+}
+
+/// A maintenance or retirement event AWS has scheduled for an instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceScheduledEvent {
+    pub instance_id: String,
+    pub region: String,
+    pub event_id: Option<String>,
+    /// instance-reboot, system-reboot, system-maintenance, instance-retirement or instance-stop
+    pub code: String,
+    pub description: String,
+    /// Start of the event window
+    pub not_before: Option<String>,
+    /// End of the event window
+    pub not_after: Option<String>,
+    /// Latest start the event can be rescheduled to
+    pub not_before_deadline: Option<String>,
+    /// The instance will be stopped or terminated; the user must act
+    pub is_retirement: bool,
+}
+
+/// Security group as listed for an account, with rule counts per direction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AwsSecurityGroupInfo {
+    pub group_id: String,
+    pub group_name: String,
+    pub description: Option<String>,
+    pub vpc_id: Option<String>,
+    pub region: String,
+    pub ingress_rules: usize,
+    pub egress_rules: usize,
+    /// SecurityConfig the group was created from, if any
+    pub security_config_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwsVpc {
+    pub vpc_id: String,
+    /// From the `Name` tag
+    pub name: Option<String>,
+    /// Primary IPv4 block first, then any associated ones
+    pub cidr_blocks: Vec<String>,
+    pub is_default: bool,
+    pub state: String,
+    pub region: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwsSubnet {
+    pub subnet_id: String,
+    pub vpc_id: String,
+    /// From the `Name` tag
+    pub name: Option<String>,
+    pub cidr_block: Option<String>,
+    pub availability_zone: String,
+    pub available_ip_addresses: i32,
+    /// Whether instances launched here get a public IPv4 address by default
+    pub map_public_ip_on_launch: bool,
+    pub region: String,
+}
+
+/// How waiting for an instance state transition ended
+#[derive(Debug, Clone)]
+pub enum InstanceStateWait {
+    /// The instance reached the target state, as described at that point
+    Reached(AwsInstance),
+    /// Polling gave up; the instance was last seen in `last_state`
+    TimedOut { last_state: String },
+}
+
+/// How a request to change an instance's type ended
+#[derive(Debug, Clone)]
+pub enum InstanceResize {
+    /// The new type was applied; the instance as described afterwards
+    Resized(AwsInstance),
+    /// The instance is running and the caller didn't allow stopping it
+    StopRequired { state: String },
+    /// The type isn't offered in the instance's availability zone
+    TypeUnavailable { instance_type: String, availability_zone: String },
+}
+
+/// State change applied to many instances with one API call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkInstanceOperation {
+    Start,
+    Stop,
+    Terminate,
+}
+
+impl BulkInstanceOperation {
+    /// Parse "start", "stop" or "terminate"
+    pub fn parse(value: &str) -> crate::aws::AwsResult<Self> {
+        match value {
+            "start" => Ok(Self::Start),
+            "stop" => Ok(Self::Stop),
+            "terminate" => Ok(Self::Terminate),
+            other => Err(crate::aws::AwsError::ConfigError(format!(
+                "Unsupported instance operation '{}'; expected start, stop or terminate",
+                other
+            ))),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BulkInstanceOperation::Start => "start",
+            BulkInstanceOperation::Stop => "stop",
+            BulkInstanceOperation::Terminate => "terminate",
+        }
+    }
+}
+
+/// An instance's state before and right after a start, stop or terminate
+/// call, as the call reported it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstanceStateTransition {
+    pub instance_id: String,
+    pub previous_state: String,
+    pub current_state: String,
+}
+
+/// Instances of one call in a bulk operation that failed together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BulkOperationFailure {
+    pub instance_ids: Vec<String>,
+    pub error: String,
+    /// `error` categorized for the command's response
+    #[serde(skip)]
+    pub api_error: Option<crate::api_error::ApiError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BulkInstanceOperationReport {
+    pub operation: BulkInstanceOperation,
+    pub transitions: Vec<InstanceStateTransition>,
+    pub failed: Vec<BulkOperationFailure>,
+}
+
+/// Elastic network interface attached to an instance
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AwsNetworkInterface {
+    pub eni_id: String,
+    /// Primary private address first
+    pub private_ips: Vec<String>,
+    pub ipv6_addresses: Vec<String>,
+    pub subnet_id: Option<String>,
+    /// Ids of the security groups on this interface
+    pub security_groups: Vec<String>,
+}
+
+/// EBS volume attached to an instance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwsVolume {
+    pub volume_id: String,
+    pub size_gb: i32,
+    pub volume_type: String,
+    pub encrypted: bool,
+}
+
+/// An EBS volume in the account, attached or not
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwsEbsVolume {
+    pub volume_id: String,
+    pub region: String,
+    pub availability_zone: String,
+    pub size_gb: i32,
+    pub volume_type: String,
+    /// creating, available, in-use, deleting, deleted or error
+    pub state: String,
+    pub encrypted: bool,
+    pub iops: Option<i32>,
+    pub attachments: Vec<EbsAttachment>,
+    pub tags: std::collections::HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EbsAttachment {
+    pub instance_id: String,
+    /// Device name the instance sees, such as /dev/sdf
+    pub device: String,
+    /// attaching, attached, detaching or detached
+    pub state: String,
+}
+
+/// A new EBS volume to create
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CreateVolumeRequest {
+    /// Volumes can only attach to instances in the same availability zone
+    pub availability_zone: String,
+    pub size_gb: i32,
+    /// gp3, gp2 or io2
+    #[serde(default = "default_root_volume_type")]
+    pub volume_type: String,
+    #[serde(default)]
+    pub encrypted: bool,
+    #[serde(default)]
+    pub iops: Option<i32>,
+    /// Value for the Name tag
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// An EBS snapshot owned by the account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwsEbsSnapshot {
+    pub snapshot_id: String,
+    pub volume_id: Option<String>,
+    pub region: String,
+    /// pending, completed, error, recoverable or recovering
+    pub state: String,
+    /// Percent copied, only while the snapshot is pending
+    pub progress_percent: Option<u8>,
+    pub volume_size_gb: i32,
+    pub description: Option<String>,
+    pub start_time: Option<String>,
+    pub encrypted: bool,
+    pub tags: std::collections::HashMap<String, String>,
+}
+
+/// A machine image from the AMI catalog or the account's own images
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwsAmi {
+    pub image_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// x86_64 or arm64
+    pub architecture: String,
+    pub creation_date: String,
+    pub owner_id: String,
+    /// Catalog distro key, such as "ubuntu-22.04"; `None` for the account's own images
+    pub distro: Option<String>,
+}
+
+/// Which images list_amis returns
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AmiListOptions {
+    /// Catalog distro keys; all known distros when empty
+    #[serde(default)]
+    pub distros: Vec<String>,
+    #[serde(default)]
+    pub architecture: Option<String>,
+    /// Include images the account owns
+    #[serde(default)]
+    pub include_own: bool,
+}
+
+/// Root EBS volume to launch an instance with instead of the AMI's default
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RootVolumeSpec {
+    pub size_gb: i32,
+    /// gp3, gp2 or io2
+    #[serde(default = "default_root_volume_type")]
+    pub volume_type: String,
+    #[serde(default)]
+    pub encrypted: bool,
+    /// Provisioned IOPS for gp3 and io2; AWS defaults apply when unset
+    #[serde(default)]
+    pub iops: Option<i32>,
+}
+
+fn default_root_volume_type() -> String {
+    "gp3".to_string()
+}
+
+impl RootVolumeSpec {
+    /// A gp3 root volume of `size_gb`
+    pub fn gp3(size_gb: i32) -> Self {
+        Self {
+            size_gb,
+            volume_type: default_root_volume_type(),
+            encrypted: false,
+            iops: None,
+        }
+    }
+}
+
+/// Optional settings for an EC2 launch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LaunchOptions {
+    /// Cloud-init or shell script run on first boot
+    pub user_data: Option<String>,
+    pub root_volume: Option<RootVolumeSpec>,
+    /// VPC the instance is meant to land in; the subnet must belong to it
+    #[serde(default)]
+    pub vpc_id: Option<String>,
+    /// Subnet to launch into; without one EC2 uses the default VPC
+    #[serde(default)]
+    pub subnet_id: Option<String>,
+    #[serde(default)]
+    pub purchase_option: PurchaseOption,
+    /// Highest hourly price in USD to pay for a spot instance; without one
+    /// AWS caps it at the on-demand price
+    #[serde(default)]
+    pub spot_max_price: Option<String>,
+}
+
+/// How an EC2 launch is billed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurchaseOption {
+    #[default]
+    OnDemand,
+    Spot,
+}
+
+impl PurchaseOption {
+    /// Value stored in the instances table's lifecycle column
+    pub fn lifecycle(self) -> &'static str {
+        match self {
+            PurchaseOption::OnDemand => "on-demand",
+            PurchaseOption::Spot => "spot",
+        }
+    }
+}
+
+/// One DescribeSpotPriceHistory entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwsSpotPrice {
+    pub instance_type: String,
+    pub availability_zone: String,
+    /// Hourly price in USD
+    pub price: f64,
+    pub timestamp: Option<String>,
+}
+
+/// What an instance is billed as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceCoverage {
+    #[default]
+    OnDemand,
+    Reserved,
+    Spot,
+}
+
+/// An active reserved instance purchase, from DescribeReservedInstances
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AwsReservedInstance {
+    pub reserved_instances_id: String,
+    pub instance_type: String,
+    pub region: String,
+    /// Set for zonal reservations; regional ones apply in any zone
+    pub availability_zone: Option<String>,
+    pub instance_count: i32,
+    /// Platform the reservation is for, e.g. "Linux/UNIX (Amazon VPC)"
+    pub product_description: String,
+    pub offering_type: String,
+    pub end: Option<String>,
+}
+
+/// Hardware of an instance type, from DescribeInstanceTypes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstanceTypeSpec {
+    pub instance_type: String,
+    pub vcpus: i32,
+    pub memory_gb: f64,
+    pub network_performance: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AwsSecurityGroup {
     pub group_id: String,
     pub group_name: String,
+    /// From DescribeSecurityGroups; None when the group couldn't be described
     pub description: Option<String>,
+    #[serde(default)]
+    pub ingress_rules: Option<usize>,
+    #[serde(default)]
+    pub egress_rules: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AwsElasticIp {
+    pub allocation_id: String,
+    pub public_ip: String,
+    pub region: String,
+    pub domain: String,
+    pub association_id: Option<String>,
+    pub instance_id: Option<String>,
+    pub tags: std::collections::HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EipReclaimReport {
+    pub dry_run: bool,
+    pub candidates: Vec<AwsElasticIp>,
+    pub released: Vec<String>,
+    pub failed: Vec<EipReleaseFailure>,
+    pub estimated_monthly_savings_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EipReleaseFailure {
+    pub allocation_id: String,
+    pub error: String,
+}
+
+/// Outcome of collecting resources from a single region
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionResult {
+    pub region: String,
+    pub success: bool,
+    pub count: usize,
+    pub error: Option<String>,
+}
+
+/// Resources collected across several regions, with a result per region
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionalCollection<T> {
+    pub items: Vec<T>,
+    pub regions: Vec<RegionResult>,
+}
+
+/// EC2 key pair generated at launch; the private key is only returned once, so
+/// it is never serialized and goes straight to the credential store (or back
+/// to the user, if storing it fails)
+#[derive(Debug, Clone)]
+pub struct GeneratedKeyPair {
+    pub key_name: String,
+    pub key_fingerprint: String,
+    pub private_key: String,
+}
+
+/// An instance launched with a generated key pair. If the private key couldn't
+/// be stored, the instance still runs and `unstored_private_key` carries the
+/// only copy of the key, to be handed to the user once.
+#[derive(Debug, Clone)]
+pub struct KeyPairLaunch {
+    pub instance_id: String,
+    pub key_name: String,
+    pub unstored_private_key: Option<UnstoredPrivateKey>,
+}
+
+#[derive(Debug, Clone)]
+pub struct UnstoredPrivateKey {
+    pub private_key: String,
+    pub error: String,
 }
 
 // ============================================================================
@@ -57,7 +539,159 @@ pub struct AwsBucket {
     pub storage_class: String,
     pub versioning_enabled: bool,
     pub encryption: Option<String>,
+    /// Any of the public access block settings is on
     pub public_access_block: bool,
+    #[serde(default)]
+    pub public_access_block_flags: PublicAccessBlockFlags,
+}
+
+/// The four S3 public access block settings of a bucket
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicAccessBlockFlags {
+    pub block_public_acls: bool,
+    pub ignore_public_acls: bool,
+    pub block_public_policy: bool,
+    pub restrict_public_buckets: bool,
+}
+
+impl PublicAccessBlockFlags {
+    /// Every setting on, blocking all public access
+    pub fn all() -> Self {
+        Self {
+            block_public_acls: true,
+            ignore_public_acls: true,
+            block_public_policy: true,
+            restrict_public_buckets: true,
+        }
+    }
+
+    pub fn any(&self) -> bool {
+        self.block_public_acls || self.ignore_public_acls || self.block_public_policy || self.restrict_public_buckets
+    }
+}
+
+/// Default server-side encryption for a bucket
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BucketEncryption {
+    /// S3-managed keys (AES256)
+    SseS3,
+    /// A KMS key, given by its ARN; it must be in the bucket's region
+    SseKms { kms_key_id: String },
+}
+
+/// A bucket's default encryption, from GetBucketEncryption
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketEncryptionStatus {
+    pub bucket: String,
+    pub region: String,
+    /// "AES256", "aws:kms" or "aws:kms:dsse"; None without default encryption
+    pub algorithm: Option<String>,
+    pub kms_key_id: Option<String>,
+}
+
+impl BucketEncryptionStatus {
+    pub fn is_encrypted(&self) -> bool {
+        self.algorithm.is_some()
+    }
+}
+
+/// Outcome of setting default encryption on one bucket
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketEncryptionResult {
+    pub bucket: String,
+    pub region: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Time-limited URL for a single S3 object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresignedUrl {
+    pub url: String,
+    pub method: String,
+    pub expires_at: String,
+}
+
+/// A local file uploaded to S3
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadedObject {
+    pub bucket: String,
+    pub key: String,
+    pub etag: String,
+    pub size_bytes: i64,
+    pub content_type: String,
+    /// Sent in parts rather than with a single PutObject
+    pub multipart: bool,
+}
+
+/// One S3 lifecycle rule, applied to objects under `prefix`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleRule {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub transitions: Vec<LifecycleTransition>,
+    #[serde(default)]
+    pub expiration_days: Option<i32>,
+}
+
+/// Move objects to `storage_class` (e.g. "STANDARD_IA", "GLACIER") after `days`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleTransition {
+    pub days: i32,
+    pub storage_class: String,
+}
+
+fn default_rule_enabled() -> bool {
+    true
+}
+
+/// Estimated effect of applying a lifecycle rule to a bucket's current objects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleImpactPreview {
+    pub bucket: String,
+    pub prefix: String,
+    /// Objects examined; all of them unless `sample_capped` is set
+    pub objects_scanned: usize,
+    pub bytes_scanned: i64,
+    /// Listing stopped at the sample cap, so the counts cover only part of the bucket
+    pub sample_capped: bool,
+    pub transitions: Vec<LifecycleTransitionImpact>,
+    pub objects_expired: usize,
+    pub bytes_expired: i64,
+    /// Estimated change in monthly storage cost in USD; negative is a saving
+    pub monthly_cost_change: f64,
+    pub note: String,
+}
+
+/// Objects a rule would move into one storage class
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LifecycleTransitionImpact {
+    pub storage_class: String,
+    pub objects: usize,
+    pub bytes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmptyBucketCleanupReport {
+    pub dry_run: bool,
+    /// Empty, unprotected buckets that are (or would be) deleted
+    pub candidates: Vec<String>,
+    pub deleted: Vec<String>,
+    pub skipped_non_empty: Vec<String>,
+    pub protected: Vec<String>,
+    pub failed: Vec<BucketCleanupFailure>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketCleanupFailure {
+    pub bucket: String,
+    pub error: String,
 }
 
 // ============================================================================
@@ -73,9 +707,24 @@ pub struct AwsIamUser {
     pub password_last_used: Option<String>,
     pub access_keys: Vec<AwsAccessKey>,
     pub attached_policies: Vec<AwsPolicy>,
+    /// Names of policies embedded in the user
+    #[serde(default)]
+    pub inline_policies: Vec<String>,
     pub groups: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AwsIamRole {
+    pub role_name: String,
+    pub role_id: String,
+    pub arn: String,
+    pub create_date: String,
+    pub description: Option<String>,
+    pub attached_policies: Vec<AwsPolicy>,
+    /// Names of policies embedded in the role
+    pub inline_policies: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AwsAccessKey {
     pub access_key_id: String,
@@ -84,6 +733,13 @@ pub struct AwsAccessKey {
     pub last_used: Option<String>,
 }
 
+/// A freshly created access key; the only time its secret is available
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewAccessKey {
+    pub access_key: AwsAccessKey,
+    pub secret_access_key: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AwsPolicy {
     pub policy_name: String,
@@ -129,6 +785,9 @@ pub struct CostAlert {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AwsHealthReport {
     pub service: String,
+    /// Region the check ran in; `None` for global services such as IAM
+    #[serde(default)]
+    pub region: Option<String>,
     pub status: String, // "healthy", "degraded", "unhealthy"
     pub details: Vec<String>,
     pub last_check: String,
@@ -0,0 +1,239 @@
+// ============================================================================
+// AZURE CLIENT
+// ============================================================================
+// Minimal Azure Resource Manager client using service principal credentials
+// ============================================================================
+
+use crate::azure::{AzureConfig, AzureError, AzureResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const LOGIN_ENDPOINT: &str = "https://login.microsoftonline.com";
+const MANAGEMENT_ENDPOINT: &str = "https://management.azure.com";
+const MANAGEMENT_SCOPE: &str = "https://management.azure.com/.default";
+const SUBSCRIPTIONS_API_VERSION: &str = "2022-12-01";
+const COMPUTE_API_VERSION: &str = "2024-07-01";
+
+/// Virtual machine as stored in the instances table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AzureVm {
+    pub vm_id: String,
+    pub name: String,
+    pub vm_size: String,
+    pub location: String,
+    pub os_disk_gb: i64,
+    pub tags: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct TokenErrorResponse {
+    error_description: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VmListResponse {
+    #[serde(default)]
+    value: Vec<VmResource>,
+    next_link: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VmResource {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    location: String,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+    properties: Option<VmProperties>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VmProperties {
+    hardware_profile: Option<HardwareProfile>,
+    storage_profile: Option<StorageProfile>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HardwareProfile {
+    vm_size: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StorageProfile {
+    os_disk: Option<OsDisk>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OsDisk {
+    #[serde(rename = "diskSizeGB")]
+    disk_size_gb: Option<i64>,
+}
+
+#[derive(Clone)]
+pub struct AzureClient {
+    config: AzureConfig,
+    http: reqwest::Client,
+}
+
+impl AzureClient {
+    pub fn new(config: AzureConfig) -> AzureResult<Self> {
+        config.validate()?;
+
+        Ok(Self {
+            config,
+            http: reqwest::Client::new(),
+        })
+    }
+
+    /// Request a management token for the service principal and check it can
+    /// read the subscription
+    pub async fn test_connection(&self) -> AzureResult<()> {
+        tracing::debug!("Testing Azure connection for subscription {}", self.config.subscription_id);
+
+        let token = self.access_token().await?;
+        let url = format!(
+            "{}/subscriptions/{}?api-version={}",
+            MANAGEMENT_ENDPOINT, self.config.subscription_id, SUBSCRIPTIONS_API_VERSION
+        );
+        self.get_json::<serde_json::Value>(&token, &url).await?;
+
+        tracing::info!("Azure connection test successful");
+        Ok(())
+    }
+
+    /// Collect every virtual machine in the subscription, following paging links
+    pub async fn collect_vms(&self) -> AzureResult<Vec<AzureVm>> {
+        tracing::info!("Collecting Azure VMs for subscription {}", self.config.subscription_id);
+
+        let token = self.access_token().await?;
+        let mut next = Some(format!(
+            "{}/subscriptions/{}/providers/Microsoft.Compute/virtualMachines?api-version={}",
+            MANAGEMENT_ENDPOINT, self.config.subscription_id, COMPUTE_API_VERSION
+        ));
+
+        let mut vms = Vec::new();
+        while let Some(url) = next {
+            let page: VmListResponse = self.get_json(&token, &url).await?;
+            vms.extend(page.value.into_iter().map(map_vm));
+            next = page.next_link;
+        }
+
+        tracing::info!("Collected {} Azure VMs", vms.len());
+        Ok(vms)
+    }
+
+    /// Client credentials grant against the tenant's token endpoint
+    async fn access_token(&self) -> AzureResult<String> {
+        let url = format!("{}/{}/oauth2/v2.0/token", LOGIN_ENDPOINT, self.config.credentials.tenant_id);
+        let response = self.http
+            .post(&url)
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", self.config.credentials.client_id.as_str()),
+                ("client_secret", self.config.credentials.client_secret.as_str()),
+                ("scope", MANAGEMENT_SCOPE),
+            ])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let description = response
+                .json::<TokenErrorResponse>()
+                .await
+                .ok()
+                .and_then(|e| e.error_description)
+                .unwrap_or_else(|| status.to_string());
+            return Err(AzureError::AuthError(description));
+        }
+
+        Ok(response.json::<TokenResponse>().await?.access_token)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, token: &str, url: &str) -> AzureResult<T> {
+        let response = self.http.get(url).bearer_auth(token).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(AzureError::ApiError { status: status.as_u16(), message });
+        }
+
+        Ok(response.json::<T>().await?)
+    }
+}
+
+/// Flatten an ARM virtual machine resource
+fn map_vm(resource: VmResource) -> AzureVm {
+    let properties = resource.properties;
+    let vm_size = properties
+        .as_ref()
+        .and_then(|p| p.hardware_profile.as_ref())
+        .and_then(|h| h.vm_size.clone())
+        .unwrap_or_else(|| "unknown".to_string());
+    let os_disk_gb = properties
+        .as_ref()
+        .and_then(|p| p.storage_profile.as_ref())
+        .and_then(|s| s.os_disk.as_ref())
+        .and_then(|d| d.disk_size_gb)
+        .unwrap_or(0);
+
+    AzureVm {
+        vm_id: resource.id,
+        name: resource.name,
+        vm_size,
+        location: resource.location,
+        os_disk_gb,
+        tags: resource.tags,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vm_list_page_mapping() {
+        let page: VmListResponse = serde_json::from_value(serde_json::json!({
+            "value": [
+                {
+                    "id": "/subscriptions/sub/resourceGroups/rg/providers/Microsoft.Compute/virtualMachines/web-1",
+                    "name": "web-1",
+                    "location": "westeurope",
+                    "tags": { "env": "prod" },
+                    "properties": {
+                        "hardwareProfile": { "vmSize": "Standard_B2s" },
+                        "storageProfile": { "osDisk": { "diskSizeGB": 64 } }
+                    }
+                },
+                { "name": "bare", "location": "eastus" }
+            ],
+            "nextLink": "https://management.azure.com/next"
+        }))
+        .unwrap();
+
+        assert_eq!(page.next_link.as_deref(), Some("https://management.azure.com/next"));
+
+        let vms: Vec<AzureVm> = page.value.into_iter().map(map_vm).collect();
+        assert_eq!(vms[0].name, "web-1");
+        assert_eq!(vms[0].vm_size, "Standard_B2s");
+        assert_eq!(vms[0].os_disk_gb, 64);
+        assert_eq!(vms[0].tags.get("env").map(String::as_str), Some("prod"));
+        assert_eq!(vms[1].vm_size, "unknown");
+        assert_eq!(vms[1].os_disk_gb, 0);
+    }
+}
@@ -0,0 +1,89 @@
+// ============================================================================
+// AZURE CONFIGURATION
+// ============================================================================
+// Service principal credentials and subscription for one Azure account
+// ============================================================================
+
+use crate::azure::{AzureError, AzureResult};
+
+/// Service principal used to request management tokens
+#[derive(Debug, Clone)]
+pub struct AzureCredentials {
+    pub tenant_id: String,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AzureConfig {
+    pub credentials: AzureCredentials,
+    pub subscription_id: String,
+}
+
+impl AzureConfig {
+    /// Build a config from the Azure fields stored on an account
+    pub fn from_credentials(tenant_id: String, client_id: String, client_secret: String, subscription_id: String) -> Self {
+        Self {
+            credentials: AzureCredentials {
+                tenant_id,
+                client_id,
+                client_secret,
+            },
+            subscription_id,
+        }
+    }
+
+    /// Check every field is set and the ids are GUIDs
+    pub fn validate(&self) -> AzureResult<()> {
+        validate_fields(
+            &self.credentials.tenant_id,
+            &self.credentials.client_id,
+            &self.credentials.client_secret,
+            &self.subscription_id,
+        )
+        .map_err(AzureError::ConfigError)
+    }
+}
+
+/// Shape checks shared with builds that have no Azure client
+pub fn validate_fields(tenant_id: &str, client_id: &str, client_secret: &str, subscription_id: &str) -> Result<(), String> {
+    for (field, value) in [
+        ("tenant_id", tenant_id),
+        ("client_id", client_id),
+        ("client_secret", client_secret),
+        ("subscription_id", subscription_id),
+    ] {
+        if value.is_empty() {
+            return Err(format!("Azure {} is required but not configured", field));
+        }
+    }
+
+    for (field, value) in [("tenant_id", tenant_id), ("client_id", client_id), ("subscription_id", subscription_id)] {
+        if uuid::Uuid::parse_str(value).is_err() {
+            return Err(format!("Azure {} '{}' is not a GUID. Please verify the value in the Azure portal.", field, value));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_validation() {
+        let tenant = "72f988bf-86f1-41af-91ab-2d7cd011db47".to_string();
+        let client = "0d2f9c1e-5b7a-4c3d-8e9f-1a2b3c4d5e6f".to_string();
+        let subscription = "5f1c2e3d-4b5a-6978-8a9b-0c1d2e3f4a5b".to_string();
+
+        let config = AzureConfig::from_credentials(tenant.clone(), client.clone(), "secret".to_string(), subscription.clone());
+        assert!(config.validate().is_ok());
+
+        let missing_secret = AzureConfig::from_credentials(tenant.clone(), client.clone(), String::new(), subscription);
+        assert!(missing_secret.validate().unwrap_err().to_string().contains("client_secret"));
+
+        let bad_subscription = AzureConfig::from_credentials(tenant, client, "secret".to_string(), "my-subscription".to_string());
+        assert!(bad_subscription.validate().unwrap_err().to_string().contains("subscription_id"));
+    }
+}
@@ -0,0 +1,27 @@
+// ============================================================================
+// AZURE ERRORS
+// ============================================================================
+// Error handling for Azure Resource Manager operations
+// ============================================================================
+
+use thiserror::Error;
+
+// Only configuration errors can occur without the Resource Manager client
+#[derive(Error, Debug)]
+#[cfg_attr(not(feature = "azure-sdk"), allow(dead_code))]
+pub enum AzureError {
+    #[error("Configuration error: {0}")]
+    ConfigError(String),
+
+    #[error("Authentication error: {0}")]
+    AuthError(String),
+
+    #[error("Azure API error ({status}): {message}")]
+    ApiError { status: u16, message: String },
+
+    #[cfg(feature = "azure-sdk")]
+    #[error("Network error: {0}")]
+    NetworkError(#[from] reqwest::Error),
+}
+
+pub type AzureResult<T> = Result<T, AzureError>;
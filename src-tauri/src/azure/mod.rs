@@ -0,0 +1,9 @@
+#[cfg(feature = "azure-sdk")]
+pub mod client;
+pub mod config;
+pub mod errors;
+
+#[cfg(feature = "azure-sdk")]
+pub use client::{AzureClient, AzureVm};
+pub use config::AzureConfig;
+pub use errors::*;
@@ -0,0 +1,362 @@
+// ============================================================================
+// COMMAND REGISTRY
+// ============================================================================
+// Metadata for every command registered with generate_handler!, used by
+// describe_commands to drive generated API clients. The tests below fail when
+// this list drifts from main.rs or from the command signatures in lib.rs.
+// ============================================================================
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandKind {
+    /// Only reads cloud resources or stored data
+    ReadOnly,
+    /// Changes cloud resources, stored data or app state
+    Mutating,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CommandArg {
+    pub name: &'static str,
+    pub rust_type: &'static str,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CommandInfo {
+    pub name: &'static str,
+    pub kind: CommandKind,
+    pub args: &'static [CommandArg],
+}
+
+const fn arg(name: &'static str, rust_type: &'static str) -> CommandArg {
+    CommandArg { name, rust_type }
+}
+
+use CommandKind::{Mutating, ReadOnly};
+
+/// Every command in the order it is registered in main.rs
+pub const COMMANDS: &[CommandInfo] = &[
+    CommandInfo { name: "greet", kind: ReadOnly, args: &[arg("name", "String")] },
+    CommandInfo { name: "get_app_info", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "get_accounts", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "get_account", kind: ReadOnly, args: &[arg("id", "i64")] },
+    CommandInfo { name: "create_account", kind: Mutating, args: &[arg("request", "serde_json::Value")] },
+    CommandInfo { name: "update_account", kind: Mutating, args: &[arg("id", "i64"), arg("request", "serde_json::Value")] },
+    CommandInfo { name: "delete_account", kind: Mutating, args: &[arg("id", "i64"), arg("force", "Option<bool>")] },
+    CommandInfo { name: "test_account_connection", kind: ReadOnly, args: &[arg("id", "i64")] },
+    CommandInfo { name: "sync_account", kind: Mutating, args: &[arg("id", "i64")] },
+    CommandInfo { name: "sync_all_accounts", kind: Mutating, args: &[] },
+    CommandInfo { name: "set_account_status", kind: Mutating, args: &[arg("id", "i64"), arg("status", "String")] },
+    CommandInfo { name: "set_credential_failure_threshold", kind: Mutating, args: &[arg("threshold", "i64")] },
+    CommandInfo { name: "set_health_webhook_url", kind: Mutating, args: &[arg("url", "Option<String>")] },
+    CommandInfo { name: "get_smtp_settings", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "set_smtp_settings", kind: Mutating, args: &[arg("settings", "Option<notifier::SmtpSettings>"), arg("password", "Option<String>")] },
+    CommandInfo { name: "test_smtp", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "get_ssh_key_paths", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "set_ssh_key_path", kind: Mutating, args: &[arg("key_name", "String"), arg("path", "Option<String>")] },
+    CommandInfo { name: "get_account_regions", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "set_account_regions", kind: Mutating, args: &[arg("account_id", "i64"), arg("regions", "Vec<database::AccountRegionSetting>")] },
+    CommandInfo { name: "set_credential_store_passphrase", kind: Mutating, args: &[arg("passphrase", "Option<String>")] },
+    CommandInfo { name: "migrate_credentials_to_keyring", kind: Mutating, args: &[] },
+    CommandInfo { name: "verify_credential_linkage", kind: Mutating, args: &[arg("repair", "Option<bool>")] },
+    CommandInfo { name: "get_projects", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "get_project", kind: ReadOnly, args: &[arg("id", "i64")] },
+    CommandInfo { name: "create_project", kind: Mutating, args: &[arg("request", "serde_json::Value")] },
+    CommandInfo { name: "update_project", kind: Mutating, args: &[arg("id", "i64"), arg("request", "serde_json::Value")] },
+    CommandInfo { name: "rename_project", kind: Mutating, args: &[arg("id", "i64"), arg("new_name", "String")] },
+    CommandInfo { name: "compute_project_health", kind: Mutating, args: &[arg("project_id", "i64"), arg("persist", "Option<bool>")] },
+    CommandInfo { name: "delete_project", kind: Mutating, args: &[arg("id", "i64")] },
+    CommandInfo { name: "delete_project_with_resources", kind: Mutating, args: &[arg("project_id", "i64"), arg("confirm_token", "Option<String>"), arg("delete_volumes", "Option<bool>")] },
+    CommandInfo { name: "get_instances", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "get_instance", kind: ReadOnly, args: &[arg("id", "i64")] },
+    CommandInfo { name: "validate_instance_request", kind: ReadOnly, args: &[arg("request", "serde_json::Value"), arg("allow_region_mismatch", "Option<bool>")] },
+    CommandInfo { name: "create_instance", kind: Mutating, args: &[arg("request", "serde_json::Value"), arg("allow_region_mismatch", "Option<bool>")] },
+    CommandInfo { name: "update_instance", kind: Mutating, args: &[arg("id", "i64"), arg("request", "serde_json::Value")] },
+    CommandInfo { name: "delete_instance", kind: Mutating, args: &[arg("id", "i64")] },
+    CommandInfo { name: "start_instance", kind: Mutating, args: &[arg("id", "i64")] },
+    CommandInfo { name: "stop_instance", kind: Mutating, args: &[arg("id", "i64")] },
+    CommandInfo { name: "restart_instance", kind: Mutating, args: &[arg("id", "i64")] },
+    CommandInfo { name: "get_blueprints", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "get_blueprint", kind: ReadOnly, args: &[arg("id", "i64")] },
+    CommandInfo { name: "create_blueprint", kind: Mutating, args: &[arg("request", "serde_json::Value")] },
+    CommandInfo { name: "update_blueprint", kind: Mutating, args: &[arg("id", "i64"), arg("request", "serde_json::Value")] },
+    CommandInfo { name: "delete_blueprint", kind: Mutating, args: &[arg("id", "i64")] },
+    CommandInfo { name: "deploy_blueprint", kind: Mutating, args: &[arg("blueprint_id", "i64"), arg("project_id", "i64"), arg("instance_name", "String"), arg("allow_region_mismatch", "Option<bool>")] },
+    CommandInfo { name: "clone_blueprint", kind: Mutating, args: &[arg("id", "i64"), arg("overrides", "serde_json::Value")] },
+    CommandInfo { name: "export_blueprint", kind: ReadOnly, args: &[arg("id", "i64")] },
+    CommandInfo { name: "import_blueprint", kind: Mutating, args: &[arg("json", "String")] },
+    CommandInfo { name: "get_security_configs", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "get_security_config", kind: ReadOnly, args: &[arg("id", "i64")] },
+    CommandInfo { name: "create_security_config", kind: Mutating, args: &[arg("request", "serde_json::Value")] },
+    CommandInfo { name: "update_security_config", kind: Mutating, args: &[arg("id", "i64"), arg("request", "serde_json::Value")] },
+    CommandInfo { name: "delete_security_config", kind: Mutating, args: &[arg("id", "i64"), arg("force", "Option<bool>")] },
+    CommandInfo { name: "collect_security_groups", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "collect_vpcs", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "collect_subnets", kind: ReadOnly, args: &[arg("account_id", "i64"), arg("vpc_id", "Option<String>")] },
+    CommandInfo { name: "get_spot_price_history", kind: ReadOnly, args: &[arg("account_id", "i64"), arg("instance_type", "String"), arg("availability_zone", "Option<String>")] },
+    CommandInfo { name: "import_security_group", kind: Mutating, args: &[arg("account_id", "i64"), arg("group_id", "String"), arg("name", "Option<String>"), arg("region", "Option<String>")] },
+    CommandInfo { name: "apply_security_config", kind: Mutating, args: &[arg("config_id", "i64"), arg("account_id", "i64"), arg("vpc_id", "Option<String>")] },
+    CommandInfo { name: "get_security_config_usage", kind: ReadOnly, args: &[arg("config_id", "i64")] },
+    CommandInfo { name: "get_images", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "get_image", kind: ReadOnly, args: &[arg("id", "i64")] },
+    CommandInfo { name: "delete_image", kind: Mutating, args: &[arg("id", "i64"), arg("delete_snapshots", "Option<bool>")] },
+    CommandInfo { name: "collect_ec2_instances", kind: ReadOnly, args: &[arg("options", "serde_json::Value")] },
+    CommandInfo { name: "get_all_instances", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "create_ec2_instance", kind: Mutating, args: &[arg("instance_data", "serde_json::Value")] },
+    CommandInfo { name: "delete_ec2_instance", kind: Mutating, args: &[arg("instance_id", "String"), arg("dry_run", "Option<bool>"), arg("options", "Option<serde_json::Value>")] },
+    CommandInfo { name: "set_instance_protection", kind: Mutating, args: &[arg("instance_id", "String"), arg("protected", "bool")] },
+    CommandInfo { name: "start_ec2_instance", kind: Mutating, args: &[arg("instance_id", "String"), arg("wait", "Option<bool>")] },
+    CommandInfo { name: "stop_ec2_instance", kind: Mutating, args: &[arg("instance_id", "String"), arg("wait", "Option<bool>")] },
+    CommandInfo { name: "restart_ec2_instance", kind: Mutating, args: &[arg("instance_id", "String"), arg("wait", "Option<bool>")] },
+    CommandInfo { name: "bulk_ec2_operation", kind: Mutating, args: &[arg("account_id", "i64"), arg("operation", "String"), arg("instance_ids", "Vec<String>")] },
+    CommandInfo { name: "resize_ec2_instance", kind: Mutating, args: &[arg("instance_id", "String"), arg("new_type", "String"), arg("restart", "Option<bool>"), arg("allow_stop", "Option<bool>"), arg("account_id", "Option<i64>")] },
+    CommandInfo { name: "get_ec2_instance_details", kind: ReadOnly, args: &[arg("instance_id", "String")] },
+    CommandInfo { name: "get_ec2_instance_ssh_config", kind: ReadOnly, args: &[arg("instance_id", "String")] },
+    CommandInfo { name: "get_instance_total_cost", kind: ReadOnly, args: &[arg("instance_id", "String"), arg("month", "String")] },
+    CommandInfo { name: "get_instance_metrics", kind: ReadOnly, args: &[arg("instance_id", "String"), arg("period", "Option<String>"), arg("account_id", "Option<i64>")] },
+    CommandInfo { name: "get_storage_utilization", kind: ReadOnly, args: &[arg("instance_id", "String")] },
+    CommandInfo { name: "stop_idle_instances", kind: Mutating, args: &[arg("account_id", "i64"), arg("cpu_threshold", "Option<f64>"), arg("window_hours", "Option<i64>"), arg("dry_run", "Option<bool>")] },
+    CommandInfo { name: "collect_reserved_instances", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "get_reservation_utilization", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "get_compute_optimizer_recommendations", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "get_instances_health_summary", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "get_instance_console_output", kind: ReadOnly, args: &[arg("account_id", "i64"), arg("instance_id", "String"), arg("max_kb", "Option<usize>")] },
+    CommandInfo { name: "get_instance_screenshot", kind: ReadOnly, args: &[arg("account_id", "i64"), arg("instance_id", "String")] },
+    CommandInfo { name: "get_instance_scheduled_events", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "list_amis", kind: ReadOnly, args: &[arg("account_id", "i64"), arg("options", "Option<aws::AmiListOptions>")] },
+    CommandInfo { name: "resolve_latest_ami", kind: ReadOnly, args: &[arg("account_id", "i64"), arg("distro", "String"), arg("arch", "Option<String>")] },
+    CommandInfo { name: "create_image_from_instance", kind: Mutating, args: &[arg("instance_id", "i64"), arg("name", "String"), arg("description", "Option<String>"), arg("no_reboot", "Option<bool>")] },
+    CommandInfo { name: "sync_instance_storage", kind: Mutating, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "export_ansible_inventory", kind: ReadOnly, args: &[arg("account_id", "i64"), arg("group_by", "String"), arg("format", "Option<String>"), arg("include_stopped", "Option<bool>")] },
+    CommandInfo { name: "check_naming_policy", kind: Mutating, args: &[arg("account_id", "i64"), arg("regex", "String"), arg("auto_rename", "Option<bool>")] },
+    CommandInfo { name: "list_unassociated_eips", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "release_unused_eips", kind: Mutating, args: &[arg("account_id", "i64"), arg("dry_run", "Option<bool>")] },
+    CommandInfo { name: "collect_elastic_ips", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "allocate_elastic_ip", kind: Mutating, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "associate_elastic_ip", kind: Mutating, args: &[arg("account_id", "i64"), arg("allocation_id", "String"), arg("instance_id", "String")] },
+    CommandInfo { name: "release_elastic_ip", kind: Mutating, args: &[arg("account_id", "i64"), arg("allocation_id", "String")] },
+    CommandInfo { name: "collect_volumes", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "create_volume", kind: Mutating, args: &[arg("account_id", "i64"), arg("request", "aws::CreateVolumeRequest")] },
+    CommandInfo { name: "attach_volume", kind: Mutating, args: &[arg("account_id", "i64"), arg("volume_id", "String"), arg("instance_id", "String"), arg("device", "String")] },
+    CommandInfo { name: "detach_volume", kind: Mutating, args: &[arg("account_id", "i64"), arg("volume_id", "String"), arg("force", "Option<bool>")] },
+    CommandInfo { name: "modify_volume_size", kind: Mutating, args: &[arg("account_id", "i64"), arg("volume_id", "String"), arg("size_gb", "i32")] },
+    CommandInfo { name: "collect_snapshots", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "create_snapshot", kind: Mutating, args: &[arg("account_id", "i64"), arg("volume_id", "String"), arg("description", "Option<String>")] },
+    CommandInfo { name: "delete_snapshot", kind: Mutating, args: &[arg("account_id", "i64"), arg("snapshot_id", "String")] },
+    CommandInfo { name: "collect_s3_buckets", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "create_s3_bucket", kind: Mutating, args: &[arg("bucket_name", "String"), arg("region", "String")] },
+    CommandInfo { name: "delete_s3_bucket", kind: Mutating, args: &[arg("bucket_name", "String"), arg("dry_run", "Option<bool>")] },
+    CommandInfo { name: "delete_empty_buckets", kind: Mutating, args: &[arg("account_id", "i64"), arg("dry_run", "Option<bool>")] },
+    CommandInfo { name: "get_s3_bucket_details", kind: ReadOnly, args: &[arg("bucket_name", "String")] },
+    CommandInfo { name: "get_s3_bucket_lifecycle", kind: ReadOnly, args: &[arg("account_id", "i64"), arg("bucket_name", "String")] },
+    CommandInfo { name: "put_s3_bucket_lifecycle", kind: Mutating, args: &[arg("account_id", "i64"), arg("bucket_name", "String"), arg("rules", "Vec<aws::LifecycleRule>")] },
+    CommandInfo { name: "preview_lifecycle_impact", kind: ReadOnly, args: &[arg("account_id", "i64"), arg("bucket", "String"), arg("rule", "aws::LifecycleRule")] },
+    CommandInfo { name: "get_s3_presigned_url", kind: ReadOnly, args: &[arg("bucket_name", "String"), arg("key", "String"), arg("expires_secs", "Option<i64>"), arg("method", "Option<String>")] },
+    CommandInfo { name: "get_s3_bucket_policy", kind: ReadOnly, args: &[arg("bucket_name", "String")] },
+    CommandInfo { name: "put_s3_bucket_policy", kind: Mutating, args: &[arg("account_id", "i64"), arg("bucket_name", "String"), arg("policy", "String")] },
+    CommandInfo { name: "put_s3_public_access_block", kind: Mutating, args: &[arg("account_id", "i64"), arg("bucket_name", "String"), arg("flags", "Option<aws::PublicAccessBlockFlags>")] },
+    CommandInfo { name: "get_bucket_encryption_report", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "enable_default_encryption", kind: Mutating, args: &[arg("account_id", "i64"), arg("buckets", "Vec<String>"), arg("kms_key_id", "Option<String>")] },
+    CommandInfo { name: "upload_s3_object", kind: Mutating, args: &[arg("account_id", "i64"), arg("bucket_name", "String"), arg("key", "String"), arg("src_path", "String"), arg("content_type", "Option<String>")] },
+    CommandInfo { name: "collect_iam_users", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "collect_iam_roles", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "get_iam_user_details", kind: ReadOnly, args: &[arg("user_name", "String")] },
+    CommandInfo { name: "get_iam_role_details", kind: ReadOnly, args: &[arg("role_name", "String")] },
+    CommandInfo { name: "list_iam_access_keys", kind: ReadOnly, args: &[arg("user_name", "String")] },
+    CommandInfo { name: "create_iam_access_key", kind: Mutating, args: &[arg("account_id", "i64"), arg("user_name", "String")] },
+    CommandInfo { name: "delete_iam_access_key", kind: Mutating, args: &[arg("account_id", "i64"), arg("user_name", "String"), arg("access_key_id", "String")] },
+    CommandInfo { name: "get_cost_summary", kind: ReadOnly, args: &[arg("start_date", "String"), arg("end_date", "String")] },
+    CommandInfo { name: "list_cost_allocation_tags", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "activate_cost_allocation_tags", kind: Mutating, args: &[arg("account_id", "i64"), arg("keys", "Vec<String>")] },
+    CommandInfo { name: "get_budget_alerts", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "create_budget_alert", kind: Mutating, args: &[arg("request", "serde_json::Value")] },
+    CommandInfo { name: "update_budget_alert", kind: Mutating, args: &[arg("id", "i64"), arg("request", "serde_json::Value")] },
+    CommandInfo { name: "delete_budget_alert", kind: Mutating, args: &[arg("id", "i64"), arg("dry_run", "Option<bool>")] },
+    CommandInfo { name: "get_cost_status", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "reset_cost_tracking", kind: Mutating, args: &[] },
+    CommandInfo { name: "get_cost_tracking_delta", kind: ReadOnly, args: &[arg("since", "String")] },
+    CommandInfo { name: "set_cost_alert_thresholds", kind: Mutating, args: &[arg("thresholds_percent", "Vec<f64>")] },
+    CommandInfo { name: "get_cost_thresholds", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "set_cost_thresholds", kind: Mutating, args: &[arg("warn_usd", "f64"), arg("limit_usd", "f64")] },
+    CommandInfo { name: "security_posture_score", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "get_security_posture_weights", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "set_security_posture_weights", kind: Mutating, args: &[arg("weights", "database::SecurityPostureWeights")] },
+    CommandInfo { name: "get_cache_stats", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "invalidate_cache", kind: Mutating, args: &[] },
+    CommandInfo { name: "invalidate_cache_region", kind: Mutating, args: &[arg("region", "String")] },
+    CommandInfo { name: "clear_aws_clients", kind: Mutating, args: &[] },
+    CommandInfo { name: "get_aws_health_status", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "force_aws_health_check", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "get_aws_health_report", kind: ReadOnly, args: &[] },
+    CommandInfo { name: "get_recent_aws_events", kind: ReadOnly, args: &[arg("since", "String")] },
+    CommandInfo { name: "get_recent_aws_errors", kind: ReadOnly, args: &[arg("limit", "Option<usize>")] },
+    CommandInfo { name: "replay_events", kind: ReadOnly, args: &[arg("after_id", "i64"), arg("types", "Option<Vec<String>>")] },
+    CommandInfo { name: "check_account_permissions", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "get_aws_service_events", kind: ReadOnly, args: &[arg("account_id", "i64")] },
+    CommandInfo { name: "describe_commands", kind: ReadOnly, args: &[] },
+];
+
+impl CommandArg {
+    /// `Option<T>` arguments may be omitted
+    pub fn required(&self) -> bool {
+        !self.rust_type.starts_with("Option<")
+    }
+
+    /// JSON type of the argument value
+    pub fn json_type(&self) -> &'static str {
+        let rust_type = self.rust_type
+            .strip_prefix("Option<")
+            .and_then(|t| t.strip_suffix('>'))
+            .unwrap_or(self.rust_type);
+
+        match rust_type {
+            "String" => "string",
+            "bool" => "boolean",
+            "i64" | "f64" | "usize" => "number",
+            t if t.starts_with("Vec<") => "array",
+            _ => "object",
+        }
+    }
+
+    /// Key the frontend passes the argument under; Tauri expects camelCase
+    pub fn js_name(&self) -> String {
+        let mut parts = self.name.split('_');
+        let mut name = parts.next().unwrap_or_default().to_string();
+        for part in parts {
+            let mut chars = part.chars();
+            if let Some(first) = chars.next() {
+                name.push(first.to_ascii_uppercase());
+                name.push_str(chars.as_str());
+            }
+        }
+        name
+    }
+}
+
+/// Registry as JSON for describe_commands
+pub fn describe() -> Vec<serde_json::Value> {
+    COMMANDS
+        .iter()
+        .map(|command| {
+            let args: Vec<serde_json::Value> = command.args
+                .iter()
+                .map(|arg| serde_json::json!({
+                    "name": arg.name,
+                    "js_name": arg.js_name(),
+                    "type": arg.json_type(),
+                    "rust_type": arg.rust_type,
+                    "required": arg.required()
+                }))
+                .collect();
+
+            serde_json::json!({
+                "name": command.name,
+                "kind": command.kind,
+                "args": args
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Names inside the generate_handler! list in main.rs
+    fn registered_commands() -> Vec<String> {
+        let main_rs = include_str!("main.rs");
+        let start = main_rs.find("generate_handler![").expect("generate_handler! in main.rs");
+        let end = start + main_rs[start..].find(']').unwrap();
+
+        main_rs[start..end]
+            .split(',')
+            .filter_map(|entry| entry.trim().strip_prefix("app_lib::"))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// (name, [(arg, type)]) for every #[tauri::command] in lib.rs, skipping
+    /// the State and AppHandle parameters Tauri injects
+    fn command_signatures() -> Vec<(String, Vec<(String, String)>)> {
+        let lib_rs = include_str!("lib.rs");
+        let mut signatures = Vec::new();
+
+        for chunk in lib_rs.split("\n#[tauri::command]").skip(1) {
+            let fn_start = chunk.find("fn ").unwrap() + 3;
+            let open = fn_start + chunk[fn_start..].find('(').unwrap();
+            let name = chunk[fn_start..open].trim().to_string();
+
+            // Parameters end at the parenthesis matching `open`
+            let mut depth = 0;
+            let mut params = Vec::new();
+            let mut current = String::new();
+            for c in chunk[open + 1..].chars() {
+                match c {
+                    '(' | '<' => depth += 1,
+                    ')' if depth == 0 => break,
+                    ')' | '>' => depth -= 1,
+                    ',' if depth == 0 => {
+                        params.push(std::mem::take(&mut current));
+                        continue;
+                    }
+                    _ => {}
+                }
+                current.push(c);
+            }
+            params.push(current);
+
+            let args = params
+                .iter()
+                .filter_map(|param| param.split_once(':'))
+                .map(|(name, ty)| (name.trim().to_string(), ty.split_whitespace().collect::<String>()))
+                .filter(|(_, ty)| !ty.starts_with("State<") && !ty.contains("AppHandle"))
+                .collect();
+            signatures.push((name, args));
+        }
+
+        signatures
+    }
+
+    #[test]
+    fn test_registry_lists_create_project() {
+        let create_project = COMMANDS.iter().find(|c| c.name == "create_project").unwrap();
+        assert_eq!(create_project.kind, CommandKind::Mutating);
+        assert_eq!(create_project.args.len(), 1);
+        assert_eq!(create_project.args[0].name, "request");
+        assert!(create_project.args[0].required());
+        assert_eq!(create_project.args[0].json_type(), "object");
+
+        let described = describe();
+        let resize = described.iter().find(|c| c["name"] == "resize_ec2_instance").unwrap();
+        assert_eq!(resize["kind"], "mutating");
+        assert_eq!(resize["args"][1]["js_name"], "newType");
+        assert_eq!(resize["args"][2]["required"], false);
+        assert_eq!(resize["args"][2]["type"], "boolean");
+    }
+
+    #[test]
+    fn test_registry_matches_generate_handler() {
+        let names: Vec<&str> = COMMANDS.iter().map(|c| c.name).collect();
+        assert_eq!(names, registered_commands());
+    }
+
+    #[test]
+    fn test_registry_matches_command_signatures() {
+        let signatures = command_signatures();
+
+        for command in COMMANDS {
+            let (_, args) = signatures
+                .iter()
+                .find(|(name, _)| name == command.name)
+                .unwrap_or_else(|| panic!("{} has no #[tauri::command] in lib.rs", command.name));
+
+            let expected: Vec<(String, String)> = command.args
+                .iter()
+                .map(|arg| (arg.name.to_string(), arg.rust_type.split_whitespace().collect()))
+                .collect();
+            assert_eq!(&expected, args, "arguments of {}", command.name);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+// ============================================================================
+// CONFIRMATION TOKENS
+// ============================================================================
+// Two-step confirmation for destructive commands: the first call describes
+// what would be destroyed and issues a short-lived token, and only a second
+// call passing that token back goes ahead
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How long an issued token stays valid
+pub const CONFIRMATION_TTL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingConfirmation {
+    pub confirm_token: String,
+    pub expires_in_seconds: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConfirmationError {
+    #[error("Confirmation token is unknown or was already used")]
+    Unknown,
+    #[error("Confirmation token has expired; review the operation again")]
+    Expired,
+    #[error("What the token confirmed has changed since it was issued; review the operation again")]
+    Mismatch,
+}
+
+/// Issued tokens, each bound to a scope describing exactly what it confirms
+/// (the operation, its target and anything that changes what gets destroyed)
+pub struct ConfirmationStore {
+    ttl: Duration,
+    pending: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl ConfirmationStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, pending: Mutex::new(HashMap::new()) }
+    }
+
+    /// Issue a token confirming `scope`
+    pub fn issue(&self, scope: &str, now: Instant) -> PendingConfirmation {
+        let token = Uuid::new_v4().simple().to_string();
+
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|_, (_, issued)| now.saturating_duration_since(*issued) < self.ttl);
+        pending.insert(token.clone(), (scope.to_string(), now));
+
+        PendingConfirmation {
+            confirm_token: token,
+            expires_in_seconds: self.ttl.as_secs(),
+        }
+    }
+
+    /// Check `token` was issued for `scope` and hasn't expired. A token is
+    /// used up by the first attempt, whether or not it succeeds.
+    pub fn confirm(&self, token: &str, scope: &str, now: Instant) -> Result<(), ConfirmationError> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let (issued_scope, issued) = pending.remove(token).ok_or(ConfirmationError::Unknown)?;
+
+        if now.saturating_duration_since(issued) >= self.ttl {
+            return Err(ConfirmationError::Expired);
+        }
+        if issued_scope != scope {
+            return Err(ConfirmationError::Mismatch);
+        }
+        Ok(())
+    }
+}
+
+impl Default for ConfirmationStore {
+    fn default() -> Self {
+        Self::new(CONFIRMATION_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_confirm_their_scope_once_before_expiring() {
+        let store = ConfirmationStore::default();
+        let now = Instant::now();
+
+        let pending = store.issue("delete_project:1", now);
+        assert_eq!(store.confirm(&pending.confirm_token, "delete_project:1", now), Ok(()));
+        assert_eq!(store.confirm(&pending.confirm_token, "delete_project:1", now), Err(ConfirmationError::Unknown));
+
+        let pending = store.issue("delete_project:1", now);
+        assert_eq!(store.confirm(&pending.confirm_token, "delete_project:2", now), Err(ConfirmationError::Mismatch));
+
+        let pending = store.issue("delete_project:1", now);
+        assert_eq!(
+            store.confirm(&pending.confirm_token, "delete_project:1", now + CONFIRMATION_TTL),
+            Err(ConfirmationError::Expired)
+        );
+
+        assert_eq!(store.confirm("not-a-token", "delete_project:1", now), Err(ConfirmationError::Unknown));
+    }
+}
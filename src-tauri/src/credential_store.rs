@@ -0,0 +1,535 @@
+// ============================================================================
+// CREDENTIAL STORE
+// ============================================================================
+// OS keyring storage with an AES-GCM encrypted file fallback for systems
+// where no keyring backend is available (headless Linux, some Wayland setups)
+// ============================================================================
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use crate::app_paths;
+use anyhow::{Context, Result};
+use keyring::Entry;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+const KEYRING_SERVICE_NAME: &str = "pocket-architect";
+const CREDENTIALS_FILE_NAME: &str = "pocket-architect-credentials.enc";
+const KEY_DERIVATION_CONTEXT: &[u8] = b"pocket-architect-credential-store-v1";
+const NONCE_LEN: usize = 12;
+
+// User-supplied passphrase; when unset the file key is derived from the machine
+static PASSPHRASE: RwLock<Option<String>> = RwLock::new(None);
+
+// Serializes read-modify-write cycles on the encrypted file
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Where an account's secrets live
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialBackend {
+    Keyring,
+    EncryptedFile,
+}
+
+impl CredentialBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CredentialBackend::Keyring => "keyring",
+            CredentialBackend::EncryptedFile => "encrypted_file",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Self {
+        match value {
+            "encrypted_file" => CredentialBackend::EncryptedFile,
+            _ => CredentialBackend::Keyring,
+        }
+    }
+}
+
+fn entry_name(account_id: i64, key: &str) -> String {
+    format!("account-{}-{}", account_id, key)
+}
+
+fn ssh_key_entry_name(instance_id: &str) -> String {
+    format!("instance-{}-ssh_private_key", instance_id)
+}
+
+/// The app has a single outbound SMTP server, so a single password entry
+const SMTP_PASSWORD_ENTRY_NAME: &str = "smtp-password";
+
+// ============================================================================
+// PUBLIC API
+// ============================================================================
+
+/// Set (or clear) the passphrase used to encrypt the fallback credentials file.
+/// Entries written under the previous key are re-encrypted with the new one;
+/// re-entering the passphrase a file is already encrypted with just unlocks it.
+pub fn set_passphrase(passphrase: Option<String>) -> Result<()> {
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let current = EncryptedFileStore::open_default()?;
+    let next = EncryptedFileStore::new(current.path.clone(), &file_secret(passphrase.as_deref()));
+    current.rekey(&next)?;
+
+    let mut guard = PASSPHRASE.write().unwrap_or_else(|e| e.into_inner());
+    *guard = passphrase;
+    Ok(())
+}
+
+pub fn has_passphrase() -> bool {
+    PASSPHRASE.read().map(|p| p.is_some()).unwrap_or(false)
+}
+
+/// Store a set of credentials for an account, preferring the OS keyring and
+/// falling back to the encrypted file if any keyring write fails. All values
+/// end up in the same backend, which is returned so the caller can record it.
+pub fn store_credentials(account_id: i64, values: &[(&str, &str)]) -> Result<CredentialBackend> {
+    let mut written = Vec::new();
+
+    for (key, value) in values {
+        match keyring_set(&entry_name(account_id, key), value) {
+            Ok(()) => written.push(*key),
+            Err(e) => {
+                tracing::warn!(
+                    "OS keyring unavailable ({}), storing credentials for account {} in encrypted file",
+                    e, account_id
+                );
+                for key in written {
+                    let _ = keyring_delete(&entry_name(account_id, key));
+                }
+                store_in_file(account_id, values)?;
+                return Ok(CredentialBackend::EncryptedFile);
+            }
+        }
+    }
+
+    Ok(CredentialBackend::Keyring)
+}
+
+/// Retrieve a single credential from the given backend
+pub fn retrieve_credential(backend: CredentialBackend, account_id: i64, key: &str) -> Result<Option<String>> {
+    match backend {
+        CredentialBackend::Keyring => match keyring_get(&entry_name(account_id, key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(anyhow::anyhow!("Failed to read {} from keyring: {}", key, e)),
+        },
+        CredentialBackend::EncryptedFile => {
+            let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let store = EncryptedFileStore::open_default()?;
+            Ok(store.load()?.remove(&entry_name(account_id, key)))
+        }
+    }
+}
+
+/// Delete credentials for an account from every backend, ignoring missing entries
+pub fn delete_credentials(account_id: i64, keys: &[&str]) -> Result<()> {
+    for key in keys {
+        let _ = keyring_delete(&entry_name(account_id, key));
+    }
+
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let store = EncryptedFileStore::open_default()?;
+    if store.exists() {
+        let mut entries = store.load()?;
+        let before = entries.len();
+        for key in keys {
+            entries.remove(&entry_name(account_id, key));
+        }
+        if entries.len() != before {
+            store.save(&entries)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Move an account's credentials from the encrypted file into the OS keyring.
+/// Returns an error (leaving the file untouched) if the keyring is still unavailable.
+pub fn migrate_to_keyring(account_id: i64, keys: &[&str]) -> Result<usize> {
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let store = EncryptedFileStore::open_default()?;
+    let mut entries = store.load()?;
+
+    let mut migrated = Vec::new();
+    for key in keys {
+        if let Some(value) = entries.get(&entry_name(account_id, key)) {
+            if let Err(e) = keyring_set(&entry_name(account_id, key), value) {
+                for key in migrated {
+                    let _ = keyring_delete(&entry_name(account_id, key));
+                }
+                return Err(anyhow::anyhow!("OS keyring still unavailable: {}", e));
+            }
+            migrated.push(*key);
+        }
+    }
+
+    for key in &migrated {
+        entries.remove(&entry_name(account_id, key));
+    }
+    store.save(&entries)?;
+
+    Ok(migrated.len())
+}
+
+/// Find which backend actually holds any of an account's credentials, checking
+/// the OS keyring before the encrypted file
+pub fn locate_credentials(account_id: i64, keys: &[&str]) -> Result<Option<CredentialBackend>> {
+    for key in keys {
+        match keyring_get(&entry_name(account_id, key)) {
+            Ok(_) => return Ok(Some(CredentialBackend::Keyring)),
+            Err(keyring::Error::NoEntry) => {}
+            Err(e) => tracing::debug!("Keyring lookup for account {} failed: {}", account_id, e),
+        }
+    }
+
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let store = EncryptedFileStore::open_default()?;
+    if store.exists() {
+        let entries = store.load()?;
+        if keys.iter().any(|key| entries.contains_key(&entry_name(account_id, key))) {
+            return Ok(Some(CredentialBackend::EncryptedFile));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Store the private key generated for an instance, preferring the OS keyring
+/// and falling back to the encrypted file
+pub fn store_ssh_private_key(instance_id: &str, private_key: &str) -> Result<CredentialBackend> {
+    let name = ssh_key_entry_name(instance_id);
+
+    match keyring_set(&name, private_key) {
+        Ok(()) => Ok(CredentialBackend::Keyring),
+        Err(e) => {
+            tracing::warn!(
+                "OS keyring unavailable ({}), storing SSH key for instance {} in encrypted file",
+                e, instance_id
+            );
+            let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let store = EncryptedFileStore::open_default()?;
+            let mut entries = store.load()?;
+            entries.insert(name, private_key.to_string());
+            store.save(&entries)?;
+            Ok(CredentialBackend::EncryptedFile)
+        }
+    }
+}
+
+/// Retrieve the private key stored for an instance from whichever backend holds it
+pub fn retrieve_ssh_private_key(instance_id: &str) -> Result<Option<String>> {
+    let name = ssh_key_entry_name(instance_id);
+
+    if let Ok(private_key) = keyring_get(&name) {
+        return Ok(Some(private_key));
+    }
+
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let store = EncryptedFileStore::open_default()?;
+    Ok(store.load()?.remove(&name))
+}
+
+/// Store the password of the outbound SMTP server, preferring the OS keyring
+/// and falling back to the encrypted file
+pub fn store_smtp_password(password: &str) -> Result<CredentialBackend> {
+    match keyring_set(SMTP_PASSWORD_ENTRY_NAME, password) {
+        Ok(()) => Ok(CredentialBackend::Keyring),
+        Err(e) => {
+            tracing::warn!("OS keyring unavailable ({}), storing SMTP password in encrypted file", e);
+            let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let store = EncryptedFileStore::open_default()?;
+            let mut entries = store.load()?;
+            entries.insert(SMTP_PASSWORD_ENTRY_NAME.to_string(), password.to_string());
+            store.save(&entries)?;
+            Ok(CredentialBackend::EncryptedFile)
+        }
+    }
+}
+
+/// Retrieve the SMTP password from whichever backend holds it
+pub fn retrieve_smtp_password() -> Result<Option<String>> {
+    if let Ok(password) = keyring_get(SMTP_PASSWORD_ENTRY_NAME) {
+        return Ok(Some(password));
+    }
+
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let store = EncryptedFileStore::open_default()?;
+    Ok(store.load()?.remove(SMTP_PASSWORD_ENTRY_NAME))
+}
+
+/// Delete the SMTP password from every backend, ignoring a missing entry
+pub fn delete_smtp_password() -> Result<()> {
+    let _ = keyring_delete(SMTP_PASSWORD_ENTRY_NAME);
+
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let store = EncryptedFileStore::open_default()?;
+    if store.exists() {
+        let mut entries = store.load()?;
+        if entries.remove(SMTP_PASSWORD_ENTRY_NAME).is_some() {
+            store.save(&entries)?;
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// KEYRING BACKEND
+// ============================================================================
+
+fn keyring_set(name: &str, value: &str) -> keyring::Result<()> {
+    let entry = Entry::new(KEYRING_SERVICE_NAME, name)?;
+    entry.set_password(value)
+}
+
+fn keyring_get(name: &str) -> keyring::Result<String> {
+    let entry = Entry::new(KEYRING_SERVICE_NAME, name)?;
+    entry.get_password()
+}
+
+fn keyring_delete(name: &str) -> keyring::Result<()> {
+    let entry = Entry::new(KEYRING_SERVICE_NAME, name)?;
+    entry.delete_password()
+}
+
+// ============================================================================
+// ENCRYPTED FILE BACKEND
+// ============================================================================
+
+pub(crate) fn store_in_file(account_id: i64, values: &[(&str, &str)]) -> Result<()> {
+    let _guard = FILE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let store = EncryptedFileStore::open_default()?;
+    let mut entries = store.load()?;
+    for (key, value) in values {
+        entries.insert(entry_name(account_id, key), value.to_string());
+    }
+    store.save(&entries)
+}
+
+/// Credentials file in the app data directory: a 12-byte nonce followed by
+/// the AES-256-GCM ciphertext of a JSON map of entry name to secret.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    key: [u8; 32],
+}
+
+impl EncryptedFileStore {
+    pub fn new(path: impl Into<PathBuf>, secret: &[u8]) -> Self {
+        Self {
+            path: path.into(),
+            key: derive_key(secret),
+        }
+    }
+
+    /// The store in the app data directory, keyed by the passphrase if one is
+    /// set. A file left in the working directory by earlier versions is moved
+    /// there first.
+    fn open_default() -> Result<Self> {
+        let path = app_paths::app_data_dir()?.join(CREDENTIALS_FILE_NAME);
+        let legacy = Path::new(CREDENTIALS_FILE_NAME);
+        if !path.exists() && legacy.is_file() {
+            std::fs::copy(legacy, &path)
+                .and_then(|_| std::fs::remove_file(legacy))
+                .context(format!("Failed to move credentials file to {}", path.display()))?;
+            tracing::info!("Moved credentials file to {}", path.display());
+        }
+
+        let passphrase = PASSPHRASE.read().unwrap_or_else(|e| e.into_inner()).clone();
+        Ok(Self::new(path, &file_secret(passphrase.as_deref())))
+    }
+
+    /// Re-encrypt the file with `next`'s key. A file `next` can already read
+    /// is left as it is.
+    pub fn rekey(&self, next: &EncryptedFileStore) -> Result<()> {
+        if !self.exists() || next.load().is_ok() {
+            return Ok(());
+        }
+        let entries = self.load()?;
+        next.save(&entries)
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    pub fn load(&self) -> Result<HashMap<String, String>> {
+        if !self.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let bytes = std::fs::read(&self.path)
+            .context(format!("Failed to read credentials file: {}", self.path.display()))?;
+
+        if bytes.len() < NONCE_LEN {
+            return Err(anyhow::anyhow!("Credentials file is corrupt"));
+        }
+
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key));
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow::anyhow!("Failed to decrypt credentials file. If a passphrase was used, set it with set_credential_store_passphrase."))?;
+
+        serde_json::from_slice(&plaintext).context("Failed to parse credentials file")
+    }
+
+    pub fn save(&self, entries: &HashMap<String, String>) -> Result<()> {
+        let plaintext = serde_json::to_vec(entries).context("Failed to serialize credentials")?;
+
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.key));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_ref())
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt credentials"))?;
+
+        let mut bytes = nonce.to_vec();
+        bytes.extend_from_slice(&ciphertext);
+
+        write_private_file(&self.path, &bytes)
+            .context(format!("Failed to write credentials file: {}", self.path.display()))
+    }
+}
+
+/// Write `bytes` to a file only the user can read. On Unix the file is
+/// created with mode 0600, so the contents are never readable by others, and
+/// a file that already existed is restricted before it's overwritten.
+pub fn write_private_file(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+
+    let mut file = options.open(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+fn file_secret(passphrase: Option<&str>) -> Vec<u8> {
+    match passphrase {
+        Some(passphrase) => passphrase.as_bytes().to_vec(),
+        None => machine_secret(),
+    }
+}
+
+fn derive_key(secret: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(KEY_DERIVATION_CONTEXT);
+    hasher.update(secret);
+    hasher.finalize().into()
+}
+
+/// Machine-bound secret: the OS machine id when available, otherwise host and user names
+fn machine_secret() -> Vec<u8> {
+    for path in ["/etc/machine-id", "/var/lib/dbus/machine-id"] {
+        if let Ok(id) = std::fs::read_to_string(path) {
+            let id = id.trim();
+            if !id.is_empty() {
+                return id.as_bytes().to_vec();
+            }
+        }
+    }
+
+    let host = std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .unwrap_or_default();
+    let user = std::env::var("USERNAME")
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_default();
+    format!("{}:{}", host, user).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("pocket-architect-{}-{}.enc", name, uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_encrypted_file_round_trip() {
+        let path = temp_path("round-trip");
+        let store = EncryptedFileStore::new(&path, b"machine-secret");
+
+        let mut entries = HashMap::new();
+        entries.insert(entry_name(1, "access_key"), "AKIAEXAMPLE".to_string());
+        store.save(&entries).unwrap();
+
+        // The secret must not be written in clear text
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("AKIAEXAMPLE"));
+
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.get("account-1-access_key").map(String::as_str), Some("AKIAEXAMPLE"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_encrypted_file_rejects_wrong_secret() {
+        let path = temp_path("wrong-secret");
+        let store = EncryptedFileStore::new(&path, b"passphrase-one");
+        store.save(&HashMap::from([("k".to_string(), "v".to_string())])).unwrap();
+
+        let other = EncryptedFileStore::new(&path, b"passphrase-two");
+        assert!(other.load().is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_rekey_keeps_existing_entries_readable() {
+        let path = temp_path("rekey");
+        let machine = EncryptedFileStore::new(&path, b"machine-secret");
+        machine.save(&HashMap::from([(entry_name(1, "secret_key"), "s3cr3t".to_string())])).unwrap();
+
+        let passphrase = EncryptedFileStore::new(&path, b"passphrase");
+        machine.rekey(&passphrase).unwrap();
+        assert_eq!(passphrase.load().unwrap().get("account-1-secret_key").map(String::as_str), Some("s3cr3t"));
+        assert!(machine.load().is_err());
+
+        // Entering the passphrase again after a restart only unlocks the file
+        machine.rekey(&passphrase).unwrap();
+        assert_eq!(passphrase.load().unwrap().len(), 1);
+
+        // Neither key can read a file written with a third one
+        let other = EncryptedFileStore::new(&path, b"other");
+        other.save(&HashMap::new()).unwrap();
+        assert!(machine.rekey(&passphrase).is_err());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_missing_file_loads_empty() {
+        let store = EncryptedFileStore::new(temp_path("missing"), b"secret");
+        assert!(store.load().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_backend_round_trip() {
+        for backend in [CredentialBackend::Keyring, CredentialBackend::EncryptedFile] {
+            assert_eq!(CredentialBackend::from_str(backend.as_str()), backend);
+        }
+        assert_eq!(CredentialBackend::from_str("unknown"), CredentialBackend::Keyring);
+    }
+}
@@ -4,14 +4,26 @@
 // SQLite database setup with SQLx and migrations
 // ============================================================================
 
-use sqlx::{sqlite::SqlitePool, migrate::MigrateDatabase};
+use sqlx::{sqlite::{SqliteConnectOptions, SqlitePool}, migrate::MigrateDatabase};
 use anyhow::{Result, Context};
 use tauri::AppHandle;
-use keyring::{Entry, Result as KeyringResult};
+use crate::credential_store::{self, CredentialBackend};
+use std::collections::HashMap;
 
 // Database connection pool
 pub type DbPool = SqlitePool;
 
+/// A database call that was refused rather than failed: the row it needed is
+/// missing or its input is invalid. Travels inside `anyhow::Error` so commands
+/// can tell it apart from SQLite failures.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DbError {
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    InvalidInput(String),
+}
+
 // ============================================================================
 // DATABASE INITIALIZATION
 // ============================================================================
@@ -41,7 +53,7 @@ pub async fn init_database(_app_handle: Option<&AppHandle>) -> Result<DbPool> {
     }
 
     // Create connection pool
-    let pool = SqlitePool::connect(&db_url)
+    let pool = SqlitePool::connect_with(connect_options(&db_url)?)
         .await
         .context("Failed to connect to database")?;
 
@@ -52,9 +64,273 @@ pub async fn init_database(_app_handle: Option<&AppHandle>) -> Result<DbPool> {
     Ok(pool)
 }
 
+/// Connection options for every pool. Foreign keys are enforced so deleting
+/// an account or project cascades to the rows it owns, and the write-ahead log
+/// lets background syncs write while commands read.
+fn connect_options(db_url: &str) -> Result<SqliteConnectOptions> {
+    use sqlx::sqlite::SqliteJournalMode;
+    use std::str::FromStr;
+
+    Ok(SqliteConnectOptions::from_str(db_url)
+        .context("Invalid database URL")?
+        .foreign_keys(true)
+        .journal_mode(SqliteJournalMode::Wal))
+}
+
 // ============================================================================
 // MIGRATIONS
 // ============================================================================
+// Numbered schema changes, each applied once and recorded with a checksum in
+// `_sqlx_migrations`. Never edit an applied migration; append a new one.
+// ============================================================================
+
+/// A schema change applied once, in version order
+struct Migration {
+    version: i64,
+    description: &'static str,
+    steps: &'static [MigrationStep],
+}
+
+enum MigrationStep {
+    /// A statement run as-is
+    Sql(&'static str),
+    /// `ALTER TABLE ... ADD COLUMN`, skipped when the column exists. Databases
+    /// from before versioned migrations may already have it.
+    AddColumn { table: &'static str, column: &'static str, definition: &'static str },
+}
+
+use MigrationStep::{AddColumn, Sql};
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create core tables",
+        steps: &[
+            Sql(r#"
+                CREATE TABLE IF NOT EXISTS projects (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    description TEXT,
+                    region TEXT NOT NULL,
+                    platform TEXT NOT NULL,
+                    status TEXT NOT NULL DEFAULT 'active',
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                );
+            "#),
+            Sql("CREATE INDEX IF NOT EXISTS idx_projects_platform ON projects(platform);"),
+            Sql("CREATE INDEX IF NOT EXISTS idx_projects_status ON projects(status);"),
+            Sql(r#"
+                CREATE TABLE IF NOT EXISTS accounts (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    platform TEXT NOT NULL,
+                    region TEXT,
+                    project_id TEXT,
+                    subscription_id TEXT,
+                    tenant_id TEXT,
+                    client_id TEXT,
+                    status TEXT NOT NULL DEFAULT 'active',
+                    encrypted BOOLEAN NOT NULL DEFAULT 0,
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                );
+            "#),
+            Sql("CREATE INDEX IF NOT EXISTS idx_accounts_platform ON accounts(platform);"),
+            Sql("CREATE INDEX IF NOT EXISTS idx_accounts_status ON accounts(status);"),
+            // Regions scanned when syncing an account
+            Sql(r#"
+                CREATE TABLE IF NOT EXISTS account_regions (
+                    account_id INTEGER NOT NULL,
+                    region TEXT NOT NULL,
+                    enabled BOOLEAN NOT NULL DEFAULT 1,
+                    PRIMARY KEY (account_id, region),
+                    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+                );
+            "#),
+            Sql(r#"
+                CREATE TABLE IF NOT EXISTS instances (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    project_id INTEGER NOT NULL,
+                    instance_type TEXT NOT NULL,
+                    platform TEXT NOT NULL,
+                    region TEXT NOT NULL,
+                    status TEXT NOT NULL DEFAULT 'pending',
+                    public_ip TEXT,
+                    private_ip TEXT,
+                    storage_gb INTEGER NOT NULL,
+                    security_config TEXT,
+                    ssh_key TEXT,
+                    tags TEXT, -- JSON array of tag objects
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+                );
+            "#),
+            Sql("CREATE INDEX IF NOT EXISTS idx_instances_project_id ON instances(project_id);"),
+            Sql("CREATE INDEX IF NOT EXISTS idx_instances_status ON instances(status);"),
+            Sql(r#"
+                CREATE TABLE IF NOT EXISTS blueprints (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    description TEXT,
+                    instance_type TEXT NOT NULL,
+                    platform TEXT NOT NULL,
+                    region TEXT NOT NULL,
+                    storage_gb INTEGER NOT NULL,
+                    security_config TEXT,
+                    tags TEXT, -- JSON array of tag objects
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                );
+            "#),
+            Sql("CREATE INDEX IF NOT EXISTS idx_blueprints_platform ON blueprints(platform);"),
+            Sql(r#"
+                CREATE TABLE IF NOT EXISTS security_configs (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    description TEXT,
+                    platform TEXT NOT NULL,
+                    rules TEXT NOT NULL, -- JSON array of security rules
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                );
+            "#),
+            Sql("CREATE INDEX IF NOT EXISTS idx_security_configs_platform ON security_configs(platform);"),
+            Sql(r#"
+                CREATE TABLE IF NOT EXISTS images (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    description TEXT,
+                    platform TEXT NOT NULL,
+                    region TEXT NOT NULL,
+                    source_instance_id INTEGER,
+                    image_id TEXT NOT NULL, -- AWS AMI ID or similar
+                    status TEXT NOT NULL DEFAULT 'available',
+                    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+                    FOREIGN KEY (source_instance_id) REFERENCES instances(id) ON DELETE SET NULL
+                );
+            "#),
+            Sql("CREATE INDEX IF NOT EXISTS idx_images_platform ON images(platform);"),
+            Sql("CREATE INDEX IF NOT EXISTS idx_images_status ON images(status);"),
+            // App-wide settings as key/value pairs
+            Sql(r#"
+                CREATE TABLE IF NOT EXISTS settings (
+                    key TEXT PRIMARY KEY,
+                    value TEXT NOT NULL,
+                    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+                );
+            "#),
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "account credential backend, read-only flag and caller identity",
+        steps: &[
+            // Which secrets backend holds the account's credentials ('keyring' or 'encrypted_file')
+            AddColumn { table: "accounts", column: "credential_backend", definition: "TEXT NOT NULL DEFAULT 'keyring'" },
+            // Read-only accounts can be synced and inspected but never modified
+            AddColumn { table: "accounts", column: "read_only", definition: "BOOLEAN NOT NULL DEFAULT 0" },
+            // AWS account number and caller ARN reported by sts:GetCallerIdentity
+            AddColumn { table: "accounts", column: "aws_account_id", definition: "TEXT" },
+            AddColumn { table: "accounts", column: "caller_arn", definition: "TEXT" },
+        ],
+    },
+    Migration {
+        version: 3,
+        description: "account role assumption, status reason and fallback region",
+        steps: &[
+            // IAM role assumed with the account's keys for cross-account access
+            AddColumn { table: "accounts", column: "role_arn", definition: "TEXT" },
+            AddColumn { table: "accounts", column: "external_id", definition: "TEXT" },
+            // Why an account is disabled or in error, and the failed connection tests leading to it
+            AddColumn { table: "accounts", column: "status_reason", definition: "TEXT" },
+            AddColumn { table: "accounts", column: "consecutive_failures", definition: "INTEGER NOT NULL DEFAULT 0" },
+            // Region AWS clients fall back to when the account's region fails
+            AddColumn { table: "accounts", column: "fallback_region", definition: "TEXT" },
+        ],
+    },
+    Migration {
+        version: 4,
+        description: "instance ssh user, user data and elastic ip",
+        steps: &[
+            // Per-instance SSH login user, overriding the one detected from the AMI
+            AddColumn { table: "instances", column: "ssh_user", definition: "TEXT" },
+            // User data script an instance was launched with, when deployed from a blueprint
+            AddColumn { table: "instances", column: "user_data", definition: "TEXT" },
+            // Elastic IP associated with the instance, kept in step by sync
+            AddColumn { table: "instances", column: "elastic_ip", definition: "TEXT" },
+            AddColumn { table: "instances", column: "eip_allocation_id", definition: "TEXT" },
+        ],
+    },
+    Migration {
+        version: 5,
+        description: "blueprint user data and image",
+        steps: &[
+            // Cloud-init / user data script run on first boot
+            AddColumn { table: "blueprints", column: "user_data", definition: "TEXT" },
+            // AMI id or catalog alias such as "ubuntu-22.04/arm64"
+            AddColumn { table: "blueprints", column: "image", definition: "TEXT" },
+        ],
+    },
+    Migration {
+        version: 6,
+        description: "security config group id and image snapshots",
+        steps: &[
+            // EC2 security group the config was last applied to
+            AddColumn { table: "security_configs", column: "aws_group_id", definition: "TEXT" },
+            // JSON array of the EBS snapshot ids backing an AMI, recorded once it's available
+            AddColumn { table: "images", column: "snapshot_ids", definition: "TEXT" },
+        ],
+    },
+    Migration {
+        version: 7,
+        description: "instance aws id and account last sync",
+        steps: &[
+            // Sync names AWS instances by their instance id; keep it in its own column for lookups
+            AddColumn { table: "instances", column: "aws_instance_id", definition: "TEXT" },
+            Sql("UPDATE instances SET aws_instance_id = name WHERE aws_instance_id IS NULL AND platform = 'aws' AND name LIKE 'i-%';"),
+            Sql("CREATE INDEX IF NOT EXISTS idx_instances_aws_instance_id ON instances(aws_instance_id);"),
+            AddColumn { table: "accounts", column: "last_sync", definition: "TEXT" },
+        ],
+    },
+    Migration {
+        version: 8,
+        description: "project owning account",
+        steps: &[
+            // Account a project's resources belong to; deleting the account deletes its projects
+            // and, through instances.project_id, their instances
+            AddColumn { table: "projects", column: "account_id", definition: "INTEGER REFERENCES accounts(id) ON DELETE CASCADE" },
+            Sql("CREATE INDEX IF NOT EXISTS idx_projects_account_id ON projects(account_id);"),
+        ],
+    },
+    Migration {
+        version: 9,
+        description: "blueprint network placement",
+        steps: &[
+            AddColumn { table: "blueprints", column: "vpc_id", definition: "TEXT" },
+            AddColumn { table: "blueprints", column: "subnet_id", definition: "TEXT" },
+        ],
+    },
+    Migration {
+        version: 10,
+        description: "instance lifecycle",
+        steps: &[
+            // 'on-demand' or 'spot', from the launch's purchase option or the instance's lifecycle at sync
+            AddColumn { table: "instances", column: "lifecycle", definition: "TEXT NOT NULL DEFAULT 'on-demand'" },
+        ],
+    },
+    Migration {
+        version: 11,
+        description: "instance deletion protection",
+        steps: &[
+            // Refuses deleting the instance from the app until it's cleared
+            AddColumn { table: "instances", column: "protected", definition: "BOOLEAN NOT NULL DEFAULT 0" },
+        ],
+    },
+];
 
 async fn run_migrations(pool: &DbPool) -> Result<()> {
     // Create migrations table if it doesn't exist
@@ -74,217 +350,103 @@ async fn run_migrations(pool: &DbPool) -> Result<()> {
     .await
     .context("Failed to create migrations table")?;
 
-    // Run project migrations
-    run_project_migrations(pool).await?;
+    let applied: HashMap<i64, Vec<u8>> = sqlx::query_as::<_, (i64, Vec<u8>)>("SELECT version, checksum FROM _sqlx_migrations WHERE success")
+        .fetch_all(pool)
+        .await
+        .context("Failed to read applied migrations")?
+        .into_iter()
+        .collect();
+
+    for migration in MIGRATIONS {
+        let checksum = migration_checksum(migration);
+        match applied.get(&migration.version) {
+            Some(recorded) if *recorded == checksum => continue,
+            Some(_) => anyhow::bail!(
+                "Migration {} ({}) was changed after it was applied",
+                migration.version, migration.description
+            ),
+            None => apply_migration(pool, migration, checksum).await?,
+        }
+    }
 
+    println!("All migrations completed");
     Ok(())
 }
 
-async fn run_project_migrations(pool: &DbPool) -> Result<()> {
-    // Projects table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS projects (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            description TEXT,
-            region TEXT NOT NULL,
-            platform TEXT NOT NULL,
-            status TEXT NOT NULL DEFAULT 'active',
-            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-        );
-        "#,
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create projects table")?;
-
-    // Create indexes
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_projects_platform ON projects(platform);",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create projects platform index")?;
-
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_projects_status ON projects(status);",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create projects status index")?;
-
-    // Accounts table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS accounts (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            platform TEXT NOT NULL,
-            region TEXT,
-            project_id TEXT,
-            subscription_id TEXT,
-            tenant_id TEXT,
-            client_id TEXT,
-            status TEXT NOT NULL DEFAULT 'active',
-            encrypted BOOLEAN NOT NULL DEFAULT 0,
-            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-        );
-        "#,
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create accounts table")?;
-
-    // Create indexes for accounts
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_accounts_platform ON accounts(platform);",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create accounts platform index")?;
-
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_accounts_status ON accounts(status);",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create accounts status index")?;
-
-    // Instances table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS instances (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            project_id INTEGER NOT NULL,
-            instance_type TEXT NOT NULL,
-            platform TEXT NOT NULL,
-            region TEXT NOT NULL,
-            status TEXT NOT NULL DEFAULT 'pending',
-            public_ip TEXT,
-            private_ip TEXT,
-            storage_gb INTEGER NOT NULL,
-            security_config TEXT,
-            ssh_key TEXT,
-            tags TEXT, -- JSON array of tag objects
-            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
-        );
-        "#,
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create instances table")?;
-
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_instances_project_id ON instances(project_id);",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create instances project_id index")?;
+/// Run a migration's steps and record it, all in one transaction
+async fn apply_migration(pool: &DbPool, migration: &Migration, checksum: Vec<u8>) -> Result<()> {
+    let started = std::time::Instant::now();
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    for step in migration.steps {
+        match step {
+            Sql(sql) => {
+                sqlx::query(sql)
+                    .execute(&mut *tx)
+                    .await
+                    .with_context(|| format!("Migration {} ({}) failed", migration.version, migration.description))?;
+            }
+            AddColumn { table, column, definition } => add_column_if_missing(&mut *tx, table, column, definition).await?,
+        }
+    }
 
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_instances_status ON instances(status);",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create instances status index")?;
+    sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (?, ?, 1, ?, ?)")
+        .bind(migration.version)
+        .bind(migration.description)
+        .bind(checksum)
+        .bind(started.elapsed().as_nanos() as i64)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to record migration")?;
 
-    // Blueprints table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS blueprints (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            description TEXT,
-            instance_type TEXT NOT NULL,
-            platform TEXT NOT NULL,
-            region TEXT NOT NULL,
-            storage_gb INTEGER NOT NULL,
-            security_config TEXT,
-            tags TEXT, -- JSON array of tag objects
-            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-        );
-        "#,
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create blueprints table")?;
+    tx.commit().await.context(format!("Failed to commit migration {}", migration.version))?;
+    println!("Applied migration {} ({})", migration.version, migration.description);
+    Ok(())
+}
 
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_blueprints_platform ON blueprints(platform);",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create blueprints platform index")?;
+/// SHA-256 over a migration's description and steps
+fn migration_checksum(migration: &Migration) -> Vec<u8> {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(migration.description.as_bytes());
+    for step in migration.steps {
+        match step {
+            Sql(sql) => hasher.update(sql.as_bytes()),
+            AddColumn { table, column, definition } => {
+                hasher.update(format!("ADD COLUMN {}.{} {}", table, column, definition).as_bytes())
+            }
+        }
+    }
+    hasher.finalize().to_vec()
+}
 
-    // Security configs table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS security_configs (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            description TEXT,
-            platform TEXT NOT NULL,
-            rules TEXT NOT NULL, -- JSON array of security rules
-            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
-        );
-        "#,
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create security_configs table")?;
+/// Latest migration applied to the database
+pub async fn get_schema_version(pool: &DbPool) -> Result<i64> {
+    let (version,): (Option<i64>,) = sqlx::query_as("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+        .fetch_one(pool)
+        .await
+        .context("Failed to read schema version")?;
 
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_security_configs_platform ON security_configs(platform);",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create security_configs platform index")?;
+    Ok(version.unwrap_or(0))
+}
 
-    // Images table
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS images (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            name TEXT NOT NULL,
-            description TEXT,
-            platform TEXT NOT NULL,
-            region TEXT NOT NULL,
-            source_instance_id INTEGER,
-            image_id TEXT NOT NULL, -- AWS AMI ID or similar
-            status TEXT NOT NULL DEFAULT 'available',
-            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
-            FOREIGN KEY (source_instance_id) REFERENCES instances(id) ON DELETE SET NULL
-        );
-        "#,
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create images table")?;
+/// Add a column to an existing table, skipping it if a previous run already added it
+async fn add_column_if_missing(conn: &mut sqlx::SqliteConnection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let columns: Vec<(String,)> = sqlx::query_as(&format!("SELECT name FROM pragma_table_info('{}')", table))
+        .fetch_all(&mut *conn)
+        .await
+        .context(format!("Failed to read columns of {}", table))?;
 
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_images_platform ON images(platform);",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create images platform index")?;
+    if columns.iter().any(|(name,)| name == column) {
+        return Ok(());
+    }
 
-    sqlx::query(
-        "CREATE INDEX IF NOT EXISTS idx_images_status ON images(status);",
-    )
-    .execute(pool)
-    .await
-    .context("Failed to create images status index")?;
+    sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+        .execute(&mut *conn)
+        .await
+        .context(format!("Failed to add {}.{} column", table, column))?;
 
-    println!("All migrations completed");
     Ok(())
 }
 
@@ -302,6 +464,8 @@ pub struct Project {
     pub status: String,
     pub created_at: String,
     pub updated_at: String,
+    /// Account owning the project, if any
+    pub account_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -310,6 +474,8 @@ pub struct CreateProjectRequest {
     pub description: Option<String>,
     pub region: String,
     pub platform: String,
+    #[serde(default)]
+    pub account_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -349,6 +515,21 @@ pub struct Account {
     pub updated_at: String,
     // Encryption flag
     pub encrypted: bool,
+    // Secrets backend: 'keyring' or 'encrypted_file'
+    pub credential_backend: String,
+    // Blocks every mutating cloud operation for the account
+    pub read_only: bool,
+    // Identity the credentials resolved to when last tested (AWS only)
+    pub aws_account_id: Option<String>,
+    pub caller_arn: Option<String>,
+    // Role assumed with the stored keys, for hub-and-member account setups (AWS only)
+    pub role_arn: Option<String>,
+    pub external_id: Option<String>,
+    // Why the account is 'disabled' or in 'error'
+    pub status_reason: Option<String>,
+    pub consecutive_failures: i64,
+    // Region to fall back to when `region` fails; a same-partition default when unset
+    pub fallback_region: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -365,6 +546,27 @@ pub struct CreateAccountRequest {
     pub subscription_id: Option<String>,
     pub tenant_id: Option<String>,
     pub service_account_key: Option<String>,
+    #[serde(default)]
+    pub read_only: Option<bool>,
+    #[serde(default)]
+    pub role_arn: Option<String>,
+    #[serde(default)]
+    pub external_id: Option<String>,
+    #[serde(default)]
+    pub fallback_region: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::FromRow)]
+pub struct AccountRegion {
+    pub account_id: i64,
+    pub region: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AccountRegionSetting {
+    pub region: String,
+    pub enabled: bool,
 }
 
 pub struct UpdateAccountRequest {
@@ -376,34 +578,41 @@ pub struct UpdateAccountRequest {
 }
 
 // ============================================================================
-// KEYRING UTILITIES
+// CREDENTIAL UTILITIES
 // ============================================================================
 
-fn get_keyring_service_name() -> &'static str {
-    "pocket-architect"
+// Every secret key an account may have stored
+const CREDENTIAL_KEYS: [&str; 4] = ["access_key", "secret_key", "service_account_key", "client_secret"];
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CredentialMigrationReport {
+    pub migrated_accounts: Vec<i64>,
+    pub failed_accounts: Vec<CredentialMigrationFailure>,
 }
 
-fn store_credential(account_id: i64, key: &str, value: &str) -> KeyringResult<()> {
-    let service = get_keyring_service_name();
-    let username = format!("account-{}-{}", account_id, key);
-    let entry = Entry::new(service, &username)?;
-    entry.set_password(value)?;
-    Ok(())
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CredentialMigrationFailure {
+    pub account_id: i64,
+    pub error: String,
 }
 
-fn retrieve_credential(account_id: i64, key: &str) -> KeyringResult<String> {
-    let service = get_keyring_service_name();
-    let username = format!("account-{}-{}", account_id, key);
-    let entry = Entry::new(service, &username)?;
-    entry.get_password()
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CredentialLinkageReport {
+    pub checked_accounts: usize,
+    pub repair: bool,
+    pub issues: Vec<CredentialLinkageIssue>,
 }
 
-fn delete_credential(account_id: i64, key: &str) -> KeyringResult<()> {
-    let service = get_keyring_service_name();
-    let username = format!("account-{}-{}", account_id, key);
-    let entry = Entry::new(service, &username)?;
-    entry.delete_password()?;
-    Ok(())
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CredentialLinkageIssue {
+    pub account_id: i64,
+    pub account_name: String,
+    pub encrypted: bool,
+    pub recorded_backend: String,
+    // Backend that actually holds credentials, if any
+    pub actual_backend: Option<CredentialBackend>,
+    pub problem: String,
+    pub repaired: bool,
 }
 
 // ============================================================================
@@ -473,20 +682,54 @@ pub async fn get_project(pool: &DbPool, id: i64) -> Result<Option<Project>> {
     Ok(project)
 }
 
+/// Account owning a project, if the project exists and has one
+pub async fn get_project_account_id(pool: &DbPool, project_id: i64) -> Result<Option<i64>> {
+    let account_id: Option<Option<i64>> = sqlx::query_scalar("SELECT account_id FROM projects WHERE id = ?")
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch project account")?;
+
+    Ok(account_id.flatten())
+}
+
+/// Project an account's synced instances are filed under: the account's
+/// oldest project, or a new one named after the account if it has none
+pub async fn get_or_create_sync_project(pool: &DbPool, account: &Account) -> Result<Project> {
+    let existing = sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE account_id = ? ORDER BY id LIMIT 1")
+        .bind(account.id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch account projects")?;
+    if let Some(project) = existing {
+        return Ok(project);
+    }
+
+    create_project(pool, CreateProjectRequest {
+        name: account.name.clone(),
+        description: Some(format!("Resources synced from account {}", account.name)),
+        region: account.region.clone().unwrap_or_else(|| crate::regions::DEFAULT_REGION.to_string()),
+        platform: account.platform.clone(),
+        account_id: Some(account.id),
+    })
+    .await
+}
+
 pub async fn create_project(
     pool: &DbPool,
     request: CreateProjectRequest,
 ) -> Result<Project> {
     let result = sqlx::query(
         r#"
-        INSERT INTO projects (name, description, region, platform, status)
-        VALUES (?, ?, ?, ?, 'active')
+        INSERT INTO projects (name, description, region, platform, status, account_id)
+        VALUES (?, ?, ?, ?, 'active', ?)
         "#,
     )
     .bind(&request.name)
     .bind(&request.description)
     .bind(&request.region)
     .bind(&request.platform)
+    .bind(request.account_id)
     .execute(pool)
     .await
     .context("Failed to create project")?;
@@ -559,6 +802,23 @@ pub async fn update_project(
     get_project(pool, id).await
 }
 
+/// Give a project a new, non-empty name
+pub async fn rename_project(pool: &DbPool, id: i64, new_name: &str) -> Result<Option<Project>> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        anyhow::bail!(DbError::InvalidInput("Project name cannot be empty".to_string()));
+    }
+
+    let request = UpdateProjectRequest {
+        name: Some(new_name.to_string()),
+        description: None,
+        region: None,
+        platform: None,
+        status: None,
+    };
+    update_project(pool, id, request).await
+}
+
 pub async fn delete_project(pool: &DbPool, id: i64) -> Result<bool> {
     let result = sqlx::query("DELETE FROM projects WHERE id = ?")
         .bind(id)
@@ -569,6 +829,108 @@ pub async fn delete_project(pool: &DbPool, id: i64) -> Result<bool> {
     Ok(result.rows_affected() > 0)
 }
 
+/// Delete a project and its instance rows in one transaction. Returns the
+/// number of instances removed, or None when the project doesn't exist.
+pub async fn delete_project_with_instances(pool: &DbPool, id: i64) -> Result<Option<usize>> {
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    let instances = sqlx::query("DELETE FROM instances WHERE project_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to delete project instances")?;
+
+    let project = sqlx::query("DELETE FROM projects WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to delete project")?;
+
+    if project.rows_affected() == 0 {
+        // Dropping the transaction rolls back the instance delete
+        return Ok(None);
+    }
+
+    tx.commit().await.context("Failed to commit project deletion")?;
+    Ok(Some(instances.rows_affected() as usize))
+}
+
+/// A project's status as derived from its instances
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProjectHealth {
+    pub project_id: i64,
+    pub status: String,
+    pub instance_count: i64,
+    /// Number of instances in each status
+    pub status_counts: HashMap<String, i64>,
+}
+
+/// Aggregate instance statuses into a project status. The first rule that
+/// matches wins:
+/// 1. no instances: "empty"
+/// 2. every instance is "error": "error"
+/// 3. any instance is "error", "degraded" or "unknown": "degraded"
+/// 4. every instance is "healthy": "healthy"
+/// 5. every instance is "stopped": "stopped"
+/// 6. any instance is still changing state (pending, starting, ...): "pending"
+/// 7. otherwise, a mix of healthy and stopped instances: "partial"
+pub fn aggregate_project_health(statuses: &[&str]) -> &'static str {
+    let all = |status: &str| statuses.iter().all(|s| *s == status);
+    let any = |wanted: &[&str]| statuses.iter().any(|s| wanted.contains(s));
+
+    if statuses.is_empty() {
+        "empty"
+    } else if all("error") {
+        "error"
+    } else if any(&["error", "degraded", "unknown"]) {
+        "degraded"
+    } else if all("healthy") {
+        "healthy"
+    } else if all("stopped") {
+        "stopped"
+    } else if statuses.iter().any(|s| *s != "healthy" && *s != "stopped") {
+        "pending"
+    } else {
+        "partial"
+    }
+}
+
+/// Derive a project's status from its instances, saving it on the project
+/// row when `persist` is set. `None` if the project doesn't exist.
+pub async fn compute_project_health(pool: &DbPool, project_id: i64, persist: bool) -> Result<Option<ProjectHealth>> {
+    if get_project(pool, project_id).await?.is_none() {
+        return Ok(None);
+    }
+
+    let statuses: Vec<String> = sqlx::query_scalar("SELECT status FROM instances WHERE project_id = ?")
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch project instance statuses")?;
+
+    let mut status_counts = HashMap::new();
+    for status in &statuses {
+        *status_counts.entry(status.clone()).or_insert(0) += 1;
+    }
+    let status = aggregate_project_health(&statuses.iter().map(String::as_str).collect::<Vec<_>>()).to_string();
+
+    if persist {
+        sqlx::query("UPDATE projects SET status = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(&status)
+            .bind(project_id)
+            .execute(pool)
+            .await
+            .context("Failed to update project status")?;
+    }
+
+    Ok(Some(ProjectHealth {
+        project_id,
+        status,
+        instance_count: statuses.len() as i64,
+        status_counts,
+    }))
+}
+
 // ============================================================================
 // ACCOUNT FUNCTIONS
 // ============================================================================
@@ -596,14 +958,66 @@ pub async fn get_account(pool: &DbPool, id: i64) -> Result<Option<Account>> {
     Ok(account)
 }
 
+/// Reject role ARNs that sts:AssumeRole would refuse
+fn validate_role_arn(role_arn: Option<&str>) -> Result<()> {
+    let Some(role_arn) = role_arn.filter(|arn| !arn.is_empty()) else {
+        return Ok(());
+    };
+
+    // arn:partition:iam::account:role/name
+    let parts: Vec<&str> = role_arn.splitn(6, ':').collect();
+    let valid = matches!(
+        parts.as_slice(),
+        ["arn", partition, "iam", "", account, resource]
+            if !partition.is_empty()
+                && account.len() == 12
+                && account.chars().all(|c| c.is_ascii_digit())
+                && resource.strip_prefix("role/").is_some_and(|name| !name.is_empty())
+    );
+    if !valid {
+        anyhow::bail!(DbError::InvalidInput(format!("Invalid role ARN '{}'. Expected arn:aws:iam::<account-id>:role/<name>", role_arn)));
+    }
+    Ok(())
+}
+
+/// A fallback region must be a known AWS region other than the account's own
+fn validate_fallback_region(region: Option<&str>, fallback_region: Option<&str>) -> Result<()> {
+    let Some(fallback) = fallback_region.filter(|r| !r.is_empty()) else {
+        return Ok(());
+    };
+    if !crate::regions::is_valid_region(fallback) {
+        anyhow::bail!(DbError::InvalidInput(format!("Unknown AWS region: {}", fallback)));
+    }
+    if region == Some(fallback) {
+        anyhow::bail!(DbError::InvalidInput("Fallback region must differ from the account region".to_string()));
+    }
+    Ok(())
+}
+
 pub async fn create_account(pool: &DbPool, request: CreateAccountRequest) -> Result<Account> {
+    create_account_with(pool, request, credential_store::store_credentials).await
+}
+
+/// Create the account row and store its secrets as one unit: the row is only
+/// committed once the secrets are stored, so a credential store failure
+/// leaves no account behind
+async fn create_account_with<F>(pool: &DbPool, request: CreateAccountRequest, store: F) -> Result<Account>
+where
+    F: FnOnce(i64, &[(&str, &str)]) -> Result<CredentialBackend>,
+{
+    validate_role_arn(request.role_arn.as_deref())?;
+    validate_fallback_region(request.region.as_deref(), request.fallback_region.as_deref())?;
+
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
     let result = sqlx::query(
         r#"
         INSERT INTO accounts (
             name, platform, region, project_id, subscription_id,
-            tenant_id, client_id, encrypted
+            tenant_id, client_id, encrypted, read_only, role_arn, external_id,
+            fallback_region
         )
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(&request.name)
@@ -614,30 +1028,41 @@ pub async fn create_account(pool: &DbPool, request: CreateAccountRequest) -> Res
     .bind(&request.tenant_id)
     .bind(&request.client_id)
     .bind(request.encrypted)
-    .execute(pool)
+    .bind(request.read_only.unwrap_or(false))
+    .bind(request.role_arn.as_deref().filter(|arn| !arn.is_empty()))
+    .bind(request.external_id.as_deref().filter(|id| !id.is_empty()))
+    .bind(request.fallback_region.as_deref().filter(|r| !r.is_empty()))
+    .execute(&mut *tx)
     .await
     .context("Failed to create account")?;
 
     let account_id = result.last_insert_rowid();
 
-    // Store sensitive credentials in keyring if encryption is enabled
+    // Store sensitive credentials in keyring (or the encrypted file fallback) if encryption is enabled
     if request.encrypted {
-        if let Some(access_key) = &request.access_key {
-            store_credential(account_id, "access_key", access_key)
-                .context("Failed to store access key in keyring")?;
-        }
-        if let Some(secret_key) = &request.secret_key {
-            store_credential(account_id, "secret_key", secret_key)
-                .context("Failed to store secret key in keyring")?;
-        }
-        if let Some(service_account_key) = &request.service_account_key {
-            store_credential(account_id, "service_account_key", service_account_key)
-                .context("Failed to store service account key in keyring")?;
+        let secrets = account_secrets(&request);
+
+        // Dropping the transaction on error rolls back the insert
+        let backend = store(account_id, &secrets)
+            .context("Failed to store account credentials; the account was not created")?;
+
+        let recorded = sqlx::query("UPDATE accounts SET credential_backend = ? WHERE id = ?")
+            .bind(backend.as_str())
+            .bind(account_id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to record credential backend; the account was not created");
+        if let Err(e) = recorded {
+            discard_credentials(account_id);
+            return Err(e);
         }
-        if let Some(client_secret) = &request.client_secret {
-            store_credential(account_id, "client_secret", client_secret)
-                .context("Failed to store client secret in keyring")?;
+    }
+
+    if let Err(e) = tx.commit().await {
+        if request.encrypted {
+            discard_credentials(account_id);
         }
+        return Err(anyhow::Error::new(e).context("Failed to create account"));
     }
 
     // Fetch the created account
@@ -646,37 +1071,75 @@ pub async fn create_account(pool: &DbPool, request: CreateAccountRequest) -> Res
         .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created account"))
 }
 
+/// The secrets set on a request, keyed by their credential store name
+fn account_secrets(request: &CreateAccountRequest) -> Vec<(&'static str, &str)> {
+    [
+        ("access_key", &request.access_key),
+        ("secret_key", &request.secret_key),
+        ("service_account_key", &request.service_account_key),
+        ("client_secret", &request.client_secret),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| value.as_deref().map(|v| (key, v)))
+    .collect()
+}
+
+/// Remove secrets stored for an account whose row was rolled back
+fn discard_credentials(account_id: i64) {
+    if let Err(e) = credential_store::delete_credentials(account_id, &CREDENTIAL_KEYS) {
+        tracing::warn!("Failed to remove credentials for uncreated account {}: {}", account_id, e);
+    }
+}
+
 pub async fn update_account(pool: &DbPool, id: i64, request: CreateAccountRequest) -> Result<Option<Account>> {
+    validate_role_arn(request.role_arn.as_deref())?;
+    validate_fallback_region(request.region.as_deref(), request.fallback_region.as_deref())?;
+
     let result = sqlx::query(
         r#"
         UPDATE accounts SET
-            name = ?, platform = ?, access_key = ?, secret_key = ?, region = ?,
-            project_id = ?, service_account_key = ?, subscription_id = ?,
-            tenant_id = ?, client_id = ?, client_secret = ?, updated_at = CURRENT_TIMESTAMP
+            name = ?, platform = ?, region = ?, project_id = ?, subscription_id = ?,
+            tenant_id = ?, client_id = ?,
+            read_only = COALESCE(?, read_only), role_arn = ?, external_id = ?,
+            fallback_region = ?, updated_at = CURRENT_TIMESTAMP
         WHERE id = ?
         "#,
     )
     .bind(&request.name)
     .bind(&request.platform)
-    .bind(&request.access_key)
-    .bind(&request.secret_key)
     .bind(&request.region)
     .bind(&request.project_id)
-    .bind(&request.service_account_key)
     .bind(&request.subscription_id)
     .bind(&request.tenant_id)
     .bind(&request.client_id)
-    .bind(&request.client_secret)
+    .bind(request.read_only)
+    .bind(request.role_arn.as_deref().filter(|arn| !arn.is_empty()))
+    .bind(request.external_id.as_deref().filter(|id| !id.is_empty()))
+    .bind(request.fallback_region.as_deref().filter(|r| !r.is_empty()))
     .bind(id)
     .execute(pool)
     .await
     .context("Failed to update account")?;
 
-    if result.rows_affected() > 0 {
-        get_account(pool, id).await
-    } else {
-        Ok(None)
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    // Secrets never live in the database; replace the stored ones when new values are given
+    let secrets = account_secrets(&request);
+    if request.encrypted && !secrets.is_empty() {
+        let backend = credential_store::store_credentials(id, &secrets)
+            .context("Failed to store account credentials")?;
+
+        sqlx::query("UPDATE accounts SET credential_backend = ? WHERE id = ?")
+            .bind(backend.as_str())
+            .bind(id)
+            .execute(pool)
+            .await
+            .context("Failed to record credential backend")?;
     }
+
+    get_account(pool, id).await
 }
 
 pub async fn update_account_fields(pool: &DbPool, id: i64, request: UpdateAccountRequest) -> Result<Option<Account>> {
@@ -697,12 +1160,40 @@ pub async fn update_account_fields(pool: &DbPool, id: i64, request: UpdateAccoun
     Ok(None)
 }
 
-pub async fn delete_account(pool: &DbPool, id: i64) -> Result<bool> {
-    // First, delete credentials from keyring if they exist
-    let _ = delete_credential(id, "access_key"); // Ignore errors if credential doesn't exist
-    let _ = delete_credential(id, "secret_key");
-    let _ = delete_credential(id, "service_account_key");
-    let _ = delete_credential(id, "client_secret");
+#[derive(Debug, Clone)]
+pub enum AccountDeletion {
+    Deleted { projects: usize, instances: usize },
+    NotFound,
+    /// The account still owns projects; `instances` counts the instances in them
+    HasDependents { projects: Vec<Project>, instances: usize },
+}
+
+/// Delete an account and its stored credentials. Accounts that own projects
+/// are only deleted when `force` is set, which cascades to the projects, their
+/// instances and the account's region settings.
+pub async fn delete_account(pool: &DbPool, id: i64, force: bool) -> Result<AccountDeletion> {
+    if get_account(pool, id).await?.is_none() {
+        return Ok(AccountDeletion::NotFound);
+    }
+
+    let projects = sqlx::query_as::<_, Project>("SELECT * FROM projects WHERE account_id = ? ORDER BY name")
+        .bind(id)
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch account projects")?;
+
+    let (instances,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM instances WHERE project_id IN (SELECT id FROM projects WHERE account_id = ?)",
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+    .context("Failed to count account instances")?;
+    let instances = instances as usize;
+
+    if !projects.is_empty() && !force {
+        return Ok(AccountDeletion::HasDependents { projects, instances });
+    }
 
     let result = sqlx::query("DELETE FROM accounts WHERE id = ?")
         .bind(id)
@@ -710,42 +1201,376 @@ pub async fn delete_account(pool: &DbPool, id: i64) -> Result<bool> {
         .await
         .context("Failed to delete account")?;
 
-    Ok(result.rows_affected() > 0)
+    if result.rows_affected() == 0 {
+        return Ok(AccountDeletion::NotFound);
+    }
+
+    // Only drop the credentials once the account is gone, so a failed delete leaves it usable
+    if let Err(e) = credential_store::delete_credentials(id, &CREDENTIAL_KEYS) {
+        tracing::warn!("Failed to delete stored credentials for account {}: {}", id, e);
+    }
+
+    if !projects.is_empty() {
+        tracing::warn!("Force-deleted account {} with {} projects and {} instances", id, projects.len(), instances);
+    }
+
+    Ok(AccountDeletion::Deleted { projects: projects.len(), instances })
 }
 
 // ============================================================================
-// CREDENTIAL RETRIEVAL FUNCTIONS
+// ACCOUNT REGION FUNCTIONS
 // ============================================================================
 
-pub async fn get_account_credentials(pool: &DbPool, account_id: i64) -> Result<AccountCredentials> {
-    let account = get_account(pool, account_id)
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("Account not found"))?;
+pub async fn get_account_regions(pool: &DbPool, account_id: i64) -> Result<Vec<AccountRegion>> {
+    sqlx::query_as::<_, AccountRegion>("SELECT * FROM account_regions WHERE account_id = ? ORDER BY region")
+        .bind(account_id)
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch account regions")
+}
 
-    let mut credentials = AccountCredentials {
-        access_key: None,
+/// Replace an account's region configuration. Every region must be a known AWS region.
+pub async fn set_account_regions(pool: &DbPool, account_id: i64, regions: Vec<AccountRegionSetting>) -> Result<Vec<AccountRegion>> {
+    if let Some(invalid) = regions.iter().find(|r| !crate::regions::is_valid_region(&r.region)) {
+        return Err(DbError::InvalidInput(format!("Unknown AWS region: {}", invalid.region)).into());
+    }
+
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    sqlx::query("DELETE FROM account_regions WHERE account_id = ?")
+        .bind(account_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to clear account regions")?;
+
+    for setting in &regions {
+        sqlx::query("INSERT OR REPLACE INTO account_regions (account_id, region, enabled) VALUES (?, ?, ?)")
+            .bind(account_id)
+            .bind(&setting.region)
+            .bind(setting.enabled)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to store account region")?;
+    }
+
+    tx.commit().await.context("Failed to commit account regions")?;
+
+    get_account_regions(pool, account_id).await
+}
+
+/// Regions to scan for an account: its enabled regions, or the account's own
+/// region when none are configured
+pub async fn get_enabled_account_regions(pool: &DbPool, account: &Account) -> Result<Vec<String>> {
+    let enabled: Vec<String> = get_account_regions(pool, account.id)
+        .await?
+        .into_iter()
+        .filter(|r| r.enabled)
+        .map(|r| r.region)
+        .collect();
+
+    if !enabled.is_empty() {
+        return Ok(enabled);
+    }
+
+    Ok(vec![account.region.clone().unwrap_or_else(|| crate::regions::DEFAULT_REGION.to_string())])
+}
+
+/// Whether mutating cloud operations are blocked for an account. Unknown
+/// accounts are not read-only, so callers go on to report them as missing.
+pub async fn is_account_read_only(pool: &DbPool, account_id: i64) -> Result<bool> {
+    let read_only: Option<bool> = sqlx::query_scalar("SELECT read_only FROM accounts WHERE id = ?")
+        .bind(account_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to check account read-only flag")?;
+
+    Ok(read_only.unwrap_or(false))
+}
+
+/// Accounts that sync, cost and health operations run against
+pub async fn get_active_accounts(pool: &DbPool) -> Result<Vec<Account>> {
+    sqlx::query_as::<_, Account>("SELECT * FROM accounts WHERE status = 'active' ORDER BY created_at DESC")
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch active accounts")
+}
+
+pub async fn is_account_active(pool: &DbPool, account_id: i64) -> Result<bool> {
+    let status: Option<String> = sqlx::query_scalar("SELECT status FROM accounts WHERE id = ?")
+        .bind(account_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to check account status")?;
+
+    Ok(status.as_deref() == Some("active"))
+}
+
+/// Enable or disable an account. 'error' is only set by failed connection tests.
+pub async fn set_account_status(pool: &DbPool, account_id: i64, status: &str) -> Result<Option<Account>> {
+    let reason = match status {
+        "active" => None,
+        "disabled" => Some("Disabled by user"),
+        other => anyhow::bail!(DbError::InvalidInput(format!("Invalid account status '{}'. Use 'active' or 'disabled'", other))),
+    };
+
+    let result = sqlx::query(
+        r#"
+        UPDATE accounts SET status = ?, status_reason = ?, consecutive_failures = 0,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        "#,
+    )
+    .bind(status)
+    .bind(reason)
+    .bind(account_id)
+    .execute(pool)
+    .await
+    .context("Failed to update account status")?;
+
+    if result.rows_affected() > 0 {
+        get_account(pool, account_id).await
+    } else {
+        Ok(None)
+    }
+}
+
+/// Count a failed or successful credential check. Once failures in a row
+/// reach the configured threshold an active account moves to 'error'; a
+/// success clears the count and returns an errored account to 'active'.
+/// Disabled accounts keep their status either way.
+pub async fn record_connection_result(pool: &DbPool, account_id: i64, failure: Option<&str>) -> Result<Option<Account>> {
+    match failure {
+        None => {
+            sqlx::query(
+                r#"
+                UPDATE accounts SET
+                    consecutive_failures = 0,
+                    status_reason = CASE WHEN status = 'error' THEN NULL ELSE status_reason END,
+                    status = CASE WHEN status = 'error' THEN 'active' ELSE status END
+                WHERE id = ?
+                "#,
+            )
+            .bind(account_id)
+            .execute(pool)
+            .await
+            .context("Failed to record connection success")?;
+        }
+        Some(error) => {
+            let threshold = get_credential_failure_threshold(pool).await?;
+            sqlx::query(
+                r#"
+                UPDATE accounts SET
+                    consecutive_failures = consecutive_failures + 1,
+                    status_reason = CASE WHEN status = 'active' AND consecutive_failures + 1 >= ?1
+                        THEN printf('%d consecutive connection failures: %s', consecutive_failures + 1, ?2)
+                        ELSE status_reason END,
+                    status = CASE WHEN status = 'active' AND consecutive_failures + 1 >= ?1
+                        THEN 'error' ELSE status END
+                WHERE id = ?3
+                "#,
+            )
+            .bind(threshold)
+            .bind(error)
+            .bind(account_id)
+            .execute(pool)
+            .await
+            .context("Failed to record connection failure")?;
+        }
+    }
+
+    get_account(pool, account_id).await
+}
+
+/// Outcome of comparing the identity behind an account's credentials with the stored one
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AccountIdentityCheck {
+    /// No identity was stored yet; it has been recorded
+    Recorded,
+    /// Same AWS account as before; the caller ARN has been refreshed
+    Matches,
+    /// The credentials now belong to a different AWS account. The stored
+    /// identity is left untouched.
+    Mismatch { stored_account_id: String },
+}
+
+/// Compare the AWS account behind an account's credentials with the stored
+/// one, recording it unless it changed
+pub async fn record_account_identity(pool: &DbPool, account_id: i64, aws_account_id: &str, caller_arn: &str) -> Result<AccountIdentityCheck> {
+    let stored: Option<Option<String>> = sqlx::query_scalar("SELECT aws_account_id FROM accounts WHERE id = ?")
+        .bind(account_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to read account identity")?;
+
+    let check = match stored.ok_or_else(|| DbError::NotFound("Account not found".to_string()))? {
+        Some(stored) if stored != aws_account_id => {
+            return Ok(AccountIdentityCheck::Mismatch { stored_account_id: stored });
+        }
+        Some(_) => AccountIdentityCheck::Matches,
+        None => AccountIdentityCheck::Recorded,
+    };
+
+    sqlx::query("UPDATE accounts SET aws_account_id = ?, caller_arn = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+        .bind(aws_account_id)
+        .bind(caller_arn)
+        .bind(account_id)
+        .execute(pool)
+        .await
+        .context("Failed to record account identity")?;
+
+    Ok(check)
+}
+
+/// Other configured accounts whose credentials resolved to the same AWS account
+pub async fn get_accounts_sharing_aws_account(pool: &DbPool, account_id: i64, aws_account_id: &str) -> Result<Vec<Account>> {
+    sqlx::query_as::<_, Account>("SELECT * FROM accounts WHERE aws_account_id = ? AND id != ? ORDER BY id")
+        .bind(aws_account_id)
+        .bind(account_id)
+        .fetch_all(pool)
+        .await
+        .context("Failed to find accounts sharing an AWS account")
+}
+
+// ============================================================================
+// CREDENTIAL RETRIEVAL FUNCTIONS
+// ============================================================================
+
+pub async fn get_account_credentials(pool: &DbPool, account_id: i64) -> Result<AccountCredentials> {
+    let account = get_account(pool, account_id)
+        .await?
+        .ok_or_else(|| DbError::NotFound("Account not found".to_string()))?;
+
+    let mut credentials = AccountCredentials {
+        access_key: None,
         secret_key: None,
         service_account_key: None,
         client_secret: None,
     };
 
     if account.encrypted {
-        // Retrieve from keyring
-        if let Ok(access_key) = retrieve_credential(account_id, "access_key") {
-            credentials.access_key = Some(access_key);
-        }
-        if let Ok(secret_key) = retrieve_credential(account_id, "secret_key") {
-            credentials.secret_key = Some(secret_key);
-        }
-        if let Ok(service_account_key) = retrieve_credential(account_id, "service_account_key") {
-            credentials.service_account_key = Some(service_account_key);
+        // Retrieve from whichever backend the account was stored in
+        let backend = CredentialBackend::from_str(&account.credential_backend);
+        credentials.access_key = credential_store::retrieve_credential(backend, account_id, "access_key")?;
+        credentials.secret_key = credential_store::retrieve_credential(backend, account_id, "secret_key")?;
+        credentials.service_account_key = credential_store::retrieve_credential(backend, account_id, "service_account_key")?;
+        credentials.client_secret = credential_store::retrieve_credential(backend, account_id, "client_secret")?;
+    }
+
+    Ok(credentials)
+}
+
+/// Move credentials of every account stored in the encrypted file fallback into the OS keyring
+pub async fn migrate_credentials_to_keyring(pool: &DbPool) -> Result<CredentialMigrationReport> {
+    let accounts = sqlx::query_as::<_, Account>(
+        "SELECT * FROM accounts WHERE credential_backend = ?"
+    )
+    .bind(CredentialBackend::EncryptedFile.as_str())
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch accounts using the encrypted file store")?;
+
+    let mut report = CredentialMigrationReport {
+        migrated_accounts: Vec::new(),
+        failed_accounts: Vec::new(),
+    };
+
+    for account in accounts {
+        match credential_store::migrate_to_keyring(account.id, &CREDENTIAL_KEYS) {
+            Ok(_) => {
+                sqlx::query("UPDATE accounts SET credential_backend = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+                    .bind(CredentialBackend::Keyring.as_str())
+                    .bind(account.id)
+                    .execute(pool)
+                    .await
+                    .context("Failed to record credential backend")?;
+                report.migrated_accounts.push(account.id);
+            }
+            Err(e) => report.failed_accounts.push(CredentialMigrationFailure {
+                account_id: account.id,
+                error: e.to_string(),
+            }),
         }
-        if let Ok(client_secret) = retrieve_credential(account_id, "client_secret") {
-            credentials.client_secret = Some(client_secret);
+    }
+
+    Ok(report)
+}
+
+/// Check every account's `encrypted` flag and recorded backend against where its
+/// credentials actually are. With `repair`, the flag and backend are updated to match.
+pub async fn verify_credential_linkage(pool: &DbPool, repair: bool) -> Result<CredentialLinkageReport> {
+    verify_credential_linkage_with(pool, repair, |account_id| {
+        credential_store::locate_credentials(account_id, &CREDENTIAL_KEYS)
+    })
+    .await
+}
+
+async fn verify_credential_linkage_with<F>(pool: &DbPool, repair: bool, locate: F) -> Result<CredentialLinkageReport>
+where
+    F: Fn(i64) -> Result<Option<CredentialBackend>>,
+{
+    let accounts = sqlx::query_as::<_, Account>("SELECT * FROM accounts ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch accounts")?;
+
+    let mut report = CredentialLinkageReport {
+        checked_accounts: accounts.len(),
+        repair,
+        issues: Vec::new(),
+    };
+
+    for account in accounts {
+        let recorded = CredentialBackend::from_str(&account.credential_backend);
+
+        let actual = match locate(account.id) {
+            Ok(actual) => actual,
+            Err(e) => {
+                report.issues.push(linkage_issue(&account, None, format!("Failed to look up stored credentials: {}", e)));
+                continue;
+            }
+        };
+
+        let problem = match actual {
+            Some(backend) if !account.encrypted => format!(
+                "Credentials exist in {} but the account is not marked encrypted", backend.as_str()
+            ),
+            Some(backend) if backend != recorded => format!(
+                "Credentials exist in {} but the account records {}", backend.as_str(), recorded.as_str()
+            ),
+            None if account.encrypted => "Account is marked encrypted but no stored credentials were found".to_string(),
+            _ => continue,
+        };
+
+        let mut issue = linkage_issue(&account, actual, problem);
+
+        if repair {
+            sqlx::query("UPDATE accounts SET encrypted = ?, credential_backend = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+                .bind(actual.is_some())
+                .bind(actual.unwrap_or(recorded).as_str())
+                .bind(account.id)
+                .execute(pool)
+                .await
+                .context("Failed to repair credential linkage")?;
+            issue.repaired = true;
         }
+
+        report.issues.push(issue);
     }
 
-    Ok(credentials)
+    Ok(report)
+}
+
+fn linkage_issue(account: &Account, actual_backend: Option<CredentialBackend>, problem: String) -> CredentialLinkageIssue {
+    CredentialLinkageIssue {
+        account_id: account.id,
+        account_name: account.name.clone(),
+        encrypted: account.encrypted,
+        recorded_backend: account.credential_backend.clone(),
+        actual_backend,
+        problem,
+        repaired: false,
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -777,6 +1602,15 @@ pub struct Instance {
     pub tags: Option<String>, // JSON
     pub created_at: String,
     pub updated_at: String,
+    pub ssh_user: Option<String>,
+    pub user_data: Option<String>,
+    pub elastic_ip: Option<String>,
+    pub eip_allocation_id: Option<String>,
+    pub aws_instance_id: Option<String>,
+    /// "on-demand" or "spot"
+    pub lifecycle: String,
+    /// Deleting the instance from the app is refused while set
+    pub protected: bool,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -790,6 +1624,9 @@ pub struct CreateInstanceRequest {
     pub security_config: Option<String>,
     pub ssh_key: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// "on-demand" (the default) or "spot"
+    #[serde(default)]
+    pub lifecycle: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -800,6 +1637,9 @@ pub struct UpdateInstanceRequest {
     pub security_config: Option<String>,
     pub ssh_key: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// SSH login user override; an empty string clears it so the user
+    /// detected from the AMI applies again
+    pub ssh_user: Option<String>,
 }
 
 // ============================================================================
@@ -819,6 +1659,11 @@ pub struct Blueprint {
     pub tags: Option<String>, // JSON
     pub created_at: String,
     pub updated_at: String,
+    pub user_data: Option<String>,
+    pub image: Option<String>,
+    /// VPC and subnet deployments launch into; the default VPC when unset
+    pub vpc_id: Option<String>,
+    pub subnet_id: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -831,6 +1676,13 @@ pub struct CreateBlueprintRequest {
     pub storage_gb: i64,
     pub security_config: Option<String>,
     pub tags: Option<Vec<String>>,
+    pub user_data: Option<String>,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub vpc_id: Option<String>,
+    #[serde(default)]
+    pub subnet_id: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -838,9 +1690,18 @@ pub struct UpdateBlueprintRequest {
     pub name: Option<String>,
     pub description: Option<String>,
     pub instance_type: Option<String>,
+    #[serde(default)]
+    pub region: Option<String>,
     pub storage_gb: Option<i64>,
     pub security_config: Option<String>,
     pub tags: Option<Vec<String>>,
+    pub user_data: Option<String>,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub vpc_id: Option<String>,
+    #[serde(default)]
+    pub subnet_id: Option<String>,
 }
 
 // ============================================================================
@@ -856,6 +1717,14 @@ pub struct SecurityConfig {
     pub rules: String, // JSON
     pub created_at: String,
     pub updated_at: String,
+    pub aws_group_id: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SecurityConfigDeletion {
+    Deleted { orphaned_instances: usize },
+    NotFound,
+    InUse(Vec<Instance>),
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -866,7 +1735,7 @@ pub struct CreateSecurityConfigRequest {
     pub rules: Vec<SecurityRule>,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct SecurityRule {
     pub rule_type: String,
     pub port: Option<i32>,
@@ -891,6 +1760,7 @@ pub struct Image {
     pub status: String,
     pub created_at: String,
     pub updated_at: String,
+    pub snapshot_ids: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -900,6 +1770,7 @@ pub struct CreateImageRequest {
     pub platform: String,
     pub region: String,
     pub source_instance_id: Option<i64>,
+    pub image_id: String,
 }
 
 // ============================================================================
@@ -929,6 +1800,48 @@ pub async fn get_instance_by_aws_id(pool: &DbPool, aws_instance_id: &str) -> Res
         .context("Failed to fetch instance by AWS ID")
 }
 
+/// Why a `CreateInstanceRequest` doesn't fit the project it names
+#[derive(Debug, Clone, PartialEq, serde::Serialize, thiserror::Error)]
+#[serde(tag = "error_type", rename_all = "snake_case")]
+pub enum InstanceRequestError {
+    #[error("Project {project_id} does not exist")]
+    ProjectNotFound { project_id: i64 },
+    #[error("Project {project_id} is a {project_platform} project but the instance is for {platform}")]
+    PlatformMismatch { project_id: i64, project_platform: String, platform: String },
+    #[error("Project {project_id} is in {project_region} but the instance is in {region}")]
+    RegionMismatch { project_id: i64, project_region: String, region: String },
+    #[error("Failed to look up project: {message}")]
+    Lookup { message: String },
+}
+
+/// Check that the request's project exists and that the instance's platform
+/// and region match it. A different region is accepted with `allow_region_mismatch`;
+/// a different platform never is.
+pub async fn validate_instance_request(pool: &DbPool, request: &CreateInstanceRequest, allow_region_mismatch: bool) -> std::result::Result<(), InstanceRequestError> {
+    let project = get_project(pool, request.project_id)
+        .await
+        .map_err(|e| InstanceRequestError::Lookup { message: e.to_string() })?
+        .ok_or(InstanceRequestError::ProjectNotFound { project_id: request.project_id })?;
+
+    if !project.platform.eq_ignore_ascii_case(&request.platform) {
+        return Err(InstanceRequestError::PlatformMismatch {
+            project_id: project.id,
+            project_platform: project.platform,
+            platform: request.platform.clone(),
+        });
+    }
+
+    if !allow_region_mismatch && project.region != request.region {
+        return Err(InstanceRequestError::RegionMismatch {
+            project_id: project.id,
+            project_region: project.region,
+            region: request.region.clone(),
+        });
+    }
+
+    Ok(())
+}
+
 pub async fn create_instance(pool: &DbPool, request: CreateInstanceRequest) -> Result<Instance> {
     let tags_json = request.tags.as_ref().map(|tags| serde_json::to_string(tags).unwrap_or_default());
 
@@ -936,9 +1849,9 @@ pub async fn create_instance(pool: &DbPool, request: CreateInstanceRequest) -> R
         r#"
         INSERT INTO instances (
             name, project_id, instance_type, platform, region, status,
-            storage_gb, security_config, ssh_key, tags
+            storage_gb, security_config, ssh_key, tags, aws_instance_id, lifecycle
         )
-        VALUES (?, ?, ?, ?, ?, 'pending', ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, ?, 'pending', ?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(&request.name)
@@ -950,6 +1863,9 @@ pub async fn create_instance(pool: &DbPool, request: CreateInstanceRequest) -> R
     .bind(&request.security_config)
     .bind(&request.ssh_key)
     .bind(&tags_json)
+    // Sync names AWS instances by their instance id
+    .bind((request.platform == "aws" && request.name.starts_with("i-")).then_some(&request.name))
+    .bind(request.lifecycle.as_deref().unwrap_or("on-demand"))
     .execute(pool)
     .await
     .context("Failed to create instance")?;
@@ -963,6 +1879,7 @@ pub async fn create_instance(pool: &DbPool, request: CreateInstanceRequest) -> R
 
 pub async fn update_instance(pool: &DbPool, id: i64, request: UpdateInstanceRequest) -> Result<Option<Instance>> {
     let tags_json = request.tags.as_ref().map(|tags| serde_json::to_string(tags).unwrap_or_default());
+    let ssh_user = request.ssh_user.as_deref().map(str::trim);
 
     let result = sqlx::query(
         r#"
@@ -973,6 +1890,7 @@ pub async fn update_instance(pool: &DbPool, id: i64, request: UpdateInstanceRequ
             security_config = COALESCE(?, security_config),
             ssh_key = COALESCE(?, ssh_key),
             tags = COALESCE(?, tags),
+            ssh_user = CASE WHEN ? IS NULL THEN ssh_user ELSE NULLIF(?, '') END,
             updated_at = CURRENT_TIMESTAMP
         WHERE id = ?
         "#,
@@ -983,6 +1901,8 @@ pub async fn update_instance(pool: &DbPool, id: i64, request: UpdateInstanceRequ
     .bind(&request.security_config)
     .bind(&request.ssh_key)
     .bind(&tags_json)
+    .bind(ssh_user)
+    .bind(ssh_user)
     .bind(id)
     .execute(pool)
     .await
@@ -1053,6 +1973,175 @@ pub async fn restart_instance(pool: &DbPool, id: i64) -> Result<Option<Instance>
     }
 }
 
+/// Record the state an instance settled in and the public IP it has there
+/// (stopped instances lose theirs unless it's an Elastic IP)
+pub async fn set_instance_state(pool: &DbPool, id: i64, status: &str, public_ip: Option<&str>) -> Result<Option<Instance>> {
+    let result = sqlx::query(
+        "UPDATE instances SET status = ?, public_ip = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?"
+    )
+    .bind(status)
+    .bind(public_ip)
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("Failed to update instance state")?;
+
+    if result.rows_affected() > 0 {
+        get_instance(pool, id).await
+    } else {
+        Ok(None)
+    }
+}
+
+/// Record the states of many instances, keyed by AWS instance id, in one
+/// transaction. Returns the instances updated.
+pub async fn set_instance_statuses(pool: &DbPool, statuses: &[(String, String)]) -> Result<Vec<Instance>> {
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    for (aws_instance_id, status) in statuses {
+        sqlx::query("UPDATE instances SET status = ?, updated_at = CURRENT_TIMESTAMP WHERE aws_instance_id = ?")
+            .bind(status)
+            .bind(aws_instance_id)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Failed to update state of instance {}", aws_instance_id))?;
+    }
+
+    tx.commit().await.context("Failed to commit instance states")?;
+
+    let mut updated = Vec::new();
+    for (aws_instance_id, _) in statuses {
+        if let Some(instance) = get_instance_by_aws_id(pool, aws_instance_id).await? {
+            updated.push(instance);
+        }
+    }
+    Ok(updated)
+}
+
+/// Set or clear an instance's deletion protection
+pub async fn set_instance_protection(pool: &DbPool, id: i64, protected: bool) -> Result<Option<Instance>> {
+    let result = sqlx::query("UPDATE instances SET protected = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+        .bind(protected)
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to update instance protection")?;
+
+    if result.rows_affected() > 0 {
+        get_instance(pool, id).await
+    } else {
+        Ok(None)
+    }
+}
+
+pub async fn get_project_instances(pool: &DbPool, project_id: i64) -> Result<Vec<Instance>> {
+    sqlx::query_as::<_, Instance>("SELECT * FROM instances WHERE project_id = ? ORDER BY created_at DESC")
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch project instances")
+}
+
+/// Instances in any of an account's projects
+pub async fn get_account_instances(pool: &DbPool, account_id: i64) -> Result<Vec<Instance>> {
+    sqlx::query_as::<_, Instance>(
+        "SELECT * FROM instances WHERE project_id IN (SELECT id FROM projects WHERE account_id = ?) ORDER BY created_at DESC",
+    )
+        .bind(account_id)
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch account instances")
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StorageChange {
+    pub id: i64,
+    pub aws_instance_id: String,
+    pub previous_storage_gb: i64,
+    pub storage_gb: i64,
+}
+
+/// Update `storage_gb` for an account's instances from attached volume totals keyed
+/// by AWS instance id (stored as the instance name by sync). Returns the rows that changed.
+pub async fn update_instance_storage(pool: &DbPool, account_id: i64, volume_totals: &HashMap<String, i64>) -> Result<Vec<StorageChange>> {
+    let mut changes = Vec::new();
+
+    for instance in get_account_instances(pool, account_id).await? {
+        let Some(&storage_gb) = volume_totals.get(&instance.name) else {
+            continue;
+        };
+
+        if storage_gb == instance.storage_gb {
+            continue;
+        }
+
+        sqlx::query("UPDATE instances SET storage_gb = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(storage_gb)
+            .bind(instance.id)
+            .execute(pool)
+            .await
+            .context("Failed to update instance storage")?;
+
+        changes.push(StorageChange {
+            id: instance.id,
+            aws_instance_id: instance.name,
+            previous_storage_gb: instance.storage_gb,
+            storage_gb,
+        });
+    }
+
+    Ok(changes)
+}
+
+/// Record the Elastic IP associated with an account's instance, keyed by AWS
+/// instance id (stored as the instance name by sync). `None` clears it.
+pub async fn set_instance_elastic_ip(pool: &DbPool, account_id: i64, aws_instance_id: &str, eip: Option<(&str, &str)>) -> Result<bool> {
+    let (allocation_id, elastic_ip) = eip.unzip();
+
+    let result = sqlx::query(
+        "UPDATE instances SET eip_allocation_id = ?, elastic_ip = ?, updated_at = CURRENT_TIMESTAMP \
+         WHERE project_id IN (SELECT id FROM projects WHERE account_id = ?) AND name = ?"
+    )
+    .bind(allocation_id)
+    .bind(elastic_ip)
+    .bind(account_id)
+    .bind(aws_instance_id)
+    .execute(pool)
+    .await
+    .context("Failed to update instance Elastic IP")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Bring the recorded Elastic IPs of an account's instances in `regions` in
+/// line with `associations` (AWS instance id -> allocation id and address).
+/// Instances in other regions are left alone. Returns how many rows changed.
+pub async fn reconcile_instance_elastic_ips(
+    pool: &DbPool,
+    account_id: i64,
+    regions: &[String],
+    associations: &HashMap<String, (String, String)>,
+) -> Result<usize> {
+    let mut changed = 0;
+
+    for instance in get_account_instances(pool, account_id).await? {
+        if instance.platform != "aws" || !regions.contains(&instance.region) {
+            continue;
+        }
+
+        let expected = associations.get(&instance.name).map(|(allocation_id, ip)| (allocation_id.as_str(), ip.as_str()));
+        let recorded = instance.eip_allocation_id.as_deref().zip(instance.elastic_ip.as_deref());
+        if expected == recorded {
+            continue;
+        }
+
+        set_instance_elastic_ip(pool, account_id, &instance.name, expected).await?;
+        changed += 1;
+    }
+
+    Ok(changed)
+}
+
 // ============================================================================
 // BLUEPRINT FUNCTIONS
 // ============================================================================
@@ -1079,9 +2168,10 @@ pub async fn create_blueprint(pool: &DbPool, request: CreateBlueprintRequest) ->
         r#"
         INSERT INTO blueprints (
             name, description, instance_type, platform, region,
-            storage_gb, security_config, tags
+            storage_gb, security_config, tags, user_data, image,
+            vpc_id, subnet_id
         )
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
     )
     .bind(&request.name)
@@ -1092,6 +2182,10 @@ pub async fn create_blueprint(pool: &DbPool, request: CreateBlueprintRequest) ->
     .bind(request.storage_gb)
     .bind(&request.security_config)
     .bind(&tags_json)
+    .bind(&request.user_data)
+    .bind(&request.image)
+    .bind(&request.vpc_id)
+    .bind(&request.subnet_id)
     .execute(pool)
     .await
     .context("Failed to create blueprint")?;
@@ -1112,9 +2206,14 @@ pub async fn update_blueprint(pool: &DbPool, id: i64, request: UpdateBlueprintRe
             name = COALESCE(?, name),
             description = COALESCE(?, description),
             instance_type = COALESCE(?, instance_type),
+            region = COALESCE(?, region),
             storage_gb = COALESCE(?, storage_gb),
             security_config = COALESCE(?, security_config),
             tags = COALESCE(?, tags),
+            user_data = COALESCE(?, user_data),
+            image = COALESCE(?, image),
+            vpc_id = COALESCE(?, vpc_id),
+            subnet_id = COALESCE(?, subnet_id),
             updated_at = CURRENT_TIMESTAMP
         WHERE id = ?
         "#,
@@ -1122,9 +2221,14 @@ pub async fn update_blueprint(pool: &DbPool, id: i64, request: UpdateBlueprintRe
     .bind(&request.name)
     .bind(&request.description)
     .bind(&request.instance_type)
+    .bind(&request.region)
     .bind(request.storage_gb)
     .bind(&request.security_config)
     .bind(&tags_json)
+    .bind(&request.user_data)
+    .bind(&request.image)
+    .bind(&request.vpc_id)
+    .bind(&request.subnet_id)
     .bind(id)
     .execute(pool)
     .await
@@ -1147,10 +2251,14 @@ pub async fn delete_blueprint(pool: &DbPool, id: i64) -> Result<bool> {
     Ok(result.rows_affected() > 0)
 }
 
-pub async fn deploy_blueprint(pool: &DbPool, blueprint_id: i64, project_id: i64, instance_name: String) -> Result<Instance> {
+/// Create an instance in `project_id` from a blueprint. The blueprint's
+/// platform must match the project's, and so must its region unless
+/// `allow_region_mismatch` is set; a rejected deployment fails with an
+/// `InstanceRequestError`.
+pub async fn deploy_blueprint(pool: &DbPool, blueprint_id: i64, project_id: i64, instance_name: String, allow_region_mismatch: bool) -> Result<Instance> {
     let blueprint = get_blueprint(pool, blueprint_id)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("Blueprint not found"))?;
+        .ok_or_else(|| DbError::NotFound("Blueprint not found".to_string()))?;
 
     let create_request = CreateInstanceRequest {
         name: instance_name,
@@ -1162,58 +2270,304 @@ pub async fn deploy_blueprint(pool: &DbPool, blueprint_id: i64, project_id: i64,
         security_config: blueprint.security_config.clone(),
         ssh_key: None, // Could be derived from project or user preferences
         tags: blueprint.tags.as_ref().and_then(|t| serde_json::from_str(t).ok()),
+        lifecycle: None,
     };
 
-    create_instance(pool, create_request).await
-}
+    validate_instance_request(pool, &create_request, allow_region_mismatch).await?;
+    if allow_region_mismatch {
+        if let Some(project) = get_project(pool, project_id).await?.filter(|p| p.region != blueprint.region) {
+            tracing::warn!(
+                "Deploying blueprint {} ({}) into project {} in {}",
+                blueprint.id, blueprint.region, project.id, project.region
+            );
+        }
+    }
 
-// ============================================================================
-// SECURITY CONFIG FUNCTIONS
-// ============================================================================
+    let instance = create_instance(pool, create_request).await?;
+    if blueprint.user_data.is_none() {
+        return Ok(instance);
+    }
 
-pub async fn get_security_configs(pool: &DbPool) -> Result<Vec<SecurityConfig>> {
-    sqlx::query_as::<_, SecurityConfig>("SELECT * FROM security_configs ORDER BY created_at DESC")
-        .fetch_all(pool)
+    // Keep the script the instance was deployed with, even if the blueprint changes later
+    sqlx::query("UPDATE instances SET user_data = ? WHERE id = ?")
+        .bind(&blueprint.user_data)
+        .bind(instance.id)
+        .execute(pool)
         .await
-        .context("Failed to fetch security configs")
-}
+        .context("Failed to record instance user data")?;
 
-pub async fn get_security_config(pool: &DbPool, id: i64) -> Result<Option<SecurityConfig>> {
-    sqlx::query_as::<_, SecurityConfig>("SELECT * FROM security_configs WHERE id = ?")
-        .bind(id)
-        .fetch_optional(pool)
-        .await
-        .context("Failed to fetch security config")
+    get_instance(pool, instance.id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Failed to retrieve deployed instance"))
 }
 
-pub async fn create_security_config(pool: &DbPool, request: CreateSecurityConfigRequest) -> Result<SecurityConfig> {
-    let rules_json = serde_json::to_string(&request.rules)
-        .context("Failed to serialize security rules")?;
+/// Create a new blueprint from `id` with any fields set in `overrides` replaced.
+/// It's named "<name> (copy)" unless a name is given. Unless overridden, the
+/// security config is copied too, so editing either blueprint's rules leaves
+/// the other alone. Returns None if the source doesn't exist.
+pub async fn clone_blueprint(pool: &DbPool, id: i64, overrides: UpdateBlueprintRequest) -> Result<Option<Blueprint>> {
+    let Some(source) = get_blueprint(pool, id).await? else {
+        return Ok(None);
+    };
 
-    let result = sqlx::query(
-        r#"
-        INSERT INTO security_configs (name, description, platform, rules)
-        VALUES (?, ?, ?, ?)
-        "#,
-    )
-    .bind(&request.name)
-    .bind(&request.description)
-    .bind(&request.platform)
-    .bind(&rules_json)
-    .execute(pool)
-    .await
-    .context("Failed to create security config")?;
+    let security_config = match (overrides.security_config, &source.security_config) {
+        (Some(config), _) => Some(config),
+        (None, Some(config_id)) => Some(copy_security_config(pool, config_id).await?),
+        (None, None) => None,
+    };
 
-    let config_id = result.last_insert_rowid();
+    let existing: Vec<String> = get_blueprints(pool).await?.into_iter().map(|b| b.name).collect();
+    let name = overrides.name.unwrap_or_else(|| format!("{} (copy)", source.name));
+    if name.trim().is_empty() {
+        anyhow::bail!(DbError::InvalidInput("Blueprint name cannot be empty".to_string()));
+    }
 
-    get_security_config(pool, config_id)
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created security config"))
-}
+    let storage_gb = overrides.storage_gb.unwrap_or(source.storage_gb);
+    if storage_gb <= 0 {
+        anyhow::bail!(DbError::InvalidInput("Blueprint storage must be positive".to_string()));
+    }
 
-pub async fn update_security_config(pool: &DbPool, id: i64, request: CreateSecurityConfigRequest) -> Result<Option<SecurityConfig>> {
-    let rules_json = serde_json::to_string(&request.rules)
-        .context("Failed to serialize security rules")?;
+    let tags = match overrides.tags {
+        Some(tags) => Some(tags),
+        None => source.tags.as_deref().map(serde_json::from_str).transpose().context("Failed to parse blueprint tags")?,
+    };
+
+    create_blueprint(pool, CreateBlueprintRequest {
+        name: unique_name(&name, &existing),
+        description: overrides.description.or(source.description),
+        instance_type: overrides.instance_type.unwrap_or(source.instance_type),
+        platform: source.platform,
+        region: overrides.region.unwrap_or(source.region),
+        storage_gb,
+        security_config,
+        tags,
+        user_data: overrides.user_data.or(source.user_data),
+        image: overrides.image.or(source.image),
+        vpc_id: overrides.vpc_id.or(source.vpc_id),
+        subnet_id: overrides.subnet_id.or(source.subnet_id),
+    }).await.map(Some)
+}
+
+/// Copy the security config a blueprint references, returning the copy's id.
+/// References to configs that no longer exist are kept as they are.
+async fn copy_security_config(pool: &DbPool, config_id: &str) -> Result<String> {
+    let config = match config_id.parse::<i64>() {
+        Ok(id) => get_security_config(pool, id).await?,
+        Err(_) => None,
+    };
+    let Some(config) = config else {
+        return Ok(config_id.to_string());
+    };
+
+    let names: Vec<String> = get_security_configs(pool).await?.into_iter().map(|c| c.name).collect();
+    let copy = create_security_config(pool, CreateSecurityConfigRequest {
+        name: unique_name(&format!("{} (copy)", config.name), &names),
+        description: config.description,
+        platform: config.platform,
+        rules: serde_json::from_str(&config.rules).context("Failed to parse security rules")?,
+    }).await?;
+    Ok(copy.id.to_string())
+}
+
+/// Version of the blueprint export format; bump it when the shape changes
+pub const BLUEPRINT_EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// A blueprint as a self-contained document, with its security config's rules
+/// embedded rather than referenced by id
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BlueprintExport {
+    pub schema_version: u32,
+    pub blueprint: ExportedBlueprint,
+    pub security_config: Option<ExportedSecurityConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExportedBlueprint {
+    pub name: String,
+    pub description: Option<String>,
+    pub instance_type: String,
+    pub platform: String,
+    pub region: String,
+    pub storage_gb: i64,
+    pub tags: Option<Vec<String>>,
+    pub user_data: Option<String>,
+    pub image: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ExportedSecurityConfig {
+    pub name: String,
+    pub description: Option<String>,
+    pub platform: String,
+    pub rules: Vec<SecurityRule>,
+}
+
+/// Export a blueprint, or None if it doesn't exist
+pub async fn export_blueprint(pool: &DbPool, id: i64) -> Result<Option<BlueprintExport>> {
+    let Some(blueprint) = get_blueprint(pool, id).await? else {
+        return Ok(None);
+    };
+
+    let config_id = blueprint.security_config.as_deref().and_then(|id| id.parse::<i64>().ok());
+    let security_config = match config_id {
+        Some(config_id) => match get_security_config(pool, config_id).await? {
+            Some(config) => Some(ExportedSecurityConfig {
+                rules: serde_json::from_str(&config.rules).context("Failed to parse security rules")?,
+                name: config.name,
+                description: config.description,
+                platform: config.platform,
+            }),
+            None => {
+                tracing::warn!("Blueprint {} references missing security config {}", id, config_id);
+                None
+            }
+        },
+        None => None,
+    };
+
+    Ok(Some(BlueprintExport {
+        schema_version: BLUEPRINT_EXPORT_SCHEMA_VERSION,
+        blueprint: ExportedBlueprint {
+            name: blueprint.name,
+            description: blueprint.description,
+            instance_type: blueprint.instance_type,
+            platform: blueprint.platform,
+            region: blueprint.region,
+            storage_gb: blueprint.storage_gb,
+            tags: blueprint.tags.as_deref().and_then(|t| serde_json::from_str(t).ok()),
+            user_data: blueprint.user_data,
+            image: blueprint.image,
+        },
+        security_config,
+    }))
+}
+
+/// Recreate a blueprint from `export_blueprint` JSON under a new id. A name
+/// already in use gets a numbered suffix. The embedded security config reuses
+/// an existing config with the same name, platform and rules, and is created
+/// otherwise.
+pub async fn import_blueprint(pool: &DbPool, json: &str) -> Result<Blueprint> {
+    let document: serde_json::Value = serde_json::from_str(json).map_err(|e| DbError::InvalidInput(format!("Blueprint export is not valid JSON: {}", e)))?;
+    match document.get("schema_version").and_then(|v| v.as_u64()) {
+        Some(version) if version == BLUEPRINT_EXPORT_SCHEMA_VERSION as u64 => {}
+        Some(version) => anyhow::bail!(DbError::InvalidInput(format!(
+            "Unsupported blueprint schema_version {}; expected {}",
+            version, BLUEPRINT_EXPORT_SCHEMA_VERSION
+        ))),
+        None => anyhow::bail!(DbError::InvalidInput("Blueprint export has no schema_version".to_string())),
+    }
+    let export: BlueprintExport = serde_json::from_value(document).map_err(|e| DbError::InvalidInput(format!("Invalid blueprint export: {}", e)))?;
+
+    let blueprint = export.blueprint;
+    if blueprint.name.trim().is_empty() {
+        anyhow::bail!(DbError::InvalidInput("Blueprint name is required".to_string()));
+    }
+    if blueprint.instance_type.trim().is_empty() || blueprint.platform.trim().is_empty() || blueprint.region.trim().is_empty() {
+        anyhow::bail!(DbError::InvalidInput("Blueprint instance type, platform and region are required".to_string()));
+    }
+    if blueprint.storage_gb <= 0 {
+        anyhow::bail!(DbError::InvalidInput("Blueprint storage must be positive".to_string()));
+    }
+
+    let security_config = match export.security_config {
+        Some(config) => Some(import_security_config(pool, config).await?.id.to_string()),
+        None => None,
+    };
+
+    let existing: Vec<String> = get_blueprints(pool).await?.into_iter().map(|b| b.name).collect();
+    create_blueprint(pool, CreateBlueprintRequest {
+        name: unique_name(&blueprint.name, &existing),
+        description: blueprint.description,
+        instance_type: blueprint.instance_type,
+        platform: blueprint.platform,
+        region: blueprint.region,
+        storage_gb: blueprint.storage_gb,
+        security_config,
+        tags: blueprint.tags,
+        user_data: blueprint.user_data,
+        image: blueprint.image,
+        // VPC and subnet ids are specific to the exporting account
+        vpc_id: None,
+        subnet_id: None,
+    }).await
+}
+
+async fn import_security_config(pool: &DbPool, config: ExportedSecurityConfig) -> Result<SecurityConfig> {
+    let existing = get_security_configs(pool).await?;
+    let rules_json = serde_json::to_string(&config.rules).context("Failed to serialize security rules")?;
+
+    if let Some(same) = existing.iter().find(|c| c.name == config.name && c.platform == config.platform && c.rules == rules_json) {
+        return Ok(same.clone());
+    }
+
+    let names: Vec<String> = existing.into_iter().map(|c| c.name).collect();
+    create_security_config(pool, CreateSecurityConfigRequest {
+        name: unique_name(&config.name, &names),
+        description: config.description,
+        platform: config.platform,
+        rules: config.rules,
+    }).await
+}
+
+/// `name`, or `name (2)`, `name (3)`, ... if it's taken
+fn unique_name(name: &str, taken: &[String]) -> String {
+    let name = name.trim();
+    if !taken.iter().any(|t| t == name) {
+        return name.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} ({})", name, n))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("unbounded range")
+}
+
+// ============================================================================
+// SECURITY CONFIG FUNCTIONS
+// ============================================================================
+
+pub async fn get_security_configs(pool: &DbPool) -> Result<Vec<SecurityConfig>> {
+    sqlx::query_as::<_, SecurityConfig>("SELECT * FROM security_configs ORDER BY created_at DESC")
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch security configs")
+}
+
+pub async fn get_security_config(pool: &DbPool, id: i64) -> Result<Option<SecurityConfig>> {
+    sqlx::query_as::<_, SecurityConfig>("SELECT * FROM security_configs WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch security config")
+}
+
+pub async fn create_security_config(pool: &DbPool, request: CreateSecurityConfigRequest) -> Result<SecurityConfig> {
+    let rules_json = serde_json::to_string(&request.rules)
+        .context("Failed to serialize security rules")?;
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO security_configs (name, description, platform, rules)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(&request.name)
+    .bind(&request.description)
+    .bind(&request.platform)
+    .bind(&rules_json)
+    .execute(pool)
+    .await
+    .context("Failed to create security config")?;
+
+    let config_id = result.last_insert_rowid();
+
+    get_security_config(pool, config_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created security config"))
+}
+
+pub async fn update_security_config(pool: &DbPool, id: i64, request: CreateSecurityConfigRequest) -> Result<Option<SecurityConfig>> {
+    let rules_json = serde_json::to_string(&request.rules)
+        .context("Failed to serialize security rules")?;
 
     let result = sqlx::query(
         r#"
@@ -1240,14 +2594,65 @@ pub async fn update_security_config(pool: &DbPool, id: i64, request: CreateSecur
     }
 }
 
-pub async fn delete_security_config(pool: &DbPool, id: i64) -> Result<bool> {
+/// Record the EC2 security group a config was applied to
+pub async fn set_security_config_group_id(pool: &DbPool, id: i64, group_id: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE security_configs SET aws_group_id = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+    )
+    .bind(group_id)
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("Failed to record security config group id")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Instances whose `security_config` references the config by id or name,
+/// or `None` if the config does not exist
+pub async fn get_security_config_usage(pool: &DbPool, id: i64) -> Result<Option<Vec<Instance>>> {
+    let Some(config) = get_security_config(pool, id).await? else {
+        return Ok(None);
+    };
+
+    let instances = sqlx::query_as::<_, Instance>(
+        "SELECT * FROM instances WHERE security_config = ? OR security_config = ? ORDER BY created_at DESC"
+    )
+    .bind(id.to_string())
+    .bind(&config.name)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch instances using security config")?;
+
+    Ok(Some(instances))
+}
+
+/// Delete a security config. Configs still referenced by instances are only
+/// deleted when `force` is set.
+pub async fn delete_security_config(pool: &DbPool, id: i64, force: bool) -> Result<SecurityConfigDeletion> {
+    let Some(instances) = get_security_config_usage(pool, id).await? else {
+        return Ok(SecurityConfigDeletion::NotFound);
+    };
+
+    if !instances.is_empty() && !force {
+        return Ok(SecurityConfigDeletion::InUse(instances));
+    }
+
     let result = sqlx::query("DELETE FROM security_configs WHERE id = ?")
         .bind(id)
         .execute(pool)
         .await
         .context("Failed to delete security config")?;
 
-    Ok(result.rows_affected() > 0)
+    if result.rows_affected() == 0 {
+        return Ok(SecurityConfigDeletion::NotFound);
+    }
+
+    if !instances.is_empty() {
+        tracing::warn!("Force-deleted security config {} still used by {} instances", id, instances.len());
+    }
+
+    Ok(SecurityConfigDeletion::Deleted { orphaned_instances: instances.len() })
 }
 
 // ============================================================================
@@ -1281,7 +2686,7 @@ pub async fn create_image(pool: &DbPool, request: CreateImageRequest) -> Result<
     .bind(&request.platform)
     .bind(&request.region)
     .bind(request.source_instance_id)
-    .bind(format!("ami-{}", chrono::Utc::now().timestamp())) // Generate a placeholder AMI ID
+    .bind(&request.image_id)
     .execute(pool)
     .await
     .context("Failed to create image")?;
@@ -1303,10 +2708,12 @@ pub async fn delete_image(pool: &DbPool, id: i64) -> Result<bool> {
     Ok(result.rows_affected() > 0)
 }
 
-pub async fn create_image_from_instance(pool: &DbPool, instance_id: i64, name: String, description: Option<String>) -> Result<Image> {
+/// Record an image created from an instance. `image_id` is the provider's id
+/// for it (the AMI id on AWS).
+pub async fn create_image_from_instance(pool: &DbPool, instance_id: i64, name: String, description: Option<String>, image_id: String) -> Result<Image> {
     let instance = get_instance(pool, instance_id)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("Instance not found"))?;
+        .ok_or_else(|| DbError::NotFound("Instance not found".to_string()))?;
 
     let request = CreateImageRequest {
         name,
@@ -1314,7 +2721,1338 @@ pub async fn create_image_from_instance(pool: &DbPool, instance_id: i64, name: S
         platform: instance.platform.clone(),
         region: instance.region.clone(),
         source_instance_id: Some(instance_id),
+        image_id,
     };
 
     create_image(pool, request).await
-}
\ No newline at end of file
+}
+
+pub async fn update_image_status(pool: &DbPool, id: i64, status: &str) -> Result<Option<Image>> {
+    sqlx::query("UPDATE images SET status = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+        .bind(status)
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to update image status")?;
+
+    get_image(pool, id).await
+}
+
+/// Link an image to the snapshots backing it
+pub async fn set_image_snapshot_ids(pool: &DbPool, id: i64, snapshot_ids: &[String]) -> Result<Option<Image>> {
+    sqlx::query("UPDATE images SET snapshot_ids = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+        .bind(serde_json::to_string(snapshot_ids)?)
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to record image snapshots")?;
+
+    get_image(pool, id).await
+}
+
+/// Snapshot ids recorded for an image; empty when none were recorded
+pub fn image_snapshot_ids(image: &Image) -> Vec<String> {
+    image.snapshot_ids
+        .as_deref()
+        .and_then(|ids| serde_json::from_str(ids).ok())
+        .unwrap_or_default()
+}
+// ============================================================================
+// SETTINGS FUNCTIONS
+// ============================================================================
+
+const COST_WARN_USD_SETTING: &str = "cost_warn_usd";
+const COST_LIMIT_USD_SETTING: &str = "cost_limit_usd";
+const CREDENTIAL_FAILURE_THRESHOLD_SETTING: &str = "credential_failure_threshold";
+const HEALTH_WEBHOOK_URL_SETTING: &str = "health_webhook_url";
+const SMTP_SETTINGS_SETTING: &str = "smtp_settings";
+const SECURITY_POSTURE_WEIGHTS_SETTING: &str = "security_posture_weights";
+const SSH_KEY_PATHS_SETTING: &str = "ssh_key_paths";
+
+/// Failed connection tests in a row before an account is marked 'error'
+pub const DEFAULT_CREDENTIAL_FAILURE_THRESHOLD: i64 = 3;
+
+/// Estimated API spend that raises a warning, and the limit past which
+/// billable resources are no longer created
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CostThresholds {
+    pub warn_usd: f64,
+    pub limit_usd: f64,
+}
+
+impl Default for CostThresholds {
+    fn default() -> Self {
+        Self { warn_usd: 0.5, limit_usd: 1.0 }
+    }
+}
+
+/// Relative weight of each category in the security posture score. Only the
+/// proportions matter; they needn't add up to 100.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SecurityPostureWeights {
+    pub iam: f64,
+    pub encryption: f64,
+    pub public_access: f64,
+    pub network: f64,
+}
+
+impl Default for SecurityPostureWeights {
+    fn default() -> Self {
+        Self { iam: 30.0, encryption: 20.0, public_access: 25.0, network: 25.0 }
+    }
+}
+
+impl SecurityPostureWeights {
+    pub fn total(&self) -> f64 {
+        self.iam + self.encryption + self.public_access + self.network
+    }
+}
+
+pub async fn get_setting(pool: &DbPool, key: &str) -> Result<Option<String>> {
+    let value: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+        .context(format!("Failed to read setting {}", key))?;
+
+    Ok(value.map(|(value,)| value))
+}
+
+/// Stored cost thresholds, falling back to the defaults for unset or unreadable values
+pub async fn get_cost_thresholds(pool: &DbPool) -> Result<CostThresholds> {
+    let defaults = CostThresholds::default();
+    let read = |value: Option<String>, default: f64| value.and_then(|v| v.parse::<f64>().ok()).unwrap_or(default);
+
+    Ok(CostThresholds {
+        warn_usd: read(get_setting(pool, COST_WARN_USD_SETTING).await?, defaults.warn_usd),
+        limit_usd: read(get_setting(pool, COST_LIMIT_USD_SETTING).await?, defaults.limit_usd),
+    })
+}
+
+pub async fn set_cost_thresholds(pool: &DbPool, thresholds: CostThresholds) -> Result<CostThresholds> {
+    if !(thresholds.limit_usd.is_finite() && thresholds.limit_usd > 0.0) {
+        anyhow::bail!(DbError::InvalidInput("Cost limit must be a positive amount".to_string()));
+    }
+    if !(thresholds.warn_usd.is_finite() && thresholds.warn_usd > 0.0 && thresholds.warn_usd <= thresholds.limit_usd) {
+        anyhow::bail!(DbError::InvalidInput("Cost warning must be a positive amount no greater than the limit".to_string()));
+    }
+
+    // One statement so both values change together
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value) VALUES (?, ?), (?, ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(COST_WARN_USD_SETTING)
+    .bind(thresholds.warn_usd.to_string())
+    .bind(COST_LIMIT_USD_SETTING)
+    .bind(thresholds.limit_usd.to_string())
+    .execute(pool)
+    .await
+    .context("Failed to save cost thresholds")?;
+
+    Ok(thresholds)
+}
+
+pub async fn get_credential_failure_threshold(pool: &DbPool) -> Result<i64> {
+    Ok(get_setting(pool, CREDENTIAL_FAILURE_THRESHOLD_SETTING)
+        .await?
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|threshold| *threshold > 0)
+        .unwrap_or(DEFAULT_CREDENTIAL_FAILURE_THRESHOLD))
+}
+
+pub async fn set_credential_failure_threshold(pool: &DbPool, threshold: i64) -> Result<i64> {
+    if threshold < 1 {
+        anyhow::bail!(DbError::InvalidInput("Credential failure threshold must be at least 1".to_string()));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value) VALUES (?, ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(CREDENTIAL_FAILURE_THRESHOLD_SETTING)
+    .bind(threshold.to_string())
+    .execute(pool)
+    .await
+    .context("Failed to save credential failure threshold")?;
+
+    Ok(threshold)
+}
+
+/// Stored security posture weights, falling back to the defaults when unset or unreadable
+pub async fn get_security_posture_weights(pool: &DbPool) -> Result<SecurityPostureWeights> {
+    Ok(get_setting(pool, SECURITY_POSTURE_WEIGHTS_SETTING)
+        .await?
+        .and_then(|v| serde_json::from_str(&v).ok())
+        .unwrap_or_default())
+}
+
+pub async fn set_security_posture_weights(pool: &DbPool, weights: SecurityPostureWeights) -> Result<SecurityPostureWeights> {
+    let values = [weights.iam, weights.encryption, weights.public_access, weights.network];
+    if values.iter().any(|w| !(w.is_finite() && *w >= 0.0)) {
+        anyhow::bail!(DbError::InvalidInput("Security posture weights must be non-negative numbers".to_string()));
+    }
+    if weights.total() <= 0.0 {
+        anyhow::bail!(DbError::InvalidInput("At least one security posture weight must be positive".to_string()));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value) VALUES (?, ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(SECURITY_POSTURE_WEIGHTS_SETTING)
+    .bind(serde_json::to_string(&weights)?)
+    .execute(pool)
+    .await
+    .context("Failed to save security posture weights")?;
+
+    Ok(weights)
+}
+
+/// Local private-key paths configured per key pair name
+pub async fn get_ssh_key_paths(pool: &DbPool) -> Result<HashMap<String, String>> {
+    match get_setting(pool, SSH_KEY_PATHS_SETTING).await? {
+        Some(value) => serde_json::from_str(&value).context("Failed to parse SSH key paths"),
+        None => Ok(HashMap::new()),
+    }
+}
+
+/// Save the private-key path used for `key_name`, or remove it when `path` is
+/// empty so the key falls back to ~/.ssh/<key_name>.pem
+pub async fn set_ssh_key_path(pool: &DbPool, key_name: &str, path: Option<&str>) -> Result<HashMap<String, String>> {
+    let key_name = key_name.trim();
+    if key_name.is_empty() {
+        anyhow::bail!(DbError::InvalidInput("Key pair name is required".to_string()));
+    }
+
+    let mut paths = get_ssh_key_paths(pool).await?;
+    match path.map(str::trim).filter(|path| !path.is_empty()) {
+        Some(path) => {
+            paths.insert(key_name.to_string(), path.to_string());
+        }
+        None => {
+            paths.remove(key_name);
+        }
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value) VALUES (?, ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(SSH_KEY_PATHS_SETTING)
+    .bind(serde_json::to_string(&paths)?)
+    .execute(pool)
+    .await
+    .context("Failed to save SSH key paths")?;
+
+    Ok(paths)
+}
+
+/// Webhook notified when AWS health changes status, if configured
+pub async fn get_health_webhook_url(pool: &DbPool) -> Result<Option<String>> {
+    Ok(get_setting(pool, HEALTH_WEBHOOK_URL_SETTING).await?.filter(|url| !url.is_empty()))
+}
+
+/// Save the health webhook URL, or remove it when `url` is empty
+pub async fn set_health_webhook_url(pool: &DbPool, url: Option<&str>) -> Result<Option<String>> {
+    let url = url.map(str::trim).filter(|url| !url.is_empty());
+
+    let Some(url) = url else {
+        sqlx::query("DELETE FROM settings WHERE key = ?")
+            .bind(HEALTH_WEBHOOK_URL_SETTING)
+            .execute(pool)
+            .await
+            .context("Failed to remove health webhook URL")?;
+        return Ok(None);
+    };
+
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        anyhow::bail!(DbError::InvalidInput("Webhook URL must start with https:// or http://".to_string()));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value) VALUES (?, ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(HEALTH_WEBHOOK_URL_SETTING)
+    .bind(url)
+    .execute(pool)
+    .await
+    .context("Failed to save health webhook URL")?;
+
+    Ok(Some(url.to_string()))
+}
+
+/// Outbound SMTP server settings for email alerts as stored, if configured.
+/// Their format belongs to the notifier, and the password isn't part of
+/// them; see `credential_store::retrieve_smtp_password`.
+pub async fn get_smtp_settings(pool: &DbPool) -> Result<Option<serde_json::Value>> {
+    match get_setting(pool, SMTP_SETTINGS_SETTING).await? {
+        Some(value) => Ok(Some(serde_json::from_str(&value).context("Failed to parse SMTP settings")?)),
+        None => Ok(None),
+    }
+}
+
+/// Save SMTP settings, or remove them when `settings` is None
+pub async fn set_smtp_settings(pool: &DbPool, settings: Option<&serde_json::Value>) -> Result<()> {
+    let Some(settings) = settings else {
+        sqlx::query("DELETE FROM settings WHERE key = ?")
+            .bind(SMTP_SETTINGS_SETTING)
+            .execute(pool)
+            .await
+            .context("Failed to remove SMTP settings")?;
+        return Ok(());
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value) VALUES (?, ?)
+        ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(SMTP_SETTINGS_SETTING)
+    .bind(settings.to_string())
+    .execute(pool)
+    .await
+    .context("Failed to save SMTP settings")?;
+
+    Ok(())
+}
+
+/// A migrated in-memory database for tests
+#[cfg(test)]
+pub(crate) async fn memory_pool() -> DbPool {
+    // A single connection so every query sees the same in-memory database
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(connect_options("sqlite::memory:").unwrap())
+        .await
+        .unwrap();
+    run_migrations(&pool).await.unwrap();
+    pool
+}
+
+/// A keyless us-east-1 AWS account for tests
+#[cfg(test)]
+pub(crate) fn unencrypted_account(name: &str) -> CreateAccountRequest {
+    CreateAccountRequest {
+        name: name.to_string(),
+        access_key: None,
+        secret_key: None,
+        region: Some("us-east-1".to_string()),
+        client_id: None,
+        client_secret: None,
+        encrypted: false,
+        platform: Some("aws".to_string()),
+        project_id: None,
+        subscription_id: None,
+        tenant_id: None,
+        service_account_key: None,
+        read_only: None,
+        role_arn: None,
+        external_id: None,
+        fallback_region: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn test_keyring_credentials_without_encrypted_flag_repaired() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, unencrypted_account("orphaned")).await.unwrap();
+        let keyring_only = |id: i64| Ok(if id == account.id { Some(CredentialBackend::Keyring) } else { None });
+
+        // Report mode flags the account without changing it
+        let report = verify_credential_linkage_with(&pool, false, keyring_only).await.unwrap();
+        assert_eq!(report.checked_accounts, 1);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].account_id, account.id);
+        assert_eq!(report.issues[0].actual_backend, Some(CredentialBackend::Keyring));
+        assert!(!report.issues[0].repaired);
+        assert!(!get_account(&pool, account.id).await.unwrap().unwrap().encrypted);
+
+        // Repair mode reconciles the flag to the keyring
+        let report = verify_credential_linkage_with(&pool, true, keyring_only).await.unwrap();
+        assert!(report.issues[0].repaired);
+        let repaired = get_account(&pool, account.id).await.unwrap().unwrap();
+        assert!(repaired.encrypted);
+        assert_eq!(repaired.credential_backend, "keyring");
+
+        let report = verify_credential_linkage_with(&pool, false, keyring_only).await.unwrap();
+        assert!(report.issues.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_security_config_in_use_blocks_delete() {
+        let pool = memory_pool().await;
+        let config = create_security_config(&pool, CreateSecurityConfigRequest {
+            name: "web".to_string(),
+            description: None,
+            platform: "aws".to_string(),
+            rules: Vec::new(),
+        }).await.unwrap();
+
+        let project = create_project(&pool, CreateProjectRequest {
+            name: "web".to_string(),
+            description: None,
+            region: "us-east-1".to_string(),
+            platform: "aws".to_string(),
+            account_id: None,
+        }).await.unwrap();
+
+        create_instance(&pool, CreateInstanceRequest {
+            name: "web-1".to_string(),
+            project_id: project.id,
+            instance_type: "t3.micro".to_string(),
+            platform: "aws".to_string(),
+            region: "us-east-1".to_string(),
+            storage_gb: 8,
+            security_config: Some(config.id.to_string()),
+            ssh_key: None,
+            tags: None,
+            lifecycle: None,
+        }).await.unwrap();
+
+        let usage = get_security_config_usage(&pool, config.id).await.unwrap().unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].name, "web-1");
+
+        // Without force the config is kept
+        match delete_security_config(&pool, config.id, false).await.unwrap() {
+            SecurityConfigDeletion::InUse(instances) => assert_eq!(instances.len(), 1),
+            other => panic!("expected InUse, got {:?}", other),
+        }
+        assert!(get_security_config(&pool, config.id).await.unwrap().is_some());
+
+        // Force deletes it anyway
+        assert!(matches!(
+            delete_security_config(&pool, config.id, true).await.unwrap(),
+            SecurityConfigDeletion::Deleted { orphaned_instances: 1 }
+        ));
+        assert!(get_security_config(&pool, config.id).await.unwrap().is_none());
+        assert!(get_security_config_usage(&pool, config.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_security_config_records_group_id() {
+        let pool = memory_pool().await;
+        let config = create_security_config(&pool, CreateSecurityConfigRequest {
+            name: "web".to_string(),
+            description: None,
+            platform: "aws".to_string(),
+            rules: Vec::new(),
+        }).await.unwrap();
+        assert!(config.aws_group_id.is_none());
+
+        assert!(set_security_config_group_id(&pool, config.id, "sg-0123456789abcdef0").await.unwrap());
+        let config = get_security_config(&pool, config.id).await.unwrap().unwrap();
+        assert_eq!(config.aws_group_id.as_deref(), Some("sg-0123456789abcdef0"));
+
+        assert!(!set_security_config_group_id(&pool, config.id + 1, "sg-1").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_account_regions() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, unencrypted_account("regions")).await.unwrap();
+
+        // Without configuration only the account region is scanned
+        assert_eq!(get_enabled_account_regions(&pool, &account).await.unwrap(), vec!["us-east-1".to_string()]);
+
+        let regions = set_account_regions(&pool, account.id, vec![
+            AccountRegionSetting { region: "eu-west-1".to_string(), enabled: true },
+            AccountRegionSetting { region: "us-west-2".to_string(), enabled: false },
+            AccountRegionSetting { region: "ap-southeast-2".to_string(), enabled: true },
+        ]).await.unwrap();
+        assert_eq!(regions.len(), 3);
+        assert_eq!(
+            get_enabled_account_regions(&pool, &account).await.unwrap(),
+            vec!["ap-southeast-2".to_string(), "eu-west-1".to_string()]
+        );
+
+        // Unknown regions are rejected and leave the configuration untouched
+        assert!(set_account_regions(&pool, account.id, vec![
+            AccountRegionSetting { region: "mars-north-1".to_string(), enabled: true },
+        ]).await.is_err());
+        assert_eq!(get_account_regions(&pool, account.id).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_grown_volume_updates_storage() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, unencrypted_account("account")).await.unwrap();
+        let project = get_or_create_sync_project(&pool, &account).await.unwrap();
+
+        let instance_request = |name: &str| CreateInstanceRequest {
+            name: name.to_string(),
+            project_id: project.id,
+            instance_type: "t3.micro".to_string(),
+            platform: "aws".to_string(),
+            region: "us-east-1".to_string(),
+            storage_gb: 8,
+            security_config: None,
+            ssh_key: None,
+            tags: None,
+            lifecycle: None,
+        };
+        let grown = create_instance(&pool, instance_request("i-grown")).await.unwrap();
+        let unchanged = create_instance(&pool, instance_request("i-unchanged")).await.unwrap();
+
+        // Root volume resized from 8 to 20 GiB plus a new 30 GiB data volume
+        let totals = HashMap::from([
+            ("i-grown".to_string(), 50),
+            ("i-unchanged".to_string(), 8),
+        ]);
+
+        let changes = update_instance_storage(&pool, account.id, &totals).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].id, grown.id);
+        assert_eq!(changes[0].previous_storage_gb, 8);
+        assert_eq!(changes[0].storage_gb, 50);
+
+        assert_eq!(get_instance(&pool, grown.id).await.unwrap().unwrap().storage_gb, 50);
+        assert_eq!(get_instance(&pool, unchanged.id).await.unwrap().unwrap().storage_gb, 8);
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_flag_without_credentials_repaired() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, unencrypted_account("missing")).await.unwrap();
+        sqlx::query("UPDATE accounts SET encrypted = 1, credential_backend = 'encrypted_file' WHERE id = ?")
+            .bind(account.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let report = verify_credential_linkage_with(&pool, true, |_| Ok(None)).await.unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert!(report.issues[0].actual_backend.is_none());
+
+        assert!(!get_account(&pool, account.id).await.unwrap().unwrap().encrypted);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_account_can_sync_but_not_delete() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, CreateAccountRequest {
+            read_only: Some(true),
+            ..unencrypted_account("production")
+        }).await.unwrap();
+        let writable = create_account(&pool, unencrypted_account("sandbox")).await.unwrap();
+
+        assert!(account.read_only);
+        assert!(!writable.read_only);
+
+        // Everything sync reads stays available
+        assert!(get_account_credentials(&pool, account.id).await.is_ok());
+        assert_eq!(get_enabled_account_regions(&pool, &account).await.unwrap(), vec!["us-east-1".to_string()]);
+
+        // Mutating commands are refused for the read-only account only
+        assert!(is_account_read_only(&pool, account.id).await.unwrap());
+        assert!(!is_account_read_only(&pool, writable.id).await.unwrap());
+        assert!(!is_account_read_only(&pool, 9999).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_cost_thresholds_round_trip() {
+        let pool = memory_pool().await;
+        assert_eq!(get_cost_thresholds(&pool).await.unwrap(), CostThresholds::default());
+
+        let thresholds = CostThresholds { warn_usd: 2.5, limit_usd: 10.0 };
+        set_cost_thresholds(&pool, thresholds).await.unwrap();
+        assert_eq!(get_cost_thresholds(&pool).await.unwrap(), thresholds);
+
+        // Invalid thresholds leave the stored ones untouched
+        assert!(set_cost_thresholds(&pool, CostThresholds { warn_usd: 20.0, limit_usd: 10.0 }).await.is_err());
+        assert!(set_cost_thresholds(&pool, CostThresholds { warn_usd: 1.0, limit_usd: 0.0 }).await.is_err());
+        assert_eq!(get_cost_thresholds(&pool).await.unwrap(), thresholds);
+    }
+
+    #[tokio::test]
+    async fn test_account_identity_mismatch_keeps_stored_identity() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, unencrypted_account("prod")).await.unwrap();
+        let other = create_account(&pool, unencrypted_account("prod-again")).await.unwrap();
+
+        let check = record_account_identity(&pool, account.id, "111122223333", "arn:aws:iam::111122223333:user/sync").await.unwrap();
+        assert_eq!(check, AccountIdentityCheck::Recorded);
+        let check = record_account_identity(&pool, account.id, "111122223333", "arn:aws:iam::111122223333:user/sync2").await.unwrap();
+        assert_eq!(check, AccountIdentityCheck::Matches);
+
+        // Swapped keys are reported and not recorded
+        let check = record_account_identity(&pool, account.id, "444455556666", "arn:aws:iam::444455556666:user/other").await.unwrap();
+        assert_eq!(check, AccountIdentityCheck::Mismatch { stored_account_id: "111122223333".to_string() });
+        let stored = get_account(&pool, account.id).await.unwrap().unwrap();
+        assert_eq!(stored.aws_account_id.as_deref(), Some("111122223333"));
+        assert_eq!(stored.caller_arn.as_deref(), Some("arn:aws:iam::111122223333:user/sync2"));
+
+        // A second account using the same AWS account is detected
+        record_account_identity(&pool, other.id, "111122223333", "arn:aws:iam::111122223333:user/sync").await.unwrap();
+        let shared = get_accounts_sharing_aws_account(&pool, account.id, "111122223333").await.unwrap();
+        assert_eq!(shared.iter().map(|a| a.id).collect::<Vec<_>>(), vec![other.id]);
+    }
+
+    #[tokio::test]
+    async fn test_role_arn_stored_with_account() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, CreateAccountRequest {
+            role_arn: Some("arn:aws:iam::444455556666:role/PocketArchitect".to_string()),
+            external_id: Some("hub-42".to_string()),
+            ..unencrypted_account("member")
+        }).await.unwrap();
+
+        assert_eq!(account.role_arn.as_deref(), Some("arn:aws:iam::444455556666:role/PocketArchitect"));
+        assert_eq!(account.external_id.as_deref(), Some("hub-42"));
+
+        // Clearing the role goes back to using the keys directly
+        let updated = update_account(&pool, account.id, CreateAccountRequest {
+            role_arn: Some(String::new()),
+            ..unencrypted_account("member")
+        }).await.unwrap().unwrap();
+        assert!(updated.role_arn.is_none());
+
+        for invalid in ["arn:aws:iam::444455556666:user/sync", "arn:aws:iam::4444:role/Short", "PocketArchitect"] {
+            let request = CreateAccountRequest {
+                role_arn: Some(invalid.to_string()),
+                ..unencrypted_account("bad")
+            };
+            assert!(create_account(&pool, request).await.is_err(), "{} should be rejected", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_account_status_lifecycle() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, unencrypted_account("flaky")).await.unwrap();
+        let disabled = create_account(&pool, unencrypted_account("parked")).await.unwrap();
+
+        let parked = set_account_status(&pool, disabled.id, "disabled").await.unwrap().unwrap();
+        assert_eq!(parked.status, "disabled");
+        assert_eq!(parked.status_reason.as_deref(), Some("Disabled by user"));
+        assert!(set_account_status(&pool, account.id, "error").await.is_err());
+        assert_eq!(get_active_accounts(&pool).await.unwrap().iter().map(|a| a.id).collect::<Vec<_>>(), vec![account.id]);
+        assert!(!is_account_active(&pool, disabled.id).await.unwrap());
+
+        // Two failures in a row trip a threshold of two
+        set_credential_failure_threshold(&pool, 2).await.unwrap();
+        let once = record_connection_result(&pool, account.id, Some("InvalidClientTokenId")).await.unwrap().unwrap();
+        assert_eq!((once.status.as_str(), once.consecutive_failures), ("active", 1));
+        let twice = record_connection_result(&pool, account.id, Some("InvalidClientTokenId")).await.unwrap().unwrap();
+        assert_eq!(twice.status, "error");
+        assert_eq!(twice.status_reason.as_deref(), Some("2 consecutive connection failures: InvalidClientTokenId"));
+        assert!(get_active_accounts(&pool).await.unwrap().is_empty());
+
+        // A success brings it back; failures never re-enable a disabled account
+        let recovered = record_connection_result(&pool, account.id, None).await.unwrap().unwrap();
+        assert_eq!((recovered.status.as_str(), recovered.consecutive_failures), ("active", 0));
+        assert!(recovered.status_reason.is_none());
+        for _ in 0..2 {
+            record_connection_result(&pool, disabled.id, Some("expired")).await.unwrap();
+        }
+        record_connection_result(&pool, disabled.id, None).await.unwrap();
+        assert_eq!(get_account(&pool, disabled.id).await.unwrap().unwrap().status, "disabled");
+    }
+
+    #[test]
+    fn test_project_health_aggregation() {
+        assert_eq!(aggregate_project_health(&["healthy", "healthy", "healthy"]), "healthy");
+        assert_eq!(aggregate_project_health(&["healthy", "degraded", "healthy"]), "degraded");
+        assert_eq!(aggregate_project_health(&["healthy", "error", "healthy"]), "degraded");
+        assert_eq!(aggregate_project_health(&["error", "error"]), "error");
+        assert_eq!(aggregate_project_health(&["stopped", "stopped"]), "stopped");
+        assert_eq!(aggregate_project_health(&["healthy", "starting"]), "pending");
+        assert_eq!(aggregate_project_health(&["healthy", "stopped"]), "partial");
+        assert_eq!(aggregate_project_health(&[]), "empty");
+    }
+
+    #[tokio::test]
+    async fn test_compute_project_health_persists_status() {
+        let pool = memory_pool().await;
+        let project = create_project(&pool, CreateProjectRequest {
+            name: "web".to_string(),
+            description: None,
+            region: "us-east-1".to_string(),
+            platform: "aws".to_string(),
+            account_id: None,
+        }).await.unwrap();
+
+        for name in ["web-1", "web-2"] {
+            let instance = create_instance(&pool, CreateInstanceRequest {
+                name: name.to_string(),
+                project_id: project.id,
+                instance_type: "t3.micro".to_string(),
+                platform: "aws".to_string(),
+                region: "us-east-1".to_string(),
+                storage_gb: 8,
+                security_config: None,
+                ssh_key: None,
+                tags: None,
+                lifecycle: None,
+            }).await.unwrap();
+            let status = if name == "web-1" { "healthy" } else { "error" };
+            sqlx::query("UPDATE instances SET status = ? WHERE id = ?").bind(status).bind(instance.id).execute(&pool).await.unwrap();
+        }
+
+        // Without persisting, the stored status is left alone
+        let health = compute_project_health(&pool, project.id, false).await.unwrap().unwrap();
+        assert_eq!(health.status, "degraded");
+        assert_eq!(health.instance_count, 2);
+        assert_eq!(health.status_counts["error"], 1);
+        assert_eq!(get_project(&pool, project.id).await.unwrap().unwrap().status, "active");
+
+        compute_project_health(&pool, project.id, true).await.unwrap();
+        assert_eq!(get_project(&pool, project.id).await.unwrap().unwrap().status, "degraded");
+
+        assert!(compute_project_health(&pool, project.id + 1, true).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_deploy_blueprint_records_user_data() {
+        let pool = memory_pool().await;
+        let project = create_project(&pool, CreateProjectRequest {
+            name: "web".to_string(),
+            description: None,
+            region: "us-east-1".to_string(),
+            platform: "aws".to_string(),
+            account_id: None,
+        }).await.unwrap();
+        let blueprint = create_blueprint(&pool, CreateBlueprintRequest {
+            name: "web".to_string(),
+            description: None,
+            instance_type: "t3.micro".to_string(),
+            platform: "aws".to_string(),
+            region: "us-east-1".to_string(),
+            storage_gb: 8,
+            security_config: None,
+            tags: None,
+            user_data: Some("#cloud-config\npackages: [nginx]\n".to_string()),
+            image: Some("ubuntu-22.04/arm64".to_string()),
+            vpc_id: None,
+            subnet_id: None,
+        }).await.unwrap();
+
+        let deployed = deploy_blueprint(&pool, blueprint.id, project.id, "web-1".to_string(), false).await.unwrap();
+        assert_eq!(deployed.user_data, blueprint.user_data);
+
+        // Later blueprint edits don't rewrite what deployed instances ran
+        update_blueprint(&pool, blueprint.id, UpdateBlueprintRequest {
+            name: None,
+            description: None,
+            instance_type: None,
+            region: None,
+            storage_gb: None,
+            security_config: None,
+            tags: None,
+            user_data: Some("#cloud-config\npackages: [apache2]\n".to_string()),
+            image: None,
+            vpc_id: None,
+            subnet_id: None,
+        }).await.unwrap();
+        let stored = get_instance(&pool, deployed.id).await.unwrap().unwrap();
+        assert_eq!(stored.user_data.as_deref(), Some("#cloud-config\npackages: [nginx]\n"));
+    }
+
+    #[tokio::test]
+    async fn test_deploy_blueprint_must_match_project_platform_and_region() {
+        let pool = memory_pool().await;
+        let project = |name: &str, region: &str, platform: &str| CreateProjectRequest {
+            name: name.to_string(),
+            description: None,
+            region: region.to_string(),
+            platform: platform.to_string(),
+            account_id: None,
+        };
+        let aws_east = create_project(&pool, project("east", "us-east-1", "aws")).await.unwrap();
+        let aws_west = create_project(&pool, project("west", "eu-west-1", "aws")).await.unwrap();
+        let gcp = create_project(&pool, project("gcp", "us-east-1", "gcp")).await.unwrap();
+        let blueprint = create_blueprint(&pool, CreateBlueprintRequest {
+            name: "web".to_string(),
+            description: None,
+            instance_type: "t3.micro".to_string(),
+            platform: "aws".to_string(),
+            region: "us-east-1".to_string(),
+            storage_gb: 8,
+            security_config: None,
+            tags: None,
+            user_data: None,
+            image: None,
+            vpc_id: None,
+            subnet_id: None,
+        }).await.unwrap();
+
+        let deployed = deploy_blueprint(&pool, blueprint.id, aws_east.id, "web-1".to_string(), false).await.unwrap();
+        assert_eq!(deployed.project_id, aws_east.id);
+        assert_eq!(deployed.region, "us-east-1");
+        assert_eq!(deployed.platform, "aws");
+
+        let err = deploy_blueprint(&pool, blueprint.id, gcp.id, "web-2".to_string(), true).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<InstanceRequestError>(), Some(InstanceRequestError::PlatformMismatch { .. })));
+
+        let err = deploy_blueprint(&pool, blueprint.id, aws_west.id, "web-3".to_string(), false).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<InstanceRequestError>(), Some(InstanceRequestError::RegionMismatch { .. })));
+
+        let err = deploy_blueprint(&pool, blueprint.id, 9999, "web-4".to_string(), false).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<InstanceRequestError>(), Some(InstanceRequestError::ProjectNotFound { .. })));
+
+        // Nothing was created for the rejected deployments
+        assert!(get_project_instances(&pool, gcp.id).await.unwrap().is_empty());
+        assert!(get_project_instances(&pool, aws_west.id).await.unwrap().is_empty());
+
+        // A region mismatch can be accepted explicitly
+        let deployed = deploy_blueprint(&pool, blueprint.id, aws_west.id, "web-5".to_string(), true).await.unwrap();
+        assert_eq!(deployed.project_id, aws_west.id);
+        assert_eq!(deployed.region, "us-east-1");
+    }
+
+    #[tokio::test]
+    async fn test_fallback_region_stored_with_account() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, CreateAccountRequest {
+            fallback_region: Some("us-east-2".to_string()),
+            ..unencrypted_account("primary")
+        }).await.unwrap();
+        assert_eq!(account.fallback_region.as_deref(), Some("us-east-2"));
+
+        let updated = update_account(&pool, account.id, unencrypted_account("primary")).await.unwrap().unwrap();
+        assert!(updated.fallback_region.is_none());
+
+        for invalid in ["us-east-1", "mars-north-1"] {
+            let request = CreateAccountRequest {
+                fallback_region: Some(invalid.to_string()),
+                ..unencrypted_account("bad")
+            };
+            assert!(create_account(&pool, request).await.is_err(), "{} should be rejected", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_synced_instances_are_filed_under_the_account_project() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, unencrypted_account("synced")).await.unwrap();
+
+        let instance_request = |project_id: i64| CreateInstanceRequest {
+            name: "i-0123456789abcdef0".to_string(),
+            project_id,
+            instance_type: "t3.micro".to_string(),
+            platform: "aws".to_string(),
+            region: "us-east-1".to_string(),
+            storage_gb: 8,
+            security_config: None,
+            ssh_key: None,
+            tags: None,
+            lifecycle: None,
+        };
+
+        // The account's id isn't a project, so foreign keys reject it
+        assert!(create_instance(&pool, instance_request(account.id)).await.is_err());
+
+        let project = get_or_create_sync_project(&pool, &account).await.unwrap();
+        assert_eq!(project.account_id, Some(account.id));
+        assert_eq!(project.name, "synced");
+        assert_eq!(get_or_create_sync_project(&pool, &account).await.unwrap().id, project.id);
+
+        let instance = create_instance(&pool, instance_request(project.id)).await.unwrap();
+        assert_eq!(get_project_account_id(&pool, instance.project_id).await.unwrap(), Some(account.id));
+        let instances = get_account_instances(&pool, account.id).await.unwrap();
+        assert_eq!(instances.iter().map(|i| i.id).collect::<Vec<_>>(), vec![instance.id]);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_instance_elastic_ips() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, unencrypted_account("account")).await.unwrap();
+        let project = get_or_create_sync_project(&pool, &account).await.unwrap();
+
+        let instance_request = |name: &str, region: &str| CreateInstanceRequest {
+            name: name.to_string(),
+            project_id: project.id,
+            instance_type: "t3.micro".to_string(),
+            platform: "aws".to_string(),
+            region: region.to_string(),
+            storage_gb: 8,
+            security_config: None,
+            ssh_key: None,
+            tags: None,
+            lifecycle: None,
+        };
+        let moved = create_instance(&pool, instance_request("i-moved", "us-east-1")).await.unwrap();
+        let released = create_instance(&pool, instance_request("i-released", "us-east-1")).await.unwrap();
+        let elsewhere = create_instance(&pool, instance_request("i-elsewhere", "eu-west-1")).await.unwrap();
+
+        for (name, allocation_id, ip) in [
+            ("i-released", "eipalloc-1", "203.0.113.10"),
+            ("i-elsewhere", "eipalloc-2", "203.0.113.20"),
+        ] {
+            assert!(set_instance_elastic_ip(&pool, account.id, name, Some((allocation_id, ip))).await.unwrap());
+        }
+
+        // The address was moved from i-released to i-moved; eu-west-1 wasn't synced
+        let associations = HashMap::from([
+            ("i-moved".to_string(), ("eipalloc-1".to_string(), "203.0.113.10".to_string())),
+        ]);
+        let changed = reconcile_instance_elastic_ips(&pool, account.id, &["us-east-1".to_string()], &associations).await.unwrap();
+        assert_eq!(changed, 2);
+
+        let moved = get_instance(&pool, moved.id).await.unwrap().unwrap();
+        assert_eq!(moved.elastic_ip.as_deref(), Some("203.0.113.10"));
+        assert_eq!(moved.eip_allocation_id.as_deref(), Some("eipalloc-1"));
+        assert!(get_instance(&pool, released.id).await.unwrap().unwrap().elastic_ip.is_none());
+        assert_eq!(get_instance(&pool, elsewhere.id).await.unwrap().unwrap().elastic_ip.as_deref(), Some("203.0.113.20"));
+
+        // Nothing changes on a second pass
+        let changed = reconcile_instance_elastic_ips(&pool, account.id, &["us-east-1".to_string()], &associations).await.unwrap();
+        assert_eq!(changed, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ssh_user_override_can_be_cleared() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, unencrypted_account("account")).await.unwrap();
+        let project = get_or_create_sync_project(&pool, &account).await.unwrap();
+        let instance = create_instance(&pool, CreateInstanceRequest {
+            name: "i-0aaaaaaaaaaaaaaa1".to_string(),
+            project_id: project.id,
+            instance_type: "t3.micro".to_string(),
+            platform: "aws".to_string(),
+            region: "us-east-1".to_string(),
+            storage_gb: 8,
+            security_config: None,
+            ssh_key: None,
+            tags: None,
+            lifecycle: None,
+        }).await.unwrap();
+        let update = |ssh_user: Option<&str>| UpdateInstanceRequest {
+            name: None,
+            instance_type: None,
+            storage_gb: None,
+            security_config: None,
+            ssh_key: None,
+            tags: None,
+            ssh_user: ssh_user.map(str::to_string),
+        };
+
+        let updated = update_instance(&pool, instance.id, update(Some("admin"))).await.unwrap().unwrap();
+        assert_eq!(updated.ssh_user.as_deref(), Some("admin"));
+
+        // Leaving the field out keeps the override
+        let updated = update_instance(&pool, instance.id, update(None)).await.unwrap().unwrap();
+        assert_eq!(updated.ssh_user.as_deref(), Some("admin"));
+
+        let updated = update_instance(&pool, instance.id, update(Some(" "))).await.unwrap().unwrap();
+        assert_eq!(updated.ssh_user, None);
+    }
+
+    #[tokio::test]
+    async fn test_credential_store_failure_leaves_no_account() {
+        let pool = memory_pool().await;
+        let request = CreateAccountRequest {
+            encrypted: true,
+            access_key: Some("AKIAEXAMPLE".to_string()),
+            secret_key: Some("secret".to_string()),
+            ..unencrypted_account("keyring-down")
+        };
+
+        let err = create_account_with(&pool, request, |_, _| Err(anyhow::anyhow!("keyring locked")))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("the account was not created"));
+        assert!(format!("{:#}", err).contains("keyring locked"));
+
+        assert!(get_accounts(&pool).await.unwrap().is_empty());
+
+        // The pool's only connection is usable again after the rollback
+        let created = create_account_with(&pool, unencrypted_account("next"), |_, _| unreachable!()).await.unwrap();
+        assert_eq!(get_accounts(&pool).await.unwrap().len(), 1);
+        assert_eq!(created.name, "next");
+    }
+
+    #[tokio::test]
+    async fn test_migrations_upgrade_unversioned_database_without_data_loss() {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+
+        // A database from before versioned migrations: early tables, one
+        // column added by a later ad-hoc run, and no migration records
+        for statement in [
+            "CREATE TABLE accounts (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, platform TEXT NOT NULL, region TEXT,
+                project_id TEXT, subscription_id TEXT, tenant_id TEXT, client_id TEXT, status TEXT NOT NULL DEFAULT 'active',
+                encrypted BOOLEAN NOT NULL DEFAULT 0, created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP)",
+            "ALTER TABLE accounts ADD COLUMN read_only BOOLEAN NOT NULL DEFAULT 0",
+            "CREATE TABLE instances (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, project_id INTEGER NOT NULL,
+                instance_type TEXT NOT NULL, platform TEXT NOT NULL, region TEXT NOT NULL, status TEXT NOT NULL DEFAULT 'pending',
+                public_ip TEXT, private_ip TEXT, storage_gb INTEGER NOT NULL, security_config TEXT, ssh_key TEXT, tags TEXT,
+                created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP, updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP)",
+            "INSERT INTO accounts (name, platform, region, read_only) VALUES ('legacy', 'aws', 'us-east-1', 1)",
+            "INSERT INTO instances (name, project_id, instance_type, platform, region, status, storage_gb)
+                VALUES ('i-0123456789abcdef0', 1, 't3.micro', 'aws', 'us-east-1', 'running', 8)",
+            "INSERT INTO instances (name, project_id, instance_type, platform, region, status, storage_gb)
+                VALUES ('web-vm', 1, 'e2-small', 'gcp', 'us-central1', 'running', 10)",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        run_migrations(&pool).await.unwrap();
+
+        let columns = |table: &'static str| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_as::<_, (String,)>(&format!("SELECT name FROM pragma_table_info('{}')", table))
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|(name,)| name)
+                    .collect::<Vec<_>>()
+            }
+        };
+        let account_columns = columns("accounts").await;
+        for column in ["credential_backend", "read_only", "fallback_region", "last_sync"] {
+            assert!(account_columns.iter().any(|c| c == column), "accounts.{} missing", column);
+        }
+        assert!(columns("instances").await.iter().any(|c| c == "aws_instance_id"));
+
+        // Existing rows survive, and AWS instances are findable by their instance id
+        let account = get_account(&pool, 1).await.unwrap().unwrap();
+        assert_eq!(account.name, "legacy");
+        assert!(account.read_only);
+        assert_eq!(account.credential_backend, "keyring");
+        let instance = get_instance_by_aws_id(&pool, "i-0123456789abcdef0").await.unwrap().unwrap();
+        assert_eq!(instance.status, "running");
+        assert_eq!(get_instances(&pool).await.unwrap().len(), 2);
+        assert!(get_instance_by_aws_id(&pool, "web-vm").await.unwrap().is_none());
+
+        // Every migration is recorded once; a second run applies nothing
+        let latest = MIGRATIONS.last().unwrap().version;
+        assert_eq!(get_schema_version(&pool).await.unwrap(), latest);
+        run_migrations(&pool).await.unwrap();
+        let (recorded,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM _sqlx_migrations").fetch_one(&pool).await.unwrap();
+        assert_eq!(recorded, MIGRATIONS.len() as i64);
+    }
+
+    #[tokio::test]
+    async fn test_migrations_refuse_changed_checksum() {
+        let pool = memory_pool().await;
+
+        sqlx::query("UPDATE _sqlx_migrations SET checksum = x'00' WHERE version = 1").execute(&pool).await.unwrap();
+
+        let err = run_migrations(&pool).await.unwrap_err();
+        assert!(err.to_string().contains("Migration 1"));
+    }
+
+    #[tokio::test]
+    async fn test_ssh_key_paths_are_set_and_removed_per_key_name() {
+        let pool = memory_pool().await;
+        assert!(get_ssh_key_paths(&pool).await.unwrap().is_empty());
+
+        set_ssh_key_path(&pool, "deploy", Some(" /keys/deploy.pem ")).await.unwrap();
+        set_ssh_key_path(&pool, "bastion", Some("~/work/bastion.pem")).await.unwrap();
+        let paths = get_ssh_key_paths(&pool).await.unwrap();
+        assert_eq!(paths.get("deploy").map(String::as_str), Some("/keys/deploy.pem"));
+        assert_eq!(paths.len(), 2);
+
+        let paths = set_ssh_key_path(&pool, "deploy", Some("")).await.unwrap();
+        assert!(!paths.contains_key("deploy"));
+        assert_eq!(get_ssh_key_paths(&pool).await.unwrap(), paths);
+
+        assert!(set_ssh_key_path(&pool, " ", Some("/keys/x.pem")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delete_account_with_projects_requires_force() {
+        let pool = memory_pool().await;
+        let account = create_account(&pool, unencrypted_account("prod")).await.unwrap();
+        set_account_regions(&pool, account.id, vec![AccountRegionSetting { region: "us-west-2".to_string(), enabled: true }]).await.unwrap();
+
+        let project = create_project(&pool, CreateProjectRequest {
+            name: "web".to_string(),
+            description: None,
+            region: "us-east-1".to_string(),
+            platform: "aws".to_string(),
+            account_id: Some(account.id),
+        }).await.unwrap();
+        assert_eq!(project.account_id, Some(account.id));
+
+        create_instance(&pool, CreateInstanceRequest {
+            name: "web-1".to_string(),
+            project_id: project.id,
+            instance_type: "t3.micro".to_string(),
+            platform: "aws".to_string(),
+            region: "us-east-1".to_string(),
+            storage_gb: 8,
+            security_config: None,
+            ssh_key: None,
+            tags: None,
+            lifecycle: None,
+        }).await.unwrap();
+
+        match delete_account(&pool, account.id, false).await.unwrap() {
+            AccountDeletion::HasDependents { projects, instances } => {
+                assert_eq!(projects.len(), 1);
+                assert_eq!(instances, 1);
+            }
+            other => panic!("expected HasDependents, got {:?}", other),
+        }
+        assert!(get_account(&pool, account.id).await.unwrap().is_some());
+        assert!(get_project(&pool, project.id).await.unwrap().is_some());
+
+        match delete_account(&pool, account.id, true).await.unwrap() {
+            AccountDeletion::Deleted { projects, instances } => assert_eq!((projects, instances), (1, 1)),
+            other => panic!("expected Deleted, got {:?}", other),
+        }
+        assert!(get_account(&pool, account.id).await.unwrap().is_none());
+        assert!(get_project(&pool, project.id).await.unwrap().is_none());
+        assert!(get_instances(&pool).await.unwrap().is_empty());
+        assert!(get_account_regions(&pool, account.id).await.unwrap().is_empty());
+
+        assert!(matches!(delete_account(&pool, account.id, false).await.unwrap(), AccountDeletion::NotFound));
+    }
+
+    #[tokio::test]
+    async fn test_deleting_project_cascades_to_instances_only_with_foreign_keys() {
+        async fn project_with_instance(pool: &DbPool) -> i64 {
+            let project = create_project(pool, CreateProjectRequest {
+                name: "web".to_string(),
+                description: None,
+                region: "us-east-1".to_string(),
+                platform: "aws".to_string(),
+                account_id: None,
+            }).await.unwrap();
+            create_instance(pool, CreateInstanceRequest {
+                name: "web-1".to_string(),
+                project_id: project.id,
+                instance_type: "t3.micro".to_string(),
+                platform: "aws".to_string(),
+                region: "us-east-1".to_string(),
+                storage_gb: 8,
+                security_config: None,
+                ssh_key: None,
+                tags: None,
+                lifecycle: None,
+            }).await.unwrap();
+            project.id
+        }
+
+        let pool = memory_pool().await;
+        let project_id = project_with_instance(&pool).await;
+        assert!(delete_project(&pool, project_id).await.unwrap());
+        assert!(get_instances(&pool).await.unwrap().is_empty());
+
+        // The same delete leaves the instance behind when enforcement is off
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(connect_options("sqlite::memory:").unwrap().foreign_keys(false))
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        let project_id = project_with_instance(&pool).await;
+        assert!(delete_project(&pool, project_id).await.unwrap());
+        assert_eq!(get_instances(&pool).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_instance_request_must_match_its_project() {
+        let pool = memory_pool().await;
+        let project = create_project(&pool, CreateProjectRequest {
+            name: "analytics".to_string(),
+            description: None,
+            region: "us-central1".to_string(),
+            platform: "gcp".to_string(),
+            account_id: None,
+        }).await.unwrap();
+
+        let request = |project_id: i64, platform: &str, region: &str| CreateInstanceRequest {
+            name: "worker-1".to_string(),
+            project_id,
+            instance_type: "e2-small".to_string(),
+            platform: platform.to_string(),
+            region: region.to_string(),
+            storage_gb: 10,
+            security_config: None,
+            ssh_key: None,
+            tags: None,
+            lifecycle: None,
+        };
+
+        assert_eq!(
+            validate_instance_request(&pool, &request(project.id + 100, "gcp", "us-central1"), false).await,
+            Err(InstanceRequestError::ProjectNotFound { project_id: project.id + 100 })
+        );
+
+        let err = validate_instance_request(&pool, &request(project.id, "aws", "eu-west-1"), true).await.unwrap_err();
+        assert!(matches!(err, InstanceRequestError::PlatformMismatch { ref project_platform, .. } if project_platform == "gcp"));
+        assert_eq!(serde_json::to_value(&err).unwrap()["error_type"], "platform_mismatch");
+
+        let other_region = request(project.id, "gcp", "europe-west1");
+        assert!(matches!(
+            validate_instance_request(&pool, &other_region, false).await,
+            Err(InstanceRequestError::RegionMismatch { .. })
+        ));
+        assert_eq!(validate_instance_request(&pool, &other_region, true).await, Ok(()));
+
+        assert_eq!(validate_instance_request(&pool, &request(project.id, "GCP", "us-central1"), false).await, Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_blueprint_export_import_round_trip() {
+        let pool = memory_pool().await;
+        let config = create_security_config(&pool, CreateSecurityConfigRequest {
+            name: "web".to_string(),
+            description: Some("HTTP and SSH".to_string()),
+            platform: "aws".to_string(),
+            rules: vec![SecurityRule {
+                rule_type: "ingress".to_string(),
+                port: Some(443),
+                protocol: Some("tcp".to_string()),
+                source: "0.0.0.0/0".to_string(),
+                description: None,
+            }],
+        }).await.unwrap();
+        let original = create_blueprint(&pool, CreateBlueprintRequest {
+            name: "web-server".to_string(),
+            description: None,
+            instance_type: "t3.small".to_string(),
+            platform: "aws".to_string(),
+            region: "us-east-1".to_string(),
+            storage_gb: 20,
+            security_config: Some(config.id.to_string()),
+            tags: Some(vec!["role=web".to_string()]),
+            user_data: Some("#!/bin/bash\napt-get install -y nginx".to_string()),
+            image: Some("ubuntu-22.04".to_string()),
+            vpc_id: None,
+            subnet_id: None,
+        }).await.unwrap();
+
+        let export = export_blueprint(&pool, original.id).await.unwrap().unwrap();
+        assert_eq!(export.schema_version, BLUEPRINT_EXPORT_SCHEMA_VERSION);
+        assert_eq!(export.security_config.as_ref().unwrap().rules[0].port, Some(443));
+        let json = serde_json::to_string(&export).unwrap();
+
+        // Same database: the name is taken, the identical security config is reused
+        let imported = import_blueprint(&pool, &json).await.unwrap();
+        assert_ne!(imported.id, original.id);
+        assert_eq!(imported.name, "web-server (2)");
+        assert_eq!(imported.security_config, original.security_config);
+        assert_eq!(get_security_configs(&pool).await.unwrap().len(), 1);
+
+        let reexported = export_blueprint(&pool, imported.id).await.unwrap().unwrap();
+        assert_eq!(reexported.blueprint, ExportedBlueprint { name: "web-server (2)".to_string(), ..export.blueprint.clone() });
+        assert_eq!(reexported.security_config, export.security_config);
+
+        // Another machine: the security config is recreated from the embedded rules
+        let other = memory_pool().await;
+        let imported = import_blueprint(&other, &json).await.unwrap();
+        assert_eq!(imported.name, "web-server");
+        let config_id: i64 = imported.security_config.unwrap().parse().unwrap();
+        let recreated = get_security_config(&other, config_id).await.unwrap().unwrap();
+        assert_eq!(recreated.name, "web");
+        assert_eq!(export_blueprint(&other, imported.id).await.unwrap().unwrap(), export);
+    }
+
+    #[tokio::test]
+    async fn test_clone_blueprint_applies_overrides_and_copies_security_config() {
+        let pool = memory_pool().await;
+        let config = create_security_config(&pool, CreateSecurityConfigRequest {
+            name: "web".to_string(),
+            description: None,
+            platform: "aws".to_string(),
+            rules: vec![SecurityRule {
+                rule_type: "ingress".to_string(),
+                port: Some(22),
+                protocol: Some("tcp".to_string()),
+                source: "10.0.0.0/8".to_string(),
+                description: None,
+            }],
+        }).await.unwrap();
+        let source = create_blueprint(&pool, CreateBlueprintRequest {
+            name: "web-server".to_string(),
+            description: Some("nginx".to_string()),
+            instance_type: "t3.small".to_string(),
+            platform: "aws".to_string(),
+            region: "us-east-1".to_string(),
+            storage_gb: 20,
+            security_config: Some(config.id.to_string()),
+            tags: Some(vec!["role=web".to_string()]),
+            user_data: None,
+            image: Some("ubuntu-22.04".to_string()),
+            vpc_id: None,
+            subnet_id: None,
+        }).await.unwrap();
+
+        let clone = clone_blueprint(&pool, source.id, UpdateBlueprintRequest {
+            name: None,
+            description: None,
+            instance_type: None,
+            region: Some("eu-west-1".to_string()),
+            storage_gb: Some(50),
+            security_config: None,
+            tags: None,
+            user_data: None,
+            image: None,
+            vpc_id: None,
+            subnet_id: None,
+        }).await.unwrap().unwrap();
+
+        assert_ne!(clone.id, source.id);
+        assert_eq!(clone.name, "web-server (copy)");
+        assert_eq!(clone.region, "eu-west-1");
+        assert_eq!(clone.storage_gb, 50);
+        assert_eq!(clone.instance_type, source.instance_type);
+        assert_eq!(clone.description, source.description);
+        assert_eq!(clone.image, source.image);
+        assert_eq!(clone.tags, source.tags);
+
+        // The clone gets its own copy of the security config
+        let clone_config_id: i64 = clone.security_config.as_deref().unwrap().parse().unwrap();
+        assert_ne!(clone_config_id, config.id);
+        let clone_config = get_security_config(&pool, clone_config_id).await.unwrap().unwrap();
+        assert_eq!(clone_config.name, "web (copy)");
+        assert_eq!(clone_config.rules, config.rules);
+
+        // The source is untouched
+        let stored = get_blueprint(&pool, source.id).await.unwrap().unwrap();
+        assert_eq!(stored.region, "us-east-1");
+        assert_eq!(stored.storage_gb, 20);
+        assert_eq!(stored.security_config, Some(config.id.to_string()));
+
+        // A second clone doesn't collide with the first
+        let overrides = UpdateBlueprintRequest {
+            name: None, description: None, instance_type: None, region: None, storage_gb: None,
+            security_config: None, tags: None, user_data: None, image: None, vpc_id: None, subnet_id: None,
+        };
+        let second = clone_blueprint(&pool, source.id, overrides.clone()).await.unwrap().unwrap();
+        assert_eq!(second.name, "web-server (copy) (2)");
+        assert!(clone_blueprint(&pool, 9999, overrides).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_blueprint_import_rejects_unknown_schema_version() {
+        let pool = memory_pool().await;
+        let json = serde_json::json!({
+            "schema_version": BLUEPRINT_EXPORT_SCHEMA_VERSION + 1,
+            "blueprint": {
+                "name": "future", "description": null, "instance_type": "t3.micro", "platform": "aws",
+                "region": "us-east-1", "storage_gb": 8, "tags": null, "user_data": null, "image": null
+            },
+            "security_config": null
+        });
+
+        let err = import_blueprint(&pool, &json.to_string()).await.unwrap_err();
+        assert!(err.to_string().contains("schema_version"));
+        assert!(matches!(err.downcast_ref::<DbError>(), Some(DbError::InvalidInput(_))));
+        assert!(import_blueprint(&pool, r#"{"blueprint": {}}"#).await.is_err());
+        assert!(get_blueprints(&pool).await.unwrap().is_empty());
+    }
+}
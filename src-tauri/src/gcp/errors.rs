@@ -0,0 +1,27 @@
+// ============================================================================
+// GCP ERRORS
+// ============================================================================
+// Error handling for Google Cloud operations
+// ============================================================================
+
+use thiserror::Error;
+
+// Only key errors can occur without the Compute Engine client
+#[derive(Error, Debug)]
+#[cfg_attr(not(feature = "gcp"), allow(dead_code))]
+pub enum GcpError {
+    #[error("Invalid service account key: {0}")]
+    KeyError(String),
+
+    #[error("Authentication error: {0}")]
+    AuthError(String),
+
+    #[error("GCP API error ({status}): {message}")]
+    ApiError { status: u16, message: String },
+
+    #[cfg(feature = "gcp")]
+    #[error("Network error: {0}")]
+    NetworkError(#[from] reqwest::Error),
+}
+
+pub type GcpResult<T> = Result<T, GcpError>;
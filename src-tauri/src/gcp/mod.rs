@@ -0,0 +1,9 @@
+#[cfg(feature = "gcp")]
+pub mod client;
+pub mod config;
+pub mod errors;
+
+#[cfg(feature = "gcp")]
+pub use client::{GcpClient, GcpInstance};
+pub use config::ServiceAccountKey;
+pub use errors::*;
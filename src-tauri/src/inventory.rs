@@ -0,0 +1,236 @@
+// ============================================================================
+// ANSIBLE INVENTORY
+// ============================================================================
+// Render instances as an Ansible inventory (INI or YAML), grouped by a tag,
+// project or region
+// ============================================================================
+
+use std::collections::{BTreeMap, HashMap};
+
+/// Group for hosts missing the grouping tag
+const UNGROUPED: &str = "ungrouped";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryGroupBy {
+    Tag(String),
+    Project,
+    Region,
+}
+
+impl InventoryGroupBy {
+    /// Parse `project`, `region` or `tag:<key>`
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.split_once(':') {
+            Some(("tag", key)) if !key.is_empty() => Ok(Self::Tag(key.to_string())),
+            None if value == "project" => Ok(Self::Project),
+            None if value == "region" => Ok(Self::Region),
+            _ => Err(format!("Invalid group_by '{}'. Use 'project', 'region' or 'tag:<key>'", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryFormat {
+    Ini,
+    Yaml,
+}
+
+impl InventoryFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "ini" => Ok(Self::Ini),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            other => Err(format!("Invalid inventory format '{}'. Use 'ini' or 'yaml'", other)),
+        }
+    }
+}
+
+/// Everything the inventory needs to know about one instance
+#[derive(Debug, Clone)]
+pub struct InventoryHost {
+    pub name: String,
+    pub platform: String,
+    pub project: String,
+    pub region: String,
+    pub state: String,
+    pub public_ip: Option<String>,
+    pub private_ip: Option<String>,
+    pub ssh_user: Option<String>,
+    pub tags: HashMap<String, String>,
+}
+
+/// Group name -> host name -> host vars
+pub type Inventory = BTreeMap<String, BTreeMap<String, BTreeMap<String, String>>>;
+
+/// Login user for a platform's stock images, used when the instance has no
+/// `ssh_user` override
+pub fn default_ansible_user(platform: &str) -> Option<&'static str> {
+    match platform.to_ascii_lowercase().as_str() {
+        "aws" => Some("ec2-user"),
+        "azure" => Some("azureuser"),
+        _ => None,
+    }
+}
+
+/// Group hosts into an inventory. Hosts without an IP address are skipped, as
+/// are non-running hosts unless `include_stopped` is set.
+pub fn build_inventory(hosts: &[InventoryHost], group_by: &InventoryGroupBy, include_stopped: bool) -> Inventory {
+    let mut inventory = Inventory::new();
+
+    for host in hosts {
+        if !include_stopped && host.state != "running" {
+            continue;
+        }
+        let Some(address) = host.public_ip.as_ref().or(host.private_ip.as_ref()) else {
+            continue;
+        };
+
+        let group = match group_by {
+            InventoryGroupBy::Tag(key) => host.tags.get(key).map(String::as_str).unwrap_or(UNGROUPED),
+            InventoryGroupBy::Project => host.project.as_str(),
+            InventoryGroupBy::Region => host.region.as_str(),
+        };
+
+        let mut vars = BTreeMap::new();
+        vars.insert("ansible_host".to_string(), address.clone());
+        if let Some(user) = host.ssh_user.as_deref().or(default_ansible_user(&host.platform)) {
+            vars.insert("ansible_user".to_string(), user.to_string());
+        }
+
+        inventory
+            .entry(group_name(group))
+            .or_default()
+            .insert(host.name.clone(), vars);
+    }
+
+    inventory
+}
+
+pub fn render_inventory(inventory: &Inventory, format: InventoryFormat) -> String {
+    match format {
+        InventoryFormat::Ini => render_ini(inventory),
+        InventoryFormat::Yaml => render_yaml(inventory),
+    }
+}
+
+fn render_ini(inventory: &Inventory) -> String {
+    let mut out = String::new();
+    for (group, hosts) in inventory {
+        out.push_str(&format!("[{}]\n", group));
+        for (host, vars) in hosts {
+            out.push_str(host);
+            for (key, value) in vars {
+                out.push_str(&format!(" {}={}", key, value));
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn render_yaml(inventory: &Inventory) -> String {
+    let mut out = String::from("all:\n  children:\n");
+    for (group, hosts) in inventory {
+        out.push_str(&format!("    {}:\n      hosts:\n", group));
+        for (host, vars) in hosts {
+            out.push_str(&format!("        {}:\n", yaml_scalar(host)));
+            for (key, value) in vars {
+                out.push_str(&format!("          {}: {}\n", key, yaml_scalar(value)));
+            }
+        }
+    }
+    out
+}
+
+/// Ansible group names may only contain letters, digits and underscores
+fn group_name(value: &str) -> String {
+    let name: String = value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+/// Quote a YAML value unless it is a plain identifier-like string
+fn yaml_scalar(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'));
+    if plain {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(name: &str, state: &str, role: Option<&str>, public_ip: Option<&str>) -> InventoryHost {
+        InventoryHost {
+            name: name.to_string(),
+            platform: "aws".to_string(),
+            project: "web".to_string(),
+            region: "us-east-1".to_string(),
+            state: state.to_string(),
+            public_ip: public_ip.map(str::to_string),
+            private_ip: Some("10.0.0.5".to_string()),
+            ssh_user: None,
+            tags: role.map(|r| HashMap::from([("Role".to_string(), r.to_string())])).unwrap_or_default(),
+        }
+    }
+
+    #[test]
+    fn test_running_instances_grouped_by_tag() {
+        let mut db_host = host("i-db", "running", Some("db"), None);
+        db_host.ssh_user = Some("ubuntu".to_string());
+
+        let hosts = vec![
+            host("i-web1", "running", Some("web"), Some("54.0.0.1")),
+            host("i-web2", "running", Some("web"), Some("54.0.0.2")),
+            host("i-web3", "stopped", Some("web"), Some("54.0.0.3")),
+            db_host,
+            host("i-misc", "running", None, Some("54.0.0.4")),
+        ];
+
+        let inventory = build_inventory(&hosts, &InventoryGroupBy::parse("tag:Role").unwrap(), false);
+
+        assert_eq!(inventory.keys().collect::<Vec<_>>(), vec!["db", "ungrouped", "web"]);
+        assert_eq!(inventory["web"].keys().collect::<Vec<_>>(), vec!["i-web1", "i-web2"]);
+        assert_eq!(inventory["web"]["i-web1"]["ansible_host"], "54.0.0.1");
+        assert_eq!(inventory["web"]["i-web1"]["ansible_user"], "ec2-user");
+        // Falls back to the private IP and honours the user override
+        assert_eq!(inventory["db"]["i-db"]["ansible_host"], "10.0.0.5");
+        assert_eq!(inventory["db"]["i-db"]["ansible_user"], "ubuntu");
+
+        assert_eq!(
+            render_inventory(&inventory, InventoryFormat::Ini),
+            "[db]\ni-db ansible_host=10.0.0.5 ansible_user=ubuntu\n\n\
+             [ungrouped]\ni-misc ansible_host=54.0.0.4 ansible_user=ec2-user\n\n\
+             [web]\ni-web1 ansible_host=54.0.0.1 ansible_user=ec2-user\n\
+             i-web2 ansible_host=54.0.0.2 ansible_user=ec2-user\n\n"
+        );
+
+        let yaml = render_inventory(&inventory, InventoryFormat::Yaml);
+        assert!(yaml.starts_with("all:\n  children:\n    db:\n      hosts:\n        i-db:\n          ansible_host: 10.0.0.5\n"));
+
+        // Stopped hosts are only included on request
+        let all = build_inventory(&hosts, &InventoryGroupBy::Tag("Role".to_string()), true);
+        assert_eq!(all["web"].len(), 3);
+    }
+
+    #[test]
+    fn test_group_by_parsing() {
+        assert_eq!(InventoryGroupBy::parse("region").unwrap(), InventoryGroupBy::Region);
+        assert_eq!(InventoryGroupBy::parse("project").unwrap(), InventoryGroupBy::Project);
+        assert_eq!(InventoryGroupBy::parse("tag:env").unwrap(), InventoryGroupBy::Tag("env".to_string()));
+        assert!(InventoryGroupBy::parse("tag:").is_err());
+        assert!(InventoryGroupBy::parse("owner").is_err());
+        assert_eq!(group_name("us-east-1"), "us_east_1");
+    }
+}
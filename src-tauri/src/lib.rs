@@ -1,58 +1,57 @@
 use tauri::State;
 use database::DbPool;
+use std::collections::HashMap;
+use platform::CloudProvider;
+use api_error::ApiError;
 
 #[cfg(feature = "aws-sdk")]
 use aws::{test_connection, AwsClient};
 
 #[cfg(not(feature = "aws-sdk"))]
 mod credential_validation {
-    pub async fn test_connection(access_key: &str, secret_key: &str) -> Result<(), String> {
+    use crate::api_error::ApiError;
+
+    pub async fn test_connection(access_key: &str, secret_key: &str) -> Result<(), ApiError> {
         // Basic validation when AWS SDK is not available
         if access_key.is_empty() || secret_key.is_empty() {
-            return Err("AWS credentials are required but not configured. Please set valid AWS access key and secret key.".to_string());
+            return Err(ApiError::AwsAuth("AWS credentials are required but not configured. Please set valid AWS access key and secret key.".to_string()));
         }
 
         if access_key.len() < 16 || access_key.len() > 128 {
-            return Err("AWS access key appears to be invalid. Access keys are typically 16-128 characters long.".to_string());
+            return Err(ApiError::InvalidInput("AWS access key appears to be invalid. Access keys are typically 16-128 characters long.".to_string()));
         }
 
         if secret_key.len() < 32 || secret_key.len() > 128 {
-            return Err("AWS secret key appears to be invalid. Secret keys are typically 32-128 characters long.".to_string());
+            return Err(ApiError::InvalidInput("AWS secret key appears to be invalid. Secret keys are typically 32-128 characters long.".to_string()));
         }
 
         if !access_key.starts_with("AKIA") && !access_key.starts_with("ASIA") {
-            return Err("AWS access key should start with 'AKIA' (IAM user) or 'ASIA' (temporary credentials). Please verify your access key.".to_string());
+            return Err(ApiError::InvalidInput("AWS access key should start with 'AKIA' (IAM user) or 'ASIA' (temporary credentials). Please verify your access key.".to_string()));
         }
 
         // Note: This is basic validation. Full validation requires AWS SDK.
         Ok(())
     }
 
-    pub async fn validate_credentials_for_operation(access_key: &str, secret_key: &str, region: &str) -> Result<(), String> {
+    pub async fn validate_credentials_for_operation(access_key: &str, secret_key: &str, region: &str) -> Result<(), ApiError> {
         // Test basic credential format
         test_connection(access_key, secret_key).await?;
 
         // Additional validation that would normally be done by AWS SDK
         if region.is_empty() {
-            return Err("AWS region is required. Please specify a valid AWS region.".to_string());
+            return Err(ApiError::InvalidInput("AWS region is required. Please specify a valid AWS region.".to_string()));
         }
 
-        // Valid AWS regions (subset for validation)
-        let valid_regions = [
-            "us-east-1", "us-east-2", "us-west-1", "us-west-2",
-            "eu-west-1", "eu-central-1", "ap-southeast-1", "ap-northeast-1"
-        ];
-
-        if !valid_regions.contains(&region) {
-            return Err(format!("AWS region '{}' may not be valid. Please verify the region code.", region));
+        if !crate::regions::is_valid_region(region) {
+            return Err(ApiError::InvalidInput(format!("AWS region '{}' may not be valid. Please verify the region code.", region)));
         }
 
-        Err("AWS SDK not available. To use live AWS data, build with: cargo build --features aws-sdk (requires Linux/Mac or compatible compiler)".to_string())
+        Err(ApiError::Internal("AWS SDK not available. To use live AWS data, build with: cargo build --features aws-sdk (requires Linux/Mac or compatible compiler)".to_string()))
     }
 }
 
 #[cfg(feature = "aws-sdk")]
-pub use aws::{test_connection, AwsClient};
+pub use aws::{test_connection, AwsClient, AwsClientManager};
 
 #[cfg(not(feature = "aws-sdk"))]
 pub use credential_validation::{test_connection, validate_credentials_for_operation};
@@ -61,16 +60,37 @@ pub use credential_validation::{test_connection, validate_credentials_for_operat
 
 // Declare modules
 mod database;
+mod credential_store;
+mod app_info;
+mod app_paths;
+mod regions;
+mod inventory;
+mod naming;
+mod platform;
+mod commands;
+mod confirmation;
+mod api_error;
+mod notifier;
 
 #[cfg(feature = "aws-sdk")]
 mod aws;
 
+// Always compiled: without the azure-sdk feature only credential validation is available
+mod azure;
+
+// Always compiled: without the gcp feature only service-account key validation is available
+mod gcp;
+
 // Re-export for main.rs
 pub use database::init_database_sync;
 
 // App state
 pub struct AppState {
     pub db: std::sync::Arc<tokio::sync::Mutex<DbPool>>,
+    #[cfg(feature = "aws-sdk")]
+    pub aws_clients: aws::AwsClientManager,
+    /// Tokens issued by the first step of destructive commands
+    pub confirmations: confirmation::ConfirmationStore,
 }
 
 // Placeholder commands - these need to be implemented
@@ -79,6 +99,18 @@ fn greet(name: String) -> Result<String, String> {
     Ok(format!("Hello, {}!", name))
 }
 
+#[tauri::command]
+async fn get_app_info(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::get_schema_version(&*db_guard).await {
+        Ok(schema_version) => Ok(serde_json::json!({
+            "success": true,
+            "data": app_info::AppInfo::current(schema_version)
+        })),
+        Err(e) => Ok(ApiError::database(format!("Failed to get app info: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
 // ============================================================================
 // ACCOUNT MANAGEMENT COMMANDS
 // ============================================================================
@@ -91,10 +123,7 @@ async fn get_accounts(state: State<'_, AppState>) -> Result<serde_json::Value, S
             "success": true,
             "data": accounts
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get accounts: {}", e)
-        }))
+        Err(e) => Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
@@ -106,14 +135,8 @@ async fn get_account(id: i64, state: State<'_, AppState>) -> Result<serde_json::
             "success": true,
             "data": account
         })),
-        Ok(None) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Account not found"
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get account: {}", e)
-        }))
+        Ok(None) => Ok(ApiError::NotFound("Account not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to get account: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
@@ -129,15 +152,9 @@ async fn create_account(
                 "success": true,
                 "data": account
             })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create account: {}", e)
-            }))
+            Err(e) => Ok(ApiError::from_db("Failed to create account", &e).into_response(serde_json::Value::Null))
         },
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Invalid request format: {}", e)
-        }))
+        Err(e) => Ok(ApiError::InvalidInput(format!("Invalid request format: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
@@ -147,45 +164,51 @@ async fn update_account(
     request: serde_json::Value,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let pool = state.db.lock().await.clone();
     match serde_json::from_value::<database::CreateAccountRequest>(request) {
-        Ok(req) => match database::update_account(&*db_guard, id, req).await {
-            Ok(Some(account)) => Ok(serde_json::json!({
-                "success": true,
-                "data": account
-            })),
-            Ok(None) => Ok(serde_json::json!({
-                "success": false,
-                "message": "Account not found"
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to update account: {}", e)
-            }))
+        Ok(req) => match database::update_account(&pool, id, req).await {
+            Ok(Some(account)) => {
+                #[cfg(feature = "aws-sdk")]
+                state.aws_clients.invalidate_account(id).await;
+
+                Ok(serde_json::json!({
+                    "success": true,
+                    "data": account
+                }))
+            },
+            Ok(None) => Ok(ApiError::NotFound("Account not found".to_string()).into_response(serde_json::Value::Null)),
+            Err(e) => Ok(ApiError::from_db("Failed to update account", &e).into_response(serde_json::Value::Null))
         },
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Invalid request format: {}", e)
-        }))
+        Err(e) => Ok(ApiError::InvalidInput(format!("Invalid request format: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
+/// Delete an account. Accounts owning projects are only deleted with `force`,
+/// which also deletes those projects and their instances.
 #[tauri::command]
-async fn delete_account(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    match database::delete_account(&*db_guard, id).await {
-        Ok(true) => Ok(serde_json::json!({
-            "success": true,
-            "message": "Account deleted successfully"
-        })),
-        Ok(false) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Account not found"
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to delete account: {}", e)
-        }))
+async fn delete_account(id: i64, force: Option<bool>, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    match database::delete_account(&pool, id, force.unwrap_or(false)).await {
+        Ok(database::AccountDeletion::Deleted { projects, instances }) => {
+            #[cfg(feature = "aws-sdk")]
+            state.aws_clients.invalidate_account(id).await;
+
+            Ok(serde_json::json!({
+                "success": true,
+                "message": if projects > 0 {
+                    format!("Account deleted along with {} projects and {} instances", projects, instances)
+                } else {
+                    "Account deleted successfully".to_string()
+                }
+            }))
+        },
+        Ok(database::AccountDeletion::NotFound) => Ok(ApiError::NotFound("Account not found".to_string()).into_response(serde_json::Value::Null)),
+        Ok(database::AccountDeletion::HasDependents { projects, instances }) => Ok(ApiError::InvalidInput(format!(
+            "Account has dependents: {} projects with {} instances. Pass force to delete them with the account.",
+            projects.len(), instances
+        ))
+        .into_response(serde_json::json!({ "error_type": "has_dependents", "projects": projects, "instances": instances }))),
+        Err(e) => Ok(ApiError::database(format!("Failed to delete account: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
@@ -194,36 +217,39 @@ async fn test_account_connection(
     id: i64,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let pool = state.db.lock().await.clone();
 
     // Get account credentials
-    let account = match database::get_account(&*db_guard, id).await {
+    let account = match database::get_account(&pool, id).await {
         Ok(Some(account)) => account,
         Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "AWS account not found. Please check your account configuration.",
-                "data": { "status": "failed", "error_type": "account_not_found" }
-            }));
+            return Ok(ApiError::NotFound("AWS account not found. Please check your account configuration.".to_string())
+                .into_response(serde_json::json!({ "status": "failed", "error_type": "account_not_found" })));
         }
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Database error: {}. Please check your database configuration.", e),
-                "data": { "status": "failed", "error_type": "database_error" }
-            }));
+            return Ok(ApiError::database(format!("Database error: {}. Please check your database configuration.", e))
+                .into_response(serde_json::json!({ "status": "failed", "error_type": "database_error" })));
         }
     };
 
+    match CloudProvider::from_platform(&account.platform) {
+        Some(CloudProvider::Aws) => {}
+        Some(CloudProvider::Azure) => return Ok(test_azure_connection(&pool, &account).await),
+        Some(CloudProvider::Gcp) => return Ok(test_gcp_connection(&pool, &account).await),
+        _ => {
+            return Ok(unsupported_platform_response(
+                &account.platform.to_ascii_lowercase(),
+                serde_json::json!({ "status": "failed", "error_type": "unsupported_platform" }),
+            ));
+        }
+    }
+
     // Get credentials for AWS access
-    let credentials = match database::get_account_credentials(&*db_guard, id).await {
+    let credentials = match database::get_account_credentials(&pool, id).await {
         Ok(creds) => creds,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to retrieve AWS credentials: {}. Please check your account configuration.", e),
-                "data": { "status": "failed", "error_type": "credentials_retrieval_error" }
-            }));
+            return Ok(ApiError::from_db_with_message(&e, format!("Failed to retrieve AWS credentials: {}. Please check your account configuration.", e))
+                .into_response(serde_json::json!({ "status": "failed", "error_type": "credentials_retrieval_error" })));
         }
     };
 
@@ -231,27 +257,97 @@ async fn test_account_connection(
     let secret_key = credentials.secret_key.as_deref().unwrap_or("");
 
     if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "AWS credentials are required but not configured. Please set valid AWS access key and secret key.",
-            "data": { "status": "failed", "error_type": "missing_credentials" }
-        }));
+        return Ok(ApiError::AwsAuth("AWS credentials are required but not configured. Please set valid AWS access key and secret key.".to_string())
+            .into_response(serde_json::json!({ "status": "failed", "error_type": "missing_credentials" })));
     }
 
     #[cfg(feature = "aws-sdk")]
     {
-        match test_connection(access_key, secret_key).await {
-            Ok(_) => Ok(serde_json::json!({
-                "success": true,
-                "message": "AWS credentials validated successfully with AWS API. Your account is ready to use.",
-                "data": { "status": "connected", "region": account.region.unwrap_or_else(|| "us-east-1".to_string()) }
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("AWS credential validation failed: {}. Please verify your access key and secret key are correct.", e),
-                "data": { "status": "failed", "error_type": "credential_validation_error" }
-            }))
+        // Accounts with a role are checked as the role, since that is what every operation uses
+        let connection = match &account.role_arn {
+            Some(role_arn) => {
+                let region = account.region.clone().unwrap_or_else(|| "us-east-1".to_string());
+                let config = aws::AwsConfig::from_credentials(access_key.to_string(), secret_key.to_string(), region)
+                    .with_role(role_arn.clone(), account.external_id.clone());
+                AwsClient::from_config(config).await.caller_identity().await
+            }
+            None => test_connection(access_key, secret_key).await,
+        };
+        let failure_reason = connection.as_ref().err().map(|e| match &account.role_arn {
+            Some(role_arn) => format!("{}. Check that role {} trusts these credentials", e, role_arn),
+            None => e.to_string(),
+        });
+
+        // Repeated credential failures move the account to 'error'
+        let recorded = database::record_connection_result(&pool, id, failure_reason.as_deref()).await;
+        let (account_status, status_reason) = match recorded {
+            Ok(Some(updated)) => (updated.status, updated.status_reason),
+            Ok(None) => (account.status.clone(), account.status_reason.clone()),
+            Err(e) => {
+                tracing::warn!("Failed to record connection result for account {}: {}", id, e);
+                (account.status.clone(), account.status_reason.clone())
+            }
+        };
+
+        let identity = match connection {
+            Ok(identity) => identity,
+            Err(e) => {
+                let reason = failure_reason.unwrap_or_else(|| e.to_string());
+                return Ok(ApiError::aws_with_message(
+                    &e,
+                    format!("AWS credential validation failed: {}. Please verify your access key and secret key are correct.", reason),
+                )
+                .into_response(serde_json::json!({
+                    "status": "failed",
+                    "error_type": "credential_validation_error",
+                    "account_status": account_status,
+                    "status_reason": status_reason
+                })));
+            }
+        };
+
+        let check = match database::record_account_identity(&pool, id, &identity.account_id, &identity.arn).await {
+            Ok(check) => check,
+            Err(e) => {
+                return Ok(ApiError::from_db("Failed to record AWS account identity", &e)
+                    .into_response(serde_json::json!({ "status": "failed", "error_type": "database_error" })));
+            }
+        };
+
+        if let database::AccountIdentityCheck::Mismatch { stored_account_id } = check {
+            return Ok(ApiError::InvalidInput(format!(
+                "These credentials belong to AWS account {}, but this account was previously verified as {}. Check whether the keys were swapped.",
+                identity.account_id, stored_account_id
+            ))
+            .into_response(serde_json::json!({
+                "status": "failed",
+                "error_type": "account_mismatch",
+                "aws_account_id": identity.account_id,
+                "stored_aws_account_id": stored_account_id,
+                "caller_arn": identity.arn
+            })));
         }
+
+        // Flag other configured accounts that point at the same AWS account
+        let shared_with: Vec<serde_json::Value> = database::get_accounts_sharing_aws_account(&pool, id, &identity.account_id)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|other| serde_json::json!({ "id": other.id, "name": other.name }))
+            .collect();
+
+        Ok(serde_json::json!({
+            "success": true,
+            "message": format!("AWS credentials validated successfully for AWS account {}. Your account is ready to use.", identity.account_id),
+            "data": {
+                "status": "connected",
+                "region": account.region.unwrap_or_else(|| "us-east-1".to_string()),
+                "aws_account_id": identity.account_id,
+                "caller_arn": identity.arn,
+                "shared_with": shared_with,
+                "account_status": account_status
+            }
+        }))
     }
 
     #[cfg(not(feature = "aws-sdk"))]
@@ -262,11 +358,7 @@ async fn test_account_connection(
                 "message": "AWS credentials format validated successfully. To test actual connectivity, build with: cargo build --features aws-sdk",
                 "data": { "status": "format_valid", "region": account.region.unwrap_or_else(|| "us-east-1".to_string()) }
             })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": e,
-                "data": { "status": "failed", "error_type": "format_validation_error" }
-            }))
+            Err(e) => Ok(e.into_response(serde_json::json!({ "status": "failed", "error_type": "format_validation_error" })))
         }
     }
 }
@@ -275,36 +367,164 @@ async fn test_account_connection(
 // async fn sync_account(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
 #[tauri::command]
 async fn sync_account(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    run_account_sync(id, &state).await
+}
+
+/// Accounts synced at once by sync_all_accounts, bounding the concurrent AWS
+/// calls and the database writes competing for the pool
+const SYNC_ALL_CONCURRENCY: usize = 3;
+
+/// Sync every active account concurrently, emitting `sync:account_completed`
+/// as each one finishes. A failing account is reported in its own result and
+/// does not stop the others.
+#[tauri::command]
+async fn sync_all_accounts(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    use tauri::{Emitter, Manager};
+
+    let accounts = match database::get_active_accounts(&*state.db.lock().await).await {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!({ "synced": 0, "accounts": [] })));
+        }
+    };
+
+    let sync_app = app.clone();
+    let response = sync_accounts(
+        accounts,
+        SYNC_ALL_CONCURRENCY,
+        move |account| {
+            let app = sync_app.clone();
+            async move {
+                let state = app.state::<AppState>();
+                run_account_sync(account.id, &state).await
+                    .unwrap_or_else(|e| ApiError::Internal(e).into_response(serde_json::json!({ "synced": 0 })))
+            }
+        },
+        |result, completed, total| {
+            let _ = app.emit("sync:account_completed", serde_json::json!({
+                "completed": completed,
+                "total": total,
+                "result": result
+            }));
+        },
+    )
+    .await;
+
+    Ok(response)
+}
+
+/// Run `sync` for each account, at most `concurrency` at a time, calling
+/// `on_completed` with each account's result as it finishes. The summary is
+/// successful only if every account synced; `partial` marks a mix.
+async fn sync_accounts<F, Fut, C>(
+    accounts: Vec<database::Account>,
+    concurrency: usize,
+    sync: F,
+    mut on_completed: C,
+) -> serde_json::Value
+where
+    F: Fn(database::Account) -> Fut,
+    Fut: std::future::Future<Output = serde_json::Value> + Send + 'static,
+    C: FnMut(&serde_json::Value, usize, usize),
+{
+    let total = accounts.len();
+    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(concurrency.max(1)));
+    let mut runs = tokio::task::JoinSet::new();
+    for account in accounts {
+        let semaphore = semaphore.clone();
+        let run = sync(account.clone());
+        runs.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            (account, run.await)
+        });
+    }
+
+    let mut results = Vec::with_capacity(total);
+    let mut synced = 0;
+    while let Some(run) = runs.join_next().await {
+        let result = match run {
+            Ok((account, response)) => {
+                let account_synced = response["data"]["synced"].as_u64().unwrap_or(0);
+                synced += account_synced;
+                serde_json::json!({
+                    "account_id": account.id,
+                    "name": account.name,
+                    "platform": account.platform,
+                    "success": response["success"].as_bool().unwrap_or(false),
+                    "message": response["message"],
+                    "synced": account_synced,
+                    "error": response["error"],
+                    "data": response["data"]
+                })
+            }
+            Err(e) => serde_json::json!({
+                "success": false,
+                "message": format!("Account sync task failed: {}", e),
+                "synced": 0
+            }),
+        };
+
+        on_completed(&result, results.len() + 1, total);
+        results.push(result);
+    }
+
+    let failed = results.iter().filter(|r| r["success"] != true).count();
+    serde_json::json!({
+        "success": failed == 0,
+        "message": if failed == 0 {
+            format!("Synced {} accounts", total)
+        } else {
+            format!("Synced {} of {} accounts; {} failed", total - failed, total, failed)
+        },
+        "data": {
+            "synced": synced,
+            "succeeded": total - failed,
+            "failed": failed,
+            "partial": failed > 0 && failed < total,
+            "accounts": results
+        }
+    })
+}
+
+/// Shared by sync_account and sync_all_accounts
+async fn run_account_sync(id: i64, state: &AppState) -> Result<serde_json::Value, String> {
+    // Only the pool is taken out of the lock, so the AWS calls below don't
+    // hold up every other command while they run
+    let pool = state.db.lock().await.clone();
 
     // Get account credentials
-    let account = match database::get_account(&*db_guard, id).await {
+    let account = match database::get_account(&pool, id).await {
         Ok(Some(account)) => account,
         Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Account not found",
-                "data": { "synced": 0 }
-            }));
+            return Ok(ApiError::NotFound("Account not found".to_string()).into_response(serde_json::json!({ "synced": 0 })));
         }
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get account: {}", e),
-                "data": { "synced": 0 }
-            }));
+            return Ok(ApiError::database(format!("Failed to get account: {}", e)).into_response(serde_json::json!({ "synced": 0 })));
         }
     };
 
+    if account.status == "disabled" {
+        return Ok(ApiError::InvalidInput("This account is disabled. Enable it to sync its resources.".to_string())
+            .into_response(serde_json::json!({ "synced": 0, "error_type": "account_disabled" })));
+    }
+
+    match CloudProvider::from_platform(&account.platform) {
+        Some(CloudProvider::Aws) => {}
+        Some(CloudProvider::Azure) => return Ok(sync_azure_account(&pool, &account).await),
+        Some(CloudProvider::Gcp) => return Ok(sync_gcp_account(&pool, &account).await),
+        _ => {
+            return Ok(unsupported_platform_response(
+                &account.platform.to_ascii_lowercase(),
+                serde_json::json!({ "synced": 0 }),
+            ));
+        }
+    }
+
     // Get credentials for AWS access
-    let credentials = match database::get_account_credentials(&*db_guard, id).await {
+    let credentials = match database::get_account_credentials(&pool, id).await {
         Ok(creds) => creds,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": { "synced": 0 }
-            }));
+            return Ok(ApiError::from_db("Failed to get credentials", &e).into_response(serde_json::json!({ "synced": 0 })));
         }
     };
 
@@ -313,90 +533,134 @@ async fn sync_account(id: i64, state: State<'_, AppState>) -> Result<serde_json:
     let region = account.region.as_deref().unwrap_or("us-east-1");
 
     if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "AWS credentials are required but not configured. Please set valid AWS access key and secret key.",
-            "data": { "synced": 0 }
-        }));
+        return Ok(ApiError::AwsAuth("AWS credentials are required but not configured. Please set valid AWS access key and secret key.".to_string())
+            .into_response(serde_json::json!({ "synced": 0 })));
     }
 
     #[cfg(feature = "aws-sdk")]
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    let aws_client = match state.aws_clients.get_client(&pool, id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to validate AWS credentials: {}. Please verify your access key and secret key are correct.", e),
-                "data": { "synced": 0 }
-            }));
+            let error = ApiError::aws_with_message(
+                &e,
+                format!("Failed to validate AWS credentials: {}. Please verify your access key and secret key are correct.", e),
+            );
+            return Ok(error.into_response(serde_json::json!({ "synced": 0 })));
         }
     };
 
+    #[cfg(feature = "aws-sdk")]
+    if let Some(response) = permission_guard(&aws_client, aws::permissions::COLLECTION_ACTIONS, serde_json::json!({ "synced": 0 })).await {
+        return Ok(response);
+    }
+
     #[cfg(not(feature = "aws-sdk"))]
     {
         // Validate credentials format without AWS SDK
         match validate_credentials_for_operation(access_key, secret_key, region).await {
-            Ok(_) => return Ok(serde_json::json!({
-                "success": false,
-                "message": "AWS SDK not available. To sync real AWS data, build with: cargo build --features aws-sdk (requires Linux/Mac or compatible compiler)",
-                "data": { "synced": 0 }
-            })),
-            Err(e) => return Ok(serde_json::json!({
-                "success": false,
-                "message": e,
-                "data": { "synced": 0 }
-            }))
+            Ok(_) => return Ok(ApiError::Internal(
+                "AWS SDK not available. To sync real AWS data, build with: cargo build --features aws-sdk (requires Linux/Mac or compatible compiler)".to_string(),
+            )
+            .into_response(serde_json::json!({ "synced": 0 }))),
+            Err(e) => return Ok(e.into_response(serde_json::json!({ "synced": 0 })))
         }
     }
 
     let mut synced_count = 0;
     let mut sync_results = Vec::new();
+    let mut region_results = serde_json::Map::new();
 
     #[cfg(feature = "aws-sdk")]
     {
-        // Sync EC2 instances
-        match aws_client.collect_instances().await {
-            Ok(instances) => {
-                let instance_count = instances.len();
-                synced_count += instance_count;
-                sync_results.push(format!("Synced {} EC2 instances", instance_count));
-
-                // Store instances in database
-                for instance in instances {
-                    let instance_request = database::CreateInstanceRequest {
-                        name: instance.instance_id.clone(),
-                        project_id: id,
-                        instance_type: instance.instance_type.clone(),
-                        platform: "aws".to_string(),
-                        region: instance.region.clone(),
-                        storage_gb: instance.storage_gb as i32,
-                        security_config: None,
-                        ssh_key: None,
-                        tags: Some(instance.tags.into_iter().map(|(k, v)| format!("{}={}", k, v)).collect()),
-                    };
-
-                    if let Err(e) = database::create_instance(&*db_guard, instance_request).await {
-                        sync_results.push(format!("Failed to store instance {}: {}", instance.instance_id, e));
-                    }
-                }
-            }
+        // Scan every enabled region for the account
+        let regions = match database::get_enabled_account_regions(&pool, &account).await {
+            Ok(regions) => regions,
             Err(e) => {
-                sync_results.push(format!("Failed to sync EC2 instances: {}", e));
+                sync_results.push(format!("Failed to load account regions, using {}: {}", region, e));
+                vec![region.to_string()]
             }
+        };
+
+        // Sync EC2 instances
+        let instances = aws_client.collect_instances_in_regions(&regions).await;
+        for result in instances.regions.iter().filter(|r| !r.success) {
+            sync_results.push(format!(
+                "Failed to sync EC2 instances in {}: {}",
+                result.region,
+                result.error.as_deref().unwrap_or("unknown error")
+            ));
         }
+        region_results.insert("ec2".to_string(), serde_json::json!(instances.regions));
 
-        // Sync S3 buckets
-        match aws_client.collect_buckets().await {
-            Ok(buckets) => {
-                let bucket_count = buckets.len();
-                synced_count += bucket_count;
-                sync_results.push(format!("Synced {} S3 buckets", bucket_count));
-            }
+        let instance_count = instances.items.len();
+        synced_count += instance_count;
+        sync_results.push(format!("Synced {} EC2 instances", instance_count));
+
+        // Store instances in database, under the account's project
+        let project_id = match database::get_or_create_sync_project(&pool, &account).await {
+            Ok(project) => Some(project.id),
             Err(e) => {
-                sync_results.push(format!("Failed to sync S3 buckets: {}", e));
+                sync_results.push(format!("Failed to get the project for synced instances: {}", e));
+                None
+            }
+        };
+        if let Some(project_id) = project_id {
+            for instance in instances.items {
+                let instance_request = database::CreateInstanceRequest {
+                    name: instance.instance_id.clone(),
+                    project_id,
+                    instance_type: instance.instance_type.clone(),
+                    platform: "aws".to_string(),
+                    region: instance.region.clone(),
+                    storage_gb: instance.storage_gb as i64,
+                    security_config: None,
+                    ssh_key: None,
+                    tags: Some(instance.tags.into_iter().map(|(k, v)| format!("{}={}", k, v)).collect()),
+                    lifecycle: Some(instance.lifecycle),
+                };
+
+                if let Err(e) = database::create_instance(&pool, instance_request).await {
+                    sync_results.push(format!("Failed to store instance {}: {}", instance.instance_id, e));
+                }
             }
         }
 
+        // Reconcile Elastic IP associations, only for regions that answered so
+        // a failed region doesn't clear its instances' addresses
+        let eips = aws_client.collect_elastic_ips_in_regions(&regions).await;
+        for result in eips.regions.iter().filter(|r| !r.success) {
+            sync_results.push(format!(
+                "Failed to sync Elastic IPs in {}: {}",
+                result.region,
+                result.error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+        let eip_regions: Vec<String> = eips.regions.iter().filter(|r| r.success).map(|r| r.region.clone()).collect();
+        let associations = aws::ec2::elastic_ips_by_instance(&eips.items);
+        match database::reconcile_instance_elastic_ips(&pool, id, &eip_regions, &associations).await {
+            Ok(0) => {}
+            Ok(changed) => sync_results.push(format!("Updated Elastic IPs on {} instances", changed)),
+            Err(e) => sync_results.push(format!("Failed to reconcile Elastic IPs: {}", e)),
+        }
+        if let Some(project_id) = project_id {
+            refresh_project_health(&pool, project_id, &mut sync_results).await;
+        }
+
+        // Sync S3 buckets
+        let buckets = aws_client.collect_buckets_in_regions(&regions).await;
+        for result in buckets.regions.iter().filter(|r| !r.success) {
+            sync_results.push(format!(
+                "Failed to sync S3 buckets in {}: {}",
+                result.region,
+                result.error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+        region_results.insert("s3".to_string(), serde_json::json!(buckets.regions));
+
+        let bucket_count = buckets.items.len();
+        synced_count += bucket_count;
+        sync_results.push(format!("Synced {} S3 buckets", bucket_count));
+
         // Sync Lambda functions
         match aws_client.collect_lambda_functions().await {
             Ok(functions) => {
@@ -424,7 +688,7 @@ async fn sync_account(id: i64, state: State<'_, AppState>) -> Result<serde_json:
         last_sync: Some(chrono::Utc::now().to_rfc3339()),
     };
 
-    if let Err(e) = database::update_account_fields(&*db_guard, id, update_request).await {
+    if let Err(e) = database::update_account_fields(&pool, id, update_request).await {
         sync_results.push(format!("Failed to update last sync time: {}", e));
     }
 
@@ -433,1819 +697,5172 @@ async fn sync_account(id: i64, state: State<'_, AppState>) -> Result<serde_json:
         "message": "Account sync completed",
         "data": {
             "synced": synced_count,
-            "results": sync_results
+            "results": sync_results,
+            "regions": region_results
         }
     }))
 }
 
-// ============================================================================
-// PROJECT MANAGEMENT COMMANDS
-// ============================================================================
-
-#[tauri::command]
-async fn get_projects(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    let options = database::ProjectListOptions::default();
-    match database::get_projects(&*db_guard, options).await {
-        Ok(projects) => Ok(serde_json::json!({
-            "success": true,
-            "data": projects
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get projects: {}", e)
-        }))
+/// Recompute and save the health of the project synced instances were stored
+/// under, noting any failure in the sync results
+#[cfg(any(feature = "aws-sdk", feature = "azure-sdk", feature = "gcp"))]
+async fn refresh_project_health(pool: &DbPool, project_id: i64, sync_results: &mut Vec<String>) {
+    match database::compute_project_health(pool, project_id, true).await {
+        Ok(Some(health)) => sync_results.push(format!("Project health: {}", health.status)),
+        Ok(None) => {}
+        Err(e) => sync_results.push(format!("Failed to update project health: {}", e)),
     }
 }
 
-#[tauri::command]
-async fn get_project(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    match database::get_project(&*db_guard, id).await {
-        Ok(Some(project)) => Ok(serde_json::json!({
-            "success": true,
-            "data": project
-        })),
-        Ok(None) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Project not found"
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get project: {}", e)
-        }))
-    }
+/// Response for accounts on a platform the app has no provider for
+fn unsupported_platform_response(platform: &str, data: serde_json::Value) -> serde_json::Value {
+    ApiError::InvalidInput(format!("Platform '{}' is not supported. Supported platforms are aws, azure and gcp.", platform)).into_response(data)
 }
 
-#[tauri::command]
-async fn create_project(
-    request: serde_json::Value,
-    state: State<'_, AppState>
-) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    match serde_json::from_value::<database::CreateProjectRequest>(request) {
-        Ok(req) => match database::create_project(&*db_guard, req).await {
-            Ok(project) => Ok(serde_json::json!({
-                "success": true,
-                "data": project
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create project: {}", e)
-            }))
-        },
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Invalid request format: {}", e)
-        }))
+/// Stamp the account's last sync time, noting any failure in the sync results
+#[cfg(any(feature = "azure-sdk", feature = "gcp"))]
+async fn record_sync_time(pool: &DbPool, account_id: i64, sync_results: &mut Vec<String>) {
+    let update_request = database::UpdateAccountRequest {
+        name: None,
+        access_key: None,
+        secret_key: None,
+        region: None,
+        last_sync: Some(chrono::Utc::now().to_rfc3339()),
+    };
+
+    if let Err(e) = database::update_account_fields(pool, account_id, update_request).await {
+        sync_results.push(format!("Failed to update last sync time: {}", e));
     }
 }
 
-#[tauri::command]
-async fn update_project(
-    id: i64,
-    request: serde_json::Value,
-    state: State<'_, AppState>
-) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    match serde_json::from_value::<database::UpdateProjectRequest>(request) {
-        Ok(req) => match database::update_project(&*db_guard, id, req).await {
-            Ok(Some(project)) => Ok(serde_json::json!({
-                "success": true,
-                "data": project
-            })),
-            Ok(None) => Ok(serde_json::json!({
-                "success": false,
-                "message": "Project not found"
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to update project: {}", e)
-            }))
-        },
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Invalid request format: {}", e)
-        }))
-    }
+/// Service principal config for an Azure account: ids from the account row,
+/// client secret from the credential store
+async fn azure_config_for_account(pool: &DbPool, account: &database::Account) -> Result<azure::AzureConfig, ApiError> {
+    let credentials = database::get_account_credentials(pool, account.id)
+        .await
+        .map_err(|e| ApiError::from_db("Failed to retrieve Azure credentials", &e))?;
+
+    Ok(azure::AzureConfig::from_credentials(
+        account.tenant_id.clone().unwrap_or_default(),
+        account.client_id.clone().unwrap_or_default(),
+        credentials.client_secret.unwrap_or_default(),
+        account.subscription_id.clone().unwrap_or_default(),
+    ))
 }
 
-#[tauri::command]
-async fn delete_project(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    match database::delete_project(&*db_guard, id).await {
-        Ok(true) => Ok(serde_json::json!({
+#[cfg(feature = "azure-sdk")]
+async fn azure_client_for_account(pool: &DbPool, account: &database::Account) -> Result<azure::AzureClient, ApiError> {
+    let config = azure_config_for_account(pool, account).await?;
+    azure::AzureClient::new(config).map_err(|e| ApiError::azure_with_message(&e, e.to_string()))
+}
+
+/// Validate the account's Azure fields when built without the Azure client
+#[cfg(not(feature = "azure-sdk"))]
+async fn validate_azure_account(pool: &DbPool, account: &database::Account) -> Result<(), ApiError> {
+    azure_config_for_account(pool, account)
+        .await?
+        .validate()
+        .map_err(|e| ApiError::azure_with_message(&e, e.to_string()))
+}
+
+#[cfg(not(feature = "azure-sdk"))]
+async fn test_azure_connection(pool: &DbPool, account: &database::Account) -> serde_json::Value {
+    match validate_azure_account(pool, account).await {
+        Ok(_) => serde_json::json!({
             "success": true,
-            "message": "Project deleted successfully"
-        })),
-        Ok(false) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Project not found"
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to delete project: {}", e)
-        }))
+            "message": "Azure credentials format validated successfully. To test actual connectivity, build with: cargo build --features azure-sdk",
+            "data": { "status": "format_valid", "subscription_id": account.subscription_id }
+        }),
+        Err(e) => e.into_response(serde_json::json!({ "status": "failed", "error_type": "format_validation_error" })),
     }
 }
 
-// ============================================================================
-// INSTANCE MANAGEMENT COMMANDS
-// ============================================================================
+#[cfg(not(feature = "azure-sdk"))]
+async fn sync_azure_account(pool: &DbPool, account: &database::Account) -> serde_json::Value {
+    let error = match validate_azure_account(pool, account).await {
+        Ok(_) => ApiError::Internal("Azure SDK not available. To sync real Azure data, build with: cargo build --features azure-sdk".to_string()),
+        Err(e) => e,
+    };
 
-#[tauri::command]
-async fn get_instances(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    match database::get_instances(&*db_guard).await {
-        Ok(instances) => Ok(serde_json::json!({
+    error.into_response(serde_json::json!({ "synced": 0 }))
+}
+
+#[cfg(feature = "azure-sdk")]
+async fn test_azure_connection(pool: &DbPool, account: &database::Account) -> serde_json::Value {
+    let client = match azure_client_for_account(pool, account).await {
+        Ok(client) => client,
+        Err(e) => {
+            return ApiError::AwsAuth(format!("{}. Please check your account configuration.", e))
+                .into_response(serde_json::json!({ "status": "failed", "error_type": "missing_credentials" }));
+        }
+    };
+
+    match client.test_connection().await {
+        Ok(_) => serde_json::json!({
             "success": true,
-            "data": instances
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get instances: {}", e)
-        }))
+            "message": "Azure credentials validated successfully with Azure Resource Manager. Your account is ready to use.",
+            "data": { "status": "connected", "subscription_id": account.subscription_id }
+        }),
+        Err(e) => ApiError::azure_with_message(
+            &e,
+            format!("Azure credential validation failed: {}. Please verify your tenant, client id and client secret.", e),
+        )
+        .into_response(serde_json::json!({ "status": "failed", "error_type": "credential_validation_error" })),
     }
 }
 
-#[tauri::command]
-async fn get_instance(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    match database::get_instance(&*db_guard, id).await {
-        Ok(Some(instance)) => Ok(serde_json::json!({
+#[cfg(feature = "azure-sdk")]
+async fn sync_azure_account(pool: &DbPool, account: &database::Account) -> serde_json::Value {
+    let client = match azure_client_for_account(pool, account).await {
+        Ok(client) => client,
+        Err(e) => {
+            return e.into_response(serde_json::json!({ "synced": 0 }));
+        }
+    };
+
+    let vms = match client.collect_vms().await {
+        Ok(vms) => vms,
+        Err(e) => {
+            return ApiError::azure("Failed to sync Azure VMs", &e).into_response(serde_json::json!({ "synced": 0 }));
+        }
+    };
+
+    let mut sync_results = vec![format!("Synced {} Azure VMs", vms.len())];
+    let synced_count = vms.len();
+
+    // Store VMs in the same instances table as EC2 instances
+    let project_id = match database::get_or_create_sync_project(pool, account).await {
+        Ok(project) => project.id,
+        Err(e) => {
+            return ApiError::database(format!("Failed to get the project for synced VMs: {}", e)).into_response(serde_json::json!({ "synced": 0 }));
+        }
+    };
+    for vm in vms {
+        let instance_request = database::CreateInstanceRequest {
+            name: vm.name.clone(),
+            project_id,
+            instance_type: vm.vm_size,
+            platform: "azure".to_string(),
+            region: vm.location,
+            storage_gb: vm.os_disk_gb,
+            security_config: None,
+            ssh_key: None,
+            tags: Some(vm.tags.into_iter().map(|(k, v)| format!("{}={}", k, v)).collect()),
+            lifecycle: None,
+        };
+
+        if let Err(e) = database::create_instance(pool, instance_request).await {
+            sync_results.push(format!("Failed to store VM {}: {}", vm.name, e));
+        }
+    }
+    refresh_project_health(pool, project_id, &mut sync_results).await;
+
+    record_sync_time(pool, account.id, &mut sync_results).await;
+
+    serde_json::json!({
+        "success": true,
+        "message": "Account sync completed",
+        "data": {
+            "synced": synced_count,
+            "results": sync_results
+        }
+    })
+}
+
+/// The account's stored service-account key, parsed and validated
+async fn gcp_key_for_account(pool: &DbPool, account: &database::Account) -> Result<gcp::ServiceAccountKey, ApiError> {
+    let credentials = database::get_account_credentials(pool, account.id)
+        .await
+        .map_err(|e| ApiError::from_db("Failed to retrieve GCP credentials", &e))?;
+
+    let key_json = credentials.service_account_key
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| ApiError::AwsAuth("GCP service account key is required but not configured".to_string()))?;
+    gcp::ServiceAccountKey::parse(&key_json).map_err(|e| ApiError::gcp_with_message(&e, e.to_string()))
+}
+
+/// GCP client from the account's stored service-account key, targeting the
+/// account's project id when set
+#[cfg(feature = "gcp")]
+async fn gcp_client_for_account(pool: &DbPool, account: &database::Account) -> Result<gcp::GcpClient, ApiError> {
+    let key = gcp_key_for_account(pool, account).await?;
+    Ok(gcp::GcpClient::new(key, account.project_id.clone()))
+}
+
+#[cfg(not(feature = "gcp"))]
+async fn test_gcp_connection(pool: &DbPool, account: &database::Account) -> serde_json::Value {
+    match gcp_key_for_account(pool, account).await {
+        Ok(key) => serde_json::json!({
             "success": true,
-            "data": instance
-        })),
-        Ok(None) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Instance not found"
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get instance: {}", e)
-        }))
+            "message": "GCP service account key validated successfully. To test actual connectivity, build with: cargo build --features gcp",
+            "data": {
+                "status": "format_valid",
+                "project_id": account.project_id.clone().filter(|p| !p.is_empty()).unwrap_or(key.project_id)
+            }
+        }),
+        Err(e) => e.into_response(serde_json::json!({ "status": "failed", "error_type": "format_validation_error" })),
     }
 }
 
-#[tauri::command]
-async fn create_instance(
-    request: serde_json::Value,
-    state: State<'_, AppState>
-) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    match serde_json::from_value::<database::CreateInstanceRequest>(request) {
-        Ok(req) => match database::create_instance(&*db_guard, req).await {
-            Ok(instance) => Ok(serde_json::json!({
-                "success": true,
-                "data": instance
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create instance: {}", e)
-            }))
-        },
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Invalid request format: {}", e)
-        }))
+#[cfg(not(feature = "gcp"))]
+async fn sync_gcp_account(pool: &DbPool, account: &database::Account) -> serde_json::Value {
+    let error = match gcp_key_for_account(pool, account).await {
+        Ok(_) => ApiError::Internal("GCP client not available. To sync real GCP data, build with: cargo build --features gcp".to_string()),
+        Err(e) => e,
+    };
+
+    error.into_response(serde_json::json!({ "synced": 0 }))
+}
+
+#[cfg(feature = "gcp")]
+async fn test_gcp_connection(pool: &DbPool, account: &database::Account) -> serde_json::Value {
+    let client = match gcp_client_for_account(pool, account).await {
+        Ok(client) => client,
+        Err(e) => {
+            return ApiError::AwsAuth(format!("{}. Please check your account configuration.", e))
+                .into_response(serde_json::json!({ "status": "failed", "error_type": "missing_credentials" }));
+        }
+    };
+
+    match client.test_connection().await {
+        Ok(_) => serde_json::json!({
+            "success": true,
+            "message": "GCP service account validated successfully with Compute Engine. Your account is ready to use.",
+            "data": { "status": "connected", "project_id": client.project_id() }
+        }),
+        Err(e) => ApiError::gcp_with_message(
+            &e,
+            format!("GCP credential validation failed: {}. Please verify your service account key and project.", e),
+        )
+        .into_response(serde_json::json!({ "status": "failed", "error_type": "credential_validation_error" })),
+    }
+}
+
+#[cfg(feature = "gcp")]
+async fn sync_gcp_account(pool: &DbPool, account: &database::Account) -> serde_json::Value {
+    let client = match gcp_client_for_account(pool, account).await {
+        Ok(client) => client,
+        Err(e) => {
+            return e.into_response(serde_json::json!({ "synced": 0 }));
+        }
+    };
+
+    let instances = match client.collect_instances().await {
+        Ok(instances) => instances,
+        Err(e) => {
+            return ApiError::gcp("Failed to sync GCP instances", &e).into_response(serde_json::json!({ "synced": 0 }));
+        }
+    };
+
+    let mut sync_results = vec![format!("Synced {} GCP instances", instances.len())];
+    let synced_count = instances.len();
+
+    // Store instances in the same instances table as EC2 instances
+    let project_id = match database::get_or_create_sync_project(pool, account).await {
+        Ok(project) => project.id,
+        Err(e) => {
+            return ApiError::database(format!("Failed to get the project for synced instances: {}", e)).into_response(serde_json::json!({ "synced": 0 }));
+        }
+    };
+    for instance in instances {
+        let instance_request = database::CreateInstanceRequest {
+            name: instance.name.clone(),
+            project_id,
+            instance_type: instance.machine_type.clone(),
+            platform: "gcp".to_string(),
+            region: instance.region().to_string(),
+            storage_gb: instance.disk_gb,
+            security_config: None,
+            ssh_key: None,
+            tags: Some(instance.labels.iter().map(|(k, v)| format!("{}={}", k, v)).collect()),
+            lifecycle: None,
+        };
+
+        if let Err(e) = database::create_instance(pool, instance_request).await {
+            sync_results.push(format!("Failed to store instance {}: {}", instance.name, e));
+        }
     }
+    refresh_project_health(pool, project_id, &mut sync_results).await;
+
+    record_sync_time(pool, account.id, &mut sync_results).await;
+
+    serde_json::json!({
+        "success": true,
+        "message": "Account sync completed",
+        "data": {
+            "synced": synced_count,
+            "project_id": client.project_id(),
+            "results": sync_results
+        }
+    })
 }
 
+/// Enable ('active') or disable ('disabled') an account. Disabled accounts are
+/// skipped by sync, cost and health commands.
 #[tauri::command]
-async fn update_instance(
-    id: i64,
-    request: serde_json::Value,
-    state: State<'_, AppState>
-) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    match serde_json::from_value::<database::UpdateInstanceRequest>(request) {
-        Ok(req) => match database::update_instance(&*db_guard, id, req).await {
-            Ok(Some(instance)) => Ok(serde_json::json!({
+async fn set_account_status(id: i64, status: String, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    match database::set_account_status(&pool, id, &status).await {
+        Ok(Some(account)) => {
+            #[cfg(feature = "aws-sdk")]
+            state.aws_clients.invalidate_account(id).await;
+
+            Ok(serde_json::json!({
                 "success": true,
-                "data": instance
-            })),
-            Ok(None) => Ok(serde_json::json!({
-                "success": false,
-                "message": "Instance not found"
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to update instance: {}", e)
+                "message": format!("Account is now {}", account.status),
+                "data": account
             }))
         },
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Invalid request format: {}", e)
-        }))
+        Ok(None) => Ok(ApiError::NotFound("Account not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::from_db("Failed to update account status", &e).into_response(serde_json::Value::Null))
     }
 }
 
+/// Failed connection tests in a row before an account is marked 'error'
 #[tauri::command]
-async fn delete_instance(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+async fn set_credential_failure_threshold(threshold: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
-    match database::delete_instance(&*db_guard, id).await {
-        Ok(true) => Ok(serde_json::json!({
+    match database::set_credential_failure_threshold(&*db_guard, threshold).await {
+        Ok(threshold) => Ok(serde_json::json!({
             "success": true,
-            "message": "Instance deleted successfully"
+            "message": format!("Accounts are marked as failing after {} consecutive connection failures", threshold),
+            "data": { "threshold": threshold }
         })),
-        Ok(false) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Instance not found"
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to delete instance: {}", e)
-        }))
+        Err(e) => Ok(ApiError::from_db("Failed to save credential failure threshold", &e).into_response(serde_json::Value::Null))
     }
 }
 
+/// Set the webhook notified when AWS health changes status; an empty URL removes it
 #[tauri::command]
-async fn start_instance(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    match database::start_instance(&*db_guard, id).await {
-        Ok(Some(instance)) => Ok(serde_json::json!({
+async fn set_health_webhook_url(url: Option<String>, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let saved = database::set_health_webhook_url(&pool, url.as_deref()).await;
+    if saved.is_ok() {
+        state.aws_clients.reset_health_monitors().await;
+    }
+    match saved {
+        Ok(Some(url)) => Ok(serde_json::json!({
             "success": true,
-            "data": instance,
-            "message": "Instance started successfully"
+            "message": "Health status changes will be posted to the webhook",
+            "data": { "url": url }
         })),
         Ok(None) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Instance not found"
+            "success": true,
+            "message": "Health webhook removed",
+            "data": { "url": null }
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to start instance: {}", e)
-        }))
+        Err(e) => Ok(ApiError::from_db("Failed to save health webhook", &e).into_response(serde_json::Value::Null))
     }
 }
 
+/// Stored SMTP settings and whether a password is set for them
 #[tauri::command]
-async fn stop_instance(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    match database::stop_instance(&*db_guard, id).await {
-        Ok(Some(instance)) => Ok(serde_json::json!({
+async fn get_smtp_settings(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    match notifier::load_smtp_settings(&pool).await {
+        Ok(settings) => {
+            let has_password = settings.is_some() && matches!(credential_store::retrieve_smtp_password(), Ok(Some(_)));
+            Ok(serde_json::json!({
+                "success": true,
+                "message": if settings.is_some() { "SMTP is configured" } else { "SMTP isn't configured" },
+                "data": { "settings": settings, "hasPassword": has_password }
+            }))
+        }
+        Err(e) => Ok(ApiError::database(format!("Failed to get SMTP settings: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Save the SMTP server used for email alerts; no settings removes it along
+/// with its password. The password goes to the OS keyring: None keeps the
+/// stored one and an empty string clears it.
+#[tauri::command]
+async fn set_smtp_settings(
+    settings: Option<notifier::SmtpSettings>,
+    password: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    if let Some(settings) = &settings {
+        if let Err(e) = settings.validate() {
+            return Ok(e.api_error().into_response(serde_json::Value::Null));
+        }
+    }
+
+    let pool = state.db.lock().await.clone();
+    let saved = match notifier::save_smtp_settings(&pool, settings.as_ref()).await {
+        Ok(saved) => saved,
+        Err(e) => return Ok(ApiError::database(format!("Failed to save SMTP settings: {}", e)).into_response(serde_json::Value::Null)),
+    };
+
+    let password_result = match (&saved, password.as_deref()) {
+        (None, _) | (Some(_), Some("")) => credential_store::delete_smtp_password().map(|_| None),
+        (Some(_), Some(password)) => credential_store::store_smtp_password(password).map(Some),
+        (Some(_), None) => Ok(None),
+    };
+    let backend = match password_result {
+        Ok(backend) => backend,
+        Err(e) => return Ok(ApiError::Internal(format!("Failed to store SMTP password: {}", e)).into_response(serde_json::Value::Null)),
+    };
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": if saved.is_some() { "SMTP settings saved" } else { "SMTP settings removed" },
+        "data": {
+            "settings": saved,
+            "passwordBackend": backend.map(|b| b.as_str())
+        }
+    }))
+}
+
+/// Send a test email through the stored SMTP settings to the alert
+/// recipients, or to the sender when there are none
+#[tauri::command]
+async fn test_smtp(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let settings = match notifier::load_smtp_settings(&pool).await {
+        Ok(Some(settings)) => settings,
+        Ok(None) => return Ok(notifier::NotifyError::NotConfigured.api_error().into_response(serde_json::Value::Null)),
+        Err(e) => return Ok(ApiError::database(format!("Failed to get SMTP settings: {}", e)).into_response(serde_json::Value::Null)),
+    };
+
+    let to = if settings.recipients.is_empty() { vec![settings.from.clone()] } else { settings.recipients.clone() };
+    let body = format!(
+        "This is a test message from Pocket Architect, sent through {}:{}.\n\nBudget and health alerts will be delivered the same way.",
+        settings.host, settings.port
+    );
+
+    match notifier::notify_email(&pool, &to, "Pocket Architect test email", &body).await {
+        Ok(()) => Ok(serde_json::json!({
             "success": true,
-            "data": instance,
-            "message": "Instance stopped successfully"
+            "message": format!("Test email sent to {}", to.join(", ")),
+            "data": { "recipients": to }
         })),
-        Ok(None) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Instance not found"
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to stop instance: {}", e)
-        }))
+        Err(e) => Ok(e.api_error().into_response(serde_json::json!({ "recipients": to }))),
     }
 }
 
 #[tauri::command]
-async fn restart_instance(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+async fn get_ssh_key_paths(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
-    match database::restart_instance(&*db_guard, id).await {
-        Ok(Some(instance)) => Ok(serde_json::json!({
+    match database::get_ssh_key_paths(&*db_guard).await {
+        Ok(paths) => Ok(serde_json::json!({
             "success": true,
-            "data": instance,
-            "message": "Instance restarted successfully"
+            "message": format!("{} SSH key path(s) configured", paths.len()),
+            "data": { "paths": paths }
         })),
-        Ok(None) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Instance not found"
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to restart instance: {}", e)
-        }))
+        Err(e) => Ok(ApiError::database(format!("Failed to load SSH key paths: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
-// ============================================================================
-// BLUEPRINT MANAGEMENT COMMANDS
-// ============================================================================
-
+/// Point a key pair name at a local private key, or clear it to fall back to
+/// ~/.ssh/<key_name>.pem
 #[tauri::command]
-async fn get_blueprints(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+async fn set_ssh_key_path(key_name: String, path: Option<String>, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
-    match database::get_blueprints(&*db_guard).await {
-        Ok(blueprints) => Ok(serde_json::json!({
+    match database::set_ssh_key_path(&*db_guard, &key_name, path.as_deref()).await {
+        Ok(paths) => Ok(serde_json::json!({
             "success": true,
-            "data": blueprints
+            "message": match paths.get(key_name.trim()) {
+                Some(path) => format!("SSH key {} will use {}", key_name.trim(), path),
+                None => format!("SSH key {} will use the default location", key_name.trim()),
+            },
+            "data": { "paths": paths }
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get blueprints: {}", e)
-        }))
+        Err(e) => Ok(ApiError::from_db("Failed to save SSH key path", &e).into_response(serde_json::Value::Null))
     }
 }
 
 #[tauri::command]
-async fn get_blueprint(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+async fn get_account_regions(account_id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
-    match database::get_blueprint(&*db_guard, id).await {
-        Ok(Some(blueprint)) => Ok(serde_json::json!({
+    match database::get_account_regions(&*db_guard, account_id).await {
+        Ok(regions) => Ok(serde_json::json!({
             "success": true,
-            "data": blueprint
-        })),
-        Ok(None) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Blueprint not found"
+            "data": {
+                "regions": regions,
+                "available_regions": regions::AWS_REGIONS
+            }
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get blueprint: {}", e)
-        }))
+        Err(e) => Ok(ApiError::database(format!("Failed to get account regions: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
 #[tauri::command]
-async fn create_blueprint(
-    request: serde_json::Value,
+async fn set_account_regions(
+    account_id: i64,
+    regions: Vec<database::AccountRegionSetting>,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
-    match serde_json::from_value::<database::CreateBlueprintRequest>(request) {
-        Ok(req) => match database::create_blueprint(&*db_guard, req).await {
-            Ok(blueprint) => Ok(serde_json::json!({
-                "success": true,
-                "data": blueprint
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create blueprint: {}", e)
-            }))
-        },
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Invalid request format: {}", e)
-        }))
+    match database::set_account_regions(&*db_guard, account_id, regions).await {
+        Ok(regions) => Ok(serde_json::json!({
+            "success": true,
+            "message": "Account regions updated",
+            "data": regions
+        })),
+        Err(e) => Ok(ApiError::from_db("Failed to set account regions", &e).into_response(serde_json::Value::Null))
     }
 }
 
 #[tauri::command]
-async fn update_blueprint(
-    id: i64,
-    request: serde_json::Value,
-    state: State<'_, AppState>
-) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-    match serde_json::from_value::<database::UpdateBlueprintRequest>(request) {
-        Ok(req) => match database::update_blueprint(&*db_guard, id, req).await {
-            Ok(Some(blueprint)) => Ok(serde_json::json!({
-                "success": true,
-                "data": blueprint
-            })),
-            Ok(None) => Ok(serde_json::json!({
-                "success": false,
-                "message": "Blueprint not found"
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to update blueprint: {}", e)
-            }))
-        },
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Invalid request format: {}", e)
-        }))
+async fn set_credential_store_passphrase(passphrase: Option<String>) -> Result<serde_json::Value, String> {
+    if let Err(e) = credential_store::set_passphrase(passphrase) {
+        return Ok(ApiError::InvalidInput(format!("Failed to change credential store passphrase: {}", e)).into_response(serde_json::Value::Null));
     }
+    Ok(serde_json::json!({
+        "success": true,
+        "message": if credential_store::has_passphrase() {
+            "Credential store passphrase set"
+        } else {
+            "Credential store passphrase cleared; using machine-derived key"
+        }
+    }))
 }
 
 #[tauri::command]
-async fn delete_blueprint(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+async fn migrate_credentials_to_keyring(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
-    match database::delete_blueprint(&*db_guard, id).await {
-        Ok(true) => Ok(serde_json::json!({
-            "success": true,
-            "message": "Blueprint deleted successfully"
-        })),
-        Ok(false) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Blueprint not found"
+    match database::migrate_credentials_to_keyring(&*db_guard).await {
+        Ok(report) => Ok(serde_json::json!({
+            "success": report.failed_accounts.is_empty(),
+            "message": format!(
+                "Migrated {} accounts to the OS keyring ({} failed)",
+                report.migrated_accounts.len(),
+                report.failed_accounts.len()
+            ),
+            "data": report
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to delete blueprint: {}", e)
-        }))
+        Err(e) => Ok(ApiError::database(format!("Failed to migrate credentials: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
 #[tauri::command]
-async fn deploy_blueprint(
-    blueprint_id: i64,
-    project_id: i64,
-    instance_name: String,
+async fn verify_credential_linkage(
+    repair: Option<bool>,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
-    match database::deploy_blueprint(&*db_guard, blueprint_id, project_id, instance_name).await {
-        Ok(instance) => Ok(serde_json::json!({
+    match database::verify_credential_linkage(&*db_guard, repair.unwrap_or(false)).await {
+        Ok(report) => Ok(serde_json::json!({
             "success": true,
-            "data": instance,
-            "message": "Blueprint deployed successfully"
+            "message": format!(
+                "Checked {} accounts, found {} credential linkage issues",
+                report.checked_accounts,
+                report.issues.len()
+            ),
+            "data": report
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to deploy blueprint: {}", e)
-        }))
+        Err(e) => Ok(ApiError::database(format!("Failed to verify credential linkage: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
 // ============================================================================
-// SECURITY CONFIG MANAGEMENT COMMANDS
+// PROJECT MANAGEMENT COMMANDS
 // ============================================================================
 
 #[tauri::command]
-async fn get_security_configs(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+async fn get_projects(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
-    match database::get_security_configs(&*db_guard).await {
-        Ok(security_configs) => Ok(serde_json::json!({
+    let options = database::ProjectListOptions::default();
+    match database::get_projects(&*db_guard, options).await {
+        Ok(projects) => Ok(serde_json::json!({
             "success": true,
-            "data": security_configs
+            "data": projects
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get security configs: {}", e)
-        }))
+        Err(e) => Ok(ApiError::database(format!("Failed to get projects: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
 #[tauri::command]
-async fn get_security_config(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+async fn get_project(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
-    match database::get_security_config(&*db_guard, id).await {
-        Ok(Some(security_config)) => Ok(serde_json::json!({
-            "success": true,
-            "data": security_config
-        })),
-        Ok(None) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Security config not found"
+    match database::get_project(&*db_guard, id).await {
+        Ok(Some(project)) => Ok(serde_json::json!({
+            "success": true,
+            "data": project
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get security config: {}", e)
-        }))
+        Ok(None) => Ok(ApiError::NotFound("Project not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to get project: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
 #[tauri::command]
-async fn create_security_config(
+async fn create_project(
     request: serde_json::Value,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
-    match serde_json::from_value::<database::CreateSecurityConfigRequest>(request) {
-        Ok(req) => match database::create_security_config(&*db_guard, req).await {
-            Ok(security_config) => Ok(serde_json::json!({
+    match serde_json::from_value::<database::CreateProjectRequest>(request) {
+        Ok(req) => match database::create_project(&*db_guard, req).await {
+            Ok(project) => Ok(serde_json::json!({
                 "success": true,
-                "data": security_config
+                "data": project
             })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create security config: {}", e)
-            }))
+            Err(e) => Ok(ApiError::database(format!("Failed to create project: {}", e)).into_response(serde_json::Value::Null))
         },
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Invalid request format: {}", e)
-        }))
+        Err(e) => Ok(ApiError::InvalidInput(format!("Invalid request format: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
 #[tauri::command]
-async fn update_security_config(
+async fn update_project(
     id: i64,
     request: serde_json::Value,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
-    match serde_json::from_value::<database::CreateSecurityConfigRequest>(request) {
-        Ok(req) => match database::update_security_config(&*db_guard, id, req).await {
-            Ok(Some(security_config)) => Ok(serde_json::json!({
+    match serde_json::from_value::<database::UpdateProjectRequest>(request) {
+        Ok(req) => match database::update_project(&*db_guard, id, req).await {
+            Ok(Some(project)) => Ok(serde_json::json!({
                 "success": true,
-                "data": security_config
-            })),
-            Ok(None) => Ok(serde_json::json!({
-                "success": false,
-                "message": "Security config not found"
+                "data": project
             })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to update security config: {}", e)
-            }))
+            Ok(None) => Ok(ApiError::NotFound("Project not found".to_string()).into_response(serde_json::Value::Null)),
+            Err(e) => Ok(ApiError::database(format!("Failed to update project: {}", e)).into_response(serde_json::Value::Null))
         },
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Invalid request format: {}", e)
-        }))
+        Err(e) => Ok(ApiError::InvalidInput(format!("Invalid request format: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Rename a project and update the project name on instances already sent to
+/// the frontend, so they don't show the old name until the next sync
+#[tauri::command]
+async fn rename_project(
+    id: i64,
+    new_name: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let project = match database::rename_project(&pool, id, &new_name).await {
+        Ok(Some(project)) => project,
+        Ok(None) => {
+            return Ok(ApiError::NotFound("Project not found".to_string()).into_response(serde_json::Value::Null));
+        }
+        Err(e) => {
+            return Ok(ApiError::from_db("Failed to rename project", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    #[cfg(feature = "aws-sdk")]
+    {
+        let event_store = state.aws_clients.event_store();
+        let renamed = event_store.rename_project(project.id, &project.name).await;
+        tracing::debug!("Renamed project {} on {} stored instances", project.id, renamed);
+        aws::AwsEventEmitter::new(app, event_store).emit_project_renamed(project.id, &project.name).await;
     }
+    #[cfg(not(feature = "aws-sdk"))]
+    let _ = app;
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": format!("Project renamed to {}", project.name),
+        "data": project
+    }))
 }
 
+/// Project status derived from its instances; `persist` saves it on the project
 #[tauri::command]
-async fn delete_security_config(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+async fn compute_project_health(
+    project_id: i64,
+    persist: Option<bool>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
-    match database::delete_security_config(&*db_guard, id).await {
-        Ok(true) => Ok(serde_json::json!({
+    match database::compute_project_health(&*db_guard, project_id, persist.unwrap_or(false)).await {
+        Ok(Some(health)) => Ok(serde_json::json!({
             "success": true,
-            "message": "Security config deleted successfully"
+            "message": format!("Project is {}", health.status),
+            "data": health
         })),
-        Ok(false) => Ok(serde_json::json!({
-            "success": false,
-            "message": "Security config not found"
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to delete security config: {}", e)
-        }))
+        Ok(None) => Ok(ApiError::NotFound("Project not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to compute project health: {}", e)).into_response(serde_json::Value::Null))
     }
 }
 
-// ============================================================================
-// DIRECT AWS OPERATIONS
-// ============================================================================
+#[tauri::command]
+async fn delete_project(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::delete_project(&*db_guard, id).await {
+        Ok(true) => Ok(serde_json::json!({
+            "success": true,
+            "message": "Project deleted successfully"
+        })),
+        Ok(false) => Ok(ApiError::NotFound("Project not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to delete project: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
 
+/// Destroy a project's cloud resources, then delete the project. The first call
+/// (without `confirm_token`) only lists the AWS calls it would make and issues a
+/// token; calling again with that token within five minutes terminates the
+/// project's instances, releases their Elastic IPs, deletes their volumes when
+/// `delete_volumes` is set, and deletes the project and its instance rows.
 #[tauri::command]
-async fn collect_ec2_instances(
-    options: serde_json::Value,
+async fn delete_project_with_resources(
+    project_id: i64,
+    confirm_token: Option<String>,
+    delete_volumes: Option<bool>,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    // Get account ID from options
-    let account_id = options.get("account_id")
-        .and_then(|v| v.as_i64())
-        .ok_or("Missing account_id in options")?;
+    let delete_volumes = delete_volumes.unwrap_or(false);
+    let pool = state.db.lock().await.clone();
 
-    let db_guard = state.db.lock().await;
+    let project = match database::get_project(&pool, project_id).await {
+        Ok(Some(project)) => project,
+        Ok(None) => return Ok(ApiError::NotFound("Project not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => return Ok(ApiError::database(format!("Failed to get project: {}", e)).into_response(serde_json::Value::Null)),
+    };
+    let instances = match database::get_project_instances(&pool, project_id).await {
+        Ok(instances) => instances,
+        Err(e) => return Ok(ApiError::database(format!("Failed to get project instances: {}", e)).into_response(serde_json::Value::Null)),
+    };
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": []
-            }));
+    // Instances launched in AWS that haven't been terminated yet, by region
+    let mut launched: std::collections::BTreeMap<String, Vec<&database::Instance>> = std::collections::BTreeMap::new();
+    for instance in &instances {
+        if instance.aws_instance_id.is_some() && instance.status != "terminated" {
+            launched.entry(instance.region.clone()).or_default().push(instance);
         }
-    };
+    }
 
-    let account = match database::get_account(&*db_guard, account_id).await {
-        Ok(Some(account)) => account,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Account not found",
-                "data": []
-            }));
+    let mut clients = HashMap::new();
+    if !launched.is_empty() {
+        let Some(account_id) = project.account_id else {
+            return Ok(ApiError::NotFound("Project has AWS instances but isn't linked to an account".to_string())
+                .into_response(serde_json::json!({ "error_type": "no_account" })));
+        };
+
+        if let Some(response) = read_only_guard(&pool, account_id).await {
+            return Ok(response);
         }
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get account: {}", e),
-                "data": []
-            }));
+
+        for region in launched.keys() {
+            match state.aws_clients.get_client_in_region(&pool, account_id, region).await {
+                Ok(client) => { clients.insert(region.clone(), client); }
+                Err(e) => {
+                    return Ok(ApiError::aws(&format!("Failed to create AWS client for {}", region), &e).into_response(serde_json::Value::Null));
+                }
+            }
         }
-    };
+    }
 
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
+    let mut targets = Vec::new();
+    for (region, region_instances) in &launched {
+        let instance_ids: Vec<String> = region_instances.iter().filter_map(|i| i.aws_instance_id.clone()).collect();
+        let mut volume_ids = HashMap::new();
+        if delete_volumes {
+            let ec2_service = aws::ec2::Ec2Service::new(clients[region].clone());
+            volume_ids = match ec2_service.get_attached_volume_ids(&instance_ids).await {
+                Ok(volume_ids) => volume_ids,
+                Err(e) => {
+                    return Ok(ApiError::aws_with_message(&e, format!("Failed to list volumes in {}: {}", region, e)).into_response(serde_json::Value::Null));
+                }
+            };
+        }
 
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "AWS credentials are required but not configured. Please set valid AWS access key and secret key.",
-            "data": []
-        }));
+        for instance in region_instances {
+            let Some(instance_id) = instance.aws_instance_id.clone() else { continue };
+            targets.push(aws::ec2::PurgeTarget {
+                region: region.clone(),
+                volume_ids: volume_ids.remove(&instance_id).unwrap_or_default(),
+                eip_allocation_id: instance.eip_allocation_id.clone(),
+                instance_id,
+            });
+        }
     }
 
-    #[cfg(feature = "aws-sdk")]
-    {
-        // Create AWS client
-        let aws_client = match AwsClient::new(access_key, secret_key, region).await {
-            Ok(client) => client,
-            Err(e) => {
-                return Ok(serde_json::json!({
-                    "success": false,
-                    "message": format!("Failed to validate AWS credentials: {}. Please verify your access key and secret key are correct.", e),
-                    "data": []
-                }));
-            }
-        };
+    let plan = aws::ec2::purge_plan(&targets, delete_volumes);
+    let result = purge_project(&pool, &state.confirmations, &project, &instances, &plan, confirm_token.as_deref(), |step| {
+        let client = clients.get(&step.region).cloned();
+        async move {
+            let client = client.ok_or_else(|| aws::AwsError::RegionError(format!("No client for region {}", step.region)))?;
+            aws::ec2::Ec2Service::new(client).execute_purge_step(&step).await
+        }
+    }).await;
 
-        // Collect instances
-        match aws_client.collect_instances().await {
-            Ok(instances) => Ok(serde_json::json!({
-                "success": true,
-                "message": format!("Successfully collected {} EC2 instances from AWS", instances.len()),
-                "data": instances
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to collect EC2 instances: {}. Please check your AWS credentials and permissions.", e),
-                "data": []
-            }))
+    if confirm_token.is_some() {
+        for client in clients.values() {
+            client.cache.invalidate_region(client.primary_region()).await;
         }
     }
+    Ok(result)
+}
 
-    #[cfg(not(feature = "aws-sdk"))]
-    {
-        // Validate credentials format without AWS SDK
-        return match validate_credentials_for_operation(access_key, secret_key, region).await {
-            Ok(_) => Ok(serde_json::json!({
-                "success": false,
-                "message": "AWS SDK not available. To collect real EC2 instances, build with: cargo build --features aws-sdk (requires Linux/Mac or compatible compiler)",
-                "data": []
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": e,
-                "data": []
-            }))
-        };
+/// The two steps of `delete_project_with_resources` once its plan is known:
+/// without a token, describe the plan and issue one; with a token bound to
+/// this exact plan, run it through `execute` and delete the project's rows only
+/// if every step succeeded. Projects with protected instances are refused.
+async fn purge_project<F, Fut>(
+    pool: &DbPool,
+    confirmations: &confirmation::ConfirmationStore,
+    project: &database::Project,
+    instances: &[database::Instance],
+    plan: &[aws::ec2::PurgeStep],
+    confirm_token: Option<&str>,
+    execute: F,
+) -> serde_json::Value
+where
+    F: FnMut(aws::ec2::PurgeStep) -> Fut,
+    Fut: std::future::Future<Output = aws::AwsResult<()>>,
+{
+    use aws::ec2::PurgeAction;
+
+    let protected: Vec<&str> = instances.iter()
+        .filter(|instance| instance.protected)
+        .map(|instance| instance.aws_instance_id.as_deref().unwrap_or(&instance.name))
+        .collect();
+    if !protected.is_empty() {
+        return ApiError::InvalidInput(format!(
+            "Project {} has protected instances; clear their protection before deleting it: {}",
+            project.name, protected.join(", ")
+        ))
+        .into_response(serde_json::json!({ "error_type": "protected", "instance_ids": protected }));
     }
-}
 
-#[tauri::command]
-async fn create_ec2_instance(
-    instance_data: serde_json::Value,
-    state: State<'_, AppState>
-) -> Result<serde_json::Value, String> {
-    // Extract required parameters
-    let account_id = instance_data.get("account_id")
-        .and_then(|v| v.as_i64())
-        .ok_or("Missing account_id")?;
+    // The token confirms the project and every resource in the plan, so it's
+    // refused if anything was added to the project since it was issued
+    let scope = format!(
+        "delete_project_with_resources:{}:{}",
+        project.id,
+        serde_json::to_string(plan).unwrap_or_default()
+    );
+
+    let Some(confirm_token) = confirm_token else {
+        let pending = confirmations.issue(&scope, std::time::Instant::now());
+        return serde_json::json!({
+            "success": true,
+            "message": format!(
+                "Deleting project {} will run {} AWS operations and remove {} instances; confirm to continue",
+                project.name, plan.len(), instances.len()
+            ),
+            "data": {
+                "confirmation_required": true,
+                "confirm_token": pending.confirm_token,
+                "expires_in_seconds": pending.expires_in_seconds,
+                "project": project,
+                "instances": instances.len(),
+                "steps": plan
+            }
+        });
+    };
 
-    let instance_type = instance_data.get("instance_type")
-        .and_then(|v| v.as_str())
-        .ok_or("Missing instance_type")?;
+    if let Err(e) = confirmations.confirm(confirm_token, &scope, std::time::Instant::now()) {
+        return ApiError::InvalidInput(e.to_string()).into_response(serde_json::json!({ "error_type": "confirmation_failed" }));
+    }
 
-    let image_id = instance_data.get("image_id")
-        .and_then(|v| v.as_str())
-        .ok_or("Missing image_id")?;
+    let report = aws::ec2::run_purge(plan, execute).await;
+    let destroyed = serde_json::json!({
+        "terminated_instances": report.completed_ids(PurgeAction::Terminate),
+        "released_eips": report.completed_ids(PurgeAction::ReleaseEip),
+        "deleted_volumes": report.completed_ids(PurgeAction::DeleteVolume)
+    });
+
+    // The report keeps only the text of the error that stopped the purge
+    if let Some((step, error)) = &report.failed {
+        return ApiError::classify(format!("Stopped at {} of {}: {}; the project was kept", step.resource_id, project.name, error))
+            .into_response(serde_json::json!({ "error_type": "purge_failed", "destroyed": destroyed, "failed": report.failed }));
+    }
 
-    let instance_name = instance_data.get("name")
-        .and_then(|v| v.as_str())
-        .unwrap_or("pocket-architect-instance");
+    match database::delete_project_with_instances(pool, project.id).await {
+        Ok(Some(instances)) => serde_json::json!({
+            "success": true,
+            "message": format!("Deleted project {} and {} instances", project.name, instances),
+            "data": { "destroyed": destroyed, "deleted_instance_rows": instances }
+        }),
+        Ok(None) => ApiError::NotFound("Project not found".to_string()).into_response(serde_json::json!({ "destroyed": destroyed })),
+        Err(e) => ApiError::database(format!("Cloud resources were destroyed but the project couldn't be deleted: {}", e))
+            .into_response(serde_json::json!({ "destroyed": destroyed })),
+    }
+}
+
+// ============================================================================
+// INSTANCE MANAGEMENT COMMANDS
+// ============================================================================
 
+#[tauri::command]
+async fn get_instances(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
     let db_guard = state.db.lock().await;
+    match database::get_instances(&*db_guard).await {
+        Ok(instances) => Ok(serde_json::json!({
+            "success": true,
+            "data": instances
+        })),
+        Err(e) => Ok(ApiError::database(format!("Failed to get instances: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": null
-            }));
-        }
-    };
+#[tauri::command]
+async fn get_instance(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::get_instance(&*db_guard, id).await {
+        Ok(Some(instance)) => Ok(serde_json::json!({
+            "success": true,
+            "data": instance
+        })),
+        Ok(None) => Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to get instance: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
 
-    let account = match database::get_account(&*db_guard, account_id).await {
-        Ok(Some(account)) => account,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Account not found",
-                "data": null
-            }));
-        }
+/// Check an instance request against its project without creating anything
+#[tauri::command]
+async fn validate_instance_request(
+    request: serde_json::Value,
+    allow_region_mismatch: Option<bool>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match serde_json::from_value::<database::CreateInstanceRequest>(request) {
+        Ok(req) => match database::validate_instance_request(&*db_guard, &req, allow_region_mismatch.unwrap_or(false)).await {
+            Ok(()) => Ok(serde_json::json!({
+                "success": true,
+                "message": "Instance request is consistent with its project"
+            })),
+            Err(e) => Ok(ApiError::instance_request(&e, e.to_string()).into_response(serde_json::json!(e)))
+        },
+        Err(e) => Ok(ApiError::InvalidInput(format!("Invalid request format: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Create an instance in a project. The instance's platform must match the
+/// project's, and so must its region unless `allow_region_mismatch` is set.
+#[tauri::command]
+async fn create_instance(
+    request: serde_json::Value,
+    allow_region_mismatch: Option<bool>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    let req = match serde_json::from_value::<database::CreateInstanceRequest>(request) {
+        Ok(req) => req,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get account: {}", e),
-                "data": null
-            }));
+            return Ok(ApiError::InvalidInput(format!("Invalid request format: {}", e)).into_response(serde_json::Value::Null));
         }
     };
 
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
+    if let Err(e) = database::validate_instance_request(&*db_guard, &req, allow_region_mismatch.unwrap_or(false)).await {
+        return Ok(ApiError::instance_request(&e, format!("Failed to create instance: {}", e)).into_response(serde_json::json!(e)));
+    }
 
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": null
-        }));
+    match database::create_instance(&*db_guard, req).await {
+        Ok(instance) => Ok(serde_json::json!({
+            "success": true,
+            "data": instance
+        })),
+        Err(e) => Ok(ApiError::database(format!("Failed to create instance: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn update_instance(
+    id: i64,
+    request: serde_json::Value,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match serde_json::from_value::<database::UpdateInstanceRequest>(request) {
+        Ok(req) => match database::update_instance(&*db_guard, id, req).await {
+            Ok(Some(instance)) => Ok(serde_json::json!({
+                "success": true,
+                "data": instance
+            })),
+            Ok(None) => Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null)),
+            Err(e) => Ok(ApiError::database(format!("Failed to update instance: {}", e)).into_response(serde_json::Value::Null))
+        },
+        Err(e) => Ok(ApiError::InvalidInput(format!("Invalid request format: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn delete_instance(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::delete_instance(&*db_guard, id).await {
+        Ok(true) => Ok(serde_json::json!({
+            "success": true,
+            "message": "Instance deleted successfully"
+        })),
+        Ok(false) => Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to delete instance: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn start_instance(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::start_instance(&*db_guard, id).await {
+        Ok(Some(instance)) => Ok(serde_json::json!({
+            "success": true,
+            "data": instance,
+            "message": "Instance started successfully"
+        })),
+        Ok(None) => Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to start instance: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn stop_instance(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::stop_instance(&*db_guard, id).await {
+        Ok(Some(instance)) => Ok(serde_json::json!({
+            "success": true,
+            "data": instance,
+            "message": "Instance stopped successfully"
+        })),
+        Ok(None) => Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to stop instance: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn restart_instance(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::restart_instance(&*db_guard, id).await {
+        Ok(Some(instance)) => Ok(serde_json::json!({
+            "success": true,
+            "data": instance,
+            "message": "Instance restarted successfully"
+        })),
+        Ok(None) => Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to restart instance: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+// ============================================================================
+// BLUEPRINT MANAGEMENT COMMANDS
+// ============================================================================
+
+#[tauri::command]
+async fn get_blueprints(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::get_blueprints(&*db_guard).await {
+        Ok(blueprints) => Ok(serde_json::json!({
+            "success": true,
+            "data": blueprints
+        })),
+        Err(e) => Ok(ApiError::database(format!("Failed to get blueprints: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn get_blueprint(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::get_blueprint(&*db_guard, id).await {
+        Ok(Some(blueprint)) => Ok(serde_json::json!({
+            "success": true,
+            "data": blueprint
+        })),
+        Ok(None) => Ok(ApiError::NotFound("Blueprint not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to get blueprint: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn create_blueprint(
+    request: serde_json::Value,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match serde_json::from_value::<database::CreateBlueprintRequest>(request) {
+        Ok(req) => match database::create_blueprint(&*db_guard, req).await {
+            Ok(blueprint) => Ok(serde_json::json!({
+                "success": true,
+                "data": blueprint
+            })),
+            Err(e) => Ok(ApiError::database(format!("Failed to create blueprint: {}", e)).into_response(serde_json::Value::Null))
+        },
+        Err(e) => Ok(ApiError::InvalidInput(format!("Invalid request format: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn update_blueprint(
+    id: i64,
+    request: serde_json::Value,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match serde_json::from_value::<database::UpdateBlueprintRequest>(request) {
+        Ok(req) => match database::update_blueprint(&*db_guard, id, req).await {
+            Ok(Some(blueprint)) => Ok(serde_json::json!({
+                "success": true,
+                "data": blueprint
+            })),
+            Ok(None) => Ok(ApiError::NotFound("Blueprint not found".to_string()).into_response(serde_json::Value::Null)),
+            Err(e) => Ok(ApiError::database(format!("Failed to update blueprint: {}", e)).into_response(serde_json::Value::Null))
+        },
+        Err(e) => Ok(ApiError::InvalidInput(format!("Invalid request format: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn delete_blueprint(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::delete_blueprint(&*db_guard, id).await {
+        Ok(true) => Ok(serde_json::json!({
+            "success": true,
+            "message": "Blueprint deleted successfully"
+        })),
+        Ok(false) => Ok(ApiError::NotFound("Blueprint not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to delete blueprint: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn deploy_blueprint(
+    blueprint_id: i64,
+    project_id: i64,
+    instance_name: String,
+    allow_region_mismatch: Option<bool>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::deploy_blueprint(&*db_guard, blueprint_id, project_id, instance_name, allow_region_mismatch.unwrap_or(false)).await {
+        Ok(instance) => Ok(serde_json::json!({
+            "success": true,
+            "data": instance,
+            "message": "Blueprint deployed successfully"
+        })),
+        Err(e) => Ok(ApiError::from_db("Failed to deploy blueprint", &e)
+            .into_response(serde_json::json!(e.downcast_ref::<database::InstanceRequestError>())))
+    }
+}
+
+/// A new blueprint derived from `id`, with the fields set in `overrides` changed
+#[tauri::command]
+async fn clone_blueprint(
+    id: i64,
+    overrides: serde_json::Value,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match serde_json::from_value::<database::UpdateBlueprintRequest>(overrides) {
+        Ok(overrides) => match database::clone_blueprint(&*db_guard, id, overrides).await {
+            Ok(Some(blueprint)) => Ok(serde_json::json!({
+                "success": true,
+                "message": format!("Blueprint cloned as {}", blueprint.name),
+                "data": blueprint
+            })),
+            Ok(None) => Ok(ApiError::NotFound("Blueprint not found".to_string()).into_response(serde_json::Value::Null)),
+            Err(e) => Ok(ApiError::from_db("Failed to clone blueprint", &e).into_response(serde_json::Value::Null))
+        },
+        Err(e) => Ok(ApiError::InvalidInput(format!("Invalid request format: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+/// A blueprint as a JSON document that `import_blueprint` can recreate elsewhere
+#[tauri::command]
+async fn export_blueprint(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::export_blueprint(&*db_guard, id).await {
+        Ok(Some(export)) => Ok(serde_json::json!({
+            "success": true,
+            "data": export
+        })),
+        Ok(None) => Ok(ApiError::NotFound("Blueprint not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to export blueprint: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn import_blueprint(json: String, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::import_blueprint(&*db_guard, &json).await {
+        Ok(blueprint) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Blueprint imported as {}", blueprint.name),
+            "data": blueprint
+        })),
+        Err(e) => Ok(ApiError::from_db("Failed to import blueprint", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+// ============================================================================
+// SECURITY CONFIG MANAGEMENT COMMANDS
+// ============================================================================
+
+#[tauri::command]
+async fn get_security_configs(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::get_security_configs(&*db_guard).await {
+        Ok(security_configs) => Ok(serde_json::json!({
+            "success": true,
+            "data": security_configs
+        })),
+        Err(e) => Ok(ApiError::database(format!("Failed to get security configs: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn get_security_config(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::get_security_config(&*db_guard, id).await {
+        Ok(Some(security_config)) => Ok(serde_json::json!({
+            "success": true,
+            "data": security_config
+        })),
+        Ok(None) => Ok(ApiError::NotFound("Security config not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to get security config: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn create_security_config(
+    request: serde_json::Value,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match serde_json::from_value::<database::CreateSecurityConfigRequest>(request) {
+        Ok(req) => match database::create_security_config(&*db_guard, req).await {
+            Ok(security_config) => Ok(serde_json::json!({
+                "success": true,
+                "data": security_config
+            })),
+            Err(e) => Ok(ApiError::database(format!("Failed to create security config: {}", e)).into_response(serde_json::Value::Null))
+        },
+        Err(e) => Ok(ApiError::InvalidInput(format!("Invalid request format: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn update_security_config(
+    id: i64,
+    request: serde_json::Value,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match serde_json::from_value::<database::CreateSecurityConfigRequest>(request) {
+        Ok(req) => match database::update_security_config(&*db_guard, id, req).await {
+            Ok(Some(security_config)) => Ok(serde_json::json!({
+                "success": true,
+                "data": security_config
+            })),
+            Ok(None) => Ok(ApiError::NotFound("Security config not found".to_string()).into_response(serde_json::Value::Null)),
+            Err(e) => Ok(ApiError::database(format!("Failed to update security config: {}", e)).into_response(serde_json::Value::Null))
+        },
+        Err(e) => Ok(ApiError::InvalidInput(format!("Invalid request format: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+/// EC2 security groups in the account's region
+#[tauri::command]
+async fn collect_security_groups(
+    account_id: i64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    match ec2_service.collect_security_groups().await {
+        Ok(groups) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Found {} security groups", groups.len()),
+            "data": groups
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to collect security groups", &e).into_response(serde_json::json!([])))
+    }
+}
+
+/// VPCs in the account's region
+#[tauri::command]
+async fn collect_vpcs(
+    account_id: i64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    match ec2_service.collect_vpcs().await {
+        Ok(vpcs) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Found {} VPCs", vpcs.len()),
+            "data": vpcs
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to collect VPCs", &e).into_response(serde_json::json!([])))
+    }
+}
+
+/// Subnets in the account's region, only those of `vpc_id` when given
+#[tauri::command]
+async fn collect_subnets(
+    account_id: i64,
+    vpc_id: Option<String>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    match ec2_service.collect_subnets(vpc_id.as_deref()).await {
+        Ok(subnets) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Found {} subnets", subnets.len()),
+            "data": subnets
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to collect subnets", &e).into_response(serde_json::json!([])))
+    }
+}
+
+/// Spot prices for an instance type in the account's region, in one
+/// availability zone or all of them, next to the on-demand estimate
+#[tauri::command]
+async fn get_spot_price_history(
+    account_id: i64,
+    instance_type: String,
+    availability_zone: Option<String>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    let history = match ec2_service.get_spot_price_history(&instance_type, availability_zone.as_deref()).await {
+        Ok(history) => history,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to get spot price history", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let on_demand_hourly = aws::adapters::on_demand_hourly_rate(&instance_type);
+    let current = aws::ec2::current_spot_prices(&history);
+    let cheapest = current.first().map(|price| price.price);
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": match cheapest {
+            Some(price) => format!("Spot price for {} is currently ${:.4}/hour", instance_type, price),
+            None => format!("No spot price history for {}", instance_type),
+        },
+        "data": {
+            "instance_type": instance_type,
+            "current": current,
+            "history": history,
+            "spot_hourly": cheapest,
+            "spot_monthly": cheapest.map(|price| price * aws::adapters::HOURS_PER_MONTH),
+            "on_demand_hourly": on_demand_hourly,
+            "on_demand_monthly": aws::adapters::estimate_monthly_cost_for_type(&instance_type),
+            "savings_percent": cheapest.map(|price| (1.0 - price / on_demand_hourly) * 100.0)
+        }
+    }))
+}
+
+/// Create a SecurityConfig from the ingress rules of an existing EC2 security
+/// group, looked up in `region` or the account's region. Permissions that can't
+/// be expressed as rules (port ranges) are listed under `skipped`.
+#[tauri::command]
+async fn import_security_group(
+    account_id: i64,
+    group_id: String,
+    name: Option<String>,
+    region: Option<String>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let client = match &region {
+        Some(region) => state.aws_clients.get_client_in_region(&pool, account_id, region).await,
+        None => state.aws_clients.get_client(&pool, account_id).await,
+    };
+    let aws_client = match client {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    let group = match ec2_service.get_security_group(&group_id).await {
+        Ok(Some(group)) => group,
+        Ok(None) => {
+            return Ok(ApiError::NotFound(format!("Security group {} not found", group_id)).into_response(serde_json::Value::Null));
+        }
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to read security group", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let imported = aws::ec2::ip_permissions_to_rules(group.ip_permissions(), "ingress");
+    let name = name
+        .filter(|n| !n.trim().is_empty())
+        .or_else(|| group.group_name().map(str::to_string))
+        .unwrap_or_else(|| group_id.clone());
+    let description = match group.description() {
+        Some(description) => format!("{} (imported from {})", description, group_id),
+        None => format!("Imported from {}", group_id),
+    };
+
+    let request = database::CreateSecurityConfigRequest {
+        name,
+        description: Some(description),
+        platform: "aws".to_string(),
+        rules: imported.rules,
+    };
+    match database::create_security_config(&pool, request).await {
+        Ok(config) => Ok(serde_json::json!({
+            "success": true,
+            "message": if imported.skipped.is_empty() {
+                format!("Imported {} as {}", group_id, config.name)
+            } else {
+                format!("Imported {} as {}; {} permissions couldn't be imported", group_id, config.name, imported.skipped.len())
+            },
+            "data": { "security_config": config, "skipped": imported.skipped }
+        })),
+        Err(e) => Ok(ApiError::database(format!("Failed to create security config: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Create or update the EC2 security group for a security config, optionally
+/// in a specific VPC, and record its group id on the config
+#[tauri::command]
+async fn apply_security_config(
+    config_id: i64,
+    account_id: i64,
+    vpc_id: Option<String>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    match apply_stored_security_config(&pool, &ec2_service, config_id, vpc_id.as_deref()).await {
+        Ok(group_id) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Security config {} applied to security group {}", config_id, group_id),
+            "data": { "group_id": group_id }
+        })),
+        Err(response) => Ok(response)
+    }
+}
+
+/// Apply a stored security config's rules to its EC2 security group and
+/// record the group id on the config. Fails with the response to return.
+async fn apply_stored_security_config(
+    pool: &DbPool,
+    ec2_service: &aws::ec2::Ec2Service,
+    config_id: i64,
+    vpc_id: Option<&str>,
+) -> Result<String, serde_json::Value> {
+    let config = match database::get_security_config(pool, config_id).await {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            return Err(ApiError::NotFound(format!("Security config {} not found", config_id)).into_response(serde_json::Value::Null));
+        }
+        Err(e) => {
+            return Err(ApiError::database(format!("Failed to get security config: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+    let rules: Vec<database::SecurityRule> = serde_json::from_str(&config.rules).map_err(|e| {
+        ApiError::InvalidInput(format!("Security config {} has invalid rules: {}", config_id, e)).into_response(serde_json::Value::Null)
+    })?;
+
+    let group_id = ec2_service
+        .apply_security_config(config_id, &config.name, config.description.as_deref(), &rules, None, vpc_id)
+        .await
+        .map_err(|e| ApiError::aws("Failed to apply security config", &e).into_response(serde_json::Value::Null))?;
+
+    if let Err(e) = database::set_security_config_group_id(pool, config_id, &group_id).await {
+        return Err(ApiError::database(format!("Failed to record security group {} for config {}: {}", group_id, config_id, e))
+            .into_response(serde_json::Value::Null));
+    }
+    Ok(group_id)
+}
+
+#[tauri::command]
+async fn get_security_config_usage(config_id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::get_security_config_usage(&*db_guard, config_id).await {
+        Ok(Some(instances)) => Ok(serde_json::json!({
+            "success": true,
+            "data": {
+                "in_use": !instances.is_empty(),
+                "instances": instances
+            }
+        })),
+        Ok(None) => Ok(ApiError::NotFound("Security config not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to get security config usage: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn delete_security_config(
+    id: i64,
+    force: Option<bool>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::delete_security_config(&*db_guard, id, force.unwrap_or(false)).await {
+        Ok(database::SecurityConfigDeletion::Deleted { orphaned_instances }) => Ok(serde_json::json!({
+            "success": true,
+            "message": if orphaned_instances > 0 {
+                format!("Security config deleted; {} instances still reference it", orphaned_instances)
+            } else {
+                "Security config deleted successfully".to_string()
+            }
+        })),
+        Ok(database::SecurityConfigDeletion::NotFound) => Ok(ApiError::NotFound("Security config not found".to_string()).into_response(serde_json::Value::Null)),
+        Ok(database::SecurityConfigDeletion::InUse(instances)) => Ok(ApiError::InvalidInput(format!(
+            "Security config is used by {} instances. Pass force to delete it anyway.",
+            instances.len()
+        ))
+        .into_response(serde_json::json!({ "instances": instances }))),
+        Err(e) => Ok(ApiError::database(format!("Failed to delete security config: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn get_images(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::get_images(&*db_guard).await {
+        Ok(images) => Ok(serde_json::json!({
+            "success": true,
+            "data": images
+        })),
+        Err(e) => Ok(ApiError::database(format!("Failed to get images: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn get_image(id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::get_image(&*db_guard, id).await {
+        Ok(Some(image)) => Ok(serde_json::json!({
+            "success": true,
+            "data": image
+        })),
+        Ok(None) => Ok(ApiError::NotFound("Image not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to get image: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Delete an image record. With `delete_snapshots` the AMI is deregistered
+/// and the snapshots backing it are deleted first.
+#[tauri::command]
+async fn delete_image(id: i64, delete_snapshots: Option<bool>, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    // Deregistering and deleting snapshots are network calls; work on a pool
+    // handle instead of holding the shared lock
+    let pool = state.db.lock().await.clone();
+
+    if delete_snapshots.unwrap_or(false) {
+        let image = match database::get_image(&pool, id).await {
+            Ok(Some(image)) => image,
+            Ok(None) => {
+                return Ok(ApiError::NotFound("Image not found".to_string()).into_response(serde_json::Value::Null));
+            }
+            Err(e) => {
+                return Ok(ApiError::database(format!("Failed to get image: {}", e)).into_response(serde_json::Value::Null));
+            }
+        };
+
+        // Images belong to the account of the instance they were created from
+        let source_instance = match image.source_instance_id {
+            Some(instance_id) => database::get_instance(&pool, instance_id).await.ok().flatten(),
+            None => None,
+        };
+        let account_id = match source_instance {
+            Some(instance) => database::get_project_account_id(&pool, instance.project_id).await.ok().flatten(),
+            None => None,
+        };
+        let Some(account_id) = account_id else {
+            return Ok(ApiError::InvalidInput("Image is not linked to an account; delete its snapshots from the snapshot list instead".to_string())
+                .into_response(serde_json::Value::Null));
+        };
+
+        if let Some(response) = read_only_guard(&pool, account_id).await {
+            return Ok(response);
+        }
+
+        let aws_client = match state.aws_clients.get_client_in_region(&pool, account_id, &image.region).await {
+            Ok(client) => client,
+            Err(e) => {
+                return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+            }
+        };
+
+        // A snapshot can't be deleted while a registered AMI uses it
+        let ec2_service = aws::ec2::Ec2Service::new(aws_client.clone());
+        if let Err(e) = ec2_service.deregister_image(&image.image_id).await {
+            return Ok(ApiError::aws_with_message(&e, format!("Failed to deregister AMI {}: {}", image.image_id, e)).into_response(serde_json::Value::Null));
+        }
+
+        let ebs_service = aws::ebs::EbsService::new(aws_client);
+        let mut failed = Vec::new();
+        for snapshot_id in database::image_snapshot_ids(&image) {
+            if let Err(e) = ebs_service.delete_snapshot(&snapshot_id).await {
+                failed.push((snapshot_id, e));
+            }
+        }
+
+        if let Some((_, first_error)) = failed.first() {
+            // Keep the record so the remaining snapshots stay linked
+            let failures: Vec<String> = failed.iter().map(|(snapshot_id, e)| format!("{}: {}", snapshot_id, e)).collect();
+            return Ok(ApiError::aws_with_message(
+                first_error,
+                format!("Deregistered AMI {} but failed to delete snapshots: {}", image.image_id, failures.join("; ")),
+            )
+            .into_response(serde_json::Value::Null));
+        }
+    }
+
+    match database::delete_image(&pool, id).await {
+        Ok(true) => Ok(serde_json::json!({
+            "success": true,
+            "message": "Image deleted successfully"
+        })),
+        Ok(false) => Ok(ApiError::NotFound("Image not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to delete image: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+// ============================================================================
+// DIRECT AWS OPERATIONS
+// ============================================================================
+
+/// Account an instance belongs to, through the project it's filed under, or
+/// the response refusing an operation on it
+async fn instance_account_id(pool: &DbPool, instance: &database::Instance) -> Result<i64, serde_json::Value> {
+    match database::get_project_account_id(pool, instance.project_id).await {
+        Ok(Some(account_id)) => Ok(account_id),
+        Ok(None) => Err(ApiError::InvalidInput("Instance not associated with an account".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Err(ApiError::database(format!("Failed to find the instance's account: {}", e)).into_response(serde_json::Value::Null)),
+    }
+}
+
+/// Response refusing a mutating operation on a read-only account. Checked
+/// before any AWS client is built so nothing reaches the account.
+async fn read_only_guard(pool: &DbPool, account_id: i64) -> Option<serde_json::Value> {
+    match database::is_account_read_only(pool, account_id).await {
+        Ok(false) => None,
+        Ok(true) => Some(ApiError::InvalidInput("This account is read-only. Sync and view its resources, or disable read-only mode to make changes.".to_string())
+            .into_response(serde_json::json!({ "status": "failed", "error_type": "account_read_only" }))),
+        Err(e) => Some(ApiError::database(format!("Failed to check account permissions: {}", e))
+            .into_response(serde_json::json!({ "status": "failed", "error_type": "database_error" }))),
+    }
+}
+
+/// Cost tracker for a billable operation, or the response refusing it when the
+/// operation would take the account's estimated spend past the cost limit
+async fn cost_limit_guard(
+    state: &AppState,
+    pool: &DbPool,
+    account_id: i64,
+    app: tauri::AppHandle,
+    service: &str,
+    operation: &str,
+) -> Result<aws::cost::SharedCostTracker, serde_json::Value> {
+    let tracker = state.aws_clients.cost_tracker(pool, account_id, app).await.map_err(|e| {
+        ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({ "status": "failed", "error_type": "client_error" }))
+    })?;
+
+    match tracker.ensure_within_limit(service, operation).await {
+        Ok(()) => Ok(tracker),
+        Err(e) => Err(ApiError::aws_with_message(
+            &e,
+            format!("{}. Raise the limit with set_cost_thresholds or reset cost tracking to continue.", e),
+        )
+        .into_response(serde_json::json!({ "status": "failed", "error_type": "cost_limit_exceeded" }))),
+    }
+}
+
+/// Response listing the IAM actions the account's keys are missing for an
+/// operation. Simulation failures are logged and let the operation proceed,
+/// since not every key may call iam:SimulatePrincipalPolicy.
+#[cfg(feature = "aws-sdk")]
+async fn permission_guard(aws_client: &aws::AwsClient, actions: &[&str], data: serde_json::Value) -> Option<serde_json::Value> {
+    match aws_client.check_permissions(actions).await {
+        Ok(missing) if missing.is_empty() => None,
+        Ok(missing) => {
+            let mut data = data;
+            if let Some(data) = data.as_object_mut() {
+                data.insert("error_type".to_string(), serde_json::json!("missing_permissions"));
+                data.insert("missing_permissions".to_string(), serde_json::json!(missing));
+            }
+            let error = ApiError::AwsPermission(format!(
+                "Your AWS credentials are missing IAM permissions: {}. Attach a policy allowing these actions to the user or role and try again.",
+                missing.join(", ")
+            ));
+            Some(error.into_response(data))
+        }
+        Err(e) => {
+            tracing::warn!("Skipping IAM permission check: {}", e);
+            None
+        }
+    }
+}
+
+#[tauri::command]
+async fn collect_ec2_instances(
+    options: serde_json::Value,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // Get account ID from options
+    let Some(account_id) = options.get("account_id").and_then(|v| v.as_i64()) else {
+        return Ok(ApiError::InvalidInput("Missing account_id in options".to_string()).into_response(serde_json::json!([])));
+    };
+
+    let pool = state.db.lock().await.clone();
+
+    // Get account credentials
+    let credentials = match database::get_account_credentials(&pool, account_id).await {
+        Ok(creds) => creds,
+        Err(e) => {
+            return Ok(ApiError::from_db("Failed to get credentials", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    let account = match database::get_account(&pool, account_id).await {
+        Ok(Some(account)) => account,
+        Ok(None) => {
+            return Ok(ApiError::NotFound("Account not found".to_string()).into_response(serde_json::json!([])));
+        }
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to get account: {}", e)).into_response(serde_json::json!([])));
+        }
+    };
+
+    let access_key = credentials.access_key.as_deref().unwrap_or("");
+    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
+    let region = account.region.as_deref().unwrap_or("us-east-1");
+
+    if access_key.is_empty() || secret_key.is_empty() {
+        return Ok(ApiError::AwsAuth("AWS credentials are required but not configured. Please set valid AWS access key and secret key.".to_string())
+            .into_response(serde_json::json!([])));
+    }
+
+    #[cfg(feature = "aws-sdk")]
+    {
+        // Create AWS client
+        let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+            Ok(client) => client,
+            Err(e) => {
+                let error = ApiError::aws_with_message(
+                    &e,
+                    format!("Failed to validate AWS credentials: {}. Please verify your access key and secret key are correct.", e),
+                );
+                return Ok(error.into_response(serde_json::json!([])));
+            }
+        };
+
+        if let Some(response) = permission_guard(&aws_client, &["ec2:DescribeInstances"], serde_json::json!([])).await {
+            return Ok(response);
+        }
+
+        // Optional `filters: { states, tag: {key, value}, tags: {key: value}, instance_types }`
+        let filters: aws::InstanceFilters = match options.get("filters").filter(|f| !f.is_null()) {
+            Some(filters) => match serde_json::from_value(filters.clone()) {
+                Ok(filters) => filters,
+                Err(e) => {
+                    return Ok(ApiError::InvalidInput(format!("Invalid instance filters: {}", e)).into_response(serde_json::json!([])));
+                }
+            },
+            None => aws::InstanceFilters::default(),
+        };
+
+        // Collect instances
+        match aws_client.collect_matching_instances(&filters).await {
+            Ok(mut instances) => {
+                aws::reservations::annotate_instances(&aws_client, &mut instances).await;
+                Ok(serde_json::json!({
+                    "success": true,
+                    "message": format!("Successfully collected {} EC2 instances from AWS", instances.len()),
+                    "data": instances
+                }))
+            }
+            Err(e) => Ok(ApiError::aws_with_message(
+                &e,
+                format!("Failed to collect EC2 instances: {}. Please check your AWS credentials and permissions.", e),
+            )
+            .into_response(serde_json::json!([])))
+        }
+    }
+
+    #[cfg(not(feature = "aws-sdk"))]
+    {
+        // Validate credentials format without AWS SDK
+        return match validate_credentials_for_operation(access_key, secret_key, region).await {
+            Ok(_) => Ok(ApiError::Internal(
+                "AWS SDK not available. To collect real EC2 instances, build with: cargo build --features aws-sdk (requires Linux/Mac or compatible compiler)".to_string(),
+            )
+            .into_response(serde_json::json!([]))),
+            Err(e) => Ok(e.into_response(serde_json::json!([])))
+        };
+    }
+}
+
+/// Instances from every active AWS account in one list, each tagged with its
+/// account. Accounts that fail are reported in `errors` rather than failing
+/// the whole view.
+#[tauri::command]
+async fn get_all_instances(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    let accounts = match database::get_active_accounts(&pool).await {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    // Clients are cached per account, so building them up front is cheap
+    let mut targets = Vec::new();
+    let mut client_errors = Vec::new();
+    for account in accounts.into_iter().filter(|a| a.platform == "aws") {
+        let target = aws::global::InstanceAccount { account_id: account.id, account_name: account.name };
+        match state.aws_clients.get_client(&pool, account.id).await {
+            Ok(client) => targets.push((target, client)),
+            Err(e) => client_errors.push((account.id, format!("Failed to create AWS client: {}", e))),
+        }
+    }
+
+    let mut view = aws::global::collect_across_accounts(
+        targets,
+        aws::global::MAX_CONCURRENT_ACCOUNTS,
+        aws::global::cached_instances,
+    ).await;
+    view.errors.extend(client_errors);
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": format!(
+            "Collected {} instances ({} accounts failed)",
+            view.instances.len(),
+            view.errors.len()
+        ),
+        "data": view
+    }))
+}
+
+#[tauri::command]
+async fn create_ec2_instance(
+    instance_data: serde_json::Value,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // Extract required parameters
+    let Some(account_id) = instance_data.get("account_id").and_then(|v| v.as_i64()) else {
+        return Ok(ApiError::InvalidInput("Missing account_id".to_string()).into_response(serde_json::Value::Null));
+    };
+
+    let Some(instance_type) = instance_data.get("instance_type").and_then(|v| v.as_str()) else {
+        return Ok(ApiError::InvalidInput("Missing instance_type".to_string()).into_response(serde_json::Value::Null));
+    };
+
+    // AMI id or catalog alias such as "ubuntu-22.04/arm64"; may come from the blueprint instead
+    let mut image = instance_data.get("image_id")
+        .and_then(|v| v.as_str())
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_string());
+
+    let key_name = instance_data.get("key_name")
+        .and_then(|v| v.as_str());
+
+    // Generate a key pair unless an existing one is supplied, otherwise the instance is unreachable
+    let generate_key_pair = instance_data.get("generate_key_pair")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(key_name.is_none());
+
+    // Security config to attach, by id (`security_config` may also hold the id as a string)
+    let security_config_id = instance_data.get("security_config_id")
+        .and_then(|v| v.as_i64())
+        .or_else(|| instance_data.get("security_config")
+            .and_then(|v| v.as_str())
+            .and_then(|v| v.parse().ok()));
+
+    // Root volume: a full `root_volume` spec, or just `storage_gb` for a gp3 volume
+    let root_volume = match instance_data.get("root_volume").filter(|v| !v.is_null()) {
+        Some(spec) => match serde_json::from_value::<aws::RootVolumeSpec>(spec.clone()) {
+            Ok(spec) => Some(spec),
+            Err(e) => return Ok(ApiError::InvalidInput(format!("Invalid root_volume: {}", e)).into_response(serde_json::Value::Null)),
+        },
+        None => instance_data.get("storage_gb")
+            .and_then(|v| v.as_i64())
+            .map(|size| aws::RootVolumeSpec::gp3(size as i32)),
+    };
+
+    let mut launch_options = aws::LaunchOptions {
+        // Cloud-init or shell script run on first boot
+        user_data: instance_data.get("user_data")
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string()),
+        root_volume,
+        // Subnet to launch into, and the VPC it must belong to
+        vpc_id: instance_data.get("vpc_id")
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string()),
+        subnet_id: instance_data.get("subnet_id")
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string()),
+        // "on_demand" (default) or "spot", with an optional hourly max price for spot
+        purchase_option: match instance_data.get("purchase_option").filter(|v| !v.is_null()) {
+            Some(option) => match serde_json::from_value::<aws::PurchaseOption>(option.clone()) {
+                Ok(option) => option,
+                Err(e) => return Ok(ApiError::InvalidInput(format!("Invalid purchase_option: {}", e)).into_response(serde_json::Value::Null)),
+            },
+            None => aws::PurchaseOption::OnDemand,
+        },
+        spot_max_price: instance_data.get("spot_max_price")
+            .and_then(|v| v.as_str().map(|v| v.to_string()).or_else(|| v.as_f64().map(|v| v.to_string())))
+            .filter(|v| !v.is_empty()),
+    };
+
+    let pool = state.db.lock().await.clone();
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // A blueprint fills in the storage size and user data the payload leaves out
+    if let Some(blueprint_id) = instance_data.get("blueprint_id").and_then(|v| v.as_i64()) {
+        match database::get_blueprint(&pool, blueprint_id).await {
+            Ok(Some(blueprint)) => {
+                if launch_options.root_volume.is_none() {
+                    launch_options.root_volume = Some(aws::RootVolumeSpec::gp3(blueprint.storage_gb as i32));
+                }
+                if launch_options.user_data.is_none() {
+                    launch_options.user_data = blueprint.user_data;
+                }
+                if image.is_none() {
+                    image = blueprint.image;
+                }
+                if launch_options.subnet_id.is_none() {
+                    launch_options.vpc_id = launch_options.vpc_id.or(blueprint.vpc_id);
+                    launch_options.subnet_id = blueprint.subnet_id;
+                }
+            }
+            Ok(None) => return Ok(ApiError::NotFound("Blueprint not found".to_string()).into_response(serde_json::Value::Null)),
+            Err(e) => return Ok(ApiError::database(format!("Failed to get blueprint: {}", e)).into_response(serde_json::Value::Null)),
+        }
+    }
+
+    let Some(image) = image else {
+        return Ok(ApiError::InvalidInput("Missing image_id".to_string()).into_response(serde_json::Value::Null));
+    };
+
+    // Reject oversized scripts and out-of-range volumes before reaching AWS
+    if let Err(e) = aws::ec2::validate_launch_options(&launch_options) {
+        return Ok(ApiError::InvalidInput(e.to_string()).into_response(serde_json::Value::Null));
+    }
+
+    let cost_tracker = match cost_limit_guard(&state, &pool, account_id, app, "ec2", "RunInstances").await {
+        Ok(tracker) => tracker,
+        Err(response) => return Ok(response),
+    };
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    // Aliases resolve to the newest matching image in the account's region
+    let image_id = match aws::ami::resolve_image(&aws_client, &image).await {
+        Ok(image_id) => image_id,
+        Err(e) => {
+            return Ok(ApiError::aws_with_message(&e, format!("Failed to resolve image {}: {}", image, e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+
+    // Check the subnet belongs to the chosen VPC before creating anything in it
+    let vpc_id = match ec2_service.resolve_launch_vpc(&launch_options).await {
+        Ok(vpc_id) => vpc_id,
+        Err(e) => {
+            return Ok(ApiError::aws_with_message(&e, e.to_string()).into_response(serde_json::json!({ "error_type": "invalid_network" })));
+        }
+    };
+
+    // Build the security group for the referenced security config, if any, in the launch VPC
+    let mut security_group_ids = Vec::new();
+    if let Some(config_id) = security_config_id {
+        match apply_stored_security_config(&pool, &ec2_service, config_id, vpc_id.as_deref()).await {
+            Ok(group_id) => security_group_ids.push(group_id),
+            Err(response) => {
+                return Ok(response);
+            }
+        }
+    }
+
+    // Create instance
+    let result = if generate_key_pair {
+        ec2_service.create_instance_with_key_pair(instance_type, &image_id, security_group_ids.clone(), None, &launch_options).await
+            .map(|launch| (launch.instance_id, Some(launch.key_name), launch.unstored_private_key))
+    } else {
+        ec2_service.create_instance(instance_type, &image_id, key_name, security_group_ids.clone(), None, &launch_options).await
+            .map(|instance_id| (instance_id, key_name.map(|k| k.to_string()), None))
+    };
+
+    if result.is_ok() {
+        if let Err(e) = cost_tracker.record_api_call("ec2", "RunInstances").await {
+            tracing::warn!("Failed to record instance launch cost: {}", e);
+        }
+    }
+
+    match result {
+        Ok((instance_id, key_name, unstored_private_key)) => {
+            let mut data = serde_json::json!({
+                "instance_id": instance_id,
+                "image_id": image_id,
+                "key_name": key_name,
+                "key_generated": generate_key_pair,
+                "security_group_ids": security_group_ids,
+                "root_volume": launch_options.root_volume,
+                "vpc_id": vpc_id,
+                "subnet_id": launch_options.subnet_id,
+                "lifecycle": launch_options.purchase_option.lifecycle()
+            });
+            // The instance is running either way; without a stored key this
+            // response is the only chance to save it
+            if let Some(unstored) = unstored_private_key {
+                data["warning"] = serde_json::json!(format!(
+                    "The private key couldn't be stored ({}). Save it now; it won't be shown again.",
+                    unstored.error
+                ));
+                data["private_key"] = serde_json::json!(unstored.private_key);
+            }
+            Ok(serde_json::json!({
+                "success": true,
+                "message": "EC2 instance created successfully",
+                "data": data
+            }))
+        }
+        Err(e) => Ok(ApiError::aws("Failed to create instance", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Terminate an instance. With `dry_run` set, EC2 validates the request
+/// without terminating anything. `options` can take final snapshots of the
+/// attached volumes first and release the instance's Elastic IP afterwards;
+/// the steps run in order and stop at the first failure. Protected instances
+/// are refused, and the local row is kept and marked terminated.
+#[tauri::command]
+async fn delete_ec2_instance(
+    instance_id: String,
+    dry_run: Option<bool>,
+    options: Option<serde_json::Value>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    let options = match options.filter(|o| !o.is_null()).map(serde_json::from_value::<aws::ec2::DeleteInstanceOptions>) {
+        None => aws::ec2::DeleteInstanceOptions::default(),
+        Some(Ok(options)) => options,
+        Some(Err(e)) => {
+            return Ok(ApiError::InvalidInput(format!("Invalid delete options: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let pool = state.db.lock().await.clone();
+
+    // Extract account_id from instance data
+    let instance = match database::get_instance_by_aws_id(&pool, &instance_id).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => {
+            return Ok(ApiError::NotFound(format!("Instance {} not found", instance_id)).into_response(serde_json::Value::Null));
+        }
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to find instance: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let account_id = match instance_account_id(&pool, &instance).await {
+        Ok(account_id) => account_id,
+        Err(response) => return Ok(response),
+    };
+
+    if let Some(response) = deletion_protected_response(&instance) {
+        return Ok(response);
+    }
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for the instance's region
+    let aws_client = match state.aws_clients.get_client_in_region(&pool, account_id, &instance.region).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    if dry_run {
+        return match ec2_service.delete_instance(&instance_id, true).await {
+            Ok(_) => Ok(serde_json::json!({
+                "success": true,
+                "message": format!("Dry run: EC2 instance {} would be terminated", instance_id),
+                "data": { "dryRun": true, "instanceId": instance_id }
+            })),
+            Err(e) => Ok(ApiError::aws("Failed to delete instance", &e).into_response(serde_json::Value::Null)),
+        };
+    }
+
+    let volume_ids = if options.final_snapshot {
+        match ec2_service.get_attached_volume_ids(&[instance_id.clone()]).await {
+            Ok(mut volumes) => volumes.remove(&instance_id).unwrap_or_default(),
+            Err(e) => {
+                return Ok(ApiError::aws("Failed to list volumes to snapshot; the instance was not terminated", &e).into_response(serde_json::Value::Null));
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let target = aws::ec2::PurgeTarget {
+        region: instance.region.clone(),
+        instance_id: instance_id.clone(),
+        eip_allocation_id: instance.eip_allocation_id.clone(),
+        volume_ids,
+    };
+    let plan = aws::ec2::delete_instance_plan(&target, &options);
+
+    // Snapshot ids, so the response can point at the final snapshots
+    let snapshots = std::sync::Mutex::new(Vec::new());
+    let mut response = delete_instance_with_plan(&pool, &instance, &plan, |step| {
+        let ec2_service = &ec2_service;
+        let snapshots = &snapshots;
+        async move {
+            if step.action == aws::ec2::PurgeAction::SnapshotVolume {
+                let snapshot = ec2_service.final_snapshot(&step).await?;
+                snapshots.lock().unwrap_or_else(|e| e.into_inner()).push(snapshot);
+                return Ok(());
+            }
+            ec2_service.execute_purge_step(&step).await
+        }
+    })
+    .await;
+
+    response["data"]["snapshots"] = serde_json::json!(snapshots.into_inner().unwrap_or_else(|e| e.into_inner()));
+    Ok(response)
+}
+
+/// Refusal for deleting an instance whose protection flag is set
+fn deletion_protected_response(instance: &database::Instance) -> Option<serde_json::Value> {
+    instance.protected.then(|| ApiError::InvalidInput(format!("Instance {} is protected; clear its protection before deleting it", instance.name))
+        .into_response(serde_json::json!({ "error_type": "protected" })))
+}
+
+/// Run a single instance's delete plan through `execute` and report every
+/// step. Once the termination went through the local row is marked
+/// terminated rather than deleted, and a released Elastic IP is cleared from it.
+async fn delete_instance_with_plan<F, Fut>(
+    pool: &DbPool,
+    instance: &database::Instance,
+    plan: &[aws::ec2::PurgeStep],
+    execute: F,
+) -> serde_json::Value
+where
+    F: FnMut(aws::ec2::PurgeStep) -> Fut,
+    Fut: std::future::Future<Output = aws::AwsResult<()>>,
+{
+    use aws::ec2::PurgeAction;
+
+    let report = aws::ec2::run_purge(plan, execute).await;
+    let terminated = !report.completed_ids(PurgeAction::Terminate).is_empty();
+    let mut notes = Vec::new();
+
+    if terminated {
+        if let Err(e) = database::set_instance_state(pool, instance.id, "terminated", None).await {
+            notes.push(format!("recording the termination failed: {}", e));
+        }
+    }
+    if !report.completed_ids(PurgeAction::ReleaseEip).is_empty() {
+        let aws_instance_id = instance.aws_instance_id.as_deref().unwrap_or(&instance.name);
+        let cleared = match database::get_project_account_id(pool, instance.project_id).await {
+            Ok(Some(account_id)) => database::set_instance_elastic_ip(pool, account_id, aws_instance_id, None).await.map(|_| ()),
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = cleared {
+            notes.push(format!("clearing the released Elastic IP failed: {}", e));
+        }
+    }
+
+    let steps = report.step_results(plan);
+    let mut message = match &report.failed {
+        None => format!("EC2 instance {} terminated", instance.name),
+        Some((step, error)) => format!(
+            "Stopped at {:?} of {}: {}{}",
+            step.action,
+            step.resource_id,
+            error,
+            if terminated { "" } else { "; the instance was not terminated" }
+        ),
+    };
+    if !notes.is_empty() {
+        message = format!("{} ({})", message, notes.join("; "));
+    }
+
+    let mut data = serde_json::json!({
+        "instance_id": instance.name,
+        "terminated": terminated,
+        "steps": steps
+    });
+    if !report.succeeded() {
+        data["error_type"] = serde_json::json!("delete_failed");
+        // The report keeps only the text of the error that stopped the delete
+        return ApiError::classify(message).into_response(data);
+    }
+
+    serde_json::json!({
+        "success": true,
+        "message": message,
+        "data": data
+    })
+}
+
+/// Set or clear an instance's deletion protection; protected instances can't
+/// be deleted from the app
+#[tauri::command]
+async fn set_instance_protection(
+    instance_id: String,
+    protected: bool,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    let instance = match database::get_instance_by_aws_id(&*db_guard, &instance_id).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => return Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to find instance: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    match database::set_instance_protection(&*db_guard, instance.id, protected).await {
+        Ok(Some(instance)) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Instance {} is {}", instance_id, if protected { "protected" } else { "no longer protected" }),
+            "data": instance
+        })),
+        Ok(None) => Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to update instance protection: {}", e)).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Wait for an instance to settle in `target` after a start, stop or reboot was
+/// accepted, then record its status and public IP and tell the UI. Gives up
+/// after five minutes with `error_type: "timeout"` and the last state seen.
+async fn finish_instance_transition(
+    pool: &DbPool,
+    ec2_service: &aws::ec2::Ec2Service,
+    instance: &database::Instance,
+    aws_instance_id: &str,
+    target: aws_sdk_ec2::types::InstanceStateName,
+    emitter: &aws::AwsEventEmitter,
+) -> serde_json::Value {
+    let aws_instance = match ec2_service.wait_for_instance_state(aws_instance_id, target.clone()).await {
+        Ok(aws::InstanceStateWait::Reached(aws_instance)) => aws_instance,
+        Ok(aws::InstanceStateWait::TimedOut { last_state }) => {
+            return ApiError::Network(format!("Instance {} is still {} after waiting for it to become {}", aws_instance_id, last_state, target.as_str()))
+                .into_response(serde_json::json!({ "status": last_state, "error_type": "timeout" }));
+        }
+        Err(e) => {
+            return ApiError::aws_with_message(&e, format!("Failed to wait for instance {}: {}", aws_instance_id, e)).into_response(serde_json::Value::Null);
+        }
+    };
+
+    let updated = match database::set_instance_state(pool, instance.id, &aws_instance.state, aws_instance.public_ip.as_deref()).await {
+        Ok(updated) => updated,
+        Err(e) => {
+            return ApiError::database(format!("Instance {} is {} but recording it failed: {}", aws_instance_id, aws_instance.state, e))
+                .into_response(serde_json::Value::Null);
+        }
+    };
+
+    let project_name = match database::get_project(pool, instance.project_id).await {
+        Ok(Some(project)) => project.name,
+        _ => "Default AWS Project".to_string(),
+    };
+    let status = aws_instance.state.clone();
+    let frontend = aws::adapters::aws_instance_to_frontend(aws_instance, instance.project_id, project_name, "#3B82F6".to_string());
+    emitter.emit_instances_updated(vec![frontend]).await;
+
+    serde_json::json!({
+        "success": true,
+        "message": format!("EC2 instance {} is {}", aws_instance_id, status),
+        "data": updated
+    })
+}
+
+#[tauri::command]
+async fn start_ec2_instance(
+    instance_id: String,
+    wait: Option<bool>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // Get account from instance
+    let pool = state.db.lock().await.clone();
+    let instance = match database::get_instance_by_aws_id(&pool, &instance_id).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => {
+            return Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null));
+        }
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to find instance: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let account_id = match instance_account_id(&pool, &instance).await {
+        Ok(account_id) => account_id,
+        Err(response) => return Ok(response),
+    };
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+    // Start instance
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    if let Err(e) = ec2_service.start_instance(&instance_id).await {
+        return Ok(ApiError::aws("Failed to start instance", &e).into_response(serde_json::Value::Null));
+    }
+
+    if !wait.unwrap_or(false) {
+        return Ok(serde_json::json!({
+            "success": true,
+            "message": "EC2 instance started successfully"
+        }));
+    }
+
+    let emitter = aws::AwsEventEmitter::new(app, state.aws_clients.event_store());
+    Ok(finish_instance_transition(&pool, &ec2_service, &instance, &instance_id, aws_sdk_ec2::types::InstanceStateName::Running, &emitter).await)
+}
+
+#[tauri::command]
+async fn stop_ec2_instance(
+    instance_id: String,
+    wait: Option<bool>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // Get account from instance
+    let pool = state.db.lock().await.clone();
+    let instance = match database::get_instance_by_aws_id(&pool, &instance_id).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => {
+            return Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null));
+        }
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to find instance: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let account_id = match instance_account_id(&pool, &instance).await {
+        Ok(account_id) => account_id,
+        Err(response) => return Ok(response),
+    };
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+    // Stop instance
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    if let Err(e) = ec2_service.stop_instance(&instance_id).await {
+        return Ok(ApiError::aws("Failed to stop instance", &e).into_response(serde_json::Value::Null));
+    }
+
+    if !wait.unwrap_or(false) {
+        return Ok(serde_json::json!({
+            "success": true,
+            "message": "EC2 instance stopped successfully"
+        }));
+    }
+
+    let emitter = aws::AwsEventEmitter::new(app, state.aws_clients.event_store());
+    Ok(finish_instance_transition(&pool, &ec2_service, &instance, &instance_id, aws_sdk_ec2::types::InstanceStateName::Stopped, &emitter).await)
+}
+
+#[tauri::command]
+async fn restart_ec2_instance(
+    instance_id: String,
+    wait: Option<bool>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // Get account from instance
+    let pool = state.db.lock().await.clone();
+    let instance = match database::get_instance_by_aws_id(&pool, &instance_id).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => {
+            return Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null));
+        }
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to find instance: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let account_id = match instance_account_id(&pool, &instance).await {
+        Ok(account_id) => account_id,
+        Err(response) => return Ok(response),
+    };
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+    // Restart instance
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    if let Err(e) = ec2_service.restart_instance(&instance_id).await {
+        return Ok(ApiError::aws("Failed to restart instance", &e).into_response(serde_json::Value::Null));
+    }
+
+    if !wait.unwrap_or(false) {
+        return Ok(serde_json::json!({
+            "success": true,
+            "message": "EC2 instance restarted successfully"
+        }));
+    }
+
+    let emitter = aws::AwsEventEmitter::new(app, state.aws_clients.event_store());
+    Ok(finish_instance_transition(&pool, &ec2_service, &instance, &instance_id, aws_sdk_ec2::types::InstanceStateName::Running, &emitter).await)
+}
+
+/// The local rows of a bulk operation's instances, or the response refusing
+/// it: every id must be a known instance of `account_id`, and protected
+/// instances can't be terminated
+async fn bulk_operation_targets(
+    pool: &DbPool,
+    account_id: i64,
+    operation: aws::BulkInstanceOperation,
+    instance_ids: &[String],
+) -> Result<Vec<database::Instance>, serde_json::Value> {
+    if instance_ids.is_empty() {
+        return Err(ApiError::InvalidInput("No instances given".to_string()).into_response(serde_json::Value::Null));
+    }
+
+    let mut instances = Vec::new();
+    let mut missing = Vec::new();
+    for instance_id in instance_ids {
+        match database::get_instance_by_aws_id(pool, instance_id).await {
+            Ok(Some(instance)) => instances.push(instance),
+            Ok(None) => missing.push(instance_id.clone()),
+            Err(e) => {
+                return Err(ApiError::database(format!("Failed to find instance {}: {}", instance_id, e)).into_response(serde_json::Value::Null));
+            }
+        }
+    }
+
+    if !missing.is_empty() {
+        return Err(ApiError::NotFound(format!("Instances not found: {}", missing.join(", "))).into_response(serde_json::json!({ "missing": missing })));
+    }
+
+    // Instances belong to the account their project is linked to
+    let mut project_accounts: HashMap<i64, Option<i64>> = HashMap::new();
+    let mut foreign: Vec<&str> = Vec::new();
+    for instance in &instances {
+        if !project_accounts.contains_key(&instance.project_id) {
+            let project_account = match database::get_project(pool, instance.project_id).await {
+                Ok(project) => project.and_then(|project| project.account_id),
+                Err(e) => {
+                    return Err(ApiError::database(format!("Failed to get project: {}", e)).into_response(serde_json::Value::Null));
+                }
+            };
+            project_accounts.insert(instance.project_id, project_account);
+        }
+        if project_accounts[&instance.project_id] != Some(account_id) {
+            foreign.extend(instance.aws_instance_id.as_deref());
+        }
+    }
+    if !foreign.is_empty() {
+        return Err(ApiError::InvalidInput(format!("Instances don't belong to account {}: {}", account_id, foreign.join(", ")))
+            .into_response(serde_json::json!({ "error_type": "account_mismatch", "instance_ids": foreign })));
+    }
+
+    if operation == aws::BulkInstanceOperation::Terminate {
+        let protected: Vec<&str> = instances.iter()
+            .filter(|instance| instance.protected)
+            .filter_map(|instance| instance.aws_instance_id.as_deref())
+            .collect();
+        if !protected.is_empty() {
+            return Err(ApiError::InvalidInput(format!("Protected instances can't be terminated: {}", protected.join(", ")))
+                .into_response(serde_json::json!({ "error_type": "protected", "instance_ids": protected })));
+        }
+    }
+
+    Ok(instances)
+}
+
+/// Start, stop or terminate many instances of an account at once, with one
+/// StartInstances/StopInstances/TerminateInstances call per region and 100
+/// instances. Returns each instance's previous and current state as EC2
+/// reported them, records the new states in one transaction and emits a
+/// single `instances_bulk_operation` event.
+#[tauri::command]
+async fn bulk_ec2_operation(
+    account_id: i64,
+    operation: String,
+    instance_ids: Vec<String>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let operation = match aws::BulkInstanceOperation::parse(&operation) {
+        Ok(operation) => operation,
+        Err(e) => return Ok(ApiError::InvalidInput(e.to_string()).into_response(serde_json::Value::Null)),
+    };
+    let mut instance_ids = instance_ids;
+    let mut seen = std::collections::HashSet::new();
+    instance_ids.retain(|id| seen.insert(id.clone()));
+
+    let pool = state.db.lock().await.clone();
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    let instances = match bulk_operation_targets(&pool, account_id, operation, &instance_ids).await {
+        Ok(instances) => instances,
+        Err(response) => return Ok(response),
+    };
+
+    // Instances are started and stopped by clients in their own region
+    let mut by_region: std::collections::BTreeMap<String, Vec<String>> = std::collections::BTreeMap::new();
+    for instance in &instances {
+        if let Some(instance_id) = &instance.aws_instance_id {
+            by_region.entry(instance.region.clone()).or_default().push(instance_id.clone());
+        }
+    }
+
+    let mut clients = Vec::new();
+    for (region, region_ids) in by_region {
+        match state.aws_clients.get_client_in_region(&pool, account_id, &region).await {
+            Ok(client) => clients.push((client, region_ids)),
+            Err(e) => {
+                return Ok(ApiError::aws(&format!("Failed to create AWS client for {}", region), &e).into_response(serde_json::Value::Null));
+            }
+        }
+    }
+
+    let mut report = aws::BulkInstanceOperationReport {
+        operation,
+        transitions: Vec::new(),
+        failed: Vec::new(),
+    };
+    for (client, region_ids) in clients {
+        let region_report = aws::ec2::Ec2Service::new(client.clone()).bulk_instance_operation(operation, &region_ids).await;
+        client.cache.update_instance_states(client.primary_region(), &region_report.transitions).await;
+        report.transitions.extend(region_report.transitions);
+        report.failed.extend(region_report.failed);
+    }
+
+    let statuses: Vec<(String, String)> = report.transitions.iter()
+        .map(|t| (t.instance_id.clone(), t.current_state.clone()))
+        .collect();
+    let updated = database::set_instance_statuses(&pool, &statuses).await;
+
+    let emitter = aws::AwsEventEmitter::new(app, state.aws_clients.event_store());
+    emitter.emit_instances_bulk_operation(&report).await;
+
+    let updated = match updated {
+        Ok(updated) => updated,
+        Err(e) => {
+            return Ok(ApiError::database(format!(
+                "{} of {} instances were sent to {} but recording their states failed: {}",
+                report.transitions.len(), instance_ids.len(), operation.as_str(), e
+            ))
+            .into_response(serde_json::json!({ "report": report })));
+        }
+    };
+
+    if let Some(first) = report.failed.first() {
+        let failed: usize = report.failed.iter().map(|f| f.instance_ids.len()).sum();
+        let message = format!("Failed to {} {} of {} instances: {}", operation.as_str(), failed, instance_ids.len(), first.error);
+        let error = match &first.api_error {
+            Some(api_error) => api_error.with_message(message),
+            None => ApiError::Internal(message),
+        };
+        return Ok(error.into_response(serde_json::json!({ "report": report, "instances": updated })));
+    }
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": format!("Requested {} of {} instances", operation.as_str(), report.transitions.len()),
+        "data": { "report": report, "instances": updated }
+    }))
+}
+
+/// Change an instance's type. Running instances are only stopped with
+/// `allow_stop`, and started again afterwards unless `restart` is false.
+/// `account_id` is needed for instances that haven't been synced yet.
+#[tauri::command]
+async fn resize_ec2_instance(
+    instance_id: String,
+    new_type: String,
+    restart: Option<bool>,
+    allow_stop: Option<bool>,
+    account_id: Option<i64>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // Get account from instance
+    let pool = state.db.lock().await.clone();
+    let instance = match database::get_instance_by_aws_id(&pool, &instance_id).await {
+        Ok(instance) => instance,
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to find instance: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let account_id = match (account_id, &instance) {
+        (Some(account_id), _) => account_id,
+        (None, Some(instance)) => match instance_account_id(&pool, instance).await {
+            Ok(account_id) => account_id,
+            Err(response) => return Ok(response),
+        },
+        (None, None) => return Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null)),
+    };
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+    // Stop, change the type and (by default) start the instance again
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    let resized = match ec2_service.resize_instance(&instance_id, &new_type, allow_stop.unwrap_or(false), restart.unwrap_or(true)).await {
+        Ok(aws::InstanceResize::Resized(resized)) => resized,
+        Ok(aws::InstanceResize::StopRequired { state }) => {
+            return Ok(ApiError::InvalidInput(format!("Instance {} is {} and must be stopped to change its type. Pass allow_stop to stop it.", instance_id, state))
+                .into_response(serde_json::json!({ "status": state, "error_type": "stop_required" })));
+        }
+        Ok(aws::InstanceResize::TypeUnavailable { instance_type, availability_zone }) => {
+            return Ok(ApiError::InvalidInput(format!("Instance type {} is not offered in {}", instance_type, availability_zone))
+                .into_response(serde_json::json!({
+                    "instance_type": instance_type,
+                    "availability_zone": availability_zone,
+                    "error_type": "instance_type_unavailable"
+                })));
+        }
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to resize instance", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    if let Some(instance) = &instance {
+        let update = database::UpdateInstanceRequest {
+            name: None,
+            instance_type: Some(new_type.clone()),
+            storage_gb: None,
+            security_config: None,
+            ssh_key: None,
+            tags: None,
+            ssh_user: None,
+        };
+        if let Err(e) = database::update_instance(&pool, instance.id, update).await {
+            tracing::warn!("Resized instance {} but failed to record the new type: {}", instance_id, e);
+        }
+        if let Err(e) = database::set_instance_state(&pool, instance.id, &resized.state, resized.public_ip.as_deref()).await {
+            tracing::warn!("Resized instance {} but failed to record its state: {}", instance_id, e);
+        }
+    }
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": format!("EC2 instance resized to {}", new_type),
+        "data": { "instance_type": new_type, "status": resized.state }
+    }))
+}
+
+#[tauri::command]
+async fn get_ec2_instance_details(
+    instance_id: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // Get account from instance
+    let pool = state.db.lock().await.clone();
+    let instance = match database::get_instance_by_aws_id(&pool, &instance_id).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => {
+            return Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::json!({})));
+        }
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to find instance: {}", e)).into_response(serde_json::json!({})));
+        }
+    };
+
+    let account_id = match instance_account_id(&pool, &instance).await {
+        Ok(account_id) => account_id,
+        Err(response) => return Ok(response),
+    };
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
+        }
+    };
+
+    // Get instance details
+    match aws_client.get_instance_details(&instance_id).await {
+        Ok(details) => Ok(serde_json::json!({
+            "success": true,
+            "message": "EC2 instance details retrieved successfully",
+            "data": details
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to get instance details", &e).into_response(serde_json::json!({})))
+    }
+}
+
+#[tauri::command]
+async fn get_ec2_instance_ssh_config(
+    instance_id: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // Get account from instance
+    let pool = state.db.lock().await.clone();
+    let instance = match database::get_instance_by_aws_id(&pool, &instance_id).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => {
+            return Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::json!({ "config": "" })));
+        }
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to find instance: {}", e)).into_response(serde_json::json!({ "config": "" })));
+        }
+    };
+
+    let account_id = match instance_account_id(&pool, &instance).await {
+        Ok(account_id) => account_id,
+        Err(response) => return Ok(response),
+    };
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({ "config": "" })));
+        }
+    };
+
+    let key_paths = match database::get_ssh_key_paths(&pool).await {
+        Ok(paths) => paths,
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to load SSH key paths: {}", e)).into_response(serde_json::json!({ "config": "" })));
+        }
+    };
+
+    // Get SSH config
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    match ec2_service.get_ssh_config(&instance_id, instance.ssh_user.as_deref(), instance.elastic_ip.as_deref(), &key_paths).await {
+        Ok(config) => Ok(serde_json::json!({
+            "success": true,
+            "message": "SSH config generated successfully",
+            "data": { "config": config }
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to generate SSH config", &e).into_response(serde_json::json!({ "config": "" })))
+    }
+}
+
+/// Monthly cost of an instance including its share of the VPC's NAT gateways
+/// and load balancers, split evenly across the VPC's instances
+#[tauri::command]
+async fn get_instance_total_cost(
+    instance_id: String,
+    month: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let instance = match database::get_instance_by_aws_id(&pool, &instance_id).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => {
+            return Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::json!({})));
+        }
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to find instance: {}", e)).into_response(serde_json::json!({})));
+        }
+    };
+
+    let account_id = match instance_account_id(&pool, &instance).await {
+        Ok(account_id) => account_id,
+        Err(response) => return Ok(response),
+    };
+
+    let aws_client = match state.aws_clients.get_client_in_region(&pool, account_id, &instance.region).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
+        }
+    };
+
+    match aws::attribution::instance_total_cost(&aws_client, &instance_id, &month).await {
+        Ok(breakdown) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Total cost for {} in {}: ${:.2}", instance_id, month, breakdown.total_usd),
+            "data": breakdown
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to attribute instance cost", &e).into_response(serde_json::json!({})))
+    }
+}
+
+/// CPU, network and status check time series for an instance over the last
+/// hour, six hours, day or week. `account_id` is needed for instances that
+/// haven't been synced yet. Results are cached for a minute.
+#[tauri::command]
+async fn get_instance_metrics(
+    instance_id: String,
+    period: Option<String>,
+    account_id: Option<i64>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let window = match period.as_deref().map(aws::cloudwatch::MetricWindow::parse).transpose() {
+        Ok(window) => window.unwrap_or_default(),
+        Err(e) => {
+            return Ok(ApiError::InvalidInput(e.to_string()).into_response(serde_json::json!({})));
+        }
+    };
+
+    let pool = state.db.lock().await.clone();
+    let instance = match database::get_instance_by_aws_id(&pool, &instance_id).await {
+        Ok(instance) => instance,
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to find instance: {}", e)).into_response(serde_json::json!({})));
+        }
+    };
+
+    let account_id = match (account_id, &instance) {
+        (Some(account_id), _) => account_id,
+        (None, Some(instance)) => match instance_account_id(&pool, instance).await {
+            Ok(account_id) => account_id,
+            Err(response) => return Ok(response),
+        },
+        (None, None) => {
+            return Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::json!({})));
+        }
+    };
+
+    let client = match &instance {
+        Some(instance) => state.aws_clients.get_client_in_region(&pool, account_id, &instance.region).await,
+        None => state.aws_clients.get_client(&pool, account_id).await,
+    };
+    let aws_client = match client {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
+        }
+    };
+
+    match aws::cloudwatch::cached_instance_metrics(&aws_client, &instance_id, window).await {
+        Ok(metrics) => {
+            let recorded_state = instance.as_ref().map_or("running", |instance| instance.status.as_str());
+            let status = aws::adapters::map_instance_health_status(recorded_state, metrics.status_checks_failing);
+            Ok(serde_json::json!({
+                "success": true,
+                "message": format!("Metrics for {} retrieved", instance_id),
+                "data": { "status": status, "metrics": metrics }
+            }))
+        }
+        Err(e) => Ok(ApiError::aws("Failed to get instance metrics", &e).into_response(serde_json::json!({})))
+    }
+}
+
+/// Provisioned EBS storage of a synced instance next to how much of its root
+/// filesystem is used, per the CloudWatch agent. `source` is "unavailable"
+/// when the agent isn't publishing disk metrics.
+#[tauri::command]
+async fn get_storage_utilization(
+    instance_id: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let instance = match database::get_instance_by_aws_id(&pool, &instance_id).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => return Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to find instance: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+    let account_id = match instance_account_id(&pool, &instance).await {
+        Ok(account_id) => account_id,
+        Err(response) => return Ok(response),
+    };
+
+    let aws_client = match state.aws_clients.get_client_in_region(&pool, account_id, &instance.region).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    // Attached EBS volumes, or the size recorded at the last sync if they can't be described
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client.clone());
+    let provisioned_gb = match ec2_service.get_attached_volume_totals(&[instance_id.clone()]).await {
+        Ok(totals) => totals.get(&instance_id).copied().unwrap_or(instance.storage_gb),
+        Err(e) => {
+            tracing::warn!("Failed to describe volumes of {}, using recorded storage: {}", instance_id, e);
+            instance.storage_gb
+        }
+    };
+
+    match aws::cloudwatch::fetch_storage_utilization(&aws_client, &instance_id, provisioned_gb).await {
+        Ok(utilization) => Ok(serde_json::json!({
+            "success": true,
+            "message": match utilization.used_percent {
+                Some(used) => format!("{} uses {:.1}% of its root filesystem ({} GB provisioned)", instance_id, used, provisioned_gb),
+                None => format!("No CloudWatch agent disk metrics for {}; only provisioned storage is known", instance_id),
+            },
+            "data": utilization
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to get storage utilization", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Stop running instances in the account's region whose average CPU over the
+/// last `window_hours` (24 by default) was below `cpu_threshold` percent (5 by
+/// default). Instances tagged `always-on` are left alone. Only lists what
+/// would be stopped unless `dry_run` is explicitly false.
+#[tauri::command]
+async fn stop_idle_instances(
+    account_id: i64,
+    cpu_threshold: Option<f64>,
+    window_hours: Option<i64>,
+    dry_run: Option<bool>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let cpu_threshold = cpu_threshold.unwrap_or(aws::idle::DEFAULT_CPU_THRESHOLD);
+    let window_hours = window_hours.unwrap_or(aws::idle::DEFAULT_WINDOW_HOURS);
+    let dry_run = dry_run.unwrap_or(true);
+
+    if let Err(e) = aws::idle::validate_idle_criteria(cpu_threshold, window_hours) {
+        return Ok(ApiError::InvalidInput(e.to_string()).into_response(serde_json::Value::Null));
+    }
+
+    let pool = state.db.lock().await.clone();
+
+    // Read-only accounts never reach AWS for mutating operations
+    if !dry_run {
+        if let Some(response) = read_only_guard(&pool, account_id).await {
+            return Ok(response);
+        }
+    }
+
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let report = match aws::idle::find_idle_instances(&aws_client, cpu_threshold, window_hours).await {
+        Ok(report) => report,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to find idle instances", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    if dry_run {
+        return Ok(serde_json::json!({
+            "success": true,
+            "message": format!(
+                "Dry run: {} idle instances would be stopped, saving about ${:.2}/month",
+                report.idle.len(), report.estimated_monthly_savings
+            ),
+            "data": { "dry_run": true, "cpu_threshold": cpu_threshold, "window_hours": window_hours, "report": report, "stopped": [], "failed": [] }
+        }));
+    }
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    let mut stopped = Vec::new();
+    let mut failed = Vec::new();
+    for instance in &report.idle {
+        match ec2_service.stop_instance(&instance.instance_id).await {
+            Ok(()) => stopped.push(instance.instance_id.clone()),
+            Err(e) => failed.push(serde_json::json!({ "instance_id": instance.instance_id, "error": e.to_string() })),
+        }
+    }
+
+    let savings: f64 = report.idle.iter()
+        .filter(|instance| stopped.contains(&instance.instance_id))
+        .map(|instance| instance.monthly_savings)
+        .sum();
+
+    Ok(serde_json::json!({
+        "success": failed.is_empty(),
+        "message": if failed.is_empty() {
+            format!("Stopped {} idle instances, saving about ${:.2}/month", stopped.len(), savings)
+        } else {
+            format!("Stopped {} of {} idle instances; {} failed", stopped.len(), report.idle.len(), failed.len())
+        },
+        "data": {
+            "dry_run": false,
+            "cpu_threshold": cpu_threshold,
+            "window_hours": window_hours,
+            "report": report,
+            "stopped": stopped,
+            "failed": failed,
+            "estimated_monthly_savings": savings
+        }
+    }))
+}
+
+/// Active reserved instances in the account's region
+#[tauri::command]
+async fn collect_reserved_instances(account_id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    match aws_client.collect_reserved_instances().await {
+        Ok(reservations) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Found {} active reserved instance purchases", reservations.len()),
+            "data": reservations
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to collect reserved instances", &e).into_response(serde_json::json!([]))),
+    }
+}
+
+/// Reserved against running instance counts per instance type in the
+/// account's region, showing unused reservations and uncovered instances
+#[tauri::command]
+async fn get_reservation_utilization(account_id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let reservations = match aws_client.collect_reserved_instances().await {
+        Ok(reservations) => reservations,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to collect reserved instances", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let filters = aws::InstanceFilters {
+        states: vec!["running".to_string()],
+        ..Default::default()
+    };
+    let mut instances = match aws_client.collect_matching_instances(&filters).await {
+        Ok(instances) => instances,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to collect EC2 instances", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    aws::reservations::annotate_coverage(&mut instances, &reservations);
+    let by_type = aws::reservations::reservation_utilization(&instances, &reservations);
+    let reserved: i32 = by_type.iter().map(|u| u.reserved).sum();
+    let covered: i32 = by_type.iter().map(|u| u.covered).sum();
+    let unused: i32 = by_type.iter().map(|u| u.unused_reservations).sum();
+    let uncovered: i32 = by_type.iter().map(|u| u.uncovered_instances).sum();
+    let coverage: serde_json::Map<String, serde_json::Value> = instances.iter()
+        .map(|instance| (instance.instance_id.clone(), serde_json::json!(instance.coverage)))
+        .collect();
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": format!(
+            "{} of {} reserved instances in use; {} running instances billed on-demand",
+            covered, reserved, uncovered
+        ),
+        "data": {
+            "region": aws_client.config.region,
+            "by_type": by_type,
+            "reserved": reserved,
+            "covered": covered,
+            "unused_reservations": unused,
+            "uncovered_instances": uncovered,
+            "coverage": coverage
+        }
+    }))
+}
+
+/// Rightsizing recommendations from AWS Compute Optimizer for the account's
+/// instances in its region, biggest projected savings first. Accounts that
+/// haven't opted in get `error.code` "opt_in_required".
+#[tauri::command]
+async fn get_compute_optimizer_recommendations(account_id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    match aws::compute_optimizer::fetch_instance_recommendations(&aws_client).await {
+        Ok(recommendations) => {
+            let savings: f64 = recommendations.iter().map(|r| r.projected_savings).sum();
+            Ok(serde_json::json!({
+                "success": true,
+                "message": format!(
+                    "{} recommendations, saving up to ${:.2}/month",
+                    recommendations.len(), savings
+                ),
+                "data": {
+                    "region": aws_client.config.region,
+                    "recommendations": recommendations,
+                    "projected_monthly_savings": savings
+                }
+            }))
+        }
+        Err(e @ aws::AwsError::OptInRequired(_)) => {
+            Ok(ApiError::aws_with_message(&e, e.to_string()).into_response(serde_json::json!({ "error_type": "opt_in_required" })))
+        }
+        Err(e) => Ok(ApiError::aws("Failed to get Compute Optimizer recommendations", &e).into_response(serde_json::Value::Null)),
+    }
+}
+
+/// Status check time series over the last hour for every running instance in
+/// the account's region, cached for a minute
+#[tauri::command]
+async fn get_instances_health_summary(
+    account_id: i64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
+        }
+    };
+
+    match aws::cloudwatch::instances_health_summary(&aws_client).await {
+        Ok(summary) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!(
+                "{} of {} running instances failing status checks",
+                summary.failing, summary.instances.len()
+            ),
+            "data": summary
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to get instance health", &e).into_response(serde_json::json!({})))
+    }
+}
+
+/// Client for an instance of `account_id`, in the instance's region when it has
+/// been synced
+async fn instance_client(state: &AppState, pool: &DbPool, account_id: i64, instance_id: &str) -> Result<AwsClient, ApiError> {
+    let region = match database::get_instance_by_aws_id(pool, instance_id).await {
+        Ok(instance) => instance.map(|instance| instance.region),
+        Err(e) => return Err(ApiError::database(format!("Failed to find instance: {}", e))),
+    };
+
+    let client = match region {
+        Some(region) => state.aws_clients.get_client_in_region(pool, account_id, &region).await,
+        None => state.aws_clients.get_client(pool, account_id).await,
+    };
+    client.map_err(|e| ApiError::aws("Failed to create AWS client", &e))
+}
+
+/// Response for a failed console call, with an error_type for the states the
+/// frontend explains instead of showing the raw error
+fn console_error_response(action: &str, error: aws::AwsError) -> serde_json::Value {
+    let error_type = match &error {
+        aws::AwsError::InstanceStateError(_) => "invalid_instance_state",
+        aws::AwsError::RateLimitError(_) => "rate_limited",
+        _ => "aws_error",
+    };
+    let message = match &error {
+        aws::AwsError::InstanceStateError(_) => format!("The instance must be running to {}", action),
+        _ => format!("Failed to {}: {}", action, error),
+    };
+
+    ApiError::aws_with_message(&error, message).into_response(serde_json::json!({ "error_type": error_type, "detail": error.to_string() }))
+}
+
+/// The last `max_kb` (64 by default) of an instance's boot log, for
+/// troubleshooting instances that can't be reached over SSH. Limited to one
+/// call per instance every 30 seconds.
+#[tauri::command]
+async fn get_instance_console_output(
+    account_id: i64,
+    instance_id: String,
+    max_kb: Option<usize>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let aws_client = match instance_client(&state, &pool, account_id, &instance_id).await {
+        Ok(client) => client,
+        Err(error) => return Ok(error.into_response(serde_json::Value::Null)),
+    };
+
+    let max_bytes = max_kb.map_or(aws::console::DEFAULT_CONSOLE_OUTPUT_BYTES, |kb| kb.max(1) * 1024);
+    match aws::console::get_console_output(&aws_client, &instance_id, max_bytes).await {
+        Ok(output) => Ok(serde_json::json!({
+            "success": true,
+            "message": if output.output.is_empty() {
+                format!("No console output captured yet for {}", instance_id)
+            } else {
+                format!("Console output for {} retrieved", instance_id)
+            },
+            "data": output
+        })),
+        Err(e) => Ok(console_error_response("get console output", e)),
+    }
+}
+
+/// A screenshot of an instance's console as a data URI. Limited to one call
+/// per instance every 30 seconds.
+#[tauri::command]
+async fn get_instance_screenshot(
+    account_id: i64,
+    instance_id: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let aws_client = match instance_client(&state, &pool, account_id, &instance_id).await {
+        Ok(client) => client,
+        Err(error) => return Ok(error.into_response(serde_json::Value::Null)),
+    };
+
+    match aws::console::get_console_screenshot(&aws_client, &instance_id).await {
+        Ok(screenshot) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Screenshot of {} captured", instance_id),
+            "data": screenshot
+        })),
+        Err(e) => Ok(console_error_response("take a screenshot", e)),
+    }
+}
+
+/// Maintenance and retirement events AWS has scheduled for the account's
+/// instances; retirements are listed first
+#[tauri::command]
+async fn get_instance_scheduled_events(
+    account_id: i64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    match ec2_service.get_scheduled_events().await {
+        Ok(events) => {
+            let retirements = events.iter().filter(|e| e.is_retirement).count();
+            let message = if retirements > 0 {
+                format!("{} scheduled events, including {} instance retirements that need action", events.len(), retirements)
+            } else {
+                format!("{} scheduled events", events.len())
+            };
+            Ok(serde_json::json!({
+                "success": true,
+                "message": message,
+                "data": events
+            }))
+        }
+        Err(e) => Ok(ApiError::aws("Failed to get scheduled events", &e).into_response(serde_json::json!([])))
+    }
+}
+
+/// Current images for Amazon Linux 2023, Ubuntu 22.04/24.04 and Debian 12 in the
+/// account's region, newest first
+#[tauri::command]
+async fn list_amis(
+    account_id: i64,
+    options: Option<aws::AmiListOptions>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    match aws::ami::list_amis(&aws_client, &options.unwrap_or_default()).await {
+        Ok(amis) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Found {} images", amis.len()),
+            "data": amis
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to list images", &e).into_response(serde_json::json!([])))
+    }
+}
+
+/// The newest image for a catalog distro such as "ubuntu-22.04"; the
+/// architecture defaults to x86_64
+#[tauri::command]
+async fn resolve_latest_ami(
+    account_id: i64,
+    distro: String,
+    arch: Option<String>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let arch = arch.unwrap_or_else(|| "x86_64".to_string());
+    match aws::ami::resolve_latest_ami(&aws_client, &distro, &arch).await {
+        Ok(ami) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Latest {} image for {} is {}", distro, arch, ami.image_id),
+            "data": ami
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to resolve image", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn create_image_from_instance(
+    instance_id: i64,
+    name: String,
+    description: Option<String>,
+    no_reboot: Option<bool>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // AMI creation takes minutes; work on a pool handle instead of holding the
+    // shared lock while polling
+    let pool = state.db.lock().await.clone();
+
+    let instance = match database::get_instance(&pool, instance_id).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => {
+            return Ok(ApiError::NotFound("Instance not found".to_string()).into_response(serde_json::Value::Null));
+        }
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to find instance: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let account_id = match instance_account_id(&pool, &instance).await {
+        Ok(account_id) => account_id,
+        Err(response) => return Ok(response),
+    };
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    let cost_tracker = match cost_limit_guard(&state, &pool, account_id, app, "ec2", "CreateImage").await {
+        Ok(tracker) => tracker,
+        Err(response) => return Ok(response),
+    };
+
+    // Get the cached AWS client for the instance's region
+    let aws_client = match state.aws_clients.get_client_in_region(&pool, account_id, &instance.region).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    let ami_id = match ec2_service.create_image(&instance.name, &name, description.as_deref(), no_reboot.unwrap_or(false)).await {
+        Ok(ami_id) => {
+            if let Err(e) = cost_tracker.record_api_call("ec2", "CreateImage").await {
+                tracing::warn!("Failed to record image creation cost: {}", e);
+            }
+            ami_id
+        }
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create image", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let image = match database::create_image_from_instance(&pool, instance_id, name, description, ami_id.clone()).await {
+        Ok(image) => image,
+        Err(e) => {
+            return Ok(ApiError::from_db_with_message(&e, format!("Created AMI {} but failed to record it: {}", ami_id, e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let status = match ec2_service.wait_for_image(&ami_id).await {
+        Ok(status) => status,
+        Err(e) => {
+            return Ok(ApiError::aws_with_message(&e, format!("Created AMI {} but failed to check its status: {}", ami_id, e)).into_response(serde_json::json!(image)));
+        }
+    };
+
+    // Link the snapshots backing the AMI so deleting the image can remove them
+    if status == "available" {
+        match ec2_service.get_image_snapshot_ids(&ami_id).await {
+            Ok(snapshot_ids) => {
+                if let Err(e) = database::set_image_snapshot_ids(&pool, image.id, &snapshot_ids).await {
+                    tracing::warn!("Failed to record snapshots of AMI {}: {}", ami_id, e);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to look up snapshots of AMI {}: {}", ami_id, e),
+        }
+    }
+
+    match database::update_image_status(&pool, image.id, &status).await {
+        Ok(Some(image)) => Ok(serde_json::json!({
+            "success": status != "failed",
+            "message": format!("Image {} is {}", ami_id, status),
+            "data": image
+        })),
+        Ok(None) => Ok(ApiError::NotFound("Image was deleted while it was being created".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => Ok(ApiError::database(format!("Failed to update image status: {}", e)).into_response(serde_json::json!(image)))
+    }
+}
+
+#[tauri::command]
+async fn sync_instance_storage(
+    account_id: i64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    let instances = match database::get_account_instances(&pool, account_id).await {
+        Ok(instances) => instances,
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to get instances: {}", e)).into_response(serde_json::json!([])));
+        }
+    };
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    // Sum the attached EBS volumes of every synced instance
+    let instance_ids: Vec<String> = instances.into_iter().map(|i| i.name).collect();
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    let volume_totals = match ec2_service.get_attached_volume_totals(&instance_ids).await {
+        Ok(totals) => totals,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to get volume sizes", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    match database::update_instance_storage(&pool, account_id, &volume_totals).await {
+        Ok(changes) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Updated storage for {} instances", changes.len()),
+            "data": changes
+        })),
+        Err(e) => Ok(ApiError::database(format!("Failed to update instance storage: {}", e)).into_response(serde_json::json!([])))
+    }
+}
+
+#[tauri::command]
+async fn export_ansible_inventory(
+    account_id: i64,
+    group_by: String,
+    format: Option<String>,
+    include_stopped: Option<bool>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let group_by = match inventory::InventoryGroupBy::parse(&group_by) {
+        Ok(group_by) => group_by,
+        Err(e) => return Ok(ApiError::InvalidInput(e).into_response(serde_json::Value::Null)),
+    };
+    let format = match inventory::InventoryFormat::parse(format.as_deref().unwrap_or("ini")) {
+        Ok(format) => format,
+        Err(e) => return Ok(ApiError::InvalidInput(e).into_response(serde_json::Value::Null)),
+    };
+
+    let pool = state.db.lock().await.clone();
+
+    let account = match database::get_account(&pool, account_id).await {
+        Ok(Some(account)) => account,
+        Ok(None) => return Ok(ApiError::NotFound("Account not found".to_string()).into_response(serde_json::Value::Null)),
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to get account: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let instances = match database::get_account_instances(&pool, account_id).await {
+        Ok(instances) => instances,
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to get instances: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    // Stored rows carry no live state or addresses, so refresh them from EC2;
+    // fall back to the stored values if AWS can't be reached
+    let mut warnings = Vec::new();
+    let mut live = HashMap::new();
+    if account.platform.eq_ignore_ascii_case("aws") {
+        let scan_regions = database::get_enabled_account_regions(&pool, &account)
+            .await
+            .unwrap_or_else(|_| vec![account.region.clone().unwrap_or_else(|| regions::DEFAULT_REGION.to_string())]);
+        match state.aws_clients.get_client(&pool, account_id).await {
+            Ok(client) => {
+                let collection = client.collect_instances_in_regions(&scan_regions).await;
+                for result in collection.regions.iter().filter(|r| !r.success) {
+                    warnings.push(format!(
+                        "Failed to refresh instances in {}: {}",
+                        result.region,
+                        result.error.as_deref().unwrap_or("unknown error")
+                    ));
+                }
+                live.extend(collection.items.into_iter().map(|i| (i.instance_id.clone(), i)));
+            }
+            Err(e) => warnings.push(format!("Using stored instance data: {}", e)),
+        }
+    }
+
+    let mut project_names: HashMap<i64, String> = HashMap::new();
+    let mut hosts = Vec::new();
+    for instance in instances {
+        if !project_names.contains_key(&instance.project_id) {
+            let name = match database::get_project(&pool, instance.project_id).await {
+                Ok(Some(project)) => project.name,
+                _ => account.name.clone(),
+            };
+            project_names.insert(instance.project_id, name);
+        }
+
+        let mut host = inventory::InventoryHost {
+            project: project_names[&instance.project_id].clone(),
+            tags: stored_instance_tags(instance.tags.as_deref()),
+            name: instance.name,
+            platform: instance.platform,
+            region: instance.region,
+            state: instance.status,
+            public_ip: instance.public_ip,
+            private_ip: instance.private_ip,
+            ssh_user: instance.ssh_user,
+        };
+        if let Some(current) = live.remove(&host.name) {
+            host.state = current.state;
+            host.public_ip = current.public_ip;
+            host.private_ip = current.private_ip;
+            host.tags = current.tags;
+        }
+        hosts.push(host);
+    }
+
+    let inventory = inventory::build_inventory(&hosts, &group_by, include_stopped.unwrap_or(false));
+    let host_count: usize = inventory.values().map(|group| group.len()).sum();
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": format!("Exported {} hosts in {} groups", host_count, inventory.len()),
+        "data": {
+            "inventory": inventory::render_inventory(&inventory, format),
+            "format": match format {
+                inventory::InventoryFormat::Ini => "ini",
+                inventory::InventoryFormat::Yaml => "yaml",
+            },
+            "hosts": host_count,
+            "warnings": warnings
+        }
+    }))
+}
+
+/// Instance tags are stored as a JSON array of `key=value` strings
+fn stored_instance_tags(tags: Option<&str>) -> HashMap<String, String> {
+    tags.and_then(|json| serde_json::from_str::<Vec<String>>(json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|tag| tag.split_once('=').map(|(k, v)| (k.to_string(), v.to_string())))
+        .collect()
+}
+
+/// Report the account's EC2 instances whose `Name` tag does not fully match
+/// `regex`. With `auto_rename`, each one is retagged with a generated
+/// compliant name (refused on read-only accounts).
+#[tauri::command]
+async fn check_naming_policy(
+    account_id: i64,
+    regex: String,
+    auto_rename: Option<bool>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let policy = match naming::NamingPolicy::new(&regex) {
+        Ok(policy) => policy,
+        Err(e) => {
+            return Ok(ApiError::InvalidInput(e).into_response(serde_json::json!({ "error_type": "invalid_regex" })));
+        }
+    };
+
+    let pool = state.db.lock().await.clone();
+    let auto_rename = auto_rename.unwrap_or(false);
+    if auto_rename {
+        if let Some(refused) = read_only_guard(&pool, account_id).await {
+            return Ok(refused);
+        }
+    }
+
+    let instances = match database::get_account_instances(&pool, account_id).await {
+        Ok(instances) => instances,
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to get instances: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    // Sync stores the EC2 instance id as the name; the display name is the Name tag
+    let named: Vec<naming::NamedInstance> = instances
+        .iter()
+        .filter(|instance| instance.platform.eq_ignore_ascii_case("aws"))
+        .map(|instance| naming::NamedInstance {
+            id: instance.id,
+            instance_id: instance.name.clone(),
+            region: instance.region.clone(),
+            name: stored_instance_tags(instance.tags.as_deref()).remove(naming::NAME_TAG_KEY),
+        })
+        .collect();
+    let violations = naming::find_violations(&policy, &named);
+
+    if !auto_rename || violations.is_empty() {
+        return Ok(serde_json::json!({
+            "success": true,
+            "message": format!("{} of {} instances violate the naming policy", violations.len(), named.len()),
+            "data": {
+                "policy": policy.pattern(),
+                "checked": named.len(),
+                "violations": violations
+            }
+        }));
+    }
+
+    let stored_tags: HashMap<i64, Option<String>> = instances.into_iter().map(|i| (i.id, i.tags)).collect();
+    let pool = &pool;
+    let aws_clients = &state.aws_clients;
+    let report = naming::rename_violations(violations, |violation, name| {
+        let tags = naming::with_name_tag(stored_tags.get(&violation.id).cloned().flatten().as_deref(), &name);
+        async move {
+            let client = aws_clients
+                .get_client_in_region(pool, account_id, &violation.region)
+                .await
+                .map_err(|e| ApiError::aws("Failed to create AWS client", &e))?;
+            aws::ec2::Ec2Service::new(client)
+                .set_name_tag(&violation.instance_id, &name)
+                .await
+                .map_err(|e| ApiError::aws("Failed to set Name tag", &e))?;
+
+            let update = database::UpdateInstanceRequest {
+                name: None,
+                instance_type: None,
+                storage_gb: None,
+                security_config: None,
+                ssh_key: None,
+                tags: Some(tags),
+                ssh_user: None,
+            };
+            if let Err(e) = database::update_instance(pool, violation.id, update).await {
+                tracing::warn!("Renamed {} but failed to store its tags: {}", violation.instance_id, e);
+            }
+            Ok(())
+        }
+    })
+    .await;
+
+    let message = format!("Renamed {} instances, {} failed", report.renamed.len(), report.failed.len());
+    let data = serde_json::json!({
+        "policy": policy.pattern(),
+        "checked": named.len(),
+        "renamed": report.renamed,
+        "failed": report.failed
+    });
+    if let Some(first) = report.failed.first() {
+        let message = format!("{}: {}", message, first.error);
+        let error = match &first.api_error {
+            Some(api_error) => api_error.with_message(message),
+            None => ApiError::Internal(message),
+        };
+        return Ok(error.into_response(data));
+    }
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": message,
+        "data": data
+    }))
+}
+
+#[tauri::command]
+async fn list_unassociated_eips(
+    account_id: i64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    // List unassociated Elastic IPs
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    match ec2_service.list_unassociated_eips().await {
+        Ok(eips) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Found {} unassociated Elastic IPs", eips.len()),
+            "data": eips
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to list Elastic IPs", &e).into_response(serde_json::json!([])))
+    }
+}
+
+#[tauri::command]
+async fn release_unused_eips(
+    account_id: i64,
+    dry_run: Option<bool>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // Releasing is irreversible, so only an explicit `dry_run: false` releases anything
+    let dry_run = dry_run.unwrap_or(true);
+
+    let pool = state.db.lock().await.clone();
+
+    // Read-only accounts may preview but never release addresses
+    if !dry_run {
+        if let Some(response) = read_only_guard(&pool, account_id).await {
+            return Ok(response);
+        }
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    // Release (or report) unused Elastic IPs
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    match ec2_service.release_unused_eips(dry_run).await {
+        Ok(report) => {
+            let message = if report.dry_run {
+                format!("Dry run: {} Elastic IPs would be released", report.candidates.len())
+            } else {
+                format!("Released {} of {} unused Elastic IPs", report.released.len(), report.candidates.len())
+            };
+            Ok(serde_json::json!({
+                "success": report.failed.is_empty(),
+                "message": message,
+                "data": report
+            }))
+        }
+        Err(e) => Ok(ApiError::aws("Failed to release unused Elastic IPs", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Every Elastic IP in the account's region, with the instance each is attached to
+#[tauri::command]
+async fn collect_elastic_ips(
+    account_id: i64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    match ec2_service.collect_elastic_ips().await {
+        Ok(eips) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Found {} Elastic IPs", eips.len()),
+            "data": eips
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to collect Elastic IPs", &e).into_response(serde_json::json!([])))
+    }
+}
+
+#[tauri::command]
+async fn allocate_elastic_ip(
+    account_id: i64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    match ec2_service.allocate_elastic_ip().await {
+        Ok(eip) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Allocated Elastic IP {}", eip.public_ip),
+            "data": eip
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to allocate Elastic IP", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Attach an Elastic IP to an instance and record it on the local instance
+/// row, moving it off any instance it was attached to before
+#[tauri::command]
+async fn associate_elastic_ip(
+    account_id: i64,
+    allocation_id: String,
+    instance_id: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+    let eip = match ec2_service.collect_elastic_ips().await {
+        Ok(eips) => match eips.into_iter().find(|eip| eip.allocation_id == allocation_id) {
+            Some(eip) => eip,
+            None => {
+                return Ok(ApiError::NotFound(format!("Elastic IP {} not found", allocation_id)).into_response(serde_json::Value::Null));
+            }
+        },
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to look up Elastic IP", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let association_id = match ec2_service.associate_elastic_ip(&allocation_id, &instance_id).await {
+        Ok(association_id) => association_id,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to associate Elastic IP", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    if let Some(previous) = eip.instance_id.as_deref().filter(|previous| *previous != instance_id) {
+        if let Err(e) = database::set_instance_elastic_ip(&pool, account_id, previous, None).await {
+            tracing::warn!("Failed to clear Elastic IP from instance {}: {}", previous, e);
+        }
+    }
+    if let Err(e) = database::set_instance_elastic_ip(&pool, account_id, &instance_id, Some((&allocation_id, &eip.public_ip))).await {
+        tracing::warn!("Failed to record Elastic IP on instance {}: {}", instance_id, e);
+    }
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": format!("Associated {} with instance {}", eip.public_ip, instance_id),
+        "data": {
+            "allocationId": allocation_id,
+            "associationId": association_id,
+            "publicIp": eip.public_ip,
+            "instanceId": instance_id,
+            "previousInstanceId": eip.instance_id
+        }
+    }))
+}
+
+#[tauri::command]
+async fn release_elastic_ip(
+    account_id: i64,
+    allocation_id: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let ec2_service = aws::ec2::Ec2Service::new(aws_client);
+
+    // Releasing an attached address would silently take the instance's IP away
+    match ec2_service.collect_elastic_ips().await {
+        Ok(eips) => {
+            if let Some(instance_id) = eips.iter().find(|eip| eip.allocation_id == allocation_id).and_then(|eip| eip.instance_id.as_ref()) {
+                return Ok(ApiError::InvalidInput(format!("Elastic IP {} is associated with instance {}; disassociate it first", allocation_id, instance_id))
+                    .into_response(serde_json::Value::Null));
+            }
+        }
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to look up Elastic IP", &e).into_response(serde_json::Value::Null));
+        }
+    }
+
+    match ec2_service.release_eip(&allocation_id).await {
+        Ok(()) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Released Elastic IP {}", allocation_id),
+            "data": { "allocationId": allocation_id }
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to release Elastic IP", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+// ============================================================================
+// EBS VOLUME OPERATIONS
+// ============================================================================
+
+/// EBS volumes in the account's region with their attachments
+#[tauri::command]
+async fn collect_volumes(
+    account_id: i64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    let ebs_service = aws::ebs::EbsService::new(aws_client);
+    match ebs_service.collect_volumes().await {
+        Ok(volumes) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Found {} EBS volumes", volumes.len()),
+            "data": volumes
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to collect volumes", &e).into_response(serde_json::json!([])))
+    }
+}
+
+#[tauri::command]
+async fn create_volume(
+    account_id: i64,
+    request: aws::CreateVolumeRequest,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let ebs_service = aws::ebs::EbsService::new(aws_client);
+    match ebs_service.create_volume(&request).await {
+        Ok(volume) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Created volume {}", volume.volume_id),
+            "data": volume
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to create volume", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Attach a volume to an instance (by AWS instance id) as `device`, e.g. /dev/sdf
+#[tauri::command]
+async fn attach_volume(
+    account_id: i64,
+    volume_id: String,
+    instance_id: String,
+    device: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
     }
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": null
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    // Create instance
-    match aws_client.create_instance(instance_type, image_id, instance_name).await {
-        Ok(instance) => Ok(serde_json::json!({
+    let ebs_service = aws::ebs::EbsService::new(aws_client);
+    match ebs_service.attach_volume(&volume_id, &instance_id, &device).await {
+        Ok(attachment) => Ok(serde_json::json!({
             "success": true,
-            "message": "EC2 instance created successfully",
-            "data": instance
+            "message": format!("Attaching volume {} to {} as {}", volume_id, attachment.instance_id, attachment.device),
+            "data": attachment
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to create instance: {}", e),
-            "data": null
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to attach volume", &e).into_response(serde_json::Value::Null))
     }
 }
 
 #[tauri::command]
-async fn delete_ec2_instance(
-    instance_id: String,
+async fn detach_volume(
+    account_id: i64,
+    volume_id: String,
+    force: Option<bool>,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    // Extract account_id from instance data
-    let instance = database::get_instance_by_aws_id(&*state.db.lock().await, &instance_id).await
-        .map_err(|e| format!("Failed to find instance: {}", e))?
-        .ok_or("Instance not found")?;
-
-    let account_id = if instance.project_id > 0 { instance.project_id } else {
-        return Ok(serde_json::json!({ "success": false, "message": "Instance not associated with an account" }));
-    };
+    let pool = state.db.lock().await.clone();
 
-    let db_guard = state.db.lock().await;
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    let account = match database::get_account(&*db_guard, account_id).await {
-        Ok(Some(account)) => account,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Account not found"
-            }));
-        }
+    let ebs_service = aws::ebs::EbsService::new(aws_client);
+    match ebs_service.detach_volume(&volume_id, force.unwrap_or(false)).await {
+        Ok(attachment) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Detaching volume {}", volume_id),
+            "data": attachment
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to detach volume", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Grow a volume and wait until the new size is usable; shrinking is refused
+#[tauri::command]
+async fn modify_volume_size(
+    account_id: i64,
+    volume_id: String,
+    size_gb: i32,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get account: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials"
-        }));
+    let ebs_service = aws::ebs::EbsService::new(aws_client);
+    match ebs_service.modify_volume_size(&volume_id, size_gb).await {
+        Ok(volume) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Volume {} is now {} GB; grow the filesystem to use the space", volume.volume_id, volume.size_gb),
+            "data": volume
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to resize volume", &e).into_response(serde_json::Value::Null))
     }
+}
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+/// Snapshots owned by the account; pending ones carry their progress percent
+#[tauri::command]
+async fn collect_snapshots(
+    account_id: i64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
         }
     };
 
-    // Delete instance
-    match aws_client.delete_instance(&instance_id).await {
-        Ok(_) => Ok(serde_json::json!({
+    let ebs_service = aws::ebs::EbsService::new(aws_client);
+    match ebs_service.collect_snapshots().await {
+        Ok(snapshots) => Ok(serde_json::json!({
             "success": true,
-            "message": "EC2 instance deleted successfully"
+            "message": format!("Found {} snapshots", snapshots.len()),
+            "data": snapshots
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to delete instance: {}", e)
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to collect snapshots", &e).into_response(serde_json::json!([])))
     }
 }
 
 #[tauri::command]
-async fn start_ec2_instance(
-    instance_id: String,
+async fn create_snapshot(
+    account_id: i64,
+    volume_id: String,
+    description: Option<String>,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    // Get account from instance
-    let db_guard = state.db.lock().await;
-    let instance = match database::get_instance_by_aws_id(&*db_guard, &instance_id).await {
-        Ok(Some(instance)) => instance,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Instance not found"
-            }));
-        }
+    let pool = state.db.lock().await.clone();
+
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to find instance: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    let account_id = if instance.project_id > 0 { instance.project_id } else { return Ok(serde_json::json!({ "success": false, "message": "Instance not associated with an account" })); };
+    let ebs_service = aws::ebs::EbsService::new(aws_client);
+    match ebs_service.create_snapshot(&volume_id, description.as_deref()).await {
+        Ok(snapshot) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Started snapshot {} of volume {}", snapshot.snapshot_id, volume_id),
+            "data": snapshot
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to create snapshot", &e).into_response(serde_json::Value::Null))
+    }
+}
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
+#[tauri::command]
+async fn delete_snapshot(
+    account_id: i64,
+    snapshot_id: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    let account = match database::get_account(&*db_guard, account_id).await {
-        Ok(Some(account)) => account,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Account not found"
-            }));
-        }
+    let ebs_service = aws::ebs::EbsService::new(aws_client);
+    match ebs_service.delete_snapshot(&snapshot_id).await {
+        Ok(()) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Deleted snapshot {}", snapshot_id)
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to delete snapshot", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+// ============================================================================
+// S3 OPERATIONS
+// ============================================================================
+
+#[tauri::command]
+async fn collect_s3_buckets(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Get first available account for S3 access
+    let accounts = match database::get_accounts(&pool).await {
+        Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get account: {}", e)
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!([])));
         }
     };
 
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials"
-        }));
+    if accounts.is_empty() {
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::json!([])));
     }
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    let account = &accounts[0];
+    let account_id = account.id;
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
         }
     };
 
-    // Start instance
-    match aws_client.start_instance(&instance_id).await {
-        Ok(_) => Ok(serde_json::json!({
+    if let Some(response) = permission_guard(&aws_client, &["s3:ListAllMyBuckets"], serde_json::json!([])).await {
+        return Ok(response);
+    }
+
+    // Collect buckets
+    match aws_client.collect_buckets().await {
+        Ok(buckets) => Ok(serde_json::json!({
             "success": true,
-            "message": "EC2 instance started successfully"
+            "message": format!("Collected {} S3 buckets", buckets.len()),
+            "data": buckets
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to start instance: {}", e)
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to collect buckets", &e).into_response(serde_json::json!([])))
     }
 }
 
 #[tauri::command]
-async fn stop_ec2_instance(
-    instance_id: String,
+async fn create_s3_bucket(
+    bucket_name: String,
+    region: String,
+    app: tauri::AppHandle,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    // Get account from instance
-    let db_guard = state.db.lock().await;
-    let instance = match database::get_instance_by_aws_id(&*db_guard, &instance_id).await {
-        Ok(Some(instance)) => instance,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Instance not found"
-            }));
-        }
+    let pool = state.db.lock().await.clone();
+
+    // Get first available account for S3 access
+    let accounts = match database::get_accounts(&pool).await {
+        Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to find instance: {}", e)
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::Value::Null));
         }
     };
 
-    let account_id = if instance.project_id > 0 { instance.project_id } else { return Ok(serde_json::json!({ "success": false, "message": "Instance not associated with an account" })); };
+    if accounts.is_empty() {
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::Value::Null));
+    }
+
+    let account = &accounts[0];
+    let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    let cost_tracker = match cost_limit_guard(&state, &pool, account_id, app, "s3", "CreateBucket").await {
+        Ok(tracker) => tracker,
+        Err(response) => return Ok(response),
+    };
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    let account = match database::get_account(&*db_guard, account_id).await {
-        Ok(Some(account)) => account,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Account not found"
-            }));
+    // Create bucket
+    match aws_client.create_bucket(&bucket_name, &region).await {
+        Ok(bucket) => {
+            if let Err(e) = cost_tracker.record_api_call("s3", "CreateBucket").await {
+                tracing::warn!("Failed to record bucket creation cost: {}", e);
+            }
+            Ok(serde_json::json!({
+                "success": true,
+                "message": "S3 bucket created successfully",
+                "data": bucket
+            }))
         }
+        Err(e) => Ok(ApiError::aws("Failed to create bucket", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+/// Delete an empty bucket. With `dry_run` set, only checks the bucket exists
+/// and is empty.
+#[tauri::command]
+async fn delete_s3_bucket(
+    bucket_name: String,
+    dry_run: Option<bool>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let dry_run = dry_run.unwrap_or(false);
+
+    let pool = state.db.lock().await.clone();
+
+    // Get first available account for S3 access
+    let accounts = match database::get_accounts(&pool).await {
+        Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get account: {}", e)
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::Value::Null));
         }
     };
 
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
+    if accounts.is_empty() {
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::Value::Null));
+    }
 
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials"
-        }));
+    let account = &accounts[0];
+    let account_id = account.id;
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
     }
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    // Stop instance
-    match aws_client.stop_instance(&instance_id).await {
+    // S3 has no dry-run flag, so check what DeleteBucket would check
+    let s3_service = aws::s3::S3Service::new(aws_client);
+    if dry_run {
+        return match s3_service.check_bucket_deletable(&bucket_name).await {
+            Ok(()) => Ok(serde_json::json!({
+                "success": true,
+                "message": format!("Dry run: S3 bucket {} would be deleted", bucket_name),
+                "data": { "dryRun": true, "bucketName": bucket_name }
+            })),
+            Err(e) => Ok(ApiError::aws("Failed to delete bucket", &e).into_response(serde_json::Value::Null)),
+        };
+    }
+
+    // Delete bucket
+    match s3_service.delete_bucket(&bucket_name).await {
         Ok(_) => Ok(serde_json::json!({
             "success": true,
-            "message": "EC2 instance stopped successfully"
+            "message": "S3 bucket deleted successfully"
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to stop instance: {}", e)
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to delete bucket", &e).into_response(serde_json::Value::Null))
     }
 }
 
+/// Delete the account's empty buckets, skipping any tagged as protected.
+/// Defaults to a dry run that only lists what would be deleted.
 #[tauri::command]
-async fn restart_ec2_instance(
-    instance_id: String,
+async fn delete_empty_buckets(
+    account_id: i64,
+    dry_run: Option<bool>,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    // Get account from instance
-    let db_guard = state.db.lock().await;
-    let instance = match database::get_instance_by_aws_id(&*db_guard, &instance_id).await {
-        Ok(Some(instance)) => instance,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Instance not found"
-            }));
-        }
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to find instance: {}", e)
-            }));
-        }
-    };
+    // Deleting is irreversible, so only an explicit `dry_run: false` deletes anything
+    let dry_run = dry_run.unwrap_or(true);
 
-    let account_id = if instance.project_id > 0 { instance.project_id } else { return Ok(serde_json::json!({ "success": false, "message": "Instance not associated with an account" })); };
+    let pool = state.db.lock().await.clone();
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
+    // Read-only accounts may preview but never delete buckets
+    if !dry_run {
+        if let Some(response) = read_only_guard(&pool, account_id).await {
+            return Ok(response);
+        }
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    let account = match database::get_account(&*db_guard, account_id).await {
-        Ok(Some(account)) => account,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Account not found"
-            }));
+    let s3_service = aws::s3::S3Service::new(aws_client);
+    match s3_service.delete_empty_buckets(dry_run).await {
+        Ok(report) => {
+            let message = if report.dry_run {
+                format!("Dry run: {} empty buckets would be deleted", report.candidates.len())
+            } else {
+                format!("Deleted {} of {} empty buckets", report.deleted.len(), report.candidates.len())
+            };
+            Ok(serde_json::json!({
+                "success": report.failed.is_empty(),
+                "message": message,
+                "data": report
+            }))
         }
+        Err(e) => Ok(ApiError::aws("Failed to delete empty buckets", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+#[tauri::command]
+async fn get_s3_bucket_details(
+    bucket_name: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Get first available account for S3 access
+    let accounts = match database::get_accounts(&pool).await {
+        Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get account: {}", e)
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!({})));
         }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
+    };
 
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials"
-        }));
+    if accounts.is_empty() {
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::json!({})));
     }
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    let account = &accounts[0];
+    let account_id = account.id;
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
         }
     };
 
-    // Restart instance
-    match aws_client.restart_instance(&instance_id).await {
-        Ok(_) => Ok(serde_json::json!({
+    // Get bucket details
+    match aws_client.get_bucket_details(&bucket_name).await {
+        Ok(details) => Ok(serde_json::json!({
             "success": true,
-            "message": "EC2 instance restarted successfully"
+            "message": "S3 bucket details retrieved successfully",
+            "data": details
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to restart instance: {}", e)
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to get bucket details", &e).into_response(serde_json::json!({})))
     }
 }
 
+/// Lifecycle rules on a bucket; a bucket without a lifecycle configuration
+/// returns an empty list
 #[tauri::command]
-async fn get_ec2_instance_details(
-    instance_id: String,
+async fn get_s3_bucket_lifecycle(
+    account_id: i64,
+    bucket_name: String,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    // Get account from instance
-    let db_guard = state.db.lock().await;
-    let instance = match database::get_instance_by_aws_id(&*db_guard, &instance_id).await {
-        Ok(Some(instance)) => instance,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Instance not found",
-                "data": {}
-            }));
-        }
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to find instance: {}", e),
-                "data": {}
-            }));
-        }
-    };
-
-    let account_id = if instance.project_id > 0 { instance.project_id } else { return Ok(serde_json::json!({ "success": false, "message": "Instance not associated with an account" })); };
+    let pool = state.db.lock().await.clone();
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
         }
     };
 
-    let account = match database::get_account(&*db_guard, account_id).await {
-        Ok(Some(account)) => account,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Account not found",
-                "data": {}
-            }));
-        }
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get account: {}", e),
-                "data": {}
-            }));
-        }
-    };
+    let s3_service = aws::s3::S3Service::new(aws_client);
+    match s3_service.get_lifecycle(&bucket_name).await {
+        Ok(rules) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Bucket {} has {} lifecycle rules", bucket_name, rules.len()),
+            "data": rules
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to get lifecycle rules", &e).into_response(serde_json::json!([])))
+    }
+}
 
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
+/// Replace a bucket's lifecycle rules; an empty list removes them
+#[tauri::command]
+async fn put_s3_bucket_lifecycle(
+    account_id: i64,
+    bucket_name: String,
+    rules: Vec<aws::LifecycleRule>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
 
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": {}
-        }));
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
     }
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
         }
     };
 
-    // Get instance details
-    match aws_client.get_instance_details(&instance_id).await {
-        Ok(details) => Ok(serde_json::json!({
+    let s3_service = aws::s3::S3Service::new(aws_client);
+    match s3_service.put_lifecycle(&bucket_name, &rules).await {
+        Ok(()) => Ok(serde_json::json!({
             "success": true,
-            "message": "EC2 instance details retrieved successfully",
-            "data": details
+            "message": format!("Set {} lifecycle rules on bucket {}", rules.len(), bucket_name),
+            "data": rules
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get instance details: {}", e),
-            "data": {}
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to set lifecycle rules", &e).into_response(serde_json::json!([])))
     }
 }
 
+/// Estimate how many objects and bytes a lifecycle rule would transition or
+/// expire, and the monthly storage cost change, without applying it
 #[tauri::command]
-async fn get_ec2_instance_ssh_config(
-    instance_id: String,
+async fn preview_lifecycle_impact(
+    account_id: i64,
+    bucket: String,
+    rule: aws::LifecycleRule,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    // Get account from instance
-    let db_guard = state.db.lock().await;
-    let instance = match database::get_instance_by_aws_id(&*db_guard, &instance_id).await {
-        Ok(Some(instance)) => instance,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Instance not found",
-                "data": { "config": "" }
-            }));
-        }
+    let pool = state.db.lock().await.clone();
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to find instance: {}", e),
-                "data": { "config": "" }
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    let account_id = if instance.project_id > 0 { instance.project_id } else { return Ok(serde_json::json!({ "success": false, "message": "Instance not associated with an account" })); };
+    let s3_service = aws::s3::S3Service::new(aws_client);
+    match s3_service.preview_lifecycle_impact(&bucket, &rule).await {
+        Ok(preview) => {
+            let moved: usize = preview.transitions.iter().map(|t| t.objects).sum();
+            Ok(serde_json::json!({
+                "success": true,
+                "message": format!(
+                    "Rule would transition {} and expire {} of {} objects (approximate)",
+                    moved, preview.objects_expired, preview.objects_scanned
+                ),
+                "data": preview
+            }))
+        }
+        Err(e) => Ok(ApiError::aws("Failed to preview lifecycle rule", &e).into_response(serde_json::Value::Null))
+    }
+}
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
+/// Time-limited GET or PUT URL for one object, valid for up to seven days
+#[tauri::command]
+async fn get_s3_presigned_url(
+    bucket_name: String,
+    key: String,
+    expires_secs: Option<i64>,
+    method: Option<String>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // One hour unless asked otherwise; S3 caps presigned URLs at seven days
+    let expires_secs = match aws::s3::validate_presign_expiry(expires_secs.unwrap_or(3600)) {
+        Ok(secs) => secs,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": { "config": "" }
-            }));
+            return Ok(ApiError::InvalidInput(e.to_string()).into_response(serde_json::Value::Null));
         }
     };
-
-    let account = match database::get_account(&*db_guard, account_id).await {
-        Ok(Some(account)) => account,
-        Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "Account not found",
-                "data": { "config": "" }
-            }));
+    let method = match aws::s3::PresignMethod::parse(method.as_deref().unwrap_or("GET")) {
+        Ok(method) => method,
+        Err(e) => {
+            return Ok(ApiError::InvalidInput(e.to_string()).into_response(serde_json::Value::Null));
         }
+    };
+
+    let pool = state.db.lock().await.clone();
+
+    // Get first available account for S3 access
+    let accounts = match database::get_accounts(&pool).await {
+        Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get account: {}", e),
-                "data": { "config": "" }
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::Value::Null));
         }
     };
 
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
+    if accounts.is_empty() {
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::Value::Null));
+    }
 
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": { "config": "" }
-        }));
+    let account = &accounts[0];
+    let account_id = account.id;
+
+    // A PUT URL lets its holder write to the bucket
+    if method == aws::s3::PresignMethod::Put {
+        if let Some(response) = read_only_guard(&pool, account_id).await {
+            return Ok(response);
+        }
     }
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": { "config": "" }
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    // Get SSH config
-    match aws_client.get_instance_ssh_config(&instance_id).await {
-        Ok(config) => Ok(serde_json::json!({
+    let s3_service = aws::s3::S3Service::new(aws_client);
+    match s3_service.generate_presigned_url(&bucket_name, &key, expires_secs, method).await {
+        Ok(presigned) => Ok(serde_json::json!({
             "success": true,
-            "message": "SSH config generated successfully",
-            "data": { "config": config }
+            "message": format!("Presigned URL valid until {}", presigned.expires_at),
+            "data": presigned
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to generate SSH config: {}", e),
-            "data": { "config": "" }
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to generate presigned URL", &e).into_response(serde_json::Value::Null))
     }
 }
 
-// ============================================================================
-// S3 OPERATIONS
-// ============================================================================
-
+/// A bucket's policy document; `data` is null when the bucket has no policy
 #[tauri::command]
-async fn collect_s3_buckets(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+async fn get_s3_bucket_policy(
+    bucket_name: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for S3 access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": []
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::Value::Null));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": []
-        }));
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::Value::Null));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": []
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
+    let s3_service = aws::s3::S3Service::new(aws_client);
+    match s3_service.get_bucket_policy(&bucket_name).await {
+        Ok(policy) => Ok(serde_json::json!({
+            "success": true,
+            "message": if policy.is_some() {
+                format!("Retrieved policy for bucket {}", bucket_name)
+            } else {
+                format!("Bucket {} has no policy", bucket_name)
+            },
+            "data": policy
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to get bucket policy", &e).into_response(serde_json::Value::Null))
+    }
+}
 
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": []
-        }));
+/// Replace a bucket's policy; malformed documents are rejected before reaching AWS
+#[tauri::command]
+async fn put_s3_bucket_policy(
+    account_id: i64,
+    bucket_name: String,
+    policy: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
     }
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": []
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    // Collect buckets
-    match aws_client.collect_buckets().await {
-        Ok(buckets) => Ok(serde_json::json!({
+    let s3_service = aws::s3::S3Service::new(aws_client);
+    match s3_service.put_bucket_policy(&bucket_name, &policy).await {
+        Ok(()) => Ok(serde_json::json!({
             "success": true,
-            "message": format!("Collected {} S3 buckets", buckets.len()),
-            "data": buckets
+            "message": format!("Set policy on bucket {}", bucket_name),
+            "data": policy
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to collect buckets: {}", e),
-            "data": []
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to set bucket policy", &e).into_response(serde_json::Value::Null))
     }
 }
 
+/// Set a bucket's four public access block settings; all on unless given
 #[tauri::command]
-async fn create_s3_bucket(
+async fn put_s3_public_access_block(
+    account_id: i64,
     bucket_name: String,
-    region: String,
+    flags: Option<aws::PublicAccessBlockFlags>,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let flags = flags.unwrap_or_else(aws::PublicAccessBlockFlags::all);
 
-    // Get first available account for S3 access
-    let accounts = match database::get_accounts(&*db_guard).await {
-        Ok(accounts) => accounts,
+    let pool = state.db.lock().await.clone();
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": null
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": null
-        }));
+    let s3_service = aws::s3::S3Service::new(aws_client);
+    match s3_service.put_public_access_block(&bucket_name, flags).await {
+        Ok(()) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Updated public access block on bucket {}", bucket_name),
+            "data": flags
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to set public access block", &e).into_response(serde_json::Value::Null))
     }
+}
 
-    let account = &accounts[0];
-    let account_id = account.id;
+/// Default encryption of every bucket in the account, listing the buckets
+/// that have none
+#[tauri::command]
+async fn get_bucket_encryption_report(account_id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
+    let s3_service = aws::s3::S3Service::new(aws_client);
+    match s3_service.collect_bucket_encryption().await {
+        Ok(buckets) => {
+            let unencrypted: Vec<&str> = buckets.iter()
+                .filter(|status| !status.is_encrypted())
+                .map(|status| status.bucket.as_str())
+                .collect();
+            Ok(serde_json::json!({
+                "success": true,
+                "message": format!("{} of {} buckets have no default encryption", unencrypted.len(), buckets.len()),
+                "data": { "buckets": buckets, "unencrypted": unencrypted }
+            }))
+        }
+        Err(e) => Ok(ApiError::aws("Failed to check bucket encryption", &e).into_response(serde_json::Value::Null)),
+    }
+}
+
+/// Make SSE-S3, or SSE-KMS with `kms_key_id` (a key ARN), the default
+/// encryption of each bucket, reporting the outcome per bucket
+#[tauri::command]
+async fn enable_default_encryption(
+    account_id: i64,
+    buckets: Vec<String>,
+    kms_key_id: Option<String>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let encryption = match kms_key_id {
+        Some(kms_key_id) => aws::BucketEncryption::SseKms { kms_key_id },
+        None => aws::BucketEncryption::SseS3,
+    };
+    if let Err(e) = aws::s3::encryption_configuration(&encryption) {
+        return Ok(ApiError::InvalidInput(e.to_string()).into_response(serde_json::Value::Null));
+    }
+    if buckets.is_empty() {
+        return Ok(ApiError::InvalidInput("No buckets given".to_string()).into_response(serde_json::Value::Null));
+    }
+
+    let pool = state.db.lock().await.clone();
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": null
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let default_region = account.region.as_deref().unwrap_or("us-east-1");
+    let s3_service = aws::s3::S3Service::new(aws_client);
+    let results = match s3_service.enable_default_encryption(&buckets, &encryption).await {
+        Ok(results) => results,
+        Err(e) => return Ok(ApiError::aws("Failed to enable default encryption", &e).into_response(serde_json::Value::Null)),
+    };
 
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": null
-        }));
+    let failed = results.iter().filter(|result| !result.success).count();
+    Ok(serde_json::json!({
+        "success": failed == 0,
+        "message": format!("Enabled default encryption on {} of {} buckets", results.len() - failed, results.len()),
+        "data": { "encryption": encryption, "results": results }
+    }))
+}
+
+/// Upload a local file to a bucket; large files go up in parts
+#[tauri::command]
+async fn upload_s3_object(
+    account_id: i64,
+    bucket_name: String,
+    key: String,
+    src_path: String,
+    content_type: Option<String>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let s3_service = aws::s3::S3Service::new(aws_client);
+    match s3_service.upload_object(&bucket_name, &key, std::path::Path::new(&src_path), content_type.as_deref()).await {
+        Ok(uploaded) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Uploaded {} to s3://{}/{}", src_path, bucket_name, key),
+            "data": uploaded
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to upload object", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+// ============================================================================
+// IAM OPERATIONS
+// ============================================================================
+
+#[tauri::command]
+async fn collect_iam_users(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Get first available account for IAM access
+    let accounts = match database::get_accounts(&pool).await {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!([])));
+        }
+    };
+
+    if accounts.is_empty() {
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::json!([])));
     }
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, default_region).await {
+    let account = &accounts[0];
+    let account_id = account.id;
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": null
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
         }
     };
 
-    // Create bucket
-    match aws_client.create_bucket(&bucket_name, &region).await {
-        Ok(bucket) => Ok(serde_json::json!({
+    // Collect IAM users
+    match aws_client.collect_iam_users().await {
+        Ok(users) => Ok(serde_json::json!({
             "success": true,
-            "message": "S3 bucket created successfully",
-            "data": bucket
+            "message": format!("Collected {} IAM users", users.len()),
+            "data": users
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to create bucket: {}", e),
-            "data": null
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to collect IAM users", &e).into_response(serde_json::json!([])))
     }
 }
 
 #[tauri::command]
-async fn delete_s3_bucket(
-    bucket_name: String,
-    state: State<'_, AppState>
-) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+async fn collect_iam_roles(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
 
-    // Get first available account for S3 access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    // Get first available account for IAM access
+    let accounts = match database::get_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e)
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!([])));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured"
-        }));
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::json!([])));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e)
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials"
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
         }
     };
 
-    // Delete bucket
-    match aws_client.delete_bucket(&bucket_name).await {
-        Ok(_) => Ok(serde_json::json!({
+    // Collect IAM roles
+    match aws_client.collect_iam_roles().await {
+        Ok(roles) => Ok(serde_json::json!({
             "success": true,
-            "message": "S3 bucket deleted successfully"
+            "message": format!("Collected {} IAM roles", roles.len()),
+            "data": roles
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to delete bucket: {}", e)
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to collect IAM roles", &e).into_response(serde_json::json!([])))
     }
 }
 
 #[tauri::command]
-async fn get_s3_bucket_details(
-    bucket_name: String,
+async fn get_iam_user_details(
+    user_name: String,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let pool = state.db.lock().await.clone();
 
-    // Get first available account for S3 access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    // Get first available account for IAM access
+    let accounts = match database::get_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!({})));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": {}
-        }));
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::json!({})));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": {}
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": {}
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
         }
     };
 
-    // Get bucket details
-    match aws_client.get_bucket_details(&bucket_name).await {
+    // Get IAM user details, including attached and inline policies
+    let iam_service = aws::iam::IamService::new(aws_client);
+    match iam_service.get_user_details(&user_name).await {
         Ok(details) => Ok(serde_json::json!({
             "success": true,
-            "message": "S3 bucket details retrieved successfully",
+            "message": "IAM user details retrieved successfully",
             "data": details
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get bucket details: {}", e),
-            "data": {}
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to get IAM user details", &e).into_response(serde_json::json!({})))
     }
 }
 
-// ============================================================================
-// IAM OPERATIONS
-// ============================================================================
-
+/// A role with its attached managed policies and inline policy names
 #[tauri::command]
-async fn collect_iam_users(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+async fn get_iam_role_details(
+    role_name: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for IAM access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": []
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!({})));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": []
-        }));
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::json!({})));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": []
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": []
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": []
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
         }
     };
 
-    // Collect IAM users
-    match aws_client.collect_iam_users().await {
-        Ok(users) => Ok(serde_json::json!({
+    let iam_service = aws::iam::IamService::new(aws_client);
+    match iam_service.get_role_details(&role_name).await {
+        Ok(details) => Ok(serde_json::json!({
             "success": true,
-            "message": format!("Collected {} IAM users", users.len()),
-            "data": users
+            "message": "IAM role details retrieved successfully",
+            "data": details
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to collect IAM users: {}", e),
-            "data": []
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to get IAM role details", &e).into_response(serde_json::json!({})))
     }
 }
 
+/// A user's access keys with status and creation date; never their secrets
 #[tauri::command]
-async fn collect_iam_roles(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+async fn list_iam_access_keys(
+    user_name: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for IAM access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": []
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::Value::Null));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": []
-        }));
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::Value::Null));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": []
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": []
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": []
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    // Collect IAM roles
-    match aws_client.collect_iam_roles().await {
-        Ok(roles) => Ok(serde_json::json!({
+    let iam_service = aws::iam::IamService::new(aws_client);
+    match iam_service.list_access_keys(&user_name).await {
+        Ok(keys) => Ok(serde_json::json!({
             "success": true,
-            "message": format!("Collected {} IAM roles", roles.len()),
-            "data": roles
+            "message": format!("Found {} access keys for {}", keys.len(), user_name),
+            "data": keys
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to collect IAM roles: {}", e),
-            "data": []
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to list access keys", &e).into_response(serde_json::Value::Null))
     }
 }
 
+/// Create an access key; the response is the only place its secret appears
 #[tauri::command]
-async fn get_iam_user_details(
+async fn create_iam_access_key(
+    account_id: i64,
     user_name: String,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
-
-    // Get first available account for IAM access
-    let accounts = match database::get_accounts(&*db_guard).await {
-        Ok(accounts) => accounts,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": {}
-            }));
-        }
-    };
+    let pool = state.db.lock().await.clone();
 
-    if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": {}
-        }));
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
     }
 
-    let account = &accounts[0];
-    let account_id = account.id;
-
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
+    let iam_service = aws::iam::IamService::new(aws_client);
+    match iam_service.create_access_key(&user_name).await {
+        Ok(key) => Ok(serde_json::json!({
+            "success": true,
+            "message": format!("Created access key {} for {}. Save the secret now; it cannot be retrieved again.", key.access_key.access_key_id, user_name),
+            "data": key
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to create access key", &e).into_response(serde_json::Value::Null))
+    }
+}
 
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": {}
-        }));
+/// Delete an access key, unless it is the user's last active one
+#[tauri::command]
+async fn delete_iam_access_key(
+    account_id: i64,
+    user_name: String,
+    access_key_id: String,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
     }
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    // Get IAM user details
-    match aws_client.get_iam_user_details(&user_name).await {
-        Ok(details) => Ok(serde_json::json!({
+    let iam_service = aws::iam::IamService::new(aws_client);
+    match iam_service.delete_access_key(&user_name, &access_key_id).await {
+        Ok(()) => Ok(serde_json::json!({
             "success": true,
-            "message": "IAM user details retrieved successfully",
-            "data": details
+            "message": format!("Deleted access key {} of {}", access_key_id, user_name),
+            "data": access_key_id
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get IAM user details: {}", e),
-            "data": {}
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to delete access key", &e).into_response(serde_json::Value::Null))
     }
 }
 
@@ -2259,58 +5876,43 @@ async fn get_cost_summary(
     end_date: String,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for cost access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_active_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Database error: {}. Please check your database configuration.", e),
-                "data": { "total_cost": 0.0, "services": [] }
-            }));
+            return Ok(ApiError::database(format!("Database error: {}. Please check your database configuration.", e))
+                .into_response(serde_json::json!({ "total_cost": 0.0, "services": [] })));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured. Please create an AWS account in Pocket Architect first.",
-            "data": { "total_cost": 0.0, "services": [] }
-        }));
+        return Ok(ApiError::NotFound("No active AWS accounts configured. Please create an AWS account in Pocket Architect first.".to_string())
+            .into_response(serde_json::json!({ "total_cost": 0.0, "services": [] })));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
     // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
+    let credentials = match database::get_account_credentials(&pool, account_id).await {
         Ok(creds) => creds,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to retrieve AWS credentials: {}. Please check your account configuration.", e),
-                "data": { "total_cost": 0.0, "services": [] }
-            }));
+            return Ok(ApiError::from_db_with_message(&e, format!("Failed to retrieve AWS credentials: {}. Please check your account configuration.", e))
+                .into_response(serde_json::json!({ "total_cost": 0.0, "services": [] })));
         }
     };
 
-    let account = match database::get_account(&*db_guard, account_id).await {
+    let account = match database::get_account(&pool, account_id).await {
         Ok(Some(account)) => account,
         Ok(None) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": "AWS account not found. Please reconfigure your account.",
-                "data": { "total_cost": 0.0, "services": [] }
-            }));
+            return Ok(ApiError::NotFound("AWS account not found. Please reconfigure your account.".to_string())
+                .into_response(serde_json::json!({ "total_cost": 0.0, "services": [] })));
         }
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Database error accessing account: {}. Please check your database.", e),
-                "data": { "total_cost": 0.0, "services": [] }
-            }));
+            return Ok(ApiError::database(format!("Database error accessing account: {}. Please check your database.", e))
+                .into_response(serde_json::json!({ "total_cost": 0.0, "services": [] })));
         }
     };
 
@@ -2319,27 +5921,32 @@ async fn get_cost_summary(
     let region = account.region.as_deref().unwrap_or("us-east-1");
 
     if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "AWS credentials are required but not configured. Please set valid AWS access key and secret key in your account settings.",
-            "data": { "total_cost": 0.0, "services": [] }
-        }));
+        return Ok(ApiError::AwsAuth("AWS credentials are required but not configured. Please set valid AWS access key and secret key in your account settings.".to_string())
+            .into_response(serde_json::json!({ "total_cost": 0.0, "services": [] })));
     }
 
     #[cfg(feature = "aws-sdk")]
     {
         // Create AWS client
-        let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+        let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
             Ok(client) => client,
             Err(e) => {
-                return Ok(serde_json::json!({
-                    "success": false,
-                    "message": format!("Failed to validate AWS credentials: {}. Please verify your access key and secret key are correct.", e),
-                    "data": { "total_cost": 0.0, "services": [] }
-                }));
+                let error = ApiError::aws_with_message(
+                    &e,
+                    format!("Failed to validate AWS credentials: {}. Please verify your access key and secret key are correct.", e),
+                );
+                return Ok(error.into_response(serde_json::json!({ "total_cost": 0.0, "services": [] })));
             }
         };
 
+        if let Some(response) = permission_guard(
+            &aws_client,
+            aws::permissions::COST_ACTIONS,
+            serde_json::json!({ "total_cost": 0.0, "services": [] }),
+        ).await {
+            return Ok(response);
+        }
+
         // Get cost summary
         match aws_client.get_cost_summary(&start_date, &end_date).await {
             Ok(cost_summary) => Ok(serde_json::json!({
@@ -2347,92 +5954,116 @@ async fn get_cost_summary(
                 "message": "Cost summary retrieved successfully from AWS Cost Explorer",
                 "data": cost_summary
             })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to retrieve cost data from AWS: {}. Please check your AWS credentials have Cost Explorer permissions.", e),
-                "data": { "total_cost": 0.0, "services": [] }
+            Err(e) => Ok(ApiError::aws_with_message(
+                &e,
+                format!("Failed to retrieve cost data from AWS: {}. Please check your AWS credentials have Cost Explorer permissions.", e),
+            )
+            .into_response(serde_json::json!({ "total_cost": 0.0, "services": [] })))
+        }
+    }
+
+    #[cfg(not(feature = "aws-sdk"))]
+    {
+        // Validate credentials format without AWS SDK
+        return match validate_credentials_for_operation(access_key, secret_key, region).await {
+            Ok(_) => Ok(ApiError::Internal(
+                "AWS SDK not available. To retrieve real cost data from AWS Cost Explorer, build with: cargo build --features aws-sdk (requires Linux/Mac or compatible compiler)".to_string(),
+            )
+            .into_response(serde_json::json!({ "total_cost": 0.0, "services": [] }))),
+            Err(e) => Ok(e.into_response(serde_json::json!({ "total_cost": 0.0, "services": [] })))
+        };
+    }
+}
+
+/// Cost allocation tags known to Cost Explorer and whether each is active
+#[tauri::command]
+async fn list_cost_allocation_tags(
+    account_id: i64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
+        }
+    };
+
+    match aws::costtags::list_cost_allocation_tags(&aws_client).await {
+        Ok(tags) => {
+            let active = tags.iter().filter(|t| t.status == "active").count();
+            Ok(serde_json::json!({
+                "success": true,
+                "message": format!("{} of {} cost allocation tags are active", active, tags.len()),
+                "data": tags
             }))
         }
-    }
+        Err(e) => Ok(ApiError::aws("Failed to list cost allocation tags", &e).into_response(serde_json::json!([])))
+    }
+}
+
+/// Activate tag keys as cost allocation tags so cost-by-tag reports include them
+#[tauri::command]
+async fn activate_cost_allocation_tags(
+    account_id: i64,
+    keys: Vec<String>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    if keys.is_empty() {
+        return Ok(ApiError::InvalidInput("No tag keys to activate".to_string()).into_response(serde_json::Value::Null));
+    }
+
+    let pool = state.db.lock().await.clone();
+
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
+    }
+
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
 
-    #[cfg(not(feature = "aws-sdk"))]
-    {
-        // Validate credentials format without AWS SDK
-        return match validate_credentials_for_operation(access_key, secret_key, region).await {
-            Ok(_) => Ok(serde_json::json!({
-                "success": false,
-                "message": "AWS SDK not available. To retrieve real cost data from AWS Cost Explorer, build with: cargo build --features aws-sdk (requires Linux/Mac or compatible compiler)",
-                "data": { "total_cost": 0.0, "services": [] }
-            })),
-            Err(e) => Ok(serde_json::json!({
-                "success": false,
-                "message": e,
-                "data": { "total_cost": 0.0, "services": [] }
-            }))
-        };
+    match aws::costtags::activate_cost_allocation_tags(&aws_client, &keys).await {
+        Ok(report) => Ok(serde_json::json!({
+            "success": report.failed.is_empty(),
+            "message": format!("Activated {} of {} cost allocation tags. {}", report.activated.len(), keys.len(), report.warning),
+            "data": report
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to activate cost allocation tags", &e).into_response(serde_json::Value::Null))
     }
 }
 
 #[tauri::command]
 async fn get_budget_alerts(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for budget access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_active_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": []
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!([])));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": []
-        }));
+        return Ok(ApiError::NotFound("No active AWS accounts configured".to_string()).into_response(serde_json::json!([])));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": []
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": []
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": []
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
         }
     };
 
@@ -2443,11 +6074,7 @@ async fn get_budget_alerts(state: State<'_, AppState>) -> Result<serde_json::Val
             "message": format!("Retrieved {} budget alerts", alerts.len()),
             "data": alerts
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get budget alerts: {}", e),
-            "data": []
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to get budget alerts", &e).into_response(serde_json::json!([])))
     }
 }
 
@@ -2456,64 +6083,33 @@ async fn create_budget_alert(
     request: serde_json::Value,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for budget access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_active_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!({})));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": {}
-        }));
+        return Ok(ApiError::NotFound("No active AWS accounts configured".to_string()).into_response(serde_json::json!({})));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": {}
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": {}
-        }));
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
     }
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
         }
     };
 
@@ -2524,11 +6120,7 @@ async fn create_budget_alert(
             "message": "Budget alert created successfully",
             "data": alert
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to create budget alert: {}", e),
-            "data": {}
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to create budget alert", &e).into_response(serde_json::json!({})))
     }
 }
 
@@ -2538,64 +6130,33 @@ async fn update_budget_alert(
     request: serde_json::Value,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for budget access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_active_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!({})));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": {}
-        }));
+        return Ok(ApiError::NotFound("No active AWS accounts configured".to_string()).into_response(serde_json::json!({})));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": {}
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": {}
-        }));
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
     }
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
         }
     };
 
@@ -2607,235 +6168,334 @@ async fn update_budget_alert(
             "message": "Budget alert updated successfully",
             "data": alert
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to update budget alert: {}", e),
-            "data": {}
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to update budget alert", &e).into_response(serde_json::json!({})))
     }
 }
 
+/// Delete a budget alert. With `dry_run` set, only checks the alert exists.
 #[tauri::command]
 async fn delete_budget_alert(
     id: i64,
+    dry_run: Option<bool>,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let dry_run = dry_run.unwrap_or(false);
+
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for budget access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_active_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e)
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::Value::Null));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured"
-        }));
+        return Ok(ApiError::NotFound("No active AWS accounts configured".to_string()).into_response(serde_json::Value::Null));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e)
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials"
-        }));
+    // Read-only accounts never reach AWS for mutating operations
+    if let Some(response) = read_only_guard(&pool, account_id).await {
+        return Ok(response);
     }
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
     // Delete budget alert
     let alert_id = format!("budget-alert-{}", id);
+    if dry_run {
+        return match aws_client.get_budget_alerts().await {
+            Ok(alerts) if alerts.iter().any(|alert| alert.id == alert_id) => Ok(serde_json::json!({
+                "success": true,
+                "message": format!("Dry run: budget alert {} would be deleted", alert_id),
+                "data": { "dryRun": true, "alertId": alert_id }
+            })),
+            Ok(_) => Ok(ApiError::NotFound(format!("Budget alert {} not found", alert_id)).into_response(serde_json::Value::Null)),
+            Err(e) => Ok(ApiError::aws("Failed to delete budget alert", &e).into_response(serde_json::Value::Null)),
+        };
+    }
+
     match aws_client.delete_budget_alert(&alert_id).await {
         Ok(_) => Ok(serde_json::json!({
             "success": true,
             "message": "Budget alert deleted successfully"
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to delete budget alert: {}", e)
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to delete budget alert", &e).into_response(serde_json::Value::Null))
     }
 }
 
 #[tauri::command]
-async fn get_cost_status(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+async fn get_cost_status(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for cost access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_active_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!({})));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": {}
-        }));
+        return Ok(ApiError::NotFound("No active AWS accounts configured".to_string()).into_response(serde_json::json!({})));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": {}
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": {}
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
-        Ok(client) => client,
+    let cost_tracker = match state.aws_clients.cost_tracker(&pool, account_id, app).await {
+        Ok(tracker) => tracker,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
         }
     };
 
     // Get cost status
-    match aws_client.get_cost_status().await {
+    match cost_tracker.get_cost_status().await {
         Ok(status) => Ok(serde_json::json!({
             "success": true,
             "message": "Cost status retrieved successfully",
             "data": status
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get cost status: {}", e),
-            "data": {}
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to get cost status", &e).into_response(serde_json::json!({})))
     }
 }
 
 #[tauri::command]
-async fn reset_cost_tracking(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+async fn reset_cost_tracking(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for cost access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_active_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e)
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::Value::Null));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured"
-        }));
+        return Ok(ApiError::NotFound("No active AWS accounts configured".to_string()).into_response(serde_json::Value::Null));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
+    let cost_tracker = match state.aws_clients.cost_tracker(&pool, account_id, app).await {
+        Ok(tracker) => tracker,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
+    // Reset cost tracking
+    cost_tracker.reset_tracking().await;
+    Ok(serde_json::json!({
+        "success": true,
+        "message": "Cost tracking reset successfully"
+    }))
+}
 
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials"
-        }));
+#[tauri::command]
+async fn get_cost_tracking_delta(
+    since: String,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let since = match chrono::DateTime::parse_from_rfc3339(&since) {
+        Ok(since) => since.with_timezone(&chrono::Utc),
+        Err(e) => {
+            return Ok(ApiError::InvalidInput(format!("Invalid timestamp '{}': {}", since, e)).into_response(serde_json::json!({})));
+        }
+    };
+
+    let pool = state.db.lock().await.clone();
+
+    // Get first available account for cost access
+    let accounts = match database::get_active_accounts(&pool).await {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!({})));
+        }
+    };
+
+    let Some(account) = accounts.first() else {
+        return Ok(ApiError::NotFound("No active AWS accounts configured".to_string()).into_response(serde_json::json!({})));
+    };
+
+    let cost_tracker = match state.aws_clients.cost_tracker(&pool, account.id, app).await {
+        Ok(tracker) => tracker,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
+        }
+    };
+
+    let delta = cost_tracker.spend_since(since).await;
+    Ok(serde_json::json!({
+        "success": true,
+        "message": format!("${:.6} accrued over {} API calls", delta.spend_usd, delta.api_calls),
+        "data": delta
+    }))
+}
+
+#[tauri::command]
+async fn get_cost_thresholds(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::get_cost_thresholds(&*db_guard).await {
+        Ok(thresholds) => Ok(serde_json::json!({
+            "success": true,
+            "message": "Cost thresholds retrieved successfully",
+            "data": thresholds
+        })),
+        Err(e) => Ok(ApiError::database(format!("Failed to get cost thresholds: {}", e)).into_response(serde_json::json!({})))
     }
+}
+
+#[tauri::command]
+async fn set_cost_thresholds(
+    warn_usd: f64,
+    limit_usd: f64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    let thresholds = match database::set_cost_thresholds(&pool, database::CostThresholds { warn_usd, limit_usd }).await {
+        Ok(thresholds) => thresholds,
+        Err(e) => {
+            return Ok(ApiError::from_db("Failed to set cost thresholds", &e).into_response(serde_json::json!({})));
+        }
+    };
+
+    // Trackers created later read the stored thresholds themselves
+    if let Err(e) = state.aws_clients.apply_cost_thresholds(thresholds).await {
+        return Ok(ApiError::aws("Saved cost thresholds but failed to apply them", &e).into_response(serde_json::json!(thresholds)));
+    }
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": format!("Cost warning set to ${:.2} and limit to ${:.2}", thresholds.warn_usd, thresholds.limit_usd),
+        "data": thresholds
+    }))
+}
+
+#[tauri::command]
+async fn set_cost_alert_thresholds(
+    thresholds_percent: Vec<f64>,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    let accounts = match database::get_active_accounts(&pool).await {
+        Ok(accounts) => accounts,
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
+
+    let Some(account) = accounts.first() else {
+        return Ok(ApiError::NotFound("No active AWS accounts configured".to_string()).into_response(serde_json::Value::Null));
+    };
+
+    let cost_tracker = match state.aws_clients.cost_tracker(&pool, account.id, app).await {
+        Ok(tracker) => tracker,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
+        }
+    };
+
+    match cost_tracker.set_alert_thresholds(thresholds_percent).await {
+        Ok(()) => Ok(serde_json::json!({
+            "success": true,
+            "message": "Cost alert thresholds updated",
+            "data": cost_tracker.alert_thresholds().await
+        })),
+        Err(e) => Ok(ApiError::aws("Failed to update cost alert thresholds", &e).into_response(serde_json::Value::Null))
+    }
+}
+
+// ============================================================================
+// SECURITY POSTURE
+// ============================================================================
+
+/// A weighted 0-100 security score for an account, with a per-category
+/// breakdown and the worst findings
+#[tauri::command]
+async fn security_posture_score(
+    account_id: i64,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    let weights = match database::get_security_posture_weights(&pool).await {
+        Ok(weights) => weights,
+        Err(e) => {
+            return Ok(ApiError::database(format!("Failed to get security posture weights: {}", e)).into_response(serde_json::Value::Null));
+        }
+    };
 
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
-    // Reset cost tracking
-    match aws_client.reset_cost_tracking().await {
-        Ok(_) => Ok(serde_json::json!({
+    let report = aws::posture::security_posture(&aws_client, &weights).await;
+    let message = if report.unavailable.is_empty() {
+        format!("Security posture score {:.0}/100 with {} findings", report.score, report.total_findings)
+    } else {
+        format!(
+            "Security posture score {:.0}/100 with {} findings; {} categories could not be scanned",
+            report.score,
+            report.total_findings,
+            report.unavailable.len()
+        )
+    };
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": message,
+        "data": report
+    }))
+}
+
+#[tauri::command]
+async fn get_security_posture_weights(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::get_security_posture_weights(&*db_guard).await {
+        Ok(weights) => Ok(serde_json::json!({
+            "success": true,
+            "message": "Security posture weights retrieved successfully",
+            "data": weights
+        })),
+        Err(e) => Ok(ApiError::database(format!("Failed to get security posture weights: {}", e)).into_response(serde_json::json!({})))
+    }
+}
+
+#[tauri::command]
+async fn set_security_posture_weights(
+    weights: database::SecurityPostureWeights,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let db_guard = state.db.lock().await;
+    match database::set_security_posture_weights(&*db_guard, weights).await {
+        Ok(weights) => Ok(serde_json::json!({
             "success": true,
-            "message": "Cost tracking reset successfully"
+            "message": "Security posture weights updated",
+            "data": weights
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to reset cost tracking: {}", e)
-        }))
+        Err(e) => Ok(ApiError::from_db("Failed to set security posture weights", &e).into_response(serde_json::json!({})))
     }
 }
 
@@ -2845,64 +6505,28 @@ async fn reset_cost_tracking(state: State<'_, AppState>) -> Result<serde_json::V
 
 #[tauri::command]
 async fn get_cache_stats(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for system access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": { "entries": 0, "size": 0 }
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!({ "entries": 0, "size": 0 })));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": { "entries": 0, "size": 0 }
-        }));
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::json!({ "entries": 0, "size": 0 })));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": { "entries": 0, "size": 0 }
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": { "entries": 0, "size": 0 }
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": { "entries": 0, "size": 0 }
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({ "entries": 0, "size": 0 })));
         }
     };
 
@@ -2918,59 +6542,28 @@ async fn get_cache_stats(state: State<'_, AppState>) -> Result<serde_json::Value
 
 #[tauri::command]
 async fn invalidate_cache(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for system access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e)
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::Value::Null));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured"
-        }));
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::Value::Null));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e)
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials"
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
@@ -2980,10 +6573,7 @@ async fn invalidate_cache(state: State<'_, AppState>) -> Result<serde_json::Valu
             "success": true,
             "message": "Cache invalidated successfully"
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to invalidate cache: {}", e)
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to invalidate cache", &e).into_response(serde_json::Value::Null))
     }
 }
 
@@ -2992,59 +6582,28 @@ async fn invalidate_cache_region(
     region: String,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for system access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e)
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::Value::Null));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured"
-        }));
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::Value::Null));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e)
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let default_region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials"
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, default_region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e)
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::Value::Null));
         }
     };
 
@@ -3054,245 +6613,134 @@ async fn invalidate_cache_region(
             "success": true,
             "message": format!("Cache region '{}' invalidated successfully", region)
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to invalidate cache region: {}", e)
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to invalidate cache region", &e).into_response(serde_json::Value::Null))
     }
 }
 
 #[tauri::command]
-async fn get_aws_health_status(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+async fn clear_aws_clients(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cleared = state.aws_clients.clear().await;
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": format!("Cleared {} cached AWS clients", cleared),
+        "data": { "cleared": cleared }
+    }))
+}
+
+#[tauri::command]
+async fn get_aws_health_status(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for system access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_active_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!({})));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": {}
-        }));
+        return Ok(ApiError::NotFound("No active AWS accounts configured".to_string()).into_response(serde_json::json!({})));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
+    // Get the account's health monitor, which alerts on status transitions
+    let monitor = match state.aws_clients.health_monitor(&pool, account_id, app).await {
+        Ok(monitor) => monitor,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
         }
     };
 
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": {}
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
-        Ok(client) => client,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": {}
-            }));
+    // Refresh a stale status first so transitions are noticed as the app polls
+    if monitor.needs_health_check().await {
+        if let Err(e) = monitor.perform_health_check().await {
+            return Ok(ApiError::aws("Failed to check AWS health", &e).into_response(serde_json::json!({})));
         }
-    };
-
-    // Get AWS health status
-    match aws_client.get_aws_health_status().await {
-        Ok(status) => Ok(serde_json::json!({
-            "success": true,
-            "message": "AWS health status retrieved successfully",
-            "data": status
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get AWS health status: {}", e),
-            "data": {}
-        }))
     }
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": "AWS health status retrieved successfully",
+        "data": monitor.get_health_status().await
+    }))
 }
 
 #[tauri::command]
-async fn force_aws_health_check(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+async fn force_aws_health_check(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for system access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_active_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!({})));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": {}
-        }));
+        return Ok(ApiError::NotFound("No active AWS accounts configured".to_string()).into_response(serde_json::json!({})));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": {}
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": {}
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
-        Ok(client) => client,
+    // Get the account's health monitor, which alerts on status transitions
+    let monitor = match state.aws_clients.health_monitor(&pool, account_id, app).await {
+        Ok(monitor) => monitor,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
         }
     };
 
     // Force AWS health check
-    match aws_client.force_aws_health_check().await {
+    match monitor.force_health_check().await {
         Ok(result) => Ok(serde_json::json!({
             "success": true,
             "message": "AWS health check completed successfully",
             "data": result
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to perform AWS health check: {}", e),
-            "data": {}
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to perform AWS health check", &e).into_response(serde_json::json!({})))
     }
 }
 
 #[tauri::command]
-async fn get_aws_health_report(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+async fn get_aws_health_report(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for system access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_active_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!({})));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": {}
-        }));
+        return Ok(ApiError::NotFound("No active AWS accounts configured".to_string()).into_response(serde_json::json!({})));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": {}
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": {}
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
-        Ok(client) => client,
+    // Get the account's health monitor, which alerts on status transitions
+    let monitor = match state.aws_clients.health_monitor(&pool, account_id, app).await {
+        Ok(monitor) => monitor,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": {}
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
         }
     };
 
     // Get AWS health report
-    match aws_client.get_aws_health_report().await {
-        Ok(report) => Ok(serde_json::json!({
-            "success": true,
-            "message": "AWS health report retrieved successfully",
-            "data": report
-        })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get AWS health report: {}", e),
-            "data": {}
-        }))
-    }
+    let report = aws::health::HealthIntegration::new(monitor).get_aws_health_report().await;
+    Ok(serde_json::json!({
+        "success": true,
+        "message": "AWS health report retrieved successfully",
+        "data": report
+    }))
 }
 
 #[tauri::command]
@@ -3300,64 +6748,28 @@ async fn get_recent_aws_events(
     since: String,
     state: State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let db_guard = state.db.lock().await;
+    let pool = state.db.lock().await.clone();
 
     // Get first available account for system access
-    let accounts = match database::get_accounts(&*db_guard).await {
+    let accounts = match database::get_accounts(&pool).await {
         Ok(accounts) => accounts,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get accounts: {}", e),
-                "data": []
-            }));
+            return Ok(ApiError::database(format!("Failed to get accounts: {}", e)).into_response(serde_json::json!([])));
         }
     };
 
     if accounts.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "No AWS accounts configured",
-            "data": []
-        }));
+        return Ok(ApiError::NotFound("No AWS accounts configured".to_string()).into_response(serde_json::json!([])));
     }
 
     let account = &accounts[0];
     let account_id = account.id;
 
-    // Get account credentials
-    let credentials = match database::get_account_credentials(&*db_guard, account_id).await {
-        Ok(creds) => creds,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to get credentials: {}", e),
-                "data": []
-            }));
-        }
-    };
-
-    let access_key = credentials.access_key.as_deref().unwrap_or("");
-    let secret_key = credentials.secret_key.as_deref().unwrap_or("");
-    let region = account.region.as_deref().unwrap_or("us-east-1");
-
-    if access_key.is_empty() || secret_key.is_empty() {
-        return Ok(serde_json::json!({
-            "success": false,
-            "message": "Missing AWS credentials",
-            "data": []
-        }));
-    }
-
-    // Create AWS client
-    let aws_client = match AwsClient::new(access_key, secret_key, region).await {
+    // Get the cached AWS client for this account
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
         Ok(client) => client,
         Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "message": format!("Failed to create AWS client: {}", e),
-                "data": []
-            }));
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
         }
     };
 
@@ -3368,14 +6780,121 @@ async fn get_recent_aws_events(
             "message": format!("Retrieved {} recent AWS events", events.len()),
             "data": events
         })),
-        Err(e) => Ok(serde_json::json!({
-            "success": false,
-            "message": format!("Failed to get recent AWS events: {}", e),
-            "data": []
-        }))
+        Err(e) => Ok(ApiError::aws("Failed to get recent AWS events", &e).into_response(serde_json::json!([])))
     }
 }
 
+/// The most recent AWS API errors, newest first, with credentials redacted
+#[tauri::command]
+async fn get_recent_aws_errors(limit: Option<usize>) -> Result<serde_json::Value, String> {
+    let errors = aws::diagnostics::recent_errors(limit.unwrap_or(50));
+    Ok(serde_json::json!({
+        "success": true,
+        "message": format!("{} recent AWS errors", errors.len()),
+        "data": errors
+    }))
+}
+
+/// Events stored after `after_id`, so a reloaded frontend can catch up on
+/// events it missed. Pass 0 to replay everything still buffered.
+#[tauri::command]
+async fn replay_events(
+    after_id: i64,
+    types: Option<Vec<String>>,
+    state: State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let replay = state.aws_clients
+        .event_store()
+        .replay(after_id.max(0) as u64, types.as_deref().unwrap_or_default())
+        .await;
+
+    let message = if replay.truncated {
+        format!("Replayed {} events; older events were evicted from the buffer", replay.events.len())
+    } else {
+        format!("Replayed {} events", replay.events.len())
+    };
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": message,
+        "data": replay
+    }))
+}
+
+#[tauri::command]
+async fn check_account_permissions(account_id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let pool = state.db.lock().await.clone();
+
+    let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+        Ok(client) => client,
+        Err(e) => {
+            return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!({})));
+        }
+    };
+
+    let report = aws::permissions::check_account_permissions(&aws_client).await;
+    let message = if report.denied == 0 {
+        format!("{} of {} permission checks passed", report.allowed, report.checks.len())
+    } else {
+        format!("Missing permissions: {}", report.denied_actions().join(", "))
+    };
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": message,
+        "data": report
+    }))
+}
+
+/// Open AWS Health Dashboard events affecting the account, cached for a few minutes
+#[tauri::command]
+async fn get_aws_service_events(account_id: i64, state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let report = match state.aws_clients.cached_service_events(account_id).await {
+        Some(report) => report,
+        None => {
+            let pool = state.db.lock().await.clone();
+            let aws_client = match state.aws_clients.get_client(&pool, account_id).await {
+                Ok(client) => client,
+                Err(e) => {
+                    return Ok(ApiError::aws("Failed to create AWS client", &e).into_response(serde_json::json!([])));
+                }
+            };
+
+            match aws::awshealth::fetch_service_events(&aws_client).await {
+                Ok(report) => {
+                    state.aws_clients.put_service_events(account_id, report.clone()).await;
+                    report
+                }
+                Err(e) => {
+                    return Ok(ApiError::aws("Failed to get AWS service events", &e).into_response(serde_json::json!([])));
+                }
+            }
+        }
+    };
+
+    let message = if report.business_support_required {
+        "AWS Health events require a Business, Enterprise On-Ramp or Enterprise support plan".to_string()
+    } else {
+        format!("Found {} open AWS service events", report.events.len())
+    };
+
+    Ok(serde_json::json!({
+        "success": true,
+        "message": message,
+        "data": report
+    }))
+}
+
+#[tauri::command]
+async fn describe_commands() -> Result<serde_json::Value, String> {
+    let commands = commands::describe();
+    Ok(serde_json::json!({
+        "success": true,
+        "message": format!("{} commands available", commands.len()),
+        "data": commands
+    }))
+}
+
 pub async fn start_backend_server() {
     // Placeholder for backend server
     println!("Backend server started");
@@ -35,6 +35,9 @@ fn run_with_database() -> Result<(), anyhow::Error> {
     // Create app state
     let app_state = AppState {
         db: Arc::new(Mutex::new(db_pool)),
+        #[cfg(feature = "aws-sdk")]
+        aws_clients: app_lib::AwsClientManager::new(),
+        confirmations: Default::default(),
     };
 
     // Run Tauri app with state
@@ -42,6 +45,7 @@ fn run_with_database() -> Result<(), anyhow::Error> {
         .manage(app_state)
         .invoke_handler(tauri::generate_handler![
             app_lib::greet,
+            app_lib::get_app_info,
             app_lib::get_accounts,
             app_lib::get_account,
             app_lib::create_account,
@@ -49,13 +53,31 @@ fn run_with_database() -> Result<(), anyhow::Error> {
             app_lib::delete_account,
             app_lib::test_account_connection,
             app_lib::sync_account,
+            app_lib::sync_all_accounts,
+            app_lib::set_account_status,
+            app_lib::set_credential_failure_threshold,
+            app_lib::set_health_webhook_url,
+            app_lib::get_smtp_settings,
+            app_lib::set_smtp_settings,
+            app_lib::test_smtp,
+            app_lib::get_ssh_key_paths,
+            app_lib::set_ssh_key_path,
+            app_lib::get_account_regions,
+            app_lib::set_account_regions,
+            app_lib::set_credential_store_passphrase,
+            app_lib::migrate_credentials_to_keyring,
+            app_lib::verify_credential_linkage,
             app_lib::get_projects,
             app_lib::get_project,
             app_lib::create_project,
             app_lib::update_project,
+            app_lib::rename_project,
+            app_lib::compute_project_health,
             app_lib::delete_project,
+            app_lib::delete_project_with_resources,
             app_lib::get_instances,
             app_lib::get_instance,
+            app_lib::validate_instance_request,
             app_lib::create_instance,
             app_lib::update_instance,
             app_lib::delete_instance,
@@ -68,40 +90,118 @@ fn run_with_database() -> Result<(), anyhow::Error> {
             app_lib::update_blueprint,
             app_lib::delete_blueprint,
             app_lib::deploy_blueprint,
+            app_lib::clone_blueprint,
+            app_lib::export_blueprint,
+            app_lib::import_blueprint,
             app_lib::get_security_configs,
             app_lib::get_security_config,
             app_lib::create_security_config,
             app_lib::update_security_config,
             app_lib::delete_security_config,
+            app_lib::collect_security_groups,
+            app_lib::collect_vpcs,
+            app_lib::collect_subnets,
+            app_lib::get_spot_price_history,
+            app_lib::import_security_group,
+            app_lib::apply_security_config,
+            app_lib::get_security_config_usage,
+            app_lib::get_images,
+            app_lib::get_image,
+            app_lib::delete_image,
             app_lib::collect_ec2_instances,
+            app_lib::get_all_instances,
             app_lib::create_ec2_instance,
             app_lib::delete_ec2_instance,
+            app_lib::set_instance_protection,
             app_lib::start_ec2_instance,
             app_lib::stop_ec2_instance,
             app_lib::restart_ec2_instance,
+            app_lib::bulk_ec2_operation,
+            app_lib::resize_ec2_instance,
             app_lib::get_ec2_instance_details,
             app_lib::get_ec2_instance_ssh_config,
+            app_lib::get_instance_total_cost,
+            app_lib::get_instance_metrics,
+            app_lib::get_storage_utilization,
+            app_lib::stop_idle_instances,
+            app_lib::collect_reserved_instances,
+            app_lib::get_reservation_utilization,
+            app_lib::get_compute_optimizer_recommendations,
+            app_lib::get_instances_health_summary,
+            app_lib::get_instance_console_output,
+            app_lib::get_instance_screenshot,
+            app_lib::get_instance_scheduled_events,
+            app_lib::list_amis,
+            app_lib::resolve_latest_ami,
+            app_lib::create_image_from_instance,
+            app_lib::sync_instance_storage,
+            app_lib::export_ansible_inventory,
+            app_lib::check_naming_policy,
+            app_lib::list_unassociated_eips,
+            app_lib::release_unused_eips,
+            app_lib::collect_elastic_ips,
+            app_lib::allocate_elastic_ip,
+            app_lib::associate_elastic_ip,
+            app_lib::release_elastic_ip,
+            app_lib::collect_volumes,
+            app_lib::create_volume,
+            app_lib::attach_volume,
+            app_lib::detach_volume,
+            app_lib::modify_volume_size,
+            app_lib::collect_snapshots,
+            app_lib::create_snapshot,
+            app_lib::delete_snapshot,
             app_lib::collect_s3_buckets,
             app_lib::create_s3_bucket,
             app_lib::delete_s3_bucket,
+            app_lib::delete_empty_buckets,
             app_lib::get_s3_bucket_details,
+            app_lib::get_s3_bucket_lifecycle,
+            app_lib::put_s3_bucket_lifecycle,
+            app_lib::preview_lifecycle_impact,
+            app_lib::get_s3_presigned_url,
+            app_lib::get_s3_bucket_policy,
+            app_lib::put_s3_bucket_policy,
+            app_lib::put_s3_public_access_block,
+            app_lib::get_bucket_encryption_report,
+            app_lib::enable_default_encryption,
+            app_lib::upload_s3_object,
             app_lib::collect_iam_users,
             app_lib::collect_iam_roles,
             app_lib::get_iam_user_details,
+            app_lib::get_iam_role_details,
+            app_lib::list_iam_access_keys,
+            app_lib::create_iam_access_key,
+            app_lib::delete_iam_access_key,
             app_lib::get_cost_summary,
+            app_lib::list_cost_allocation_tags,
+            app_lib::activate_cost_allocation_tags,
             app_lib::get_budget_alerts,
             app_lib::create_budget_alert,
             app_lib::update_budget_alert,
             app_lib::delete_budget_alert,
             app_lib::get_cost_status,
             app_lib::reset_cost_tracking,
+            app_lib::get_cost_tracking_delta,
+            app_lib::set_cost_alert_thresholds,
+            app_lib::get_cost_thresholds,
+            app_lib::set_cost_thresholds,
+            app_lib::security_posture_score,
+            app_lib::get_security_posture_weights,
+            app_lib::set_security_posture_weights,
             app_lib::get_cache_stats,
             app_lib::invalidate_cache,
             app_lib::invalidate_cache_region,
+            app_lib::clear_aws_clients,
             app_lib::get_aws_health_status,
             app_lib::force_aws_health_check,
             app_lib::get_aws_health_report,
             app_lib::get_recent_aws_events,
+            app_lib::get_recent_aws_errors,
+            app_lib::replay_events,
+            app_lib::check_account_permissions,
+            app_lib::get_aws_service_events,
+            app_lib::describe_commands,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
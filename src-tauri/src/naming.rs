@@ -0,0 +1,264 @@
+// ============================================================================
+// NAMING POLICY
+// ============================================================================
+// Check instance `Name` tags against a governance regex and generate
+// compliant replacement names
+// ============================================================================
+
+use crate::api_error::ApiError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::future::Future;
+
+pub const NAME_TAG_KEY: &str = "Name";
+
+/// A regex every instance name must match in full
+#[derive(Debug, Clone)]
+pub struct NamingPolicy {
+    pattern: String,
+    regex: Regex,
+}
+
+impl NamingPolicy {
+    /// Compile `pattern`, anchored so it has to match the whole name
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        if pattern.trim().is_empty() {
+            return Err("Naming policy regex cannot be empty".to_string());
+        }
+
+        let regex = Regex::new(&format!("^(?:{})$", pattern))
+            .map_err(|e| format!("Invalid naming policy regex: {}", e))?;
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            regex,
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn is_compliant(&self, name: &str) -> bool {
+        self.regex.is_match(name)
+    }
+}
+
+/// An instance as far as the naming policy is concerned
+#[derive(Debug, Clone)]
+pub struct NamedInstance {
+    pub id: i64,
+    pub instance_id: String,
+    pub region: String,
+    /// Value of the `Name` tag, if any
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamingViolation {
+    pub id: i64,
+    pub instance_id: String,
+    pub region: String,
+    pub name: Option<String>,
+    /// Compliant name `auto_rename` would apply, or `None` when no generated
+    /// candidate satisfies the policy
+    pub suggested_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameFailure {
+    pub instance_id: String,
+    pub error: String,
+    /// `error` categorized for the command's response
+    #[serde(skip)]
+    pub api_error: Option<ApiError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameReport {
+    pub renamed: Vec<NamingViolation>,
+    pub failed: Vec<RenameFailure>,
+}
+
+/// Lowercase the name and collapse anything but letters and digits into single dashes
+fn slug(name: &str) -> String {
+    name.to_ascii_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// First generated name that matches the policy and is not already taken. Candidates,
+/// in order: the current name as a slug, the slug suffixed with the instance id's
+/// last 8 characters, the instance id, and `instance-<suffix>`.
+pub fn compliant_name(policy: &NamingPolicy, instance: &NamedInstance, taken: &HashSet<String>) -> Option<String> {
+    let suffix = instance.instance_id
+        .strip_prefix("i-")
+        .unwrap_or(&instance.instance_id);
+    let suffix = &suffix[suffix.len().saturating_sub(8)..];
+    let base = instance.name.as_deref().map(slug).filter(|s| !s.is_empty());
+
+    let mut candidates = Vec::new();
+    if let Some(base) = &base {
+        candidates.push(base.clone());
+        candidates.push(format!("{}-{}", base, suffix));
+    }
+    candidates.push(instance.instance_id.clone());
+    candidates.push(format!("instance-{}", suffix));
+
+    candidates
+        .into_iter()
+        .find(|candidate| policy.is_compliant(candidate) && !taken.contains(candidate))
+}
+
+/// Instances whose name violates the policy. Instances without a `Name` tag
+/// are violations too. Suggested names never collide with existing compliant
+/// names or with each other.
+pub fn find_violations(policy: &NamingPolicy, instances: &[NamedInstance]) -> Vec<NamingViolation> {
+    let mut taken: HashSet<String> = instances
+        .iter()
+        .filter_map(|i| i.name.clone())
+        .filter(|name| policy.is_compliant(name))
+        .collect();
+
+    instances
+        .iter()
+        .filter(|i| !i.name.as_deref().is_some_and(|name| policy.is_compliant(name)))
+        .map(|instance| {
+            let suggested_name = compliant_name(policy, instance, &taken);
+            if let Some(name) = &suggested_name {
+                taken.insert(name.clone());
+            }
+            NamingViolation {
+                id: instance.id,
+                instance_id: instance.instance_id.clone(),
+                region: instance.region.clone(),
+                name: instance.name.clone(),
+                suggested_name,
+            }
+        })
+        .collect()
+}
+
+/// Apply each violation's suggested name through `retag`. Violations without a
+/// suggestion are reported as failures.
+pub async fn rename_violations<F, Fut>(violations: Vec<NamingViolation>, mut retag: F) -> RenameReport
+where
+    F: FnMut(NamingViolation, String) -> Fut,
+    Fut: Future<Output = Result<(), ApiError>>,
+{
+    let mut renamed = Vec::new();
+    let mut failed = Vec::new();
+
+    for violation in violations {
+        let Some(name) = violation.suggested_name.clone() else {
+            let error = ApiError::InvalidInput("No generated name satisfies the naming policy".to_string());
+            failed.push(RenameFailure {
+                instance_id: violation.instance_id.clone(),
+                error: error.to_string(),
+                api_error: Some(error),
+            });
+            continue;
+        };
+
+        match retag(violation.clone(), name).await {
+            Ok(()) => renamed.push(violation),
+            Err(error) => failed.push(RenameFailure {
+                instance_id: violation.instance_id.clone(),
+                error: error.to_string(),
+                api_error: Some(error),
+            }),
+        }
+    }
+
+    RenameReport { renamed, failed }
+}
+
+/// Stored `key=value` tag list with the `Name` tag replaced
+pub fn with_name_tag(tags: Option<&str>, name: &str) -> Vec<String> {
+    let prefix = format!("{}=", NAME_TAG_KEY);
+    let mut tags: Vec<String> = tags
+        .and_then(|json| serde_json::from_str::<Vec<String>>(json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|tag| !tag.starts_with(&prefix))
+        .collect();
+    tags.push(format!("{}{}", prefix, name));
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(id: i64, instance_id: &str, name: Option<&str>) -> NamedInstance {
+        NamedInstance {
+            id,
+            instance_id: instance_id.to_string(),
+            region: "us-east-1".to_string(),
+            name: name.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_non_compliant_instances_reported() {
+        assert!(NamingPolicy::new("[a-z").is_err());
+        assert!(NamingPolicy::new("").is_err());
+
+        let policy = NamingPolicy::new("(web|db)-[a-z0-9-]+").unwrap();
+        let instances = vec![
+            instance(1, "i-0aaa1111bbbb2222c", Some("web-frontend")),
+            instance(2, "i-0ddd3333eeee4444f", Some("Web Frontend")),
+            instance(3, "i-0fff5555aaaa6666b", None),
+            // Anchored: a compliant substring is not enough
+            instance(4, "i-0ccc7777dddd8888e", Some("old-web-1")),
+        ];
+
+        let violations = find_violations(&policy, &instances);
+        let ids: Vec<i64> = violations.iter().map(|v| v.id).collect();
+        assert_eq!(ids, vec![2, 3, 4]);
+
+        // "web-frontend" is taken, so the suffixed slug is suggested
+        assert_eq!(violations[0].suggested_name.as_deref(), Some("web-frontend-eee4444f"));
+        // No candidate for an untagged or "old-" instance starts with web- or db-
+        assert_eq!(violations[1].suggested_name, None);
+        assert_eq!(violations[2].suggested_name, None);
+    }
+
+    #[tokio::test]
+    async fn test_auto_rename_applies_compliant_name() {
+        let policy = NamingPolicy::new("[a-z0-9-]+").unwrap();
+        let instances = vec![
+            instance(1, "i-0aaa1111bbbb2222c", Some("Billing API (prod)")),
+            instance(2, "i-0ddd3333eeee4444f", Some("reporting")),
+        ];
+
+        let violations = find_violations(&policy, &instances);
+        assert_eq!(violations.len(), 1);
+
+        let mut applied = Vec::new();
+        let report = rename_violations(violations, |violation, name| {
+            applied.push((violation.instance_id, name));
+            async { Ok(()) }
+        })
+        .await;
+
+        assert_eq!(applied, vec![("i-0aaa1111bbbb2222c".to_string(), "billing-api-prod".to_string())]);
+        assert!(policy.is_compliant(&applied[0].1));
+        assert_eq!(report.renamed.len(), 1);
+        assert!(report.failed.is_empty());
+
+        let violations = find_violations(&policy, &instances);
+        let report = rename_violations(violations, |_, _| async {
+            Err(ApiError::AwsPermission("Failed to set Name tag: Permission denied: ec2:CreateTags".to_string()))
+        })
+        .await;
+        assert_eq!(report.failed[0].error, "Failed to set Name tag: Permission denied: ec2:CreateTags");
+        assert_eq!(report.failed[0].api_error.as_ref().map(ApiError::code), Some("aws_permission"));
+
+        let tags = with_name_tag(Some(r#"["Name=Billing API (prod)","env=prod"]"#), &applied[0].1);
+        assert_eq!(tags, vec!["env=prod".to_string(), "Name=billing-api-prod".to_string()]);
+    }
+}
@@ -0,0 +1,305 @@
+// ============================================================================
+// EMAIL NOTIFIER
+// ============================================================================
+// Direct email for budget and health alerts through an outbound SMTP server,
+// for those who'd rather not set up SNS. The SMTP password lives in the
+// credential store; only the rest of the settings are kept in the database.
+// ============================================================================
+
+use crate::api_error::ApiError;
+use crate::credential_store;
+use crate::database::{self, DbPool};
+use anyhow::Context;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long connecting to and talking to the SMTP server may take
+const SEND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How the connection to the SMTP server is secured
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// TLS from the start, usually on port 465
+    Tls,
+    /// Plain connection upgraded with STARTTLS, usually on port 587
+    #[default]
+    #[serde(rename = "starttls")]
+    StartTls,
+    /// No encryption, for local relays only
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmtpSettings {
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    /// Login for servers that require authentication; the password is stored separately
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Sender address, e.g. "Pocket Architect <alerts@example.com>"
+    pub from: String,
+    /// Who budget and health alerts go to
+    #[serde(default)]
+    pub recipients: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NotifyError {
+    #[error("{0}")]
+    InvalidSettings(String),
+    #[error("SMTP isn't configured. Set the SMTP server first.")]
+    NotConfigured,
+    #[error("Failed to send email: {0}")]
+    SendFailed(String),
+}
+
+impl NotifyError {
+    pub fn api_error(&self) -> ApiError {
+        match self {
+            NotifyError::InvalidSettings(message) => ApiError::InvalidInput(message.clone()),
+            NotifyError::NotConfigured => ApiError::InvalidInput(self.to_string()),
+            NotifyError::SendFailed(_) => ApiError::Network(self.to_string()),
+        }
+    }
+}
+
+fn parse_mailbox(address: &str, what: &str) -> Result<Mailbox, NotifyError> {
+    address
+        .trim()
+        .parse::<Mailbox>()
+        .map_err(|e| NotifyError::InvalidSettings(format!("Invalid {} address '{}': {}", what, address, e)))
+}
+
+impl SmtpSettings {
+    /// Check the server and addresses before anything is sent or saved
+    pub fn validate(&self) -> Result<(), NotifyError> {
+        let host = self.host.trim();
+        if host.is_empty() {
+            return Err(NotifyError::InvalidSettings("SMTP host is required".to_string()));
+        }
+        if host.contains("://") || host.contains(char::is_whitespace) || host.contains('/') {
+            return Err(NotifyError::InvalidSettings(format!(
+                "SMTP host must be a host name or IP address, got '{}'",
+                self.host
+            )));
+        }
+        if self.port == 0 {
+            return Err(NotifyError::InvalidSettings("SMTP port must be between 1 and 65535".to_string()));
+        }
+        if self.from.trim().is_empty() {
+            return Err(NotifyError::InvalidSettings("Sender (from) address is required".to_string()));
+        }
+        parse_mailbox(&self.from, "sender")?;
+        for recipient in &self.recipients {
+            parse_mailbox(recipient, "recipient")?;
+        }
+        Ok(())
+    }
+
+    fn transport(&self, password: Option<&str>) -> Result<AsyncSmtpTransport<Tokio1Executor>, NotifyError> {
+        let host = self.host.trim();
+        let builder = match self.security {
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+            SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)),
+        }
+        .map_err(|e| NotifyError::InvalidSettings(format!("Invalid SMTP host '{}': {}", host, e)))?;
+
+        let mut builder = builder.port(self.port).timeout(Some(SEND_TIMEOUT));
+        if let Some(username) = self.username.as_deref().filter(|u| !u.is_empty()) {
+            builder = builder.credentials(Credentials::new(username.to_string(), password.unwrap_or_default().to_string()));
+        }
+        Ok(builder.build())
+    }
+}
+
+/// The stored SMTP settings, if configured
+pub async fn load_smtp_settings(pool: &DbPool) -> anyhow::Result<Option<SmtpSettings>> {
+    match database::get_smtp_settings(pool).await? {
+        Some(value) => Ok(Some(serde_json::from_value(value).context("Failed to parse SMTP settings")?)),
+        None => Ok(None),
+    }
+}
+
+/// Save settings that passed `validate`, trimmed, or remove them when
+/// `settings` is None
+pub async fn save_smtp_settings(pool: &DbPool, settings: Option<&SmtpSettings>) -> anyhow::Result<Option<SmtpSettings>> {
+    let settings = settings.map(|settings| SmtpSettings {
+        host: settings.host.trim().to_string(),
+        username: settings.username.as_deref().map(str::trim).filter(|u| !u.is_empty()).map(str::to_string),
+        ..settings.clone()
+    });
+    let value = settings.as_ref().map(serde_json::to_value).transpose()?;
+    database::set_smtp_settings(pool, value.as_ref()).await?;
+    Ok(settings)
+}
+
+/// Send a plain-text email through the given server
+pub async fn send_email(settings: &SmtpSettings, password: Option<&str>, to: &[String], subject: &str, body: &str) -> Result<(), NotifyError> {
+    settings.validate()?;
+    if to.is_empty() {
+        return Err(NotifyError::InvalidSettings("At least one recipient is required".to_string()));
+    }
+
+    let mut message = Message::builder()
+        .from(parse_mailbox(&settings.from, "sender")?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for recipient in to {
+        message = message.to(parse_mailbox(recipient, "recipient")?);
+    }
+    let message = message
+        .body(body.to_string())
+        .map_err(|e| NotifyError::InvalidSettings(format!("Failed to build email: {}", e)))?;
+
+    settings
+        .transport(password)?
+        .send(message)
+        .await
+        .map_err(|e| NotifyError::SendFailed(e.to_string()))?;
+
+    tracing::info!("Sent email '{}' to {} recipients via {}", subject, to.len(), settings.host);
+    Ok(())
+}
+
+/// Email `to` using the stored SMTP settings and password
+pub async fn notify_email(pool: &DbPool, to: &[String], subject: &str, body: &str) -> Result<(), NotifyError> {
+    let settings = load_smtp_settings(pool)
+        .await
+        .map_err(|e| NotifyError::InvalidSettings(format!("Failed to load SMTP settings: {}", e)))?
+        .ok_or(NotifyError::NotConfigured)?;
+    let password = credential_store::retrieve_smtp_password()
+        .map_err(|e| NotifyError::InvalidSettings(format!("Failed to read SMTP password: {}", e)))?;
+
+    send_email(&settings, password.as_deref(), to, subject, body).await
+}
+
+/// Email an alert to the configured recipients. Alerts go out whether or not
+/// SMTP is set up, so an unconfigured notifier is skipped quietly and send
+/// failures are only logged.
+#[cfg_attr(not(feature = "aws-sdk"), allow(dead_code))]
+pub async fn notify_alert(pool: &DbPool, subject: &str, body: &str) {
+    let recipients = match load_smtp_settings(pool).await {
+        Ok(Some(settings)) if !settings.recipients.is_empty() => settings.recipients,
+        Ok(_) => return,
+        Err(e) => {
+            tracing::warn!("Failed to load SMTP settings for alert email: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = notify_email(pool, &recipients, subject, body).await {
+        tracing::warn!("Failed to email alert '{}': {}", subject, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// Accept one SMTP session, answering every command with success, and
+    /// return the message data the client sent
+    async fn mock_smtp_server() -> (u16, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            writer.write_all(b"220 mock.test ESMTP\r\n").await.unwrap();
+
+            let mut data = String::new();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                let command = line.to_ascii_uppercase();
+                if command.starts_with("EHLO") {
+                    writer.write_all(b"250-mock.test\r\n250 8BITMIME\r\n").await.unwrap();
+                } else if command.starts_with("DATA") {
+                    writer.write_all(b"354 Go ahead\r\n").await.unwrap();
+                    while let Some(line) = lines.next_line().await.unwrap() {
+                        if line == "." {
+                            break;
+                        }
+                        data.push_str(&line);
+                        data.push('\n');
+                    }
+                    writer.write_all(b"250 Queued\r\n").await.unwrap();
+                } else if command.starts_with("QUIT") {
+                    writer.write_all(b"221 Bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    writer.write_all(b"250 OK\r\n").await.unwrap();
+                }
+            }
+            data
+        });
+
+        (port, server)
+    }
+
+    fn settings(host: &str, port: u16) -> SmtpSettings {
+        SmtpSettings {
+            host: host.to_string(),
+            port,
+            security: SmtpSecurity::None,
+            username: None,
+            from: "Pocket Architect <alerts@example.com>".to_string(),
+            recipients: vec!["ops@example.com".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_smtp_sends_with_valid_settings_and_rejects_missing_host() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async {
+            let (port, server) = mock_smtp_server().await;
+            let to = vec!["ops@example.com".to_string()];
+            send_email(&settings("127.0.0.1", port), None, &to, "Budget alert", "Spend crossed 80% of the limit")
+                .await
+                .unwrap();
+
+            let data = server.await.unwrap();
+            assert!(data.contains("Subject: Budget alert"), "{}", data);
+            assert!(data.contains("To: ops@example.com"), "{}", data);
+            assert!(data.contains("Spend crossed 80% of the limit"), "{}", data);
+        });
+
+        // Nothing is sent without a host
+        let error = rt
+            .block_on(send_email(&settings("  ", 25), None, &["ops@example.com".to_string()], "Test", "Body"))
+            .unwrap_err();
+        assert_eq!(error, NotifyError::InvalidSettings("SMTP host is required".to_string()));
+        assert_eq!(error.api_error().code(), "invalid_input");
+
+        // Other settings are checked up front too
+        assert!(settings("smtp://mail.example.com", 587).validate().is_err());
+        assert!(settings("mail.example.com", 0).validate().is_err());
+        assert!(SmtpSettings { from: "not an address".to_string(), ..settings("mail.example.com", 587) }.validate().is_err());
+        assert!(settings("mail.example.com", 587).validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_smtp_settings_are_saved_trimmed_and_removed() {
+        let pool = database::memory_pool().await;
+        assert_eq!(load_smtp_settings(&pool).await.unwrap(), None);
+
+        let entered = SmtpSettings { username: Some("  ".to_string()), ..settings(" mail.example.com ", 587) };
+        let saved = save_smtp_settings(&pool, Some(&entered)).await.unwrap().unwrap();
+        assert_eq!(saved.host, "mail.example.com");
+        assert_eq!(saved.username, None);
+        assert_eq!(load_smtp_settings(&pool).await.unwrap(), Some(saved));
+
+        assert_eq!(save_smtp_settings(&pool, None).await.unwrap(), None);
+        assert_eq!(load_smtp_settings(&pool).await.unwrap(), None);
+    }
+}
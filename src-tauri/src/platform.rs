@@ -0,0 +1,39 @@
+// ============================================================================
+// CLOUD PLATFORMS
+// ============================================================================
+// Maps an account's `platform` column to the provider that serves it
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudProvider {
+    Aws,
+    Azure,
+    Gcp,
+}
+
+impl CloudProvider {
+    /// Provider for an account platform, ignoring case
+    pub fn from_platform(platform: &str) -> Option<Self> {
+        match platform.to_ascii_lowercase().as_str() {
+            "aws" => Some(Self::Aws),
+            "azure" => Some(Self::Azure),
+            "gcp" => Some(Self::Gcp),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_dispatch() {
+        assert_eq!(CloudProvider::from_platform("azure"), Some(CloudProvider::Azure));
+        assert_eq!(CloudProvider::from_platform("Azure"), Some(CloudProvider::Azure));
+        assert_eq!(CloudProvider::from_platform("aws"), Some(CloudProvider::Aws));
+        assert_eq!(CloudProvider::from_platform("gcp"), Some(CloudProvider::Gcp));
+        assert_eq!(CloudProvider::from_platform("oci"), None);
+        assert_eq!(CloudProvider::from_platform(""), None);
+    }
+}
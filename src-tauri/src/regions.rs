@@ -0,0 +1,67 @@
+// ============================================================================
+// AWS REGIONS
+// ============================================================================
+// Commercial AWS regions, shared by credential validation and per-account
+// region configuration
+// ============================================================================
+
+pub const AWS_REGIONS: &[&str] = &[
+    // North America
+    "us-east-1", "us-east-2", "us-west-1", "us-west-2",
+    "ca-central-1", "ca-west-1", "mx-central-1",
+    // South America
+    "sa-east-1",
+    // Europe
+    "eu-central-1", "eu-central-2", "eu-west-1", "eu-west-2", "eu-west-3",
+    "eu-north-1", "eu-south-1", "eu-south-2",
+    // Asia Pacific
+    "ap-east-1", "ap-east-2", "ap-south-1", "ap-south-2",
+    "ap-northeast-1", "ap-northeast-2", "ap-northeast-3",
+    "ap-southeast-1", "ap-southeast-2", "ap-southeast-3", "ap-southeast-4",
+    "ap-southeast-5", "ap-southeast-7",
+    // Middle East and Africa
+    "me-south-1", "me-central-1", "il-central-1", "af-south-1",
+];
+
+pub const DEFAULT_REGION: &str = "us-east-1";
+
+pub fn is_valid_region(region: &str) -> bool {
+    AWS_REGIONS.contains(&region)
+}
+
+/// Fallback for a region without one configured. Credentials only work within
+/// their partition, so China and GovCloud regions fall back to a sibling.
+pub fn default_fallback_region(region: &str) -> &'static str {
+    match region {
+        "cn-north-1" => "cn-northwest-1",
+        r if r.starts_with("cn-") => "cn-north-1",
+        "us-gov-west-1" => "us-gov-east-1",
+        r if r.starts_with("us-gov-") => "us-gov-west-1",
+        "us-east-1" => "us-west-2",
+        _ => DEFAULT_REGION,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_validation() {
+        assert!(is_valid_region("us-east-1"));
+        assert!(is_valid_region("ap-southeast-4"));
+        assert!(is_valid_region(DEFAULT_REGION));
+        assert!(!is_valid_region("us-east-9"));
+        assert!(!is_valid_region(""));
+    }
+
+    #[test]
+    fn test_default_fallback_region_stays_in_partition() {
+        assert_eq!(default_fallback_region("us-east-1"), "us-west-2");
+        assert_eq!(default_fallback_region("eu-west-1"), "us-east-1");
+        assert_eq!(default_fallback_region("cn-north-1"), "cn-northwest-1");
+        assert_eq!(default_fallback_region("cn-northwest-1"), "cn-north-1");
+        assert_eq!(default_fallback_region("us-gov-west-1"), "us-gov-east-1");
+        assert_eq!(default_fallback_region("us-gov-east-1"), "us-gov-west-1");
+    }
+}
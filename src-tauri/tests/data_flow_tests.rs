@@ -12,6 +12,9 @@ mod tests {
         let db_pool = init_database_sync(None).expect("Failed to init database");
         AppState {
             db: Arc::new(Mutex::new(db_pool)),
+            #[cfg(feature = "aws-sdk")]
+            aws_clients: app_lib::AwsClientManager::new(),
+            confirmations: Default::default(),
         }
     }
 
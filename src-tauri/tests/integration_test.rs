@@ -11,6 +11,9 @@ async fn test_basic_data_flow() {
     let db_pool = app_lib::init_database_sync(None).expect("Failed to init database");
     let state = AppState {
         db: Arc::new(Mutex::new(db_pool)),
+        #[cfg(feature = "aws-sdk")]
+        aws_clients: app_lib::AwsClientManager::new(),
+        confirmations: Default::default(),
     };
     println!("✅ Database initialized");
 
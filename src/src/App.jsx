@@ -1,5 +1,6 @@
 import { useState, useEffect } from 'react'
 import { invoke } from '@tauri-apps/api/tauri'
+import { listen } from '@tauri-apps/api/event'
 import UpdateManager from './UpdateManager'
 import './App.css'
 
@@ -12,7 +13,10 @@ function App() {
     name: '',
     access_key: '',
     secret_key: '',
-    region: 'us-east-1'
+    region: 'us-east-1',
+    read_only: false,
+    role_arn: '',
+    external_id: ''
   })
 
   // Load accounts on component mount
@@ -45,7 +49,7 @@ function App() {
       })
       if (response.success) {
         setMessage('Account created successfully!')
-        setNewAccount({ name: '', access_key: '', secret_key: '', region: 'us-east-1' })
+        setNewAccount({ name: '', access_key: '', secret_key: '', region: 'us-east-1', read_only: false, role_arn: '', external_id: '' })
         setShowCreateForm(false)
         loadAccounts()
       } else {
@@ -77,13 +81,52 @@ function App() {
     }
   }
 
+  const handleSyncAllAccounts = async () => {
+    const unlisten = await listen('sync:account_completed', (event) => {
+      const { completed, total, result } = event.payload
+      setMessage(`Syncing accounts... ${completed}/${total} done (${result.name || 'account'}: ${result.success ? 'ok' : result.message})`)
+    })
+    try {
+      setLoading(true)
+      setMessage('Syncing all accounts...')
+      const response = await invoke('sync_all_accounts')
+      const failures = (response.data?.accounts || [])
+        .filter((result) => !result.success)
+        .map((result) => `${result.name}: ${result.message}`)
+      setMessage(`${response.message}. Synced ${response.data?.synced || 0} resources.${failures.length ? ` ${failures.join('; ')}` : ''}`)
+      loadAccounts()
+    } catch (error) {
+      setMessage(`Failed to sync accounts: ${error}`)
+    } finally {
+      unlisten()
+      setLoading(false)
+    }
+  }
+
+  const handleSetAccountStatus = async (accountId, status) => {
+    try {
+      setLoading(true)
+      const response = await invoke('set_account_status', { id: accountId, status })
+      setMessage(response.success ? response.message : `Error: ${response.message}`)
+      loadAccounts()
+    } catch (error) {
+      setMessage(`Failed to update account status: ${error}`)
+    } finally {
+      setLoading(false)
+    }
+  }
+
   const handleTestConnection = async (accountId) => {
     try {
       setLoading(true)
       setMessage('Testing connection...')
       const response = await invoke('test_account_connection', { id: accountId })
       if (response.success) {
-        setMessage(`Connection test successful: ${response.message}`)
+        const sharedWith = (response.data?.shared_with || []).map((other) => other.name)
+        const shared = sharedWith.length ? ` Same AWS account as: ${sharedWith.join(', ')}` : ''
+        setMessage(`Connection test successful: ${response.message}${shared}`)
+        // Pick up the verified AWS account number
+        await loadAccounts()
       } else {
         setMessage(`Connection test failed: ${response.message}`)
       }
@@ -111,6 +154,9 @@ function App() {
           >
             {showCreateForm ? 'Cancel' : 'Add AWS Account'}
           </button>
+          <button onClick={handleSyncAllAccounts} disabled={loading || accounts.length === 0}>
+            Sync All
+          </button>
           <button onClick={loadAccounts} disabled={loading}>
             Refresh
           </button>
@@ -167,6 +213,35 @@ function App() {
                 <option value="ap-southeast-1">Asia Pacific (Singapore)</option>
               </select>
             </div>
+            <div className="form-group">
+              <label>Role ARN (optional):</label>
+              <input
+                type="text"
+                value={newAccount.role_arn}
+                onChange={(e) => setNewAccount({...newAccount, role_arn: e.target.value})}
+                placeholder="arn:aws:iam::123456789012:role/PocketArchitect"
+              />
+            </div>
+            {newAccount.role_arn && (
+              <div className="form-group">
+                <label>External ID (optional):</label>
+                <input
+                  type="text"
+                  value={newAccount.external_id}
+                  onChange={(e) => setNewAccount({...newAccount, external_id: e.target.value})}
+                />
+              </div>
+            )}
+            <div className="form-group">
+              <label>
+                <input
+                  type="checkbox"
+                  checked={newAccount.read_only}
+                  onChange={(e) => setNewAccount({...newAccount, read_only: e.target.checked})}
+                />
+                Read-only (sync and view resources, never modify them)
+              </label>
+            </div>
             <div className="form-actions">
               <button type="submit" disabled={loading}>
                 {loading ? 'Creating...' : 'Create Account'}
@@ -190,7 +265,12 @@ function App() {
                   <div className="account-details">
                     <p><strong>Region:</strong> {account.region || 'Not set'}</p>
                     <p><strong>Last Sync:</strong> {account.last_sync ? new Date(account.last_sync).toLocaleString() : 'Never'}</p>
-                    <p><strong>Status:</strong> {account.is_active ? 'Active' : 'Inactive'}</p>
+                    <p title={account.status_reason || ''}><strong>Status:</strong> {account.status}</p>
+                    {account.read_only && <p><strong>Mode:</strong> Read-only</p>}
+                    {account.role_arn && <p><strong>Role:</strong> {account.role_arn}</p>}
+                    {account.aws_account_id && (
+                      <p title={account.caller_arn || ''}><strong>AWS Account:</strong> {account.aws_account_id}</p>
+                    )}
                   </div>
                   <div className="account-actions">
                     <button
@@ -207,6 +287,12 @@ function App() {
                     >
                       {loading ? 'Syncing...' : 'Sync Resources'}
                     </button>
+                    <button
+                      onClick={() => handleSetAccountStatus(account.id, account.status === 'disabled' ? 'active' : 'disabled')}
+                      disabled={loading}
+                    >
+                      {account.status === 'disabled' ? 'Enable' : 'Disable'}
+                    </button>
                   </div>
                 </div>
               ))}